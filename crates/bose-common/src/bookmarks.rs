@@ -0,0 +1,207 @@
+//! 書籤 — 讓代理在不同工作階段之間保存重要的搜尋結果與筆記
+//!
+//! 存成單一 JSON 檔（`BOOKMARKS_PATH` 或設定檔 `[bookmarks] path`）；每次變更先寫入暫存檔再改名，
+//! 中途失敗不會留下損毀的檔案。同一網址只有一筆書籤，再次加入時更新標題、摘要與筆記。
+
+use crate::url::{canonicalize, dedup_key};
+use crate::{BoseConfig, BoseResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// 單筆書籤
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub id: u64,
+    pub url: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// 找到此結果的查詢
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 新增書籤的內容
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NewBookmark {
+    pub url: String,
+    pub title: String,
+    pub snippet: Option<String>,
+    pub note: Option<String>,
+    pub query: Option<String>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BookmarkFile {
+    next_id: u64,
+    bookmarks: Vec<Bookmark>,
+}
+
+/// JSON 檔書籤庫
+pub struct BookmarkStore {
+    path: PathBuf,
+    data: Mutex<BookmarkFile>,
+}
+
+impl BookmarkStore {
+    /// 開啟書籤檔；檔案不存在時於第一次寫入時建立
+    pub fn open(path: impl Into<PathBuf>) -> BoseResult<Self> {
+        let path = path.into();
+        let data = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BookmarkFile::default(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            path,
+            data: Mutex::new(data),
+        })
+    }
+
+    /// 依 `BOOKMARKS_PATH` 設定開啟；未設定路徑時為 None
+    pub fn from_config(config: &BoseConfig) -> BoseResult<Option<Self>> {
+        config.bookmarks_path.as_ref().map(Self::open).transpose()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 加入（或更新同網址的）書籤；網址先經 `canonicalize`，只差追蹤參數的網址視為同一個
+    ///
+    /// 已有書籤時覆寫標題；摘要、筆記與查詢有值時才覆寫，標籤合併。
+    pub fn add(&self, new: NewBookmark) -> BoseResult<Bookmark> {
+        let mut data = self.data.lock().unwrap();
+        let key = dedup_key(&new.url);
+        let bookmark = match data.bookmarks.iter_mut().find(|b| dedup_key(&b.url) == key) {
+            Some(existing) => {
+                existing.title = new.title;
+                existing.snippet = new.snippet.or(existing.snippet.take());
+                existing.note = new.note.or(existing.note.take());
+                existing.query = new.query.or(existing.query.take());
+                for tag in new.tags {
+                    if !existing.tags.contains(&tag) {
+                        existing.tags.push(tag);
+                    }
+                }
+                existing.clone()
+            }
+            None => {
+                data.next_id += 1;
+                let bookmark = Bookmark {
+                    id: data.next_id,
+                    url: canonicalize(&new.url),
+                    title: new.title,
+                    snippet: new.snippet,
+                    note: new.note,
+                    query: new.query,
+                    tags: new.tags,
+                    created_at: Utc::now(),
+                };
+                data.bookmarks.push(bookmark.clone());
+                bookmark
+            }
+        };
+        self.persist(&data)?;
+        Ok(bookmark)
+    }
+
+    /// 刪除書籤；ID 不存在時回傳 false
+    pub fn remove(&self, id: u64) -> BoseResult<bool> {
+        let mut data = self.data.lock().unwrap();
+        let before = data.bookmarks.len();
+        data.bookmarks.retain(|b| b.id != id);
+        if data.bookmarks.len() == before {
+            return Ok(false);
+        }
+        self.persist(&data)?;
+        Ok(true)
+    }
+
+    /// 書籤，新的在前；`filter` 比對標題、網址、筆記、查詢與標籤（不分大小寫）
+    pub fn list(&self, filter: Option<&str>) -> Vec<Bookmark> {
+        let data = self.data.lock().unwrap();
+        let filter = filter.map(str::to_lowercase).filter(|f| !f.is_empty());
+        data.bookmarks
+            .iter()
+            .rev()
+            .filter(|b| match &filter {
+                Some(filter) => [Some(&b.title), Some(&b.url), b.note.as_ref(), b.query.as_ref()]
+                    .into_iter()
+                    .flatten()
+                    .chain(&b.tags)
+                    .any(|field| field.to_lowercase().contains(filter.as_str())),
+                None => true,
+            })
+            .cloned()
+            .collect()
+    }
+
+    fn persist(&self, data: &BookmarkFile) -> BoseResult<()> {
+        if let Some(dir) = self.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(data)?)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new(url: &str, note: Option<&str>) -> NewBookmark {
+        NewBookmark {
+            url: url.into(),
+            title: format!("Title {url}"),
+            note: note.map(Into::into),
+            tags: vec!["rust".into()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_add_update_remove_persist() {
+        let path = std::env::temp_dir().join(format!("bose-bookmarks-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let store = BookmarkStore::open(&path).unwrap();
+        let first = store.add(new("https://a.com", Some("key finding"))).unwrap();
+        let second = store.add(new("https://b.com", None)).unwrap();
+        assert_eq!((first.id, second.id), (1, 2));
+
+        // 同網址（只差追蹤參數）更新，沒提供的筆記保留
+        let updated = store
+            .add(NewBookmark {
+                tags: vec!["security".into()],
+                ..new("https://a.com/?utm_source=newsletter", None)
+            })
+            .unwrap();
+        assert_eq!(updated.id, 1);
+        assert_eq!(updated.note.as_deref(), Some("key finding"));
+        assert_eq!(updated.tags, vec!["rust", "security"]);
+
+        let reopened = BookmarkStore::open(&path).unwrap();
+        let urls: Vec<String> = reopened.list(None).into_iter().map(|b| b.url).collect();
+        assert_eq!(urls, vec!["https://b.com", "https://a.com"]);
+        assert_eq!(reopened.list(Some("FINDING")).len(), 1);
+        assert_eq!(reopened.list(Some("security")).len(), 1);
+
+        assert!(reopened.remove(2).unwrap());
+        assert!(!reopened.remove(2).unwrap());
+        // 刪除後新的 ID 不重用
+        assert_eq!(reopened.add(new("https://c.com", None)).unwrap().id, 3);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,194 @@
+//! 快取與限流後端 — 預設為行程內記憶體，啟用 `redis` feature 時可讓多個副本共用狀態
+
+use crate::types::{SearchQuery, SearchResponse};
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+/// 後端方法回傳的 boxed future（讓 trait 可作為 `dyn` 使用）
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// 搜尋回應快取後端
+///
+/// 後端故障時應視為未命中 / 略過寫入，不可讓快取錯誤中斷搜尋。
+pub trait CacheBackend: Send + Sync {
+    fn get<'a>(&'a self, query: &'a SearchQuery) -> BoxFuture<'a, Option<SearchResponse>>;
+
+    fn insert<'a>(&'a self, query: &'a SearchQuery, response: SearchResponse) -> BoxFuture<'a, ()>;
+}
+
+/// 以字串鍵存放序列化值的共用儲存
+///
+/// 讓結果型別不同的快取（例如根目錄 CLI 的 `SearchCache`）也能與其他副本共用同一個 Redis；
+/// 與 `CacheBackend` 相同，故障時視為未命中 / 略過寫入。
+pub trait KeyValueBackend: Send + Sync {
+    fn get_bytes<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Option<Vec<u8>>>;
+
+    fn set_bytes<'a>(&'a self, key: &'a str, value: Vec<u8>, ttl: Duration) -> BoxFuture<'a, ()>;
+}
+
+/// 限流後端
+pub trait RateLimitBackend: Send + Sync {
+    /// 嘗試取得一次請求額度，不阻塞
+    fn try_acquire(&self) -> BoxFuture<'_, bool>;
+}
+
+/// 快取鍵格式版本；正規化規則變動時遞增，避免共用快取（Redis）讀到舊格式的鍵
+const KEY_VERSION: &str = "v1";
+
+/// 以完整查詢參數作為快取鍵（見 `CacheKeyBuilder`）
+pub fn cache_key(query: &SearchQuery) -> String {
+    CacheKeyBuilder::from_query(query).build()
+}
+
+/// 正規化快取鍵
+///
+/// 查詢文字摺疊空白並轉小寫，篩選條件去除空白與大小寫差異，引擎清單排序去重，
+/// 因此 `"Rust async"` 與 `"rust  async"` 命中同一筆，而不同的結果數 / 語言 / 引擎不會互相覆蓋。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheKeyBuilder {
+    query: String,
+    num_results: u32,
+    category: Option<String>,
+    language: Option<String>,
+    time_range: Option<String>,
+    engines: Vec<String>,
+}
+
+impl CacheKeyBuilder {
+    pub fn new(query: &str) -> Self {
+        Self {
+            query: normalize_query(query),
+            ..Default::default()
+        }
+    }
+
+    pub fn from_query(query: &SearchQuery) -> Self {
+        Self::new(&query.query)
+            .num_results(query.num_results)
+            .category(query.category.as_deref())
+            .language(query.language.as_deref())
+            .time_range(query.time_range.as_deref())
+            .engines(&query.engines)
+    }
+
+    pub fn num_results(mut self, n: u32) -> Self {
+        self.num_results = n;
+        self
+    }
+
+    pub fn category(mut self, category: Option<&str>) -> Self {
+        self.category = normalize_filter(category);
+        self
+    }
+
+    pub fn language(mut self, language: Option<&str>) -> Self {
+        self.language = normalize_filter(language);
+        self
+    }
+
+    pub fn time_range(mut self, time_range: Option<&str>) -> Self {
+        self.time_range = normalize_filter(time_range);
+        self
+    }
+
+    pub fn engines<S: AsRef<str>>(mut self, engines: &[S]) -> Self {
+        let mut engines: Vec<String> = engines
+            .iter()
+            .filter_map(|e| normalize_filter(Some(e.as_ref())))
+            .collect();
+        engines.sort();
+        engines.dedup();
+        self.engines = engines;
+        self
+    }
+
+    /// 可讀的正規化表示（除錯用；實際鍵值為其雜湊）
+    pub fn canonical(&self) -> String {
+        format!(
+            "q={}\nn={}\ncategory={}\nlanguage={}\ntime_range={}\nengines={}",
+            self.query,
+            self.num_results,
+            self.category.as_deref().unwrap_or(""),
+            self.language.as_deref().unwrap_or(""),
+            self.time_range.as_deref().unwrap_or(""),
+            self.engines.join(","),
+        )
+    }
+
+    /// 產生固定長度的快取鍵：`v1:<FNV-1a 128 位元雜湊>`（跨行程、跨版本穩定）
+    pub fn build(&self) -> String {
+        let hash = crate::hash::fnv1a_128(self.canonical().as_bytes());
+        format!("{KEY_VERSION}:{hash:032x}")
+    }
+}
+
+fn normalize_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn normalize_filter(value: Option<&str>) -> Option<String> {
+    let value = value?.trim();
+    (!value.is_empty()).then(|| value.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_key_includes_params() {
+        let plain = cache_key(&SearchQuery::new("rust"));
+        let with_category = cache_key(&SearchQuery::new("rust").with_category("it"));
+        assert_ne!(plain, with_category);
+        assert_eq!(plain, cache_key(&SearchQuery::new("rust")));
+    }
+
+    #[test]
+    fn test_cache_key_normalizes_query() {
+        assert_eq!(
+            cache_key(&SearchQuery::new("Rust async")),
+            cache_key(&SearchQuery::new("  rust \t ASYNC "))
+        );
+        assert_ne!(
+            cache_key(&SearchQuery::new("rust async")),
+            cache_key(&SearchQuery::new("rust async").with_num_results(20))
+        );
+    }
+
+    #[test]
+    fn test_cache_key_normalizes_filters() {
+        let mut a = SearchQuery::new("rust").with_engines(vec!["google".into(), "Bing".into()]);
+        a.language = Some("zh-TW".into());
+        let mut b = SearchQuery::new("rust").with_engines(vec![
+            "bing".into(),
+            "google".into(),
+            "google".into(),
+        ]);
+        b.language = Some(" zh-tw ".into());
+        assert_eq!(cache_key(&a), cache_key(&b));
+
+        let mut empty = SearchQuery::new("rust");
+        empty.category = Some(" ".into());
+        assert_eq!(cache_key(&empty), cache_key(&SearchQuery::new("rust")));
+        assert_ne!(
+            cache_key(&SearchQuery::new("rust")),
+            cache_key(&SearchQuery::new("rust").with_engines(vec!["google".into()]))
+        );
+    }
+
+    #[test]
+    fn test_cache_key_format() {
+        let key = cache_key(&SearchQuery::new("rust"));
+        assert!(key.starts_with("v1:"));
+        assert_eq!(key.len(), 3 + 32);
+        assert_eq!(
+            CacheKeyBuilder::new("Rust").canonical(),
+            "q=rust\nn=0\ncategory=\nlanguage=\ntime_range=\nengines="
+        );
+    }
+}
@@ -1,9 +1,352 @@
+use crate::intent::{QueryIntent, detect_intent};
+use crate::schedule::ScheduledJob;
+use crate::secret::{Secret, load_secret};
+use crate::{BoseError, BoseResult, SearchQuery};
+use std::collections::{BTreeMap, HashMap};
+
 /// 全域配置
 #[derive(Debug, Clone)]
 pub struct BoseConfig {
     pub searxng_url: String,
     pub default_num_results: u32,
     pub request_timeout_secs: u64,
+    /// 逾時、連線失敗與 5xx 的重試次數（0 = 不重試）
+    pub max_retries: u32,
+    /// 結果少於此數且有拼字建議時，改用第一個建議重新搜尋（0 = 停用）
+    pub auto_correct_min_results: usize,
+    /// 搜尋快取 TTL（秒）
+    pub cache_ttl_secs: u64,
+    /// 搜尋快取項目上限（0 = 停用）
+    pub cache_max_entries: usize,
+    /// 多個 SearXNG 實例（設定後取代 `searxng_url`，依區域選出主要實例）
+    pub searxng_instances: Vec<SearxngInstance>,
+    /// 本機所在區域；優先使用此區域的主要實例
+    pub searxng_region: Option<String>,
+    /// 實例探測間隔（秒）
+    pub searxng_probe_interval_secs: u64,
+    /// 對外請求使用的代理
+    pub proxy: ProxyConfig,
+    /// 搜尋事件日誌（JSONL）；None 時停用
+    pub event_log_path: Option<std::path::PathBuf>,
+    /// 事件日誌單檔大小上限，超過時輪替
+    pub event_log_max_bytes: u64,
+    /// 保留的輪替舊檔數
+    pub event_log_max_files: usize,
+    /// 書籤檔（JSON）；None 時停用書籤工具
+    pub bookmarks_path: Option<std::path::PathBuf>,
+    /// Exa API 金鑰（`EXA_API_KEY`、`EXA_API_KEY_FILE` 或金鑰圈）
+    pub exa_api_key: Option<Secret>,
+    /// Tavily API 金鑰（`TAVILY_API_KEY`、`TAVILY_API_KEY_FILE` 或金鑰圈）
+    pub tavily_api_key: Option<Secret>,
+    /// 各引擎的啟用狀態與限流（鍵為引擎名稱）
+    pub engines: BTreeMap<String, EngineSettings>,
+    /// 階梯式檢索的升級閾值
+    pub tiers: TierThresholds,
+    /// 語義路由器的關鍵字與長度門檻
+    pub router: RouterSettings,
+    /// 付費引擎的花費上限
+    pub budget: BudgetSettings,
+    /// 排程搜尋（設定檔 `[[schedule]]`）
+    pub schedule: Vec<ScheduledJob>,
+}
+
+/// 單一引擎的設定
+#[derive(Debug, Clone, PartialEq)]
+pub struct EngineSettings {
+    pub enabled: bool,
+    /// 每秒請求數上限；None 時使用引擎預設
+    pub rate_limit_rps: Option<f64>,
+}
+
+impl Default for EngineSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rate_limit_rps: None,
+        }
+    }
+}
+
+impl BoseConfig {
+    /// 引擎是否啟用；沒有 `[engines.<名稱>]` 區段時視為啟用
+    pub fn engine_enabled(&self, name: &str) -> bool {
+        self.engines.get(name).is_none_or(|e| e.enabled)
+    }
+
+    /// 設定的每秒請求數；None 時由呼叫端使用引擎預設
+    pub fn engine_rps(&self, name: &str) -> Option<f64> {
+        self.engines.get(name).and_then(|e| e.rate_limit_rps)
+    }
+}
+
+/// 階梯式檢索設定：置信度低於閾值時升級到下一層
+#[derive(Debug, Clone, PartialEq)]
+pub struct TierThresholds {
+    /// L1 → L2 的置信度閾值
+    pub l1_threshold: f64,
+    /// L2 → L3 的置信度閾值（也是第三層之後未指定閾值時的預設）
+    pub l2_threshold: f64,
+    /// 每層的最大結果數
+    pub max_results_per_tier: usize,
+    /// 檢索鏈的順序；空白時為 DuckDuckGo → Exa → Tavily（略過沒有金鑰的引擎）
+    pub chain: Vec<TierEntry>,
+    /// 單次搜尋的花費上限（美元）
+    pub max_cost_usd: Option<f64>,
+    /// L1 開始後經過此毫秒數仍未完成，就推測性啟動 L2；None 為停用
+    pub hedge_after_ms: Option<u64>,
+    /// 升級後保留前面各層的結果並融合
+    pub merge_tiers: bool,
+}
+
+impl Default for TierThresholds {
+    fn default() -> Self {
+        Self {
+            l1_threshold: 0.80,
+            l2_threshold: 0.85,
+            max_results_per_tier: 10,
+            chain: Vec::new(),
+            max_cost_usd: None,
+            hedge_after_ms: None,
+            merge_tiers: false,
+        }
+    }
+}
+
+/// 檢索鏈中的一層（設定檔 `[[tiers.chain]]`）
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TierEntry {
+    /// 搜尋提供者名稱：`duckduckgo`、`exa`、`tavily`
+    pub provider: String,
+    /// 此層置信度達到閾值即停止；未指定時依層級沿用 `l1_threshold` / `l2_threshold`
+    #[serde(default)]
+    pub threshold: Option<f64>,
+    /// 覆寫提供者的單次成本估計（美元）
+    #[serde(default)]
+    pub cost: Option<f64>,
+}
+
+/// 付費引擎（Exa、Tavily）的硬性預算；達到上限後不再升級到付費層級
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BudgetSettings {
+    /// 每日（UTC）花費上限（美元）
+    pub daily_usd: Option<f64>,
+    /// 每月（UTC）花費上限（美元）
+    pub monthly_usd: Option<f64>,
+    /// 花費帳本（SQLite）；None 時只在記憶體中累計，預算只對本次執行有效
+    pub ledger_path: Option<std::path::PathBuf>,
+}
+
+impl BudgetSettings {
+    pub fn is_empty(&self) -> bool {
+        self.daily_usd.is_none() && self.monthly_usd.is_none() && self.ledger_path.is_none()
+    }
+}
+
+/// 語義路由器設定
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouterSettings {
+    /// 簡單查詢的最大長度（字元數）
+    pub simple_max_length: usize,
+    /// 複雜查詢的關鍵字
+    pub complex_keywords: Vec<String>,
+    /// 超過此長度（字元數）的查詢在送出前先濃縮
+    pub max_query_chars: usize,
+    /// 各複雜度使用的 LLM 模型
+    pub models: ComplexityTable,
+    /// 各複雜度使用的搜尋策略（`ROUTER_STRATEGIES` 其中之一）
+    pub strategies: ComplexityTable,
+    /// 依查詢意圖調整引擎：導航 → 單一引擎、程式碼 → 程式碼引擎、新聞 → `news` 類別
+    pub intent_routing: bool,
+    /// 導航查詢使用的單一 SearXNG 引擎
+    pub navigational_engine: String,
+    /// 程式碼查詢使用的 SearXNG 引擎
+    pub code_engines: Vec<String>,
+}
+
+/// 可用的搜尋策略名稱
+pub const ROUTER_STRATEGIES: &[&str] = &["single_engine", "tiered_retrieval", "deep_research", "hybrid"];
+
+/// 依查詢複雜度（簡單 / 中等 / 複雜）對應的值
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComplexityTable {
+    pub simple: String,
+    pub medium: String,
+    pub complex: String,
+}
+
+impl ComplexityTable {
+    fn new(simple: &str, medium: &str, complex: &str) -> Self {
+        Self {
+            simple: simple.to_string(),
+            medium: medium.to_string(),
+            complex: complex.to_string(),
+        }
+    }
+}
+
+impl Default for RouterSettings {
+    fn default() -> Self {
+        Self {
+            simple_max_length: 50,
+            complex_keywords: [
+                "分析",
+                "比較",
+                "為什麼",
+                "如何",
+                "evaluate",
+                "analyze",
+                "compare",
+            ]
+            .map(String::from)
+            .to_vec(),
+            max_query_chars: 300,
+            models: ComplexityTable::new("claude-haiku-4-5", "claude-sonnet-4-5", "claude-opus-4-5"),
+            strategies: ComplexityTable::new("single_engine", "tiered_retrieval", "deep_research"),
+            intent_routing: true,
+            navigational_engine: "duckduckgo".to_string(),
+            code_engines: ["github", "stackoverflow", "superuser", "askubuntu"]
+                .map(String::from)
+                .to_vec(),
+        }
+    }
+}
+
+impl RouterSettings {
+    /// 簡單查詢：短、沒有複雜關鍵字、最多一個問句與兩個子句
+    pub fn is_simple(&self, query: &str) -> bool {
+        let lower = query.to_lowercase();
+        let has_complex_keyword = self.complex_keywords.iter().any(|kw| lower.contains(&kw.to_lowercase()));
+        let clause_count = query.matches([',', '，', '、']).count() + 1;
+        let question_count = query.matches(['?', '？']).count();
+        !has_complex_keyword
+            && question_count <= 1
+            && clause_count <= 2
+            && query.chars().count() <= self.simple_max_length
+    }
+
+    /// 判斷查詢意圖；啟用 `intent_routing` 且呼叫端沒有指定引擎與類別時，依意圖調整 `query`
+    pub fn route(&self, query: &mut SearchQuery) -> QueryIntent {
+        let intent = detect_intent(&query.query);
+        if !self.intent_routing || !query.engines.is_empty() || query.category.is_some() {
+            return intent;
+        }
+        match intent {
+            QueryIntent::Navigational if !self.navigational_engine.is_empty() => {
+                query.engines = vec![self.navigational_engine.clone()];
+            }
+            QueryIntent::Code if !self.code_engines.is_empty() => {
+                query.engines = self.code_engines.clone();
+            }
+            QueryIntent::News => query.category = Some("news".to_string()),
+            _ => {}
+        }
+        intent
+    }
+}
+
+/// 代理設定：預設代理加上依引擎覆寫（HTTP、HTTPS、SOCKS5，例如經由 Tor 的 `socks5h://127.0.0.1:9050`）
+///
+/// 未設定時 reqwest 仍會沿用系統的 `HTTP_PROXY` / `HTTPS_PROXY` / `ALL_PROXY`。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProxyConfig {
+    /// 所有請求的預設代理（`SEARCH_PROXY`）
+    pub default: Option<String>,
+    /// 依引擎覆寫（`ENGINE_PROXIES`）；鍵為引擎名稱，`searxng` 為 SearXNG 實例、`fetch` 為頁面抓取、`translate` 為查詢翻譯，根目錄 CLI 另有 `duckduckgo`、`exa`、`tavily`
+    pub per_engine: HashMap<String, String>,
+}
+
+impl ProxyConfig {
+    const SCHEMES: &'static [&'static str] = &["http", "https", "socks5", "socks5h"];
+
+    /// 解析 `ENGINE_PROXIES`：逗號分隔的 `引擎=代理網址`
+    ///
+    /// 例如 `searxng=socks5h://127.0.0.1:9050,fetch=http://research-proxy:3128`。
+    pub fn parse_per_engine(value: &str) -> HashMap<String, String> {
+        value
+            .split(',')
+            .filter_map(|entry| entry.split_once('='))
+            .map(|(engine, url)| (engine.trim().to_string(), url.trim().to_string()))
+            .filter(|(engine, url)| !engine.is_empty() && !url.is_empty())
+            .collect()
+    }
+
+    /// 該引擎使用的代理：依引擎覆寫優先，否則為預設代理
+    pub fn for_engine(&self, engine: &str) -> Option<&str> {
+        self.per_engine
+            .get(engine)
+            .or(self.default.as_ref())
+            .map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.default.is_none() && self.per_engine.is_empty()
+    }
+
+    /// 檢查所有代理網址的協定，讓錯誤在啟動時就浮現，而不是第一次請求時
+    pub fn validate(&self) -> BoseResult<()> {
+        self.default
+            .iter()
+            .chain(self.per_engine.values())
+            .try_for_each(|url| Self::proxy(url).map(drop))
+    }
+
+    /// 將代理套用到 client builder；網址無效或協定不支援時回傳錯誤
+    pub fn apply(
+        &self,
+        builder: reqwest::ClientBuilder,
+        engine: &str,
+    ) -> BoseResult<reqwest::ClientBuilder> {
+        match self.for_engine(engine) {
+            Some(url) => Ok(builder.proxy(Self::proxy(url)?)),
+            None => Ok(builder),
+        }
+    }
+
+    /// 以代理網址建立 `reqwest::Proxy`；只接受 HTTP(S) 與 SOCKS5
+    pub fn proxy(url: &str) -> BoseResult<reqwest::Proxy> {
+        let scheme = url.split_once("://").map(|(scheme, _)| scheme);
+        if !scheme.is_some_and(|s| Self::SCHEMES.contains(&s.to_ascii_lowercase().as_str())) {
+            return Err(BoseError::ConfigError(format!(
+                "proxy must use one of {}: {url}",
+                Self::SCHEMES.join(", ")
+            )));
+        }
+        reqwest::Proxy::all(url).map_err(|e| BoseError::ConfigError(format!("proxy {url}: {e}")))
+    }
+}
+
+/// 叢集中的單一 SearXNG 實例
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearxngInstance {
+    pub region: String,
+    pub url: String,
+}
+
+impl SearxngInstance {
+    /// 未標示區域的實例歸在此區域
+    pub const DEFAULT_REGION: &'static str = "default";
+
+    /// 解析 `SEARXNG_INSTANCES`：逗號分隔的 `區域=網址` 或單純網址
+    ///
+    /// 例如 `eu=http://searx-eu-1:8080,eu=http://searx-eu-2:8080,us=http://searx-us:8080`。
+    pub fn parse_list(value: &str) -> Vec<Self> {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| match entry.split_once('=') {
+                Some((region, url)) if !region.contains("://") => Self {
+                    region: region.trim().to_string(),
+                    url: url.trim().trim_end_matches('/').to_string(),
+                },
+                _ => Self {
+                    region: Self::DEFAULT_REGION.to_string(),
+                    url: entry.trim_end_matches('/').to_string(),
+                },
+            })
+            .collect()
+    }
 }
 
 impl Default for BoseConfig {
@@ -12,25 +355,299 @@ impl Default for BoseConfig {
             searxng_url: "http://localhost:8080".to_string(),
             default_num_results: 10,
             request_timeout_secs: 30,
+            max_retries: 2,
+            auto_correct_min_results: 0,
+            cache_ttl_secs: 300,
+            cache_max_entries: 1000,
+            searxng_instances: Vec::new(),
+            searxng_region: None,
+            searxng_probe_interval_secs: 30,
+            proxy: ProxyConfig::default(),
+            event_log_path: None,
+            event_log_max_bytes: 10 * 1024 * 1024,
+            event_log_max_files: 5,
+            bookmarks_path: None,
+            exa_api_key: None,
+            tavily_api_key: None,
+            engines: BTreeMap::new(),
+            tiers: TierThresholds::default(),
+            router: RouterSettings::default(),
+            budget: BudgetSettings::default(),
+            schedule: Vec::new(),
         }
     }
 }
 
 impl BoseConfig {
-    pub fn from_env() -> Self {
+    pub fn from_env() -> BoseResult<Self> {
+        let mut config = Self::default();
+        config.apply_env()?;
+        Ok(config)
+    }
+
+    /// 以已設定的環境變數覆寫目前的值；未設定或無法解析的變數保留原值
+    ///
+    /// 金鑰的 `*_FILE` 讀不到或金鑰圈無法使用時回傳錯誤。
+    pub fn apply_env(&mut self) -> BoseResult<()> {
+        if let Ok(url) = std::env::var("SEARXNG_URL") {
+            self.searxng_url = url;
+        }
+        set(
+            &mut self.default_num_results,
+            env_parse("DEFAULT_NUM_RESULTS"),
+        );
+        set(
+            &mut self.request_timeout_secs,
+            env_parse("REQUEST_TIMEOUT_SECS"),
+        );
+        set(&mut self.max_retries, env_parse("MAX_RETRIES"));
+        set(
+            &mut self.auto_correct_min_results,
+            env_parse("AUTO_CORRECT_MIN_RESULTS"),
+        );
+        set(&mut self.cache_ttl_secs, env_parse("CACHE_TTL_SECS"));
+        set(&mut self.cache_max_entries, env_parse("CACHE_MAX_ENTRIES"));
+        if let Ok(v) = std::env::var("SEARXNG_INSTANCES") {
+            self.searxng_instances = SearxngInstance::parse_list(&v);
+        }
+        if let Some(region) = std::env::var("SEARXNG_REGION")
+            .ok()
+            .filter(|r| !r.is_empty())
+        {
+            self.searxng_region = Some(region);
+        }
+        set(
+            &mut self.searxng_probe_interval_secs,
+            env_parse("SEARXNG_PROBE_INTERVAL_SECS"),
+        );
+        if let Some(proxy) = std::env::var("SEARCH_PROXY").ok().filter(|p| !p.is_empty()) {
+            self.proxy.default = Some(proxy);
+        }
+        if let Ok(v) = std::env::var("ENGINE_PROXIES") {
+            self.proxy
+                .per_engine
+                .extend(ProxyConfig::parse_per_engine(&v));
+        }
+        if let Some(path) = std::env::var("EVENT_LOG_PATH")
+            .ok()
+            .filter(|p| !p.is_empty())
+        {
+            self.event_log_path = Some(path.into());
+        }
+        set(
+            &mut self.event_log_max_bytes,
+            env_parse("EVENT_LOG_MAX_BYTES"),
+        );
+        set(
+            &mut self.event_log_max_files,
+            env_parse("EVENT_LOG_MAX_FILES"),
+        );
+        if let Some(path) = std::env::var("BOOKMARKS_PATH")
+            .ok()
+            .filter(|p| !p.is_empty())
+        {
+            self.bookmarks_path = Some(path.into());
+        }
+        if let Some(usd) = env_parse("BUDGET_DAILY_USD") {
+            self.budget.daily_usd = Some(usd);
+        }
+        if let Some(usd) = env_parse("BUDGET_MONTHLY_USD") {
+            self.budget.monthly_usd = Some(usd);
+        }
+        if let Some(path) = std::env::var("COST_LEDGER_PATH")
+            .ok()
+            .filter(|p| !p.is_empty())
+        {
+            self.budget.ledger_path = Some(path.into());
+        }
+        if let Some(key) = load_secret("EXA_API_KEY")? {
+            self.exa_api_key = Some(key);
+        }
+        if let Some(key) = load_secret("TAVILY_API_KEY")? {
+            self.tavily_api_key = Some(key);
+        }
+        Ok(())
+    }
+}
+
+/// 需要 API 金鑰的付費引擎
+const KEYED_ENGINES: [&str; 2] = ["exa", "tavily"];
+
+/// 單一設定問題：出錯的欄位與修正方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    /// 欄位路徑，與設定檔相同，例如 `tiers.l1_threshold`
+    pub field: String,
+    pub message: String,
+}
+
+impl ConfigError {
+    fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
         Self {
-            searxng_url: std::env::var("SEARXNG_URL")
-                .unwrap_or_else(|_| "http://localhost:8080".to_string()),
-            default_num_results: std::env::var("DEFAULT_NUM_RESULTS")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(10),
-            request_timeout_secs: std::env::var("REQUEST_TIMEOUT_SECS")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(30),
+            field: field.into(),
+            message: message.into(),
         }
     }
+
+    /// 合併多個問題為一個 `BoseError`，每行一個
+    pub fn into_bose_error(errors: Vec<ConfigError>) -> BoseError {
+        let lines: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        BoseError::ConfigError(lines.join("\n"))
+    }
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "`{}`: {}", self.field, self.message)
+    }
+}
+
+impl BoseConfig {
+    /// 啟動時檢查設定，一次回報所有問題，而不是在第一個請求深處才失敗
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let mut errors = Vec::new();
+
+        check_url(&mut errors, "searxng.url", &self.searxng_url);
+        for (i, instance) in self.searxng_instances.iter().enumerate() {
+            check_url(&mut errors, &format!("searxng.instances[{i}]"), &instance.url);
+        }
+
+        for (field, value) in [
+            ("search.request_timeout_secs", self.request_timeout_secs),
+            ("search.default_num_results", u64::from(self.default_num_results)),
+            ("searxng.probe_interval_secs", self.searxng_probe_interval_secs),
+            ("event_log.max_bytes", self.event_log_max_bytes),
+            ("router.max_query_chars", self.router.max_query_chars as u64),
+        ] {
+            if value == 0 {
+                errors.push(ConfigError::new(field, "必須大於 0"));
+            }
+        }
+
+        let tiers = &self.tiers;
+        for (field, value) in [
+            ("tiers.l1_threshold", tiers.l1_threshold),
+            ("tiers.l2_threshold", tiers.l2_threshold),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                errors.push(ConfigError::new(field, format!("必須介於 0 與 1 之間（目前 {value}）")));
+            }
+        }
+        for (i, entry) in tiers.chain.iter().enumerate() {
+            if entry.provider.trim().is_empty() {
+                errors.push(ConfigError::new(format!("tiers.chain[{i}].provider"), "不可為空"));
+            }
+            if entry.threshold.is_some_and(|v| !(0.0..=1.0).contains(&v)) {
+                errors.push(ConfigError::new(format!("tiers.chain[{i}].threshold"), "必須介於 0 與 1 之間"));
+            }
+            if entry.cost.is_some_and(|v| v < 0.0) {
+                errors.push(ConfigError::new(format!("tiers.chain[{i}].cost"), "不可為負數"));
+            }
+        }
+        if tiers.max_cost_usd.is_some_and(|v| v < 0.0) {
+            errors.push(ConfigError::new("tiers.max_cost_usd", "不可為負數"));
+        }
+        if tiers.l1_threshold >= tiers.l2_threshold {
+            errors.push(ConfigError::new(
+                "tiers.l1_threshold",
+                format!(
+                    "必須小於 tiers.l2_threshold（目前 {} ≥ {}），否則 L2 結果永遠不會升級到 L3",
+                    tiers.l1_threshold, tiers.l2_threshold
+                ),
+            ));
+        }
+
+        for (field, value) in [
+            ("budget.daily_usd", self.budget.daily_usd),
+            ("budget.monthly_usd", self.budget.monthly_usd),
+        ] {
+            if value.is_some_and(|v| v < 0.0) {
+                errors.push(ConfigError::new(field, "不可為負數"));
+            }
+        }
+
+        let engines = self
+            .proxy
+            .per_engine
+            .keys()
+            .map(String::as_str)
+            .chain(std::iter::once("default"));
+        for engine in engines {
+            if let Err(e) = self.proxy.apply(reqwest::Client::builder(), engine) {
+                let field = match engine {
+                    "default" => "proxy.default".to_string(),
+                    engine => format!("proxy.per_engine.{engine}"),
+                };
+                errors.push(ConfigError::new(field, message(e)));
+            }
+        }
+
+        for engine in KEYED_ENGINES {
+            let enabled = self.engines.get(engine).is_some_and(|e| e.enabled);
+            let key = match engine {
+                "exa" => &self.exa_api_key,
+                _ => &self.tavily_api_key,
+            };
+            if enabled && key.is_none() {
+                let var = engine.to_uppercase();
+                errors.push(ConfigError::new(
+                    format!("engines.{engine}"),
+                    format!(
+                        "已啟用但沒有 API 金鑰；請設定 {var}_API_KEY、{var}_API_KEY_FILE 或 keys.{engine}，或設 enabled = false"
+                    ),
+                ));
+            }
+        }
+
+        let mut names = std::collections::HashSet::new();
+        for (i, job) in self.schedule.iter().enumerate() {
+            if !names.insert(job.name.as_str()) {
+                errors.push(ConfigError::new(
+                    format!("schedule[{i}].name"),
+                    format!("`{}` 重複", job.name),
+                ));
+            }
+            if job.query.trim().is_empty() {
+                errors.push(ConfigError::new(format!("schedule[{i}].query"), "不可為空"));
+            }
+            for (j, webhook) in job.webhooks.iter().enumerate() {
+                check_url(&mut errors, &format!("schedule[{i}].webhooks[{j}]"), &webhook.url);
+            }
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+fn check_url(errors: &mut Vec<ConfigError>, field: &str, value: &str) {
+    match url::Url::parse(value) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") && url.host().is_some() => {}
+        Ok(url) => errors.push(ConfigError::new(
+            field,
+            format!("`{value}` 必須是 http(s) 網址（目前協定為 {}）", url.scheme()),
+        )),
+        Err(e) => errors.push(ConfigError::new(field, format!("`{value}` 不是有效網址: {e}"))),
+    }
+}
+
+/// 取出 `ConfigError` 的訊息，避免巢狀錯誤重複「配置錯誤:」前綴
+pub(crate) fn message(error: BoseError) -> String {
+    match error {
+        BoseError::ConfigError(msg) => msg,
+        other => other.to_string(),
+    }
+}
+
+/// 有值時覆寫
+pub(crate) fn set<T>(slot: &mut T, value: Option<T>) {
+    if let Some(value) = value {
+        *slot = value;
+    }
+}
+
+/// 讀取並解析環境變數；未設定或無法解析時為 None
+pub fn env_parse<T: std::str::FromStr>(key: &str) -> Option<T> {
+    std::env::var(key).ok().and_then(|v| v.parse().ok())
 }
 
 #[cfg(test)]
@@ -43,11 +660,132 @@ mod tests {
         assert_eq!(c.searxng_url, "http://localhost:8080");
         assert_eq!(c.default_num_results, 10);
         assert_eq!(c.request_timeout_secs, 30);
+        assert_eq!(c.max_retries, 2);
+        assert_eq!(c.cache_ttl_secs, 300);
+        assert_eq!(c.cache_max_entries, 1000);
+    }
+
+    #[test]
+    fn test_router_route_by_intent() {
+        let router = RouterSettings::default();
+
+        let mut nav = SearchQuery::new("github.com");
+        assert_eq!(router.route(&mut nav), QueryIntent::Navigational);
+        assert_eq!(nav.engines, vec!["duckduckgo"]);
+
+        let mut code = SearchQuery::new("tokio::spawn lifetime error");
+        assert_eq!(router.route(&mut code), QueryIntent::Code);
+        assert!(code.engines.contains(&"github".to_string()));
+
+        let mut news = SearchQuery::new("latest rust release news");
+        assert_eq!(router.route(&mut news), QueryIntent::News);
+        assert_eq!(news.category.as_deref(), Some("news"));
+        assert!(news.engines.is_empty());
+
+        // 呼叫端指定的類別優先
+        let mut explicit = SearchQuery::new("serde_json from_str").with_category("it");
+        assert_eq!(router.route(&mut explicit), QueryIntent::Code);
+        assert!(explicit.engines.is_empty());
+
+        let disabled = RouterSettings {
+            intent_routing: false,
+            ..RouterSettings::default()
+        };
+        let mut nav = SearchQuery::new("github.com");
+        disabled.route(&mut nav);
+        assert!(nav.engines.is_empty());
+    }
+
+    #[test]
+    fn test_router_is_simple() {
+        let router = RouterSettings::default();
+        assert!(router.is_simple("rust release date"));
+        assert!(!router.is_simple("compare tokio and async-std"));
+        assert!(!router.is_simple("what is rust? who made it?"));
+        assert!(!router.is_simple(&"long query ".repeat(10)));
+    }
+
+    #[test]
+    fn test_parse_instances() {
+        let instances = SearxngInstance::parse_list(
+            "eu=http://a:8080/, http://b:8080 ,us = http://c:8080,,",
+        );
+        assert_eq!(
+            instances,
+            vec![
+                SearxngInstance { region: "eu".into(), url: "http://a:8080".into() },
+                SearxngInstance { region: "default".into(), url: "http://b:8080".into() },
+                SearxngInstance { region: "us".into(), url: "http://c:8080".into() },
+            ]
+        );
+        assert!(SearxngInstance::parse_list("").is_empty());
+    }
+
+    #[test]
+    fn test_proxy_config() {
+        let proxy = ProxyConfig {
+            default: Some("http://proxy:3128".into()),
+            per_engine: ProxyConfig::parse_per_engine(
+                "searxng = socks5h://127.0.0.1:9050, broken, fetch=",
+            ),
+        };
+        assert_eq!(proxy.per_engine.len(), 1);
+        assert_eq!(proxy.for_engine("searxng"), Some("socks5h://127.0.0.1:9050"));
+        assert_eq!(proxy.for_engine("fetch"), Some("http://proxy:3128"));
+        assert!(proxy.apply(reqwest::Client::builder(), "searxng").is_ok());
+        assert!(ProxyConfig::default().for_engine("searxng").is_none());
+
+        let bad = ProxyConfig {
+            default: Some("ftp://proxy".into()),
+            ..ProxyConfig::default()
+        };
+        assert!(matches!(
+            bad.apply(reqwest::Client::builder(), "searxng"),
+            Err(BoseError::ConfigError(_))
+        ));
+        assert!(bad.validate().is_err());
+        assert!(proxy.validate().is_ok());
+        assert!(ProxyConfig::default().is_empty());
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(BoseConfig::default().validate(), Ok(()));
+
+        let mut config = BoseConfig {
+            searxng_url: "localhost:8080".into(),
+            request_timeout_secs: 0,
+            ..BoseConfig::default()
+        };
+        config.tiers.l1_threshold = 0.9;
+        config.proxy.per_engine.insert("fetch".into(), "ftp://proxy".into());
+        config.engines.insert("exa".into(), EngineSettings::default());
+        config.engines.insert(
+            "tavily".into(),
+            EngineSettings { enabled: false, ..EngineSettings::default() },
+        );
+
+        let errors = config.validate().unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "searxng.url",
+                "search.request_timeout_secs",
+                "tiers.l1_threshold",
+                "proxy.per_engine.fetch",
+                "engines.exa",
+            ]
+        );
+        assert!(errors[4].to_string().contains("EXA_API_KEY"));
+
+        config.exa_api_key = Some(Secret::new("key"));
+        assert_eq!(config.validate().unwrap_err().len(), 4);
     }
 
     #[test]
     fn test_config_from_env() {
-        let c = BoseConfig::from_env();
+        let c = BoseConfig::from_env().unwrap();
         assert_eq!(c.default_num_results, 10);
     }
 }
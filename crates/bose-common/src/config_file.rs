@@ -0,0 +1,577 @@
+//! 設定檔載入 — TOML / YAML，分層合併為 `BoseConfig`
+//!
+//! 優先順序（後者覆寫前者）：預設值 → 設定檔 → 環境變數 → 命令列覆寫（`cache.ttl_secs=60`）。
+//! 未知欄位與型別錯誤都會指出完整的欄位路徑，例如 `tiers.l1_threshold`。
+//!
+//! ```toml
+//! [searxng]
+//! url = "http://localhost:8080"
+//!
+//! [cache]
+//! ttl_secs = 600
+//!
+//! [engines.exa]
+//! rate_limit_rps = 5
+//!
+//! [tiers]
+//! l1_threshold = 0.75
+//!
+//! [[tiers.chain]]
+//! provider = "duckduckgo"
+//! threshold = 0.7
+//!
+//! [[tiers.chain]]
+//! provider = "exa"
+//!
+//! [router]
+//! complex_keywords = ["分析", "compare"]
+//!
+//! [router.models]
+//! simple = "gpt-4o-mini"
+//!
+//! [budget]
+//! daily_usd = 1.0
+//! ledger_path = "~/.bose/spend.db"
+//!
+//! [[schedule]]
+//! name = "xz-backdoor"
+//! query = "CVE-2024-3094"
+//! cron = "@hourly"
+//! webhooks = ["https://hooks.slack.com/services/T000/B000/XXXX"]
+//! ```
+
+use crate::config::{message, set};
+use crate::schedule::ScheduledJob;
+use crate::{
+    BoseConfig, BoseError, BoseResult, ComplexityTable, ConfigError, SearxngInstance, Secret, TierEntry,
+    ROUTER_STRATEGIES,
+};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// 設定檔內容；每個欄位都是選填，只覆寫有寫出來的值
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    pub searxng: Option<SearxngSection>,
+    pub search: Option<SearchSection>,
+    pub cache: Option<CacheSection>,
+    pub proxy: Option<ProxySection>,
+    pub event_log: Option<EventLogSection>,
+    pub bookmarks: Option<BookmarksSection>,
+    pub keys: Option<KeysSection>,
+    pub engines: Option<BTreeMap<String, EngineSection>>,
+    pub tiers: Option<TiersSection>,
+    pub router: Option<RouterSection>,
+    pub budget: Option<BudgetSection>,
+    pub schedule: Option<Vec<ScheduledJob>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SearxngSection {
+    pub url: Option<String>,
+    /// `區域=網址` 或單純網址，與 `SEARXNG_INSTANCES` 相同
+    pub instances: Option<Vec<String>>,
+    pub region: Option<String>,
+    pub probe_interval_secs: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SearchSection {
+    pub default_num_results: Option<u32>,
+    pub request_timeout_secs: Option<u64>,
+    pub max_retries: Option<u32>,
+    pub auto_correct_min_results: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CacheSection {
+    pub ttl_secs: Option<u64>,
+    pub max_entries: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProxySection {
+    pub default: Option<String>,
+    pub per_engine: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EventLogSection {
+    pub path: Option<PathBuf>,
+    pub max_bytes: Option<u64>,
+    pub max_files: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BookmarksSection {
+    pub path: Option<PathBuf>,
+}
+
+/// 金鑰建議改用 `*_FILE` 或金鑰圈；寫在設定檔時請限制檔案權限
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KeysSection {
+    pub exa: Option<Secret>,
+    pub tavily: Option<Secret>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EngineSection {
+    pub enabled: Option<bool>,
+    pub rate_limit_rps: Option<f64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TiersSection {
+    pub l1_threshold: Option<f64>,
+    pub l2_threshold: Option<f64>,
+    pub max_results_per_tier: Option<usize>,
+    pub chain: Option<Vec<TierEntry>>,
+    pub max_cost_usd: Option<f64>,
+    pub hedge_after_ms: Option<u64>,
+    pub merge_tiers: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RouterSection {
+    pub simple_max_length: Option<usize>,
+    pub complex_keywords: Option<Vec<String>>,
+    pub max_query_chars: Option<usize>,
+    pub models: Option<ComplexitySection>,
+    pub strategies: Option<ComplexitySection>,
+    pub intent_routing: Option<bool>,
+    pub navigational_engine: Option<String>,
+    pub code_engines: Option<Vec<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BudgetSection {
+    pub daily_usd: Option<f64>,
+    pub monthly_usd: Option<f64>,
+    pub ledger_path: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ComplexitySection {
+    pub simple: Option<String>,
+    pub medium: Option<String>,
+    pub complex: Option<String>,
+}
+
+impl ComplexitySection {
+    fn apply(self, table: &mut ComplexityTable) {
+        set(&mut table.simple, self.simple);
+        set(&mut table.medium, self.medium);
+        set(&mut table.complex, self.complex);
+    }
+}
+
+/// 設定檔格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// 依副檔名判斷（`.toml`、`.yaml`、`.yml`）
+    pub fn from_path(path: &Path) -> BoseResult<Self> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Ok(Self::Toml),
+            Some("yaml" | "yml") => Ok(Self::Yaml),
+            _ => Err(BoseError::ConfigError(format!(
+                "{}: 無法判斷設定檔格式（副檔名須為 .toml、.yaml 或 .yml）",
+                path.display()
+            ))),
+        }
+    }
+}
+
+impl ConfigFile {
+    /// 解析設定檔內容；錯誤訊息包含欄位路徑
+    pub fn parse(content: &str, format: ConfigFormat) -> BoseResult<Self> {
+        let file: Self = match format {
+            ConfigFormat::Toml => deserialize(toml::Deserializer::new(content))?,
+            ConfigFormat::Yaml if content.trim().is_empty() => Self::default(),
+            ConfigFormat::Yaml => deserialize(serde_yaml::Deserializer::from_str(content))?,
+        };
+        file.validate()?;
+        Ok(file)
+    }
+
+    pub fn read(path: &Path) -> BoseResult<Self> {
+        let format = ConfigFormat::from_path(path)?;
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| BoseError::ConfigError(format!("{}: {e}", path.display())))?;
+        Self::parse(&content, format)
+            .map_err(|e| BoseError::ConfigError(format!("{}: {}", path.display(), message(e))))
+    }
+
+    /// 解析一筆命令列覆寫 `路徑=值`，值採 TOML 語法；不是合法 TOML 時當作字串
+    ///
+    /// 例如 `cache.ttl_secs=60`、`engines.exa.enabled=false`、`searxng.url=http://searx:8080`。
+    pub fn parse_override(entry: &str) -> BoseResult<Self> {
+        let Some((key, value)) = entry.split_once('=') else {
+            return Err(BoseError::ConfigError(format!(
+                "覆寫 `{entry}` 須為 `路徑=值` 格式"
+            )));
+        };
+        let (key, value) = (key.trim(), value.trim());
+        let parsed = Self::parse(&format!("{key} = {value}"), ConfigFormat::Toml).or_else(|_| {
+            let quoted = toml::Value::String(value.to_string());
+            Self::parse(&format!("{key} = {quoted}"), ConfigFormat::Toml)
+        });
+        parsed.map_err(|e| BoseError::ConfigError(format!("覆寫 `{entry}`: {}", message(e))))
+    }
+
+    fn validate(&self) -> BoseResult<()> {
+        let invalid =
+            |field: &str, reason: &str| Err(BoseError::ConfigError(format!("`{field}` {reason}")));
+        if let Some(tiers) = &self.tiers {
+            for (field, value) in [
+                ("tiers.l1_threshold", tiers.l1_threshold),
+                ("tiers.l2_threshold", tiers.l2_threshold),
+            ] {
+                if value.is_some_and(|v| !(0.0..=1.0).contains(&v)) {
+                    return invalid(field, "必須介於 0 與 1 之間");
+                }
+            }
+        }
+        if let Some(strategies) = self.router.as_ref().and_then(|r| r.strategies.as_ref()) {
+            for (level, value) in [
+                ("simple", &strategies.simple),
+                ("medium", &strategies.medium),
+                ("complex", &strategies.complex),
+            ] {
+                if value.as_deref().is_some_and(|v| !ROUTER_STRATEGIES.contains(&v)) {
+                    return invalid(
+                        &format!("router.strategies.{level}"),
+                        &format!("必須是 {} 其中之一", ROUTER_STRATEGIES.join("、")),
+                    );
+                }
+            }
+        }
+        for (name, engine) in self.engines.iter().flatten() {
+            if engine.rate_limit_rps.is_some_and(|v| v <= 0.0) {
+                return invalid(&format!("engines.{name}.rate_limit_rps"), "必須大於 0");
+            }
+        }
+        Ok(())
+    }
+
+    /// 將有寫出來的值覆寫到 `config`
+    pub fn apply(self, config: &mut BoseConfig) {
+        if let Some(s) = self.searxng {
+            set(&mut config.searxng_url, s.url);
+            if let Some(instances) = s.instances {
+                config.searxng_instances = instances
+                    .iter()
+                    .flat_map(|entry| SearxngInstance::parse_list(entry))
+                    .collect();
+            }
+            if s.region.is_some() {
+                config.searxng_region = s.region;
+            }
+            set(
+                &mut config.searxng_probe_interval_secs,
+                s.probe_interval_secs,
+            );
+        }
+        if let Some(s) = self.search {
+            set(&mut config.default_num_results, s.default_num_results);
+            set(&mut config.request_timeout_secs, s.request_timeout_secs);
+            set(&mut config.max_retries, s.max_retries);
+            set(
+                &mut config.auto_correct_min_results,
+                s.auto_correct_min_results,
+            );
+        }
+        if let Some(s) = self.cache {
+            set(&mut config.cache_ttl_secs, s.ttl_secs);
+            set(&mut config.cache_max_entries, s.max_entries);
+        }
+        if let Some(s) = self.proxy {
+            if s.default.is_some() {
+                config.proxy.default = s.default;
+            }
+            config
+                .proxy
+                .per_engine
+                .extend(s.per_engine.unwrap_or_default());
+        }
+        if let Some(s) = self.event_log {
+            if s.path.is_some() {
+                config.event_log_path = s.path;
+            }
+            set(&mut config.event_log_max_bytes, s.max_bytes);
+            set(&mut config.event_log_max_files, s.max_files);
+        }
+        if let Some(path) = self.bookmarks.and_then(|s| s.path) {
+            config.bookmarks_path = Some(path);
+        }
+        if let Some(s) = self.keys {
+            if s.exa.is_some() {
+                config.exa_api_key = s.exa;
+            }
+            if s.tavily.is_some() {
+                config.tavily_api_key = s.tavily;
+            }
+        }
+        for (name, s) in self.engines.unwrap_or_default() {
+            let engine = config.engines.entry(name).or_default();
+            set(&mut engine.enabled, s.enabled);
+            if s.rate_limit_rps.is_some() {
+                engine.rate_limit_rps = s.rate_limit_rps;
+            }
+        }
+        if let Some(s) = self.tiers {
+            set(&mut config.tiers.l1_threshold, s.l1_threshold);
+            set(&mut config.tiers.l2_threshold, s.l2_threshold);
+            set(
+                &mut config.tiers.max_results_per_tier,
+                s.max_results_per_tier,
+            );
+            set(&mut config.tiers.chain, s.chain);
+            if s.max_cost_usd.is_some() {
+                config.tiers.max_cost_usd = s.max_cost_usd;
+            }
+            if s.hedge_after_ms.is_some() {
+                config.tiers.hedge_after_ms = s.hedge_after_ms;
+            }
+            set(&mut config.tiers.merge_tiers, s.merge_tiers);
+        }
+        if let Some(s) = self.router {
+            set(&mut config.router.simple_max_length, s.simple_max_length);
+            set(&mut config.router.complex_keywords, s.complex_keywords);
+            set(&mut config.router.max_query_chars, s.max_query_chars);
+            if let Some(models) = s.models {
+                models.apply(&mut config.router.models);
+            }
+            if let Some(strategies) = s.strategies {
+                strategies.apply(&mut config.router.strategies);
+            }
+            set(&mut config.router.intent_routing, s.intent_routing);
+            set(&mut config.router.navigational_engine, s.navigational_engine);
+            set(&mut config.router.code_engines, s.code_engines);
+        }
+        if let Some(s) = self.budget {
+            if s.daily_usd.is_some() {
+                config.budget.daily_usd = s.daily_usd;
+            }
+            if s.monthly_usd.is_some() {
+                config.budget.monthly_usd = s.monthly_usd;
+            }
+            if s.ledger_path.is_some() {
+                config.budget.ledger_path = s.ledger_path;
+            }
+        }
+        set(&mut config.schedule, self.schedule);
+    }
+}
+
+impl BoseConfig {
+    /// 分層載入：預設值 → 設定檔 → 環境變數 → 覆寫，最後以 `validate` 檢查
+    pub fn load(path: Option<&Path>, overrides: &[String]) -> BoseResult<Self> {
+        let mut config = Self::default();
+        if let Some(path) = path {
+            ConfigFile::read(path)?.apply(&mut config);
+        }
+        config.apply_env()?;
+        for entry in overrides {
+            ConfigFile::parse_override(entry)?.apply(&mut config);
+        }
+        config.validate().map_err(ConfigError::into_bose_error)?;
+        Ok(config)
+    }
+
+    /// `BOSE_CONFIG` 指定設定檔時載入，否則同 `from_env`
+    pub fn from_env_or_file() -> BoseResult<Self> {
+        let path = std::env::var_os("BOSE_CONFIG")
+            .filter(|p| !p.is_empty())
+            .map(PathBuf::from);
+        Self::load(path.as_deref(), &[])
+    }
+}
+
+/// 反序列化並在錯誤訊息前加上欄位路徑
+fn deserialize<'de, D, T>(deserializer: D) -> BoseResult<T>
+where
+    D: serde::Deserializer<'de>,
+    D::Error: std::fmt::Display,
+    T: DeserializeOwned,
+{
+    serde_path_to_error::deserialize(deserializer).map_err(|e| {
+        let path = e.path().to_string();
+        let inner = e.into_inner().to_string();
+        let inner = inner.trim();
+        BoseError::ConfigError(if path == "." {
+            inner.to_string()
+        } else {
+            format!("`{path}`: {inner}")
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn err(result: BoseResult<ConfigFile>) -> String {
+        message(result.unwrap_err())
+    }
+
+    #[test]
+    fn test_toml_layers() {
+        let file = ConfigFile::parse(
+            r#"
+            [searxng]
+            url = "http://searx:8080"
+            instances = ["eu=http://a:8080", "http://b:8080"]
+
+            [cache]
+            ttl_secs = 600
+
+            [keys]
+            exa = "exa-from-file"
+
+            [engines.exa]
+            rate_limit_rps = 5
+
+            [tiers]
+            l1_threshold = 0.7
+
+            [[tiers.chain]]
+            provider = "exa"
+            threshold = 0.9
+
+            [router]
+            complex_keywords = ["分析"]
+            code_engines = ["github"]
+
+            [router.models]
+            complex = "gpt-4o"
+
+            [router.strategies]
+            medium = "hybrid"
+
+            [budget]
+            daily_usd = 2.5
+            "#,
+            ConfigFormat::Toml,
+        )
+        .unwrap();
+        let mut config = BoseConfig::default();
+        file.apply(&mut config);
+        assert_eq!(config.searxng_url, "http://searx:8080");
+        assert_eq!(config.searxng_instances.len(), 2);
+        assert_eq!(config.cache_ttl_secs, 600);
+        assert_eq!(config.cache_max_entries, 1000);
+        assert_eq!(
+            config.exa_api_key.as_ref().unwrap().expose(),
+            "exa-from-file"
+        );
+        assert_eq!(config.engines["exa"].rate_limit_rps, Some(5.0));
+        assert!(config.engines["exa"].enabled);
+        assert_eq!(config.tiers.l1_threshold, 0.7);
+        assert_eq!(config.tiers.l2_threshold, 0.85);
+        assert_eq!(config.tiers.chain[0].provider, "exa");
+        assert_eq!(config.tiers.chain[0].threshold, Some(0.9));
+        assert_eq!(config.router.complex_keywords, vec!["分析"]);
+        assert_eq!(config.router.models.complex, "gpt-4o");
+        assert_eq!(config.router.models.simple, "claude-haiku-4-5");
+        assert_eq!(config.router.strategies.medium, "hybrid");
+        assert_eq!(config.router.code_engines, vec!["github"]);
+        assert!(config.router.intent_routing);
+        assert_eq!(config.budget.daily_usd, Some(2.5));
+        assert!(config.budget.monthly_usd.is_none());
+
+        // 覆寫只改動指定的欄位
+        ConfigFile::parse_override("engines.exa.enabled=false")
+            .unwrap()
+            .apply(&mut config);
+        ConfigFile::parse_override("searxng.url=http://override:8080")
+            .unwrap()
+            .apply(&mut config);
+        assert!(!config.engines["exa"].enabled);
+        assert_eq!(config.engines["exa"].rate_limit_rps, Some(5.0));
+        assert!(!config.engine_enabled("exa"));
+        assert!(config.engine_enabled("duckduckgo"));
+        assert_eq!(config.engine_rps("exa"), Some(5.0));
+        assert_eq!(config.searxng_url, "http://override:8080");
+    }
+
+    #[test]
+    fn test_yaml() {
+        let file = ConfigFile::parse(
+            "search:\n  max_retries: 4\nengines:\n  tavily:\n    enabled: false\n",
+            ConfigFormat::Yaml,
+        )
+        .unwrap();
+        let mut config = BoseConfig::default();
+        file.apply(&mut config);
+        assert_eq!(config.max_retries, 4);
+        assert!(!config.engines["tavily"].enabled);
+        assert!(ConfigFile::parse("", ConfigFormat::Yaml).is_ok());
+    }
+
+    #[test]
+    fn test_errors_name_field() {
+        let typo = err(ConfigFile::parse(
+            "[cache]\ntll_secs = 1\n",
+            ConfigFormat::Toml,
+        ));
+        assert!(typo.starts_with("`cache.tll_secs`"), "{typo}");
+        assert!(typo.contains("unknown field"), "{typo}");
+
+        let wrong_type = err(ConfigFile::parse(
+            "tiers:\n  l1_threshold: high\n",
+            ConfigFormat::Yaml,
+        ));
+        assert!(
+            wrong_type.starts_with("`tiers.l1_threshold`"),
+            "{wrong_type}"
+        );
+
+        let range = err(ConfigFile::parse(
+            "[tiers]\nl2_threshold = 1.5\n",
+            ConfigFormat::Toml,
+        ));
+        assert_eq!(range, "`tiers.l2_threshold` 必須介於 0 與 1 之間");
+
+        let rps = err(ConfigFile::parse(
+            "[engines.exa]\nrate_limit_rps = 0\n",
+            ConfigFormat::Toml,
+        ));
+        assert_eq!(rps, "`engines.exa.rate_limit_rps` 必須大於 0");
+
+        let strategy = err(ConfigFile::parse(
+            "[router.strategies]\ncomplex = \"deep\"\n",
+            ConfigFormat::Toml,
+        ));
+        assert!(strategy.starts_with("`router.strategies.complex` 必須是"), "{strategy}");
+
+        let cron = err(ConfigFile::parse(
+            "[[schedule]]\nname = \"xz\"\nquery = \"xz\"\ncron = \"* * *\"\n",
+            ConfigFormat::Toml,
+        ));
+        assert!(cron.starts_with("`schedule[0].cron`"), "{cron}");
+
+        assert!(err(ConfigFile::parse_override("cache.ttl_secs")).contains("路徑=值"));
+        assert!(err(ConfigFile::parse_override("cache.ttl_secs=abc")).contains("cache.ttl_secs"));
+        assert!(ConfigFormat::from_path(Path::new("bose.json")).is_err());
+    }
+}
@@ -0,0 +1,152 @@
+//! 結果比較 — 比較同一查詢前後兩次的結果，找出新增、消失與內容變動的項目
+//!
+//! 結果以 `url::dedup_key` 對應（忽略追蹤參數、片段、主機大小寫、預設連接埠與結尾斜線），
+//! 內容以標題與摘要的雜湊比較。排程搜尋用它決定要通知什麼，也可用來自建監控。
+
+use crate::url::dedup_key;
+use crate::{SearchResponse, SearchResult};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// 只出現在其中一邊的結果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiffEntry {
+    /// 在所屬回應中的排名（從 1 起算）
+    pub rank: usize,
+    pub result: SearchResult,
+}
+
+/// 兩邊都有、但標題或摘要不同的結果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChangedEntry {
+    pub previous_rank: usize,
+    pub rank: usize,
+    pub previous: SearchResult,
+    pub current: SearchResult,
+}
+
+/// 比較結果；各清單依所屬回應中的排名排序
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ResultDiff {
+    pub added: Vec<DiffEntry>,
+    pub removed: Vec<DiffEntry>,
+    pub changed: Vec<ChangedEntry>,
+    /// 兩邊相同（網址與內容都一樣）的結果數
+    pub unchanged: usize,
+}
+
+impl ResultDiff {
+    /// 沒有新增、消失或變動
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// 比較兩次搜尋回應
+pub fn diff(previous: &SearchResponse, current: &SearchResponse) -> ResultDiff {
+    diff_results(&previous.results, &current.results)
+}
+
+/// 比較兩組結果；同一回應中重複的網址只取排名最前的一筆
+pub fn diff_results(previous: &[SearchResult], current: &[SearchResult]) -> ResultDiff {
+    let before = index(previous);
+    let after = index(current);
+
+    let mut diff = ResultDiff::default();
+    for (key, &(rank, result)) in &after {
+        match before.get(key) {
+            None => diff.added.push(DiffEntry { rank, result: result.clone() }),
+            Some(&(previous_rank, previous)) if content_hash(previous) != content_hash(result) => {
+                diff.changed.push(ChangedEntry {
+                    previous_rank,
+                    rank,
+                    previous: previous.clone(),
+                    current: result.clone(),
+                })
+            }
+            Some(_) => diff.unchanged += 1,
+        }
+    }
+    for (key, &(rank, result)) in &before {
+        if !after.contains_key(key) {
+            diff.removed.push(DiffEntry { rank, result: result.clone() });
+        }
+    }
+    diff.added.sort_by_key(|e| e.rank);
+    diff.removed.sort_by_key(|e| e.rank);
+    diff.changed.sort_by_key(|e| e.rank);
+    diff
+}
+
+fn index(results: &[SearchResult]) -> HashMap<String, (usize, &SearchResult)> {
+    let mut index = HashMap::new();
+    for (i, result) in results.iter().enumerate() {
+        index.entry(dedup_key(&result.url)).or_insert((i + 1, result));
+    }
+    index
+}
+
+/// 標題與摘要（空白正規化後）的 FNV-1a 64 位元雜湊
+pub fn content_hash(result: &SearchResult) -> u64 {
+    let text = [result.title.as_str(), result.snippet.as_deref().unwrap_or("")]
+        .map(|part| part.split_whitespace().collect::<Vec<_>>().join(" "))
+        .join("\n");
+    crate::hash::fnv1a_64(text.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(url: &str, snippet: &str) -> SearchResult {
+        SearchResult {
+            title: "Title".into(),
+            url: url.into(),
+            snippet: Some(snippet.into()),
+            engine: "google".into(),
+            score: None,
+            category: "general".into(),
+            language: None,
+        }
+    }
+
+    #[test]
+    fn test_diff_results() {
+        let previous = vec![
+            result("https://a.com", "same"),
+            result("https://b.com/page", "old text"),
+            result("https://gone.com", "bye"),
+        ];
+        let current = vec![
+            result("https://new.com", "hello"),
+            result("https://A.com/?utm_source=feed#top", "same"),
+            result("https://b.com/page/", "new   text"),
+            // 重複網址只取第一筆
+            result("https://new.com/", "duplicate"),
+        ];
+        let diff = diff_results(&previous, &current);
+
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!((diff.added[0].rank, diff.added[0].result.url.as_str()), (1, "https://new.com"));
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!((diff.removed[0].rank, diff.removed[0].result.url.as_str()), (3, "https://gone.com"));
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!((diff.changed[0].previous_rank, diff.changed[0].rank), (2, 3));
+        assert_eq!(diff.changed[0].current.snippet.as_deref(), Some("new   text"));
+        assert_eq!(diff.unchanged, 1);
+        assert!(!diff.is_empty());
+    }
+
+    #[test]
+    fn test_content_hash_ignores_whitespace() {
+        assert_eq!(
+            content_hash(&result("https://a.com", "a  b\n c")),
+            content_hash(&result("https://other.com", "a b c"))
+        );
+        assert_ne!(
+            content_hash(&result("https://a.com", "a b")),
+            content_hash(&result("https://a.com", "a b c"))
+        );
+        assert!(diff_results(&[result("https://a.com", "x")], &[result("https://a.com", "x")]).is_empty());
+    }
+}
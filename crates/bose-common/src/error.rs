@@ -16,6 +16,25 @@ pub enum BoseError {
 
     #[error("查詢無效: {0}")]
     InvalidQuery(String),
+
+    #[error("快取後端失敗: {0}")]
+    CacheError(String),
+
+    #[error("瀏覽器渲染失敗: {0}")]
+    BrowserError(String),
+
+    #[error("I/O 失敗: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("分享檔無效: {0}")]
+    ShareError(String),
+
+    #[error("查詢翻譯失敗: {0}")]
+    TranslationError(String),
+
+    /// 代抓網頁時上游回應非 2xx 或無法解析網域
+    #[error("上游請求失敗: {0}")]
+    UpstreamError(String),
 }
 
 pub type BoseResult<T> = Result<T, BoseError>;
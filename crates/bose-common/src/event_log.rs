@@ -0,0 +1,341 @@
+//! 搜尋事件日誌 — 只附加的 JSONL，記錄每次搜尋供事後稽核
+//!
+//! 代理自主搜尋時，每一行記錄查詢、引擎、結果數、置信度、成本與延遲。
+//! 檔案超過 `max_bytes` 時輪替為 `events.jsonl.1`、`.2`…，最多保留 `max_files` 份舊檔。
+//! 寫入與輪替在 blocking 執行緒進行，不佔用 async 執行緒。
+
+use crate::{BoseConfig, BoseError, BoseResult, SearchQuery, SearchResponse};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 一次搜尋的事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchEvent {
+    pub timestamp: DateTime<Utc>,
+    /// 發出搜尋的後端，例如 `searxng`
+    pub source: String,
+    pub query: String,
+    /// 指定的引擎（空 = SearXNG 預設引擎）
+    pub engines: Vec<String>,
+    pub result_count: usize,
+    /// 各引擎貢獻的結果數
+    pub results_by_engine: BTreeMap<String, usize>,
+    pub cached: bool,
+    /// 結果置信度（0.0 ~ 1.0）；沒有結果時為 None
+    pub confidence: Option<f64>,
+    /// 本次搜尋的費用（美元）；自架 SearXNG 與快取命中為 0
+    pub cost_usd: f64,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+impl SearchEvent {
+    /// 成功的搜尋；置信度由回應估算，呼叫端可用 `with_confidence` 覆寫
+    pub fn success(
+        source: &str,
+        query: &SearchQuery,
+        response: &SearchResponse,
+        cached: bool,
+        latency: Duration,
+    ) -> Self {
+        let mut results_by_engine = BTreeMap::new();
+        for result in &response.results {
+            *results_by_engine.entry(result.engine.clone()).or_insert(0) += 1;
+        }
+        Self {
+            result_count: response.results.len(),
+            results_by_engine,
+            cached,
+            confidence: estimate_confidence(&query.query, response),
+            ..Self::base(source, query, latency)
+        }
+    }
+
+    /// 失敗的搜尋
+    pub fn failure(
+        source: &str,
+        query: &SearchQuery,
+        error: &BoseError,
+        latency: Duration,
+    ) -> Self {
+        Self {
+            error: Some(error.to_string()),
+            ..Self::base(source, query, latency)
+        }
+    }
+
+    /// 不經 `SearchQuery` 的搜尋（例如根目錄 CLI 直接呼叫 Exa / DuckDuckGo）
+    pub fn new(source: &str, query: &str, latency: Duration) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            source: source.to_string(),
+            query: query.to_string(),
+            engines: Vec::new(),
+            result_count: 0,
+            results_by_engine: BTreeMap::new(),
+            cached: false,
+            confidence: None,
+            cost_usd: 0.0,
+            latency_ms: latency.as_millis() as u64,
+            error: None,
+        }
+    }
+
+    fn base(source: &str, query: &SearchQuery, latency: Duration) -> Self {
+        Self {
+            engines: query.engines.clone(),
+            ..Self::new(source, &query.query, latency)
+        }
+    }
+
+    /// 記錄某個引擎回傳的結果數
+    pub fn with_results(mut self, engine: &str, count: usize, cached: bool) -> Self {
+        if !self.engines.iter().any(|e| e == engine) {
+            self.engines.push(engine.to_string());
+        }
+        *self.results_by_engine.entry(engine.to_string()).or_insert(0) += count;
+        self.result_count += count;
+        self.cached = cached;
+        self
+    }
+
+    pub fn with_error(mut self, error: impl std::fmt::Display) -> Self {
+        self.error = Some(error.to_string());
+        self
+    }
+
+    /// 以呼叫端自己的置信度模型覆寫估算值
+    pub fn with_confidence(mut self, confidence: f64) -> Self {
+        self.confidence = Some(confidence.clamp(0.0, 1.0));
+        self
+    }
+
+    pub fn with_cost(mut self, usd: f64) -> Self {
+        self.cost_usd = usd;
+        self
+    }
+}
+
+/// 前 5 筆的標題與摘要涵蓋多少查詢詞，與實際回應的引擎佔查詢引擎的比例，各佔一半
+fn estimate_confidence(query: &str, response: &SearchResponse) -> Option<f64> {
+    if response.results.is_empty() {
+        return None;
+    }
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    let top = &response.results[..response.results.len().min(5)];
+    let coverage = if terms.is_empty() {
+        1.0
+    } else {
+        top.iter()
+            .map(|result| {
+                let text = format!("{} {}", result.title, result.snippet.as_deref().unwrap_or_default())
+                    .to_lowercase();
+                terms.iter().filter(|term| text.contains(term.as_str())).count() as f64 / terms.len() as f64
+            })
+            .sum::<f64>()
+            / top.len() as f64
+    };
+    let responding: BTreeSet<&str> = response.results.iter().map(|r| r.engine.as_str()).collect();
+    let agreement = match response.engines_used.len() {
+        0 | 1 => 1.0,
+        used => (responding.len() as f64 / used as f64).min(1.0),
+    };
+    Some((coverage + agreement) / 2.0)
+}
+
+/// 帶輪替的 JSONL 事件日誌
+pub struct EventLog {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    file: Mutex<(File, u64)>,
+}
+
+impl EventLog {
+    pub fn open(path: impl Into<PathBuf>, max_bytes: u64, max_files: usize) -> BoseResult<Self> {
+        let path = path.into();
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = append(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path,
+            max_bytes,
+            max_files,
+            file: Mutex::new((file, size)),
+        })
+    }
+
+    /// 依 `EVENT_LOG_PATH` 等設定開啟；未設定路徑時為 None
+    pub fn from_config(config: &BoseConfig) -> BoseResult<Option<Self>> {
+        config
+            .event_log_path
+            .as_ref()
+            .map(|path| Self::open(path, config.event_log_max_bytes, config.event_log_max_files))
+            .transpose()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 在 blocking 執行緒附加一筆事件，供 async handler 呼叫
+    pub async fn append(self: &Arc<Self>, event: SearchEvent) -> BoseResult<()> {
+        let log = Arc::clone(self);
+        tokio::task::spawn_blocking(move || log.write(&event))
+            .await
+            .map_err(std::io::Error::other)?
+    }
+
+    /// 同步附加一筆事件；超過大小上限時先輪替
+    pub fn write(&self, event: &SearchEvent) -> BoseResult<()> {
+        let mut line = serde_json::to_vec(event)?;
+        line.push(b'\n');
+
+        let mut guard = self.file.lock().unwrap();
+        let (file, size) = &mut *guard;
+        if *size > 0 && *size + line.len() as u64 > self.max_bytes {
+            file.flush()?;
+            self.rotate()?;
+            *file = append(&self.path)?;
+            *size = 0;
+        }
+        file.write_all(&line)?;
+        *size += line.len() as u64;
+        Ok(())
+    }
+
+    /// `events.jsonl` → `.1`、`.1` → `.2`…，超過 `max_files` 的最舊檔刪除
+    fn rotate(&self) -> BoseResult<()> {
+        if self.max_files == 0 {
+            std::fs::remove_file(&self.path)?;
+            return Ok(());
+        }
+        let _ = std::fs::remove_file(self.rotated(self.max_files));
+        for n in (1..self.max_files).rev() {
+            let from = self.rotated(n);
+            if from.exists() {
+                std::fs::rename(&from, self.rotated(n + 1))?;
+            }
+        }
+        std::fs::rename(&self.path, self.rotated(1))?;
+        Ok(())
+    }
+
+    fn rotated(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{n}"));
+        PathBuf::from(name)
+    }
+}
+
+fn append(path: &Path) -> std::io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SearchResult;
+
+    fn response() -> SearchResponse {
+        let result = |engine: &str| SearchResult {
+            title: "Rust".into(),
+            url: "https://rust-lang.org".into(),
+            snippet: None,
+            engine: engine.into(),
+            score: None,
+            category: "general".into(),
+            language: None,
+        };
+        SearchResponse {
+            results: vec![result("google"), result("bing"), result("google")],
+            query: "rust".into(),
+            elapsed_seconds: 0.1,
+            total_results: None,
+            engines_used: vec!["google".into(), "bing".into()],
+            suggestions: Vec::new(),
+            corrected_from: None,
+            instant_answer: None,
+        }
+    }
+
+    #[test]
+    fn test_event_fields() {
+        let query = SearchQuery::new("rust").with_engines(vec!["google".into()]);
+        let event = SearchEvent::success(
+            "searxng",
+            &query,
+            &response(),
+            true,
+            Duration::from_millis(42),
+        );
+        assert_eq!(event.result_count, 3);
+        assert_eq!(event.results_by_engine["google"], 2);
+        assert_eq!(event.engines, vec!["google"]);
+        assert_eq!((event.cached, event.latency_ms), (true, 42));
+        // 三筆都含 "rust"，兩個查詢引擎都有回應
+        assert_eq!(event.confidence, Some(1.0));
+        assert_eq!(event.cost_usd, 0.0);
+
+        let failed = SearchEvent::failure(
+            "searxng",
+            &query,
+            &BoseError::SearxngError("HTTP 502".into()),
+            Duration::ZERO,
+        );
+        assert_eq!(failed.result_count, 0);
+        assert_eq!(failed.confidence, None);
+        assert!(failed.error.unwrap().contains("502"));
+
+        let direct = SearchEvent::new("exa", "rust", Duration::ZERO)
+            .with_results("exa", 4, false)
+            .with_confidence(1.7)
+            .with_cost(0.005);
+        assert_eq!((direct.result_count, direct.results_by_engine["exa"]), (4, 4));
+        assert_eq!(direct.engines, vec!["exa"]);
+        assert_eq!((direct.confidence, direct.cost_usd), (Some(1.0), 0.005));
+    }
+
+    #[tokio::test]
+    async fn test_append_and_rotate() {
+        let dir = std::env::temp_dir().join(format!("bose-events-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("events.jsonl");
+        let event = SearchEvent::success(
+            "searxng",
+            &SearchQuery::new("rust"),
+            &response(),
+            false,
+            Duration::ZERO,
+        );
+        let line_len = serde_json::to_vec(&event).unwrap().len() as u64 + 1;
+
+        // 每個檔案放得下兩行；寫五行後保留目前檔加兩份舊檔
+        let log = Arc::new(EventLog::open(&path, line_len * 2, 2).unwrap());
+        for _ in 0..5 {
+            log.append(event.clone()).await.unwrap();
+        }
+        let lines = |p: &Path| {
+            std::fs::read_to_string(p)
+                .map(|s| s.lines().count())
+                .unwrap_or(0)
+        };
+        assert_eq!(lines(&path), 1);
+        assert_eq!(lines(&log.rotated(1)), 2);
+        assert_eq!(lines(&log.rotated(2)), 2);
+        assert!(!log.rotated(3).exists());
+
+        let first = std::fs::read_to_string(&path).unwrap();
+        let parsed: SearchEvent = serde_json::from_str(first.lines().next().unwrap()).unwrap();
+        assert_eq!(parsed, event);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
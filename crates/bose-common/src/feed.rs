@@ -0,0 +1,199 @@
+//! 訂閱源輸出 — 將查詢的最新結果渲染為 RSS 2.0 / Atom，讓一般閱讀器可以訂閱搜尋
+
+use crate::types::SearchResponse;
+use chrono::{DateTime, Utc};
+use std::fmt::Write;
+
+/// 訂閱源格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FeedFormat {
+    #[default]
+    Rss,
+    Atom,
+}
+
+impl FeedFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "application/rss+xml; charset=utf-8",
+            FeedFormat::Atom => "application/atom+xml; charset=utf-8",
+        }
+    }
+
+    /// 渲染搜尋回應；`link` 為訂閱源自身的網址
+    pub fn render(self, resp: &SearchResponse, link: &str, updated: DateTime<Utc>) -> String {
+        match self {
+            FeedFormat::Rss => to_rss(resp, link, updated),
+            FeedFormat::Atom => to_atom(resp, link, updated),
+        }
+    }
+}
+
+impl std::str::FromStr for FeedFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "rss" => Ok(FeedFormat::Rss),
+            "atom" => Ok(FeedFormat::Atom),
+            other => Err(format!("unknown feed format: {other}")),
+        }
+    }
+}
+
+/// 渲染為 RSS 2.0
+pub fn to_rss(resp: &SearchResponse, link: &str, updated: DateTime<Utc>) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<rss version=\"2.0\">\n<channel>\n");
+    writeln!(out, "  <title>Bose Search: {}</title>", escape(&resp.query)).unwrap();
+    writeln!(out, "  <link>{}</link>", escape(link)).unwrap();
+    writeln!(
+        out,
+        "  <description>Latest results for \"{}\"</description>",
+        escape(&resp.query)
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "  <lastBuildDate>{}</lastBuildDate>",
+        updated.to_rfc2822()
+    )
+    .unwrap();
+
+    for r in &resp.results {
+        out.push_str("  <item>\n");
+        writeln!(out, "    <title>{}</title>", escape(&r.title)).unwrap();
+        writeln!(out, "    <link>{}</link>", escape(&r.url)).unwrap();
+        writeln!(
+            out,
+            "    <guid isPermaLink=\"true\">{}</guid>",
+            escape(&r.url)
+        )
+        .unwrap();
+        if let Some(ref s) = r.snippet {
+            writeln!(out, "    <description>{}</description>", escape(s)).unwrap();
+        }
+        writeln!(out, "    <category>{}</category>", escape(&r.category)).unwrap();
+        out.push_str("  </item>\n");
+    }
+
+    out.push_str("</channel>\n</rss>\n");
+    out
+}
+
+/// 渲染為 Atom 1.0
+pub fn to_atom(resp: &SearchResponse, link: &str, updated: DateTime<Utc>) -> String {
+    let updated = updated.to_rfc3339();
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    writeln!(out, "  <title>Bose Search: {}</title>", escape(&resp.query)).unwrap();
+    writeln!(out, "  <id>{}</id>", escape(link)).unwrap();
+    writeln!(out, "  <link rel=\"self\" href=\"{}\"/>", escape(link)).unwrap();
+    writeln!(out, "  <updated>{updated}</updated>").unwrap();
+    out.push_str("  <author><name>bose-search</name></author>\n");
+
+    for r in &resp.results {
+        out.push_str("  <entry>\n");
+        writeln!(out, "    <title>{}</title>", escape(&r.title)).unwrap();
+        writeln!(out, "    <id>{}</id>", escape(&r.url)).unwrap();
+        writeln!(out, "    <link href=\"{}\"/>", escape(&r.url)).unwrap();
+        writeln!(out, "    <updated>{updated}</updated>").unwrap();
+        if let Some(ref s) = r.snippet {
+            writeln!(out, "    <summary>{}</summary>", escape(s)).unwrap();
+        }
+        writeln!(out, "    <category term=\"{}\"/>", escape(&r.category)).unwrap();
+        out.push_str("  </entry>\n");
+    }
+
+    out.push_str("</feed>\n");
+    out
+}
+
+/// XML 字元跳脫
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            // XML 1.0 不允許的控制字元
+            c if (c as u32) < 0x20 && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SearchResult;
+    use chrono::TimeZone;
+
+    fn sample() -> SearchResponse {
+        SearchResponse {
+            results: vec![
+                SearchResult {
+                    title: "Rust & Cargo".into(),
+                    url: "https://rust-lang.org/?a=1&b=2".into(),
+                    snippet: Some("<Fast> systems language".into()),
+                    engine: "google".into(),
+                    score: Some(1.0),
+                    category: "it".into(),
+                    language: None,
+                },
+                SearchResult {
+                    title: "No snippet".into(),
+                    url: "https://example.com".into(),
+                    snippet: None,
+                    engine: "brave".into(),
+                    score: None,
+                    category: "general".into(),
+                    language: None,
+                },
+            ],
+            query: "rust \"lang\"".into(),
+            elapsed_seconds: 0.5,
+            total_results: None,
+            engines_used: vec!["google".into(), "brave".into()],
+            suggestions: Vec::new(),
+            corrected_from: None,
+            instant_answer: None,
+        }
+    }
+
+    fn updated() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 2, 11, 8, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_rss_snapshot() {
+        let xml = to_rss(&sample(), "http://localhost:3000/feed?q=rust", updated());
+        insta::assert_snapshot!(xml);
+    }
+
+    #[test]
+    fn test_atom_snapshot() {
+        let xml = to_atom(&sample(), "http://localhost:3000/feed?q=rust", updated());
+        insta::assert_snapshot!(xml);
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("a<b>&\"'"), "a&lt;b&gt;&amp;&quot;&apos;");
+        assert_eq!(escape("bell\u{7}"), "bell");
+    }
+
+    #[test]
+    fn test_feed_format_parse() {
+        assert_eq!("ATOM".parse::<FeedFormat>(), Ok(FeedFormat::Atom));
+        assert_eq!("rss".parse::<FeedFormat>(), Ok(FeedFormat::Rss));
+        assert!("json".parse::<FeedFormat>().is_err());
+        assert_eq!(FeedFormat::default(), FeedFormat::Rss);
+    }
+}
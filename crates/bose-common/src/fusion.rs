@@ -0,0 +1,88 @@
+//! 結果融合 — Reciprocal Rank Fusion (RRF)
+
+use crate::types::SearchResult;
+use crate::url::dedup_key;
+use std::collections::HashMap;
+
+/// RRF 常數 k（原論文建議值）
+pub const RRF_K: f64 = 60.0;
+
+/// 以 RRF 融合多個排名列表
+///
+/// 每個結果得分為 `Σ 1 / (k + rank)`（rank 從 1 起算），依 `dedup_key` 去重並保留第一次出現的版本，
+/// 融合分數寫入 `score`。同分時維持首次出現的順序。
+pub fn rrf_merge(lists: &[Vec<SearchResult>], k: f64) -> Vec<SearchResult> {
+    let mut fused: Vec<(SearchResult, f64)> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for list in lists {
+        for (rank, result) in list.iter().enumerate() {
+            let contribution = 1.0 / (k + rank as f64 + 1.0);
+            let key = dedup_key(&result.url);
+            match index.get(&key) {
+                Some(&i) => fused[i].1 += contribution,
+                None => {
+                    index.insert(key, fused.len());
+                    fused.push((result.clone(), contribution));
+                }
+            }
+        }
+    }
+
+    // sort_by 為穩定排序，同分保留原順序
+    fused.sort_by(|a, b| b.1.total_cmp(&a.1));
+    fused
+        .into_iter()
+        .map(|(mut result, score)| {
+            result.score = Some(score);
+            result
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(url: &str, engine: &str) -> SearchResult {
+        SearchResult {
+            title: url.into(),
+            url: url.into(),
+            snippet: None,
+            engine: engine.into(),
+            score: None,
+            category: "general".into(),
+            language: None,
+        }
+    }
+
+    #[test]
+    fn test_rrf_empty() {
+        assert!(rrf_merge(&[], RRF_K).is_empty());
+    }
+
+    #[test]
+    fn test_rrf_shared_url_ranks_first() {
+        let a = vec![
+            result("https://a.com", "google"),
+            result("https://b.com", "google"),
+        ];
+        let b = vec![
+            result("https://c.com", "brave"),
+            result("https://b.com", "brave"),
+        ];
+        let merged = rrf_merge(&[a, b], RRF_K);
+
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[0].url, "https://b.com");
+        assert_eq!(merged[0].engine, "google");
+        assert_eq!(merged[1].url, "https://a.com");
+        assert_eq!(merged[2].url, "https://c.com");
+    }
+
+    #[test]
+    fn test_rrf_score_values() {
+        let merged = rrf_merge(&[vec![result("https://a.com", "google")]], RRF_K);
+        assert_eq!(merged[0].score, Some(1.0 / 61.0));
+    }
+}
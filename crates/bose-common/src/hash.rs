@@ -0,0 +1,42 @@
+//! 穩定雜湊 — FNV-1a，跨行程、跨版本結果不變
+//!
+//! `std::hash::DefaultHasher` 的演算法不保證穩定，不能用在會落地的鍵值
+//! （快取檔名、分塊 id、證據目錄）；這些地方一律改用這裡的函式。
+
+const OFFSET_64: u64 = 0xcbf29ce484222325;
+const PRIME_64: u64 = 0x100000001b3;
+const OFFSET_128: u128 = 0x6c62272e07bb014262b821756295c58d;
+const PRIME_128: u128 = 0x0000000001000000000000000000013b;
+
+/// FNV-1a 64 位元雜湊
+pub fn fnv1a_64(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(OFFSET_64, |hash, byte| (hash ^ u64::from(*byte)).wrapping_mul(PRIME_64))
+}
+
+/// FNV-1a 128 位元雜湊（快取鍵等需要更低碰撞率的地方）
+pub fn fnv1a_128(bytes: &[u8]) -> u128 {
+    bytes
+        .iter()
+        .fold(OFFSET_128, |hash, byte| (hash ^ u128::from(*byte)).wrapping_mul(PRIME_128))
+}
+
+/// 16 位十六進位的 64 位元雜湊，適合當檔名或 id
+pub fn fnv1a_hex(text: &str) -> String {
+    format!("{:016x}", fnv1a_64(text.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_vectors() {
+        assert_eq!(fnv1a_64(b""), OFFSET_64);
+        assert_eq!(fnv1a_64(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a_64(b"foobar"), 0x85944171f73967e8);
+        assert_eq!(fnv1a_128(b"a"), 0xd228cb696f1a8caf78912b704e4a8964);
+        assert_eq!(fnv1a_hex("a"), "af63dc4c8601ec8c");
+    }
+}
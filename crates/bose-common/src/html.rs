@@ -0,0 +1,171 @@
+//! HTML 實體解碼 — 根目錄的 `HtmlCleaner` 與 bose-http 的結構化資料提取共用
+//!
+//! 涵蓋常見具名實體與十進位、十六進位數字參照；無法辨識的實體原樣保留。
+
+/// 解碼 HTML 實體（`&amp;`、`&nbsp;`、`&#8217;`、`&#x2019;`）
+///
+/// 呼叫端負責在移除標籤之後才解碼，否則 `&lt;script&gt;` 會被當成標籤。
+pub fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(amp) = rest.find('&') {
+        result.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        // 實體名稱最長約 32 字元；找不到分號就不是實體
+        let decoded = rest[1..]
+            .char_indices()
+            .take(32)
+            .find(|&(_, c)| c == ';')
+            .and_then(|(end, _)| Some((decode_entity(&rest[1..1 + end])?, end + 2)));
+        match decoded {
+            Some((ch, len)) => {
+                if let Some(ch) = ch {
+                    result.push(ch);
+                }
+                rest = &rest[len..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// 解碼單一實體（不含 `&` 與 `;`）；外層 None 表示無法辨識，內層 None 表示解碼為空字串
+fn decode_entity(name: &str) -> Option<Option<char>> {
+    if let Some(num) = name.strip_prefix('#') {
+        let code = match num.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => num.parse::<u32>().ok()?,
+        };
+        return Some(Some(numeric_char(code)));
+    }
+    let ch = match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        "ensp" => '\u{2002}',
+        "emsp" => '\u{2003}',
+        "thinsp" => '\u{2009}',
+        "shy" | "zwj" | "zwnj" => return Some(None),
+        "ndash" => '–',
+        "mdash" => '—',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "sbquo" => '‚',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "bdquo" => '„',
+        "laquo" => '«',
+        "raquo" => '»',
+        "hellip" => '…',
+        "bull" => '•',
+        "middot" => '·',
+        "prime" => '′',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "deg" => '°',
+        "plusmn" => '±',
+        "times" => '×',
+        "divide" => '÷',
+        "minus" => '−',
+        "frac12" => '½',
+        "frac14" => '¼',
+        "frac34" => '¾',
+        "sect" => '§',
+        "para" => '¶',
+        "euro" => '€',
+        "pound" => '£',
+        "yen" => '¥',
+        "cent" => '¢',
+        "larr" => '←',
+        "rarr" => '→',
+        "uarr" => '↑',
+        "darr" => '↓',
+        "harr" => '↔',
+        "le" => '≤',
+        "ge" => '≥',
+        "ne" => '≠',
+        "asymp" => '≈',
+        "infin" => '∞',
+        "iexcl" => '¡',
+        "iquest" => '¿',
+        "szlig" => 'ß',
+        "aacute" => 'á',
+        "agrave" => 'à',
+        "acirc" => 'â',
+        "auml" => 'ä',
+        "atilde" => 'ã',
+        "aring" => 'å',
+        "ccedil" => 'ç',
+        "eacute" => 'é',
+        "egrave" => 'è',
+        "ecirc" => 'ê',
+        "euml" => 'ë',
+        "iacute" => 'í',
+        "icirc" => 'î',
+        "iuml" => 'ï',
+        "ntilde" => 'ñ',
+        "oacute" => 'ó',
+        "ograve" => 'ò',
+        "ocirc" => 'ô',
+        "ouml" => 'ö',
+        "otilde" => 'õ',
+        "oslash" => 'ø',
+        "uacute" => 'ú',
+        "ugrave" => 'ù',
+        "ucirc" => 'û',
+        "uuml" => 'ü',
+        "Auml" => 'Ä',
+        "Eacute" => 'É',
+        "Ouml" => 'Ö',
+        "Uuml" => 'Ü',
+        _ => return None,
+    };
+    Some(Some(ch))
+}
+
+/// 數字參照轉字元；0x80–0x9F 依 HTML5 規範視為 Windows-1252（`&#146;` → ’），
+/// 無效碼位換成 U+FFFD
+fn numeric_char(code: u32) -> char {
+    const WINDOWS_1252: [char; 32] = [
+        '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8d}', 'Ž',
+        '\u{8f}', '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ',
+        '\u{9d}', 'ž', 'Ÿ',
+    ];
+    match code {
+        0 => char::REPLACEMENT_CHARACTER,
+        0x80..=0x9F => WINDOWS_1252[(code - 0x80) as usize],
+        _ => char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(
+            decode_entities("Tom &amp; Jerry&#8217;s &#x201C;show&#x201d; &mdash; 5&nbsp;min"),
+            "Tom & Jerry’s “show” — 5\u{a0}min"
+        );
+        assert_eq!(decode_entities("it&#146;s co&shy;operate"), "it’s cooperate");
+        // 無法辨識或不完整的實體原樣保留
+        assert_eq!(
+            decode_entities("AT&T &unknown; a & b &#xZZ; &#0;"),
+            "AT&T &unknown; a & b &#xZZ; \u{fffd}"
+        );
+    }
+}
@@ -0,0 +1,158 @@
+//! 查詢意圖 — 導航、資訊、程式碼、新聞
+//!
+//! 與複雜度（`RouterSettings::is_simple`）是不同的維度：意圖決定查詢送往哪些引擎，
+//! 例如導航查詢只需要單一引擎，程式碼查詢改送 GitHub / Stack Exchange。
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// 查詢意圖
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryIntent {
+    /// 想前往特定網站：網址、網域、「官網」、「login」
+    Navigational,
+    /// 一般資訊查詢
+    Informational,
+    /// 程式碼、錯誤訊息、API 用法
+    Code,
+    /// 時事、即時資訊
+    News,
+}
+
+impl QueryIntent {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Navigational => "navigational",
+            Self::Informational => "informational",
+            Self::Code => "code",
+            Self::News => "news",
+        }
+    }
+}
+
+impl fmt::Display for QueryIntent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+const NAVIGATIONAL_KEYWORDS: &[&str] = &[
+    "login", "log in", "sign in", "homepage", "home page", "official site", "official website",
+    "官網", "官方網站", "登入", "首頁",
+];
+
+const NEWS_KEYWORDS: &[&str] = &[
+    "news", "latest", "today", "yesterday", "breaking", "this week", "announced", "headlines",
+    "新聞", "最新", "今天", "今日", "昨天", "本週", "即時", "快訊",
+];
+
+const CODE_KEYWORDS: &[&str] = &[
+    "error", "exception", "stack trace", "traceback", "segfault", "panic", "compile", "compiler",
+    "undefined reference", "npm", "cargo", "pip", "regex", "api", "function", "struct", "crate",
+    "rust", "python", "javascript", "typescript", "golang", "java", "c++", "kotlin", "sql",
+    "編譯", "報錯", "錯誤訊息", "程式碼", "函式",
+];
+
+/// 常見的頂級網域，用於辨識沒有 `http://` 的網域查詢
+const COMMON_TLDS: &[&str] = &[
+    "com", "org", "net", "io", "dev", "gov", "edu", "app", "ai", "co", "tw", "jp", "cn", "uk", "de",
+];
+
+/// 依關鍵字與字面特徵判斷意圖；導航 > 新聞 > 程式碼 > 資訊
+pub fn detect_intent(query: &str) -> QueryIntent {
+    let trimmed = query.trim();
+    let lower = trimmed.to_lowercase();
+
+    if looks_like_url(&lower) || contains_phrase(&lower, NAVIGATIONAL_KEYWORDS) {
+        return QueryIntent::Navigational;
+    }
+    if contains_phrase(&lower, NEWS_KEYWORDS) {
+        return QueryIntent::News;
+    }
+    if looks_like_code(trimmed) || contains_phrase(&lower, CODE_KEYWORDS) {
+        return QueryIntent::Code;
+    }
+    QueryIntent::Informational
+}
+
+/// 單一詞且是網址或 `名稱.頂級網域`
+fn looks_like_url(lower: &str) -> bool {
+    if lower.contains(char::is_whitespace) {
+        return false;
+    }
+    if lower.starts_with("http://") || lower.starts_with("https://") || lower.starts_with("www.") {
+        return true;
+    }
+    let host = lower.split('/').next().unwrap_or(lower);
+    match host.rsplit_once('.') {
+        Some((name, tld)) => !name.is_empty() && COMMON_TLDS.contains(&tld),
+        None => false,
+    }
+}
+
+/// 路徑分隔、呼叫語法、反引號，或 snake_case / camelCase 識別字
+fn looks_like_code(query: &str) -> bool {
+    if ["::", "()", "=>", "->", "`", "#include", "</"].iter().any(|m| query.contains(m)) {
+        return true;
+    }
+    query.split_whitespace().any(|word| {
+        let word = word.trim_matches(|c: char| !c.is_alphanumeric() && c != '_');
+        let snake = word.contains('_') && word.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        let camel = word.chars().next().is_some_and(|c| c.is_ascii_lowercase())
+            && word.chars().skip(1).any(|c| c.is_ascii_uppercase())
+            && word.chars().all(|c| c.is_ascii_alphanumeric());
+        // 例外型別：TypeError、NullPointerException
+        let exception = ["Error", "Exception"]
+            .iter()
+            .any(|suffix| word.len() > suffix.len() && word.ends_with(suffix));
+        snake || camel || exception
+    })
+}
+
+/// 英文關鍵字需落在詞界上，避免 "api" 命中 "rapid"；中文關鍵字直接比對
+fn contains_phrase(lower: &str, keywords: &[&str]) -> bool {
+    keywords.iter().any(|kw| {
+        if !kw.is_ascii() {
+            return lower.contains(kw);
+        }
+        lower.match_indices(kw).any(|(i, _)| {
+            let before = lower[..i].chars().next_back();
+            let after = lower[i + kw.len()..].chars().next();
+            !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_intent() {
+        let cases = [
+            ("github.com", QueryIntent::Navigational),
+            ("https://docs.rs/tokio", QueryIntent::Navigational),
+            ("chase bank login", QueryIntent::Navigational),
+            ("台積電 官網", QueryIntent::Navigational),
+            ("latest rust release news", QueryIntent::News),
+            ("台積電 今天 股價", QueryIntent::News),
+            ("std::fs::read_to_string example", QueryIntent::Code),
+            ("TypeError: cannot read property of undefined", QueryIntent::Code),
+            ("serde_json from_str", QueryIntent::Code),
+            ("useEffect cleanup", QueryIntent::Code),
+            ("rapid prototyping tips", QueryIntent::Informational),
+            ("capital of australia", QueryIntent::Informational),
+            ("version 1.2 changes", QueryIntent::Informational),
+        ];
+        for (query, expected) in cases {
+            assert_eq!(detect_intent(query), expected, "{query}");
+        }
+    }
+
+    #[test]
+    fn test_intent_serde() {
+        assert_eq!(serde_json::to_string(&QueryIntent::Navigational).unwrap(), "\"navigational\"");
+        assert_eq!(QueryIntent::Code.to_string(), "code");
+    }
+}
@@ -0,0 +1,403 @@
+//! 語言過濾 — 依文字書寫系統剔除不符合請求語言的結果
+//!
+//! SearXNG 的 `language` 參數常被個別引擎忽略，因此在回應後再以書寫系統過濾一次。
+//! 查詢中的專有名詞（例如產品名）若出現在結果中，即使書寫系統不同也保留，
+//! 避免以中日文標題介紹英文產品的頁面被誤刪。比對前會做全形 / 變音符號 / 西里爾字母轉寫正規化。
+//!
+//! 拉丁、西里爾、阿拉伯字母由多種語言共用，書寫系統相符時再以 whatlang 偵測語言比對
+//! （例如請求 `en` 時剔除德文頁面）；偵測結果不可靠時一律保留。
+
+use crate::types::SearchResult;
+
+/// 書寫系統
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Script {
+    Latin,
+    Han,
+    Kana,
+    Hangul,
+    Cyrillic,
+    Arabic,
+}
+
+impl Script {
+    fn of(ch: char) -> Option<Self> {
+        match ch as u32 {
+            0x41..=0x5A | 0x61..=0x7A | 0xC0..=0x24F | 0xFF21..=0xFF3A | 0xFF41..=0xFF5A => {
+                Some(Script::Latin)
+            }
+            0x3040..=0x30FF | 0x31F0..=0x31FF | 0xFF66..=0xFF9F => Some(Script::Kana),
+            0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF => Some(Script::Han),
+            0xAC00..=0xD7AF | 0x1100..=0x11FF | 0x3130..=0x318F => Some(Script::Hangul),
+            0x400..=0x4FF => Some(Script::Cyrillic),
+            0x600..=0x6FF | 0x750..=0x77F => Some(Script::Arabic),
+            _ => None,
+        }
+    }
+}
+
+/// 語言代碼（`en`、`zh-TW`、`ja` ...）對應可接受的書寫系統；未知或 `all` 回傳 None
+pub fn scripts_for_language(code: &str) -> Option<&'static [Script]> {
+    let primary = code.split(['-', '_']).next()?.to_ascii_lowercase();
+    let scripts: &'static [Script] = match primary.as_str() {
+        "zh" => &[Script::Han],
+        "ja" => &[Script::Kana, Script::Han],
+        "ko" => &[Script::Hangul],
+        "ru" | "uk" | "bg" | "sr" | "be" | "kk" => &[Script::Cyrillic],
+        "ar" | "fa" | "ur" => &[Script::Arabic],
+        "en" | "de" | "fr" | "es" | "it" | "pt" | "nl" | "sv" | "no" | "da" | "fi" | "pl"
+        | "cs" | "tr" | "vi" | "id" | "ro" | "hu" => &[Script::Latin],
+        _ => return None,
+    };
+    Some(scripts)
+}
+
+/// 判斷文字的主要書寫系統
+///
+/// 只要出現假名即視為日文（日文標題常以漢字為主）；其餘取字元數最多者。
+pub fn detect_script(text: &str) -> Option<Script> {
+    let mut counts = [0usize; 6];
+    for ch in text.chars() {
+        if let Some(script) = Script::of(ch) {
+            counts[script as usize] += 1;
+        }
+    }
+
+    if counts[Script::Kana as usize] > 0 {
+        return Some(Script::Kana);
+    }
+
+    const ORDER: [Script; 6] = [
+        Script::Latin,
+        Script::Han,
+        Script::Kana,
+        Script::Hangul,
+        Script::Cyrillic,
+        Script::Arabic,
+    ];
+    ORDER
+        .into_iter()
+        .filter(|s| counts[*s as usize] > 0)
+        .max_by_key(|s| counts[*s as usize])
+}
+
+/// 偵測文字語言，回傳 ISO 639-1 代碼；文字太短、判斷不可靠或語言不在支援清單時回傳 None
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    let info = whatlang::detect(text)?;
+    if !info.is_reliable() {
+        return None;
+    }
+    iso639_1(info.lang())
+}
+
+/// 為尚未標記語言的結果填入偵測到的語言（依標題與摘要）
+pub fn tag_languages(results: &mut [SearchResult]) {
+    for result in results.iter_mut().filter(|r| r.language.is_none()) {
+        let text = result_text(result);
+        result.language = detect_language(&text).map(str::to_string);
+    }
+}
+
+fn iso639_1(lang: whatlang::Lang) -> Option<&'static str> {
+    use whatlang::Lang;
+    let code = match lang {
+        Lang::Eng => "en",
+        Lang::Deu => "de",
+        Lang::Fra => "fr",
+        Lang::Spa => "es",
+        Lang::Ita => "it",
+        Lang::Por => "pt",
+        Lang::Nld => "nl",
+        Lang::Swe => "sv",
+        Lang::Nob => "no",
+        Lang::Dan => "da",
+        Lang::Fin => "fi",
+        Lang::Pol => "pl",
+        Lang::Ces => "cs",
+        Lang::Tur => "tr",
+        Lang::Vie => "vi",
+        Lang::Ind => "id",
+        Lang::Ron => "ro",
+        Lang::Hun => "hu",
+        Lang::Rus => "ru",
+        Lang::Ukr => "uk",
+        Lang::Bul => "bg",
+        Lang::Srp => "sr",
+        Lang::Bel => "be",
+        Lang::Ara => "ar",
+        Lang::Pes => "fa",
+        Lang::Urd => "ur",
+        Lang::Cmn => "zh",
+        Lang::Jpn => "ja",
+        Lang::Kor => "ko",
+        _ => return None,
+    };
+    Some(code)
+}
+
+/// 多種語言共用的書寫系統，需要再比對語言
+fn is_shared_script(script: Script) -> bool {
+    matches!(script, Script::Latin | Script::Cyrillic | Script::Arabic)
+}
+
+fn result_text(result: &SearchResult) -> String {
+    format!(
+        "{} {}",
+        result.title,
+        result.snippet.as_deref().unwrap_or("")
+    )
+}
+
+/// 轉寫正規化：全形轉半形、去除變音符號、西里爾字母轉拉丁字母，並轉小寫
+pub fn fold_for_matching(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        let ch = match ch as u32 {
+            0xFF01..=0xFF5E => char::from_u32(ch as u32 - 0xFEE0).unwrap_or(ch),
+            _ => ch,
+        };
+        match fold_char(ch) {
+            Some(s) => out.push_str(s),
+            None => out.extend(ch.to_lowercase()),
+        }
+    }
+    out
+}
+
+fn fold_char(ch: char) -> Option<&'static str> {
+    let s = match ch.to_lowercase().next()? {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'а' => "a",
+        'ç' | 'ц' => "c",
+        'è' | 'é' | 'ê' | 'ë' | 'е' | 'э' => "e",
+        'ì' | 'í' | 'î' | 'ï' | 'и' | 'й' => "i",
+        'ñ' | 'н' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'о' => "o",
+        'ù' | 'ú' | 'û' | 'ü' | 'у' => "u",
+        'ý' | 'ÿ' | 'ы' => "y",
+        'ß' => "ss",
+        'б' => "b",
+        'в' => "v",
+        'г' => "g",
+        'д' => "d",
+        'ё' => "yo",
+        'ж' => "zh",
+        'з' => "z",
+        'к' => "k",
+        'л' => "l",
+        'м' => "m",
+        'п' => "p",
+        'р' => "r",
+        'с' => "s",
+        'т' => "t",
+        'ф' => "f",
+        'х' => "kh",
+        'ч' => "ch",
+        'ш' => "sh",
+        'щ' => "shch",
+        'ю' => "yu",
+        'я' => "ya",
+        'ъ' | 'ь' => "",
+        _ => return None,
+    };
+    Some(s)
+}
+
+/// 從查詢中挑出可能是專有名詞的詞（大寫開頭、含數字的型號、或非查詢主要書寫系統的詞）
+fn proper_nouns(query: &str) -> Vec<String> {
+    let query_script = detect_script(query);
+    query
+        .split(|c: char| c.is_whitespace() || matches!(c, ',' | '，' | '、' | '?' | '？'))
+        .filter(|w| w.chars().count() >= 2)
+        .filter(|w| {
+            let first_upper = w.chars().next().is_some_and(char::is_uppercase);
+            let has_digit = w.chars().any(|c| c.is_ascii_digit());
+            let foreign = detect_script(w).is_some_and(|s| Some(s) != query_script);
+            first_upper || has_digit || foreign
+        })
+        .map(fold_for_matching)
+        .collect()
+}
+
+/// 語言後置過濾器
+#[derive(Debug, Clone)]
+pub struct LanguageFilter {
+    allowed: Vec<Script>,
+    /// 請求語言的主要代碼（`zh-TW` → `zh`）
+    languages: Vec<String>,
+    proper_nouns: Vec<String>,
+}
+
+impl LanguageFilter {
+    /// 由請求的語言代碼建立過濾器；所有代碼都無法辨識時回傳 None（不過濾）
+    pub fn new(languages: &[&str], query: &str) -> Option<Self> {
+        let mut allowed = Vec::new();
+        let mut codes = Vec::new();
+        for lang in languages {
+            allowed.extend_from_slice(scripts_for_language(lang)?);
+            if let Some(primary) = lang.split(['-', '_']).next() {
+                codes.push(primary.to_ascii_lowercase());
+            }
+        }
+        if allowed.is_empty() {
+            return None;
+        }
+        Some(Self {
+            allowed,
+            languages: codes,
+            proper_nouns: proper_nouns(query),
+        })
+    }
+
+    /// 結果是否保留
+    pub fn keep(&self, result: &SearchResult) -> bool {
+        let text = result_text(result);
+        let Some(script) = detect_script(&text) else {
+            return true;
+        };
+        if self.allowed.contains(&script) {
+            if !is_shared_script(script) {
+                return true;
+            }
+            let detected = match &result.language {
+                Some(lang) => Some(lang.as_str()),
+                None => detect_language(&text),
+            };
+            match detected {
+                Some(lang) if !self.languages.iter().any(|l| l == lang) => {}
+                _ => return true,
+            }
+        }
+        let folded = fold_for_matching(&text);
+        self.proper_nouns
+            .iter()
+            .any(|noun| folded.contains(noun.as_str()))
+    }
+
+    /// 套用過濾，回傳保留的結果與剔除數量
+    pub fn apply(&self, results: Vec<SearchResult>) -> (Vec<SearchResult>, usize) {
+        let before = results.len();
+        let kept: Vec<SearchResult> = results.into_iter().filter(|r| self.keep(r)).collect();
+        let dropped = before - kept.len();
+        (kept, dropped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(title: &str, snippet: &str) -> SearchResult {
+        SearchResult {
+            title: title.into(),
+            url: "https://example.com".into(),
+            snippet: Some(snippet.into()),
+            engine: "google".into(),
+            score: None,
+            category: "general".into(),
+            language: None,
+        }
+    }
+
+    #[test]
+    fn test_detect_script() {
+        assert_eq!(detect_script("Rust programming"), Some(Script::Latin));
+        assert_eq!(detect_script("繁體中文內容"), Some(Script::Han));
+        assert_eq!(detect_script("東京のラーメン"), Some(Script::Kana));
+        assert_eq!(detect_script("한국어"), Some(Script::Hangul));
+        assert_eq!(detect_script("Привет мир"), Some(Script::Cyrillic));
+        assert_eq!(detect_script("12345 !!"), None);
+    }
+
+    #[test]
+    fn test_scripts_for_language() {
+        assert_eq!(scripts_for_language("zh-TW"), Some(&[Script::Han][..]));
+        assert_eq!(scripts_for_language("en"), Some(&[Script::Latin][..]));
+        assert!(scripts_for_language("all").is_none());
+    }
+
+    #[test]
+    fn test_fold_for_matching() {
+        assert_eq!(fold_for_matching("Ｂｏｓｅ"), "bose");
+        assert_eq!(fold_for_matching("Café"), "cafe");
+        assert_eq!(fold_for_matching("Москва"), "moskva");
+    }
+
+    #[test]
+    fn test_filter_drops_other_scripts() {
+        let filter = LanguageFilter::new(&["en"], "bluetooth headphones").unwrap();
+        let (kept, dropped) = filter.apply(vec![
+            result("Best bluetooth headphones", "Review"),
+            result("藍牙耳機推薦", "評測"),
+        ]);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(dropped, 1);
+    }
+
+    #[test]
+    fn test_filter_keeps_proper_noun_matches() {
+        let filter = LanguageFilter::new(&["en"], "Bose QuietComfort review").unwrap();
+        assert!(filter.keep(&result("Ｂｏｓｅ 耳機評測", "降噪表現")));
+        assert!(filter.keep(&result("QuietComfort 評測", "降噪")));
+        assert!(!filter.keep(&result("耳機評測", "降噪表現")));
+    }
+
+    #[test]
+    fn test_filter_foreign_term_in_cjk_query() {
+        let filter = LanguageFilter::new(&["zh-TW"], "Rust 非同步 教學").unwrap();
+        assert!(filter.keep(&result("Async Rust book", "tokio")));
+        assert!(!filter.keep(&result("Python asyncio guide", "event loop")));
+    }
+
+    #[test]
+    fn test_detect_language() {
+        assert_eq!(
+            detect_language("This is a guide to the best wireless headphones that you can buy this year"),
+            Some("en")
+        );
+        assert_eq!(
+            detect_language("Die besten kabellosen Kopfhörer im großen Vergleichstest des Jahres"),
+            Some("de")
+        );
+        assert_eq!(detect_language("Быстрая коричневая лиса прыгает через ленивую собаку"), Some("ru"));
+        assert_eq!(detect_language("東京で一番おいしいラーメン屋さんを紹介します"), Some("ja"));
+        assert_eq!(detect_language("ok"), None);
+    }
+
+    #[test]
+    fn test_tag_languages() {
+        let mut results = vec![
+            result(
+                "Best wireless headphones",
+                "We tested the most popular models for sound quality and comfort",
+            ),
+            result("x", ""),
+        ];
+        tag_languages(&mut results);
+        assert_eq!(results[0].language.as_deref(), Some("en"));
+        assert_eq!(results[1].language, None);
+    }
+
+    #[test]
+    fn test_filter_drops_other_latin_languages() {
+        let filter = LanguageFilter::new(&["en"], "wireless headphones").unwrap();
+        let english = result(
+            "Best wireless headphones",
+            "We tested the most popular models for sound quality and comfort",
+        );
+        let german = result(
+            "Die besten kabellosen Kopfhörer",
+            "Wir haben die beliebtesten Modelle auf Klang und Tragekomfort getestet",
+        );
+        assert!(filter.keep(&english));
+        assert!(!filter.keep(&german));
+
+        let mut tagged = german.clone();
+        tagged.language = Some("en".into());
+        assert!(filter.keep(&tagged));
+    }
+
+    #[test]
+    fn test_filter_unknown_language() {
+        assert!(LanguageFilter::new(&["all"], "rust").is_none());
+    }
+}
@@ -5,7 +5,31 @@
 pub mod types;
 pub mod error;
 pub mod config;
+pub mod config_file;
+pub mod fusion;
+pub mod diff;
+pub mod language;
+pub mod intent;
+pub mod feed;
+pub mod share;
+pub mod research;
+pub mod cache;
+pub mod memory_cache;
+pub mod net;
+pub mod event_log;
+pub mod hash;
+pub mod html;
+pub mod bookmarks;
+pub mod retry;
+pub mod schedule;
+pub mod secret;
+pub mod text;
+pub mod url;
+#[cfg(feature = "redis")]
+pub mod redis_cache;
 
 pub use types::*;
 pub use error::*;
 pub use config::*;
+pub use config_file::{ConfigFile, ConfigFormat};
+pub use secret::{Secret, load_secret};
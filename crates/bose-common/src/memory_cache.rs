@@ -0,0 +1,213 @@
+//! 行程內快取後端 — bose-http 與 bose-mcp 未設定共用後端時使用
+
+use crate::cache::{BoxFuture, CacheBackend, cache_key};
+use crate::types::{SearchQuery, SearchResponse};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// 行程內搜尋回應快取（所有請求共用，預設後端）
+pub struct ResponseCache {
+    entries: Mutex<HashMap<String, (Instant, SearchResponse)>>,
+    ttl: Duration,
+    max_entries: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evicted: AtomicU64,
+}
+
+/// 快取統計（累計值）
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub hits: u64,
+    /// 未命中（含已過期）
+    pub misses: u64,
+    /// 因容量不足被淘汰
+    pub evicted: u64,
+}
+
+impl CacheStats {
+    /// 命中率（0.0 - 1.0）；尚未查詢過時為 0
+    pub fn hit_ratio(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+impl ResponseCache {
+    pub fn new(ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            ttl,
+            max_entries,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evicted: AtomicU64::new(0),
+        }
+    }
+
+    /// 以完整查詢參數作為快取鍵
+    pub fn key(query: &SearchQuery) -> String {
+        cache_key(query)
+    }
+
+    pub fn get(&self, query: &SearchQuery) -> Option<SearchResponse> {
+        let mut entries = self.entries.lock().unwrap();
+        let key = Self::key(query);
+        let hit = match entries.get(&key) {
+            Some((stored_at, resp)) if stored_at.elapsed() < self.ttl => Some(resp.clone()),
+            Some(_) => {
+                entries.remove(&key);
+                None
+            }
+            None => None,
+        };
+        let counter = if hit.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        hit
+    }
+
+    pub fn insert(&self, query: &SearchQuery, response: SearchResponse) {
+        if self.max_entries == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        let ttl = self.ttl;
+        entries.retain(|_, (stored_at, _)| stored_at.elapsed() < ttl);
+
+        if entries.len() >= self.max_entries {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, (stored_at, _))| *stored_at)
+                .map(|(k, _)| k.clone());
+            if let Some(k) = oldest {
+                entries.remove(&k);
+                self.evicted.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        entries.insert(Self::key(query), (Instant::now(), response));
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.len(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evicted: self.evicted.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl CacheBackend for ResponseCache {
+    fn get<'a>(&'a self, query: &'a SearchQuery) -> BoxFuture<'a, Option<SearchResponse>> {
+        Box::pin(async move { ResponseCache::get(self, query) })
+    }
+
+    fn insert<'a>(&'a self, query: &'a SearchQuery, response: SearchResponse) -> BoxFuture<'a, ()> {
+        Box::pin(async move { ResponseCache::insert(self, query, response) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(query: &str) -> SearchResponse {
+        SearchResponse {
+            results: vec![],
+            query: query.into(),
+            elapsed_seconds: 0.1,
+            total_results: None,
+            engines_used: vec![],
+            suggestions: Vec::new(),
+            corrected_from: None,
+            instant_answer: None,
+        }
+    }
+
+    #[test]
+    fn test_cache_hit() {
+        let cache = ResponseCache::new(Duration::from_secs(60), 10);
+        let q = SearchQuery::new("rust");
+        cache.insert(&q, response("rust"));
+        assert_eq!(cache.get(&q).unwrap().query, "rust");
+    }
+
+    #[test]
+    fn test_cache_key_includes_params() {
+        let cache = ResponseCache::new(Duration::from_secs(60), 10);
+        cache.insert(&SearchQuery::new("rust"), response("rust"));
+        assert!(
+            cache
+                .get(&SearchQuery::new("rust").with_category("it"))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_cache_expired() {
+        let cache = ResponseCache::new(Duration::ZERO, 10);
+        let q = SearchQuery::new("rust");
+        cache.insert(&q, response("rust"));
+        assert!(cache.get(&q).is_none());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_cache_evicts_oldest() {
+        let cache = ResponseCache::new(Duration::from_secs(60), 2);
+        let (a, b, c) = (
+            SearchQuery::new("a"),
+            SearchQuery::new("b"),
+            SearchQuery::new("c"),
+        );
+        cache.insert(&a, response("a"));
+        cache.insert(&b, response("b"));
+        cache.insert(&c, response("c"));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&a).is_none());
+        assert!(cache.get(&c).is_some());
+        assert_eq!(cache.stats().evicted, 1);
+    }
+
+    #[test]
+    fn test_cache_stats() {
+        let cache = ResponseCache::new(Duration::from_secs(60), 10);
+        let q = SearchQuery::new("rust");
+        assert!(cache.get(&q).is_none());
+        cache.insert(&q, response("rust"));
+        assert!(cache.get(&q).is_some());
+        assert!(cache.get(&SearchQuery::new("Rust")).is_some());
+
+        let stats = cache.stats();
+        assert_eq!((stats.entries, stats.hits, stats.misses), (1, 2, 1));
+        assert!((stats.hit_ratio() - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_cache_as_backend() {
+        let backend: &dyn CacheBackend = &ResponseCache::new(Duration::from_secs(60), 10);
+        let q = SearchQuery::new("rust");
+        backend.insert(&q, response("rust")).await;
+        assert_eq!(backend.get(&q).await.unwrap().query, "rust");
+    }
+}
@@ -0,0 +1,160 @@
+//! 對外抓取的位址檢查 — 代抓呼叫端提供的網址時，避免服務被用來探測內網（SSRF）
+//!
+//! - `ensure_public`：送出請求前檢查網址，主機名稱先解析 DNS
+//! - `PublicResolver` / `public_redirect_policy`：裝在 `reqwest::Client` 上，
+//!   連線時與每次轉址再檢查一次，擋住轉址到內網與 DNS rebinding
+
+use crate::error::{BoseError, BoseResult};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use std::net::IpAddr;
+use url::{Host, Url};
+
+/// 轉址次數上限（與 reqwest 預設相同）
+const MAX_REDIRECTS: usize = 10;
+
+/// 是否為可公開連線的位址
+///
+/// 迴路、私有、鏈路本地、CGNAT（100.64.0.0/10）、未指定、廣播、多播、文件保留
+/// 與 IPv6 唯一本地位址皆否；IPv4 對映的 IPv6 位址依 IPv4 判斷。
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                || a == 0
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_public_ip(v4.into()),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    || first & 0xfe00 == 0xfc00
+                    || first & 0xffc0 == 0xfe80)
+            }
+        },
+    }
+}
+
+/// 確認網址只會連到公開位址：限 http / https，主機名稱解析後任一位址非公開即拒絕
+///
+/// 被拒絕時回傳 `InvalidQuery`；網域無法解析時回傳 `UpstreamError`。
+pub async fn ensure_public(raw: &str) -> BoseResult<()> {
+    let url = Url::parse(raw).map_err(|e| BoseError::InvalidQuery(format!("invalid url {raw}: {e}")))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(BoseError::InvalidQuery(format!("unsupported url scheme: {}", url.scheme())));
+    }
+    let addrs: Vec<IpAddr> = match url.host() {
+        Some(Host::Ipv4(ip)) => vec![ip.into()],
+        Some(Host::Ipv6(ip)) => vec![ip.into()],
+        Some(Host::Domain(domain)) => {
+            let port = url.port_or_known_default().unwrap_or(80);
+            tokio::net::lookup_host((domain, port))
+                .await
+                .map_err(|e| BoseError::UpstreamError(format!("cannot resolve {domain}: {e}")))?
+                .map(|addr| addr.ip())
+                .collect()
+        }
+        None => return Err(BoseError::InvalidQuery(format!("url has no host: {raw}"))),
+    };
+    match addrs.iter().find(|ip| !is_public_ip(**ip)) {
+        Some(ip) => Err(BoseError::InvalidQuery(format!(
+            "{raw} resolves to non-public address {ip}"
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// 只回傳公開位址的 DNS 解析器；全部位址都非公開時解析失敗
+///
+/// 連線時使用的就是這次解析的結果，因此檢查後才把網域指向內網（DNS rebinding）也會被擋下。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addrs: Vec<_> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{host} resolves to no public address").into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// 不跟隨轉址到 IP 字面值為非公開位址的網址；網域名稱交給 `PublicResolver` 檢查
+pub fn public_redirect_policy() -> reqwest::redirect::Policy {
+    reqwest::redirect::Policy::custom(|attempt| {
+        let blocked = match attempt.url().host() {
+            Some(Host::Ipv4(ip)) => !is_public_ip(ip.into()),
+            Some(Host::Ipv6(ip)) => !is_public_ip(ip.into()),
+            _ => false,
+        };
+        if blocked {
+            let message = format!("redirect to non-public address {}", attempt.url());
+            attempt.error(message)
+        } else if attempt.previous().len() >= MAX_REDIRECTS {
+            attempt.error("too many redirects")
+        } else {
+            attempt.follow()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_public_ip() {
+        for ip in ["8.8.8.8", "1.1.1.1", "2606:4700::1111"] {
+            assert!(is_public_ip(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ensure_public() {
+        assert!(ensure_public("https://93.184.215.14/page").await.is_ok());
+        for url in [
+            "http://127.0.0.1:8080/admin",
+            "http://[::1]/",
+            "http://169.254.169.254/latest/meta-data/",
+            "http://localhost:3000/",
+        ] {
+            let err = ensure_public(url).await.unwrap_err();
+            assert!(matches!(err, BoseError::InvalidQuery(_)), "{url}: {err}");
+        }
+        assert!(matches!(
+            ensure_public("file:///etc/passwd").await,
+            Err(BoseError::InvalidQuery(_))
+        ));
+    }
+}
@@ -0,0 +1,163 @@
+//! Redis 後端 — 多個 bose-mcp / bose-http / CLI 副本共用快取與限流狀態
+
+use crate::cache::{BoxFuture, CacheBackend, KeyValueBackend, RateLimitBackend, cache_key};
+use crate::error::{BoseError, BoseResult};
+use crate::types::{SearchQuery, SearchResponse};
+use redis::AsyncCommands;
+use redis::aio::ConnectionManager;
+use std::time::Duration;
+
+/// 令牌桶：以 Redis 伺服器時間補充令牌，避免各副本時鐘不同步；桶補滿後鍵自動過期
+const TOKEN_BUCKET: &str = r"
+local rate, burst, ttl = tonumber(ARGV[1]), tonumber(ARGV[2]), tonumber(ARGV[3])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) + tonumber(time[2]) / 1000000
+local state = redis.call('HMGET', KEYS[1], 'tokens', 'at')
+local tokens = tonumber(state[1]) or burst
+local at = tonumber(state[2]) or now
+tokens = math.min(burst, tokens + math.max(0, now - at) * rate)
+local allowed = 0
+if tokens >= 1 then
+    tokens = tokens - 1
+    allowed = 1
+end
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'at', tostring(now))
+if ttl > 0 then
+    redis.call('EXPIRE', KEYS[1], ttl)
+end
+return allowed
+";
+
+/// Redis 快取 / 限流後端
+///
+/// 快取以 `SET EX` 儲存 JSON；限流為所有副本共用的令牌桶（每秒 `requests_per_second` 個、
+/// 最多累積 `burst` 個），以 Lua 腳本原子地更新。Redis 無法連線時快取視為未命中、限流放行（fail-open）。
+#[derive(Clone)]
+pub struct RedisBackend {
+    conn: ConnectionManager,
+    prefix: String,
+    ttl_secs: u64,
+    /// `(requests_per_second, burst)`；None 時不限流
+    rate: Option<(f64, u32)>,
+}
+
+impl RedisBackend {
+    /// 連線到 `url`（例如 `redis://127.0.0.1:6379`）
+    pub async fn connect(url: &str, prefix: impl Into<String>, ttl_secs: u64) -> BoseResult<Self> {
+        let client = redis::Client::open(url).map_err(|e| BoseError::CacheError(e.to_string()))?;
+        let conn = ConnectionManager::new(client)
+            .await
+            .map_err(|e| BoseError::CacheError(e.to_string()))?;
+        Ok(Self {
+            conn,
+            prefix: prefix.into(),
+            ttl_secs,
+            rate: None,
+        })
+    }
+
+    /// 啟用共用限流：每秒補充 `requests_per_second` 個令牌，最多累積 `burst` 個
+    pub fn with_rate_limit(mut self, requests_per_second: f64, burst: u32) -> Self {
+        self.rate = Some((requests_per_second, burst));
+        self
+    }
+
+    fn cache_redis_key(&self, query: &SearchQuery) -> String {
+        format!("{}:cache:{}", self.prefix, cache_key(query))
+    }
+
+    fn rate_redis_key(&self) -> String {
+        format!("{}:rl", self.prefix)
+    }
+}
+
+/// 桶從空到滿所需的秒數（加一秒餘裕）；不補充時為 0，鍵不過期
+fn bucket_ttl(requests_per_second: f64, burst: u32) -> u64 {
+    if requests_per_second > 0.0 {
+        (burst as f64 / requests_per_second).ceil() as u64 + 1
+    } else {
+        0
+    }
+}
+
+impl CacheBackend for RedisBackend {
+    fn get<'a>(&'a self, query: &'a SearchQuery) -> BoxFuture<'a, Option<SearchResponse>> {
+        Box::pin(async move {
+            let mut conn = self.conn.clone();
+            let raw: Option<String> = conn.get(self.cache_redis_key(query)).await.ok()?;
+            serde_json::from_str(&raw?).ok()
+        })
+    }
+
+    fn insert<'a>(&'a self, query: &'a SearchQuery, response: SearchResponse) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            let Ok(json) = serde_json::to_string(&response) else {
+                return;
+            };
+            let mut conn = self.conn.clone();
+            let _: Result<(), _> = conn
+                .set_ex(self.cache_redis_key(query), json, self.ttl_secs)
+                .await;
+        })
+    }
+}
+
+impl KeyValueBackend for RedisBackend {
+    fn get_bytes<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Option<Vec<u8>>> {
+        Box::pin(async move {
+            let mut conn = self.conn.clone();
+            conn.get(format!("{}:kv:{}", self.prefix, key)).await.ok()?
+        })
+    }
+
+    fn set_bytes<'a>(&'a self, key: &'a str, value: Vec<u8>, ttl: Duration) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            let mut conn = self.conn.clone();
+            let _: Result<(), _> = conn
+                .set_ex(format!("{}:kv:{}", self.prefix, key), value, ttl.as_secs().max(1))
+                .await;
+        })
+    }
+}
+
+impl RateLimitBackend for RedisBackend {
+    fn try_acquire(&self) -> BoxFuture<'_, bool> {
+        Box::pin(async move {
+            let Some((rate, burst)) = self.rate else {
+                return true;
+            };
+            let mut conn = self.conn.clone();
+            let allowed: Result<i64, _> = redis::cmd("EVAL")
+                .arg(TOKEN_BUCKET)
+                .arg(1)
+                .arg(self.rate_redis_key())
+                .arg(rate)
+                .arg(burst)
+                .arg(bucket_ttl(rate, burst))
+                .query_async(&mut conn)
+                .await;
+            allowed.map_or(true, |allowed| allowed == 1)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_connect_invalid_url() {
+        let err = RedisBackend::connect("not-a-redis-url", "bose", 60)
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err, BoseError::CacheError(_)));
+    }
+
+    #[test]
+    fn test_bucket_ttl() {
+        assert_eq!(bucket_ttl(10.0, 20), 3);
+        assert_eq!(bucket_ttl(0.5, 1), 3);
+        assert_eq!(bucket_ttl(0.0, 5), 0);
+    }
+}
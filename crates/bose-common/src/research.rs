@@ -0,0 +1,54 @@
+//! 深度研究 — 將多題查詢拆成子問題
+//!
+//! 規則與主程式庫的 `bose_search::sub_questions` 相同：依問號、分號與換行切開，
+//! 去掉重複與過短的片段；拆不出兩題以上時只有原查詢。
+
+/// 子問題最少字元數，過短的片段（例如 `ok?`）不單獨成題
+const MIN_SUB_QUESTION_CHARS: usize = 8;
+
+/// 拆成最多 `max` 個子問題
+pub fn sub_questions(query: &str, max: usize) -> Vec<String> {
+    let mut questions: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for c in query.chars() {
+        current.push(c);
+        if matches!(c, '?' | '？' | ';' | '；' | '\n') {
+            push_question(&mut questions, &current);
+            current.clear();
+        }
+    }
+    push_question(&mut questions, &current);
+
+    if questions.len() < 2 {
+        return vec![query.trim().to_string()];
+    }
+    questions.truncate(max.max(1));
+    questions
+}
+
+fn push_question(questions: &mut Vec<String>, fragment: &str) {
+    let question = fragment.trim().trim_end_matches([';', '；']).trim();
+    if question.chars().count() < MIN_SUB_QUESTION_CHARS {
+        return;
+    }
+    if !questions.iter().any(|q| q.eq_ignore_ascii_case(question)) {
+        questions.push(question.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sub_questions() {
+        assert_eq!(
+            sub_questions("What is tokio? How does its scheduler work?", 4),
+            vec!["What is tokio?", "How does its scheduler work?"]
+        );
+        assert_eq!(sub_questions("Rust 的所有權是什麼？借用檢查器如何運作？", 4).len(), 2);
+        assert_eq!(sub_questions("tokio runtime", 4), vec!["tokio runtime"]);
+        assert_eq!(sub_questions("What is tokio? what is tokio? ok?", 4).len(), 1);
+        assert_eq!(sub_questions("What is tokio; what is async-std; what is smol", 2).len(), 2);
+    }
+}
@@ -0,0 +1,197 @@
+//! 重試 — 對逾時、連線失敗與 5xx 以指數退避加抖動重試
+//!
+//! 所有引擎客戶端共用 `with_backoff`；哪些錯誤值得重試由 [`Retryable`] 判斷。
+
+use crate::BoseError;
+use std::future::Future;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 重試策略
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// 最多嘗試次數（含第一次）
+    pub max_attempts: u32,
+    /// 第一次重試前的等待時間，之後每次加倍
+    pub base_delay: Duration,
+    /// 單次等待上限
+    pub max_delay: Duration,
+    /// 抖動比例（0.0 ~ 1.0）：實際等待在 `delay * (1 - jitter) ..= delay` 之間
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+            jitter: 0.5,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 重試 `retries` 次（總共嘗試 `retries + 1` 次）
+    pub fn with_retries(retries: u32) -> Self {
+        Self {
+            max_attempts: retries + 1,
+            ..Self::default()
+        }
+    }
+
+    /// 不重試
+    pub fn none() -> Self {
+        Self::with_retries(0)
+    }
+
+    /// 第 `attempt` 次失敗（從 1 開始）後、抖動前的等待時間
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    fn jittered(&self, attempt: u32) -> Duration {
+        let delay = self.delay(attempt);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        delay.mul_f64(1.0 - jitter * unit_random())
+    }
+}
+
+/// 可判斷是否值得重試的錯誤
+pub trait Retryable {
+    fn is_retryable(&self) -> bool;
+}
+
+impl Retryable for BoseError {
+    /// 逾時、連線失敗與 5xx 可重試；4xx、解析與配置錯誤重試也不會成功
+    fn is_retryable(&self) -> bool {
+        match self {
+            BoseError::HttpError(e) => {
+                e.is_timeout() || e.is_connect() || e.status().is_some_and(|s| s.is_server_error())
+            }
+            BoseError::SearxngError(msg) | BoseError::UpstreamError(msg) => msg
+                .strip_prefix("HTTP ")
+                .is_some_and(|status| status.starts_with('5')),
+            BoseError::JsonError(_)
+            | BoseError::CacheError(_)
+            | BoseError::IoError(_)
+            | BoseError::ConfigError(_)
+            | BoseError::InvalidQuery(_)
+            | BoseError::BrowserError(_)
+            | BoseError::ShareError(_)
+            | BoseError::TranslationError(_) => false,
+        }
+    }
+}
+
+/// 執行 `op`，可重試的錯誤依策略退避後再試；回傳最後一次的結果
+pub async fn with_backoff<T, E, F, Fut>(policy: &RetryPolicy, mut op: F) -> Result<T, E>
+where
+    E: Retryable + std::fmt::Display,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut attempt = 1;
+    loop {
+        match op().await {
+            Err(e) if attempt < policy.max_attempts && e.is_retryable() => {
+                let delay = policy.jittered(attempt);
+                tracing::warn!(
+                    attempt,
+                    max_attempts = policy.max_attempts,
+                    delay_ms = delay.as_millis() as u64,
+                    error = %e,
+                    "Retrying after transient error"
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            outcome => return outcome,
+        }
+    }
+}
+
+/// 不引入 rand：以時間與遞增計數混出 [0, 1) 的值，只用於抖動
+fn unit_random() -> f64 {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64)
+        .unwrap_or(0);
+    let mut x = nanos ^ COUNTER.fetch_add(0x9E37_79B9_7F4A_7C15, Ordering::Relaxed);
+    x ^= x >> 33;
+    x = x.wrapping_mul(0xFF51_AFD7_ED55_8CCD);
+    x ^= x >> 33;
+    (x >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn fast(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(4),
+            jitter: 0.5,
+        }
+    }
+
+    #[test]
+    fn test_delay_doubles_up_to_cap() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay(1), Duration::from_millis(200));
+        assert_eq!(policy.delay(3), Duration::from_millis(800));
+        assert_eq!(policy.delay(10), Duration::from_secs(5));
+        for attempt in 1..5 {
+            let d = policy.jittered(attempt);
+            assert!(d <= policy.delay(attempt) && d >= policy.delay(attempt) / 2);
+        }
+    }
+
+    #[test]
+    fn test_classification() {
+        assert!(BoseError::SearxngError("HTTP 503 Service Unavailable".into()).is_retryable());
+        assert!(!BoseError::SearxngError("HTTP 404 Not Found".into()).is_retryable());
+        assert!(!BoseError::InvalidQuery("empty".into()).is_retryable());
+        assert!(!BoseError::ConfigError("x".into()).is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_retries_until_success() {
+        let calls = AtomicU32::new(0);
+        let result = with_backoff(&fast(3), || async {
+            match calls.fetch_add(1, Ordering::SeqCst) {
+                0 | 1 => Err(BoseError::SearxngError("HTTP 502 Bad Gateway".into())),
+                _ => Ok("ok"),
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), "ok");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_stops_on_permanent_error_or_exhaustion() {
+        let calls = AtomicU32::new(0);
+        let result: Result<(), _> = with_backoff(&fast(5), || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(BoseError::SearxngError("HTTP 400 Bad Request".into()))
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        calls.store(0, Ordering::SeqCst);
+        let result: Result<(), _> = with_backoff(&fast(3), || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(BoseError::SearxngError("HTTP 500".into()))
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,464 @@
+//! 排程搜尋 — 依 cron 運算式定期執行查詢，把新出現的結果推送到 webhook
+//!
+//! 讓 bose 成為輕量的告警系統，例如追蹤 CVE、產品名稱或外洩的憑證：
+//!
+//! ```toml
+//! [[schedule]]
+//! name = "xz-backdoor"
+//! query = "CVE-2024-3094 exploit"
+//! cron = "*/30 * * * *"
+//! webhooks = [
+//!     "https://hooks.slack.com/services/T000/B000/XXXX",
+//!     { url = "https://alerts.example.com/bose", format = "json" },
+//! ]
+//! ```
+//!
+//! cron 為標準五欄（分 時 日 月 週，UTC），支援 `*`、`,`、`-`、`/`、月份與星期英文縮寫，
+//! 以及 `@hourly`、`@daily`、`@weekly`、`@monthly`、`@yearly`。日與週都有限定時任一符合即執行。
+//! webhook 格式未指定時依網址判斷：Slack、Discord 或一般 JSON。
+
+use crate::{BoseError, BoseResult, SearchQuery, SearchResult};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Timelike, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// 訊息中最多列出的結果數
+const MAX_LISTED: usize = 10;
+/// Discord 訊息長度上限
+const DISCORD_MAX_CHARS: usize = 2000;
+
+/// 五欄 cron 運算式（UTC）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    source: String,
+    minutes: u64,
+    hours: u32,
+    days: u32,
+    months: u16,
+    weekdays: u8,
+    /// 日欄位有限定（不是 `*` 開頭）
+    days_restricted: bool,
+    /// 週欄位有限定（不是 `*` 開頭）
+    weekdays_restricted: bool,
+}
+
+const MONTH_NAMES: &[&str] = &[
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAY_NAMES: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+impl FromStr for CronSchedule {
+    type Err = BoseError;
+
+    fn from_str(s: &str) -> BoseResult<Self> {
+        let expr = match s.trim() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(cron_error(s, "須為五個欄位（分 時 日 月 週）"));
+        };
+        // 星期 7 與 0 同為週日
+        let weekdays = parse_field(s, weekday, 0, 7, WEEKDAY_NAMES)?;
+        Ok(Self {
+            source: s.trim().to_string(),
+            minutes: parse_field(s, minute, 0, 59, &[])?,
+            hours: parse_field(s, hour, 0, 23, &[])? as u32,
+            days: parse_field(s, day, 1, 31, &[])? as u32,
+            months: parse_field(s, month, 1, 12, MONTH_NAMES)? as u16,
+            weekdays: ((weekdays | (weekdays >> 7)) & 0x7f) as u8,
+            days_restricted: !day.starts_with('*'),
+            weekdays_restricted: !weekday.starts_with('*'),
+        })
+    }
+}
+
+impl CronSchedule {
+    /// `after` 之後（不含）第一個符合的時間，精確到分鐘；永遠不會觸發時為 None（例如 `0 0 31 2 *`）
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let first_day = start.date_naive();
+        // 八年內必定涵蓋所有日期組合（含 2 月 29 日）
+        for offset in 0..366 * 8 {
+            let date = first_day + Duration::days(offset);
+            if !self.matches_date(date) {
+                continue;
+            }
+            let (hour_from, minute_from) = if offset == 0 {
+                (start.hour(), start.minute())
+            } else {
+                (0, 0)
+            };
+            for hour in hour_from..24 {
+                if self.hours & (1 << hour) == 0 {
+                    continue;
+                }
+                let minute_from = if hour == hour_from { minute_from } else { 0 };
+                if let Some(minute) = (minute_from..60).find(|m| self.minutes & (1 << m) != 0) {
+                    return Utc
+                        .with_ymd_and_hms(date.year(), date.month(), date.day(), hour, minute, 0)
+                        .single();
+                }
+            }
+        }
+        None
+    }
+
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        if self.months & (1 << date.month()) == 0 {
+            return false;
+        }
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+}
+
+impl fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl<'de> Deserialize<'de> for CronSchedule {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let expr = String::deserialize(deserializer)?;
+        expr.parse()
+            .map_err(|e| serde::de::Error::custom(crate::config::message(e)))
+    }
+}
+
+/// 解析單一欄位為位元遮罩（第 n 位代表值 n）
+fn parse_field(expr: &str, field: &str, min: u32, max: u32, names: &[&str]) -> BoseResult<u64> {
+    let value = |text: &str| -> BoseResult<u32> {
+        let lower = text.to_ascii_lowercase();
+        let parsed = match names.iter().position(|name| *name == lower) {
+            // 月份從 1 開始，星期從 0 開始
+            Some(index) => index as u32 + min,
+            None => text
+                .parse()
+                .map_err(|_| cron_error(expr, &format!("無法解析 `{text}`")))?,
+        };
+        if (min..=max).contains(&parsed) {
+            Ok(parsed)
+        } else {
+            Err(cron_error(expr, &format!("`{text}` 超出範圍 {min}-{max}")))
+        }
+    };
+
+    let mut mask = 0u64;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| cron_error(expr, &format!("無效的間隔 `{item}`")))?;
+                (range, step)
+            }
+            None => (item, 1),
+        };
+        let (from, to) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((from, to)) => (value(from)?, value(to)?),
+            // `5/15` 表示從 5 開始每 15
+            None if step > 1 => (value(range)?, max),
+            None => {
+                let v = value(range)?;
+                (v, v)
+            }
+        };
+        if from > to {
+            return Err(cron_error(expr, &format!("範圍 `{range}` 起點大於終點")));
+        }
+        for v in (from..=to).step_by(step as usize) {
+            mask |= 1 << v;
+        }
+    }
+    Ok(mask)
+}
+
+fn cron_error(expr: &str, reason: &str) -> BoseError {
+    BoseError::ConfigError(format!("cron `{expr}` {reason}"))
+}
+
+/// webhook 訊息格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookFormat {
+    /// Slack incoming webhook（`text`，mrkdwn 連結）
+    Slack,
+    /// Discord webhook（`content`，Markdown 連結）
+    Discord,
+    /// 一般 JSON：工作名稱、查詢與完整的新結果
+    Json,
+}
+
+impl WebhookFormat {
+    /// 依網址判斷：`hooks.slack.com` 為 Slack，`discord.com/api/webhooks` 為 Discord，其他為 JSON
+    pub fn detect(url: &str) -> Self {
+        let host = url::Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_ascii_lowercase))
+            .unwrap_or_default();
+        if host == "hooks.slack.com" {
+            Self::Slack
+        } else if (host == "discord.com" || host.ends_with(".discord.com") || host == "discordapp.com")
+            && url.contains("/api/webhooks/")
+        {
+            Self::Discord
+        } else {
+            Self::Json
+        }
+    }
+}
+
+/// 通知目標；設定檔中可寫成網址字串或 `{ url, format }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Webhook {
+    pub url: String,
+    pub format: WebhookFormat,
+}
+
+impl Webhook {
+    pub fn new(url: impl Into<String>) -> Self {
+        let url = url.into();
+        let format = WebhookFormat::detect(&url);
+        Self { url, format }
+    }
+
+    pub fn with_format(mut self, format: WebhookFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// 新結果通知的請求內容
+    pub fn payload(
+        &self,
+        job: &ScheduledJob,
+        new_results: &[SearchResult],
+        checked_at: DateTime<Utc>,
+    ) -> serde_json::Value {
+        match self.format {
+            WebhookFormat::Slack => {
+                let items = new_results
+                    .iter()
+                    .map(|r| format!("• <{}|{}>", r.url, r.title.replace(['<', '>', '|'], " ")));
+                serde_json::json!({ "text": message(job, new_results.len(), "*", items) })
+            }
+            WebhookFormat::Discord => {
+                let items = new_results.iter().map(|r| format!("• [{}](<{}>)", r.title, r.url));
+                let text = message(job, new_results.len(), "**", items);
+                serde_json::json!({ "content": crate::text::truncate_chars(&text, DISCORD_MAX_CHARS) })
+            }
+            WebhookFormat::Json => serde_json::json!({
+                "job": job.name,
+                "query": job.query,
+                "checked_at": checked_at.to_rfc3339(),
+                "count": new_results.len(),
+                "new_results": new_results,
+            }),
+        }
+    }
+}
+
+fn message(
+    job: &ScheduledJob,
+    count: usize,
+    bold: &str,
+    items: impl Iterator<Item = String>,
+) -> String {
+    let mut lines = vec![format!(
+        "🔔 {bold}{}{bold}: {count} new result{} for `{}`",
+        job.name,
+        if count == 1 { "" } else { "s" },
+        job.query
+    )];
+    lines.extend(items.take(MAX_LISTED));
+    if count > MAX_LISTED {
+        lines.push(format!("…and {} more", count - MAX_LISTED));
+    }
+    lines.join("\n")
+}
+
+impl<'de> Deserialize<'de> for Webhook {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Table {
+            url: String,
+            format: Option<WebhookFormat>,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Url(String),
+            Table(Table),
+        }
+
+        Ok(match Raw::deserialize(deserializer)? {
+            Raw::Url(url) => Webhook::new(url),
+            Raw::Table(Table { url, format }) => {
+                let webhook = Webhook::new(url);
+                match format {
+                    Some(format) => webhook.with_format(format),
+                    None => webhook,
+                }
+            }
+        })
+    }
+}
+
+/// 一項排程搜尋（設定檔 `[[schedule]]`）
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScheduledJob {
+    pub name: String,
+    pub query: String,
+    pub cron: CronSchedule,
+    pub num_results: Option<u32>,
+    pub category: Option<String>,
+    #[serde(default)]
+    pub engines: Vec<String>,
+    #[serde(default)]
+    pub webhooks: Vec<Webhook>,
+}
+
+impl ScheduledJob {
+    pub fn search_query(&self, default_num_results: u32) -> SearchQuery {
+        let mut query = SearchQuery::new(&self.query)
+            .with_num_results(self.num_results.unwrap_or(default_num_results))
+            .with_engines(self.engines.clone());
+        query.category = self.category.clone();
+        query
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn next(expr: &str, after: &str) -> Option<String> {
+        let schedule: CronSchedule = expr.parse().unwrap();
+        schedule
+            .next_after(at(after))
+            .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+    }
+
+    fn result(url: &str) -> SearchResult {
+        SearchResult {
+            title: format!("Title {url}"),
+            url: url.into(),
+            snippet: None,
+            engine: "google".into(),
+            score: None,
+            category: "general".into(),
+            language: None,
+        }
+    }
+
+    fn job(webhooks: Vec<Webhook>) -> ScheduledJob {
+        ScheduledJob {
+            name: "xz".into(),
+            query: "CVE-2024-3094".into(),
+            cron: "@hourly".parse().unwrap(),
+            num_results: None,
+            category: None,
+            engines: vec![],
+            webhooks,
+        }
+    }
+
+    #[test]
+    fn test_next_after() {
+        assert_eq!(next("*/15 * * * *", "2024-03-29T10:07:30Z").unwrap(), "2024-03-29 10:15");
+        assert_eq!(next("*/15 * * * *", "2024-03-29T10:45:00Z").unwrap(), "2024-03-29 11:00");
+        assert_eq!(next("@daily", "2024-12-31T23:59:59Z").unwrap(), "2025-01-01 00:00");
+        assert_eq!(next("30 9 * * mon-fri", "2024-03-29T10:00:00Z").unwrap(), "2024-04-01 09:30");
+        assert_eq!(next("0 0 29 feb *", "2024-03-01T00:00:00Z").unwrap(), "2028-02-29 00:00");
+        // 星期 7 等同週日
+        assert_eq!(next("0 12 * * 7", "2024-03-29T00:00:00Z").unwrap(), "2024-03-31 12:00");
+        // 日與週都有限定時任一符合即可
+        assert_eq!(next("0 0 15 * 1", "2024-04-02T00:00:00Z").unwrap(), "2024-04-08 00:00");
+        assert_eq!(next("0 0 31 2 *", "2024-01-01T00:00:00Z"), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for expr in ["* * * *", "60 * * * *", "*/0 * * * *", "5-1 * * * *", "0 0 * foo *"] {
+            let err = expr.parse::<CronSchedule>().unwrap_err().to_string();
+            assert!(err.contains(expr), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_webhook_detect_and_deserialize() {
+        assert_eq!(
+            WebhookFormat::detect("https://hooks.slack.com/services/T/B/X"),
+            WebhookFormat::Slack
+        );
+        assert_eq!(
+            WebhookFormat::detect("https://discord.com/api/webhooks/1/abc"),
+            WebhookFormat::Discord
+        );
+        assert_eq!(WebhookFormat::detect("https://example.com/hook"), WebhookFormat::Json);
+
+        let job: ScheduledJob = toml::from_str(
+            r#"
+            name = "xz"
+            query = "CVE-2024-3094"
+            cron = "*/30 * * * *"
+            webhooks = [
+                "https://hooks.slack.com/services/T/B/X",
+                { url = "https://hooks.slack.com/services/T/B/Y", format = "json" },
+            ]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(job.webhooks[0].format, WebhookFormat::Slack);
+        assert_eq!(job.webhooks[1].format, WebhookFormat::Json);
+
+        let err = toml::from_str::<ScheduledJob>(
+            "name = \"a\"\nquery = \"b\"\ncron = \"* * *\"",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("五個欄位"), "{err}");
+    }
+
+    #[test]
+    fn test_payloads() {
+        let job = job(vec![]);
+        let found: Vec<SearchResult> = (0..12).map(|i| result(&format!("https://a.com/{i}"))).collect();
+        let now = at("2024-03-29T10:00:00Z");
+
+        let slack = Webhook::new("https://hooks.slack.com/services/T/B/X").payload(&job, &found, now);
+        let text = slack["text"].as_str().unwrap();
+        assert!(text.starts_with("🔔 *xz*: 12 new results for `CVE-2024-3094`"));
+        assert!(text.contains("• <https://a.com/0|Title https://a.com/0>"));
+        assert!(text.ends_with("…and 2 more"));
+
+        let discord = Webhook::new("https://discord.com/api/webhooks/1/abc").payload(&job, &found[..1], now);
+        assert!(discord["content"].as_str().unwrap().contains("1 new result for"));
+
+        let json = Webhook::new("https://example.com/hook").payload(&job, &found[..2], now);
+        assert_eq!(json["job"], "xz");
+        assert_eq!(json["count"], 2);
+        assert_eq!(json["new_results"][1]["url"], "https://a.com/1");
+        assert_eq!(json["checked_at"], "2024-03-29T10:00:00+00:00");
+    }
+}
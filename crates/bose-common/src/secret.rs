@@ -0,0 +1,133 @@
+//! 機密設定載入 — 環境變數、`*_FILE` 檔案、作業系統金鑰圈
+//!
+//! 依序查找 `NAME`、`NAME_FILE`（Docker / Kubernetes secrets 掛載的檔案）、
+//! 金鑰圈（`keyring` feature，服務名稱 [`KEYRING_SERVICE`]、帳號為變數名稱）。
+//! 後兩者讓金鑰不出現在 `ps e` 與容器的環境變數傾印中。
+
+use crate::{BoseError, BoseResult};
+use std::fmt;
+
+/// 金鑰圈中的服務名稱
+pub const KEYRING_SERVICE: &str = "bose-search";
+
+/// 機密字串；`Debug` / `Display` 不顯示內容，避免寫進日誌
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    /// 取出明文（只在送出請求時使用）
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(***)")
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Secret {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Secret)
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("***")
+    }
+}
+
+/// 依 `NAME` → `NAME_FILE` → 金鑰圈的順序載入機密；都沒有時為 None
+///
+/// `NAME_FILE` 指向的檔案讀不到時回傳錯誤，而不是默默當作未設定。
+pub fn load_secret(name: &str) -> BoseResult<Option<Secret>> {
+    if let Some(value) = std::env::var(name).ok().filter(|v| !v.is_empty()) {
+        return Ok(Some(Secret(value)));
+    }
+    if let Some(path) = std::env::var_os(format!("{name}_FILE")).filter(|p| !p.is_empty()) {
+        return read_secret_file(&path).map(Some);
+    }
+    from_keyring(name)
+}
+
+fn read_secret_file(path: &std::ffi::OsStr) -> BoseResult<Secret> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        BoseError::ConfigError(format!("無法讀取機密檔案 {}: {e}", path.to_string_lossy()))
+    })?;
+    // 檔案結尾的換行不屬於金鑰
+    let value = content.trim_end_matches(['\r', '\n']);
+    if value.is_empty() {
+        return Err(BoseError::ConfigError(format!(
+            "機密檔案 {} 是空的",
+            path.to_string_lossy()
+        )));
+    }
+    Ok(Secret(value.to_string()))
+}
+
+#[cfg(feature = "keyring")]
+fn from_keyring(name: &str) -> BoseResult<Option<Secret>> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, name)
+        .map_err(|e| BoseError::ConfigError(format!("金鑰圈無法使用: {e}")))?;
+    match entry.get_password() {
+        Ok(value) => Ok(Some(Secret(value))),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(BoseError::ConfigError(format!(
+            "讀取金鑰圈 {name} 失敗: {e}"
+        ))),
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+fn from_keyring(_name: &str) -> BoseResult<Option<Secret>> {
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_redacted() {
+        let secret = Secret::new("sk-live-123");
+        assert_eq!(format!("{secret:?}"), "Secret(***)");
+        assert_eq!(secret.to_string(), "***");
+        assert_eq!(secret.expose(), "sk-live-123");
+    }
+
+    #[test]
+    fn test_load_from_file() {
+        let path = std::env::temp_dir().join(format!("bose-secret-{}", std::process::id()));
+        std::fs::write(&path, "file-key\n").unwrap();
+        // SAFETY: 測試專用的變數名稱，不與其他測試共用
+        unsafe {
+            std::env::set_var("BOSE_TEST_SECRET_FILE", &path);
+        }
+        let secret = load_secret("BOSE_TEST_SECRET").unwrap().unwrap();
+        assert_eq!(secret.expose(), "file-key");
+
+        // 環境變數優先於檔案
+        unsafe {
+            std::env::set_var("BOSE_TEST_SECRET", "env-key");
+        }
+        assert_eq!(
+            load_secret("BOSE_TEST_SECRET").unwrap().unwrap().expose(),
+            "env-key"
+        );
+        std::fs::remove_file(&path).unwrap();
+
+        unsafe {
+            std::env::set_var("BOSE_TEST_MISSING_FILE", "/nonexistent/bose-secret");
+        }
+        assert!(matches!(
+            load_secret("BOSE_TEST_MISSING"),
+            Err(BoseError::ConfigError(_))
+        ));
+    }
+}
@@ -0,0 +1,306 @@
+//! 結果分享 — 將搜尋回應或報告序列化為可分享的壓縮檔
+//!
+//! 分享檔是 gzip 壓縮的 JSON；`encode()` 另外產生可貼在聊天訊息裡的文字形式
+//! `bose1:<base64url>`。讀取時兩種形式（以及未壓縮的 JSON）都接受。
+
+use crate::error::{BoseError, BoseResult};
+use crate::types::SearchResponse;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, Utc};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// 分享檔格式版本；欄位不相容變動時遞增
+pub const SHARE_FORMAT_VERSION: u32 = 1;
+
+/// 文字形式的前綴（含格式版本）
+const TOKEN_PREFIX: &str = "bose1:";
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+/// 解壓上限，避免惡意分享檔耗盡記憶體
+const MAX_DECODED_BYTES: u64 = 32 * 1024 * 1024;
+
+/// 分享內容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SharedContent {
+    Response(Box<SearchResponse>),
+    Report { title: String, markdown: String },
+}
+
+/// 可分享的搜尋證據
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareArtifact {
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub content: SharedContent,
+}
+
+impl ShareArtifact {
+    pub fn response(response: SearchResponse) -> Self {
+        Self::new(SharedContent::Response(Box::new(response)))
+    }
+
+    pub fn report(title: impl Into<String>, markdown: impl Into<String>) -> Self {
+        Self::new(SharedContent::Report {
+            title: title.into(),
+            markdown: markdown.into(),
+        })
+    }
+
+    fn new(content: SharedContent) -> Self {
+        Self {
+            version: SHARE_FORMAT_VERSION,
+            created_at: Utc::now(),
+            author: None,
+            note: None,
+            content,
+        }
+    }
+
+    pub fn with_author(mut self, author: impl Into<String>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+
+    /// gzip 壓縮的 JSON
+    pub fn to_bytes(&self) -> BoseResult<Vec<u8>> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        serde_json::to_writer(&mut encoder, self)?;
+        Ok(encoder.finish()?)
+    }
+
+    /// 讀取 gzip 壓縮或未壓縮的 JSON
+    pub fn from_bytes(bytes: &[u8]) -> BoseResult<Self> {
+        let json = if bytes.starts_with(&GZIP_MAGIC) {
+            let mut json = Vec::new();
+            GzDecoder::new(bytes)
+                .take(MAX_DECODED_BYTES + 1)
+                .read_to_end(&mut json)?;
+            if json.len() as u64 > MAX_DECODED_BYTES {
+                return Err(BoseError::ShareError("artifact too large".into()));
+            }
+            json
+        } else {
+            bytes.to_vec()
+        };
+
+        let artifact: Self = serde_json::from_slice(&json)?;
+        if artifact.version > SHARE_FORMAT_VERSION {
+            return Err(BoseError::ShareError(format!(
+                "unsupported version {} (max {SHARE_FORMAT_VERSION})",
+                artifact.version
+            )));
+        }
+        Ok(artifact)
+    }
+
+    /// 文字形式：`bose1:<base64url(gzip json)>`
+    pub fn encode(&self) -> BoseResult<String> {
+        Ok(format!(
+            "{TOKEN_PREFIX}{}",
+            URL_SAFE_NO_PAD.encode(self.to_bytes()?)
+        ))
+    }
+
+    pub fn decode(token: &str) -> BoseResult<Self> {
+        let payload = token
+            .trim()
+            .strip_prefix(TOKEN_PREFIX)
+            .ok_or_else(|| BoseError::ShareError(format!("missing `{TOKEN_PREFIX}` prefix")))?;
+        let bytes = URL_SAFE_NO_PAD
+            .decode(payload)
+            .map_err(|e| BoseError::ShareError(e.to_string()))?;
+        Self::from_bytes(&bytes)
+    }
+
+    pub fn write_to(&self, path: impl AsRef<Path>) -> BoseResult<()> {
+        let mut file = std::fs::File::create(path)?;
+        file.write_all(&self.to_bytes()?)?;
+        Ok(())
+    }
+
+    /// 讀取分享檔；檔案內容也可以是 `encode()` 的文字形式
+    pub fn read_from(path: impl AsRef<Path>) -> BoseResult<Self> {
+        let bytes = std::fs::read(path)?;
+        match std::str::from_utf8(&bytes) {
+            Ok(text) if text.trim_start().starts_with(TOKEN_PREFIX) => Self::decode(text),
+            _ => Self::from_bytes(&bytes),
+        }
+    }
+
+    /// 以純文字呈現（供 `view` 指令使用）
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        write!(
+            out,
+            "Shared {}",
+            self.created_at.format("%Y-%m-%d %H:%M UTC")
+        )
+        .unwrap();
+        if let Some(author) = &self.author {
+            write!(out, " by {author}").unwrap();
+        }
+        writeln!(out).unwrap();
+        if let Some(note) = &self.note {
+            writeln!(out, "Note: {note}").unwrap();
+        }
+        writeln!(out).unwrap();
+
+        match &self.content {
+            SharedContent::Response(resp) => {
+                writeln!(
+                    out,
+                    "Query: \"{}\" — {} results ({:.1}s)\n",
+                    resp.query,
+                    resp.results.len(),
+                    resp.elapsed_seconds
+                )
+                .unwrap();
+                for (i, r) in resp.results.iter().enumerate() {
+                    writeln!(out, "{}. {}", i + 1, r.title).unwrap();
+                    writeln!(out, "   {}", r.url).unwrap();
+                    writeln!(out, "   Source: {} | Category: {}", r.engine, r.category).unwrap();
+                    if let Some(snippet) = &r.snippet {
+                        writeln!(out, "   {snippet}").unwrap();
+                    }
+                    writeln!(out).unwrap();
+                }
+                if !resp.engines_used.is_empty() {
+                    writeln!(out, "Engines: {}", resp.engines_used.join(", ")).unwrap();
+                }
+            }
+            SharedContent::Report { title, markdown } => {
+                writeln!(out, "# {title}\n\n{markdown}").unwrap();
+            }
+        }
+        out
+    }
+}
+
+/// 上傳分享檔到 paste 服務，回傳服務回應的網址
+///
+/// 以 `POST` 送出 `encode()` 的文字形式（`text/plain`），預期回應本文即為分享網址
+/// （相容 0x0.st、ix.io 一類服務，以及自架的 paste 端點）。
+#[cfg(feature = "paste")]
+pub async fn upload_paste(
+    http: &reqwest::Client,
+    endpoint: &str,
+    artifact: &ShareArtifact,
+) -> BoseResult<String> {
+    let resp = http
+        .post(endpoint)
+        .header(reqwest::header::CONTENT_TYPE, "text/plain")
+        .body(artifact.encode()?)
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        return Err(BoseError::ShareError(format!(
+            "paste service returned HTTP {}",
+            resp.status()
+        )));
+    }
+    let url = resp.text().await?.trim().to_string();
+    if url.is_empty() {
+        return Err(BoseError::ShareError(
+            "paste service returned no URL".into(),
+        ));
+    }
+    Ok(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SearchResult;
+
+    fn response() -> SearchResponse {
+        SearchResponse {
+            results: vec![SearchResult {
+                title: "Rust".into(),
+                url: "https://www.rust-lang.org".into(),
+                snippet: Some("A language empowering everyone".into()),
+                engine: "google".into(),
+                score: Some(1.0),
+                category: "general".into(),
+                language: None,
+            }],
+            query: "rust".into(),
+            elapsed_seconds: 0.4,
+            total_results: Some(1),
+            engines_used: vec!["google".into()],
+            suggestions: Vec::new(),
+            corrected_from: None,
+            instant_answer: None,
+        }
+    }
+
+    #[test]
+    fn test_token_roundtrip() {
+        let artifact = ShareArtifact::response(response()).with_author("alice");
+        let token = artifact.encode().unwrap();
+        assert!(token.starts_with("bose1:"));
+
+        let decoded = ShareArtifact::decode(&token).unwrap();
+        assert_eq!(decoded.author.as_deref(), Some("alice"));
+        match decoded.content {
+            SharedContent::Response(resp) => assert_eq!(resp.results, response().results),
+            _ => panic!("expected response"),
+        }
+    }
+
+    #[test]
+    fn test_file_roundtrip_and_plain_json() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("bose-share-{}.bose", std::process::id()));
+        let artifact = ShareArtifact::report("Findings", "- item").with_note("draft");
+        artifact.write_to(&path).unwrap();
+        assert_eq!(&std::fs::read(&path).unwrap()[..2], &GZIP_MAGIC);
+
+        let read = ShareArtifact::read_from(&path).unwrap();
+        assert_eq!(read.note.as_deref(), Some("draft"));
+
+        std::fs::write(&path, artifact.encode().unwrap()).unwrap();
+        assert!(ShareArtifact::read_from(&path).is_ok());
+
+        let json = serde_json::to_vec(&artifact).unwrap();
+        assert!(ShareArtifact::from_bytes(&json).is_ok());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_decode_rejects_invalid() {
+        assert!(ShareArtifact::decode("not-a-token").is_err());
+        assert!(ShareArtifact::decode("bose1:!!!").is_err());
+
+        let mut future = ShareArtifact::report("t", "m");
+        future.version = SHARE_FORMAT_VERSION + 1;
+        let err = ShareArtifact::from_bytes(&future.to_bytes().unwrap()).unwrap_err();
+        assert!(matches!(err, BoseError::ShareError(_)));
+    }
+
+    #[test]
+    fn test_render() {
+        let text = ShareArtifact::response(response()).render();
+        assert!(text.contains("Query: \"rust\" — 1 results"));
+        assert!(text.contains("1. Rust\n   https://www.rust-lang.org"));
+        assert!(text.contains("Engines: google"));
+
+        let report = ShareArtifact::report("Findings", "body").render();
+        assert!(report.contains("# Findings\n\nbody"));
+    }
+}
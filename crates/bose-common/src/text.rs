@@ -0,0 +1,113 @@
+//! 文字截斷工具 — 一律切在 UTF-8 字元邊界上
+//!
+//! 直接以位元組索引切字串（`&s[..200]`）遇到中日韓文字會 panic；
+//! 所有要裁短輸出的地方都應改用這裡的函式。
+
+/// 最多保留 `max_chars` 個字元
+pub fn truncate_chars(text: &str, max_chars: usize) -> &str {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
+
+/// 最多保留 `max_chars` 個字元，有截斷時盡量停在空白處並加上 `…`
+pub fn ellipsize(text: &str, max_chars: usize) -> String {
+    let cut = truncate_chars(text, max_chars);
+    if cut.len() == text.len() {
+        return text.to_string();
+    }
+    // 英文避免切在單字中間；找不到空白（例如中文）就直接切
+    let cut = match cut.rfind(char::is_whitespace) {
+        Some(space) if space > cut.len() / 2 => &cut[..space],
+        _ => cut,
+    };
+    format!("{}…", cut.trim_end())
+}
+
+/// 換行與連續空白併成一個空白，並去掉頭尾空白
+pub fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 估算 token 數：連續英數字約 4 字元一個，中日韓文字與標點各一個
+pub fn estimate_tokens(text: &str) -> usize {
+    let mut tokens = 0;
+    let mut run = 0usize;
+    for ch in text.chars() {
+        if ch.is_alphanumeric() && !is_cjk(ch) {
+            run += 1;
+            continue;
+        }
+        tokens += run.div_ceil(4);
+        run = 0;
+        if !ch.is_whitespace() {
+            tokens += 1;
+        }
+    }
+    tokens + run.div_ceil(4)
+}
+
+/// 不超過 `max_tokens`（依 `estimate_tokens` 估算）的最長前綴
+pub fn truncate_tokens(text: &str, max_tokens: usize) -> &str {
+    let mut tokens = 0;
+    let mut run = 0usize;
+    for (i, ch) in text.char_indices() {
+        if ch.is_alphanumeric() && !is_cjk(ch) {
+            run += 1;
+        } else {
+            tokens += run.div_ceil(4);
+            run = 0;
+            if !ch.is_whitespace() {
+                tokens += 1;
+            }
+        }
+        if tokens + run.div_ceil(4) > max_tokens {
+            return &text[..i];
+        }
+    }
+    text
+}
+
+/// 中日韓文字：BPE 詞表多半一字一個 token 以上，斷詞時也不以空白分隔
+pub fn is_cjk(ch: char) -> bool {
+    matches!(ch as u32,
+        0x1100..=0x11FF | 0x2E80..=0x9FFF | 0xA960..=0xA97F | 0xAC00..=0xD7AF
+        | 0xF900..=0xFAFF | 0xFF00..=0xFFEF | 0x20000..=0x3FFFD)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_chars_multibyte() {
+        assert_eq!(truncate_chars("繁體中文內容", 4), "繁體中文");
+        assert_eq!(truncate_chars("héllo", 2), "hé");
+        assert_eq!(truncate_chars("short", 10), "short");
+        assert_eq!(truncate_chars("🦀🦀", 1), "🦀");
+        assert_eq!(truncate_chars("abc", 0), "");
+    }
+
+    #[test]
+    fn test_ellipsize() {
+        assert_eq!(ellipsize("The quick brown fox", 12), "The quick…");
+        assert_eq!(ellipsize("繁體中文內容", 4), "繁體中文…");
+        assert_eq!(ellipsize("fits", 4), "fits");
+    }
+
+    #[test]
+    fn test_collapse_whitespace() {
+        assert_eq!(collapse_whitespace("  Rust\n\t async   runtime "), "Rust async runtime");
+        assert_eq!(collapse_whitespace("\n"), "");
+    }
+
+    #[test]
+    fn test_tokens() {
+        assert_eq!(estimate_tokens(&"a".repeat(400)), 100);
+        assert_eq!(estimate_tokens("繁體中文"), 4);
+        assert_eq!(truncate_tokens("繁體中文內容", 3), "繁體中");
+        assert_eq!(truncate_tokens("abcdefgh ijkl", 2), "abcdefgh ");
+        assert_eq!(truncate_tokens("fits", 5), "fits");
+    }
+}
@@ -9,26 +9,38 @@ pub struct SearchResult {
     pub engine: String,
     pub score: Option<f64>,
     pub category: String,
+    /// 偵測到的內容語言（ISO 639-1，例如 `en`、`zh`）；未偵測或不可靠時為 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
 }
 
 /// 搜尋請求參數
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchQuery {
     pub query: String,
+    #[serde(default = "default_num_results")]
     pub num_results: u32,
     pub category: Option<String>,
     pub language: Option<String>,
     pub time_range: Option<String>,
+    /// 限定使用的引擎（空 = 由 SearXNG 決定）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub engines: Vec<String>,
+}
+
+fn default_num_results() -> u32 {
+    10
 }
 
 impl SearchQuery {
     pub fn new(query: impl Into<String>) -> Self {
         Self {
             query: query.into(),
-            num_results: 10,
+            num_results: default_num_results(),
             category: None,
             language: None,
             time_range: None,
+            engines: Vec::new(),
         }
     }
 
@@ -41,6 +53,11 @@ impl SearchQuery {
         self.category = Some(cat.into());
         self
     }
+
+    pub fn with_engines(mut self, engines: Vec<String>) -> Self {
+        self.engines = engines;
+        self
+    }
 }
 
 /// 搜尋回應
@@ -51,6 +68,31 @@ pub struct SearchResponse {
     pub elapsed_seconds: f64,
     pub total_results: Option<u64>,
     pub engines_used: Vec<String>,
+    /// 拼字建議（SearXNG 的 `suggestions`）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<String>,
+    /// 自動修正時的原始查詢；`query` 為實際使用的建議
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub corrected_from: Option<String>,
+    /// 引擎直接給出的答案或資訊框（SearXNG 的 `answers` / `infoboxes`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instant_answer: Option<InstantAnswer>,
+}
+
+/// 直接答案：計算、換算、定義或知識面板
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InstantAnswer {
+    /// 資訊框標題；直接答案沒有標題
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// 資訊框的屬性（標籤、值）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<(String, String)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub engine: Option<String>,
 }
 
 #[cfg(test)]
@@ -65,6 +107,7 @@ mod tests {
         assert!(q.category.is_none());
         assert!(q.language.is_none());
         assert!(q.time_range.is_none());
+        assert!(q.engines.is_empty());
     }
 
     #[test]
@@ -76,6 +119,14 @@ mod tests {
         assert_eq!(q.category.as_deref(), Some("it"));
     }
 
+    #[test]
+    fn test_search_query_deserialize_defaults() {
+        let q: SearchQuery = serde_json::from_str(r#"{"query":"rust"}"#).unwrap();
+        assert_eq!(q.query, "rust");
+        assert_eq!(q.num_results, 10);
+        assert!(q.category.is_none());
+    }
+
     #[test]
     fn test_search_result_serialize() {
         let r = SearchResult {
@@ -85,6 +136,7 @@ mod tests {
             engine: "google".into(),
             score: Some(0.95),
             category: "general".into(),
+            language: None,
         };
         insta::assert_json_snapshot!(r);
     }
@@ -98,6 +150,7 @@ mod tests {
             engine: "bing".into(),
             score: None,
             category: "it".into(),
+            language: None,
         };
         insta::assert_json_snapshot!(r);
     }
@@ -112,11 +165,15 @@ mod tests {
                 engine: "google".into(),
                 score: Some(1.0),
                 category: "general".into(),
+                language: None,
             }],
             query: "rust".into(),
             elapsed_seconds: 0.5,
             total_results: Some(100),
             engines_used: vec!["google".into()],
+            suggestions: Vec::new(),
+            corrected_from: None,
+            instant_answer: None,
         };
         insta::assert_json_snapshot!(resp);
     }
@@ -130,6 +187,7 @@ mod tests {
             engine: "bing".into(),
             score: Some(0.5),
             category: "general".into(),
+            language: None,
         };
         let json = serde_json::to_string(&r).unwrap();
         let r2: SearchResult = serde_json::from_str(&json).unwrap();
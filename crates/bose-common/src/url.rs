@@ -0,0 +1,150 @@
+//! 網址正規化 — 去掉追蹤參數與 session id、解開轉址連結、統一大小寫與連接埠
+//!
+//! SearXNG 回傳的網址一律經過 `canonicalize` 再呈現給使用者；去重、結果比較與書籤比對使用 `dedup_key`。
+
+use ::url::Url;
+
+/// 追蹤參數（完整名稱，不分大小寫）
+const TRACKING_PARAMS: &[&str] = &[
+    "fbclid", "gclid", "dclid", "gbraid", "wbraid", "msclkid", "yclid", "mc_cid", "mc_eid", "igshid", "_hsenc",
+    "_hsmi", "mkt_tok", "ref_src",
+];
+
+/// 追蹤參數前綴
+const TRACKING_PREFIXES: &[&str] = &["utm_"];
+
+/// session id 參數
+const SESSION_PARAMS: &[&str] = &[
+    "jsessionid", "phpsessid", "sid", "sessionid", "session_id", "aspsessionid", "cfid", "cftoken", "zenid",
+];
+
+/// 轉址連結最多解開的層數
+const MAX_REDIRECT_DEPTH: usize = 3;
+
+/// 正規化網址；無法解析時回傳去掉前後空白的原字串
+///
+/// `//example.com/a` 這類省略 scheme 的網址視為 https；只有網域時不加結尾斜線。
+pub fn canonicalize(raw: &str) -> String {
+    canonicalize_relative(raw, None)
+}
+
+/// 以 `base` 解析相對網址（例如搜尋頁上的 `/l/?uddg=…`）後正規化
+pub fn canonicalize_relative(raw: &str, base: Option<&str>) -> String {
+    let trimmed = raw.trim();
+    let parsed = match base.and_then(|base| Url::parse(base).ok()) {
+        Some(base) => base.join(trimmed),
+        None if trimmed.starts_with("//") => Url::parse(&format!("https:{}", trimmed)),
+        None => Url::parse(trimmed),
+    };
+    let Ok(mut url) = parsed else {
+        return trimmed.to_string();
+    };
+    for _ in 0..MAX_REDIRECT_DEPTH {
+        match redirect_target(&url) {
+            Some(target) => url = target,
+            None => break,
+        }
+    }
+    if !matches!(url.scheme(), "http" | "https") {
+        return url.to_string();
+    }
+
+    url.set_fragment(None);
+    // Java 系統把 session id 放在路徑參數：`/page;jsessionid=ABC`
+    if let Some(index) = url.path().to_ascii_lowercase().find(";jsessionid=") {
+        let path = url.path()[..index].to_string();
+        url.set_path(&path);
+    }
+    let pairs: Vec<(String, String)> = url.query_pairs().map(|(k, v)| (k.into_owned(), v.into_owned())).collect();
+    let kept: Vec<&(String, String)> = pairs.iter().filter(|(key, _)| !is_tracking(key)).collect();
+    // 沒有要去掉的參數時保留原本的編碼
+    if kept.len() != pairs.len() || url.query() == Some("") {
+        if kept.is_empty() {
+            url.set_query(None);
+        } else {
+            url.query_pairs_mut().clear().extend_pairs(kept);
+        }
+    }
+    // 只有網域的網址不加結尾斜線（`https://example.com`）
+    let mut canonical = url.to_string();
+    if url.path() == "/" && url.query().is_none() {
+        canonical.pop();
+    }
+    canonical
+}
+
+/// 比對用的鍵：正規化後再去掉結尾斜線（沒有查詢參數時）
+pub fn dedup_key(raw: &str) -> String {
+    let mut key = canonicalize(raw);
+    if !key.contains('?') && key.ends_with('/') {
+        key.pop();
+    }
+    key
+}
+
+fn is_tracking(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    TRACKING_PARAMS.contains(&key.as_str())
+        || SESSION_PARAMS.contains(&key.as_str())
+        || TRACKING_PREFIXES.iter().any(|prefix| key.starts_with(prefix))
+}
+
+/// 搜尋引擎與社群網站的轉址連結所指向的網址
+fn redirect_target(url: &Url) -> Option<Url> {
+    let host = url.host_str()?.trim_start_matches("www.");
+    let params: &[&str] = match (host, url.path()) {
+        ("duckduckgo.com" | "html.duckduckgo.com", "/l/") => &["uddg"],
+        (host, "/url") if host.starts_with("google.") => &["q", "url"],
+        ("l.facebook.com" | "lm.facebook.com", "/l.php") => &["u"],
+        _ => return None,
+    };
+    let target = url.query_pairs().find(|(key, _)| params.contains(&key.as_ref()))?.1;
+    Url::parse(&target).ok().filter(|t| matches!(t.scheme(), "http" | "https"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strips_tracking_and_normalizes() {
+        assert_eq!(
+            canonicalize("HTTPS://Example.COM:443/Guide?utm_source=x&id=7&UTM_Medium=y&fbclid=abc#top"),
+            "https://example.com/Guide?id=7"
+        );
+        assert_eq!(canonicalize("http://example.com:8080/a?gclid=1"), "http://example.com:8080/a");
+        assert_eq!(canonicalize("https://shop.test/item;jsessionid=ABC123?PHPSESSID=z"), "https://shop.test/item");
+        // 沒有追蹤參數時保留原本的編碼
+        assert_eq!(canonicalize("https://a.com/s?q=rust+async&x"), "https://a.com/s?q=rust+async&x");
+        assert_eq!(canonicalize("  not a url "), "not a url");
+    }
+
+    #[test]
+    fn test_resolves_redirects() {
+        assert_eq!(
+            canonicalize("//duckduckgo.com/l/?uddg=https%3A%2F%2Fdocs.rs%2Ftokio%3Futm_source%3Dddg&rut=abc"),
+            "https://docs.rs/tokio"
+        );
+        assert_eq!(
+            canonicalize_relative("/url?q=https://rust-lang.org/&sa=U", Some("https://www.google.com/search?q=rust")),
+            "https://rust-lang.org"
+        );
+        assert_eq!(
+            canonicalize_relative("../b?x=1", Some("https://example.com/a/c")),
+            "https://example.com/b?x=1"
+        );
+        // 非 http 的轉址目標不解開
+        assert_eq!(
+            canonicalize("https://l.facebook.com/l.php?u=javascript:alert(1)"),
+            "https://l.facebook.com/l.php?u=javascript:alert(1)"
+        );
+    }
+
+    #[test]
+    fn test_dedup_key() {
+        assert_eq!(dedup_key("https://Example.com/docs/?utm_campaign=a"), "https://example.com/docs");
+        assert_eq!(canonicalize("https://example.com/#intro"), "https://example.com");
+        assert_eq!(dedup_key("https://example.com"), dedup_key("https://example.com/#intro"));
+        assert_eq!(dedup_key("https://example.com/?q=1"), "https://example.com/?q=1");
+    }
+}
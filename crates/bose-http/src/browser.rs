@@ -0,0 +1,88 @@
+//! Chromium 渲染器 — 以 headless Chromium 擷取截圖 / PDF 與渲染 JavaScript 頁面（`browser` feature）
+
+use crate::capture::{CaptureKind, PageRenderer};
+use bose_common::cache::BoxFuture;
+use bose_common::{BoseError, BoseResult};
+use chromiumoxide::cdp::browser_protocol::page::PrintToPdfParams;
+use chromiumoxide::page::ScreenshotParams;
+use chromiumoxide::{Browser, BrowserConfig, Page};
+use futures::StreamExt;
+use tokio::sync::OnceCell;
+
+/// 第一次擷取時才啟動瀏覽器，之後所有請求共用同一個行程
+#[derive(Default)]
+pub struct ChromiumRenderer {
+    browser: OnceCell<Browser>,
+}
+
+impl ChromiumRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    async fn browser(&self) -> BoseResult<&Browser> {
+        self.browser
+            .get_or_try_init(|| async {
+                let config = BrowserConfig::builder()
+                    .build()
+                    .map_err(BoseError::ConfigError)?;
+                let (browser, mut handler) =
+                    Browser::launch(config).await.map_err(browser_error)?;
+                tokio::spawn(async move {
+                    while let Some(event) = handler.next().await {
+                        if event.is_err() {
+                            break;
+                        }
+                    }
+                });
+                tracing::info!("Headless Chromium launched");
+                Ok(browser)
+            })
+            .await
+    }
+
+    async fn open(&self, url: &str) -> BoseResult<Page> {
+        let page = self
+            .browser()
+            .await?
+            .new_page(url)
+            .await
+            .map_err(browser_error)?;
+        page.wait_for_navigation().await.map_err(browser_error)?;
+        Ok(page)
+    }
+}
+
+impl PageRenderer for ChromiumRenderer {
+    fn capture<'a>(
+        &'a self,
+        url: &'a str,
+        kind: CaptureKind,
+    ) -> BoxFuture<'a, BoseResult<Vec<u8>>> {
+        Box::pin(async move {
+            let page = self.open(url).await?;
+            let bytes = match kind {
+                CaptureKind::Screenshot => {
+                    page.screenshot(ScreenshotParams::builder().full_page(true).build())
+                        .await
+                }
+                CaptureKind::Pdf => page.pdf(PrintToPdfParams::default()).await,
+            };
+            let _ = page.close().await;
+            bytes.map_err(browser_error)
+        })
+    }
+
+    fn render_html<'a>(&'a self, url: &'a str) -> BoxFuture<'a, BoseResult<String>> {
+        Box::pin(async move {
+            let page = self.open(url).await?;
+            let html = page.content().await;
+            let _ = page.close().await;
+            html.map_err(browser_error)
+        })
+    }
+}
+
+fn browser_error(e: chromiumoxide::error::CdpError) -> BoseError {
+    BoseError::BrowserError(e.to_string())
+}
@@ -0,0 +1,221 @@
+//! 證據保存 — 擷取結果頁面的截圖 / PDF，與 HTML 快照一起存入證據目錄
+//!
+//! 目錄配置：`<root>/<url 雜湊>/<擷取時間>/{snapshot.html, screenshot.png, page.pdf, meta.json}`，
+//! 紀錄的 `id` 為 `<url 雜湊>/<擷取時間>`，不對外揭露伺服器上的路徑。
+//! 瀏覽器擷取透過 `PageRenderer` 接入，啟用 `browser` feature 時提供 Chromium 實作；
+//! 同一個整合點也用於 JavaScript 頁面的渲染退路（見 `/extract`）。
+
+use bose_common::cache::BoxFuture;
+use bose_common::{BoseError, BoseResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+/// 擷取類型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureKind {
+    Screenshot,
+    Pdf,
+}
+
+impl CaptureKind {
+    fn file_name(self) -> &'static str {
+        match self {
+            CaptureKind::Screenshot => "screenshot.png",
+            CaptureKind::Pdf => "page.pdf",
+        }
+    }
+}
+
+/// 以瀏覽器渲染頁面的整合點
+pub trait PageRenderer: Send + Sync {
+    /// 擷取頁面的截圖或 PDF
+    fn capture<'a>(&'a self, url: &'a str, kind: CaptureKind)
+    -> BoxFuture<'a, BoseResult<Vec<u8>>>;
+
+    /// 執行頁面腳本後回傳渲染完成的 HTML
+    fn render_html<'a>(&'a self, url: &'a str) -> BoxFuture<'a, BoseResult<String>>;
+}
+
+/// 單一網址的證據紀錄（同時寫入 `meta.json`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvidenceRecord {
+    pub url: String,
+    pub captured_at: DateTime<Utc>,
+    /// 證據識別碼（相對於證據目錄，見 `EvidenceStore::dir`）
+    pub id: String,
+    /// 已寫入的檔案名稱
+    pub files: Vec<String>,
+    /// 個別擷取失敗的原因（快照本身成功）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// 證據目錄
+#[derive(Debug, Clone)]
+pub struct EvidenceStore {
+    root: PathBuf,
+}
+
+impl EvidenceStore {
+    pub fn new(root: impl Into<PathBuf>) -> io::Result<Self> {
+        let root = root.into();
+        std::fs::create_dir_all(&root)?;
+        Ok(Self { root })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// 證據識別碼對應的目錄
+    pub fn dir(&self, id: &str) -> PathBuf {
+        self.root.join(id)
+    }
+
+    /// 保存 HTML 快照，並依序執行要求的擷取；擷取失敗只記錄在 `errors`
+    pub async fn preserve(
+        &self,
+        url: &str,
+        html: &str,
+        renderer: Option<&dyn PageRenderer>,
+        kinds: &[CaptureKind],
+    ) -> io::Result<EvidenceRecord> {
+        let captured_at = Utc::now();
+        let id = format!(
+            "{}/{}",
+            url_digest(url),
+            captured_at.format("%Y%m%dT%H%M%S%.3fZ")
+        );
+        let dir = self.dir(&id);
+        fs::create_dir_all(&dir).await?;
+
+        fs::write(dir.join("snapshot.html"), html).await?;
+        let mut record = EvidenceRecord {
+            url: url.to_string(),
+            captured_at,
+            id,
+            files: vec!["snapshot.html".to_string()],
+            errors: Vec::new(),
+        };
+
+        for kind in kinds {
+            let outcome = match renderer {
+                Some(renderer) => renderer.capture(url, *kind).await,
+                None => Err(BoseError::ConfigError("no page renderer configured".into())),
+            };
+            match outcome {
+                Ok(bytes) => {
+                    fs::write(dir.join(kind.file_name()), bytes).await?;
+                    record.files.push(kind.file_name().to_string());
+                }
+                Err(e) => record.errors.push(format!("{}: {e}", kind.file_name())),
+            }
+        }
+
+        let meta = serde_json::to_vec_pretty(&record).map_err(io::Error::other)?;
+        fs::write(dir.join("meta.json"), meta).await?;
+        Ok(record)
+    }
+}
+
+/// 以 FNV-1a 雜湊作為網址的目錄名稱（跨版本穩定）
+fn url_digest(url: &str) -> String {
+    bose_common::hash::fnv1a_hex(url)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// 渲染後的測試頁面
+    pub(crate) const RENDERED_HTML: &str = "<html><body><article>Rendered client side content about \
+        Rust async runtimes, executors, wakers, pinning and cancellation safety in \
+        production services with many concurrent tasks.</article></body></html>";
+
+    /// 回傳固定位元組的測試用渲染器；`fail_pdf` 時 PDF 擷取失敗
+    pub(crate) struct FakeRenderer {
+        pub fail_pdf: bool,
+    }
+
+    impl PageRenderer for FakeRenderer {
+        fn capture<'a>(
+            &'a self,
+            _url: &'a str,
+            kind: CaptureKind,
+        ) -> BoxFuture<'a, BoseResult<Vec<u8>>> {
+            Box::pin(async move {
+                match kind {
+                    CaptureKind::Pdf if self.fail_pdf => {
+                        Err(BoseError::BrowserError("print failed".into()))
+                    }
+                    CaptureKind::Pdf => Ok(b"%PDF-1.7".to_vec()),
+                    CaptureKind::Screenshot => Ok(b"\x89PNG".to_vec()),
+                }
+            })
+        }
+
+        fn render_html<'a>(&'a self, _url: &'a str) -> BoxFuture<'a, BoseResult<String>> {
+            Box::pin(async { Ok(RENDERED_HTML.to_string()) })
+        }
+    }
+
+    pub(crate) fn temp_root(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("bose-evidence-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_preserve_snapshot_and_captures() {
+        let store = EvidenceStore::new(temp_root("preserve")).unwrap();
+        let renderer = FakeRenderer { fail_pdf: true };
+        let record = store
+            .preserve(
+                "https://example.com/a",
+                "<html>a</html>",
+                Some(&renderer),
+                &[CaptureKind::Screenshot, CaptureKind::Pdf],
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(record.files, vec!["snapshot.html", "screenshot.png"]);
+        assert_eq!(record.errors.len(), 1);
+        assert!(record.id.starts_with(&url_digest("https://example.com/a")));
+        let dir = store.dir(&record.id);
+        assert_eq!(
+            std::fs::read(dir.join("screenshot.png")).unwrap(),
+            b"\x89PNG"
+        );
+        let meta: EvidenceRecord =
+            serde_json::from_slice(&std::fs::read(dir.join("meta.json")).unwrap()).unwrap();
+        assert_eq!(meta.url, "https://example.com/a");
+        assert_eq!(meta.id, record.id);
+        let _ = std::fs::remove_dir_all(store.root());
+    }
+
+    #[tokio::test]
+    async fn test_preserve_without_renderer() {
+        let store = EvidenceStore::new(temp_root("no-renderer")).unwrap();
+        let record = store
+            .preserve("https://example.com", "<p>x</p>", None, &[CaptureKind::Pdf])
+            .await
+            .unwrap();
+        assert_eq!(record.files, vec!["snapshot.html"]);
+        assert!(record.errors[0].contains("no page renderer"));
+        let _ = std::fs::remove_dir_all(store.root());
+    }
+
+    #[test]
+    fn test_url_digest_stable() {
+        assert_eq!(
+            url_digest("https://example.com"),
+            url_digest("https://example.com")
+        );
+        assert_ne!(url_digest("https://a.com"), url_digest("https://b.com"));
+    }
+}
@@ -0,0 +1,95 @@
+use bose_common::config::env_parse;
+
+/// HTTP 伺服器配置
+#[derive(Debug, Clone)]
+pub struct HttpConfig {
+    pub bind_addr: String,
+    pub cache_ttl_secs: u64,
+    pub cache_max_entries: usize,
+    pub rate_limit_rps: f64,
+    pub rate_limit_burst: u32,
+    pub stream_engines: Vec<String>,
+    /// 共用快取 / 限流的 Redis（需啟用 `redis` feature）
+    pub redis_url: Option<String>,
+    /// 證據目錄（未設定時停用 `/evidence`）
+    pub evidence_dir: Option<String>,
+    /// 分享檔上傳端點（需啟用 `paste` feature）
+    pub paste_url: Option<String>,
+    /// 允許代抓私有與迴路位址（預設拒絕，避免 SSRF）
+    pub allow_private_urls: bool,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            bind_addr: "127.0.0.1:3000".to_string(),
+            cache_ttl_secs: 300,
+            cache_max_entries: 1000,
+            rate_limit_rps: 10.0,
+            rate_limit_burst: 20,
+            stream_engines: ["google", "bing", "duckduckgo", "brave"]
+                .map(String::from)
+                .to_vec(),
+            redis_url: None,
+            evidence_dir: None,
+            paste_url: None,
+            allow_private_urls: false,
+        }
+    }
+}
+
+impl HttpConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            bind_addr: std::env::var("BOSE_HTTP_ADDR").unwrap_or(defaults.bind_addr),
+            cache_ttl_secs: env_parse("HTTP_CACHE_TTL_SECS").unwrap_or(defaults.cache_ttl_secs),
+            cache_max_entries: env_parse("HTTP_CACHE_MAX_ENTRIES")
+                .unwrap_or(defaults.cache_max_entries),
+            rate_limit_rps: env_parse("HTTP_RATE_LIMIT_RPS").unwrap_or(defaults.rate_limit_rps),
+            rate_limit_burst: env_parse("HTTP_RATE_LIMIT_BURST")
+                .unwrap_or(defaults.rate_limit_burst),
+            stream_engines: std::env::var("HTTP_STREAM_ENGINES")
+                .ok()
+                .map(|v| {
+                    v.split(',')
+                        .map(|e| e.trim().to_string())
+                        .filter(|e| !e.is_empty())
+                        .collect()
+                })
+                .unwrap_or(defaults.stream_engines),
+            redis_url: std::env::var("REDIS_URL").ok().filter(|v| !v.is_empty()),
+            evidence_dir: std::env::var("HTTP_EVIDENCE_DIR")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            paste_url: std::env::var("SHARE_PASTE_URL")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            allow_private_urls: env_parse("HTTP_ALLOW_PRIVATE_URLS")
+                .unwrap_or(defaults.allow_private_urls),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_http_config_default() {
+        let c = HttpConfig::default();
+        assert_eq!(c.bind_addr, "127.0.0.1:3000");
+        assert_eq!(c.cache_ttl_secs, 300);
+        assert_eq!(c.cache_max_entries, 1000);
+        assert_eq!(c.rate_limit_burst, 20);
+        assert_eq!(c.stream_engines.len(), 4);
+        assert!(c.redis_url.is_none());
+        assert!(!c.allow_private_urls);
+    }
+
+    #[test]
+    fn test_http_config_from_env() {
+        let c = HttpConfig::from_env();
+        assert_eq!(c.cache_max_entries, 1000);
+    }
+}
@@ -0,0 +1,154 @@
+use bose_common::text::{self, collapse_whitespace};
+use crate::structured::extract_structured;
+use serde::Serialize;
+
+/// 清理後少於此字數視為空頁面（多半是 JavaScript 應用的外殼）
+pub const MIN_CONTENT_WORDS: usize = 20;
+
+/// JS 應用外殼常見的 noscript 提示
+const JS_REQUIRED_MARKERS: &[&str] = &[
+    "enable javascript",
+    "javascript is required",
+    "javascript is disabled",
+    "requires javascript",
+];
+
+/// 單一頁面的提取結果
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ExtractedPage {
+    pub url: String,
+    pub title: Option<String>,
+    pub content: String,
+    /// 內容是否來自瀏覽器渲染（靜態提取為空時的退路）
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub rendered: bool,
+    /// JSON-LD / OpenGraph / microdata（見 `structured`）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub structured: Option<serde_json::Value>,
+}
+
+impl ExtractedPage {
+    /// 清理後幾乎沒有內容，或只剩「請啟用 JavaScript」之類的提示
+    pub fn is_empty_after_cleaning(&self) -> bool {
+        let words = self.content.split_whitespace().count();
+        let cjk = self.content.chars().filter(|c| !c.is_ascii()).count();
+        if words + cjk / 2 < MIN_CONTENT_WORDS {
+            return true;
+        }
+        let lower = self.content.to_lowercase();
+        words < MIN_CONTENT_WORDS * 5 && JS_REQUIRED_MARKERS.iter().any(|m| lower.contains(m))
+    }
+}
+
+/// 從 HTML 提取標題與純文字內容
+pub fn extract_page(url: &str, html: &str, max_chars: usize) -> ExtractedPage {
+    let content = text::truncate_chars(&strip_tags(html), max_chars).to_string();
+    ExtractedPage {
+        url: url.to_string(),
+        title: extract_title(html),
+        content,
+        rendered: false,
+        structured: extract_structured(html),
+    }
+}
+
+fn extract_title(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let start = lower.find("<title")?;
+    let open_end = lower[start..].find('>')? + start + 1;
+    let close = lower[open_end..].find("</title>")? + open_end;
+    let title = collapse_whitespace(&html[open_end..close]);
+    (!title.is_empty()).then_some(title)
+}
+
+/// 移除標籤與 script/style 區塊，並正規化空白
+fn strip_tags(html: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut tag = String::new();
+    let mut in_tag = false;
+    let mut skipping = false;
+
+    for ch in html.chars() {
+        match ch {
+            '<' => {
+                in_tag = true;
+                tag.clear();
+            }
+            '>' if in_tag => {
+                let name = tag.trim_start().to_ascii_lowercase();
+                if name.starts_with("script") || name.starts_with("style") {
+                    skipping = true;
+                } else if name.starts_with("/script") || name.starts_with("/style") {
+                    skipping = false;
+                }
+                in_tag = false;
+                out.push(' ');
+            }
+            _ if in_tag => tag.push(ch),
+            _ if !skipping => out.push(ch),
+            _ => {}
+        }
+    }
+
+    collapse_whitespace(&out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_page() {
+        let html = "<html><head><title> Rust  Book </title><style>p{}</style></head>\
+                    <body><h1>Intro</h1><p>Hello <b>world</b></p><script>x()</script></body></html>";
+        let page = extract_page("https://example.com", html, 1000);
+        assert_eq!(page.title.as_deref(), Some("Rust Book"));
+        assert!(page.content.contains("Intro Hello world"));
+        assert!(!page.content.contains("x()"));
+        assert!(!page.content.contains("p{}"));
+    }
+
+    #[test]
+    fn test_extract_structured() {
+        let html = r#"<meta property="og:type" content="article"><p>Body</p>"#;
+        let page = extract_page("https://example.com", html, 1000);
+        assert_eq!(page.structured.unwrap()["opengraph"]["type"], "article");
+        assert!(
+            extract_page("https://example.com", "<p>x</p>", 10)
+                .structured
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_extract_max_chars() {
+        let page = extract_page("https://example.com", "<p>abcdefghij</p>", 4);
+        assert_eq!(page.content, "abcd");
+        assert!(page.title.is_none());
+    }
+
+    #[test]
+    fn test_empty_after_cleaning() {
+        let shell =
+            "<html><body><div id=\"root\"></div><script src=\"app.js\"></script></body></html>";
+        assert!(extract_page("https://example.com", shell, 1000).is_empty_after_cleaning());
+
+        let noscript = format!(
+            "<noscript>You need to enable JavaScript to run this app.</noscript><p>{}</p>",
+            "menu ".repeat(30)
+        );
+        assert!(extract_page("https://example.com", &noscript, 5000).is_empty_after_cleaning());
+
+        let article = format!(
+            "<p>{}</p>",
+            "Rust ownership explained in depth. ".repeat(10)
+        );
+        assert!(!extract_page("https://example.com", &article, 5000).is_empty_after_cleaning());
+    }
+
+    #[test]
+    fn test_extract_multibyte() {
+        let page = extract_page("https://example.com", "<p>繁體中文內容</p>", 3);
+        assert_eq!(page.content, "繁體中");
+    }
+}
@@ -0,0 +1,31 @@
+//! Bose HTTP — REST API 伺服器
+//!
+//! 以 JSON 透過 HTTP 暴露 `/search`、`/extract`、`/triage`、`/health`，給非 MCP 客戶端使用；
+//! `/sites` 批次解析 favicon 與網站名稱；`/ws/search` 以 WebSocket 逐引擎串流結果；`/evidence` 保存頁面快照與截圖 / PDF；
+//! 設定檔 `[[schedule]]` 的排程搜尋在背景執行，新結果推送到 webhook。
+
+#[cfg(feature = "browser")]
+pub mod browser;
+pub mod capture;
+pub mod config;
+pub mod extract;
+pub mod rate_limit;
+pub mod routes;
+pub mod scheduler;
+pub mod site_info;
+pub mod stream;
+pub mod structured;
+pub mod triage;
+
+pub use bose_common::memory_cache::ResponseCache;
+#[cfg(feature = "browser")]
+pub use browser::ChromiumRenderer;
+pub use capture::{CaptureKind, EvidenceRecord, EvidenceStore, PageRenderer};
+pub use config::HttpConfig;
+pub use rate_limit::RateLimiter;
+pub use routes::{AppState, router};
+pub use scheduler::Scheduler;
+pub use site_info::{SiteInfo, SiteInfoResolver};
+pub use stream::{StreamFrame, stream_search};
+pub use structured::extract_structured;
+pub use triage::{TriageRow, classify, render_table, triage_url};
@@ -0,0 +1,159 @@
+use bose_common::BoseConfig;
+use bose_common::cache::{CacheBackend, RateLimitBackend};
+use bose_common::event_log::EventLog;
+use bose_common::net::{PublicResolver, public_redirect_policy};
+use bose_common::share::ShareArtifact;
+use bose_http::{
+    AppState, EvidenceStore, HttpConfig, PageRenderer, RateLimiter, ResponseCache, Scheduler,
+    SiteInfoResolver, router,
+};
+use bose_searxng::{SearxngClient, SearxngCluster};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter("bose=info")
+        .init();
+
+    // `bose-http view <檔案或 bose1: 文字>`：顯示分享檔後結束
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let [command, target] = args.as_slice()
+        && command == "view"
+    {
+        print!("{}", view(target)?.render());
+        return Ok(());
+    }
+
+    let config = BoseConfig::from_env_or_file()?;
+    let http_config = HttpConfig::from_env();
+
+    let (cache, limiter) = backends(&http_config).await?;
+
+    let client = SearxngClient::new(&config)?;
+    if let Some(cluster) = client.cluster() {
+        SearxngCluster::spawn_prober(
+            cluster,
+            Duration::from_secs(config.searxng_probe_interval_secs),
+        );
+    }
+    let client = match EventLog::from_config(&config)? {
+        Some(events) => {
+            tracing::info!(path = %events.path().display(), "Search event log enabled");
+            client.with_event_log(Arc::new(events))
+        }
+        None => client,
+    };
+    #[cfg(feature = "chaos")]
+    let client = match bose_searxng::FaultInjector::from_env()? {
+        Some(faults) => client.with_fault_injector(Arc::new(faults)),
+        None => client,
+    };
+
+    let state = Arc::new(AppState {
+        client,
+        http: http_client(&config, true)?,
+        fetch_http: http_client(&config, http_config.allow_private_urls)?,
+        allow_private_urls: http_config.allow_private_urls,
+        cache,
+        limiter,
+        stream_engines: http_config.stream_engines.clone(),
+        renderer: renderer(),
+        evidence: http_config
+            .evidence_dir
+            .as_ref()
+            .map(EvidenceStore::new)
+            .transpose()?,
+        // 網站名稱與 favicon 很少變動，快取一天
+        sites: SiteInfoResolver::new(Duration::from_secs(24 * 60 * 60))
+            .with_max_entries(http_config.cache_max_entries)
+            .with_private_addresses(http_config.allow_private_urls),
+        paste_url: http_config.paste_url.clone(),
+    });
+
+    if !config.schedule.is_empty() {
+        let scheduler = Arc::new(Scheduler::new(
+            state.client.clone(),
+            state.http.clone(),
+            config.default_num_results,
+        ));
+        scheduler.spawn(config.schedule.clone());
+        tracing::info!(jobs = config.schedule.len(), "Scheduled searches enabled");
+    }
+
+    let listener = tokio::net::TcpListener::bind(&http_config.bind_addr).await?;
+    tracing::info!(
+        addr = %http_config.bind_addr,
+        searxng = %config.searxng_url,
+        "Bose HTTP Server starting"
+    );
+
+    axum::serve(listener, router(state)).await?;
+    Ok(())
+}
+
+/// 對外抓取用的客戶端；`allow_private` 為 false 時連線與轉址只允許公開位址
+fn http_client(config: &BoseConfig, allow_private: bool) -> anyhow::Result<reqwest::Client> {
+    let builder = reqwest::Client::builder()
+        .timeout(Duration::from_secs(config.request_timeout_secs))
+        .user_agent("bose-search/0.1");
+    let builder = if allow_private {
+        builder
+    } else {
+        builder
+            .dns_resolver(Arc::new(PublicResolver))
+            .redirect(public_redirect_policy())
+    };
+    Ok(config.proxy.apply(builder, "fetch")?.build()?)
+}
+
+fn view(target: &str) -> anyhow::Result<ShareArtifact> {
+    let artifact = if target.starts_with("bose1:") {
+        ShareArtifact::decode(target)?
+    } else {
+        ShareArtifact::read_from(target)?
+    };
+    Ok(artifact)
+}
+
+/// 依配置選擇快取與限流後端：有 `REDIS_URL` 時多副本共用 Redis，否則使用行程內記憶體
+async fn backends(
+    config: &HttpConfig,
+) -> anyhow::Result<(Arc<dyn CacheBackend>, Arc<dyn RateLimitBackend>)> {
+    #[cfg(feature = "redis")]
+    if let Some(url) = &config.redis_url {
+        let backend = Arc::new(
+            bose_common::redis_cache::RedisBackend::connect(url, "bose", config.cache_ttl_secs)
+                .await?
+                .with_rate_limit(config.rate_limit_rps, config.rate_limit_burst),
+        );
+        tracing::info!("Using Redis cache and rate-limit backend");
+        return Ok((backend.clone(), backend));
+    }
+
+    #[cfg(not(feature = "redis"))]
+    if config.redis_url.is_some() {
+        tracing::warn!("REDIS_URL is set but bose-http was built without the `redis` feature");
+    }
+
+    let cache = Arc::new(ResponseCache::new(
+        Duration::from_secs(config.cache_ttl_secs),
+        config.cache_max_entries,
+    ));
+    let limiter = Arc::new(RateLimiter::new(
+        config.rate_limit_rps,
+        config.rate_limit_burst,
+    ));
+    Ok((cache, limiter))
+}
+
+#[cfg(feature = "browser")]
+fn renderer() -> Option<Arc<dyn PageRenderer>> {
+    Some(Arc::new(bose_http::ChromiumRenderer::new()))
+}
+
+#[cfg(not(feature = "browser"))]
+fn renderer() -> Option<Arc<dyn PageRenderer>> {
+    None
+}
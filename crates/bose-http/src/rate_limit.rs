@@ -0,0 +1,64 @@
+use bose_common::cache::{BoxFuture, RateLimitBackend};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// 行程內 Token Bucket 速率限制器（所有請求共用，預設後端）
+pub struct RateLimiter {
+    state: Mutex<(f64, Instant)>,
+    max_tokens: f64,
+    refill_rate: f64,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_second: f64, burst: u32) -> Self {
+        Self {
+            state: Mutex::new((burst as f64, Instant::now())),
+            max_tokens: burst as f64,
+            refill_rate: requests_per_second,
+        }
+    }
+
+    /// 嘗試取得一個 token，不阻塞
+    pub fn try_acquire(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let (tokens, last_refill) = &mut *state;
+        let now = Instant::now();
+        let elapsed = now.duration_since(*last_refill).as_secs_f64();
+        *tokens = (*tokens + elapsed * self.refill_rate).min(self.max_tokens);
+        *last_refill = now;
+
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl RateLimitBackend for RateLimiter {
+    fn try_acquire(&self) -> BoxFuture<'_, bool> {
+        Box::pin(async move { RateLimiter::try_acquire(self) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_exhaustion() {
+        let limiter = RateLimiter::new(0.0, 2);
+        assert!(limiter.try_acquire());
+        assert!(limiter.try_acquire());
+        assert!(!limiter.try_acquire());
+    }
+
+    #[test]
+    fn test_refill() {
+        let limiter = RateLimiter::new(1000.0, 1);
+        assert!(limiter.try_acquire());
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert!(limiter.try_acquire());
+    }
+}
@@ -0,0 +1,1023 @@
+use crate::capture::{CaptureKind, EvidenceRecord, EvidenceStore, PageRenderer};
+use crate::extract::{ExtractedPage, extract_page};
+use crate::site_info::{SiteInfo, SiteInfoResolver};
+use crate::stream::{StreamFrame, stream_search};
+use crate::triage::{TriageRow, render_table, triage_url};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{OriginalUri, Query, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use bose_common::cache::{CacheBackend, RateLimitBackend};
+use bose_common::event_log::SearchEvent;
+use bose_common::feed::FeedFormat;
+use bose_common::share::ShareArtifact;
+use bose_common::{BoseError, SearchQuery, SearchResponse};
+use bose_searxng::SearxngClient;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 單次 `/extract` 請求允許的最大 URL 數
+const MAX_EXTRACT_URLS: usize = 20;
+const DEFAULT_EXTRACT_CHARS: usize = 20_000;
+/// 單次 `/triage` 請求允許的最大 URL 數
+const MAX_TRIAGE_URLS: usize = 50;
+/// 單次 `/sites` 請求允許的最大 URL 數（通常是一整頁搜尋結果）
+const MAX_SITE_URLS: usize = 100;
+
+/// 所有 handler 共用的狀態
+pub struct AppState {
+    pub client: SearxngClient,
+    /// 排程 webhook 與分享檔上傳等由設定決定目標的請求
+    pub http: reqwest::Client,
+    /// 代抓呼叫端提供的網址（`/extract`、`/evidence`、`/triage`、`/sites`）；
+    /// 預設只連公開位址（見 `bose_common::net`）
+    pub fetch_http: reqwest::Client,
+    /// 允許代抓私有與迴路位址（`HTTP_ALLOW_PRIVATE_URLS`，供內網部署）
+    pub allow_private_urls: bool,
+    /// 回應快取（預設 `ResponseCache`，多副本部署可換成共用後端）
+    pub cache: Arc<dyn CacheBackend>,
+    /// 限流（預設 `RateLimiter`）
+    pub limiter: Arc<dyn RateLimitBackend>,
+    /// 串流搜尋預設扇出的引擎
+    pub stream_engines: Vec<String>,
+    /// 瀏覽器渲染器（截圖 / PDF）
+    pub renderer: Option<Arc<dyn PageRenderer>>,
+    /// 證據目錄；None 時 `/evidence` 回傳 503
+    pub evidence: Option<EvidenceStore>,
+    /// favicon / 網站名稱解析快取
+    pub sites: SiteInfoResolver,
+    /// 分享檔上傳端點（`paste` feature）
+    pub paste_url: Option<String>,
+}
+
+pub fn router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/search", post(search))
+        .route("/extract", post(extract))
+        .route("/triage", post(triage))
+        .route("/evidence", post(evidence))
+        .route("/sites", post(sites))
+        .route("/share", post(share))
+        .route("/feed", get(feed))
+        .route("/health", get(health))
+        .route("/metrics", get(metrics))
+        .route("/ws/search", get(ws_search))
+        .with_state(state)
+}
+
+/// API 錯誤，序列化為 `{"error": "..."}`
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    fn rate_limited() -> Self {
+        Self::new(StatusCode::TOO_MANY_REQUESTS, "Rate limit exceeded")
+    }
+}
+
+impl From<BoseError> for ApiError {
+    fn from(e: BoseError) -> Self {
+        let status = match e {
+            BoseError::InvalidQuery(_) => StatusCode::BAD_REQUEST,
+            BoseError::ShareError(_) => StatusCode::BAD_REQUEST,
+            BoseError::ConfigError(_) | BoseError::IoError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_GATEWAY,
+        };
+        Self::new(status, e.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = Json(serde_json::json!({ "error": self.message }));
+        (self.status, body).into_response()
+    }
+}
+
+async fn search(
+    State(state): State<Arc<AppState>>,
+    Json(query): Json<SearchQuery>,
+) -> Result<Json<SearchResponse>, ApiError> {
+    Ok(Json(cached_search(&state, &query).await?))
+}
+
+/// 先查快取，未命中才消耗限流額度並查詢 SearXNG
+async fn cached_search(state: &AppState, query: &SearchQuery) -> Result<SearchResponse, ApiError> {
+    if query.query.trim().is_empty() {
+        return Err(BoseError::InvalidQuery("query is empty".into()).into());
+    }
+
+    if let Some(cached) = state.cache.get(query).await {
+        tracing::debug!(query = %query.query, "Cache hit");
+        // 快取在客戶端之外，命中時由這裡補記事件
+        if let Some(events) = state.client.event_log() {
+            let event = SearchEvent::success("searxng", query, &cached, true, Duration::ZERO);
+            if let Err(e) = events.append(event).await {
+                tracing::warn!(error = %e, "Failed to write search event");
+            }
+        }
+        return Ok(cached);
+    }
+
+    if !state.limiter.try_acquire().await {
+        return Err(ApiError::rate_limited());
+    }
+
+    let resp = state.client.search(query).await?;
+    state.cache.insert(query, resp.clone()).await;
+    Ok(resp)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FeedParams {
+    pub q: String,
+    /// `rss`（預設）或 `atom`
+    pub format: Option<String>,
+    pub category: Option<String>,
+    pub language: Option<String>,
+    pub time_range: Option<String>,
+    pub num_results: Option<u32>,
+}
+
+/// 以 RSS / Atom 輸出查詢的最新結果，供閱讀器訂閱
+async fn feed(
+    State(state): State<Arc<AppState>>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+    Query(params): Query<FeedParams>,
+) -> Result<Response, ApiError> {
+    let format = match params.format.as_deref() {
+        Some(f) => f
+            .parse::<FeedFormat>()
+            .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e))?,
+        None => FeedFormat::default(),
+    };
+
+    let mut query = SearchQuery::new(params.q);
+    query.category = params.category;
+    query.language = params.language;
+    query.time_range = params.time_range;
+    if let Some(n) = params.num_results {
+        query.num_results = n;
+    }
+
+    let resp = cached_search(&state, &query).await?;
+
+    let host = headers
+        .get(header::HOST)
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or("localhost");
+    let link = format!("http://{host}{uri}");
+    let body = format.render(&resp, &link, chrono::Utc::now());
+
+    Ok(([(header::CONTENT_TYPE, format.content_type())], body).into_response())
+}
+
+async fn ws_search(ws: WebSocketUpgrade, State(state): State<Arc<AppState>>) -> Response {
+    ws.on_upgrade(move |socket| handle_stream(socket, state))
+}
+
+/// 第一則文字訊息為 JSON `SearchQuery`，之後逐一送出 `StreamFrame`
+async fn handle_stream(mut socket: WebSocket, state: Arc<AppState>) {
+    let query = match socket.recv().await {
+        Some(Ok(Message::Text(text))) => {
+            serde_json::from_str::<SearchQuery>(&text).map_err(|e| format!("Invalid query: {e}"))
+        }
+        _ => return,
+    };
+
+    let query = query.and_then(|q| {
+        if q.query.trim().is_empty() {
+            Err("query is empty".to_string())
+        } else {
+            Ok(q)
+        }
+    });
+
+    let query = match query {
+        Ok(q) => q,
+        Err(error) => {
+            let _ = send_error(&mut socket, error).await;
+            return;
+        }
+    };
+
+    if let Some(response) = state.cache.get(&query).await {
+        let frame = StreamFrame::Complete { response: Box::new(response) };
+        let _ = send_frame(&mut socket, &frame).await;
+        return;
+    }
+    if !state.limiter.try_acquire().await {
+        let _ = send_error(&mut socket, "Rate limit exceeded".to_string()).await;
+        return;
+    }
+
+    let engines = if query.engines.is_empty() {
+        state.stream_engines.clone()
+    } else {
+        query.engines.clone()
+    };
+    let mut rx = stream_search(state.client.clone(), query.clone(), engines);
+
+    while let Some(frame) = rx.recv().await {
+        if let StreamFrame::Complete { ref response } = frame {
+            state.cache.insert(&query, (**response).clone()).await;
+        }
+        if send_frame(&mut socket, &frame).await.is_err() {
+            return;
+        }
+    }
+    let _ = socket.send(Message::Close(None)).await;
+}
+
+async fn send_frame(socket: &mut WebSocket, frame: &StreamFrame) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(frame).unwrap_or_default();
+    socket.send(Message::Text(text.into())).await
+}
+
+async fn send_error(socket: &mut WebSocket, error: String) -> Result<(), axum::Error> {
+    send_frame(
+        socket,
+        &StreamFrame::Error {
+            engine: None,
+            error,
+        },
+    )
+    .await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExtractRequest {
+    pub urls: Vec<String>,
+    pub max_chars: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExtractFailure {
+    pub url: String,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExtractResponse {
+    pub results: Vec<ExtractedPage>,
+    pub failed: Vec<ExtractFailure>,
+}
+
+async fn extract(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ExtractRequest>,
+) -> Result<Json<ExtractResponse>, ApiError> {
+    if req.urls.is_empty() || req.urls.len() > MAX_EXTRACT_URLS {
+        return Err(BoseError::InvalidQuery(format!(
+            "urls must contain 1..={MAX_EXTRACT_URLS} entries"
+        ))
+        .into());
+    }
+    if !state.limiter.try_acquire().await {
+        return Err(ApiError::rate_limited());
+    }
+
+    let max_chars = req.max_chars.unwrap_or(DEFAULT_EXTRACT_CHARS);
+    let fetches = req.urls.iter().map(|url| async {
+        guard_url(&state, url).await?;
+        fetch_page(&state.fetch_http, state.renderer.as_deref(), url, max_chars).await
+    });
+    let outcomes = futures::future::join_all(fetches).await;
+
+    let mut resp = ExtractResponse {
+        results: Vec::new(),
+        failed: Vec::new(),
+    };
+    for (url, outcome) in req.urls.into_iter().zip(outcomes) {
+        match outcome {
+            Ok(page) => resp.results.push(page),
+            Err(e) => {
+                tracing::warn!(%url, error = %e, "Extract failed");
+                resp.failed.push(ExtractFailure {
+                    url,
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+    Ok(Json(resp))
+}
+
+/// 靜態提取；清理後為空且有渲染器時，改以瀏覽器渲染後再提取一次
+async fn fetch_page(
+    http: &reqwest::Client,
+    renderer: Option<&dyn PageRenderer>,
+    url: &str,
+    max_chars: usize,
+) -> Result<ExtractedPage, BoseError> {
+    let html = fetch_html(http, url).await?;
+    let page = extract_page(url, &html, max_chars);
+    let Some(renderer) = renderer.filter(|_| page.is_empty_after_cleaning()) else {
+        return Ok(page);
+    };
+
+    match renderer.render_html(url).await {
+        Ok(rendered) => {
+            let mut fallback = extract_page(url, &rendered, max_chars);
+            if fallback.content.chars().count() <= page.content.chars().count() {
+                return Ok(page);
+            }
+            fallback.rendered = true;
+            tracing::debug!(%url, "Static extraction empty, used rendered page");
+            Ok(fallback)
+        }
+        Err(e) => {
+            tracing::warn!(%url, error = %e, "Render fallback failed");
+            Ok(page)
+        }
+    }
+}
+
+async fn fetch_html(http: &reqwest::Client, url: &str) -> Result<String, BoseError> {
+    let resp = http.get(url).send().await?;
+    if !resp.status().is_success() {
+        return Err(BoseError::UpstreamError(format!("HTTP {}", resp.status())));
+    }
+    Ok(resp.text().await?)
+}
+
+/// 代抓前確認網址只連到公開位址（`allow_private_urls` 時略過）
+async fn guard_url(state: &AppState, url: &str) -> Result<(), BoseError> {
+    if state.allow_private_urls {
+        return Ok(());
+    }
+    bose_common::net::ensure_public(url).await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShareRequest {
+    #[serde(flatten)]
+    pub query: SearchQuery,
+    pub author: Option<String>,
+    pub note: Option<String>,
+    /// 上傳到 paste 服務（需 `paste` feature 與 `SHARE_PASTE_URL`）
+    #[serde(default)]
+    pub upload: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShareResponse {
+    /// `bose1:` 文字形式，可用 `bose-http view` 開啟
+    pub token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// 執行搜尋並將回應封裝為可分享的證據
+async fn share(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ShareRequest>,
+) -> Result<Json<ShareResponse>, ApiError> {
+    let response = cached_search(&state, &req.query).await?;
+    let mut artifact = ShareArtifact::response(response);
+    artifact.author = req.author;
+    artifact.note = req.note;
+
+    let url = if req.upload {
+        Some(upload_artifact(&state, &artifact).await?)
+    } else {
+        None
+    };
+    Ok(Json(ShareResponse {
+        token: artifact.encode()?,
+        url,
+    }))
+}
+
+#[cfg(feature = "paste")]
+async fn upload_artifact(state: &AppState, artifact: &ShareArtifact) -> Result<String, ApiError> {
+    let Some(endpoint) = &state.paste_url else {
+        return Err(BoseError::ConfigError("SHARE_PASTE_URL is not set".into()).into());
+    };
+    Ok(bose_common::share::upload_paste(&state.http, endpoint, artifact).await?)
+}
+
+#[cfg(not(feature = "paste"))]
+async fn upload_artifact(_state: &AppState, _artifact: &ShareArtifact) -> Result<String, ApiError> {
+    Err(BoseError::ShareError("upload requires the `paste` feature".into()).into())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SitesRequest {
+    pub urls: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SitesResponse {
+    pub sites: Vec<SiteInfo>,
+}
+
+/// 批次解析結果網址所屬網站的 favicon 與名稱
+async fn sites(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<SitesRequest>,
+) -> Result<Json<SitesResponse>, ApiError> {
+    if req.urls.is_empty() || req.urls.len() > MAX_SITE_URLS {
+        return Err(BoseError::InvalidQuery(format!(
+            "urls must contain 1..={MAX_SITE_URLS} entries"
+        ))
+        .into());
+    }
+    if !state.limiter.try_acquire().await {
+        return Err(ApiError::rate_limited());
+    }
+    let sites = state.sites.resolve_batch(&state.fetch_http, &req.urls).await;
+    Ok(Json(SitesResponse { sites }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EvidenceRequest {
+    pub urls: Vec<String>,
+    /// 除 HTML 快照外要擷取的項目（`screenshot`、`pdf`）
+    #[serde(default)]
+    pub captures: Vec<CaptureKind>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EvidenceResponse {
+    pub results: Vec<EvidenceRecord>,
+    pub failed: Vec<ExtractFailure>,
+}
+
+/// 保存頁面 HTML 快照，並視需要以瀏覽器擷取截圖 / PDF
+async fn evidence(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<EvidenceRequest>,
+) -> Result<Json<EvidenceResponse>, ApiError> {
+    let Some(store) = &state.evidence else {
+        return Err(ApiError::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "evidence store is not configured (set HTTP_EVIDENCE_DIR)",
+        ));
+    };
+    if req.urls.is_empty() || req.urls.len() > MAX_EXTRACT_URLS {
+        return Err(BoseError::InvalidQuery(format!(
+            "urls must contain 1..={MAX_EXTRACT_URLS} entries"
+        ))
+        .into());
+    }
+    if !state.limiter.try_acquire().await {
+        return Err(ApiError::rate_limited());
+    }
+
+    let mut resp = EvidenceResponse {
+        results: Vec::new(),
+        failed: Vec::new(),
+    };
+    // 逐一處理，避免同時開啟大量瀏覽器分頁
+    for url in req.urls {
+        let fetched = match guard_url(&state, &url).await {
+            Ok(()) => fetch_html(&state.fetch_http, &url).await,
+            Err(e) => Err(e),
+        };
+        let outcome = match fetched {
+            Ok(html) => store
+                .preserve(&url, &html, state.renderer.as_deref(), &req.captures)
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        match outcome {
+            Ok(record) => resp.results.push(record),
+            Err(error) => {
+                tracing::warn!(%url, %error, "Evidence capture failed");
+                resp.failed.push(ExtractFailure { url, error });
+            }
+        }
+    }
+    Ok(Json(resp))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TriageRequest {
+    pub urls: Vec<String>,
+    /// `json`（預設）或 `table`（Markdown 表格）
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TriageResponse {
+    pub rows: Vec<TriageRow>,
+}
+
+async fn triage(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<TriageRequest>,
+) -> Result<Response, ApiError> {
+    if req.urls.is_empty() || req.urls.len() > MAX_TRIAGE_URLS {
+        return Err(BoseError::InvalidQuery(format!(
+            "urls must contain 1..={MAX_TRIAGE_URLS} entries"
+        ))
+        .into());
+    }
+    let as_table = match req.format.as_deref() {
+        None | Some("json") => false,
+        Some("table") => true,
+        Some(other) => {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                format!("unknown format: {other}"),
+            ));
+        }
+    };
+    if !state.limiter.try_acquire().await {
+        return Err(ApiError::rate_limited());
+    }
+
+    let rows = futures::future::join_all(req.urls.iter().map(|url| async {
+        match guard_url(&state, url).await {
+            Ok(()) => triage_url(&state.fetch_http, url).await,
+            Err(e) => TriageRow::unreachable(url, None, e.to_string()),
+        }
+    }))
+    .await;
+
+    if as_table {
+        let headers = [(header::CONTENT_TYPE, "text/markdown; charset=utf-8")];
+        return Ok((headers, render_table(&rows)).into_response());
+    }
+    Ok(Json(TriageResponse { rows }).into_response())
+}
+
+async fn health(State(state): State<Arc<AppState>>) -> (StatusCode, Json<serde_json::Value>) {
+    let healthy = state.client.health_check().await.unwrap_or(false);
+    let status = if healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    let mut body = serde_json::json!({
+        "status": if healthy { "ok" } else { "degraded" },
+        "searxng": healthy,
+    });
+    if let Some(cluster) = state.client.cluster() {
+        body["searxng_cluster"] = serde_json::json!(cluster.assignments());
+    }
+    (status, Json(body))
+}
+
+/// Prometheus 指標（目前為 SearXNG 叢集的健康狀態、延遲與主要實例分配）
+async fn metrics(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let body = state
+        .client
+        .cluster()
+        .map(|cluster| cluster.render_metrics())
+        .unwrap_or_default();
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rate_limit::RateLimiter;
+    use axum::body::Body;
+    use axum::http::Request;
+    use bose_common::memory_cache::ResponseCache;
+    use tower::ServiceExt;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn state(uri: &str, burst: u32) -> Arc<AppState> {
+        Arc::new(AppState {
+            client: SearxngClient::from_url(uri).unwrap(),
+            http: reqwest::Client::new(),
+            fetch_http: reqwest::Client::new(),
+            // mock 伺服器在 127.0.0.1
+            allow_private_urls: true,
+            cache: Arc::new(ResponseCache::new(Duration::from_secs(60), 100)),
+            limiter: Arc::new(RateLimiter::new(0.0, burst)),
+            stream_engines: vec!["google".into()],
+            renderer: None,
+            evidence: None,
+            sites: SiteInfoResolver::new(Duration::from_secs(60)).with_private_addresses(true),
+            paste_url: None,
+        })
+    }
+
+    fn post_json(uri: &str, body: serde_json::Value) -> Request<Body> {
+        Request::post(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    async fn body_json(resp: Response) -> serde_json::Value {
+        let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    async fn mock_searxng() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "query": "rust",
+                "results": [{
+                    "url": "https://rust-lang.org",
+                    "title": "Rust",
+                    "engine": "google"
+                }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn test_search_endpoint_uses_cache() {
+        let server = mock_searxng().await;
+        let app = router(state(&server.uri(), 10));
+
+        for _ in 0..2 {
+            let resp = app
+                .clone()
+                .oneshot(post_json("/search", serde_json::json!({ "query": "rust" })))
+                .await
+                .unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+            let body = body_json(resp).await;
+            assert_eq!(body["results"][0]["title"], "Rust");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_empty_query() {
+        let app = router(state("http://127.0.0.1:9", 10));
+        let resp = app
+            .oneshot(post_json("/search", serde_json::json!({ "query": "  " })))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        assert!(body_json(resp).await["error"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_search_rate_limited() {
+        let app = router(state("http://127.0.0.1:9", 0));
+        let resp = app
+            .oneshot(post_json("/search", serde_json::json!({ "query": "rust" })))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn test_extract_endpoint() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/page"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string("<title>Page</title><p>Body text</p>"),
+            )
+            .mount(&server)
+            .await;
+
+        let app = router(state(&server.uri(), 10));
+        let urls = [
+            format!("{}/page", server.uri()),
+            format!("{}/missing", server.uri()),
+        ];
+        let resp = app
+            .oneshot(post_json("/extract", serde_json::json!({ "urls": urls })))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = body_json(resp).await;
+        assert_eq!(body["results"][0]["title"], "Page");
+        assert_eq!(body["results"][0]["content"], "Page Body text");
+        assert_eq!(body["failed"].as_array().unwrap().len(), 1);
+        let error = body["failed"][0]["error"].as_str().unwrap();
+        assert_eq!(error, BoseError::UpstreamError("HTTP 404 Not Found".into()).to_string());
+        assert_eq!(
+            ApiError::from(BoseError::UpstreamError(error.into())).status,
+            StatusCode::BAD_GATEWAY
+        );
+    }
+
+    #[tokio::test]
+    async fn test_extract_rejects_private_addresses() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("<p>internal</p>"))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let mut app_state = Arc::try_unwrap(state(&server.uri(), 10)).ok().unwrap();
+        app_state.allow_private_urls = false;
+        let app = router(Arc::new(app_state));
+
+        let urls = [
+            format!("{}/admin", server.uri()),
+            "http://169.254.169.254/latest/meta-data/".to_string(),
+        ];
+        let resp = app
+            .oneshot(post_json("/extract", serde_json::json!({ "urls": urls })))
+            .await
+            .unwrap();
+        let body = body_json(resp).await;
+        assert!(body["results"].as_array().unwrap().is_empty());
+        for failure in body["failed"].as_array().unwrap() {
+            assert!(failure["error"].as_str().unwrap().contains("non-public address"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extract_render_fallback() {
+        use crate::capture::tests::{FakeRenderer, RENDERED_HTML};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/app"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                "<div id=\"root\"></div><noscript>You need to enable JavaScript</noscript>",
+            ))
+            .mount(&server)
+            .await;
+
+        let mut app_state = Arc::try_unwrap(state(&server.uri(), 10)).ok().unwrap();
+        app_state.renderer = Some(Arc::new(FakeRenderer { fail_pdf: false }));
+        let app = router(Arc::new(app_state));
+
+        let urls = [format!("{}/app", server.uri())];
+        let resp = app
+            .oneshot(post_json("/extract", serde_json::json!({ "urls": urls })))
+            .await
+            .unwrap();
+        let body = body_json(resp).await;
+        let expected = extract_page(&urls[0], RENDERED_HTML, DEFAULT_EXTRACT_CHARS);
+        assert_eq!(body["results"][0]["content"], expected.content);
+        assert_eq!(body["results"][0]["rendered"], true);
+    }
+
+    #[tokio::test]
+    async fn test_share_endpoint() {
+        let server = mock_searxng().await;
+        let app = router(state(&server.uri(), 10));
+
+        let body = serde_json::json!({ "query": "rust", "author": "alice" });
+        let resp = app.oneshot(post_json("/share", body)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = body_json(resp).await;
+        assert!(body.get("url").is_none());
+
+        let artifact = ShareArtifact::decode(body["token"].as_str().unwrap()).unwrap();
+        assert_eq!(artifact.author.as_deref(), Some("alice"));
+        assert!(artifact.render().contains("Query: \"rust\""));
+    }
+
+    #[tokio::test]
+    async fn test_sites_endpoint() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("<title>Mock Site</title>"))
+            .mount(&server)
+            .await;
+
+        let app = router(state(&server.uri(), 10));
+        let urls = [format!("{}/a", server.uri()), format!("{}/b", server.uri())];
+        let resp = app
+            .clone()
+            .oneshot(post_json("/sites", serde_json::json!({ "urls": urls })))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = body_json(resp).await;
+        assert_eq!(body["sites"].as_array().unwrap().len(), 1);
+        assert_eq!(body["sites"][0]["site_name"], "Mock Site");
+
+        let resp = app
+            .oneshot(post_json("/sites", serde_json::json!({ "urls": [] })))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_feed_endpoint() {
+        let server = mock_searxng().await;
+        let app = router(state(&server.uri(), 10));
+
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::get("/feed?q=rust&format=atom")
+                    .header("host", "search.local")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers()[header::CONTENT_TYPE],
+            FeedFormat::Atom.content_type()
+        );
+        let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let xml = String::from_utf8(bytes.to_vec()).unwrap();
+        assert!(xml.contains("<feed xmlns=\"http://www.w3.org/2005/Atom\">"));
+        assert!(xml.contains("http://search.local/feed?q=rust&amp;format=atom"));
+        assert!(xml.contains("<link href=\"https://rust-lang.org\"/>"));
+
+        // 第二次請求由快取提供（mock 只允許一次呼叫）
+        let resp = app
+            .oneshot(Request::get("/feed?q=rust").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers()[header::CONTENT_TYPE],
+            FeedFormat::Rss.content_type()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_feed_unknown_format() {
+        let app = router(state("http://127.0.0.1:9", 10));
+        let resp = app
+            .oneshot(
+                Request::get("/feed?q=rust&format=json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_triage_endpoint() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/docs/intro"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("server", "nginx")
+                    .set_body_string("<title>Intro</title><div id=\"__NEXT_DATA__\"></div>"),
+            )
+            .mount(&server)
+            .await;
+
+        let app = router(state(&server.uri(), 10));
+        let urls = [
+            format!("{}/docs/intro", server.uri()),
+            format!("{}/missing", server.uri()),
+        ];
+        let resp = app
+            .clone()
+            .oneshot(post_json("/triage", serde_json::json!({ "urls": urls })))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = body_json(resp).await;
+        let rows = body["rows"].as_array().unwrap();
+        assert_eq!(rows[0]["availability"], "reachable");
+        assert_eq!(rows[0]["page_type"], "documentation");
+        assert_eq!(
+            rows[0]["technologies"],
+            serde_json::json!(["nginx", "Next.js"])
+        );
+        assert_eq!(rows[0]["title"], "Intro");
+        assert_eq!(rows[1]["availability"], "unreachable");
+        assert_eq!(rows[1]["status"], 404);
+
+        let resp = app
+            .oneshot(post_json(
+                "/triage",
+                serde_json::json!({ "urls": urls, "format": "table" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(String::from_utf8_lossy(&bytes).starts_with("| URL | Status |"));
+    }
+
+    #[tokio::test]
+    async fn test_evidence_endpoint() {
+        use crate::capture::tests::{FakeRenderer, temp_root};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/page"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("<p>proof</p>"))
+            .mount(&server)
+            .await;
+
+        let root = temp_root("endpoint");
+        let mut app_state = Arc::try_unwrap(state(&server.uri(), 10)).ok().unwrap();
+        app_state.renderer = Some(Arc::new(FakeRenderer { fail_pdf: false }));
+        app_state.evidence = Some(EvidenceStore::new(&root).unwrap());
+        let app = router(Arc::new(app_state));
+
+        let body = serde_json::json!({
+            "urls": [format!("{}/page", server.uri()), format!("{}/missing", server.uri())],
+            "captures": ["screenshot", "pdf"],
+        });
+        let resp = app.oneshot(post_json("/evidence", body)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = body_json(resp).await;
+        assert_eq!(
+            body["results"][0]["files"],
+            serde_json::json!(["snapshot.html", "screenshot.png", "page.pdf"])
+        );
+        assert_eq!(body["failed"].as_array().unwrap().len(), 1);
+
+        assert!(body["results"][0].get("dir").is_none());
+        let id = body["results"][0]["id"].as_str().unwrap();
+        let snapshot = std::fs::read_to_string(root.join(id).join("snapshot.html"));
+        assert_eq!(snapshot.unwrap(), "<p>proof</p>");
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[tokio::test]
+    async fn test_evidence_not_configured() {
+        let app = router(state("http://127.0.0.1:9", 10));
+        let body = serde_json::json!({ "urls": ["https://example.com"] });
+        let resp = app.oneshot(post_json("/evidence", body)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_health_endpoint() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+
+        let app = router(state(&server.uri(), 10));
+        let resp = app
+            .oneshot(Request::get("/health").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body_json(resp).await["searxng"], false);
+    }
+
+    #[tokio::test]
+    async fn test_health_and_metrics_report_cluster() {
+        let server = mock_searxng().await;
+        let instances = bose_common::SearxngInstance::parse_list(&format!("eu={}", server.uri()));
+        let cluster = Arc::new(bose_searxng::SearxngCluster::new(
+            reqwest::Client::new(),
+            instances,
+            None,
+        ));
+        let mut st = Arc::try_unwrap(state("http://unused.invalid", 10))
+            .ok()
+            .unwrap();
+        st.client = st.client.with_cluster(cluster);
+        let app = router(Arc::new(st));
+
+        let resp = app
+            .clone()
+            .oneshot(Request::get("/health").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = body_json(resp).await;
+        assert_eq!(body["searxng_cluster"][0]["region"], "eu");
+        assert_eq!(body["searxng_cluster"][0]["primary"], server.uri());
+
+        let resp = app
+            .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let text = String::from_utf8(bytes.to_vec()).unwrap();
+        assert!(text.contains(&format!(
+            "bose_searxng_primary{{region=\"eu\",instance=\"{}\"}} 1",
+            server.uri()
+        )));
+    }
+}
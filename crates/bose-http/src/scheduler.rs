@@ -0,0 +1,165 @@
+//! 排程搜尋 — 依設定檔 `[[schedule]]` 定期執行查詢，把新出現的結果 POST 到 webhook
+//!
+//! 每項工作一個背景 task。第一次執行只建立基準，之後以 `diff` 與上一次的結果比較，有新結果才通知；
+//! 基準只存在記憶體，重新啟動後重新建立，不會把舊結果再推送一次。
+
+use bose_common::diff::diff;
+use bose_common::schedule::ScheduledJob;
+use bose_common::{BoseResult, SearchResponse, SearchResult};
+use bose_searxng::SearxngClient;
+use chrono::Utc;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+/// 排程搜尋執行器
+pub struct Scheduler {
+    client: SearxngClient,
+    http: reqwest::Client,
+    default_num_results: u32,
+}
+
+impl Scheduler {
+    pub fn new(client: SearxngClient, http: reqwest::Client, default_num_results: u32) -> Self {
+        Self {
+            client,
+            http,
+            default_num_results,
+        }
+    }
+
+    /// 為每項工作啟動背景 task
+    pub fn spawn(self: Arc<Self>, jobs: Vec<ScheduledJob>) -> Vec<JoinHandle<()>> {
+        jobs.into_iter()
+            .map(|job| {
+                let scheduler = self.clone();
+                tokio::spawn(async move { scheduler.run(job).await })
+            })
+            .collect()
+    }
+
+    async fn run(&self, job: ScheduledJob) {
+        let mut previous = None;
+        loop {
+            let now = Utc::now();
+            let Some(next) = job.cron.next_after(now) else {
+                tracing::warn!(job = %job.name, cron = %job.cron, "Schedule never fires; job stopped");
+                return;
+            };
+            tokio::time::sleep((next - now).to_std().unwrap_or_default()).await;
+
+            match self.tick(&job, &mut previous).await {
+                Ok(found) => tracing::info!(job = %job.name, new = found.len(), "Scheduled search done"),
+                Err(e) => tracing::warn!(job = %job.name, error = %e, "Scheduled search failed"),
+            }
+        }
+    }
+
+    /// 執行一次工作：搜尋、與 `previous` 比較、通知 webhook，並更新 `previous`
+    ///
+    /// 回傳新結果；`previous` 為 None 時只建立基準，不通知。搜尋失敗時保留原本的基準。
+    pub async fn tick(
+        &self,
+        job: &ScheduledJob,
+        previous: &mut Option<SearchResponse>,
+    ) -> BoseResult<Vec<SearchResult>> {
+        let response = self
+            .client
+            .search(&job.search_query(self.default_num_results))
+            .await?;
+        let found: Vec<SearchResult> = match previous.as_ref() {
+            Some(previous) => diff(previous, &response)
+                .added
+                .into_iter()
+                .map(|entry| entry.result)
+                .collect(),
+            None => Vec::new(),
+        };
+        *previous = Some(response);
+
+        if !found.is_empty() {
+            self.notify(job, &found).await;
+        }
+        Ok(found)
+    }
+
+    /// 推送到所有 webhook；單一 webhook 失敗只記錄警告
+    async fn notify(&self, job: &ScheduledJob, found: &[SearchResult]) {
+        let checked_at = Utc::now();
+        for webhook in &job.webhooks {
+            let sent = self
+                .http
+                .post(&webhook.url)
+                .json(&webhook.payload(job, found, checked_at))
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status);
+            if let Err(e) = sent {
+                tracing::warn!(job = %job.name, webhook = %webhook.url, error = %e, "Webhook delivery failed");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bose_common::schedule::Webhook;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn searxng_results(urls: &[&str]) -> ResponseTemplate {
+        let results: Vec<_> = urls
+            .iter()
+            .map(|url| serde_json::json!({ "url": url, "title": url, "engine": "google" }))
+            .collect();
+        ResponseTemplate::new(200)
+            .set_body_json(serde_json::json!({ "query": "xz", "results": results }))
+    }
+
+    #[tokio::test]
+    async fn test_tick_notifies_new_results_only() {
+        let server = MockServer::start().await;
+        for urls in [&["https://a.com"][..], &["https://a.com", "https://b.com"][..]] {
+            Mock::given(method("GET"))
+                .and(path("/search"))
+                .respond_with(searxng_results(urls))
+                .up_to_n_times(1)
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("POST"))
+            .and(path("/hook"))
+            .and(body_partial_json(serde_json::json!({
+                "job": "xz",
+                "count": 1,
+                "new_results": [{ "url": "https://b.com" }],
+            })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let job = ScheduledJob {
+            name: "xz".into(),
+            query: "xz".into(),
+            cron: "@hourly".parse().unwrap(),
+            num_results: None,
+            category: None,
+            engines: vec![],
+            webhooks: vec![Webhook::new(format!("{}/hook", server.uri()))],
+        };
+        let scheduler = Scheduler::new(
+            SearxngClient::from_url(&server.uri()).unwrap(),
+            reqwest::Client::new(),
+            10,
+        );
+
+        let mut previous = None;
+        // 第一次只建立基準
+        assert!(scheduler.tick(&job, &mut previous).await.unwrap().is_empty());
+        let found = scheduler.tick(&job, &mut previous).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].url, "https://b.com");
+        assert_eq!(previous.unwrap().results.len(), 2);
+    }
+}
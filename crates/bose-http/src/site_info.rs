@@ -0,0 +1,309 @@
+//! 網站資訊 — 解析結果網域的 favicon 與網站名稱，供網頁介面與格式化輸出使用
+//!
+//! 每個來源（scheme + host + port）只抓一次首頁並快取；批次 API 會先去重再並行抓取，
+//! 避免每個結果各自發出請求。快取有筆數上限，預設不抓私有與迴路位址。
+
+use crate::structured::scan_tags;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use url::Url;
+
+/// 單一網站的顯示資訊
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SiteInfo {
+    /// 來源（例如 `https://docs.rs`）
+    pub origin: String,
+    pub site_name: Option<String>,
+    /// favicon 的絕對網址；首頁未宣告時退回 `/favicon.ico`
+    pub favicon: String,
+}
+
+/// 預設最多快取的來源數
+pub const DEFAULT_MAX_SITE_ENTRIES: usize = 1000;
+
+/// 帶快取的網站資訊解析器
+pub struct SiteInfoResolver {
+    ttl: Duration,
+    max_entries: usize,
+    allow_private: bool,
+    entries: Mutex<HashMap<String, (Instant, SiteInfo)>>,
+}
+
+impl SiteInfoResolver {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            max_entries: DEFAULT_MAX_SITE_ENTRIES,
+            allow_private: false,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 快取筆數上限；已滿時先清掉過期項目，仍滿則淘汰最舊的一筆
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries.max(1);
+        self
+    }
+
+    /// 允許解析私有與迴路位址的網站（內網部署）
+    pub fn with_private_addresses(mut self, allow: bool) -> Self {
+        self.allow_private = allow;
+        self
+    }
+
+    /// 解析多個結果網址的網站資訊；同一來源只解析一次，無效或非公開位址的網址略過
+    ///
+    /// 回傳依來源第一次出現的順序排列。
+    pub async fn resolve_batch(&self, http: &reqwest::Client, urls: &[String]) -> Vec<SiteInfo> {
+        let mut origins: Vec<Url> = Vec::new();
+        for url in urls {
+            let Some(origin) = origin_of(url) else {
+                continue;
+            };
+            if !origins.contains(&origin) {
+                origins.push(origin);
+            }
+        }
+        futures::future::join_all(origins.iter().map(|origin| self.resolve(http, origin)))
+            .await
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    async fn resolve(&self, http: &reqwest::Client, origin: &Url) -> Option<SiteInfo> {
+        let key = origin_key(origin);
+        if let Some(info) = self.cached(&key) {
+            return Some(info);
+        }
+        if !self.allow_private
+            && let Err(e) = bose_common::net::ensure_public(origin.as_str()).await
+        {
+            tracing::debug!(%origin, error = %e, "Site info fetch refused");
+            return None;
+        }
+
+        // 抓取失敗也快取（只有預設 favicon），避免重複打到無回應的網站
+        let info = match fetch_home(http, origin).await {
+            Some(html) => parse_site_info(origin, &html),
+            None => parse_site_info(origin, ""),
+        };
+        self.store(key, info.clone());
+        Some(info)
+    }
+
+    fn store(&self, key: String, info: SiteInfo) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.max_entries && !entries.contains_key(&key) {
+            entries.retain(|_, (stored_at, _)| stored_at.elapsed() < self.ttl);
+            if entries.len() >= self.max_entries
+                && let Some(oldest) = entries
+                    .iter()
+                    .min_by_key(|(_, (stored_at, _))| *stored_at)
+                    .map(|(key, _)| key.clone())
+            {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(key, (Instant::now(), info));
+    }
+
+    fn cached(&self, key: &str) -> Option<SiteInfo> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some((stored_at, info)) if stored_at.elapsed() < self.ttl => Some(info.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+async fn fetch_home(http: &reqwest::Client, origin: &Url) -> Option<String> {
+    let resp = http.get(origin.as_str()).send().await.ok()?;
+    if !resp.status().is_success() {
+        tracing::debug!(%origin, status = %resp.status(), "Site info fetch failed");
+        return None;
+    }
+    resp.text().await.ok()
+}
+
+/// 從首頁 HTML 取出網站名稱（`og:site_name` 優先，其次 `application-name`、`<title>`）與 favicon
+pub fn parse_site_info(origin: &Url, html: &str) -> SiteInfo {
+    let tags = scan_tags(html);
+
+    let meta = |key: &str| {
+        tags.iter()
+            .filter(|t| t.name == "meta")
+            .find(|t| t.attr("property").or_else(|| t.attr("name")) == Some(key))
+            .and_then(|t| t.attr("content"))
+            .map(|c| c.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|c| !c.is_empty())
+    };
+    let site_name = meta("og:site_name")
+        .or_else(|| meta("application-name"))
+        .or_else(|| title(html));
+
+    // 依偏好順序挑選 icon：明確的 icon > shortcut icon > apple-touch-icon
+    let icon_rank = |rel: &str| {
+        let rel = rel.to_ascii_lowercase();
+        let parts: Vec<&str> = rel.split_whitespace().collect();
+        if parts == ["icon"] {
+            Some(0)
+        } else if parts.contains(&"icon") {
+            Some(1)
+        } else if parts.iter().any(|p| p.starts_with("apple-touch-icon")) {
+            Some(2)
+        } else {
+            None
+        }
+    };
+    let favicon = tags
+        .iter()
+        .filter(|t| t.name == "link")
+        .filter_map(|t| Some((icon_rank(t.attr("rel")?)?, t.attr("href")?)))
+        .filter(|(_, href)| !href.trim().is_empty())
+        .min_by_key(|(rank, _)| *rank)
+        .and_then(|(_, href)| origin.join(href.trim()).ok())
+        .or_else(|| origin.join("/favicon.ico").ok())
+        .map(String::from)
+        .unwrap_or_default();
+
+    SiteInfo {
+        origin: origin_key(origin),
+        site_name,
+        favicon,
+    }
+}
+
+fn title(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let start = lower.find("<title")?;
+    let open_end = lower[start..].find('>')? + start + 1;
+    let close = lower[open_end..].find("</title>")? + open_end;
+    let title = html[open_end..close]
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    (!title.is_empty()).then_some(title)
+}
+
+/// 結果網址 → 首頁網址（只接受 http / https）
+fn origin_of(url: &str) -> Option<Url> {
+    let url = Url::parse(url).ok()?;
+    if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+        return None;
+    }
+    url.join("/").ok()
+}
+
+fn origin_key(origin: &Url) -> String {
+    origin.origin().ascii_serialization()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn origin(url: &str) -> Url {
+        Url::parse(url).unwrap()
+    }
+
+    #[test]
+    fn test_parse_site_info() {
+        let html = r#"<head><title>Home | Docs</title>
+            <meta property="og:site_name" content="Docs.rs">
+            <link rel="apple-touch-icon" href="/apple.png">
+            <link rel="icon" type="image/svg+xml" href="static/icon.svg">
+        </head>"#;
+        let info = parse_site_info(&origin("https://docs.rs/"), html);
+        assert_eq!(info.origin, "https://docs.rs");
+        assert_eq!(info.site_name.as_deref(), Some("Docs.rs"));
+        assert_eq!(info.favicon, "https://docs.rs/static/icon.svg");
+    }
+
+    #[test]
+    fn test_parse_site_info_fallbacks() {
+        let info = parse_site_info(
+            &origin("http://example.com:8080/"),
+            "<title> Example  Domain </title>",
+        );
+        assert_eq!(info.site_name.as_deref(), Some("Example Domain"));
+        assert_eq!(info.favicon, "http://example.com:8080/favicon.ico");
+
+        let empty = parse_site_info(&origin("https://a.com/"), "");
+        assert!(empty.site_name.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_batch_dedupes_and_caches() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"<meta name="application-name" content="Mock"><link rel="shortcut icon" href="/f.ico">"#,
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let resolver = SiteInfoResolver::new(Duration::from_secs(60)).with_private_addresses(true);
+        let http = reqwest::Client::new();
+        let urls = vec![
+            format!("{}/a", server.uri()),
+            format!("{}/b?x=1", server.uri()),
+            "not a url".to_string(),
+            "ftp://files.example.com/x".to_string(),
+        ];
+        let sites = resolver.resolve_batch(&http, &urls).await;
+        assert_eq!(sites.len(), 1);
+        assert_eq!(sites[0].site_name.as_deref(), Some("Mock"));
+        assert_eq!(sites[0].favicon, format!("{}/f.ico", server.uri()));
+
+        // 第二次由快取回應（mock 只允許一次請求）
+        let again = resolver.resolve_batch(&http, &urls[..1]).await;
+        assert_eq!(again, sites);
+        assert_eq!(resolver.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_refuses_private_addresses_and_evicts() {
+        let (a, b) = (MockServer::start().await, MockServer::start().await);
+        for server in [&a, &b] {
+            Mock::given(method("GET"))
+                .respond_with(ResponseTemplate::new(200).set_body_string("<title>Internal</title>"))
+                .mount(server)
+                .await;
+        }
+        let http = reqwest::Client::new();
+
+        // 預設不抓 127.0.0.1 上的網站
+        let resolver = SiteInfoResolver::new(Duration::from_secs(60));
+        assert!(resolver.resolve_batch(&http, &[a.uri()]).await.is_empty());
+        assert!(resolver.is_empty());
+        assert_eq!(a.received_requests().await.unwrap().len(), 0);
+
+        // 超過上限時淘汰最舊的來源
+        let resolver = SiteInfoResolver::new(Duration::from_secs(60))
+            .with_private_addresses(true)
+            .with_max_entries(1);
+        resolver.resolve_batch(&http, &[a.uri()]).await;
+        resolver.resolve_batch(&http, &[b.uri()]).await;
+        assert_eq!(resolver.len(), 1);
+        assert!(resolver.cached(&origin_key(&origin(&b.uri()))).is_some());
+    }
+}
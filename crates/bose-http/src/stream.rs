@@ -0,0 +1,167 @@
+use bose_common::fusion::{RRF_K, rrf_merge};
+use bose_common::{SearchQuery, SearchResponse, SearchResult};
+use bose_searxng::SearxngClient;
+use futures::StreamExt;
+use futures::stream::FuturesUnordered;
+use serde::Serialize;
+use std::time::Instant;
+use tokio::sync::mpsc;
+
+/// 串流搜尋的訊息框
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamFrame {
+    /// 單一引擎的結果
+    Partial {
+        engine: String,
+        results: Vec<SearchResult>,
+        elapsed_seconds: f64,
+    },
+    /// 單一引擎失敗，或請求本身無效（engine 為 None）
+    Error {
+        engine: Option<String>,
+        error: String,
+    },
+    /// 所有引擎完成後的融合排名
+    Complete { response: Box<SearchResponse> },
+}
+
+/// 對每個引擎各發一次查詢，依回應順序串流結果，最後送出 RRF 融合的 `Complete`
+pub fn stream_search(
+    client: SearxngClient,
+    query: SearchQuery,
+    engines: Vec<String>,
+) -> mpsc::Receiver<StreamFrame> {
+    let (tx, rx) = mpsc::channel(engines.len() + 1);
+
+    tokio::spawn(async move {
+        let start = Instant::now();
+        let mut pending: FuturesUnordered<_> = engines
+            .into_iter()
+            .map(|engine| {
+                let client = client.clone();
+                let q = query.clone().with_engines(vec![engine.clone()]);
+                async move { (engine, client.search(&q).await) }
+            })
+            .collect();
+
+        let mut lists = Vec::new();
+        let mut engines_used = Vec::new();
+        let mut instant_answer = None;
+
+        while let Some((engine, outcome)) = pending.next().await {
+            let frame = match outcome {
+                Ok(resp) => {
+                    lists.push(resp.results.clone());
+                    engines_used.push(engine.clone());
+                    instant_answer = instant_answer.or(resp.instant_answer);
+                    StreamFrame::Partial {
+                        engine,
+                        results: resp.results,
+                        elapsed_seconds: start.elapsed().as_secs_f64(),
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!(%engine, error = %e, "Stream engine failed");
+                    StreamFrame::Error {
+                        engine: Some(engine),
+                        error: e.to_string(),
+                    }
+                }
+            };
+            if tx.send(frame).await.is_err() {
+                // 接收端已關閉，放棄剩餘引擎
+                return;
+            }
+        }
+
+        let mut results = rrf_merge(&lists, RRF_K);
+        results.truncate(query.num_results as usize);
+        let response = SearchResponse {
+            results,
+            query: query.query,
+            elapsed_seconds: start.elapsed().as_secs_f64(),
+            total_results: None,
+            engines_used,
+            suggestions: Vec::new(),
+            corrected_from: None,
+            instant_answer,
+        };
+        let _ = tx
+            .send(StreamFrame::Complete { response: Box::new(response) })
+            .await;
+    });
+
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn mount_engine(server: &MockServer, engine: &str, status: u16, urls: &[&str]) {
+        let results: Vec<_> = urls
+            .iter()
+            .map(|u| serde_json::json!({ "url": u, "title": u, "engine": engine }))
+            .collect();
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .and(query_param("engines", engine))
+            .respond_with(
+                ResponseTemplate::new(status)
+                    .set_body_json(serde_json::json!({ "query": "rust", "results": results })),
+            )
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_stream_partials_then_complete() {
+        let server = MockServer::start().await;
+        mount_engine(&server, "google", 200, &["https://a.com", "https://b.com"]).await;
+        mount_engine(&server, "brave", 200, &["https://b.com", "https://c.com"]).await;
+        mount_engine(&server, "bing", 500, &[]).await;
+
+        let client = SearxngClient::from_url(&server.uri()).unwrap();
+        let engines = vec!["google".into(), "brave".into(), "bing".into()];
+        let mut rx = stream_search(client, SearchQuery::new("rust"), engines);
+
+        let mut frames = Vec::new();
+        while let Some(frame) = rx.recv().await {
+            frames.push(frame);
+        }
+
+        assert_eq!(frames.len(), 4);
+        let partials = frames
+            .iter()
+            .filter(|f| matches!(f, StreamFrame::Partial { .. }))
+            .count();
+        let errors = frames
+            .iter()
+            .filter(|f| matches!(f, StreamFrame::Error { engine: Some(e), .. } if e == "bing"))
+            .count();
+        assert_eq!((partials, errors), (2, 1));
+
+        match frames.last().unwrap() {
+            StreamFrame::Complete { response } => {
+                assert_eq!(response.results[0].url, "https://b.com");
+                assert_eq!(response.results.len(), 3);
+                assert_eq!(response.engines_used.len(), 2);
+            }
+            other => panic!("Expected Complete, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_frame_serialize() {
+        let frame = StreamFrame::Error {
+            engine: None,
+            error: "bad".into(),
+        };
+        let json = serde_json::to_value(&frame).unwrap();
+        assert_eq!(json["type"], "error");
+        assert!(json["engine"].is_null());
+    }
+}
@@ -0,0 +1,381 @@
+//! 結構化資料提取 — schema.org JSON-LD、OpenGraph 與 microdata
+//!
+//! 產出 `{"json_ld": [...], "opengraph": {...}, "microdata": [...]}`，沒有資料的來源省略；
+//! 三者皆無時回傳 `None`。
+
+use bose_common::html::decode_entities;
+use serde_json::{Map, Value};
+
+/// 從 HTML 提取結構化資料
+pub fn extract_structured(html: &str) -> Option<Value> {
+    let tags = scan_tags(html);
+    let mut out = Map::new();
+
+    let json_ld = json_ld(html, &tags);
+    if !json_ld.is_empty() {
+        out.insert("json_ld".into(), Value::Array(json_ld));
+    }
+    let opengraph = opengraph(&tags);
+    if !opengraph.is_empty() {
+        out.insert("opengraph".into(), Value::Object(opengraph));
+    }
+    let microdata = microdata(html, &tags);
+    if !microdata.is_empty() {
+        out.insert("microdata".into(), Value::Array(microdata));
+    }
+
+    (!out.is_empty()).then_some(Value::Object(out))
+}
+
+/// 解析出的開始標籤
+pub(crate) struct Tag {
+    pub name: String,
+    attrs: Vec<(String, String)>,
+    /// `>` 之後的位元組位置
+    end: usize,
+}
+
+impl Tag {
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn has_attr(&self, name: &str) -> bool {
+        self.attrs.iter().any(|(k, _)| k == name)
+    }
+}
+
+/// 掃描所有開始標籤（結束標籤、註解與 `<!DOCTYPE>` 略過）
+pub(crate) fn scan_tags(html: &str) -> Vec<Tag> {
+    let bytes = html.as_bytes();
+    let mut tags = Vec::new();
+    let mut i = 0;
+
+    while let Some(offset) = html[i..].find('<') {
+        let start = i + offset + 1;
+        if html[start..].starts_with("!--") {
+            i = html[start..]
+                .find("-->")
+                .map_or(html.len(), |e| start + e + 3);
+            continue;
+        }
+        if !bytes.get(start).is_some_and(|b| b.is_ascii_alphabetic()) {
+            i = start;
+            continue;
+        }
+
+        let (tag, end) = parse_tag(html, start);
+        let raw_text = matches!(tag.name.as_str(), "script" | "style");
+        let name = tag.name.clone();
+        tags.push(tag);
+        i = end;
+        // script/style 內容不是標籤，直接跳到結束標籤
+        if raw_text {
+            let close = format!("</{name}");
+            i = find_ci(html, i, &close).unwrap_or(html.len());
+        }
+    }
+    tags
+}
+
+fn parse_tag(html: &str, start: usize) -> (Tag, usize) {
+    let bytes = html.as_bytes();
+    let mut i = start;
+    while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'>' && bytes[i] != b'/'
+    {
+        i += 1;
+    }
+    let name = html[start..i].to_ascii_lowercase();
+    let mut attrs = Vec::new();
+
+    loop {
+        while i < bytes.len() && (bytes[i].is_ascii_whitespace() || bytes[i] == b'/') {
+            i += 1;
+        }
+        if i >= bytes.len() || bytes[i] == b'>' {
+            break;
+        }
+        let key_start = i;
+        while i < bytes.len() && !bytes[i].is_ascii_whitespace() && !b"=>/".contains(&bytes[i]) {
+            i += 1;
+        }
+        let key = html[key_start..i].to_ascii_lowercase();
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        let mut value = String::new();
+        if bytes.get(i) == Some(&b'=') {
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            match bytes.get(i) {
+                Some(&quote @ (b'"' | b'\'')) => {
+                    let value_start = i + 1;
+                    let value_end = html[value_start..]
+                        .find(quote as char)
+                        .map_or(html.len(), |e| value_start + e);
+                    value = decode_entities(&html[value_start..value_end]);
+                    i = (value_end + 1).min(html.len());
+                }
+                _ => {
+                    let value_start = i;
+                    while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'>' {
+                        i += 1;
+                    }
+                    value = decode_entities(&html[value_start..i]);
+                }
+            }
+        }
+        if !key.is_empty() {
+            attrs.push((key, value));
+        }
+    }
+
+    let end = (i + 1).min(html.len());
+    (Tag { name, attrs, end }, end)
+}
+
+/// `<script type="application/ld+json">` 內容；`@graph` 展開為個別項目
+fn json_ld(html: &str, tags: &[Tag]) -> Vec<Value> {
+    let mut items = Vec::new();
+    for tag in tags {
+        let is_ld = tag.name == "script"
+            && tag
+                .attr("type")
+                .is_some_and(|t| t.trim().eq_ignore_ascii_case("application/ld+json"));
+        if !is_ld {
+            continue;
+        }
+        let close = find_ci(html, tag.end, "</script").unwrap_or(html.len());
+        let Ok(value) = serde_json::from_str::<Value>(html[tag.end..close].trim()) else {
+            continue;
+        };
+        match value {
+            Value::Array(values) => items.extend(values),
+            Value::Object(mut obj) => match obj.remove("@graph") {
+                Some(Value::Array(graph)) => items.extend(graph),
+                _ => items.push(Value::Object(obj)),
+            },
+            _ => {}
+        }
+    }
+    items
+}
+
+/// `og:*` meta 標籤（去掉 `og:` 前綴；重複的屬性如 `og:image` 收成陣列）
+fn opengraph(tags: &[Tag]) -> Map<String, Value> {
+    let mut og = Map::new();
+    for tag in tags.iter().filter(|t| t.name == "meta") {
+        let Some(property) = tag.attr("property").or_else(|| tag.attr("name")) else {
+            continue;
+        };
+        let Some(key) = property.strip_prefix("og:") else {
+            continue;
+        };
+        let Some(content) = tag.attr("content").filter(|c| !c.trim().is_empty()) else {
+            continue;
+        };
+        insert_multi(&mut og, key, Value::String(content.trim().to_string()));
+    }
+    og
+}
+
+/// microdata：每個最外層 `itemscope` 一個項目，巢狀 `itemscope` 成為屬性值
+fn microdata(html: &str, tags: &[Tag]) -> Vec<Value> {
+    // 以開始標籤順序近似巢狀結構：遇到 itemscope 推入堆疊，其結束位置之後彈出
+    let mut items = Vec::new();
+    let mut stack: Vec<(usize, Option<String>, Map<String, Value>)> = Vec::new();
+
+    for tag in tags {
+        while stack.last().is_some_and(|(close, ..)| tag.end > *close) {
+            pop_item(&mut stack, &mut items);
+        }
+
+        if tag.has_attr("itemscope") {
+            let close = element_end(html, tag);
+            let mut props = Map::new();
+            if let Some(item_type) = tag.attr("itemtype") {
+                props.insert("@type".into(), Value::String(schema_type(item_type)));
+            }
+            stack.push((close, tag.attr("itemprop").map(str::to_string), props));
+            continue;
+        }
+
+        let Some(prop) = tag.attr("itemprop") else {
+            continue;
+        };
+        let Some((_, _, props)) = stack.last_mut() else {
+            continue;
+        };
+        let value = ["content", "href", "src", "datetime", "value"]
+            .iter()
+            .find_map(|attr| tag.attr(attr))
+            .map(str::to_string)
+            .unwrap_or_else(|| inner_text(html, tag));
+        if !value.is_empty() {
+            for name in prop.split_whitespace() {
+                insert_multi(props, name, Value::String(value.clone()));
+            }
+        }
+    }
+    while !stack.is_empty() {
+        pop_item(&mut stack, &mut items);
+    }
+    items
+}
+
+fn pop_item(stack: &mut Vec<(usize, Option<String>, Map<String, Value>)>, items: &mut Vec<Value>) {
+    let Some((_, prop, props)) = stack.pop() else {
+        return;
+    };
+    let value = Value::Object(props);
+    match (prop, stack.last_mut()) {
+        (Some(prop), Some((_, _, parent))) => insert_multi(parent, &prop, value),
+        _ => items.push(value),
+    }
+}
+
+/// `https://schema.org/Product` → `Product`
+fn schema_type(item_type: &str) -> String {
+    item_type
+        .split_whitespace()
+        .next()
+        .unwrap_or("")
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or("")
+        .to_string()
+}
+
+/// 同名屬性出現多次時轉為陣列
+fn insert_multi(map: &mut Map<String, Value>, key: &str, value: Value) {
+    match map.get_mut(key) {
+        Some(Value::Array(values)) => values.push(value),
+        Some(existing) => {
+            let first = existing.take();
+            *existing = Value::Array(vec![first, value]);
+        }
+        None => {
+            map.insert(key.to_string(), value);
+        }
+    }
+}
+
+/// 元素結束位置（對應的結束標籤；找不到時視為延伸到文件結尾）
+fn element_end(html: &str, tag: &Tag) -> usize {
+    let open = format!("<{}", tag.name);
+    let close = format!("</{}", tag.name);
+    let mut depth = 1usize;
+    let mut i = tag.end;
+    loop {
+        let next_close = find_ci(html, i, &close);
+        let next_open = find_ci(html, i, &open).filter(|o| {
+            html.as_bytes()
+                .get(o + open.len())
+                .is_some_and(|b| b.is_ascii_whitespace() || *b == b'>')
+        });
+        match (next_open, next_close) {
+            (Some(o), Some(c)) if o < c => {
+                depth += 1;
+                i = o + open.len();
+            }
+            (_, Some(c)) => {
+                depth -= 1;
+                if depth == 0 {
+                    return c;
+                }
+                i = c + close.len();
+            }
+            (_, None) => return html.len(),
+        }
+    }
+}
+
+/// 標籤後到下一個標籤前的文字
+fn inner_text(html: &str, tag: &Tag) -> String {
+    let rest = &html[tag.end..];
+    let text = &rest[..rest.find('<').unwrap_or(rest.len())];
+    decode_entities(&text.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+fn find_ci(html: &str, from: usize, needle: &str) -> Option<usize> {
+    let needle = needle.as_bytes();
+    html.as_bytes()[from..]
+        .windows(needle.len())
+        .position(|w| w.eq_ignore_ascii_case(needle))
+        .map(|p| from + p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_json_ld_graph() {
+        let html = r#"<script type="application/ld+json">
+            {"@context": "https://schema.org", "@graph": [
+                {"@type": "Article", "headline": "Rust 2024"},
+                {"@type": "FAQPage", "mainEntity": []}
+            ]}
+        </script><script type="application/ld+json">not json</script>"#;
+        let data = extract_structured(html).unwrap();
+        assert_eq!(data["json_ld"][0]["headline"], "Rust 2024");
+        assert_eq!(data["json_ld"][1]["@type"], "FAQPage");
+        assert_eq!(data["json_ld"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_opengraph() {
+        let html = r#"<head>
+            <meta property="og:title" content="Tom &amp; Jerry">
+            <meta property="og:image" content="https://a.png" />
+            <meta property='og:image' content='https://b.png'>
+            <meta name="description" content="ignored">
+        </head>"#;
+        let data = extract_structured(html).unwrap();
+        assert_eq!(data["opengraph"]["title"], "Tom & Jerry");
+        assert_eq!(
+            data["opengraph"]["image"],
+            json!(["https://a.png", "https://b.png"])
+        );
+        assert!(data.get("json_ld").is_none());
+    }
+
+    #[test]
+    fn test_microdata_nested() {
+        let html = r#"<div itemscope itemtype="https://schema.org/Product">
+            <span itemprop="name">Ferris Plush</span>
+            <div itemprop="offers" itemscope itemtype="https://schema.org/Offer">
+                <meta itemprop="price" content="19.99">
+                <span itemprop="priceCurrency">USD</span>
+            </div>
+            <a itemprop="url" href="https://shop.example/ferris">buy</a>
+        </div>
+        <p itemprop="orphan">ignored</p>"#;
+        let data = extract_structured(html).unwrap();
+        let product = &data["microdata"][0];
+        assert_eq!(product["@type"], "Product");
+        assert_eq!(product["name"], "Ferris Plush");
+        assert_eq!(product["offers"]["@type"], "Offer");
+        assert_eq!(product["offers"]["price"], "19.99");
+        assert_eq!(product["offers"]["priceCurrency"], "USD");
+        assert_eq!(product["url"], "https://shop.example/ferris");
+        assert_eq!(data["microdata"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_no_structured_data() {
+        assert!(
+            extract_structured(
+                "<p>plain <b>page</b></p><!-- <meta property=\"og:title\" content=\"x\"> -->"
+            )
+            .is_none()
+        );
+    }
+}
@@ -0,0 +1,373 @@
+//! URL 分流 — 批次抓取外部清單中的網址並分類（技術、頁面類型、可用性、標題）
+
+use crate::extract::extract_page;
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+use std::fmt::Write;
+
+/// 分類時只看前段內容即可，避免大頁面拖慢整批
+const TRIAGE_MAX_CHARS: usize = 5_000;
+
+/// 網址可用性
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Availability {
+    Reachable,
+    /// 連線失敗或 4xx / 5xx
+    Unreachable,
+    /// 停放 / 待售網域
+    Parked,
+    /// 內容需付費訂閱
+    Paywalled,
+}
+
+/// 頁面類型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PageType {
+    Homepage,
+    Article,
+    Documentation,
+    Repository,
+    Product,
+    Login,
+    Other,
+}
+
+/// 單一網址的分流結果
+#[derive(Debug, Clone, Serialize)]
+pub struct TriageRow {
+    pub url: String,
+    pub status: Option<u16>,
+    pub availability: Availability,
+    pub page_type: Option<PageType>,
+    pub technologies: Vec<String>,
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl TriageRow {
+    /// 無法抓取的網址（連線失敗、讀取失敗或被拒絕代抓）
+    pub fn unreachable(url: &str, status: Option<u16>, error: String) -> Self {
+        Self {
+            url: url.to_string(),
+            status,
+            availability: Availability::Unreachable,
+            page_type: None,
+            technologies: Vec::new(),
+            title: None,
+            error: Some(error),
+        }
+    }
+}
+
+/// 抓取並分類單一網址；網路錯誤記錄在 `error`，不會中斷整批
+pub async fn triage_url(http: &reqwest::Client, url: &str) -> TriageRow {
+    let unreachable = |status: Option<u16>, error: String| TriageRow::unreachable(url, status, error);
+
+    let resp = match http.get(url).send().await {
+        Ok(resp) => resp,
+        Err(e) => return unreachable(None, e.to_string()),
+    };
+    let status = resp.status();
+    let headers = resp.headers().clone();
+    let html = match resp.text().await {
+        Ok(html) => html,
+        Err(e) => return unreachable(Some(status.as_u16()), e.to_string()),
+    };
+
+    let mut row = classify(url, status.as_u16(), &headers, &html);
+    // 付費牆常以 402 / 403 回應，仍保留分類結果
+    if !status.is_success() && row.availability != Availability::Paywalled {
+        row.availability = Availability::Unreachable;
+        row.error = Some(format!("HTTP {status}"));
+    }
+    row
+}
+
+/// 依回應內容分類（不做網路請求）
+pub fn classify(url: &str, status: u16, headers: &HeaderMap, html: &str) -> TriageRow {
+    let page = extract_page(url, html, TRIAGE_MAX_CHARS);
+    let lower = html.to_ascii_lowercase();
+    let text = page.content.to_ascii_lowercase();
+
+    let availability = if is_parked(&lower, &text) {
+        Availability::Parked
+    } else if status == 402 || is_paywalled(&lower) {
+        Availability::Paywalled
+    } else {
+        Availability::Reachable
+    };
+
+    TriageRow {
+        url: url.to_string(),
+        status: Some(status),
+        availability,
+        page_type: Some(page_type(url, &lower)),
+        technologies: detect_technologies(headers, &lower),
+        title: page.title,
+        error: None,
+    }
+}
+
+const PARKED_MARKERS: &[&str] = &[
+    "this domain is for sale",
+    "domain may be for sale",
+    "buy this domain",
+    "this domain has been registered",
+    "domain is parked",
+    "parked free",
+    "sedoparking",
+    "parkingcrew",
+    "bodis.com",
+    "hugedomains",
+];
+
+fn is_parked(html: &str, text: &str) -> bool {
+    PARKED_MARKERS
+        .iter()
+        .any(|m| html.contains(m) || text.contains(m))
+}
+
+const PAYWALL_MARKERS: &[&str] = &[
+    "\"isaccessibleforfree\":false",
+    "\"isaccessibleforfree\": false",
+    "\"isaccessibleforfree\":\"false\"",
+    "\"isaccessibleforfree\": \"false\"",
+    "class=\"paywall",
+    "id=\"paywall",
+    "subscribe to continue reading",
+    "subscribers only",
+    "this content is for subscribers",
+];
+
+fn is_paywalled(html: &str) -> bool {
+    PAYWALL_MARKERS.iter().any(|m| html.contains(m))
+}
+
+fn page_type(url: &str, html: &str) -> PageType {
+    let parsed = url::Url::parse(url).ok();
+    let host = parsed
+        .as_ref()
+        .and_then(|u| u.host_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    let segments: Vec<&str> = parsed
+        .as_ref()
+        .and_then(|u| u.path_segments())
+        .map(|s| s.filter(|p| !p.is_empty()).collect())
+        .unwrap_or_default();
+
+    let code_host = ["github.com", "gitlab.com", "codeberg.org", "bitbucket.org"]
+        .iter()
+        .any(|h| host == *h);
+    if code_host && segments.len() >= 2 {
+        return PageType::Repository;
+    }
+    if host.starts_with("docs.")
+        || host.ends_with("readthedocs.io")
+        || host == "docs.rs"
+        || segments
+            .first()
+            .is_some_and(|s| matches!(*s, "docs" | "doc" | "documentation" | "api"))
+    {
+        return PageType::Documentation;
+    }
+    if html.contains("type=\"password\"") || html.contains("type='password'") {
+        return PageType::Login;
+    }
+    if html.contains("og:type\" content=\"product") || html.contains("\"@type\":\"product\"") {
+        return PageType::Product;
+    }
+    if html.contains("og:type\" content=\"article") || html.contains("<article") {
+        return PageType::Article;
+    }
+    if segments.is_empty() {
+        return PageType::Homepage;
+    }
+    PageType::Other
+}
+
+/// (技術名稱, HTML 特徵)
+const HTML_SIGNATURES: &[(&str, &str)] = &[
+    ("WordPress", "wp-content/"),
+    ("Drupal", "drupal-settings-json"),
+    ("Joomla", "content=\"joomla"),
+    ("Shopify", "cdn.shopify.com"),
+    ("Squarespace", "static.squarespace.com"),
+    ("Wix", "static.wixstatic.com"),
+    ("Ghost", "content=\"ghost"),
+    ("Hugo", "content=\"hugo"),
+    ("Next.js", "__next_data__"),
+    ("Nuxt", "__nuxt__"),
+    ("Gatsby", "___gatsby"),
+    ("Angular", "ng-version="),
+    ("React", "data-reactroot"),
+    ("Vue", "data-v-app"),
+    ("jQuery", "jquery"),
+];
+
+fn detect_technologies(headers: &HeaderMap, html: &str) -> Vec<String> {
+    let mut found: Vec<String> = Vec::new();
+    let mut push = |name: &str| {
+        if !found.iter().any(|f| f == name) {
+            found.push(name.to_string());
+        }
+    };
+
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_ascii_lowercase)
+    };
+    if let Some(server) = header("server") {
+        for (name, marker) in [
+            ("nginx", "nginx"),
+            ("Apache", "apache"),
+            ("Cloudflare", "cloudflare"),
+            ("IIS", "microsoft-iis"),
+        ] {
+            if server.contains(marker) {
+                push(name);
+            }
+        }
+    }
+    if headers.contains_key("cf-ray") {
+        push("Cloudflare");
+    }
+    if let Some(powered) = header("x-powered-by") {
+        for (name, marker) in [
+            ("PHP", "php"),
+            ("Express", "express"),
+            ("ASP.NET", "asp.net"),
+            ("Next.js", "next.js"),
+        ] {
+            if powered.contains(marker) {
+                push(name);
+            }
+        }
+    }
+
+    for (name, marker) in HTML_SIGNATURES {
+        if html.contains(marker) {
+            push(name);
+        }
+    }
+    found
+}
+
+/// 將分流結果輸出為 Markdown 表格
+pub fn render_table(rows: &[TriageRow]) -> String {
+    let mut out = String::from("| URL | Status | Availability | Type | Technologies | Title |\n");
+    out.push_str("|-----|--------|--------------|------|--------------|-------|\n");
+    for row in rows {
+        let status = row
+            .status
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| "-".into());
+        let availability = serde_json::to_value(row.availability).unwrap_or_default();
+        let page_type = row
+            .page_type
+            .and_then(|t| serde_json::to_value(t).ok())
+            .and_then(|v| v.as_str().map(String::from))
+            .unwrap_or_else(|| "-".into());
+        let technologies = if row.technologies.is_empty() {
+            "-".to_string()
+        } else {
+            row.technologies.join(", ")
+        };
+        let title = row
+            .title
+            .as_deref()
+            .or(row.error.as_deref())
+            .unwrap_or("-")
+            .replace('|', "\\|");
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} | {} | {} |",
+            row.url,
+            status,
+            availability.as_str().unwrap_or("-"),
+            page_type,
+            technologies,
+            title
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_classify_wordpress_article() {
+        let mut headers = HeaderMap::new();
+        headers.insert("server", HeaderValue::from_static("nginx/1.25"));
+        headers.insert("x-powered-by", HeaderValue::from_static("PHP/8.2"));
+        let html = r#"<html><head><title>Post</title>
+            <meta property="og:type" content="article">
+            <link rel="stylesheet" href="/wp-content/themes/x.css"></head>
+            <body><article>Hello</article></body></html>"#;
+
+        let row = classify("https://blog.example.com/2024/post", 200, &headers, html);
+        assert_eq!(row.availability, Availability::Reachable);
+        assert_eq!(row.page_type, Some(PageType::Article));
+        assert_eq!(row.technologies, vec!["nginx", "PHP", "WordPress"]);
+        assert_eq!(row.title.as_deref(), Some("Post"));
+    }
+
+    #[test]
+    fn test_classify_parked() {
+        let html = "<html><title>example.xyz</title><body>This domain is for sale! Contact HugeDomains</body></html>";
+        let row = classify("https://example.xyz/", 200, &HeaderMap::new(), html);
+        assert_eq!(row.availability, Availability::Parked);
+    }
+
+    #[test]
+    fn test_classify_paywalled() {
+        let html = r#"<script type="application/ld+json">{"isAccessibleForFree": false}</script>"#;
+        let row = classify("https://news.example.com/a", 200, &HeaderMap::new(), html);
+        assert_eq!(row.availability, Availability::Paywalled);
+    }
+
+    #[test]
+    fn test_page_types() {
+        assert_eq!(
+            page_type("https://github.com/tokio-rs/tokio", ""),
+            PageType::Repository
+        );
+        assert_eq!(
+            page_type("https://docs.rs/tokio", ""),
+            PageType::Documentation
+        );
+        assert_eq!(
+            page_type("https://example.com/docs/intro", ""),
+            PageType::Documentation
+        );
+        assert_eq!(
+            page_type("https://example.com/signin", "<input type=\"password\">"),
+            PageType::Login
+        );
+        assert_eq!(page_type("https://example.com/", ""), PageType::Homepage);
+        assert_eq!(page_type("https://example.com/about", ""), PageType::Other);
+    }
+
+    #[test]
+    fn test_render_table() {
+        let rows = vec![TriageRow {
+            url: "https://a.com".into(),
+            status: None,
+            availability: Availability::Unreachable,
+            page_type: None,
+            technologies: Vec::new(),
+            title: None,
+            error: Some("connection refused".into()),
+        }];
+        let table = render_table(&rows);
+        assert!(table.contains("| https://a.com | - | unreachable | - | - | connection refused |"));
+    }
+}
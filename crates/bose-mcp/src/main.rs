@@ -1,5 +1,8 @@
+use bose_common::bookmarks::{Bookmark, BookmarkStore, NewBookmark};
+use bose_common::event_log::EventLog;
+use bose_common::memory_cache::ResponseCache;
 use bose_common::*;
-use bose_searxng::SearxngClient;
+use bose_searxng::{SearxngClient, SearxngCluster};
 use rmcp::{
     ErrorData as McpError, ServerHandler, ServiceExt,
     handler::server::tool::ToolRouter,
@@ -9,6 +12,8 @@ use rmcp::{
     transport::stdio,
 };
 use std::fmt::Write;
+use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 struct WebSearchParams {
@@ -26,24 +31,81 @@ struct WebSearchParams {
 
     #[schemars(description = "Time range: day, week, month, year")]
     time_range: Option<String>,
+
+    #[schemars(description = "Also search in these languages by translating the query (e.g. [\"ja\", \"zh-CN\"]); requires a configured translator")]
+    cross_languages: Option<Vec<String>>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+struct DeepResearchParams {
+    #[schemars(description = "The research question; several questions separated by '?' or ';' are researched one by one")]
+    query: String,
+
+    #[schemars(description = "Maximum number of sub-questions (default: 4)")]
+    max_sub_questions: Option<u32>,
+
+    #[schemars(description = "Results per sub-question (default: 5)")]
+    results_per_question: Option<u32>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+struct BookmarkParams {
+    #[schemars(description = "URL of the result to keep")]
+    url: String,
+
+    #[schemars(description = "Title of the result")]
+    title: String,
+
+    #[schemars(description = "Snippet or key excerpt")]
+    snippet: Option<String>,
+
+    #[schemars(description = "Why this result matters; kept when the URL is bookmarked again without a note")]
+    note: Option<String>,
+
+    #[schemars(description = "The search query that found this result")]
+    query: Option<String>,
+
+    #[schemars(description = "Tags for grouping findings, e.g. [\"cve-2024-3094\"]")]
+    tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+struct ListBookmarksParams {
+    #[schemars(description = "Only bookmarks whose title, URL, note, query or tags contain this text")]
+    filter: Option<String>,
+
+    #[schemars(description = "Maximum number of bookmarks, newest first (default: 20)")]
+    limit: Option<u32>,
 }
 
 #[derive(Clone)]
 struct BoseSearchServer {
     client: SearxngClient,
+    /// 行程內快取，用於回報統計；使用 Redis 時為 None
+    cache: Option<Arc<ResponseCache>>,
+    bookmarks: Option<Arc<BookmarkStore>>,
+    router: RouterSettings,
     tool_router: ToolRouter<Self>,
 }
 
 #[tool_router]
 impl BoseSearchServer {
-    fn new(client: SearxngClient) -> Self {
+    fn new(
+        client: SearxngClient,
+        cache: Option<Arc<ResponseCache>>,
+        bookmarks: Option<Arc<BookmarkStore>>,
+        router: RouterSettings,
+    ) -> Self {
         Self {
             client,
+            cache,
+            bookmarks,
+            router,
             tool_router: Self::tool_router(),
         }
     }
 
-    #[tool(description = "Search the web via SearXNG meta-search engine (247 engines). Returns title, URL, snippet, source engine, and category for each result.")]
+    #[tool(description = "Search the web via SearXNG meta-search engine (247 engines). Returns title, URL, snippet, source engine, and category for each result, plus a direct answer or infobox when an engine provides one.")]
     async fn web_search(
         &self,
         Parameters(params): Parameters<WebSearchParams>,
@@ -56,10 +118,16 @@ impl BoseSearchServer {
         }
         query.language = params.language;
         query.time_range = params.time_range;
+        let intent = self.router.route(&mut query);
+        tracing::debug!(%intent, engines = ?query.engines, "Routed query");
 
-        match self.client.search(&query).await {
+        let outcome = match params.cross_languages.filter(|langs| !langs.is_empty()) {
+            Some(langs) => self.client.search_multilingual(&query, &langs).await,
+            None => self.client.search(&query).await,
+        };
+        match outcome {
             Ok(resp) => Ok(CallToolResult::success(vec![Content::text(
-                format_response(&resp),
+                format_response(&resp, self.router.is_simple(&params.query)),
             )])),
             Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
                 "Search failed: {e}"
@@ -67,12 +135,89 @@ impl BoseSearchServer {
         }
     }
 
+    #[tool(description = "Research a multi-part question: splits it into sub-questions, searches each one, removes sources repeated across sub-questions, and returns a report with findings per sub-question and a numbered source list.")]
+    async fn deep_research(
+        &self,
+        Parameters(params): Parameters<DeepResearchParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let per_question = params.results_per_question.unwrap_or(5);
+        let questions = research::sub_questions(&params.query, params.max_sub_questions.unwrap_or(4) as usize);
+
+        let mut sections = Vec::with_capacity(questions.len());
+        for question in questions {
+            let mut query = SearchQuery::new(&question).with_num_results(per_question);
+            self.router.route(&mut query);
+            let outcome = self.client.search(&query).await.map_err(|e| e.to_string());
+            sections.push((question, outcome));
+        }
+
+        if let Some((_, Err(e))) = sections.last().filter(|_| sections.iter().all(|(_, r)| r.is_err())) {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Research failed: {e}"
+            ))]));
+        }
+        Ok(CallToolResult::success(vec![Content::text(format_research(
+            &params.query,
+            &sections,
+            per_question as usize,
+        ))]))
+    }
+
+    #[tool(description = "Bookmark an important search result with an optional note and tags so it can be recalled in later sessions. Bookmarking the same URL again updates it.")]
+    async fn bookmark(
+        &self,
+        Parameters(params): Parameters<BookmarkParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let Some(store) = &self.bookmarks else {
+            return Ok(bookmarks_disabled());
+        };
+        let added = store.add(NewBookmark {
+            url: params.url,
+            title: params.title,
+            snippet: params.snippet,
+            note: params.note,
+            query: params.query,
+            tags: params.tags.unwrap_or_default(),
+        });
+        match added {
+            Ok(bookmark) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Bookmarked #{}: {}",
+                bookmark.id, bookmark.title
+            ))])),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Bookmark failed: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(description = "List bookmarked search results and notes saved in earlier sessions, newest first.")]
+    async fn list_bookmarks(
+        &self,
+        Parameters(params): Parameters<ListBookmarksParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let Some(store) = &self.bookmarks else {
+            return Ok(bookmarks_disabled());
+        };
+        let bookmarks = store.list(params.filter.as_deref());
+        let limit = params.limit.unwrap_or(20) as usize;
+        Ok(CallToolResult::success(vec![Content::text(format_bookmarks(
+            &bookmarks, limit,
+        ))]))
+    }
+
     #[tool(description = "Check if the SearXNG search backend is healthy and responding.")]
     async fn health_check(&self) -> Result<CallToolResult, McpError> {
         match self.client.health_check().await {
-            Ok(true) => Ok(CallToolResult::success(vec![Content::text(
-                "SearXNG is healthy",
-            )])),
+            Ok(true) => {
+                let mut text = "SearXNG is healthy".to_string();
+                if let Some(cluster) = self.client.cluster() {
+                    write!(text, "\n{}", format_assignments(&cluster.assignments())).unwrap();
+                }
+                if let Some(cache) = &self.cache {
+                    write!(text, "\n{}", format_cache_stats(&cache.stats())).unwrap();
+                }
+                Ok(CallToolResult::success(vec![Content::text(text)]))
+            }
             Ok(false) => Ok(CallToolResult::error(vec![Content::text(
                 "SearXNG is not responding",
             )])),
@@ -101,15 +246,21 @@ impl ServerHandler for BoseSearchServer {
             },
             instructions: Some(
                 "Bose Search — meta-search engine powered by SearXNG with 247 backends. \
-                 Use web_search to find information on any topic."
+                 Use web_search to find information on any topic, \
+                 and deep_research for multi-part questions that need a report with sources. \
+                 Use bookmark to keep important findings and list_bookmarks to recall them in later sessions."
                     .into(),
             ),
         }
     }
 }
 
-fn format_response(resp: &SearchResponse) -> String {
+/// 簡單查詢把直接答案放在最前面，其他查詢放在結果之後
+fn format_response(resp: &SearchResponse, simple: bool) -> String {
     let mut out = String::new();
+    if let Some(answer) = resp.instant_answer.as_ref().filter(|_| simple) {
+        out.push_str(&format_instant_answer(answer));
+    }
     writeln!(
         out,
         "Found {} results for \"{}\" ({:.1}s):\n",
@@ -118,23 +269,206 @@ fn format_response(resp: &SearchResponse) -> String {
         resp.elapsed_seconds
     )
     .unwrap();
+    if let Some(original) = &resp.corrected_from {
+        writeln!(out, "(No good results for \"{original}\"; showing the suggested spelling instead)\n").unwrap();
+    } else if !resp.suggestions.is_empty() {
+        writeln!(out, "Did you mean: {}\n", resp.suggestions.join(", ")).unwrap();
+    }
 
     for (i, r) in resp.results.iter().enumerate() {
         writeln!(out, "{}. [{}]({})", i + 1, r.title, r.url).unwrap();
-        writeln!(out, "   Source: {} | Category: {}", r.engine, r.category).unwrap();
+        write!(out, "   Source: {} | Category: {}", r.engine, r.category).unwrap();
+        if let Some(lang) = &r.language {
+            write!(out, " | Language: {lang}").unwrap();
+        }
+        writeln!(out).unwrap();
         if let Some(ref s) = r.snippet {
-            let truncated = if s.len() > 200 { &s[..200] } else { s };
+            let truncated = text::truncate_chars(s, 200);
             writeln!(out, "   {truncated}").unwrap();
         }
         writeln!(out).unwrap();
     }
 
+    if let Some(answer) = resp.instant_answer.as_ref().filter(|_| !simple) {
+        out.push_str(&format_instant_answer(answer));
+    }
     if !resp.engines_used.is_empty() {
         writeln!(out, "Engines: {}", resp.engines_used.join(", ")).unwrap();
     }
     out
 }
 
+/// 逐題列出結果，來源跨子問題去重後統一編號
+fn format_research(query: &str, sections: &[(String, Result<SearchResponse, String>)], per_question: usize) -> String {
+    let mut out = String::new();
+    writeln!(out, "Research: \"{query}\"").unwrap();
+    let mut sources: Vec<(&str, &str)> = Vec::new();
+    let mut engines: Vec<&str> = Vec::new();
+
+    for (i, (question, outcome)) in sections.iter().enumerate() {
+        writeln!(out, "\n{}. {question}", i + 1).unwrap();
+        let resp = match outcome {
+            Ok(resp) => resp,
+            Err(e) => {
+                writeln!(out, "   (search failed: {e})").unwrap();
+                continue;
+            }
+        };
+        if resp.results.is_empty() {
+            writeln!(out, "   (no results)").unwrap();
+        }
+        for r in resp.results.iter().take(per_question) {
+            let number = match sources.iter().position(|(url, _)| *url == r.url) {
+                Some(index) => index + 1,
+                None => {
+                    sources.push((&r.url, &r.title));
+                    sources.len()
+                }
+            };
+            match &r.snippet {
+                Some(snippet) => writeln!(out, "   [{number}] {}", text::ellipsize(snippet, 200)).unwrap(),
+                None => writeln!(out, "   [{number}] {}", r.title).unwrap(),
+            }
+        }
+        for engine in &resp.engines_used {
+            if !engines.contains(&engine.as_str()) {
+                engines.push(engine);
+            }
+        }
+    }
+
+    if !sources.is_empty() {
+        writeln!(out, "\nSources:").unwrap();
+    }
+    for (i, (url, title)) in sources.iter().enumerate() {
+        writeln!(out, "[{}] {title} — {url}", i + 1).unwrap();
+    }
+    if !engines.is_empty() {
+        writeln!(out, "\nEngines: {}", engines.join(", ")).unwrap();
+    }
+    out
+}
+
+fn bookmarks_disabled() -> CallToolResult {
+    CallToolResult::error(vec![Content::text(
+        "Bookmarks are disabled; set BOOKMARKS_PATH to enable them",
+    )])
+}
+
+fn format_bookmarks(bookmarks: &[Bookmark], limit: usize) -> String {
+    if bookmarks.is_empty() {
+        return "No bookmarks".to_string();
+    }
+    let mut out = String::new();
+    writeln!(out, "{} bookmarks:\n", bookmarks.len()).unwrap();
+    for b in bookmarks.iter().take(limit) {
+        writeln!(out, "#{} [{}]({})", b.id, b.title, b.url).unwrap();
+        write!(out, "   Saved: {}", b.created_at.format("%Y-%m-%d")).unwrap();
+        if let Some(query) = &b.query {
+            write!(out, " | Query: {query}").unwrap();
+        }
+        if !b.tags.is_empty() {
+            write!(out, " | Tags: {}", b.tags.join(", ")).unwrap();
+        }
+        writeln!(out).unwrap();
+        if let Some(note) = &b.note {
+            writeln!(out, "   Note: {note}").unwrap();
+        }
+        if let Some(snippet) = &b.snippet {
+            writeln!(out, "   {}", text::truncate_chars(snippet, 200)).unwrap();
+        }
+        writeln!(out).unwrap();
+    }
+    if bookmarks.len() > limit {
+        writeln!(out, "({} more not shown)", bookmarks.len() - limit).unwrap();
+    }
+    out
+}
+
+fn format_instant_answer(answer: &InstantAnswer) -> String {
+    let mut out = String::from("Answer");
+    if let Some(title) = &answer.title {
+        write!(out, " — {title}").unwrap();
+    }
+    out.push_str(":\n");
+    if !answer.text.is_empty() {
+        writeln!(out, "   {}", text::truncate_chars(&answer.text, 500)).unwrap();
+    }
+    for (label, value) in &answer.attributes {
+        writeln!(out, "   {label}: {value}").unwrap();
+    }
+    match (&answer.url, &answer.engine) {
+        (Some(url), Some(engine)) => writeln!(out, "   Source: {engine} | {url}").unwrap(),
+        (Some(url), None) => writeln!(out, "   Source: {url}").unwrap(),
+        (None, Some(engine)) => writeln!(out, "   Source: {engine}").unwrap(),
+        (None, None) => {}
+    }
+    writeln!(out).unwrap();
+    out
+}
+
+fn format_cache_stats(stats: &memory_cache::CacheStats) -> String {
+    format!(
+        "Cache: {} entries, {} hits / {} misses ({:.0}% hit ratio), {} evicted",
+        stats.entries,
+        stats.hits,
+        stats.misses,
+        stats.hit_ratio() * 100.0,
+        stats.evicted
+    )
+}
+
+fn format_assignments(assignments: &[bose_searxng::RegionAssignment]) -> String {
+    let mut out = String::from("Cluster:");
+    for a in assignments {
+        let primary = match (&a.primary, a.primary_latency_ms) {
+            (Some(url), Some(ms)) => format!("{url} ({ms}ms)"),
+            (Some(url), None) => url.clone(),
+            (None, _) => "none".to_string(),
+        };
+        write!(
+            out,
+            "\n  {}: primary {primary}, {} standby, {} unhealthy",
+            a.region,
+            a.standbys.len(),
+            a.unhealthy.len()
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// 有 `REDIS_URL` 時多個副本共用 Redis 快取，否則使用行程內記憶體（並回傳以便回報統計）
+async fn with_cache(
+    client: SearxngClient,
+    config: &BoseConfig,
+) -> anyhow::Result<(SearxngClient, Option<Arc<ResponseCache>>)> {
+    let redis_url = std::env::var("REDIS_URL").ok().filter(|v| !v.is_empty());
+
+    #[cfg(feature = "redis")]
+    if let Some(url) = &redis_url {
+        let backend =
+            bose_common::redis_cache::RedisBackend::connect(url, "bose", config.cache_ttl_secs)
+                .await?;
+        tracing::info!("Using Redis cache backend");
+        return Ok((client.with_cache(Arc::new(backend)), None));
+    }
+
+    #[cfg(not(feature = "redis"))]
+    if redis_url.is_some() {
+        tracing::warn!("REDIS_URL is set but bose-mcp was built without the `redis` feature");
+    }
+
+    if config.cache_max_entries == 0 {
+        return Ok((client, None));
+    }
+    let cache = Arc::new(ResponseCache::new(
+        Duration::from_secs(config.cache_ttl_secs),
+        config.cache_max_entries,
+    ));
+    Ok((client.with_cache(cache.clone()), Some(cache)))
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // tracing → stderr (stdout reserved for MCP JSON-RPC)
@@ -143,12 +477,44 @@ async fn main() -> anyhow::Result<()> {
         .with_writer(std::io::stderr)
         .init();
 
-    let config = BoseConfig::from_env();
+    let config = BoseConfig::from_env_or_file()?;
     let client = SearxngClient::new(&config)?;
+    if let Some(cluster) = client.cluster() {
+        SearxngCluster::spawn_prober(
+            cluster,
+            Duration::from_secs(config.searxng_probe_interval_secs),
+        );
+    }
+    let client = match EventLog::from_config(&config)? {
+        Some(events) => {
+            tracing::info!(path = %events.path().display(), "Search event log enabled");
+            client.with_event_log(Arc::new(events))
+        }
+        None => client,
+    };
+    let client = match bose_searxng::translator_from_env(&config)? {
+        Some(translator) => {
+            tracing::info!(translator = translator.name(), "Cross-language search enabled");
+            client.with_translator(translator)
+        }
+        None => client,
+    };
+    #[cfg(feature = "chaos")]
+    let client = match bose_searxng::FaultInjector::from_env()? {
+        Some(faults) => client.with_fault_injector(Arc::new(faults)),
+        None => client,
+    };
 
     tracing::info!(url = %config.searxng_url, "Bose MCP Server starting");
 
-    let server = BoseSearchServer::new(client);
+    let (client, cache) = with_cache(client, &config).await?;
+
+    let bookmarks = BookmarkStore::from_config(&config)?.map(|store| {
+        tracing::info!(path = %store.path().display(), "Bookmarks enabled");
+        Arc::new(store)
+    });
+
+    let server = BoseSearchServer::new(client, cache, bookmarks, config.router.clone());
     let service = server.serve(stdio()).await.inspect_err(|e| {
         tracing::error!(%e, "Failed to start MCP server");
     })?;
@@ -0,0 +1,259 @@
+//! 故障注入（`chaos` feature）— 在正式上線前驗證容錯移轉、重試與部分失敗處理
+//!
+//! 依引擎設定機率注入延遲、5xx 與截斷的 JSON。規則以 `BOSE_CHAOS` 設定：
+//!
+//! ```text
+//! BOSE_CHAOS="*:latency=0.2@300ms,error=0.05;google:error=0.5,truncate=0.1"
+//! ```
+//!
+//! `*` 為預設規則；單一引擎的查詢（例如串流搜尋的逐引擎請求）優先使用該引擎的規則。
+
+use bose_common::{BoseError, BoseResult};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 預設規則的鍵
+pub const ANY_ENGINE: &str = "*";
+
+/// 單一引擎的故障機率（0.0 ~ 1.0）
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FaultRule {
+    pub latency_probability: f64,
+    pub latency: Duration,
+    pub error_probability: f64,
+    pub truncate_probability: f64,
+}
+
+/// 故障注入器
+pub struct FaultInjector {
+    rules: HashMap<String, FaultRule>,
+    rng: AtomicU64,
+}
+
+impl FaultInjector {
+    pub fn new(rules: HashMap<String, FaultRule>) -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        Self {
+            rules,
+            rng: AtomicU64::new(seed | 1),
+        }
+    }
+
+    /// 固定亂數種子，讓測試可重現
+    pub fn with_seed(self, seed: u64) -> Self {
+        self.rng.store(seed | 1, Ordering::Relaxed);
+        self
+    }
+
+    /// 讀取 `BOSE_CHAOS`；未設定時為 None
+    pub fn from_env() -> BoseResult<Option<Self>> {
+        match std::env::var("BOSE_CHAOS") {
+            Ok(spec) if !spec.trim().is_empty() => Ok(Some(Self::parse(&spec)?)),
+            _ => Ok(None),
+        }
+    }
+
+    /// 解析規則：`引擎:latency=P@300ms,error=P,truncate=P;...`
+    pub fn parse(spec: &str) -> BoseResult<Self> {
+        let mut rules = HashMap::new();
+        for entry in spec.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+            let (engine, faults) = entry
+                .split_once(':')
+                .ok_or_else(|| chaos_error(entry, "expected `engine:fault=p,...`"))?;
+            let mut rule = FaultRule::default();
+            for fault in faults.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+                let (name, value) = fault
+                    .split_once('=')
+                    .ok_or_else(|| chaos_error(fault, "expected `fault=p`"))?;
+                match name.trim() {
+                    "latency" => {
+                        let (p, delay) = value
+                            .split_once('@')
+                            .ok_or_else(|| chaos_error(fault, "expected `latency=p@300ms`"))?;
+                        rule.latency_probability = probability(fault, p)?;
+                        rule.latency = duration(fault, delay)?;
+                    }
+                    "error" => rule.error_probability = probability(fault, value)?,
+                    "truncate" => rule.truncate_probability = probability(fault, value)?,
+                    other => return Err(chaos_error(other, "unknown fault")),
+                }
+            }
+            rules.insert(engine.trim().to_string(), rule);
+        }
+        Ok(Self::new(rules))
+    }
+
+    fn rule(&self, engine: &str) -> Option<&FaultRule> {
+        self.rules
+            .get(engine)
+            .or_else(|| self.rules.get(ANY_ENGINE))
+    }
+
+    /// xorshift64*，回傳 [0, 1)
+    fn next_f64(&self) -> f64 {
+        let mut x = 0;
+        let _ = self
+            .rng
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |mut s| {
+                s ^= s >> 12;
+                s ^= s << 25;
+                s ^= s >> 27;
+                x = s;
+                Some(s)
+            });
+        (x.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn hit(&self, probability: f64) -> bool {
+        probability > 0.0 && self.next_f64() < probability
+    }
+
+    /// 依規則改寫一次請求的結果：延遲、換成 503，或截斷成功回應的 JSON
+    pub async fn inject(
+        &self,
+        engine: &str,
+        outcome: reqwest::Result<reqwest::Response>,
+    ) -> reqwest::Result<reqwest::Response> {
+        let Some(rule) = self.rule(engine) else {
+            return outcome;
+        };
+
+        if self.hit(rule.latency_probability) {
+            tracing::warn!(
+                engine,
+                delay_ms = rule.latency.as_millis() as u64,
+                "Chaos: injected latency"
+            );
+            tokio::time::sleep(rule.latency).await;
+        }
+        if self.hit(rule.error_probability) {
+            tracing::warn!(engine, "Chaos: injected 503");
+            return Ok(synthetic(503, "injected failure".into()));
+        }
+        match outcome {
+            Ok(resp) if resp.status().is_success() && self.hit(rule.truncate_probability) => {
+                tracing::warn!(engine, "Chaos: truncated response body");
+                let status = resp.status().as_u16();
+                let mut body = resp.bytes().await?.to_vec();
+                body.truncate(body.len() / 2);
+                Ok(synthetic(status, body))
+            }
+            other => other,
+        }
+    }
+}
+
+fn synthetic(status: u16, body: Vec<u8>) -> reqwest::Response {
+    let response = http::Response::builder()
+        .status(status)
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(body)
+        .expect("static response parts are valid");
+    reqwest::Response::from(response)
+}
+
+fn chaos_error(input: &str, reason: &str) -> BoseError {
+    BoseError::ConfigError(format!("BOSE_CHAOS `{input}`: {reason}"))
+}
+
+fn probability(input: &str, value: &str) -> BoseResult<f64> {
+    match value.trim().parse::<f64>() {
+        Ok(p) if (0.0..=1.0).contains(&p) => Ok(p),
+        _ => Err(chaos_error(input, "probability must be between 0 and 1")),
+    }
+}
+
+fn duration(input: &str, value: &str) -> BoseResult<Duration> {
+    let value = value.trim();
+    let parsed = if let Some(ms) = value.strip_suffix("ms") {
+        ms.parse().ok().map(Duration::from_millis)
+    } else if let Some(s) = value.strip_suffix('s') {
+        s.parse().ok().map(Duration::from_secs_f64)
+    } else {
+        None
+    };
+    parsed.ok_or_else(|| chaos_error(input, "duration must end in `ms` or `s`"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SearxngClient;
+    use bose_common::SearchQuery;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_parse() {
+        let injector =
+            FaultInjector::parse("*:latency=0.2@300ms,error=0.05; google:error=1,truncate=0.5")
+                .unwrap();
+        assert_eq!(
+            injector.rule("bing"),
+            Some(&FaultRule {
+                latency_probability: 0.2,
+                latency: Duration::from_millis(300),
+                error_probability: 0.05,
+                truncate_probability: 0.0,
+            })
+        );
+        assert_eq!(injector.rule("google").unwrap().error_probability, 1.0);
+
+        for bad in [
+            "google",
+            "google:error=2",
+            "google:latency=0.1",
+            "google:boom=0.1",
+        ] {
+            assert!(FaultInjector::parse(bad).is_err(), "{bad}");
+        }
+        assert!(FaultInjector::parse("x:latency=0.1@1.5s").is_ok());
+    }
+
+    #[test]
+    fn test_probabilities() {
+        let injector = FaultInjector::new(HashMap::new()).with_seed(42);
+        assert!(!injector.hit(0.0));
+        assert!(injector.hit(1.0));
+        let hits = (0..10_000).filter(|_| injector.hit(0.3)).count();
+        assert!((2_700..3_300).contains(&hits), "{hits}");
+    }
+
+    async fn mock() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "query": "rust",
+                "results": [{ "url": "https://rust-lang.org", "title": "Rust", "engine": "google" }]
+            })))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn test_injected_failures_surface_as_errors() {
+        let server = mock().await;
+        let injector = FaultInjector::parse("google:error=1;bing:truncate=1").unwrap();
+        let client = SearxngClient::from_url(&server.uri())
+            .unwrap()
+            .with_fault_injector(std::sync::Arc::new(injector));
+
+        let google = SearchQuery::new("rust").with_engines(vec!["google".into()]);
+        assert!(matches!(
+            client.search(&google).await,
+            Err(BoseError::SearxngError(msg)) if msg.contains("503")
+        ));
+
+        let bing = SearchQuery::new("rust").with_engines(vec!["bing".into()]);
+        assert!(client.search(&bing).await.is_err());
+
+        // 沒有對應規則的引擎不受影響
+        let brave = SearchQuery::new("rust").with_engines(vec!["brave".into()]);
+        assert_eq!(client.search(&brave).await.unwrap().results.len(), 1);
+    }
+}
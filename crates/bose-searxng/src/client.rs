@@ -1,5 +1,14 @@
+use bose_common::cache::CacheBackend;
+use bose_common::event_log::{EventLog, SearchEvent};
+use bose_common::language::{self, LanguageFilter};
+use bose_common::retry::{with_backoff, RetryPolicy};
+use bose_common::fusion::{rrf_merge, RRF_K};
 use bose_common::{BoseConfig, BoseError, BoseResult, SearchQuery, SearchResponse};
+use crate::cluster::SearxngCluster;
+use crate::middleware::Middleware;
 use crate::response::SearxngResponse;
+use crate::translate::Translator;
+use std::sync::Arc;
 use std::time::Instant;
 
 /// SearXNG HTTP 客戶端
@@ -7,20 +16,50 @@ use std::time::Instant;
 pub struct SearxngClient {
     http: reqwest::Client,
     base_url: String,
+    cache: Option<Arc<dyn CacheBackend>>,
+    cluster: Option<Arc<SearxngCluster>>,
+    retry: RetryPolicy,
+    auto_correct_min_results: usize,
+    middleware: Vec<Arc<dyn Middleware>>,
+    events: Option<Arc<EventLog>>,
+    translator: Option<Arc<dyn Translator>>,
+    #[cfg(feature = "chaos")]
+    faults: Option<Arc<crate::chaos::FaultInjector>>,
 }
 
 impl SearxngClient {
     pub fn new(config: &BoseConfig) -> BoseResult<Self> {
-        let http = reqwest::Client::builder()
+        let builder = reqwest::Client::builder()
             .tcp_keepalive(std::time::Duration::from_secs(60))
             .timeout(std::time::Duration::from_secs(config.request_timeout_secs))
-            .user_agent("bose-search/0.1")
+            .user_agent("bose-search/0.1");
+        let http = config
+            .proxy
+            .apply(builder, "searxng")?
             .build()
             .map_err(BoseError::HttpError)?;
 
+        // 設定多個實例時自動組成叢集；探測由呼叫端以 `SearxngCluster::spawn_prober` 啟動
+        let cluster = (!config.searxng_instances.is_empty()).then(|| {
+            Arc::new(SearxngCluster::new(
+                http.clone(),
+                config.searxng_instances.clone(),
+                config.searxng_region.clone(),
+            ))
+        });
+
         Ok(Self {
             http,
             base_url: config.searxng_url.clone(),
+            cache: None,
+            cluster,
+            retry: RetryPolicy::with_retries(config.max_retries),
+            auto_correct_min_results: config.auto_correct_min_results,
+            middleware: Vec::new(),
+            events: None,
+            translator: None,
+            #[cfg(feature = "chaos")]
+            faults: None,
         })
     }
 
@@ -32,12 +71,230 @@ impl SearxngClient {
         Self::new(&config)
     }
 
+    /// 啟用回應快取：TTL 內相同（正規化後）的查詢直接由快取回應
+    pub fn with_cache(mut self, cache: Arc<dyn CacheBackend>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// 使用多實例叢集：請求送往選舉出的主要實例，失敗時依序轉往備援
+    pub fn with_cluster(mut self, cluster: Arc<SearxngCluster>) -> Self {
+        self.cluster = Some(cluster);
+        self
+    }
+
+    /// 覆寫重試策略（預設依 `max_retries` 設定）
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// 結果少於 `min_results` 且有拼字建議時，改用第一個建議重新搜尋（0 = 停用）
+    pub fn with_auto_correct(mut self, min_results: usize) -> Self {
+        self.auto_correct_min_results = min_results;
+        self
+    }
+
+    /// 註冊中介層（自訂日誌、標頭、指標）；依註冊順序執行
+    pub fn with_middleware(mut self, middleware: Arc<dyn Middleware>) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
+
+    /// 將每次搜尋（包含快取命中與失敗）附加到事件日誌
+    pub fn with_event_log(mut self, events: Arc<EventLog>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    pub fn event_log(&self) -> Option<&Arc<EventLog>> {
+        self.events.as_ref()
+    }
+
+    /// 啟用跨語言搜尋（`search_multilingual`）所需的查詢翻譯器
+    pub fn with_translator(mut self, translator: Arc<dyn Translator>) -> Self {
+        self.translator = Some(translator);
+        self
+    }
+
+    pub fn translator(&self) -> Option<&Arc<dyn Translator>> {
+        self.translator.as_ref()
+    }
+
+    /// 注入故障（延遲、5xx、截斷 JSON），用於驗證容錯行為
+    #[cfg(feature = "chaos")]
+    pub fn with_fault_injector(mut self, faults: Arc<crate::chaos::FaultInjector>) -> Self {
+        tracing::warn!("Fault injection enabled for SearXNG requests");
+        self.faults = Some(faults);
+        self
+    }
+
+    pub fn cluster(&self) -> Option<&Arc<SearxngCluster>> {
+        self.cluster.as_ref()
+    }
+
+    /// 依序嘗試的實例網址（單一實例時只有 `base_url`）
+    fn base_urls(&self) -> Vec<String> {
+        match &self.cluster {
+            Some(cluster) if !cluster.is_empty() => cluster.candidates(),
+            _ => vec![self.base_url.clone()],
+        }
+    }
+
     pub async fn search(&self, query: &SearchQuery) -> BoseResult<SearchResponse> {
+        let response = self.search_once(query).await?;
+        if response.results.len() >= self.auto_correct_min_results {
+            return Ok(response);
+        }
+        let Some(suggestion) = response
+            .suggestions
+            .first()
+            .filter(|s| !s.trim().is_empty() && !s.trim().eq_ignore_ascii_case(query.query.trim()))
+        else {
+            return Ok(response);
+        };
+
+        tracing::info!(query = %query.query, %suggestion, "Few results, retrying with suggestion");
+        let corrected_query = SearchQuery {
+            query: suggestion.clone(),
+            ..query.clone()
+        };
+        match self.search_once(&corrected_query).await {
+            Ok(mut corrected) if corrected.results.len() > response.results.len() => {
+                corrected.corrected_from = Some(query.query.clone());
+                Ok(corrected)
+            }
+            Ok(_) => Ok(response),
+            Err(e) => {
+                tracing::warn!(error = %e, "Corrected search failed, keeping original results");
+                Ok(response)
+            }
+        }
+    }
+
+    /// 跨語言搜尋：把查詢翻成各目標語言後平行搜尋，與原查詢的結果以 RRF 融合
+    ///
+    /// 每種語言的搜尋都帶上該語言的 `language` 參數；未偵測到內容語言的結果標記為該目標語言。
+    /// 翻譯或單一語言搜尋失敗只記錄警告，全部失敗時回傳原查詢的錯誤。
+    pub async fn search_multilingual(
+        &self,
+        query: &SearchQuery,
+        languages: &[String],
+    ) -> BoseResult<SearchResponse> {
+        let Some(translator) = &self.translator else {
+            return Err(BoseError::ConfigError(
+                "cross-language search requires TRANSLATE_URL or TRANSLATE_LLM_URL".into(),
+            ));
+        };
+        let start = Instant::now();
+
+        let mut targets: Vec<&str> = Vec::new();
+        for lang in languages.iter().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+            let same_as_query = query
+                .language
+                .as_deref()
+                .is_some_and(|q| q.eq_ignore_ascii_case(lang));
+            if !same_as_query && !targets.iter().any(|t| t.eq_ignore_ascii_case(lang)) {
+                targets.push(lang);
+            }
+        }
+
+        let translated = futures::future::join_all(targets.iter().map(|&lang| async move {
+            let text = translator.translate(&query.query, lang).await?;
+            tracing::info!(%lang, translated = %text, translator = translator.name(), "Translated query");
+            let localized = SearchQuery {
+                query: text,
+                language: Some(lang.to_string()),
+                ..query.clone()
+            };
+            let mut response = self.search(&localized).await?;
+            for result in &mut response.results {
+                result.language.get_or_insert_with(|| lang.to_string());
+            }
+            Ok::<_, BoseError>(response)
+        }));
+        let (original, translated) = futures::join!(self.search(query), translated);
+
+        let mut responses = Vec::new();
+        for (lang, outcome) in targets.iter().zip(translated) {
+            match outcome {
+                Ok(response) => responses.push(response),
+                Err(e) => tracing::warn!(%lang, error = %e, "Cross-language search failed"),
+            }
+        }
+        let mut merged = match original {
+            Ok(response) => response,
+            Err(e) if responses.is_empty() => return Err(e),
+            Err(e) => {
+                tracing::warn!(error = %e, "Original-language search failed");
+                SearchResponse {
+                    results: Vec::new(),
+                    query: query.query.clone(),
+                    elapsed_seconds: 0.0,
+                    total_results: None,
+                    engines_used: Vec::new(),
+                    suggestions: Vec::new(),
+                    corrected_from: None,
+                    instant_answer: None,
+                }
+            }
+        };
+
+        let mut lists = vec![std::mem::take(&mut merged.results)];
+        for response in responses {
+            lists.push(response.results);
+            merged.total_results = match (merged.total_results, response.total_results) {
+                (Some(a), Some(b)) => Some(a + b),
+                (a, b) => a.or(b),
+            };
+            for engine in response.engines_used {
+                if !merged.engines_used.contains(&engine) {
+                    merged.engines_used.push(engine);
+                }
+            }
+        }
+        merged.results = rrf_merge(&lists, RRF_K);
+        merged.elapsed_seconds = start.elapsed().as_secs_f64();
+        Ok(merged)
+    }
+
+    /// 單次搜尋（含快取與事件日誌），不做自動修正
+    async fn search_once(&self, query: &SearchQuery) -> BoseResult<SearchResponse> {
+        let start = Instant::now();
+        let outcome = self.cached_fetch(query).await;
+        if let Some(events) = &self.events {
+            let event = match &outcome {
+                Ok((response, cached)) => {
+                    SearchEvent::success("searxng", query, response, *cached, start.elapsed())
+                }
+                Err(e) => SearchEvent::failure("searxng", query, e, start.elapsed()),
+            };
+            if let Err(e) = events.append(event).await {
+                tracing::warn!(error = %e, "Failed to write search event");
+            }
+        }
+        outcome.map(|(response, _)| response)
+    }
+
+    /// 回傳回應與是否來自快取
+    async fn cached_fetch(&self, query: &SearchQuery) -> BoseResult<(SearchResponse, bool)> {
+        let Some(cache) = &self.cache else {
+            return Ok((self.fetch(query).await?, false));
+        };
+        if let Some(cached) = cache.get(query).await {
+            tracing::debug!(query = %query.query, "Cache hit");
+            return Ok((cached, true));
+        }
+        let response = self.fetch(query).await?;
+        cache.insert(query, response.clone()).await;
+        Ok((response, false))
+    }
+
+    async fn fetch(&self, query: &SearchQuery) -> BoseResult<SearchResponse> {
         let start = Instant::now();
 
         let mut url = format!(
-            "{}/search?q={}&format=json&number_of_results={}",
-            self.base_url,
+            "/search?q={}&format=json&number_of_results={}",
             urlencoding::encode(&query.query),
             query.num_results,
         );
@@ -51,18 +308,14 @@ impl SearxngClient {
         if let Some(ref tr) = query.time_range {
             url.push_str(&format!("&time_range={}", urlencoding::encode(tr)));
         }
+        if !query.engines.is_empty() {
+            url.push_str(&format!("&engines={}", urlencoding::encode(&query.engines.join(","))));
+        }
 
         tracing::info!(query = %query.query, "SearXNG search");
 
-        let resp = self.http.get(&url).send().await?;
-
-        if !resp.status().is_success() {
-            return Err(BoseError::SearxngError(
-                format!("HTTP {}", resp.status())
-            ));
-        }
-
-        let searxng_resp: SearxngResponse = resp.json().await?;
+        // 每次重試都重新走一遍所有實例，讓叢集有機會在退避期間恢復
+        let searxng_resp = with_backoff(&self.retry, || self.send(&url, &query.engines)).await?;
         let elapsed = start.elapsed().as_secs_f64();
 
         if !searxng_resp.unresponsive_engines.is_empty() {
@@ -72,8 +325,22 @@ impl SearxngClient {
             );
         }
 
-        let result_count = searxng_resp.results.len();
-        let response = searxng_resp.into_search_response(elapsed);
+        let mut response = searxng_resp.into_search_response(elapsed);
+        language::tag_languages(&mut response.results);
+
+        // 引擎常忽略 language 參數，依書寫系統再過濾一次
+        if let Some(filter) = query
+            .language
+            .as_deref()
+            .and_then(|lang| LanguageFilter::new(&[lang], &query.query))
+        {
+            let (kept, dropped) = filter.apply(std::mem::take(&mut response.results));
+            if dropped > 0 {
+                tracing::debug!(dropped, "Language filter removed results");
+            }
+            response.results = kept;
+        }
+        let result_count = response.results.len();
 
         tracing::info!(
             query = %query.query,
@@ -85,7 +352,57 @@ impl SearxngClient {
         Ok(response)
     }
 
+    /// 依序嘗試各實例；連線失敗或 5xx 時回報叢集並改用下一個實例
+    #[cfg_attr(not(feature = "chaos"), allow(unused_variables))]
+    async fn send(&self, path_and_query: &str, engines: &[String]) -> BoseResult<SearxngResponse> {
+        let bases = self.base_urls();
+        let mut last_error = None;
+        for base in &bases {
+            let mut request = self.http.get(format!("{base}{path_and_query}")).build()?;
+            for layer in &self.middleware {
+                layer.on_request(&mut request);
+            }
+            let url = request.url().clone();
+            let start = Instant::now();
+            let outcome = self.http.execute(request).await;
+            #[cfg(feature = "chaos")]
+            let outcome = match &self.faults {
+                Some(faults) => {
+                    let engine = match engines {
+                        [engine] => engine.as_str(),
+                        _ => crate::chaos::ANY_ENGINE,
+                    };
+                    faults.inject(engine, outcome).await
+                }
+                None => outcome,
+            };
+            for layer in &self.middleware {
+                match &outcome {
+                    Ok(resp) => layer.on_response(&url, resp, start.elapsed()),
+                    Err(e) => layer.on_error(&url, e, start.elapsed()),
+                }
+            }
+            let error = match outcome {
+                Ok(resp) if resp.status().is_success() => return Ok(resp.json().await?),
+                Ok(resp) if !resp.status().is_server_error() => {
+                    return Err(BoseError::SearxngError(format!("HTTP {}", resp.status())));
+                }
+                Ok(resp) => BoseError::SearxngError(format!("HTTP {}", resp.status())),
+                Err(e) => BoseError::HttpError(e),
+            };
+            if let Some(cluster) = &self.cluster {
+                cluster.report_failure(base);
+            }
+            last_error = Some(error);
+        }
+        Err(last_error.unwrap_or_else(|| BoseError::SearxngError("no SearXNG instance".into())))
+    }
+
+    /// 叢集模式下重新探測所有實例，至少一個健康即視為正常
     pub async fn health_check(&self) -> BoseResult<bool> {
+        if let Some(cluster) = self.cluster.as_ref().filter(|c| !c.is_empty()) {
+            return Ok(cluster.probe().await > 0);
+        }
         let url = format!("{}/search?q=test&format=json&number_of_results=1", self.base_url);
         match self.http.get(&url).send().await {
             Ok(resp) => Ok(resp.status().is_success()),
@@ -151,6 +468,48 @@ mod tests {
         assert!(resp.engines_used.contains(&"duckduckgo".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_auto_correct_with_suggestion() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .and(query_param("q", "rsut async"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "query": "rsut async",
+                "results": [],
+                "suggestions": ["rust async"]
+            })))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .and(query_param("q", "rust async"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "query": "rust async",
+                "results": [{ "url": "https://tokio.rs", "title": "Tokio", "engine": "google" }]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        // 預設停用：只回傳建議
+        let client = SearxngClient::from_url(&mock_server.uri()).unwrap();
+        let resp = client.search(&SearchQuery::new("rsut async")).await.unwrap();
+        assert!(resp.results.is_empty());
+        assert_eq!(resp.suggestions, vec!["rust async"]);
+        assert!(resp.corrected_from.is_none());
+
+        let resp = client
+            .with_auto_correct(3)
+            .search(&SearchQuery::new("rsut async"))
+            .await
+            .unwrap();
+        assert_eq!(resp.query, "rust async");
+        assert_eq!(resp.corrected_from.as_deref(), Some("rsut async"));
+        assert_eq!(resp.results.len(), 1);
+    }
+
     #[tokio::test]
     async fn test_search_with_category() {
         let mock_server = MockServer::start().await;
@@ -182,6 +541,54 @@ mod tests {
         assert_eq!(resp.results[0].category, "it");
     }
 
+    #[tokio::test]
+    async fn test_search_with_engines() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .and(query_param("engines", "google,brave"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "query": "rust",
+                "results": []
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = SearxngClient::from_url(&mock_server.uri()).unwrap();
+        let query = SearchQuery::new("rust")
+            .with_engines(vec!["google".into(), "brave".into()]);
+        assert!(client.search(&query).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_search_language_filter() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .and(query_param("language", "en"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "query": "Tokio runtime",
+                "results": [
+                    { "url": "https://tokio.rs", "title": "Tokio runtime docs", "engine": "google" },
+                    { "url": "https://example.cn/a", "title": "非同步執行期介紹", "engine": "bing" },
+                    { "url": "https://example.cn/b", "title": "Tokio 執行期介紹", "engine": "bing" }
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = SearxngClient::from_url(&mock_server.uri()).unwrap();
+        let mut query = SearchQuery::new("Tokio runtime");
+        query.language = Some("en".into());
+        let resp = client.search(&query).await.unwrap();
+
+        assert_eq!(resp.results.len(), 2);
+        assert_eq!(resp.results[1].url, "https://example.cn/b");
+    }
+
     #[tokio::test]
     async fn test_search_http_error() {
         let mock_server = MockServer::start().await;
@@ -203,6 +610,152 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_search_retries_transient_5xx() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "query": "rust",
+                "results": [{ "url": "https://rust-lang.org", "title": "Rust", "engine": "google" }]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = SearxngClient::from_url(&mock_server.uri()).unwrap();
+        let resp = client.search(&SearchQuery::new("rust")).await.unwrap();
+        assert_eq!(resp.results.len(), 1);
+
+        // 關閉重試時第一次 5xx 就回傳錯誤
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&mock_server)
+            .await;
+        let client = client.with_retry_policy(RetryPolicy::none());
+        assert!(client.search(&SearchQuery::new("rust")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_middleware_hooks() {
+        use std::sync::Mutex;
+        use wiremock::matchers::header;
+
+        #[derive(Default)]
+        struct Recorder(Mutex<Vec<String>>);
+
+        impl Middleware for Recorder {
+            fn on_request(&self, request: &mut reqwest::Request) {
+                request
+                    .headers_mut()
+                    .insert("x-trace-id", reqwest::header::HeaderValue::from_static("abc"));
+            }
+
+            fn on_response(
+                &self,
+                url: &reqwest::Url,
+                response: &reqwest::Response,
+                _elapsed: std::time::Duration,
+            ) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("{} {}", url.path(), response.status().as_u16()));
+            }
+        }
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .and(header("x-trace-id", "abc"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "query": "rust",
+                "results": [],
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let recorder = Arc::new(Recorder::default());
+        let client = SearxngClient::from_url(&mock_server.uri())
+            .unwrap()
+            .with_middleware(recorder.clone());
+        client.search(&SearchQuery::new("rust")).await.unwrap();
+        assert_eq!(*recorder.0.lock().unwrap(), vec!["/search 200"]);
+    }
+
+    #[tokio::test]
+    async fn test_search_uses_cache() {
+        use bose_common::memory_cache::ResponseCache;
+        use std::time::Duration;
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "query": "rust",
+                "results": [],
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let cache = Arc::new(ResponseCache::new(Duration::from_secs(60), 10));
+        let client = SearxngClient::from_url(&mock_server.uri())
+            .unwrap()
+            .with_cache(cache.clone());
+        client.search(&SearchQuery::new("rust")).await.unwrap();
+        client.search(&SearchQuery::new("Rust ")).await.unwrap();
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_search_fails_over_to_standby() {
+        let down = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(502))
+            .expect(1)
+            .mount(&down)
+            .await;
+        let up = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "query": "rust",
+                "results": [{ "url": "https://rust-lang.org", "title": "Rust", "engine": "google" }]
+            })))
+            .mount(&up)
+            .await;
+
+        let instances = bose_common::SearxngInstance::parse_list(&format!(
+            "eu={},eu={}",
+            down.uri(),
+            up.uri()
+        ));
+        let cluster = Arc::new(SearxngCluster::new(reqwest::Client::new(), instances, None));
+        let client = SearxngClient::from_url("http://unused.invalid")
+            .unwrap()
+            .with_cluster(cluster.clone());
+
+        // 第一次請求打到失效的主要實例後轉往備援，之後直接使用新的主要實例
+        for _ in 0..2 {
+            let resp = client.search(&SearchQuery::new("rust")).await.unwrap();
+            assert_eq!(resp.results.len(), 1);
+        }
+        assert_eq!(cluster.assignments()[0].primary, Some(up.uri()));
+    }
+
     #[tokio::test]
     async fn test_health_check_success() {
         let mock_server = MockServer::start().await;
@@ -235,4 +788,62 @@ mod tests {
         let healthy = client.health_check().await.unwrap();
         assert!(!healthy);
     }
+
+    struct FixedTranslator;
+
+    impl Translator for FixedTranslator {
+        fn translate<'a>(
+            &'a self,
+            _text: &'a str,
+            target: &'a str,
+        ) -> bose_common::cache::BoxFuture<'a, BoseResult<String>> {
+            Box::pin(async move {
+                match target {
+                    "ja" => Ok("ルーター 脆弱性".to_string()),
+                    _ => Err(BoseError::TranslationError(format!("unsupported {target}"))),
+                }
+            })
+        }
+
+        fn name(&self) -> &'static str {
+            "fixed"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_multilingual() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .and(query_param("q", "router vulnerability"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "query": "router vulnerability",
+                "results": [{ "url": "https://nvd.nist.gov/a", "title": "Router advisory", "engine": "google" }]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .and(query_param("q", "ルーター 脆弱性"))
+            .and(query_param("language", "ja"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "query": "ルーター 脆弱性",
+                "results": [{ "url": "https://jvn.jp/vu/1", "title": "JVN#1 ルーターにおける脆弱性", "engine": "bing" }]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = SearxngClient::from_url(&mock_server.uri()).unwrap();
+        let query = SearchQuery::new("router vulnerability");
+        let langs = vec!["ja".to_string(), "ko".to_string()];
+        assert!(client.search_multilingual(&query, &langs).await.is_err());
+
+        let client = client.with_translator(Arc::new(FixedTranslator));
+        let resp = client.search_multilingual(&query, &langs).await.unwrap();
+        assert_eq!(resp.query, "router vulnerability");
+        assert_eq!(resp.results.len(), 2);
+        let jvn = resp.results.iter().find(|r| r.url == "https://jvn.jp/vu/1").unwrap();
+        assert_eq!(jvn.language.as_deref(), Some("ja"));
+        assert!(resp.engines_used.contains(&"bing".to_string()));
+    }
 }
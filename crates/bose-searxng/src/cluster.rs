@@ -0,0 +1,457 @@
+//! SearXNG 叢集 — 多個實例的主要 / 熱備援選舉
+//!
+//! 定期探測每個實例的延遲，在每個區域選出延遲最低的健康實例作為主要實例，其餘作為熱備援。
+//! 主要實例失敗時立即標記為不健康並重新選舉，流量自動轉移到同區域的備援，
+//! 同區域全部失效時再轉往其他區域。
+
+use bose_common::SearxngInstance;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+/// 現任主要實例的延遲在最佳者的此倍數內時不換手，避免延遲抖動造成頻繁切換
+const HYSTERESIS_RATIO: f64 = 1.25;
+/// 延遲差距小於此值時同樣不換手
+const HYSTERESIS_SLACK: Duration = Duration::from_millis(20);
+
+#[derive(Debug, Clone, Default)]
+struct Probe {
+    healthy: bool,
+    latency: Option<Duration>,
+}
+
+struct Member {
+    instance: SearxngInstance,
+    probe: Probe,
+}
+
+/// 單一區域的選舉結果
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RegionAssignment {
+    pub region: String,
+    /// 主要實例；區域內沒有健康實例時為 None
+    pub primary: Option<String>,
+    pub primary_latency_ms: Option<u64>,
+    /// 健康的備援實例（依延遲排序）
+    pub standbys: Vec<String>,
+    pub unhealthy: Vec<String>,
+}
+
+/// 多實例 SearXNG 叢集
+pub struct SearxngCluster {
+    http: reqwest::Client,
+    local_region: Option<String>,
+    members: Mutex<Vec<Member>>,
+    /// 區域 → 主要實例在 `members` 的索引
+    primaries: Mutex<BTreeMap<String, usize>>,
+}
+
+impl SearxngCluster {
+    /// 建立叢集；尚未探測前所有實例都視為健康，並以設定順序的第一個作為主要實例
+    pub fn new(
+        http: reqwest::Client,
+        instances: Vec<SearxngInstance>,
+        local_region: Option<String>,
+    ) -> Self {
+        let members = instances
+            .into_iter()
+            .map(|instance| Member {
+                instance,
+                probe: Probe {
+                    healthy: true,
+                    ..Probe::default()
+                },
+            })
+            .collect();
+        let cluster = Self {
+            http,
+            local_region,
+            members: Mutex::new(members),
+            primaries: Mutex::new(BTreeMap::new()),
+        };
+        cluster.elect();
+        cluster
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 並行探測所有實例後重新選舉，回傳健康實例數
+    pub async fn probe(&self) -> usize {
+        let urls: Vec<String> = self
+            .members
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|m| m.instance.url.clone())
+            .collect();
+        let probes =
+            futures::future::join_all(urls.iter().map(|url| probe_instance(&self.http, url))).await;
+
+        let healthy = {
+            let mut members = self.members.lock().unwrap();
+            for (member, probe) in members.iter_mut().zip(probes) {
+                member.probe = probe;
+            }
+            members.iter().filter(|m| m.probe.healthy).count()
+        };
+        self.elect();
+        healthy
+    }
+
+    /// 回報實例請求失敗：標記為不健康並立即重新選舉
+    pub fn report_failure(&self, url: &str) {
+        {
+            let mut members = self.members.lock().unwrap();
+            for member in members.iter_mut().filter(|m| m.instance.url == url) {
+                member.probe.healthy = false;
+            }
+        }
+        tracing::warn!(instance = %url, "SearXNG instance failed, re-electing");
+        self.elect();
+    }
+
+    /// 依每個區域的探測結果選出主要實例
+    fn elect(&self) {
+        let members = self.members.lock().unwrap();
+        let mut primaries = self.primaries.lock().unwrap();
+
+        let mut regions: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+        for (i, member) in members.iter().enumerate() {
+            regions
+                .entry(member.instance.region.as_str())
+                .or_default()
+                .push(i);
+        }
+
+        for (region, indices) in regions {
+            let best = indices
+                .iter()
+                .copied()
+                .filter(|&i| members[i].probe.healthy)
+                .min_by_key(|&i| members[i].probe.latency.unwrap_or(Duration::MAX));
+            let current = primaries
+                .get(region)
+                .copied()
+                .filter(|&i| members[i].probe.healthy);
+
+            let elected = match (current, best) {
+                (Some(current), Some(best))
+                    if keeps_primary(&members[current].probe, &members[best].probe) =>
+                {
+                    Some(current)
+                }
+                (_, best) => best,
+            };
+
+            let previous = primaries.get(region).copied();
+            match elected {
+                Some(i) => {
+                    if previous != Some(i) {
+                        tracing::info!(
+                            region,
+                            primary = %members[i].instance.url,
+                            latency_ms = ?members[i].probe.latency.map(|l| l.as_millis()),
+                            "SearXNG primary elected"
+                        );
+                    }
+                    primaries.insert(region.to_string(), i);
+                }
+                None => {
+                    if previous.is_some() {
+                        tracing::warn!(region, "No healthy SearXNG instance in region");
+                    }
+                    primaries.remove(region);
+                }
+            }
+        }
+    }
+
+    /// 送出請求時依序嘗試的實例網址
+    ///
+    /// 順序：本機區域主要 → 本機區域備援 → 其他區域主要 → 其他健康實例 → 不健康實例
+    /// （探測結果可能過時，最後仍給它們一次機會）。
+    pub fn candidates(&self) -> Vec<String> {
+        let members = self.members.lock().unwrap();
+        let primaries = self.primaries.lock().unwrap();
+        let local = self
+            .local_region
+            .as_deref()
+            .or_else(|| members.first().map(|m| m.instance.region.as_str()));
+
+        let rank = |i: usize| {
+            let member = &members[i];
+            let is_local = Some(member.instance.region.as_str()) == local;
+            let is_primary = primaries.get(&member.instance.region) == Some(&i);
+            let tier = match (member.probe.healthy, is_local, is_primary) {
+                (false, _, _) => 4,
+                (true, true, true) => 0,
+                (true, true, false) => 1,
+                (true, false, true) => 2,
+                (true, false, false) => 3,
+            };
+            (tier, member.probe.latency.unwrap_or(Duration::MAX), i)
+        };
+
+        let mut order: Vec<usize> = (0..members.len()).collect();
+        order.sort_by_key(|&i| rank(i));
+        order
+            .into_iter()
+            .map(|i| members[i].instance.url.clone())
+            .collect()
+    }
+
+    /// 目前每個區域的主要 / 備援分配
+    pub fn assignments(&self) -> Vec<RegionAssignment> {
+        let members = self.members.lock().unwrap();
+        let primaries = self.primaries.lock().unwrap();
+
+        let mut regions: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+        for (i, member) in members.iter().enumerate() {
+            regions
+                .entry(member.instance.region.as_str())
+                .or_default()
+                .push(i);
+        }
+
+        regions
+            .into_iter()
+            .map(|(region, mut indices)| {
+                let primary = primaries.get(region).copied();
+                indices.sort_by_key(|&i| members[i].probe.latency.unwrap_or(Duration::MAX));
+                let url = |i: usize| members[i].instance.url.clone();
+                RegionAssignment {
+                    region: region.to_string(),
+                    primary: primary.map(url),
+                    primary_latency_ms: primary
+                        .and_then(|i| members[i].probe.latency)
+                        .map(|l| l.as_millis() as u64),
+                    standbys: indices
+                        .iter()
+                        .copied()
+                        .filter(|&i| Some(i) != primary && members[i].probe.healthy)
+                        .map(url)
+                        .collect(),
+                    unhealthy: indices
+                        .iter()
+                        .copied()
+                        .filter(|&i| !members[i].probe.healthy)
+                        .map(url)
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// Prometheus 文字格式的叢集指標
+    pub fn render_metrics(&self) -> String {
+        let members = self.members.lock().unwrap();
+        let primaries = self.primaries.lock().unwrap();
+        let mut out = String::new();
+
+        writeln!(
+            out,
+            "# HELP bose_searxng_instance_up Whether the last probe of the instance succeeded."
+        )
+        .unwrap();
+        writeln!(out, "# TYPE bose_searxng_instance_up gauge").unwrap();
+        for m in members.iter() {
+            writeln!(
+                out,
+                "bose_searxng_instance_up{{region=\"{}\",instance=\"{}\"}} {}",
+                m.instance.region,
+                m.instance.url,
+                u8::from(m.probe.healthy)
+            )
+            .unwrap();
+        }
+
+        writeln!(
+            out,
+            "# HELP bose_searxng_probe_latency_seconds Latency of the last successful probe."
+        )
+        .unwrap();
+        writeln!(out, "# TYPE bose_searxng_probe_latency_seconds gauge").unwrap();
+        for m in members.iter() {
+            if let Some(latency) = m.probe.latency {
+                writeln!(
+                    out,
+                    "bose_searxng_probe_latency_seconds{{region=\"{}\",instance=\"{}\"}} {:.3}",
+                    m.instance.region,
+                    m.instance.url,
+                    latency.as_secs_f64()
+                )
+                .unwrap();
+            }
+        }
+
+        writeln!(
+            out,
+            "# HELP bose_searxng_primary Whether the instance is the elected primary of its region."
+        )
+        .unwrap();
+        writeln!(out, "# TYPE bose_searxng_primary gauge").unwrap();
+        for (i, m) in members.iter().enumerate() {
+            let is_primary = primaries.get(&m.instance.region) == Some(&i);
+            writeln!(
+                out,
+                "bose_searxng_primary{{region=\"{}\",instance=\"{}\"}} {}",
+                m.instance.region,
+                m.instance.url,
+                u8::from(is_primary)
+            )
+            .unwrap();
+        }
+        out
+    }
+
+    /// 啟動背景探測任務（需在 tokio runtime 內呼叫）；任務只持有弱參照，叢集釋放後自動結束
+    pub fn spawn_prober(cluster: &Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        let weak: Weak<Self> = Arc::downgrade(cluster);
+        tokio::spawn(async move {
+            loop {
+                let Some(cluster) = weak.upgrade() else {
+                    break;
+                };
+                let healthy = cluster.probe().await;
+                tracing::debug!(healthy, total = cluster.len(), "SearXNG cluster probed");
+                drop(cluster);
+                tokio::time::sleep(interval).await;
+            }
+        })
+    }
+}
+
+/// 現任主要實例是否仍夠快而不需要換手
+fn keeps_primary(current: &Probe, best: &Probe) -> bool {
+    match (current.latency, best.latency) {
+        (Some(current), Some(best)) => {
+            current.as_secs_f64() <= best.as_secs_f64() * HYSTERESIS_RATIO
+                || current <= best + HYSTERESIS_SLACK
+        }
+        // 尚未量到延遲時維持現狀
+        (None, _) | (_, None) => true,
+    }
+}
+
+async fn probe_instance(http: &reqwest::Client, base_url: &str) -> Probe {
+    let url = format!("{base_url}/search?q=test&format=json&number_of_results=1");
+    let start = Instant::now();
+    let healthy = match http.get(&url).send().await {
+        Ok(resp) => resp.status().is_success(),
+        Err(_) => false,
+    };
+    Probe {
+        healthy,
+        latency: healthy.then(|| start.elapsed()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn instance(region: &str, url: &str) -> SearxngInstance {
+        SearxngInstance {
+            region: region.into(),
+            url: url.into(),
+        }
+    }
+
+    async fn server(status: u16, delay_ms: u64) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .respond_with(
+                ResponseTemplate::new(status)
+                    .set_body_json(serde_json::json!({ "query": "test", "results": [] }))
+                    .set_delay(Duration::from_millis(delay_ms)),
+            )
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn test_elects_fastest_healthy_per_region() {
+        let slow = server(200, 150).await;
+        let fast = server(200, 0).await;
+        let down = server(503, 0).await;
+        let us = server(200, 0).await;
+
+        let cluster = SearxngCluster::new(
+            reqwest::Client::new(),
+            vec![
+                instance("eu", &slow.uri()),
+                instance("eu", &fast.uri()),
+                instance("eu", &down.uri()),
+                instance("us", &us.uri()),
+            ],
+            Some("eu".into()),
+        );
+        // 探測前以設定順序的第一個為主要實例
+        assert_eq!(cluster.candidates()[0], slow.uri());
+
+        assert_eq!(cluster.probe().await, 3);
+        let assignments = cluster.assignments();
+        assert_eq!(assignments[0].region, "eu");
+        assert_eq!(assignments[0].primary.as_deref(), Some(fast.uri().as_str()));
+        assert_eq!(assignments[0].standbys, vec![slow.uri()]);
+        assert_eq!(assignments[0].unhealthy, vec![down.uri()]);
+        assert_eq!(assignments[1].primary.as_deref(), Some(us.uri().as_str()));
+
+        assert_eq!(
+            cluster.candidates(),
+            vec![fast.uri(), slow.uri(), us.uri(), down.uri()]
+        );
+
+        let metrics = cluster.render_metrics();
+        assert!(metrics.contains(&format!(
+            "bose_searxng_primary{{region=\"eu\",instance=\"{}\"}} 1",
+            fast.uri()
+        )));
+        assert!(metrics.contains(&format!(
+            "bose_searxng_instance_up{{region=\"eu\",instance=\"{}\"}} 0",
+            down.uri()
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_failure_shifts_traffic() {
+        let a = server(200, 0).await;
+        let b = server(200, 0).await;
+        let cluster = SearxngCluster::new(
+            reqwest::Client::new(),
+            vec![instance("eu", &a.uri()), instance("us", &b.uri())],
+            None,
+        );
+        cluster.probe().await;
+        assert_eq!(cluster.candidates()[0], a.uri());
+
+        // 本機區域沒有健康實例時轉往其他區域
+        cluster.report_failure(&a.uri());
+        assert_eq!(cluster.candidates(), vec![b.uri(), a.uri()]);
+        assert!(cluster.assignments()[0].primary.is_none());
+    }
+
+    #[test]
+    fn test_hysteresis() {
+        let probe = |ms| Probe {
+            healthy: true,
+            latency: Some(Duration::from_millis(ms)),
+        };
+        assert!(keeps_primary(&probe(110), &probe(100)));
+        assert!(keeps_primary(&probe(30), &probe(15)));
+        assert!(!keeps_primary(&probe(200), &probe(100)));
+    }
+}
@@ -1,6 +1,16 @@
 //! SearXNG 客戶端 — 元搜尋引擎 HTTP 客戶端
 
+#[cfg(feature = "chaos")]
+pub mod chaos;
 pub mod client;
+pub mod cluster;
+pub mod middleware;
 pub mod response;
+pub mod translate;
 
+#[cfg(feature = "chaos")]
+pub use chaos::FaultInjector;
 pub use client::SearxngClient;
+pub use cluster::{RegionAssignment, SearxngCluster};
+pub use middleware::Middleware;
+pub use translate::{LibreTranslate, LlmTranslator, Translator, translator_from_env};
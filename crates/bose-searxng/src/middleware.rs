@@ -0,0 +1,18 @@
+//! 請求 / 回應中介層 — 不修改客戶端即可加入自訂日誌、標頭或指標
+//!
+//! 以 `SearxngClient::with_middleware` 註冊；每次送往 SearXNG 實例的請求（包含容錯移轉與重試）
+//! 依註冊順序經過 `on_request`，完成後呼叫 `on_response` 或 `on_error`。
+
+use std::time::Duration;
+
+/// 中介層；所有方法預設不做任何事，只需實作用得到的掛鉤
+pub trait Middleware: Send + Sync {
+    /// 送出前呼叫，可修改請求（例如加入標頭）
+    fn on_request(&self, _request: &mut reqwest::Request) {}
+
+    /// 收到回應時呼叫（包含非 2xx 回應）
+    fn on_response(&self, _url: &reqwest::Url, _response: &reqwest::Response, _elapsed: Duration) {}
+
+    /// 連線失敗、逾時等沒有回應的錯誤
+    fn on_error(&self, _url: &reqwest::Url, _error: &reqwest::Error, _elapsed: Duration) {}
+}
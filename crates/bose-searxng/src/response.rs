@@ -1,5 +1,6 @@
 use serde::Deserialize;
-use bose_common::{SearchResult, SearchResponse};
+use bose_common::url::{canonicalize, dedup_key};
+use bose_common::{InstantAnswer, SearchResult, SearchResponse};
 
 /// SearXNG JSON 回應的頂層結構
 #[derive(Debug, Deserialize)]
@@ -12,6 +13,49 @@ pub struct SearxngResponse {
     pub suggestions: Vec<String>,
     #[serde(default)]
     pub unresponsive_engines: Vec<(String, String)>,
+    #[serde(default)]
+    pub answers: Vec<SearxngAnswer>,
+    #[serde(default)]
+    pub infoboxes: Vec<SearxngInfobox>,
+}
+
+/// SearXNG 直接答案；舊版為純字串，新版為物件
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum SearxngAnswer {
+    Text(String),
+    Object {
+        answer: String,
+        url: Option<String>,
+        engine: Option<String>,
+    },
+}
+
+/// SearXNG 資訊框（知識面板）
+#[derive(Debug, Deserialize)]
+pub struct SearxngInfobox {
+    pub infobox: String,
+    /// 資訊框的來源網址
+    pub id: Option<String>,
+    pub content: Option<String>,
+    pub engine: Option<String>,
+    #[serde(default)]
+    pub urls: Vec<SearxngLink>,
+    #[serde(default)]
+    pub attributes: Vec<SearxngAttribute>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearxngLink {
+    pub title: Option<String>,
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearxngAttribute {
+    pub label: String,
+    /// 數值、日期等非字串值以 JSON 原樣呈現
+    pub value: serde_json::Value,
 }
 
 /// SearXNG 單個搜尋結果
@@ -29,16 +73,64 @@ impl From<SearxngResult> for SearchResult {
     fn from(r: SearxngResult) -> Self {
         Self {
             title: r.title,
-            url: r.url,
+            url: canonicalize(&r.url),
             snippet: r.content,
             engine: r.engine.unwrap_or_else(|| "unknown".to_string()),
             score: r.score,
             category: r.category.unwrap_or_else(|| "general".to_string()),
+            language: None,
+        }
+    }
+}
+
+impl From<SearxngAnswer> for InstantAnswer {
+    fn from(answer: SearxngAnswer) -> Self {
+        let (text, url, engine) = match answer {
+            SearxngAnswer::Text(text) => (text, None, None),
+            SearxngAnswer::Object { answer, url, engine } => (answer, url, engine),
+        };
+        Self {
+            title: None,
+            text,
+            url,
+            attributes: Vec::new(),
+            engine,
+        }
+    }
+}
+
+impl From<SearxngInfobox> for InstantAnswer {
+    fn from(infobox: SearxngInfobox) -> Self {
+        let url = infobox.id.filter(|id| id.starts_with("http"))
+            .or_else(|| infobox.urls.into_iter().next().map(|link| link.url));
+        let attributes = infobox.attributes.into_iter()
+            .map(|a| {
+                let value = match a.value {
+                    serde_json::Value::String(s) => s,
+                    other => other.to_string(),
+                };
+                (a.label, value)
+            })
+            .collect();
+        Self {
+            title: Some(infobox.infobox),
+            text: infobox.content.unwrap_or_default(),
+            url,
+            attributes,
+            engine: infobox.engine,
         }
     }
 }
 
 impl SearxngResponse {
+    /// 直接答案優先於資訊框，各取第一個
+    fn instant_answer(answers: Vec<SearxngAnswer>, infoboxes: Vec<SearxngInfobox>) -> Option<InstantAnswer> {
+        answers.into_iter()
+            .map(InstantAnswer::from)
+            .find(|a| !a.text.trim().is_empty())
+            .or_else(|| infoboxes.into_iter().next().map(Into::into))
+    }
+
     pub fn into_search_response(self, elapsed: f64) -> SearchResponse {
         let engines_used: Vec<String> = self.results.iter()
             .filter_map(|r| r.engine.clone())
@@ -46,12 +138,22 @@ impl SearxngResponse {
             .into_iter()
             .collect();
 
+        // 只差追蹤參數的網址 SearXNG 不會合併，保留排名較前的一筆
+        let mut seen = std::collections::HashSet::new();
+        let results = self.results.into_iter()
+            .map(SearchResult::from)
+            .filter(|r| seen.insert(dedup_key(&r.url)))
+            .collect();
+
         SearchResponse {
             query: self.query,
-            results: self.results.into_iter().map(Into::into).collect(),
+            results,
             elapsed_seconds: elapsed,
             total_results: self.number_of_results,
             engines_used,
+            suggestions: self.suggestions,
+            corrected_from: None,
+            instant_answer: Self::instant_answer(self.answers, self.infoboxes),
         }
     }
 }
@@ -153,5 +255,56 @@ mod tests {
         assert_eq!(search_resp.results.len(), 1);
         assert_eq!(search_resp.elapsed_seconds, 0.5);
         assert_eq!(search_resp.total_results, Some(100));
+        assert!(search_resp.instant_answer.is_none());
+        assert_eq!(search_resp.suggestions, vec!["rust lang"]);
+    }
+
+    #[test]
+    fn test_canonicalizes_and_dedups_urls() {
+        let mut json = sample_searxng_json();
+        json["results"] = serde_json::json!([
+            { "url": "https://Example.com/post?utm_source=rss#comments", "title": "Post", "engine": "bing" },
+            { "url": "https://example.com/post/", "title": "Post", "engine": "google" },
+            { "url": "https://other.org/?fbclid=x", "title": "Other", "engine": "google" },
+        ]);
+        let resp: SearxngResponse = serde_json::from_value(json).unwrap();
+        let urls: Vec<String> = resp.into_search_response(0.1).results.into_iter().map(|r| r.url).collect();
+        assert_eq!(urls, vec!["https://example.com/post", "https://other.org"]);
+    }
+
+    #[test]
+    fn test_answers_preferred_over_infobox() {
+        let mut json = sample_searxng_json();
+        json["answers"] = serde_json::json!([
+            "42",
+            {"answer": "1 mile = 1.609 km", "url": "https://example.com/units", "engine": "unit converter"}
+        ]);
+        json["infoboxes"] = serde_json::json!([{ "infobox": "Rust", "content": "A language" }]);
+        let resp: SearxngResponse = serde_json::from_value(json).unwrap();
+        let answer = resp.into_search_response(0.1).instant_answer.unwrap();
+        assert_eq!(answer.text, "42");
+        assert!(answer.title.is_none());
+    }
+
+    #[test]
+    fn test_infobox_to_instant_answer() {
+        let mut json = sample_searxng_json();
+        json["infoboxes"] = serde_json::json!([{
+            "infobox": "Rust (programming language)",
+            "id": "https://en.wikipedia.org/wiki/Rust_(programming_language)",
+            "content": "Rust is a general-purpose programming language.",
+            "engine": "wikipedia",
+            "urls": [{"title": "Official website", "url": "https://rust-lang.org"}],
+            "attributes": [
+                {"label": "Designed by", "value": "Graydon Hoare"},
+                {"label": "First appeared", "value": 2015}
+            ]
+        }]);
+        let resp: SearxngResponse = serde_json::from_value(json).unwrap();
+        let answer = resp.into_search_response(0.1).instant_answer.unwrap();
+        assert_eq!(answer.title.as_deref(), Some("Rust (programming language)"));
+        assert_eq!(answer.url.as_deref(), Some("https://en.wikipedia.org/wiki/Rust_(programming_language)"));
+        assert_eq!(answer.engine.as_deref(), Some("wikipedia"));
+        assert_eq!(answer.attributes[1], ("First appeared".to_string(), "2015".to_string()));
     }
 }
@@ -0,0 +1,257 @@
+//! 查詢翻譯 — 跨語言搜尋前把查詢翻成目標語言
+//!
+//! 內建兩種翻譯器：LibreTranslate API 與 OpenAI 相容的 chat completions 端點
+//! （OpenAI、Ollama、vLLM 等）。`translator_from_env()` 依環境變數選擇其一。
+
+use bose_common::cache::BoxFuture;
+use bose_common::{BoseConfig, BoseError, BoseResult, Secret, load_secret};
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// 查詢翻譯器
+pub trait Translator: Send + Sync {
+    /// 將 `text` 翻成 `target`（ISO 639-1 或 SearXNG 語言碼，例如 `ja`、`zh-TW`）
+    fn translate<'a>(&'a self, text: &'a str, target: &'a str) -> BoxFuture<'a, BoseResult<String>>;
+
+    fn name(&self) -> &'static str;
+}
+
+/// 依環境變數建立翻譯器：`TRANSLATE_URL`（LibreTranslate）優先，其次 `TRANSLATE_LLM_URL`
+///
+/// 請求沿用 `config` 的逾時與代理設定（`ENGINE_PROXIES` 的 `translate` 項目）。
+pub fn translator_from_env(config: &BoseConfig) -> BoseResult<Option<Arc<dyn Translator>>> {
+    let env = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+    if env("TRANSLATE_URL").is_none() && env("TRANSLATE_LLM_URL").is_none() {
+        return Ok(None);
+    }
+    let builder = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(config.request_timeout_secs));
+    let http = config
+        .proxy
+        .apply(builder, "translate")?
+        .build()
+        .map_err(BoseError::HttpError)?;
+    if let Some(url) = env("TRANSLATE_URL") {
+        let translator = LibreTranslate::new(http, url, load_secret("TRANSLATE_API_KEY")?);
+        return Ok(Some(Arc::new(translator)));
+    }
+    if let Some(url) = env("TRANSLATE_LLM_URL") {
+        let model = env("TRANSLATE_LLM_MODEL").unwrap_or_else(|| DEFAULT_LLM_MODEL.to_string());
+        let translator = LlmTranslator::new(http, url, model, load_secret("TRANSLATE_LLM_API_KEY")?);
+        return Ok(Some(Arc::new(translator)));
+    }
+    Ok(None)
+}
+
+/// 翻譯服務的語言碼只取主要部分（`zh-TW` → `zh`）
+fn primary_language(code: &str) -> &str {
+    code.split(['-', '_']).next().unwrap_or(code)
+}
+
+fn translate_error(provider: &str, message: impl std::fmt::Display) -> BoseError {
+    BoseError::TranslationError(format!("{provider}: {message}"))
+}
+
+/// LibreTranslate（`POST /translate`）
+pub struct LibreTranslate {
+    http: reqwest::Client,
+    base_url: String,
+    api_key: Option<Secret>,
+}
+
+#[derive(Deserialize)]
+struct LibreTranslateResponse {
+    #[serde(rename = "translatedText")]
+    translated_text: String,
+}
+
+impl LibreTranslate {
+    pub fn new(http: reqwest::Client, base_url: impl Into<String>, api_key: Option<Secret>) -> Self {
+        Self {
+            http,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            api_key,
+        }
+    }
+
+    async fn request(&self, text: &str, target: &str) -> BoseResult<String> {
+        let mut body = serde_json::json!({
+            "q": text,
+            "source": "auto",
+            "target": primary_language(target),
+            "format": "text",
+        });
+        if let Some(key) = &self.api_key {
+            body["api_key"] = key.expose().into();
+        }
+        let resp = self
+            .http
+            .post(format!("{}/translate", self.base_url))
+            .json(&body)
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            return Err(translate_error(self.name(), format!("HTTP {}", resp.status())));
+        }
+        let parsed: LibreTranslateResponse = resp.json().await?;
+        Ok(parsed.translated_text.trim().to_string())
+    }
+}
+
+impl Translator for LibreTranslate {
+    fn translate<'a>(&'a self, text: &'a str, target: &'a str) -> BoxFuture<'a, BoseResult<String>> {
+        Box::pin(self.request(text, target))
+    }
+
+    fn name(&self) -> &'static str {
+        "libretranslate"
+    }
+}
+
+const DEFAULT_LLM_MODEL: &str = "gpt-4o-mini";
+
+/// OpenAI 相容的 chat completions 端點（`POST {base}/chat/completions`）
+pub struct LlmTranslator {
+    http: reqwest::Client,
+    base_url: String,
+    model: String,
+    api_key: Option<Secret>,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+#[derive(Deserialize)]
+struct ChatMessage {
+    content: String,
+}
+
+impl LlmTranslator {
+    pub fn new(
+        http: reqwest::Client,
+        base_url: impl Into<String>,
+        model: impl Into<String>,
+        api_key: Option<Secret>,
+    ) -> Self {
+        Self {
+            http,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            model: model.into(),
+            api_key,
+        }
+    }
+
+    async fn request(&self, text: &str, target: &str) -> BoseResult<String> {
+        let body = serde_json::json!({
+            "model": self.model,
+            "temperature": 0,
+            "messages": [
+                {
+                    "role": "system",
+                    "content": format!(
+                        "Translate the user's web search query into the language with code `{target}`. \
+                         Keep product names, version numbers and identifiers (CVE IDs, model numbers) unchanged. \
+                         Reply with the translated query only."
+                    ),
+                },
+                { "role": "user", "content": text },
+            ],
+        });
+        let mut request = self
+            .http
+            .post(format!("{}/chat/completions", self.base_url))
+            .json(&body);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key.expose());
+        }
+        let resp = request.send().await?;
+        if !resp.status().is_success() {
+            return Err(translate_error(self.name(), format!("HTTP {}", resp.status())));
+        }
+        let parsed: ChatResponse = resp.json().await?;
+        let content = parsed
+            .choices
+            .into_iter()
+            .next()
+            .map(|c| c.message.content)
+            .ok_or_else(|| translate_error(self.name(), "empty response"))?;
+        // 模型偶爾會把譯文包在引號裡
+        Ok(content.trim().trim_matches(['"', '「', '」']).trim().to_string())
+    }
+}
+
+impl Translator for LlmTranslator {
+    fn translate<'a>(&'a self, text: &'a str, target: &'a str) -> BoxFuture<'a, BoseResult<String>> {
+        Box::pin(self.request(text, target))
+    }
+
+    fn name(&self) -> &'static str {
+        "llm"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_libretranslate() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .and(body_partial_json(serde_json::json!({
+                "q": "router vulnerability",
+                "target": "zh",
+                "api_key": "k",
+            })))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "translatedText": "路由器漏洞" })),
+            )
+            .mount(&server)
+            .await;
+
+        let translator =
+            LibreTranslate::new(reqwest::Client::new(), server.uri(), Some(Secret::new("k")));
+        let text = translator.translate("router vulnerability", "zh-TW").await.unwrap();
+        assert_eq!(text, "路由器漏洞");
+    }
+
+    #[tokio::test]
+    async fn test_llm_translator() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(header("authorization", "Bearer k"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{ "message": { "role": "assistant", "content": "\"ルーター 脆弱性\"\n" } }]
+            })))
+            .mount(&server)
+            .await;
+
+        let translator =
+            LlmTranslator::new(reqwest::Client::new(), server.uri(), "m", Some(Secret::new("k")));
+        assert_eq!(translator.translate("router vulnerability", "ja").await.unwrap(), "ルーター 脆弱性");
+    }
+
+    #[tokio::test]
+    async fn test_translation_http_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&server)
+            .await;
+        let translator = LibreTranslate::new(reqwest::Client::new(), server.uri(), None);
+        let err = translator.translate("q", "ja").await.unwrap_err();
+        assert!(matches!(err, BoseError::TranslationError(_)));
+    }
+}
@@ -0,0 +1,451 @@
+//! 查詢分析 - 由查詢歷史統計熱門查詢、各引擎延遲百分位、升級率與快取效益
+//!
+//! 資料來自 `ResultStore`：查詢紀錄提供查詢文字與使用層級，`record_call()` 記下的引擎呼叫
+//! 提供延遲與快取命中。`QueryAnalytics::compute()` 是純函式，`collect()` 直接讀資料庫。
+
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::routing::RetrievalTier;
+use crate::storage::{tier_name, EngineCall, QueryRecord, ResultStore};
+use crate::types::SearchError;
+
+/// 一個熱門查詢
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopQuery {
+    /// 最早出現的原始寫法（統計時忽略大小寫與多餘空白）
+    pub query: String,
+    pub count: usize,
+    pub last_seen: SystemTime,
+}
+
+/// 單一引擎的延遲分佈
+#[derive(Debug, Clone, PartialEq)]
+pub struct EngineLatency {
+    pub engine: String,
+    pub calls: usize,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+/// 階梯式檢索的升級統計（只計入有記錄層級的查詢）
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EscalationStats {
+    pub l1: usize,
+    pub l2: usize,
+    pub l3: usize,
+}
+
+impl EscalationStats {
+    pub fn tiered(&self) -> usize {
+        self.l1 + self.l2 + self.l3
+    }
+
+    /// 升級到 L2 以上的比例；沒有資料時為 0
+    pub fn rate(&self) -> f32 {
+        ratio(self.l2 + self.l3, self.tiered())
+    }
+
+    fn add(&mut self, tier: RetrievalTier) {
+        match tier {
+            RetrievalTier::L1 => self.l1 += 1,
+            RetrievalTier::L2 => self.l2 += 1,
+            RetrievalTier::L3 => self.l3 += 1,
+        }
+    }
+}
+
+/// 一個時間區段的快取命中
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CacheBucket {
+    pub start: SystemTime,
+    pub lookups: usize,
+    pub hits: usize,
+}
+
+impl CacheBucket {
+    pub fn hit_rate(&self) -> f32 {
+        ratio(self.hits, self.lookups)
+    }
+}
+
+/// 快取效益（只計入啟用快取時的呼叫）
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheEffectiveness {
+    pub lookups: usize,
+    pub hits: usize,
+    /// 估計省下的時間：命中次數 ×（未命中平均延遲 − 命中平均延遲）
+    pub time_saved: Duration,
+    /// 依時間先後排序，只含有呼叫的區段
+    pub buckets: Vec<CacheBucket>,
+}
+
+impl CacheEffectiveness {
+    pub fn hit_rate(&self) -> f32 {
+        ratio(self.hits, self.lookups)
+    }
+}
+
+/// 查詢統計
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryStats {
+    pub queries: usize,
+    pub distinct_queries: usize,
+    pub top_queries: Vec<TopQuery>,
+    /// 依呼叫次數由多到少
+    pub latency: Vec<EngineLatency>,
+    pub escalation: EscalationStats,
+    pub cache: CacheEffectiveness,
+}
+
+/// 統計設定
+#[derive(Debug, Clone)]
+pub struct QueryAnalytics {
+    top: usize,
+    bucket: Duration,
+}
+
+impl Default for QueryAnalytics {
+    fn default() -> Self {
+        Self {
+            top: 10,
+            bucket: Duration::from_secs(86_400),
+        }
+    }
+}
+
+impl QueryAnalytics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 熱門查詢的數量（預設 10）
+    pub fn with_top(mut self, top: usize) -> Self {
+        self.top = top;
+        self
+    }
+
+    /// 快取命中率的時間區段長度（預設一天，至少一秒）
+    pub fn with_bucket(mut self, bucket: Duration) -> Self {
+        self.bucket = bucket.max(Duration::from_secs(1));
+        self
+    }
+
+    /// 讀取 `[from, to)` 區間內的歷史並統計
+    pub fn collect(
+        &self,
+        store: &ResultStore,
+        from: SystemTime,
+        to: SystemTime,
+    ) -> Result<QueryStats, SearchError> {
+        Ok(self.compute(&store.between(from, to)?, &store.calls_between(from, to)?))
+    }
+
+    pub fn compute(&self, records: &[QueryRecord], calls: &[EngineCall]) -> QueryStats {
+        let top_queries = top_queries(records);
+        let mut escalation = EscalationStats::default();
+        for tier in records.iter().filter_map(|r| r.tier) {
+            escalation.add(tier);
+        }
+        QueryStats {
+            queries: records.len(),
+            distinct_queries: top_queries.len(),
+            top_queries: top_queries.into_iter().take(self.top).collect(),
+            latency: engine_latency(calls),
+            escalation,
+            cache: self.cache_effectiveness(calls),
+        }
+    }
+
+    fn cache_effectiveness(&self, calls: &[EngineCall]) -> CacheEffectiveness {
+        let bucket_secs = self.bucket.as_secs();
+        let mut buckets: Vec<CacheBucket> = Vec::new();
+        let (mut hit_latency, mut miss_latency) = (Vec::new(), Vec::new());
+        for call in calls {
+            let Some(hit) = call.cache_hit else { continue };
+            if hit {
+                hit_latency.push(call.latency);
+            } else {
+                miss_latency.push(call.latency);
+            }
+
+            let secs = call.recorded_at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+            let start = UNIX_EPOCH + Duration::from_secs(secs - secs % bucket_secs);
+            let index = match buckets.binary_search_by_key(&start, |b| b.start) {
+                Ok(index) => index,
+                Err(index) => {
+                    buckets.insert(index, CacheBucket { start, lookups: 0, hits: 0 });
+                    index
+                }
+            };
+            buckets[index].lookups += 1;
+            buckets[index].hits += hit as usize;
+        }
+
+        let time_saved = match (mean(&hit_latency), mean(&miss_latency)) {
+            (Some(hit), Some(miss)) => miss.saturating_sub(hit) * hit_latency.len() as u32,
+            _ => Duration::ZERO,
+        };
+        CacheEffectiveness {
+            lookups: hit_latency.len() + miss_latency.len(),
+            hits: hit_latency.len(),
+            time_saved,
+            buckets,
+        }
+    }
+}
+
+fn top_queries(records: &[QueryRecord]) -> Vec<TopQuery> {
+    let mut by_key: HashMap<String, TopQuery> = HashMap::new();
+    for record in records {
+        let key = record.query.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+        let entry = by_key.entry(key).or_insert_with(|| TopQuery {
+            query: record.query.clone(),
+            count: 0,
+            last_seen: record.recorded_at,
+        });
+        entry.count += 1;
+        entry.last_seen = entry.last_seen.max(record.recorded_at);
+    }
+    let mut top: Vec<TopQuery> = by_key.into_values().collect();
+    top.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then(b.last_seen.cmp(&a.last_seen))
+            .then_with(|| a.query.cmp(&b.query))
+    });
+    top
+}
+
+fn engine_latency(calls: &[EngineCall]) -> Vec<EngineLatency> {
+    let mut by_engine: HashMap<&str, Vec<Duration>> = HashMap::new();
+    for call in calls {
+        by_engine.entry(&call.engine).or_default().push(call.latency);
+    }
+    let mut latency: Vec<EngineLatency> = by_engine
+        .into_iter()
+        .map(|(engine, mut samples)| {
+            samples.sort();
+            EngineLatency {
+                engine: engine.to_string(),
+                calls: samples.len(),
+                p50: percentile(&samples, 50),
+                p90: percentile(&samples, 90),
+                p99: percentile(&samples, 99),
+                max: samples.last().copied().unwrap_or_default(),
+            }
+        })
+        .collect();
+    latency.sort_by(|a, b| b.calls.cmp(&a.calls).then_with(|| a.engine.cmp(&b.engine)));
+    latency
+}
+
+/// 最近排名法（nearest-rank）的百分位數；`sorted` 須已排序
+fn percentile(sorted: &[Duration], p: usize) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (p * sorted.len()).div_ceil(100).max(1);
+    sorted[rank.min(sorted.len()) - 1]
+}
+
+fn mean(samples: &[Duration]) -> Option<Duration> {
+    let total: Duration = samples.iter().sum();
+    (!samples.is_empty()).then(|| total / samples.len() as u32)
+}
+
+fn ratio(part: usize, total: usize) -> f32 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f32 / total as f32
+    }
+}
+
+impl fmt::Display for QueryStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "查詢: {}（不重複 {}）", self.queries, self.distinct_queries)?;
+
+        if !self.top_queries.is_empty() {
+            writeln!(f, "\n熱門查詢:")?;
+            for (i, top) in self.top_queries.iter().enumerate() {
+                writeln!(f, "  {:>2}. {} ×{}", i + 1, top.query, top.count)?;
+            }
+        }
+
+        if !self.latency.is_empty() {
+            writeln!(f, "\n引擎延遲 (p50 / p90 / p99 / max):")?;
+            for engine in &self.latency {
+                writeln!(
+                    f,
+                    "  {:<12} {:>5} 次  {} / {} / {} / {} ms",
+                    engine.engine,
+                    engine.calls,
+                    engine.p50.as_millis(),
+                    engine.p90.as_millis(),
+                    engine.p99.as_millis(),
+                    engine.max.as_millis()
+                )?;
+            }
+        }
+
+        let escalation = &self.escalation;
+        if escalation.tiered() > 0 {
+            writeln!(
+                f,
+                "\n升級率: {:.1}%（{}: {}，{}: {}，{}: {}）",
+                escalation.rate() * 100.0,
+                tier_name(RetrievalTier::L1),
+                escalation.l1,
+                tier_name(RetrievalTier::L2),
+                escalation.l2,
+                tier_name(RetrievalTier::L3),
+                escalation.l3
+            )?;
+        }
+
+        let cache = &self.cache;
+        if cache.lookups > 0 {
+            writeln!(
+                f,
+                "\n快取命中率: {:.1}%（{}/{}，估計省下 {:.1} 秒）",
+                cache.hit_rate() * 100.0,
+                cache.hits,
+                cache.lookups,
+                cache.time_saved.as_secs_f32()
+            )?;
+            for bucket in &cache.buckets {
+                let secs = bucket.start.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+                let start = DateTime::<Utc>::from(bucket.start);
+                write!(f, "  {}", start.format("%Y-%m-%d"))?;
+                if secs % 86_400 != 0 {
+                    write!(f, " {}", start.format("%H:%M"))?;
+                }
+                writeln!(
+                    f,
+                    "  {:.1}%（{}/{}）",
+                    bucket.hit_rate() * 100.0,
+                    bucket.hits,
+                    bucket.lookups
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 86_400;
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    fn record(id: i64, query: &str, tier: Option<RetrievalTier>, secs: u64) -> QueryRecord {
+        QueryRecord {
+            id,
+            query: query.to_string(),
+            params: serde_json::Value::Null,
+            tier,
+            confidence: None,
+            recorded_at: at(secs),
+            results: Vec::new(),
+        }
+    }
+
+    fn call(engine: &str, ms: u64, cache_hit: Option<bool>, secs: u64) -> EngineCall {
+        EngineCall {
+            query_id: 0,
+            engine: engine.to_string(),
+            latency: Duration::from_millis(ms),
+            cache_hit,
+            recorded_at: at(secs),
+        }
+    }
+
+    #[test]
+    fn test_top_queries_and_escalation() {
+        let records = vec![
+            record(1, "Rust async", Some(RetrievalTier::L1), 10),
+            record(2, "rust  async", Some(RetrievalTier::L2), 20),
+            record(3, "tokio", Some(RetrievalTier::L3), 30),
+            record(4, "serde", None, 40),
+        ];
+        let stats = QueryAnalytics::new().with_top(2).compute(&records, &[]);
+
+        assert_eq!((stats.queries, stats.distinct_queries), (4, 3));
+        assert_eq!(stats.top_queries.len(), 2);
+        assert_eq!(stats.top_queries[0].query, "Rust async");
+        assert_eq!(stats.top_queries[0].count, 2);
+        assert_eq!(stats.top_queries[0].last_seen, at(20));
+        // 同次數時最近出現的在前
+        assert_eq!(stats.top_queries[1].query, "serde");
+
+        assert_eq!(stats.escalation.tiered(), 3);
+        assert!((stats.escalation.rate() - 2.0 / 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_latency_percentiles() {
+        let calls: Vec<EngineCall> = (1..=100)
+            .map(|ms| call("duckduckgo", ms, None, 0))
+            .chain([call("exa", 800, None, 0)])
+            .collect();
+        let stats = QueryAnalytics::new().compute(&[], &calls);
+
+        assert_eq!(stats.latency.len(), 2);
+        let ddg = &stats.latency[0];
+        assert_eq!((ddg.engine.as_str(), ddg.calls), ("duckduckgo", 100));
+        assert_eq!(ddg.p50, Duration::from_millis(50));
+        assert_eq!(ddg.p90, Duration::from_millis(90));
+        assert_eq!(ddg.p99, Duration::from_millis(99));
+        assert_eq!(ddg.max, Duration::from_millis(100));
+        assert_eq!(stats.latency[1].p50, Duration::from_millis(800));
+    }
+
+    #[test]
+    fn test_cache_effectiveness_over_time() {
+        let calls = vec![
+            call("duckduckgo", 500, Some(false), DAY + 10),
+            call("duckduckgo", 10, Some(true), DAY + 20),
+            call("duckduckgo", 10, Some(true), 2 * DAY + 5),
+            call("duckduckgo", 10, Some(true), 2 * DAY + 6),
+            // 未啟用快取的呼叫不計入
+            call("duckduckgo", 700, None, 2 * DAY + 7),
+        ];
+        let stats = QueryAnalytics::new().compute(&[], &calls);
+        let cache = &stats.cache;
+
+        assert_eq!((cache.lookups, cache.hits), (4, 3));
+        assert!((cache.hit_rate() - 0.75).abs() < 1e-6);
+        assert_eq!(cache.time_saved, Duration::from_millis(3 * 490));
+        assert_eq!(cache.buckets.len(), 2);
+        assert_eq!(cache.buckets[0].start, at(DAY));
+        assert!((cache.buckets[0].hit_rate() - 0.5).abs() < 1e-6);
+        assert_eq!((cache.buckets[1].lookups, cache.buckets[1].hits), (2, 2));
+    }
+
+    #[test]
+    fn test_collect_from_store() {
+        let store = ResultStore::in_memory().unwrap();
+        let id = store
+            .record("rust", &serde_json::json!({}), Some(RetrievalTier::L2), Some(0.8), &[])
+            .unwrap();
+        store.record_call(id, "duckduckgo", Duration::from_millis(300), Some(false)).unwrap();
+
+        let now = SystemTime::now();
+        let hour = Duration::from_secs(3600);
+        let stats = QueryAnalytics::new().collect(&store, now - hour, now + hour).unwrap();
+        assert_eq!(stats.queries, 1);
+        assert_eq!(stats.escalation.l2, 1);
+        assert_eq!(stats.latency[0].p50, Duration::from_millis(300));
+        assert_eq!(stats.cache.lookups, 1);
+    }
+}
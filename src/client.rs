@@ -1,28 +1,232 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
 use crate::types::{SearchEngine, SearchError, SearchResult};
 use crate::duckduckgo::DuckDuckGoClient;
 use crate::exa::ExaClient;
+use crate::filtering::DomainFilter;
+use crate::spam::SpamDetector;
+use crate::middleware::{Middleware, MiddlewareStack};
+use crate::optimization::idle::IdleTracker;
+use crate::optimization::key_pool::{KeyPool, KeyUsage, RotationStrategy};
+use crate::optimization::ProxyConfig;
+use crate::optimization::rate_limiter::RateLimiter;
+use crate::optimization::stats::IN_FLIGHT_REQUESTS;
+use crate::optimization::zero_copy::{CachedSearchResult, CategoryTtls, SearchCache};
+use crate::routing::{
+    corroborate, correct_query, CorroboratedResult, KeywordCondenser, PreparedQuery, QueryCondenser, SemanticRouter, SpellCorrection,
+};
+use crate::vectorstore::hybrid::{local_results, rrf_fuse, HybridResult, RRF_K};
+use crate::vectorstore::SemanticIndex;
+use bose_common::cache::{CacheKeyBuilder, KeyValueBackend};
 
 /// 統一的搜尋客戶端，支援多個搜尋引擎
 pub struct MultiSearchClient {
     duckduckgo: DuckDuckGoClient,
     exa: Option<ExaClient>,
+    /// `with_exa` 建立限流器時的初始速率
+    exa_rps: f64,
+    idle: IdleTracker,
+    cache: Option<Arc<SearchCache>>,
+    /// 與其他副本共用的快取（例如 Redis），在本地快取之後查詢
+    shared_cache: Option<Arc<dyn KeyValueBackend>>,
+    negative_ttl: Duration,
+    router: SemanticRouter,
+    condenser: Arc<dyn QueryCondenser>,
+    /// 最近一次濃縮的結果與引擎長度上限，避免 `--explain` 與搜尋各呼叫一次濃縮器
+    last_condensed: Mutex<Option<(usize, PreparedQuery)>>,
+    proxies: ProxyConfig,
+    middleware: MiddlewareStack,
+    local_index: Option<SemanticIndex>,
+    filter: Option<Arc<DomainFilter>>,
+    spam: Option<Arc<SpamDetector>>,
 }
 
 impl MultiSearchClient {
     /// 建立新的多引擎搜尋客戶端
     pub fn new() -> Self {
         Self {
-            duckduckgo: DuckDuckGoClient::new(),
+            duckduckgo: DuckDuckGoClient::new()
+                .with_rate_limiter(Arc::new(RateLimiter::per_second(DUCKDUCKGO_REQUESTS_PER_SECOND))),
             exa: None,
+            exa_rps: EXA_REQUESTS_PER_SECOND,
+            idle: IdleTracker::new(),
+            cache: None,
+            shared_cache: None,
+            negative_ttl: DEFAULT_NEGATIVE_TTL,
+            router: SemanticRouter::with_defaults(),
+            condenser: Arc::new(KeywordCondenser),
+            last_condensed: Mutex::new(None),
+            proxies: ProxyConfig::default(),
+            middleware: MiddlewareStack::default(),
+            local_index: None,
+            filter: None,
+            spam: None,
         }
     }
 
-    /// 設定 Exa API 金鑰
-    pub fn with_exa(mut self, api_key: &str) -> Self {
-        self.exa = Some(ExaClient::new(api_key));
+    /// 設定 Exa API 金鑰；逗號分隔多把金鑰時輪流使用
+    pub fn with_exa(self, api_key: &str) -> Self {
+        self.with_exa_keys(Arc::new(KeyPool::parse(api_key, RotationStrategy::RoundRobin)))
+    }
+
+    /// 以金鑰池設定 Exa（自訂輪替策略，或與其他客戶端共用用量統計）
+    pub fn with_exa_keys(mut self, keys: Arc<KeyPool>) -> Self {
+        let exa = ExaClient::with_key_pool(keys)
+            .with_rate_limiter(Arc::new(RateLimiter::per_second(self.exa_rps)))
+            .with_middleware(self.middleware.clone());
+        // 代理已在 with_proxies 驗證過；仍建立失敗時不啟用 Exa，避免繞過代理直接連線
+        self.exa = match self.proxies.for_engine("exa") {
+            Some(url) => exa
+                .with_proxy(url)
+                .map_err(|e| log::error!("❌ 無法以代理建立 Exa 客戶端，已停用 Exa: {}", e))
+                .ok(),
+            None => Some(exa),
+        };
+        self
+    }
+
+    /// 覆寫引擎的初始速率（每秒請求數，例如設定檔的 `engines.<名稱>.rate_limit_rps`），與 `with_exa` 的呼叫順序無關
+    ///
+    /// 此客戶端不使用 Tavily，指定 Tavily 時不做任何事。
+    pub fn with_rate_limit(mut self, engine: SearchEngine, rps: f64) -> Self {
+        let limiter = Arc::new(RateLimiter::per_second(rps));
+        match engine {
+            SearchEngine::DuckDuckGo => {
+                self.duckduckgo = std::mem::take(&mut self.duckduckgo).with_rate_limiter(limiter);
+            }
+            SearchEngine::Exa => {
+                self.exa_rps = rps;
+                self.exa = self.exa.take().map(|exa| exa.with_rate_limiter(limiter));
+            }
+            SearchEngine::Tavily => {}
+        }
+        self
+    }
+
+    /// 每把 Exa 金鑰的用量；未設定 Exa 時為空
+    pub fn exa_key_usage(&self) -> Vec<KeyUsage> {
+        self.exa.as_ref().map(|exa| exa.key_pool().usage()).unwrap_or_default()
+    }
+
+    /// 註冊中介層（自訂日誌、標頭、指標）；依註冊順序執行，與 `with_exa` 的呼叫順序無關
+    pub fn with_middleware(mut self, middleware: Arc<dyn Middleware>) -> Self {
+        self.middleware.push(middleware);
+        self.duckduckgo = std::mem::take(&mut self.duckduckgo).with_middleware(self.middleware.clone());
+        self.exa = self.exa.take().map(|exa| exa.with_middleware(self.middleware.clone()));
+        self
+    }
+
+    /// 經由代理送出所有引擎請求（可依引擎覆寫），與 `with_exa` 的呼叫順序無關
+    pub fn with_proxies(mut self, proxies: ProxyConfig) -> Result<Self, SearchError> {
+        proxies
+            .validate()
+            .map_err(|e| SearchError::ConfigError(e.to_string()))?;
+        if let Some(url) = proxies.for_engine("duckduckgo") {
+            self.duckduckgo = std::mem::take(&mut self.duckduckgo).with_proxy(url)?;
+        }
+        if let Some(url) = proxies.for_engine("exa")
+            && let Some(exa) = self.exa.take()
+        {
+            self.exa = Some(exa.with_proxy(url)?);
+        }
+        self.proxies = proxies;
+        Ok(self)
+    }
+
+    /// 啟用結果快取：TTL 內重複的查詢直接由快取回應
+    pub fn with_cache(mut self, cache: Arc<SearchCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// 與其他副本共用快取（例如 `bose_common::redis_cache::RedisBackend`）
+    ///
+    /// 本地快取未命中時再查共用快取；只共用非空的結果，負面快取仍留在本地。
+    pub fn with_shared_cache(mut self, shared: Arc<dyn KeyValueBackend>) -> Self {
+        self.shared_cache = Some(shared);
+        self
+    }
+
+    /// 設定負面快取（零結果與暫時性錯誤）的 TTL；`Duration::ZERO` 停用
+    pub fn with_negative_ttl(mut self, ttl: Duration) -> Self {
+        self.negative_ttl = ttl;
+        self
+    }
+
+    /// 替換過長查詢的濃縮器（預設 `KeywordCondenser`，可換成 LLM 濃縮）
+    pub fn with_condenser(mut self, condenser: Arc<dyn QueryCondenser>) -> Self {
+        self.condenser = condenser;
+        self
+    }
+
+    /// 設定本地語義索引（先前研究抓取的內容），供 `search_hybrid` 使用
+    pub fn with_local_index(mut self, index: SemanticIndex) -> Self {
+        self.local_index = Some(index);
+        self
+    }
+
+    /// 以網域封鎖 / 允許清單過濾所有引擎的結果；快取保存的是過濾前的結果
+    pub fn with_filter(mut self, filter: Arc<DomainFilter>) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// 目前使用的網域過濾器（用於讀取 `stats()`）
+    pub fn filter(&self) -> Option<&Arc<DomainFilter>> {
+        self.filter.as_ref()
+    }
+
+    /// 標記、降低排名或移除疑似內容農場的結果（在網域過濾之後）
+    pub fn with_spam_detector(mut self, spam: Arc<SpamDetector>) -> Self {
+        self.spam = Some(spam);
         self
     }
 
+    /// 目前使用的內容農場偵測器
+    pub fn spam_detector(&self) -> Option<&Arc<SpamDetector>> {
+        self.spam.as_ref()
+    }
+
+    /// 以自訂的路由器決定查詢類別（快取 TTL）與濃縮門檻，例如由設定檔的 `[router]` 載入
+    pub fn with_router(mut self, router: SemanticRouter) -> Self {
+        self.router = router;
+        self
+    }
+
+    /// 目前使用的語義路由器
+    pub fn router(&self) -> &SemanticRouter {
+        &self.router
+    }
+
+    /// 決定實際送往引擎的查詢（過長時濃縮）
+    ///
+    /// 同一查詢重複呼叫時沿用上次的濃縮結果，不再呼叫濃縮器。
+    pub async fn prepare_query(&self, query: &str, engine: SearchEngine) -> PreparedQuery {
+        let limit = engine.max_query_chars();
+        if let Some((cached_limit, prepared)) = &*self.last_condensed.lock().unwrap()
+            && *cached_limit == limit
+            && prepared.original == query
+        {
+            return prepared.clone();
+        }
+        let prepared = self.router.prepare_query(query, limit, self.condenser.as_ref()).await;
+        if prepared.is_condensed() {
+            *self.last_condensed.lock().unwrap() = Some((limit, prepared.clone()));
+        }
+        prepared
+    }
+
+    /// 目前使用的快取（用於讀取 `stats()`）
+    pub fn cache(&self) -> Option<&Arc<SearchCache>> {
+        self.cache.as_ref()
+    }
+
+    /// 取得活動追蹤器，搭配 `IdleReaper` 在閒置時釋放資源
+    pub fn idle_tracker(&self) -> IdleTracker {
+        self.idle.clone()
+    }
+
     /// 執行搜尋
     pub async fn search(
         &self,
@@ -30,6 +234,178 @@ impl MultiSearchClient {
         engine: SearchEngine,
         num_results: usize,
     ) -> Result<Vec<SearchResult>, SearchError> {
+        let mut results = self.search_unfiltered(query, engine, num_results).await?;
+        if let Some(filter) = &self.filter {
+            filter.apply(query, &mut results);
+        }
+        if let Some(spam) = &self.spam {
+            spam.apply(&mut results);
+        }
+        Ok(results)
+    }
+
+    async fn search_unfiltered(
+        &self,
+        query: &str,
+        engine: SearchEngine,
+        num_results: usize,
+    ) -> Result<Vec<SearchResult>, SearchError> {
+        self.idle.touch();
+        let key = cache_key(query, engine, num_results);
+        if let Some(cache) = &self.cache {
+            if let Some(cached) = cache.get(&key) {
+                log::debug!("⚡ 快取命中");
+                return Ok(cached.iter().map(CachedSearchResult::to_search_result).collect());
+            }
+            if let Some(error) = cache.get_failure(&key) {
+                log::debug!("⚡ 快取命中（先前的查詢失敗，暫不重試）");
+                return Err(error);
+            }
+        }
+
+        let category = self.router.categorize(query);
+        if let Some(shared) = &self.shared_cache
+            && let Some(results) = shared
+                .get_bytes(&key)
+                .await
+                .and_then(|bytes| serde_json::from_slice::<Vec<SearchResult>>(&bytes).ok())
+        {
+            log::debug!("⚡ 共用快取命中");
+            if let Some(cache) = &self.cache {
+                let cached: Vec<_> = results.iter().map(CachedSearchResult::from_search_result).collect();
+                if let Err(e) = cache.store_for(&key, &cached, category) {
+                    log::warn!("⚠️ 快取寫入失敗: {}", e);
+                }
+            }
+            return Ok(results);
+        }
+
+        let prepared = self.prepare_query(query, engine).await;
+        if prepared.is_condensed() {
+            log::info!("✂️ 查詢過長，已濃縮為: {}", prepared.sent);
+        }
+        let outcome = self.search_uncached(&prepared.sent, engine, num_results).await;
+        if let (Some(shared), Ok(results)) = (&self.shared_cache, &outcome)
+            && !results.is_empty()
+            && let Ok(json) = serde_json::to_vec(results)
+        {
+            let ttls = self.cache.as_ref().map_or(CategoryTtls::default(), |cache| cache.category_ttls());
+            shared.set_bytes(&key, json, ttls.ttl(category)).await;
+        }
+        if let Some(cache) = &self.cache {
+            let stored = match &outcome {
+                // 零結果只保留較短的時間，避免重試同一個無效查詢時消耗付費額度
+                Ok(results) if results.is_empty() && !self.negative_ttl.is_zero() => {
+                    cache.store_with_ttl(&key, &[], self.negative_ttl)
+                }
+                Ok(results) if results.is_empty() => Ok(()),
+                Ok(results) => {
+                    let cached: Vec<_> = results.iter().map(CachedSearchResult::from_search_result).collect();
+                    cache.store_for(&key, &cached, category)
+                }
+                Err(error) if !self.negative_ttl.is_zero() => {
+                    cache.store_failure(&key, error, self.negative_ttl)
+                }
+                Err(_) => Ok(()),
+            };
+            if let Err(e) = stored {
+                log::warn!("⚠️ 快取寫入失敗: {}", e);
+            }
+        }
+        outcome
+    }
+
+    /// 執行搜尋；零結果時以拼字修正後的查詢重試一次
+    ///
+    /// 回傳實際使用的修正（若有重試），方便呼叫端告知使用者。
+    pub async fn search_with_spellcheck(
+        &self,
+        query: &str,
+        engine: SearchEngine,
+        num_results: usize,
+    ) -> Result<(Vec<SearchResult>, Option<SpellCorrection>), SearchError> {
+        let results = self.search(query, engine, num_results).await?;
+        if !results.is_empty() {
+            return Ok((results, None));
+        }
+        let Some(correction) = correct_query(query) else {
+            return Ok((results, None));
+        };
+        log::info!("🔤 零結果，改用修正後的查詢重試: {}", correction.corrected);
+        let results = self.search(&correction.corrected, engine, num_results).await?;
+        Ok((results, Some(correction)))
+    }
+
+    /// 以多個引擎搜尋同一查詢，合併結果並標記各結果被哪些其他引擎佐證
+    ///
+    /// 個別引擎失敗時略過；全部失敗才回傳第一個錯誤。
+    pub async fn search_corroborated(
+        &self,
+        query: &str,
+        engines: &[SearchEngine],
+        num_results: usize,
+    ) -> Result<Vec<CorroboratedResult>, SearchError> {
+        let mut found: Vec<(&str, Vec<SearchResult>)> = Vec::new();
+        let mut first_error = None;
+        for &engine in engines {
+            match self.search(query, engine, num_results).await {
+                Ok(results) => found.push((engine.name(), results)),
+                Err(e) => {
+                    log::warn!("⚠️ {} 搜尋失敗，不列入佐證: {}", engine.name(), e);
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        if let Some(e) = first_error.filter(|_| found.is_empty()) {
+            return Err(e);
+        }
+        let sources: Vec<(&str, &[SearchResult])> =
+            found.iter().map(|(name, results)| (*name, results.as_slice())).collect();
+        Ok(corroborate(&sources))
+    }
+
+    /// 混合檢索：同時查詢本地索引與網路，以 RRF 融合並標記來源
+    ///
+    /// 任一邊失敗時只用另一邊的結果；兩邊都失敗才回傳錯誤（網路的錯誤優先）。
+    /// 未設定本地索引時等同只做網路搜尋。
+    pub async fn search_hybrid(
+        &self,
+        query: &str,
+        engine: SearchEngine,
+        num_results: usize,
+    ) -> Result<Vec<HybridResult>, SearchError> {
+        let local = async {
+            match &self.local_index {
+                Some(index) => index.query(query, num_results * 2).await.map(|hits| local_results(&hits)),
+                None => Ok(Vec::new()),
+            }
+        };
+        let (local, web) = tokio::join!(local, self.search(query, engine, num_results));
+
+        let (local, web) = match (local, web) {
+            (Ok(local), Ok(web)) => (local, web),
+            (Ok(local), Err(e)) if !local.is_empty() => {
+                log::warn!("⚠️ 網路搜尋失敗，只使用本地索引: {}", e);
+                (local, Vec::new())
+            }
+            (Err(e), Ok(web)) => {
+                log::warn!("⚠️ 本地索引查詢失敗，只使用網路結果: {}", e);
+                (Vec::new(), web)
+            }
+            (_, Err(e)) => return Err(e),
+        };
+        let mut fused = rrf_fuse(&local, &web, RRF_K);
+        fused.truncate(num_results);
+        Ok(fused)
+    }
+
+    async fn search_uncached(
+        &self,
+        query: &str,
+        engine: SearchEngine,
+        num_results: usize,
+    ) -> Result<Vec<SearchResult>, SearchError> {
+        let _in_flight = IN_FLIGHT_REQUESTS.track();
         match engine {
             SearchEngine::DuckDuckGo => {
                 println!("🦆 使用 DuckDuckGo 搜尋（完全免費）...");
@@ -51,8 +427,278 @@ impl MultiSearchClient {
     }
 }
 
+/// 各引擎的初始速率；實際速率依 429 / `Retry-After` 自動調整
+pub(crate) const DUCKDUCKGO_REQUESTS_PER_SECOND: f64 = 2.0;
+pub(crate) const EXA_REQUESTS_PER_SECOND: f64 = 5.0;
+pub(crate) const TAVILY_REQUESTS_PER_SECOND: f64 = 5.0;
+
+/// 負面快取預設 TTL（零結果與暫時性錯誤）
+pub const DEFAULT_NEGATIVE_TTL: Duration = Duration::from_secs(60);
+
+/// 快取鍵：引擎、結果數與正規化後的查詢（摺疊空白、轉小寫）
+fn cache_key(query: &str, engine: SearchEngine, num_results: usize) -> String {
+    CacheKeyBuilder::new(query)
+        .num_results(num_results as u32)
+        .engines(&[format!("{:?}", engine)])
+        .build()
+}
+
 impl Default for MultiSearchClient {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_key_normalizes_query() {
+        assert_eq!(
+            cache_key("Rust  async", SearchEngine::DuckDuckGo, 10),
+            cache_key("rust async", SearchEngine::DuckDuckGo, 10)
+        );
+        assert_ne!(
+            cache_key("rust", SearchEngine::DuckDuckGo, 10),
+            cache_key("rust", SearchEngine::Exa, 10)
+        );
+        assert_ne!(
+            cache_key("rust", SearchEngine::DuckDuckGo, 10),
+            cache_key("rust", SearchEngine::DuckDuckGo, 5)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_prepare_query_condenses_once() {
+        use std::future::Future;
+        use std::pin::Pin;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct Counting(AtomicUsize);
+
+        impl QueryCondenser for Counting {
+            fn condense<'a>(
+                &'a self,
+                _query: &'a str,
+                _max_chars: usize,
+            ) -> Pin<Box<dyn Future<Output = Option<String>> + Send + 'a>> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Box::pin(async { Some("short".to_string()) })
+            }
+
+            fn name(&self) -> &'static str {
+                "counting"
+            }
+        }
+
+        let condenser = Arc::new(Counting(AtomicUsize::new(0)));
+        let client = MultiSearchClient::new().with_condenser(condenser.clone());
+        let query = "word ".repeat(200);
+        let first = client.prepare_query(&query, SearchEngine::Tavily).await;
+        assert_eq!(client.prepare_query(&query, SearchEngine::Tavily).await, first);
+        assert_eq!(condenser.0.load(Ordering::SeqCst), 1);
+
+        // 引擎上限不同時重新濃縮
+        client.prepare_query(&query, SearchEngine::Exa).await;
+        assert_eq!(condenser.0.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_search_served_from_cache() {
+        let cache = Arc::new(SearchCache::new(10, 3600));
+        let cached = vec![CachedSearchResult::from_search_result(&SearchResult {
+            title: "Cached".to_string(),
+            url: "https://example.com".to_string(),
+            snippet: None,
+            content: None,
+            published_date: None,
+            metadata: Default::default(),
+        })];
+        cache
+            .store(&cache_key("rust", SearchEngine::Tavily, 3), &cached)
+            .unwrap();
+
+        // Tavily 未實作，只有快取命中時才會成功
+        let client = MultiSearchClient::new().with_cache(cache.clone());
+        let results = client.search("Rust", SearchEngine::Tavily, 3).await.unwrap();
+        assert_eq!(results[0].title, "Cached");
+        assert!(client.search("other", SearchEngine::Tavily, 3).await.is_err());
+        assert_eq!((cache.stats().hits, cache.stats().misses), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_search_served_from_shared_cache() {
+        use bose_common::cache::BoxFuture;
+        use std::collections::HashMap;
+
+        #[derive(Default)]
+        struct MemoryStore(Mutex<HashMap<String, Vec<u8>>>);
+
+        impl KeyValueBackend for MemoryStore {
+            fn get_bytes<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Option<Vec<u8>>> {
+                Box::pin(async move { self.0.lock().unwrap().get(key).cloned() })
+            }
+
+            fn set_bytes<'a>(&'a self, key: &'a str, value: Vec<u8>, _ttl: Duration) -> BoxFuture<'a, ()> {
+                Box::pin(async move {
+                    self.0.lock().unwrap().insert(key.to_string(), value);
+                })
+            }
+        }
+
+        // 另一個副本寫入的結果
+        let shared = Arc::new(MemoryStore::default());
+        let result = SearchResult {
+            title: "Shared".to_string(),
+            url: "https://example.com".to_string(),
+            snippet: None,
+            content: None,
+            published_date: Some("2024-03-14".to_string()),
+            metadata: Default::default(),
+        };
+        shared
+            .set_bytes(&cache_key("rust", SearchEngine::Tavily, 3), serde_json::to_vec(&[result]).unwrap(), Duration::ZERO)
+            .await;
+
+        // Tavily 未實作，只有共用快取命中時才會成功；命中後也寫入本地快取
+        let cache = Arc::new(SearchCache::new(10, 3600));
+        let client = MultiSearchClient::new().with_cache(cache.clone()).with_shared_cache(shared);
+        let results = client.search("Rust", SearchEngine::Tavily, 3).await.unwrap();
+        assert_eq!(results[0].title, "Shared");
+        assert_eq!(results[0].published_date.as_deref(), Some("2024-03-14"));
+        assert!(cache.get(&cache_key("rust", SearchEngine::Tavily, 3)).is_some());
+        assert!(client.search("other", SearchEngine::Tavily, 3).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_search_applies_filter_to_cached_results() {
+        let cache = Arc::new(SearchCache::new(10, 3600));
+        let cached: Vec<_> = ["https://www.pinterest.com/pin/1", "https://rust-lang.org"]
+            .iter()
+            .map(|url| {
+                CachedSearchResult::from_search_result(&SearchResult {
+                    title: url.to_string(),
+                    url: url.to_string(),
+                    snippet: None,
+                    content: None,
+                    published_date: None,
+                    metadata: Default::default(),
+                })
+            })
+            .collect();
+        cache.store(&cache_key("rust", SearchEngine::Tavily, 3), &cached).unwrap();
+
+        let filter = Arc::new(DomainFilter::new(
+            crate::filtering::DomainList::new(["pinterest.com"]).unwrap(),
+            Default::default(),
+        ));
+        let client = MultiSearchClient::new().with_cache(cache).with_filter(filter.clone());
+        let results = client.search("rust", SearchEngine::Tavily, 3).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].url, "https://rust-lang.org");
+        assert_eq!(filter.stats().removed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_spellcheck_retries_zero_results() {
+        let cache = Arc::new(SearchCache::new(10, 3600));
+        cache.store(&cache_key("rust asnyc", SearchEngine::Tavily, 3), &[]).unwrap();
+        let hit = CachedSearchResult::from_search_result(&SearchResult {
+            title: "Async Rust".to_string(),
+            url: "https://rust-lang.github.io/async-book/".to_string(),
+            snippet: None,
+            content: None,
+            published_date: None,
+            metadata: Default::default(),
+        });
+        cache
+            .store(&cache_key("rust async", SearchEngine::Tavily, 3), &[hit])
+            .unwrap();
+
+        let client = MultiSearchClient::new().with_cache(cache);
+        let (results, correction) = client
+            .search_with_spellcheck("rust asnyc", SearchEngine::Tavily, 3)
+            .await
+            .unwrap();
+        assert_eq!(results[0].title, "Async Rust");
+        assert_eq!(correction.unwrap().corrected, "rust async");
+
+        let (results, correction) = client
+            .search_with_spellcheck("rust async", SearchEngine::Tavily, 3)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(correction.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_failures_are_negatively_cached() {
+        let cache = Arc::new(SearchCache::new(10, 3600));
+        let client = MultiSearchClient::new().with_cache(cache.clone());
+
+        // Exa 未設定金鑰 → API 錯誤，第二次由負面快取回應
+        let first = client.search("rust", SearchEngine::Exa, 3).await.unwrap_err();
+        assert!(matches!(first, SearchError::ApiError(_)));
+        let second = client.search("rust", SearchEngine::Exa, 3).await.unwrap_err();
+        assert_eq!(second.to_string(), first.to_string());
+        // 每次查詢只計一次未命中；失敗紀錄不算命中
+        assert_eq!((cache.stats().hits, cache.stats().misses), (0, 2));
+
+        // 停用時不記錄失敗
+        let cache = Arc::new(SearchCache::new(10, 3600));
+        let client = MultiSearchClient::new()
+            .with_cache(cache.clone())
+            .with_negative_ttl(Duration::ZERO);
+        assert!(client.search("rust", SearchEngine::Exa, 3).await.is_err());
+        assert!(cache.get_failure(&cache_key("rust", SearchEngine::Exa, 3)).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_search_hybrid_fuses_local_and_web() {
+        use crate::embeddings::HashingEmbedder;
+        use crate::processing::{Chunker, ChunkerConfig};
+        use crate::vectorstore::hybrid::ResultOrigin;
+        use crate::vectorstore::{HnswConfig, HnswStore};
+
+        let index = SemanticIndex::new(
+            Arc::new(HashingEmbedder::default()),
+            Arc::new(HnswStore::new(HnswConfig::default())),
+        );
+        let chunker = Chunker::new(ChunkerConfig::default());
+        let mut chunks = chunker.chunk("https://tokio.rs", "Tokio is an async runtime for Rust.");
+        chunks.extend(chunker.chunk("https://notes.example", "Notes on the Rust async runtime."));
+        index.index(chunks).await.unwrap();
+
+        let cache = Arc::new(SearchCache::new(10, 3600));
+        let web = CachedSearchResult::from_search_result(&SearchResult {
+            title: "Tokio".to_string(),
+            url: "https://tokio.rs".to_string(),
+            snippet: None,
+            content: None,
+            published_date: None,
+            metadata: Default::default(),
+        });
+        cache
+            .store(&cache_key("rust async runtime", SearchEngine::Tavily, 3), &[web])
+            .unwrap();
+
+        let client = MultiSearchClient::new().with_cache(cache).with_local_index(index);
+        let results = client
+            .search_hybrid("rust async runtime", SearchEngine::Tavily, 3)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].result.title, "Tokio");
+        assert_eq!(results[0].origin, ResultOrigin::Both);
+        assert_eq!(results[1].origin, ResultOrigin::Local);
+
+        // 網路失敗（Tavily 未實作）時退回本地結果
+        let results = client.search_hybrid("tokio", SearchEngine::Tavily, 3).await.unwrap();
+        assert!(results.iter().all(|r| r.origin == ResultOrigin::Local));
+        assert!(MultiSearchClient::new()
+            .search_hybrid("tokio", SearchEngine::Tavily, 3)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,328 @@
+//! 成本追蹤與預算 - 記錄付費引擎的每次花費，預算用盡時拒絕付費層級
+//!
+//! 花費依（UTC 日期、引擎）累計在 SQLite，可跨行程保存；`TieredRetrieval` 在升級到
+//! Exa / Tavily 前先確認預算，呼叫後記錄實際花費。
+
+use bose_common::BudgetSettings;
+use chrono::{Datelike, Months};
+use rusqlite::{params, Connection};
+use std::fmt;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::routing::confidence::{epoch_date, epoch_days};
+use crate::storage::{storage_error, unix_secs};
+use crate::types::{SearchEngine, SearchError};
+
+/// Exa 單次搜尋成本估計（美元）
+pub const EXA_COST: f32 = 0.005;
+/// Tavily 單次提取成本估計（美元）
+pub const TAVILY_COST: f32 = 0.010;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS spend (
+    day     INTEGER NOT NULL,
+    engine  TEXT NOT NULL,
+    calls   INTEGER NOT NULL,
+    usd     REAL NOT NULL,
+    PRIMARY KEY (day, engine)
+);
+";
+
+/// 引擎單次呼叫的成本估計
+pub fn estimate(engine: SearchEngine) -> f32 {
+    match engine {
+        SearchEngine::DuckDuckGo => 0.0,
+        SearchEngine::Exa => EXA_COST,
+        SearchEngine::Tavily => TAVILY_COST,
+    }
+}
+
+/// 預算週期（UTC）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetPeriod {
+    Day,
+    Month,
+}
+
+impl fmt::Display for BudgetPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BudgetPeriod::Day => "day",
+            BudgetPeriod::Month => "month",
+        })
+    }
+}
+
+/// 硬性預算：週期內花費達到上限後拒絕付費呼叫
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Budget {
+    pub period: BudgetPeriod,
+    pub limit_usd: f32,
+    /// None 表示所有引擎合計
+    pub engine: Option<SearchEngine>,
+}
+
+impl Budget {
+    pub fn daily(limit_usd: f32) -> Self {
+        Self { period: BudgetPeriod::Day, limit_usd, engine: None }
+    }
+
+    pub fn monthly(limit_usd: f32) -> Self {
+        Self { period: BudgetPeriod::Month, limit_usd, engine: None }
+    }
+
+    /// 只計算單一引擎的花費
+    pub fn for_engine(mut self, engine: SearchEngine) -> Self {
+        self.engine = Some(engine);
+        self
+    }
+
+    fn applies_to(&self, engine: SearchEngine) -> bool {
+        self.engine.is_none_or(|e| e == engine)
+    }
+}
+
+/// 單一引擎在某週期的花費
+#[derive(Debug, Clone, PartialEq)]
+pub struct EngineSpend {
+    pub engine: String,
+    pub calls: u64,
+    pub usd: f32,
+}
+
+/// 花費帳本
+pub struct CostTracker {
+    conn: Mutex<Connection>,
+    budgets: Vec<Budget>,
+}
+
+impl CostTracker {
+    /// 開啟（或建立）帳本檔案
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SearchError> {
+        Self::init(Connection::open(path).map_err(storage_error)?)
+    }
+
+    /// 記憶體帳本（行程結束即消失）
+    pub fn in_memory() -> Result<Self, SearchError> {
+        Self::init(Connection::open_in_memory().map_err(storage_error)?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, SearchError> {
+        conn.execute_batch(SCHEMA).map_err(storage_error)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            budgets: Vec::new(),
+        })
+    }
+
+    /// 依 `BoseConfig.budget` 建立：有帳本路徑時跨執行累計，否則只在記憶體中累計
+    pub fn from_settings(settings: &BudgetSettings) -> Result<Self, SearchError> {
+        let mut tracker = match &settings.ledger_path {
+            Some(path) => Self::open(path)?,
+            None => Self::in_memory()?,
+        };
+        if let Some(usd) = settings.daily_usd {
+            tracker = tracker.with_budget(Budget::daily(usd as f32));
+        }
+        if let Some(usd) = settings.monthly_usd {
+            tracker = tracker.with_budget(Budget::monthly(usd as f32));
+        }
+        Ok(tracker)
+    }
+
+    /// 加入硬性預算（可多個，全部都必須滿足）
+    pub fn with_budget(mut self, budget: Budget) -> Self {
+        self.budgets.push(budget);
+        self
+    }
+
+    pub fn budgets(&self) -> &[Budget] {
+        &self.budgets
+    }
+
+    /// 記錄一次呼叫的花費
+    pub fn record(&self, engine: SearchEngine, usd: f32) -> Result<(), SearchError> {
+        self.record_at(engine, usd, SystemTime::now())
+    }
+
+    pub fn record_at(&self, engine: SearchEngine, usd: f32, at: SystemTime) -> Result<(), SearchError> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO spend (day, engine, calls, usd) VALUES (?1, ?2, 1, ?3)
+             ON CONFLICT(day, engine) DO UPDATE SET calls = calls + 1, usd = usd + excluded.usd",
+            params![day_of(at), engine.name(), usd as f64],
+        )
+        .map_err(storage_error)?;
+        Ok(())
+    }
+
+    /// 週期內的花費；`engine` 為 None 時為所有引擎合計
+    pub fn spend(&self, period: BudgetPeriod, engine: Option<SearchEngine>) -> Result<f32, SearchError> {
+        self.spend_at(period, engine, SystemTime::now())
+    }
+
+    fn spend_at(
+        &self,
+        period: BudgetPeriod,
+        engine: Option<SearchEngine>,
+        at: SystemTime,
+    ) -> Result<f32, SearchError> {
+        Ok(self
+            .breakdown_at(period, at)?
+            .into_iter()
+            .filter(|s| engine.is_none_or(|e| s.engine == e.name()))
+            .map(|s| s.usd)
+            .sum())
+    }
+
+    /// 週期內各引擎的呼叫次數與花費
+    pub fn breakdown(&self, period: BudgetPeriod) -> Result<Vec<EngineSpend>, SearchError> {
+        self.breakdown_at(period, SystemTime::now())
+    }
+
+    fn breakdown_at(&self, period: BudgetPeriod, at: SystemTime) -> Result<Vec<EngineSpend>, SearchError> {
+        let (start, end) = period_days(period, day_of(at));
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT engine, SUM(calls), SUM(usd) FROM spend
+                 WHERE day >= ?1 AND day < ?2 GROUP BY engine ORDER BY engine",
+            )
+            .map_err(storage_error)?;
+        let rows = stmt
+            .query_map(params![start, end], |row| {
+                Ok(EngineSpend {
+                    engine: row.get(0)?,
+                    calls: row.get::<_, i64>(1)? as u64,
+                    usd: row.get::<_, f64>(2)? as f32,
+                })
+            })
+            .map_err(storage_error)?;
+        rows.collect::<Result<_, _>>().map_err(storage_error)
+    }
+
+    /// 這次呼叫是否仍在所有預算之內；回傳第一個會被超出的預算
+    pub fn check(&self, engine: SearchEngine, usd: f32) -> Result<Option<Budget>, SearchError> {
+        self.check_at(engine, usd, SystemTime::now())
+    }
+
+    fn check_at(&self, engine: SearchEngine, usd: f32, at: SystemTime) -> Result<Option<Budget>, SearchError> {
+        if usd <= 0.0 {
+            return Ok(None);
+        }
+        for budget in self.budgets.iter().filter(|b| b.applies_to(engine)) {
+            let spent = self.spend_at(budget.period, budget.engine, at)?;
+            if spent + usd > budget.limit_usd + f32::EPSILON {
+                return Ok(Some(*budget));
+            }
+        }
+        Ok(None)
+    }
+
+    /// 預算檢查的簡易版本；帳本讀取失敗時視為超出預算（寧可不花錢）
+    pub fn allows(&self, engine: SearchEngine, usd: f32) -> bool {
+        match self.check(engine, usd) {
+            Ok(None) => true,
+            Ok(Some(budget)) => {
+                log::warn!(
+                    "💸 {} 預算 ${:.3} 已用盡，不使用 {}",
+                    budget.period,
+                    budget.limit_usd,
+                    engine.name()
+                );
+                false
+            }
+            Err(e) => {
+                log::error!("❌ 無法讀取花費帳本，拒絕付費呼叫: {}", e);
+                false
+            }
+        }
+    }
+}
+
+fn day_of(at: SystemTime) -> i64 {
+    unix_secs(at).div_euclid(86_400)
+}
+
+/// 週期的 [起始日, 結束日)
+fn period_days(period: BudgetPeriod, day: i64) -> (i64, i64) {
+    match period {
+        BudgetPeriod::Day => (day, day + 1),
+        BudgetPeriod::Month => {
+            let start = epoch_date(day).with_day(1).expect("每個月都有 1 日");
+            let end = start + Months::new(1);
+            (epoch_days(start), epoch_days(end))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn day(year: i32, month: u32, day: u32) -> i64 {
+        epoch_days(NaiveDate::from_ymd_opt(year, month, day).unwrap())
+    }
+
+    fn at(year: i32, month: u32, d: u32) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(day(year, month, d) as u64 * 86_400 + 3_600)
+    }
+
+    #[test]
+    fn test_calendar() {
+        assert_eq!(
+            period_days(BudgetPeriod::Month, day(2024, 2, 10)),
+            (day(2024, 2, 1), day(2024, 3, 1))
+        );
+        assert_eq!(period_days(BudgetPeriod::Month, day(2025, 12, 31)).1, day(2026, 1, 1));
+        assert_eq!(period_days(BudgetPeriod::Day, day(2024, 2, 29)), (day(2024, 2, 29), day(2024, 3, 1)));
+    }
+
+    #[test]
+    fn test_spend_by_period_and_engine() {
+        let tracker = CostTracker::in_memory().unwrap();
+        tracker.record_at(SearchEngine::Exa, EXA_COST, at(2026, 3, 1)).unwrap();
+        tracker.record_at(SearchEngine::Exa, EXA_COST, at(2026, 3, 2)).unwrap();
+        tracker.record_at(SearchEngine::Tavily, TAVILY_COST, at(2026, 3, 2)).unwrap();
+        tracker.record_at(SearchEngine::Exa, EXA_COST, at(2026, 4, 1)).unwrap();
+
+        let day = |engine| tracker.spend_at(BudgetPeriod::Day, engine, at(2026, 3, 2)).unwrap();
+        assert!((day(None) - 0.015).abs() < 1e-6);
+        assert!((day(Some(SearchEngine::Exa)) - 0.005).abs() < 1e-6);
+
+        let month = tracker.breakdown_at(BudgetPeriod::Month, at(2026, 3, 20)).unwrap();
+        assert_eq!(month.len(), 2);
+        assert_eq!((month[0].engine.as_str(), month[0].calls), ("exa", 2));
+        assert_eq!((month[1].engine.as_str(), month[1].calls), ("tavily", 1));
+    }
+
+    #[test]
+    fn test_budgets() {
+        let tracker = CostTracker::in_memory()
+            .unwrap()
+            .with_budget(Budget::daily(0.01))
+            .with_budget(Budget::monthly(0.005).for_engine(SearchEngine::Tavily));
+        let today = at(2026, 5, 5);
+
+        assert_eq!(tracker.check_at(SearchEngine::Exa, EXA_COST, today).unwrap(), None);
+        // 單一引擎預算只限制該引擎
+        assert_eq!(
+            tracker.check_at(SearchEngine::Tavily, TAVILY_COST, today).unwrap(),
+            Some(Budget::monthly(0.005).for_engine(SearchEngine::Tavily))
+        );
+
+        tracker.record_at(SearchEngine::Exa, EXA_COST, today).unwrap();
+        tracker.record_at(SearchEngine::Exa, EXA_COST, today).unwrap();
+        assert_eq!(
+            tracker.check_at(SearchEngine::Exa, EXA_COST, today).unwrap(),
+            Some(Budget::daily(0.01))
+        );
+        // 隔天重新計算日預算；免費引擎不受影響
+        assert_eq!(tracker.check_at(SearchEngine::Exa, EXA_COST, at(2026, 5, 6)).unwrap(), None);
+        assert_eq!(tracker.check_at(SearchEngine::DuckDuckGo, 0.0, today).unwrap(), None);
+    }
+}
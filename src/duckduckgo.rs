@@ -1,19 +1,54 @@
-use crate::types::{SearchError, SearchResult};
+use crate::middleware::MiddlewareStack;
+use crate::optimization::connection_pool::proxied_client;
+use crate::optimization::{with_backoff, RateLimiter, RetryPolicy};
+use crate::types::{SearchEngine, SearchError, SearchResult};
+use crate::url::canonicalize;
 use reqwest::Client;
 use serde_json::Value;
+use std::sync::Arc;
 
 /// DuckDuckGo 搜尋客戶端（完全免費，無需 API 金鑰）
 pub struct DuckDuckGoClient {
     client: Client,
+    retry: RetryPolicy,
+    middleware: MiddlewareStack,
+    limiter: Option<Arc<RateLimiter>>,
 }
 
 impl DuckDuckGoClient {
     pub fn new() -> Self {
         Self {
             client: Client::new(),
+            retry: RetryPolicy::default(),
+            middleware: MiddlewareStack::default(),
+            limiter: None,
         }
     }
 
+    /// 經由代理（HTTP / SOCKS5）送出請求
+    pub fn with_proxy(mut self, url: &str) -> Result<Self, SearchError> {
+        self.client = proxied_client(url)?;
+        Ok(self)
+    }
+
+    /// 每個 HTTP 請求都經過這些中介層
+    pub fn with_middleware(mut self, middleware: MiddlewareStack) -> Self {
+        self.middleware = middleware;
+        self
+    }
+
+    /// 請求前先取得 token，並將 429 / `Retry-After` 回報給限流器自動降速
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.limiter = Some(limiter);
+        self
+    }
+
+    /// 覆寫逾時、連線失敗與 5xx 的重試策略
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
     /// 執行搜尋
     pub async fn search(&self, query: &str, num_results: usize) -> Result<Vec<SearchResult>, SearchError> {
         // DuckDuckGo Instant Answer API
@@ -22,11 +57,23 @@ impl DuckDuckGoClient {
             urlencoding::encode(query)
         );
 
-        let response = self.client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| SearchError::NetworkError(e.to_string()))?;
+        let response = with_backoff(&self.retry, || async {
+            if let Some(limiter) = &self.limiter {
+                limiter.acquire().await;
+            }
+            let response = self
+                .middleware
+                .send(SearchEngine::DuckDuckGo, &self.client, self.client.get(&url))
+                .await?;
+            if let Some(limiter) = &self.limiter {
+                limiter.observe(response.status(), response.headers());
+            }
+            if response.status().is_server_error() {
+                return Err(SearchError::ApiError(format!("DuckDuckGo API 錯誤 {}", response.status())));
+            }
+            Ok(response)
+        })
+        .await?;
 
         let json: Value = response
             .json()
@@ -40,9 +87,11 @@ impl DuckDuckGoClient {
             if !abstract_text.is_empty() {
                 results.push(SearchResult {
                     title: json["Heading"].as_str().unwrap_or("DuckDuckGo Result").to_string(),
-                    url: json["AbstractURL"].as_str().unwrap_or("").to_string(),
+                    url: canonicalize(json["AbstractURL"].as_str().unwrap_or("")),
                     snippet: Some(abstract_text.to_string()),
                     content: None,
+                    published_date: None,
+                    metadata: Default::default(),
                 });
             }
         }
@@ -53,9 +102,11 @@ impl DuckDuckGoClient {
                 if let Some(text) = topic["Text"].as_str() {
                     results.push(SearchResult {
                         title: text.split(" - ").next().unwrap_or(text).to_string(),
-                        url: topic["FirstURL"].as_str().unwrap_or("").to_string(),
+                        url: canonicalize(topic["FirstURL"].as_str().unwrap_or("")),
                         snippet: Some(text.to_string()),
                         content: None,
+                        published_date: None,
+                        metadata: Default::default(),
                     });
                 }
             }
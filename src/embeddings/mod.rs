@@ -0,0 +1,305 @@
+//! 向量嵌入 - 可替換的 `Embedder` 後端，供重排序、語義路由與去重共用
+//!
+//! - `HashingEmbedder`：特徵雜湊，不需模型也不需網路，適合測試與離線環境
+//! - `OpenAiEmbedder`：OpenAI 相容的 `/embeddings` API（OpenAI、Ollama、vLLM、TEI ...）
+//! - `OnnxEmbedder`：本機 ONNX 句向量模型（需 `onnx` feature）
+
+#[cfg(feature = "onnx")]
+mod onnx;
+
+#[cfg(feature = "onnx")]
+pub use onnx::OnnxEmbedder;
+
+use crate::types::SearchError;
+use bose_common::hash::fnv1a_64;
+use bose_common::text::is_cjk;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::future::Future;
+use std::pin::Pin;
+
+/// `Embedder::embed` 回傳的 future
+pub type EmbedFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<Vec<f32>>, SearchError>> + Send + 'a>>;
+
+/// 向量嵌入後端
+pub trait Embedder: Send + Sync {
+    /// 向量維度
+    fn dimensions(&self) -> usize;
+
+    /// 批次嵌入，輸出順序與輸入相同
+    fn embed<'a>(&'a self, texts: &'a [String]) -> EmbedFuture<'a>;
+}
+
+/// 嵌入單一文字
+pub async fn embed_one(embedder: &dyn Embedder, text: &str) -> Result<Vec<f32>, SearchError> {
+    let texts = [text.to_string()];
+    embedder
+        .embed(&texts)
+        .await?
+        .pop()
+        .ok_or_else(|| SearchError::ParseError("嵌入結果為空".into()))
+}
+
+/// 餘弦相似度；長度不同或零向量回傳 0
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = (a.iter().map(|x| x * x).sum::<f32>() * b.iter().map(|x| x * x).sum::<f32>()).sqrt();
+    if norm == 0.0 {
+        0.0
+    } else {
+        dot / norm
+    }
+}
+
+/// 將向量縮放為單位長度
+pub(crate) fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|x| *x /= norm);
+    }
+}
+
+/// 特徵雜湊嵌入：詞與字元三元組雜湊到固定維度，中日韓文字取相鄰兩字
+///
+/// 只能抓到字面重疊，無法理解同義詞；用於沒有模型時的退路。
+#[derive(Debug, Clone)]
+pub struct HashingEmbedder {
+    dimensions: usize,
+}
+
+impl Default for HashingEmbedder {
+    fn default() -> Self {
+        Self::new(256)
+    }
+}
+
+impl HashingEmbedder {
+    pub fn new(dimensions: usize) -> Self {
+        Self {
+            dimensions: dimensions.max(1),
+        }
+    }
+
+    /// 同步計算單一文字的向量
+    pub fn embed_text(&self, text: &str) -> Vec<f32> {
+        let mut vector = vec![0.0; self.dimensions];
+        let lower = text.to_lowercase();
+        for word in lower.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()) {
+            let chars: Vec<char> = word.chars().collect();
+            if chars.iter().any(|c| is_cjk(*c)) {
+                for pair in chars.windows(2) {
+                    self.add(&mut vector, &pair.iter().collect::<String>(), 1.0);
+                }
+                if chars.len() == 1 {
+                    self.add(&mut vector, word, 1.0);
+                }
+                continue;
+            }
+            self.add(&mut vector, word, 1.0);
+            // 字元三元組讓詞形變化（embed / embedding）也有相似度
+            let padded: Vec<char> = format!("#{}#", word).chars().collect();
+            for gram in padded.windows(3) {
+                self.add(&mut vector, &gram.iter().collect::<String>(), 0.5);
+            }
+        }
+        normalize(&mut vector);
+        vector
+    }
+
+    fn add(&self, vector: &mut [f32], feature: &str, weight: f32) {
+        let hash = fnv1a_64(feature.as_bytes());
+        let index = (hash % self.dimensions as u64) as usize;
+        // 最高位元決定正負，降低碰撞造成的偏差
+        let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+        vector[index] += sign * weight;
+    }
+}
+
+impl Embedder for HashingEmbedder {
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    fn embed<'a>(&'a self, texts: &'a [String]) -> EmbedFuture<'a> {
+        Box::pin(async move { Ok(texts.iter().map(|t| self.embed_text(t)).collect()) })
+    }
+}
+
+/// OpenAI 相容的 `/embeddings` API
+pub struct OpenAiEmbedder {
+    client: Client,
+    base_url: String,
+    model: String,
+    api_key: Option<String>,
+    dimensions: usize,
+    batch_size: usize,
+}
+
+impl OpenAiEmbedder {
+    /// `base_url` 例如 `https://api.openai.com/v1` 或 `http://localhost:11434/v1`
+    pub fn new(base_url: &str, model: &str, dimensions: usize) -> Self {
+        Self {
+            client: Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            model: model.to_string(),
+            api_key: None,
+            dimensions,
+            batch_size: 64,
+        }
+    }
+
+    pub fn with_api_key(mut self, api_key: &str) -> Self {
+        self.api_key = Some(api_key.to_string());
+        self
+    }
+
+    /// 每個請求最多送出幾段文字
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, SearchError> {
+        let mut request = self
+            .client
+            .post(format!("{}/embeddings", self.base_url))
+            .json(&json!({ "model": self.model, "input": texts }));
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| SearchError::NetworkError(e.to_string()))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(SearchError::ApiError(format!("Embedding API {}: {}", status, body)));
+        }
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| SearchError::ParseError(e.to_string()))?;
+
+        let mut items: Vec<(u64, Vec<f32>)> = body["data"]
+            .as_array()
+            .ok_or_else(|| SearchError::ParseError("Embedding 回應缺少 data".into()))?
+            .iter()
+            .map(|item| {
+                let vector: Vec<f32> = item["embedding"]
+                    .as_array()
+                    .map(|v| v.iter().filter_map(Value::as_f64).map(|x| x as f32).collect())
+                    .unwrap_or_default();
+                (item["index"].as_u64().unwrap_or(0), vector)
+            })
+            .collect();
+        items.sort_by_key(|(index, _)| *index);
+
+        if items.len() != texts.len() {
+            return Err(SearchError::ParseError(format!(
+                "Embedding 數量不符：送出 {}，收到 {}",
+                texts.len(),
+                items.len()
+            )));
+        }
+        if let Some((_, v)) = items.iter().find(|(_, v)| v.len() != self.dimensions) {
+            return Err(SearchError::ParseError(format!(
+                "Embedding 維度不符：預期 {}，收到 {}",
+                self.dimensions,
+                v.len()
+            )));
+        }
+        Ok(items.into_iter().map(|(_, v)| v).collect())
+    }
+}
+
+impl Embedder for OpenAiEmbedder {
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    fn embed<'a>(&'a self, texts: &'a [String]) -> EmbedFuture<'a> {
+        Box::pin(async move {
+            let mut vectors = Vec::with_capacity(texts.len());
+            for batch in texts.chunks(self.batch_size) {
+                vectors.extend(self.embed_batch(batch).await?);
+            }
+            Ok(vectors)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_hashing_embedder_similarity() {
+        let embedder = HashingEmbedder::default();
+        let texts: Vec<String> = [
+            "Rust async runtime tutorial",
+            "tutorial for the Rust async runtime",
+            "chocolate cake recipe",
+            "非同步執行環境",
+            "非同步執行",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let v = embedder.embed(&texts).await.unwrap();
+
+        assert_eq!(v[0].len(), 256);
+        assert!(cosine_similarity(&v[0], &v[1]) > 0.8);
+        assert!(cosine_similarity(&v[0], &v[2]) < 0.2);
+        assert!(cosine_similarity(&v[3], &v[4]) > 0.7);
+        assert_eq!(v[0], embedder.embed_text("Rust async runtime tutorial"));
+    }
+
+    #[test]
+    fn test_cosine_edge_cases() {
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[1.0]), 0.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+        assert!((cosine_similarity(&[1.0, 1.0], &[2.0, 2.0]) - 1.0).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_openai_embedder_orders_by_index() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/embeddings"))
+            .and(header("authorization", "Bearer sk-test"))
+            .and(body_partial_json(serde_json::json!({ "input": ["first", "second"] })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [{ "index": 1, "embedding": [0.0, 1.0] }, { "index": 0, "embedding": [1.0, 0.0] }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let embedder = OpenAiEmbedder::new(&format!("{}/v1/", server.uri()), "text-embedding-3-small", 2)
+            .with_api_key("sk-test");
+        let texts = vec!["first".to_string(), "second".to_string()];
+        let vectors = embedder.embed(&texts).await.unwrap();
+        assert_eq!(vectors, vec![vec![1.0, 0.0], vec![0.0, 1.0]]);
+    }
+
+    #[tokio::test]
+    async fn test_openai_embedder_dimension_mismatch() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "data": [{ "index": 0, "embedding": [1.0] }] })),
+            )
+            .mount(&server)
+            .await;
+        let embedder = OpenAiEmbedder::new(&server.uri(), "m", 3);
+        let result = embed_one(&embedder, "text").await;
+        assert!(matches!(result, Err(SearchError::ParseError(_))));
+    }
+}
@@ -0,0 +1,125 @@
+//! 本機 ONNX 句向量模型（例如 all-MiniLM-L6-v2、bge-small）
+//!
+//! 模型目錄需包含 `model.onnx` 與 HuggingFace 格式的 `tokenizer.json`；
+//! 輸出的 `last_hidden_state` 依 attention mask 做平均池化後正規化。
+
+use super::{normalize, EmbedFuture, Embedder};
+use crate::types::SearchError;
+use std::path::Path;
+use std::sync::Arc;
+use tract_onnx::prelude::*;
+
+type Plan = SimplePlan<TypedFact, Box<dyn TypedOp>, Graph<TypedFact, Box<dyn TypedOp>>>;
+
+/// 本機 ONNX 嵌入
+pub struct OnnxEmbedder {
+    model: Arc<Plan>,
+    tokenizer: Arc<tokenizers::Tokenizer>,
+    /// 模型是否有 `token_type_ids` 輸入（BERT 系列有，部分匯出版本沒有）
+    token_types: bool,
+    max_tokens: usize,
+    dimensions: usize,
+}
+
+impl OnnxEmbedder {
+    /// 從模型目錄載入；輸入固定為 `max_tokens` 長度，較短的以 padding 補齊
+    pub fn load(dir: &Path, dimensions: usize, max_tokens: usize) -> Result<Self, SearchError> {
+        let error = |e: &dyn std::fmt::Display| SearchError::ConfigError(format!("載入 ONNX 模型失敗: {}", e));
+
+        let tokenizer = tokenizers::Tokenizer::from_file(dir.join("tokenizer.json")).map_err(|e| error(&e))?;
+        let mut model = tract_onnx::onnx()
+            .model_for_path(dir.join("model.onnx"))
+            .map_err(|e| error(&e))?;
+        let token_types = model.inputs.len() >= 3;
+        for input in 0..model.inputs.len().min(3) {
+            model = model
+                .with_input_fact(input, i64::fact([1, max_tokens]).into())
+                .map_err(|e| error(&e))?;
+        }
+        let model = model
+            .into_optimized()
+            .and_then(|m| m.into_runnable())
+            .map_err(|e| error(&e))?;
+
+        Ok(Self {
+            model: Arc::new(model),
+            tokenizer: Arc::new(tokenizer),
+            token_types,
+            max_tokens,
+            dimensions,
+        })
+    }
+
+    fn embed_text(&self, text: &str) -> Result<Vec<f32>, SearchError> {
+        let encoding = self
+            .tokenizer
+            .encode(text, true)
+            .map_err(|e| SearchError::ParseError(format!("tokenize 失敗: {}", e)))?;
+        let len = encoding.get_ids().len().min(self.max_tokens);
+        let pad = |values: &[u32]| {
+            let mut padded: Vec<i64> = values[..len].iter().map(|&v| v as i64).collect();
+            padded.resize(self.max_tokens, 0);
+            tract_ndarray::Array2::from_shape_vec((1, self.max_tokens), padded)
+        };
+        let error = |e: &dyn std::fmt::Display| SearchError::ParseError(format!("ONNX 推論失敗: {}", e));
+
+        let ids = pad(encoding.get_ids()).map_err(|e| error(&e))?;
+        let mask = pad(encoding.get_attention_mask()).map_err(|e| error(&e))?;
+        let mut inputs: TVec<TValue> = tvec!(Tensor::from(ids).into(), Tensor::from(mask.clone()).into());
+        if self.token_types {
+            let types = pad(encoding.get_type_ids()).map_err(|e| error(&e))?;
+            inputs.push(Tensor::from(types).into());
+        }
+
+        let outputs = self.model.run(inputs).map_err(|e| error(&e))?;
+        let hidden = outputs[0].to_array_view::<f32>().map_err(|e| error(&e))?;
+        let shape = hidden.shape();
+        if shape.len() != 3 || shape[2] != self.dimensions {
+            return Err(SearchError::ParseError(format!(
+                "ONNX 輸出維度不符：預期 {}，收到 {:?}",
+                self.dimensions, shape
+            )));
+        }
+
+        // 只平均實際 token（mask = 1）的向量
+        let mut pooled = vec![0.0f32; self.dimensions];
+        let mut count = 0.0f32;
+        for token in 0..len {
+            if mask[[0, token]] == 0 {
+                continue;
+            }
+            count += 1.0;
+            for (dim, value) in pooled.iter_mut().enumerate() {
+                *value += hidden[[0, token, dim]];
+            }
+        }
+        if count > 0.0 {
+            pooled.iter_mut().for_each(|v| *v /= count);
+        }
+        normalize(&mut pooled);
+        Ok(pooled)
+    }
+}
+
+impl Embedder for OnnxEmbedder {
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    fn embed<'a>(&'a self, texts: &'a [String]) -> EmbedFuture<'a> {
+        Box::pin(async move {
+            let embedder = Self {
+                model: self.model.clone(),
+                tokenizer: self.tokenizer.clone(),
+                token_types: self.token_types,
+                max_tokens: self.max_tokens,
+                dimensions: self.dimensions,
+            };
+            let texts = texts.to_vec();
+            // 推論吃 CPU，移到阻塞執行緒
+            tokio::task::spawn_blocking(move || texts.iter().map(|t| embedder.embed_text(t)).collect())
+                .await
+                .map_err(|e| SearchError::ParseError(e.to_string()))?
+        })
+    }
+}
@@ -1,68 +1,450 @@
-use crate::types::{SearchError, SearchResult};
+use crate::fetcher::metadata::{AUTHOR, FAVICON, OG_IMAGE};
+use crate::middleware::MiddlewareStack;
+use crate::optimization::connection_pool::proxied_client;
+use crate::optimization::{with_backoff, KeyPool, RateLimiter, RetryPolicy, RotationStrategy};
+use crate::processing::fill_missing_snippets;
+use crate::routing::confidence::{epoch_date, parse_date, today};
+use crate::types::{lenient_vec, log_unknown_fields, SearchEngine, SearchError, SearchQuery, SearchResult};
+use crate::url::{canonicalize, dedup_key};
 use reqwest::Client;
-use serde_json::{json, Value};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Exa 支援的內容類別
+const EXA_CATEGORIES: &[&str] = &[
+    "company",
+    "research paper",
+    "news",
+    "pdf",
+    "github",
+    "tweet",
+    "personal site",
+    "linkedin profile",
+    "financial report",
+];
+
+/// 每個結果取回的全文字元數
+const TEXT_MAX_CHARACTERS: usize = 1000;
+
+/// Exa 搜尋類型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExaSearchType {
+    /// 由 Exa 決定
+    #[default]
+    Auto,
+    /// 語義（embedding）搜尋
+    Neural,
+    /// 傳統關鍵字搜尋
+    Keyword,
+}
+
+impl ExaSearchType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ExaSearchType::Auto => "auto",
+            ExaSearchType::Neural => "neural",
+            ExaSearchType::Keyword => "keyword",
+        }
+    }
+}
+
+/// `contents` 請求選項
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExaContents {
+    pub text: ExaTextOptions,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExaTextOptions {
+    pub max_characters: usize,
+}
+
+impl Default for ExaContents {
+    fn default() -> Self {
+        Self {
+            text: ExaTextOptions {
+                max_characters: TEXT_MAX_CHARACTERS,
+            },
+        }
+    }
+}
+
+/// `/search` 的請求
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExaSearchRequest {
+    pub query: String,
+    #[serde(rename = "type")]
+    pub search_type: ExaSearchType,
+    pub num_results: usize,
+    pub contents: ExaContents,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub include_domains: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude_domains: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_published_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_published_date: Option<String>,
+}
+
+impl ExaSearchRequest {
+    /// 由共用的查詢條件建立；`today` 為距 1970-01-01 的天數，用來換算 `time_range`
+    pub fn from_query(query: &SearchQuery, search_type: ExaSearchType, today: i64) -> Self {
+        let category = query.category.as_deref().and_then(|category| {
+            let mapped = exa_category(category);
+            if mapped.is_none() {
+                log::debug!("Exa 不支援類別 {}，忽略", category);
+            }
+            mapped
+        });
+        let start_published_date = match (query.start_date.as_deref(), query.time_range) {
+            (Some(date), _) => parse_date(date).map(|(y, m, d)| format!("{:04}-{:02}-{:02}T00:00:00.000Z", y, m, d)),
+            (None, Some(range)) => {
+                let date = epoch_date(today - range.days().ceil() as i64);
+                Some(format!("{}T00:00:00.000Z", date.format("%Y-%m-%d")))
+            }
+            (None, None) => None,
+        };
+        let end_published_date = query
+            .end_date
+            .as_deref()
+            .and_then(parse_date)
+            .map(|(y, m, d)| format!("{:04}-{:02}-{:02}T23:59:59.999Z", y, m, d));
+
+        Self {
+            query: query.query.clone(),
+            search_type,
+            num_results: query.num_results,
+            contents: ExaContents::default(),
+            include_domains: query.include_domains.clone(),
+            exclude_domains: query.exclude_domains.clone(),
+            category,
+            start_published_date,
+            end_published_date,
+        }
+    }
+}
+
+/// `/findSimilar` 的請求
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExaFindSimilarRequest {
+    pub url: String,
+    pub num_results: usize,
+    /// 排除與種子網址同網域的頁面
+    pub exclude_source_domain: bool,
+    pub contents: ExaContents,
+}
+
+/// `/search` 與 `/findSimilar` 的回應；未知的欄位保留在 `extra`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExaResponse {
+    /// 無法解析的單筆結果會被略過
+    #[serde(deserialize_with = "lenient_vec")]
+    pub results: Vec<ExaResult>,
+    pub request_id: Option<String>,
+    /// `auto` 時 Exa 實際採用的搜尋類型
+    pub resolved_search_type: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 單筆結果
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExaResult {
+    pub url: String,
+    pub id: Option<String>,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    /// ISO 8601
+    pub published_date: Option<String>,
+    pub score: Option<f64>,
+    /// 全文（`contents.text`）
+    pub text: Option<String>,
+    pub summary: Option<String>,
+    pub highlights: Option<Vec<String>>,
+    pub image: Option<String>,
+    pub favicon: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl From<ExaResult> for SearchResult {
+    fn from(result: ExaResult) -> Self {
+        let non_empty = |value: Option<String>| value.filter(|v| !v.trim().is_empty());
+        let mut metadata = BTreeMap::new();
+        for (key, value) in [(AUTHOR, result.author), (FAVICON, result.favicon), (OG_IMAGE, result.image)] {
+            if let Some(value) = non_empty(value) {
+                metadata.insert(key.to_string(), value);
+            }
+        }
+        let highlight = result.highlights.and_then(|h| h.into_iter().find(|h| !h.trim().is_empty()));
+        SearchResult {
+            title: non_empty(result.title).unwrap_or_else(|| "無標題".to_string()),
+            url: canonicalize(&result.url),
+            snippet: highlight.or(non_empty(result.summary)),
+            content: result.text,
+            published_date: non_empty(result.published_date),
+            metadata,
+        }
+    }
+}
 
 /// Exa 搜尋客戶端（$10 免費額度，AI 語義搜尋）
 pub struct ExaClient {
     client: Client,
-    api_key: String,
+    keys: Arc<KeyPool>,
+    limiter: Option<Arc<RateLimiter>>,
+    retry: RetryPolicy,
+    middleware: MiddlewareStack,
+    search_type: ExaSearchType,
 }
 
 impl ExaClient {
+    /// `api_key` 可為逗號分隔的多把金鑰，預設輪流使用
     pub fn new(api_key: &str) -> Self {
+        Self::with_key_pool(Arc::new(KeyPool::parse(api_key, RotationStrategy::RoundRobin)))
+    }
+
+    /// 使用金鑰池；429 / 402 時自動換下一把金鑰
+    pub fn with_key_pool(keys: Arc<KeyPool>) -> Self {
         Self {
             client: Client::new(),
-            api_key: api_key.to_string(),
+            keys,
+            limiter: None,
+            retry: RetryPolicy::default(),
+            middleware: MiddlewareStack::default(),
+            search_type: ExaSearchType::default(),
         }
     }
 
+    /// 請求前先取得 token，並將 429 / `Retry-After` 回報給限流器自動降速
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.limiter = Some(limiter);
+        self
+    }
+
+    /// 經由代理（HTTP / SOCKS5）送出請求
+    pub fn with_proxy(mut self, url: &str) -> Result<Self, SearchError> {
+        self.client = proxied_client(url)?;
+        Ok(self)
+    }
+
+    /// 每個 HTTP 請求都經過這些中介層
+    pub fn with_middleware(mut self, middleware: MiddlewareStack) -> Self {
+        self.middleware = middleware;
+        self
+    }
+
+    /// 覆寫逾時、連線失敗與 5xx 的重試策略
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// 搜尋類型（預設 auto）
+    pub fn with_search_type(mut self, search_type: ExaSearchType) -> Self {
+        self.search_type = search_type;
+        self
+    }
+
+    /// 金鑰池（查詢每把金鑰的用量）
+    pub fn key_pool(&self) -> &Arc<KeyPool> {
+        &self.keys
+    }
+
     /// 執行搜尋
     pub async fn search(&self, query: &str, num_results: usize) -> Result<Vec<SearchResult>, SearchError> {
-        let url = "https://api.exa.ai/search";
-
-        let body = json!({
-            "query": query,
-            "type": "auto",
-            "numResults": num_results,
-            "contents": {
-                "text": {
-                    "maxCharacters": 1000
+        self.search_query(&SearchQuery::new(query).with_num_results(num_results)).await
+    }
+
+    /// 帶過濾條件的搜尋：網域、類別與發布日期都交給 Exa 在伺服器端過濾
+    pub async fn search_query(&self, query: &SearchQuery) -> Result<Vec<SearchResult>, SearchError> {
+        let request = ExaSearchRequest::from_query(query, self.search_type, today());
+        let response = self.post("https://api.exa.ai/search", &request).await?;
+        let mut results: Vec<SearchResult> = response.results.into_iter().map(SearchResult::from).collect();
+
+        // 只有全文時補上查詢導向摘要
+        fill_missing_snippets(&mut results, &query.query);
+
+        Ok(results)
+    }
+
+    /// 找出與 `url` 內容相似的頁面（`/findSimilar`），不含種子網址本身與同網域的頁面
+    pub async fn find_similar(&self, url: &str, num_results: usize) -> Result<Vec<SearchResult>, SearchError> {
+        let request = ExaFindSimilarRequest {
+            url: url.to_string(),
+            num_results,
+            exclude_source_domain: true,
+            contents: ExaContents::default(),
+        };
+
+        let seed = dedup_key(url);
+        let response = self.post("https://api.exa.ai/findSimilar", &request).await?;
+        let mut results: Vec<SearchResult> = response
+            .results
+            .into_iter()
+            .map(SearchResult::from)
+            .filter(|r| dedup_key(&r.url) != seed)
+            .collect();
+
+        // 沒有查詢詞，摘要取全文開頭
+        fill_missing_snippets(&mut results, "");
+
+        Ok(results)
+    }
+
+    /// 送出請求並解析回應；429 / 402 且還有可用金鑰時立即換一把重送
+    async fn post<T: Serialize>(&self, url: &str, body: &T) -> Result<ExaResponse, SearchError> {
+        let response = with_backoff(&self.retry, || async {
+            // 每把金鑰最多試一次
+            let mut remaining = self.keys.len();
+            loop {
+                if let Some(limiter) = &self.limiter {
+                    limiter.acquire().await;
                 }
-            }
-        });
+                let lease = self
+                    .keys
+                    .next()
+                    .ok_or_else(|| SearchError::ConfigError("未設定 Exa API 金鑰".to_string()))?;
+                remaining -= 1;
 
-        let response = self.client
-            .post(url)
-            .header("x-api-key", &self.api_key)
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| SearchError::NetworkError(e.to_string()))?;
+                let request = self.client
+                    .post(url)
+                    .header("x-api-key", &lease.key)
+                    .header("Content-Type", "application/json")
+                    .json(body);
+                let response = self.middleware.send(SearchEngine::Exa, &self.client, request).await?;
+                if let Some(limiter) = &self.limiter {
+                    limiter.observe(response.status(), response.headers());
+                }
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(SearchError::ApiError(format!("Exa API 錯誤 {}: {}", status, error_text)));
-        }
+                if !response.status().is_success() {
+                    let status = response.status();
+                    if self.keys.observe(&lease, status, response.headers()) && remaining > 0 {
+                        continue;
+                    }
+                    let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+                    return Err(SearchError::ApiError(format!("Exa API 錯誤 {}: {}", status, error_text)));
+                }
+                return Ok(response);
+            }
+        })
+        .await?;
 
-        let json: Value = response
+        let response: ExaResponse = response
             .json()
             .await
-            .map_err(|e| SearchError::ParseError(e.to_string()))?;
-
-        let results = json["results"]
-            .as_array()
-            .ok_or_else(|| SearchError::ParseError("無法解析結果".to_string()))?
-            .iter()
-            .map(|r| SearchResult {
-                title: r["title"].as_str().unwrap_or("無標題").to_string(),
-                url: r["url"].as_str().unwrap_or("").to_string(),
-                snippet: r["snippet"].as_str().map(|s| s.to_string()),
-                content: r["text"].as_str().map(|s| s.to_string()),
+            .map_err(|e| SearchError::ParseError(format!("無法解析 Exa 回應: {}", e)))?;
+        log_unknown_fields("Exa", std::iter::once(&response.extra).chain(response.results.iter().map(|r| &r.extra)));
+        Ok(response)
+    }
+}
+
+/// 共用類別名稱對應到 Exa 的類別（也接受 SearXNG 的 `science`）
+fn exa_category(category: &str) -> Option<&'static str> {
+    let normalized = category.trim().to_ascii_lowercase().replace(['_', '-'], " ");
+    let alias = match normalized.as_str() {
+        "science" | "research" | "paper" | "papers" => "research paper",
+        "twitter" | "tweets" => "tweet",
+        other => other,
+    };
+    EXA_CATEGORIES.iter().copied().find(|c| *c == alias)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TimeRange;
+    use serde_json::json;
+
+    #[test]
+    fn test_search_request_snapshot() {
+        let query = SearchQuery::new("rust async runtime")
+            .with_num_results(5)
+            .with_include_domains(vec!["docs.rs".into(), "github.com".into()])
+            .with_exclude_domains(vec!["medium.com".into()])
+            .with_category("science")
+            .with_date_range(Some("2024-03-14"), Some("Thu, 30 May 2024 08:00:00 GMT"));
+        insta::assert_json_snapshot!(ExaSearchRequest::from_query(&query, ExaSearchType::Neural, 0));
+    }
+
+    #[test]
+    fn test_search_request_defaults_and_time_range() {
+        let body = serde_json::to_value(ExaSearchRequest::from_query(
+            &SearchQuery::new("rust"),
+            ExaSearchType::default(),
+            0,
+        ))
+        .unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "query": "rust",
+                "type": "auto",
+                "numResults": 10,
+                "contents": { "text": { "maxCharacters": 1000 } },
             })
-            .collect();
+        );
 
-        Ok(results)
+        // 2024-03-15 的一週前
+        let query = SearchQuery::new("rust").with_time_range(TimeRange::Week).with_category("recipes");
+        let request = ExaSearchRequest::from_query(&query, ExaSearchType::Keyword, 19_797);
+        assert_eq!(request.start_published_date.as_deref(), Some("2024-03-08T00:00:00.000Z"));
+        assert_eq!(request.category, None);
+        assert_eq!(exa_category("Research_Paper"), Some("research paper"));
+    }
+
+    #[test]
+    fn test_response_snapshot() {
+        let response: ExaResponse = serde_json::from_value(json!({
+            "requestId": "b5947044c4b78efa9552a7c89b306d95",
+            "resolvedSearchType": "neural",
+            "costDollars": { "total": 0.005 },
+            "results": [
+                {
+                    "id": "https://tokio.rs/blog/2024-01-01",
+                    "url": "https://tokio.rs/blog/2024-01-01?utm_source=exa",
+                    "title": "Announcing Tokio 1.35",
+                    "author": "Carl Lerche",
+                    "publishedDate": "2024-01-01T00:00:00.000Z",
+                    "score": 0.42,
+                    "text": "Tokio 1.35 adds a new scheduler metric.",
+                    "favicon": "https://tokio.rs/favicon.ico",
+                    "subpages": []
+                },
+                { "id": "missing-url", "title": "No URL" },
+                {
+                    "url": "https://docs.rs/tokio",
+                    "title": "",
+                    "author": null,
+                    "score": "high",
+                    "highlights": ["Tokio is an event-driven, non-blocking I/O platform."]
+                },
+                {
+                    "url": "https://github.com/tokio-rs/tokio",
+                    "title": null,
+                    "highlights": ["", "A runtime for writing reliable applications."]
+                }
+            ]
+        }))
+        .unwrap();
+        // 缺少 url 與 score 型別不符的項目被略過
+        assert_eq!(response.results.len(), 2);
+        assert_eq!(response.resolved_search_type.as_deref(), Some("neural"));
+        assert!(response.extra.contains_key("costDollars"));
+        assert!(response.results[0].extra.contains_key("subpages"));
+
+        let results: Vec<SearchResult> = response.results.into_iter().map(SearchResult::from).collect();
+        insta::assert_json_snapshot!(results);
     }
 }
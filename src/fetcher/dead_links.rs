@@ -0,0 +1,281 @@
+//! 失效連結檢查 - 搜尋後以 HEAD 並行檢查前 N 個結果，標記或移除 404 / 410 / 逾時的連結
+//!
+//! 不接受 HEAD 的伺服器（405 / 501）改用 GET。可選擇改用 Wayback Machine 的快照，
+//! 避免代理程式引用已經不存在的頁面。
+
+use super::wayback;
+use crate::optimization::PooledClient;
+use crate::types::SearchResult;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 失效連結的處理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DeadLinkAction {
+    /// 保留結果，只在報告中標記
+    #[default]
+    Mark,
+    /// 移除結果
+    Drop,
+}
+
+/// 檢查配置
+#[derive(Debug, Clone)]
+pub struct LinkCheckConfig {
+    /// 只檢查排名前 N 的結果
+    pub top_n: usize,
+    /// 單一連結的逾時，逾時視為失效
+    pub timeout: Duration,
+    pub action: DeadLinkAction,
+    /// 失效時改用 Wayback Machine 的快照（有快照就不標記也不移除）
+    pub wayback_fallback: bool,
+    pub wayback_api: String,
+}
+
+impl Default for LinkCheckConfig {
+    fn default() -> Self {
+        Self {
+            top_n: 10,
+            timeout: Duration::from_secs(5),
+            action: DeadLinkAction::default(),
+            wayback_fallback: false,
+            wayback_api: wayback::DEFAULT_WAYBACK_API.into(),
+        }
+    }
+}
+
+/// 單一連結的狀態
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkStatus {
+    Alive(u16),
+    /// 404 或 410
+    NotFound(u16),
+    Timeout,
+    /// 連線失敗（DNS、拒絕連線、TLS）
+    Unreachable(String),
+}
+
+impl LinkStatus {
+    pub fn is_dead(&self) -> bool {
+        !matches!(self, Self::Alive(_))
+    }
+}
+
+impl fmt::Display for LinkStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Alive(status) => write!(f, "HTTP {}", status),
+            Self::NotFound(status) => write!(f, "HTTP {}", status),
+            Self::Timeout => write!(f, "逾時"),
+            Self::Unreachable(error) => write!(f, "無法連線: {}", error),
+        }
+    }
+}
+
+/// 單一結果的檢查結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkReport {
+    /// 檢查的原始網址
+    pub url: String,
+    pub status: LinkStatus,
+    /// 改用的 Wayback 快照
+    pub snapshot: Option<String>,
+}
+
+/// 失效連結檢查器；複製成本低，複本共用連線池
+#[derive(Clone)]
+pub struct LinkChecker {
+    client: Arc<PooledClient>,
+    config: Arc<LinkCheckConfig>,
+}
+
+impl LinkChecker {
+    pub fn new(client: Arc<PooledClient>, config: LinkCheckConfig) -> Self {
+        Self {
+            client,
+            config: Arc::new(config),
+        }
+    }
+
+    pub fn config(&self) -> &LinkCheckConfig {
+        &self.config
+    }
+
+    /// 檢查單一網址
+    pub async fn check(&self, url: &str) -> LinkStatus {
+        let request = async {
+            let response = self.client.head(url).await?;
+            if matches!(response.status().as_u16(), 405 | 501) {
+                return self.client.get(url).await;
+            }
+            Ok(response)
+        };
+        match tokio::time::timeout(self.config.timeout, request).await {
+            Err(_) => LinkStatus::Timeout,
+            Ok(Err(e)) => LinkStatus::Unreachable(e.to_string()),
+            Ok(Ok(response)) => match response.status().as_u16() {
+                status @ (404 | 410) => LinkStatus::NotFound(status),
+                status => LinkStatus::Alive(status),
+            },
+        }
+    }
+
+    /// 並行檢查前 `top_n` 個結果並依設定處理失效連結，回傳依原排名排列的報告
+    pub async fn verify(&self, results: &mut Vec<SearchResult>) -> Vec<LinkReport> {
+        let checked = self.config.top_n.min(results.len());
+        let mut tasks = tokio::task::JoinSet::new();
+        for (i, result) in results.iter().take(checked).enumerate() {
+            let checker = self.clone();
+            let url = result.url.clone();
+            tasks.spawn(async move {
+                let status = checker.check(&url).await;
+                let snapshot = if status.is_dead() && checker.config.wayback_fallback {
+                    checker.snapshot(&url).await
+                } else {
+                    None
+                };
+                (i, LinkReport { url, status, snapshot })
+            });
+        }
+
+        let mut reports: Vec<Option<LinkReport>> = vec![None; checked];
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok((i, report)) = joined {
+                reports[i] = Some(report);
+            }
+        }
+
+        let mut dropped = vec![false; results.len()];
+        for (i, report) in reports.iter().enumerate() {
+            let Some(report) = report.as_ref().filter(|r| r.status.is_dead()) else {
+                continue;
+            };
+            match &report.snapshot {
+                Some(snapshot) => results[i].url = snapshot.clone(),
+                None => dropped[i] = self.config.action == DeadLinkAction::Drop,
+            }
+        }
+        let dead = reports.iter().flatten().filter(|r| r.status.is_dead()).count();
+        if dead > 0 {
+            log::info!("🔗 前 {} 個結果中有 {} 個失效連結", checked, dead);
+        }
+        let mut index = 0;
+        results.retain(|_| {
+            index += 1;
+            !dropped[index - 1]
+        });
+        reports.into_iter().flatten().collect()
+    }
+
+    async fn snapshot(&self, url: &str) -> Option<String> {
+        match wayback::lookup(&self.client, &self.config.wayback_api, url).await {
+            Ok(snapshot) => snapshot.map(|s| s.url),
+            Err(e) => {
+                log::debug!("無法取得 {} 的封存快照: {}", url, e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// 依路徑回應的本機伺服器；`/slow` 逾時才回應，`/nohead` 對 HEAD 回應 405
+    async fn serve() -> MockServer {
+        let server = MockServer::start().await;
+        let snapshot = format!("{}/web/20240101000000/https://gone.example/", server.uri());
+        Mock::given(method("HEAD"))
+            .and(path("/nohead"))
+            .respond_with(ResponseTemplate::new(405))
+            .mount(&server)
+            .await;
+        let routes = [
+            ("/slow", ResponseTemplate::new(200).set_delay(Duration::from_secs(3))),
+            ("/ok", ResponseTemplate::new(200)),
+            ("/nohead", ResponseTemplate::new(200)),
+            ("/gone", ResponseTemplate::new(410)),
+            (
+                "/available",
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({ "archived_snapshots": { "closest": {
+                    "available": true, "timestamp": "20240101000000", "url": snapshot,
+                }}})),
+            ),
+        ];
+        for (route, response) in routes {
+            Mock::given(path(route)).respond_with(response).mount(&server).await;
+        }
+        server
+    }
+
+    fn result(url: String) -> SearchResult {
+        SearchResult {
+            title: url.clone(),
+            url,
+            snippet: None,
+            content: None,
+            published_date: None,
+            metadata: Default::default(),
+        }
+    }
+
+    fn checker(config: LinkCheckConfig) -> LinkChecker {
+        let config = LinkCheckConfig {
+            timeout: Duration::from_millis(500),
+            ..config
+        };
+        LinkChecker::new(Arc::new(PooledClient::with_defaults().unwrap()), config)
+    }
+
+    #[tokio::test]
+    async fn test_check_statuses() {
+        let server = serve().await;
+        let addr = server.uri();
+        let checker = checker(LinkCheckConfig::default());
+        assert_eq!(checker.check(&format!("{}/ok", addr)).await, LinkStatus::Alive(200));
+        assert_eq!(checker.check(&format!("{}/nohead", addr)).await, LinkStatus::Alive(200));
+        assert_eq!(checker.check(&format!("{}/missing", addr)).await, LinkStatus::NotFound(404));
+        assert_eq!(checker.check(&format!("{}/gone", addr)).await, LinkStatus::NotFound(410));
+        assert_eq!(checker.check(&format!("{}/slow", addr)).await, LinkStatus::Timeout);
+    }
+
+    #[tokio::test]
+    async fn test_verify_drops_and_swaps_snapshots() {
+        let server = serve().await;
+        let addr = server.uri();
+        let urls = ["/ok", "/missing", "/slow", "/gone"].map(|path| format!("{}{}", addr, path));
+        let fresh = || urls.iter().cloned().map(result).collect::<Vec<_>>();
+
+        // 只檢查前三個：/gone 不受影響
+        let mut results = fresh();
+        let reports = checker(LinkCheckConfig {
+            top_n: 3,
+            action: DeadLinkAction::Drop,
+            ..Default::default()
+        })
+        .verify(&mut results)
+        .await;
+        assert_eq!(reports.len(), 3);
+        assert_eq!(reports[2].status, LinkStatus::Timeout);
+        let kept: Vec<&str> = results.iter().map(|r| r.url.as_str()).collect();
+        assert_eq!(kept, vec![urls[0].as_str(), urls[3].as_str()]);
+
+        let mut results = fresh();
+        let reports = checker(LinkCheckConfig {
+            top_n: 2,
+            wayback_fallback: true,
+            wayback_api: format!("{}/available", addr),
+            ..Default::default()
+        })
+        .verify(&mut results)
+        .await;
+        let snapshot = format!("{}/web/20240101000000/https://gone.example/", addr);
+        assert_eq!(reports[1].snapshot.as_deref(), Some(snapshot.as_str()));
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[1].url, snapshot);
+    }
+}
@@ -0,0 +1,273 @@
+//! 網站中繼資料 - 抓取排名前 N 的結果頁面，取出 favicon、OpenGraph 標題 / 描述 / 圖片與網站名稱
+//!
+//! 結果寫入 `SearchResult::metadata`，供 CLI 與報告呈現，也讓 LLM 知道來源是哪個網站。
+//! 透過 `Fetcher` 抓取，因此同樣遵守 robots.txt 與主機並發限制。
+
+use super::Fetcher;
+use crate::types::SearchResult;
+use bose_common::text::collapse_whitespace;
+use reqwest::Url;
+use scraper::{Html, Selector};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// `SearchResult::metadata` 的鍵
+pub const SITE_NAME: &str = "site_name";
+pub const FAVICON: &str = "favicon";
+pub const OG_TITLE: &str = "og_title";
+pub const OG_DESCRIPTION: &str = "og_description";
+pub const OG_IMAGE: &str = "og_image";
+/// 引擎提供的作者（Exa）
+pub const AUTHOR: &str = "author";
+/// 引擎提供的相關度分數（Tavily）
+pub const SCORE: &str = "score";
+/// 頁面只有付費牆前導段落（也沒有封存快照）時為 `"true"`；`ContextPruner` 不裁剪這類內容
+pub const PAYWALLED: &str = "paywalled";
+
+/// 頁面上的網站中繼資料
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SiteMetadata {
+    /// `og:site_name`，其次 `application-name`
+    pub site_name: Option<String>,
+    /// `og:title`，其次 `twitter:title`
+    pub title: Option<String>,
+    /// `og:description`，其次 `description`
+    pub description: Option<String>,
+    /// `og:image`（已轉成絕對網址）
+    pub image: Option<String>,
+    /// `<link rel="icon">`，沒有時為網站根目錄的 `/favicon.ico`
+    pub favicon: Option<String>,
+    /// 抓到的只是付費牆前導段落
+    pub paywalled: bool,
+}
+
+impl SiteMetadata {
+    /// 由 HTML 取出中繼資料；`base_url` 用來解析相對網址（通常是重導後的最終網址）
+    pub fn parse(base_url: &str, html: &str) -> Self {
+        let document = Html::parse_document(html);
+        let base = Url::parse(base_url).ok();
+        let resolve = |href: &str| match &base {
+            Some(base) => base.join(href).ok().map(|url| url.to_string()),
+            None => Url::parse(href).ok().map(|url| url.to_string()),
+        };
+
+        let mut meta: BTreeMap<String, String> = BTreeMap::new();
+        if let Ok(selector) = Selector::parse("meta[content]") {
+            for element in document.select(&selector) {
+                let element = element.value();
+                let Some(key) = element.attr("property").or(element.attr("name")) else { continue };
+                let content = collapse_whitespace(element.attr("content").unwrap_or(""));
+                if !content.is_empty() {
+                    // 同一個鍵出現多次時取第一個
+                    meta.entry(key.trim().to_ascii_lowercase()).or_insert(content);
+                }
+            }
+        }
+        let first = |keys: &[&str]| keys.iter().find_map(|key| meta.get(*key).cloned());
+
+        let favicon = Selector::parse("link[rel][href]").ok().and_then(|selector| {
+            let icons: Vec<(bool, &str)> = document
+                .select(&selector)
+                .filter_map(|element| {
+                    let element = element.value();
+                    let rel = element.attr("rel")?.to_ascii_lowercase();
+                    let tokens: Vec<&str> = rel.split_whitespace().collect();
+                    let apple = tokens.iter().any(|t| t.starts_with("apple-touch-icon"));
+                    (tokens.contains(&"icon") || apple).then_some((apple, element.attr("href")?))
+                })
+                .collect();
+            // 一般的 icon 優先於 apple-touch-icon
+            icons.iter().find(|(apple, _)| !apple).or(icons.first()).and_then(|(_, href)| resolve(href))
+        });
+        let favicon = favicon.or_else(|| {
+            base.as_ref()
+                .filter(|base| matches!(base.scheme(), "http" | "https"))
+                .and_then(|base| base.join("/favicon.ico").ok())
+                .map(|url| url.to_string())
+        });
+
+        Self {
+            site_name: first(&["og:site_name", "application-name"]),
+            title: first(&["og:title", "twitter:title"]),
+            description: first(&["og:description", "description", "twitter:description"]),
+            image: first(&["og:image", "og:image:url", "twitter:image"]).and_then(|href| resolve(&href)),
+            favicon,
+            paywalled: false,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// 寫入 `SearchResult::metadata`；已有的鍵保留原值
+    pub fn insert_into(&self, metadata: &mut BTreeMap<String, String>) {
+        let fields = [
+            (SITE_NAME, &self.site_name),
+            (OG_TITLE, &self.title),
+            (OG_DESCRIPTION, &self.description),
+            (OG_IMAGE, &self.image),
+            (FAVICON, &self.favicon),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                metadata.entry(key.to_string()).or_insert_with(|| value.clone());
+            }
+        }
+        if self.paywalled {
+            metadata.insert(PAYWALLED.to_string(), "true".to_string());
+        }
+    }
+}
+
+/// 補充配置
+#[derive(Debug, Clone)]
+pub struct EnrichConfig {
+    /// 只補充排名前 N 的結果
+    pub top_n: usize,
+    /// 單一頁面的逾時，逾時的結果不補充
+    pub timeout: Duration,
+}
+
+impl Default for EnrichConfig {
+    fn default() -> Self {
+        Self {
+            top_n: 5,
+            timeout: Duration::from_secs(8),
+        }
+    }
+}
+
+/// 中繼資料補充器；複製成本低，複本共用抓取器
+#[derive(Clone)]
+pub struct Enricher {
+    fetcher: Fetcher,
+    config: Arc<EnrichConfig>,
+}
+
+impl Enricher {
+    pub fn new(fetcher: Fetcher, config: EnrichConfig) -> Self {
+        Self {
+            fetcher,
+            config: Arc::new(config),
+        }
+    }
+
+    pub fn config(&self) -> &EnrichConfig {
+        &self.config
+    }
+
+    /// 抓取單一頁面並取出中繼資料；PDF 等非 HTML 內容回傳空的中繼資料
+    pub async fn lookup(&self, url: &str) -> Option<SiteMetadata> {
+        match tokio::time::timeout(self.config.timeout, self.fetcher.fetch(url)).await {
+            Ok(Ok(page)) if page.text.is_none() => Some(SiteMetadata {
+                paywalled: !page.has_full_text(),
+                ..SiteMetadata::parse(&page.final_url, &page.html)
+            }),
+            Ok(Ok(_)) => Some(SiteMetadata::default()),
+            Ok(Err(e)) => {
+                log::debug!("無法抓取 {} 的中繼資料: {}", url, e);
+                None
+            }
+            Err(_) => {
+                log::debug!("抓取 {} 的中繼資料逾時", url);
+                None
+            }
+        }
+    }
+
+    /// 並行補充前 `top_n` 個結果（已有網站名稱的略過），回傳補充成功的數量
+    pub async fn enrich(&self, results: &mut [SearchResult]) -> usize {
+        let mut tasks = tokio::task::JoinSet::new();
+        for (i, result) in results.iter().take(self.config.top_n).enumerate() {
+            if result.metadata.contains_key(SITE_NAME) {
+                continue;
+            }
+            let enricher = self.clone();
+            let url = result.url.clone();
+            tasks.spawn(async move { (i, enricher.lookup(&url).await) });
+        }
+
+        let mut enriched = 0;
+        while let Some(joined) = tasks.join_next().await {
+            let Ok((i, Some(metadata))) = joined else { continue };
+            if !metadata.is_empty() {
+                metadata.insert_into(&mut results[i].metadata);
+                enriched += 1;
+            }
+        }
+        enriched
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fetcher::FetcherConfig;
+    use crate::optimization::PooledClient;
+    use wiremock::matchers::path;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const PAGE: &str = r#"<html><head>
+        <meta property="og:site_name" content="Tokio">
+        <meta property="og:title" content="Tokio - An asynchronous Rust runtime">
+        <meta name="description" content="Tokio is a runtime for writing
+            reliable network applications.">
+        <meta property="og:image" content="/img/social.png">
+        <link rel="apple-touch-icon" href="/apple.png">
+        <link rel="shortcut icon" href="favicon-32.png">
+        </head><body><p>Hello</p></body></html>"#;
+
+    #[test]
+    fn test_parse_opengraph_and_favicon() {
+        let metadata = SiteMetadata::parse("https://tokio.rs/docs/overview", PAGE);
+        assert_eq!(metadata.site_name.as_deref(), Some("Tokio"));
+        assert_eq!(metadata.title.as_deref(), Some("Tokio - An asynchronous Rust runtime"));
+        assert_eq!(
+            metadata.description.as_deref(),
+            Some("Tokio is a runtime for writing reliable network applications.")
+        );
+        assert_eq!(metadata.image.as_deref(), Some("https://tokio.rs/img/social.png"));
+        assert_eq!(metadata.favicon.as_deref(), Some("https://tokio.rs/docs/favicon-32.png"));
+
+        let bare = SiteMetadata::parse("https://example.com/a/b", "<p>no head</p>");
+        assert_eq!(bare.favicon.as_deref(), Some("https://example.com/favicon.ico"));
+        assert_eq!(bare.site_name, None);
+
+        let mut map = BTreeMap::from([(SITE_NAME.to_string(), "Exa".to_string())]);
+        metadata.insert_into(&mut map);
+        assert_eq!(map[SITE_NAME], "Exa");
+        assert_eq!(map[OG_IMAGE], "https://tokio.rs/img/social.png");
+        assert_eq!(map.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_enrich_top_results() {
+        let server = MockServer::start().await;
+        Mock::given(path("/page"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(PAGE, "text/html"))
+            .mount(&server)
+            .await;
+        let addr = server.uri();
+
+        let result = |path: &str| SearchResult {
+            title: path.to_string(),
+            url: format!("{}{}", addr, path),
+            snippet: None,
+            content: None,
+            published_date: None,
+            metadata: BTreeMap::new(),
+        };
+        let mut results = vec![result("/page"), result("/missing"), result("/page")];
+        let fetcher = Fetcher::new(Arc::new(PooledClient::with_defaults().unwrap()), FetcherConfig::default());
+        let enricher = Enricher::new(fetcher, EnrichConfig { top_n: 2, ..Default::default() });
+
+        assert_eq!(enricher.enrich(&mut results).await, 1);
+        assert_eq!(results[0].metadata[SITE_NAME], "Tokio");
+        assert_eq!(results[0].metadata[FAVICON], format!("{}/favicon-32.png", addr));
+        assert!(results[1].metadata.is_empty());
+        // 超出 top_n 的結果不抓取
+        assert!(results[2].metadata.is_empty());
+    }
+}
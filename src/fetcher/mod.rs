@@ -0,0 +1,579 @@
+//! 網頁抓取 - 第一方抓取結果頁面，取得原始 HTML、最終網址與回應標頭
+//!
+//! L3 與深度研究原本只能依賴 Tavily extract；這裡透過 `PooledClient` 直接抓取，
+//! 限制每個主機的並發數、回應大小與內容類型，預設遵守 robots.txt 與 Crawl-delay（見 `robots`）。
+//! 重導次數上限由 `PoolConfig::max_redirects` 控制。
+//! 啟用 `pdf` feature 時也接受 `application/pdf`，並直接擷取成純文字。
+//! HTML 頁面會偵測付費牆（見 `paywall`），可選擇改抓 Wayback Machine 的快照。
+
+pub mod dead_links;
+pub mod metadata;
+pub mod paywall;
+pub mod robots;
+pub mod wayback;
+
+use crate::optimization::PooledClient;
+use crate::processing::{ContextPruner, HtmlCleaner};
+use crate::types::SearchError;
+use paywall::PaywallMarker;
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, CONTENT_TYPE, USER_AGENT};
+use reqwest::Url;
+use robots::{RobotsCache, RobotsRules};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+const PDF: &str = "application/pdf";
+
+/// 抓取配置
+#[derive(Debug, Clone)]
+pub struct FetcherConfig {
+    /// 每個主機同時進行的請求數
+    pub max_per_host: usize,
+    /// 回應本文上限（位元組），超過的部分截掉
+    pub max_body_bytes: usize,
+    /// 接受的內容類型；回應沒有 Content-Type 時一律接受
+    pub allowed_content_types: Vec<String>,
+    /// 抓取前檢查 robots.txt 並遵守 Crawl-delay；關閉時不抓 robots.txt
+    pub respect_robots: bool,
+    /// 送出的 User-Agent，也用來比對 robots.txt 的群組
+    pub user_agent: String,
+    /// Crawl-delay 上限，避免設定過大的網站拖住整批抓取
+    pub max_crawl_delay: Duration,
+    /// 偵測到付費牆時改抓 Wayback Machine 的快照
+    pub wayback_fallback: bool,
+    /// Wayback Machine 可用性 API
+    pub wayback_api: String,
+}
+
+impl Default for FetcherConfig {
+    fn default() -> Self {
+        Self {
+            max_per_host: 2,
+            max_body_bytes: 2 * 1024 * 1024,
+            allowed_content_types: vec![
+                "text/html".into(),
+                "application/xhtml+xml".into(),
+                "text/plain".into(),
+            ],
+            respect_robots: true,
+            user_agent: concat!("bose-search/", env!("CARGO_PKG_VERSION")).into(),
+            max_crawl_delay: Duration::from_secs(10),
+            wayback_fallback: false,
+            wayback_api: wayback::DEFAULT_WAYBACK_API.into(),
+        }
+    }
+}
+
+/// 抓取結果
+#[derive(Debug, Clone)]
+pub struct FetchedPage {
+    /// 請求的網址
+    pub url: String,
+    /// 重導後的最終網址
+    pub final_url: String,
+    pub status: u16,
+    pub headers: HeaderMap,
+    /// 不含參數的 MIME 類型（`text/html`）
+    pub content_type: Option<String>,
+    pub html: String,
+    /// 已擷取的純文字（PDF）；HTML 頁面為 None
+    pub text: Option<String>,
+    /// 本文超過 `max_body_bytes` 被截斷
+    pub truncated: bool,
+    /// 原網址是付費牆後的前導段落
+    pub is_paywalled: bool,
+    pub paywall_markers: Vec<PaywallMarker>,
+    /// 內容改取自 Wayback Machine 的快照時為快照網址
+    pub archived_url: Option<String>,
+}
+
+impl FetchedPage {
+    /// 清理後的純文字：PDF 取擷取結果，HTML 走 `HtmlCleaner::extract`
+    pub fn cleaned_text(&self) -> String {
+        match &self.text {
+            Some(text) => text.clone(),
+            None => HtmlCleaner::extract(&self.html),
+        }
+    }
+
+    /// 有完整內容：不是付費牆，或已改用封存快照
+    pub fn has_full_text(&self) -> bool {
+        !self.is_paywalled || self.archived_url.is_some()
+    }
+
+    /// 沒有完整內容時在結果的 `metadata` 標記 `paywalled`，讓裁剪器略過前導段落
+    pub fn mark_paywall(&self, metadata: &mut BTreeMap<String, String>) {
+        if !self.has_full_text() {
+            metadata.insert(metadata::PAYWALLED.to_string(), "true".to_string());
+        }
+    }
+
+    /// 清理後交給裁剪器
+    pub fn pruned(&self, pruner: &ContextPruner) -> String {
+        pruner.prune(&self.cleaned_text())
+    }
+}
+
+/// 網頁抓取器；複製成本低，複本共用連線池、主機限制與 robots.txt 快取
+#[derive(Clone)]
+pub struct Fetcher {
+    client: Arc<PooledClient>,
+    config: Arc<FetcherConfig>,
+    hosts: Arc<Mutex<HashMap<String, Arc<Semaphore>>>>,
+    robots: Arc<RobotsCache>,
+}
+
+impl Fetcher {
+    pub fn new(client: Arc<PooledClient>, config: FetcherConfig) -> Self {
+        Self {
+            client,
+            config: Arc::new(config),
+            hosts: Arc::new(Mutex::new(HashMap::new())),
+            robots: Arc::new(RobotsCache::default()),
+        }
+    }
+
+    pub fn config(&self) -> &FetcherConfig {
+        &self.config
+    }
+
+    /// 抓取單一網址；偵測到付費牆且啟用 `wayback_fallback` 時，快照內容較完整就改用快照
+    pub async fn fetch(&self, url: &str) -> Result<FetchedPage, SearchError> {
+        let page = self.fetch_direct(url).await?;
+        if !page.is_paywalled || !self.config.wayback_fallback {
+            return Ok(page);
+        }
+        match self.fetch_archived(url).await {
+            Ok(Some(archived)) if archived.cleaned_text().len() > page.cleaned_text().len() => {
+                log::info!("🏛️ {} 有付費牆，改用封存快照 {}", url, archived.final_url);
+                Ok(FetchedPage {
+                    url: page.url,
+                    is_paywalled: true,
+                    paywall_markers: page.paywall_markers,
+                    archived_url: Some(archived.url.clone()),
+                    ..archived
+                })
+            }
+            Ok(_) => Ok(page),
+            Err(e) => {
+                log::debug!("無法取得 {} 的封存快照: {}", url, e);
+                Ok(page)
+            }
+        }
+    }
+
+    /// 查詢 Wayback Machine 最接近的快照並抓取
+    async fn fetch_archived(&self, url: &str) -> Result<Option<FetchedPage>, SearchError> {
+        match wayback::lookup(&self.client, &self.config.wayback_api, url).await? {
+            Some(snapshot) => self.fetch_direct(&snapshot.raw_url()).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    async fn fetch_direct(&self, url: &str) -> Result<FetchedPage, SearchError> {
+        let parsed = Url::parse(url)
+            .map_err(|e| SearchError::ParseError(format!("無效的網址 {}: {}", url, e)))?;
+        if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+            return Err(SearchError::ParseError(format!("不支援的網址: {}", url)));
+        }
+        let host = host_key(&parsed);
+
+        let semaphore = self.host_semaphore(&host);
+        if self.config.respect_robots {
+            let rules = self.robots_rules(&parsed, &host).await;
+            if !rules.allows(&robots_path(&parsed)) {
+                return Err(SearchError::ApiError(format!("robots.txt 禁止抓取: {}", url)));
+            }
+            if let Some(delay) = rules.crawl_delay() {
+                let wait = self.robots.reserve(&host, delay.min(self.config.max_crawl_delay));
+                if !wait.is_zero() {
+                    log::debug!("依 Crawl-delay 等待 {:?} 後抓取 {}", wait, url);
+                    tokio::time::sleep(wait).await;
+                }
+            }
+        }
+        let _permit = semaphore
+            .acquire()
+            .await
+            .map_err(|e| SearchError::NetworkError(e.to_string()))?;
+
+        let response = self
+            .client
+            .get_with_headers(url, self.request_headers())
+            .await
+            .map_err(|e| SearchError::NetworkError(e.to_string()))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(SearchError::ApiError(format!("HTTP {}: {}", status.as_u16(), url)));
+        }
+
+        let final_url = response.url().to_string();
+        let headers = response.headers().clone();
+        let content_type = headers
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.split(';').next().unwrap_or("").trim().to_ascii_lowercase());
+        let is_pdf = content_type.as_deref() == Some(PDF);
+        if let Some(ct) = &content_type {
+            let accepted = self.config.allowed_content_types.iter().any(|allowed| allowed == ct)
+                || (is_pdf && cfg!(feature = "pdf"));
+            if !accepted {
+                return Err(SearchError::ParseError(format!("不支援的內容類型 {}: {}", ct, url)));
+            }
+        }
+
+        let (body, truncated) = read_body(response, self.config.max_body_bytes).await?;
+        let (html, text) = if is_pdf {
+            (String::new(), pdf_text(url, body, truncated).await?)
+        } else {
+            (String::from_utf8_lossy(&body).into_owned(), None)
+        };
+        let mut page = FetchedPage {
+            url: url.to_string(),
+            final_url,
+            status: status.as_u16(),
+            headers,
+            content_type,
+            html,
+            text,
+            truncated,
+            is_paywalled: false,
+            paywall_markers: Vec::new(),
+            archived_url: None,
+        };
+        if page.text.is_none() {
+            page.paywall_markers = paywall::detect(&page.final_url, &page.html, &page.cleaned_text());
+            page.is_paywalled = paywall::is_paywalled(&page.paywall_markers);
+        }
+        Ok(page)
+    }
+
+    /// 並行抓取多個網址，結果順序與輸入相同
+    pub async fn fetch_all(&self, urls: &[String]) -> Vec<Result<FetchedPage, SearchError>> {
+        let mut tasks = tokio::task::JoinSet::new();
+        for (i, url) in urls.iter().enumerate() {
+            let fetcher = self.clone();
+            let url = url.clone();
+            tasks.spawn(async move { (i, fetcher.fetch(&url).await) });
+        }
+
+        let mut results: Vec<Option<Result<FetchedPage, SearchError>>> =
+            urls.iter().map(|_| None).collect();
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok((i, result)) = joined {
+                results[i] = Some(result);
+            }
+        }
+        results
+            .into_iter()
+            .map(|r| r.unwrap_or_else(|| Err(SearchError::NetworkError("抓取工作中止".into()))))
+            .collect()
+    }
+
+    fn request_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Ok(agent) = HeaderValue::from_str(&self.config.user_agent) {
+            headers.insert(USER_AGENT, agent);
+        }
+        headers.insert(
+            ACCEPT,
+            HeaderValue::from_static("text/html,application/xhtml+xml;q=0.9,text/plain;q=0.8,*/*;q=0.5"),
+        );
+        headers
+    }
+
+    fn host_semaphore(&self, host: &str) -> Arc<Semaphore> {
+        self.hosts
+            .lock()
+            .unwrap()
+            .entry(host.to_string())
+            .or_insert_with(|| Arc::new(Semaphore::new(self.config.max_per_host.max(1))))
+            .clone()
+    }
+
+    /// 依主機快取 robots.txt：4xx 與連線失敗視為全部允許，5xx 視為全部禁止
+    async fn robots_rules(&self, url: &Url, host: &str) -> Arc<RobotsRules> {
+        if let Some(rules) = self.robots.get(host) {
+            return rules;
+        }
+        let robots_url = format!("{}://{}/robots.txt", url.scheme(), host);
+        let rules = match self.client.get_with_headers(&robots_url, self.request_headers()).await {
+            Ok(response) if response.status().is_success() => {
+                let text = response.text().await.unwrap_or_default();
+                RobotsRules::parse(&text, &self.config.user_agent)
+            }
+            Ok(response) if response.status().is_server_error() => {
+                log::debug!("robots.txt 回應 {}，暫不抓取 {}", response.status(), host);
+                RobotsRules::disallow_all()
+            }
+            _ => RobotsRules::allow_all(),
+        };
+        self.robots.insert(host, rules)
+    }
+}
+
+/// robots.txt 比對的路徑（含查詢字串）
+fn robots_path(url: &Url) -> String {
+    match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    }
+}
+
+/// `host:port`，作為並發限制與 robots.txt 快取的鍵
+fn host_key(url: &Url) -> String {
+    let host = url.host_str().unwrap_or("").to_ascii_lowercase();
+    match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host,
+    }
+}
+
+/// 在阻塞執行緒上擷取 PDF 文字
+#[cfg(feature = "pdf")]
+async fn pdf_text(url: &str, body: Vec<u8>, truncated: bool) -> Result<Option<String>, SearchError> {
+    // 截斷的 PDF 少了 xref 表，無法解析
+    if truncated {
+        return Err(SearchError::ParseError(format!("PDF 超過大小上限: {}", url)));
+    }
+    let text = tokio::task::spawn_blocking(move || crate::processing::pdf::extract_pdf_text(&body))
+        .await
+        .map_err(|e| SearchError::ParseError(e.to_string()))??;
+    Ok(Some(text))
+}
+
+/// 未啟用 `pdf` feature 時無法擷取（除非 `allowed_content_types` 明確列出 PDF，否則不會走到這裡）
+#[cfg(not(feature = "pdf"))]
+async fn pdf_text(_url: &str, _body: Vec<u8>, _truncated: bool) -> Result<Option<String>, SearchError> {
+    Ok(None)
+}
+
+/// 讀取本文，超過上限時截斷
+async fn read_body(mut response: reqwest::Response, max: usize) -> Result<(Vec<u8>, bool), SearchError> {
+    let mut body = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| SearchError::NetworkError(e.to_string()))?
+    {
+        let room = max - body.len();
+        if chunk.len() > room {
+            body.extend_from_slice(&chunk[..room]);
+            return Ok((body, true));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok((body, false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::path;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// 依路徑回應固定內容的本機伺服器；未列出的路徑回 404
+    async fn serve(routes: Vec<(&'static str, ResponseTemplate)>) -> MockServer {
+        let server = MockServer::start().await;
+        for (route, response) in routes {
+            Mock::given(path(route)).respond_with(response).mount(&server).await;
+        }
+        server
+    }
+
+    fn ok(content_type: &str, body: &str) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_raw(body.to_string(), content_type)
+    }
+
+    fn fetcher(config: FetcherConfig) -> Fetcher {
+        Fetcher::new(Arc::new(PooledClient::with_defaults().unwrap()), config)
+    }
+
+    #[tokio::test]
+    async fn test_fetch_follows_redirect() {
+        let server = serve(vec![
+            ("/old", ResponseTemplate::new(301).insert_header("Location", "/new")),
+            ("/new", ok("text/html; charset=utf-8", "<h1>新頁面</h1>")),
+        ])
+        .await;
+        let addr = server.uri();
+        let page = fetcher(FetcherConfig::default())
+            .fetch(&format!("{}/old", addr))
+            .await
+            .unwrap();
+        assert_eq!(page.final_url, format!("{}/new", addr));
+        assert_eq!(page.content_type.as_deref(), Some("text/html"));
+        assert_eq!(page.html, "<h1>新頁面</h1>");
+        assert!(!page.truncated);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_rejects_content_type_and_status() {
+        let server = serve(vec![("/image", ok("image/png", "PNG"))]).await;
+        let addr = server.uri();
+        let fetcher = fetcher(FetcherConfig::default());
+        assert!(matches!(
+            fetcher.fetch(&format!("{}/image", addr)).await,
+            Err(SearchError::ParseError(_))
+        ));
+        assert!(matches!(
+            fetcher.fetch(&format!("{}/missing", addr)).await,
+            Err(SearchError::ApiError(_))
+        ));
+        assert!(matches!(fetcher.fetch("ftp://example.com").await, Err(SearchError::ParseError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_cleaned_text_feeds_pruner() {
+        let server = serve(vec![(
+            "/doc",
+            ok("text/html", "<html><body><h1>Title</h1><p>Body &amp; text.</p></body></html>"),
+        )])
+        .await;
+        let addr = server.uri();
+        let page = fetcher(FetcherConfig::default())
+            .fetch(&format!("{}/doc", addr))
+            .await
+            .unwrap();
+        assert!(page.text.is_none());
+        let text = page.pruned(&ContextPruner::new(100));
+        assert!(text.contains("Body & text."));
+        assert!(!text.contains("<p>"));
+    }
+
+    #[cfg(not(feature = "pdf"))]
+    #[tokio::test]
+    async fn test_pdf_rejected_without_feature() {
+        let server = serve(vec![("/paper.pdf", ok(PDF, "%PDF-1.4"))]).await;
+        let addr = server.uri();
+        let result = fetcher(FetcherConfig::default())
+            .fetch(&format!("{}/paper.pdf", addr))
+            .await;
+        assert!(matches!(result, Err(SearchError::ParseError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_truncates_body() {
+        let server = serve(vec![("/big", ok("text/plain", &"x".repeat(5000)))]).await;
+        let addr = server.uri();
+        let page = fetcher(FetcherConfig {
+            max_body_bytes: 1000,
+            ..Default::default()
+        })
+        .fetch(&format!("{}/big", addr))
+        .await
+        .unwrap();
+        assert_eq!(page.html.len(), 1000);
+        assert!(page.truncated);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_all_respects_robots() {
+        let server = serve(vec![
+            ("/robots.txt", ok("text/plain", "User-agent: *\nDisallow: /private\nAllow: /private/ok\n")),
+            ("/private/secret", ok("text/html", "secret")),
+            ("/private/ok", ok("text/html", "ok")),
+        ])
+        .await;
+        let addr = server.uri();
+        let urls = vec![format!("{}/private/secret", addr), format!("{}/private/ok", addr)];
+
+        let results = fetcher(FetcherConfig::default()).fetch_all(&urls).await;
+        assert!(matches!(results[0], Err(SearchError::ApiError(_))));
+        assert_eq!(results[1].as_ref().unwrap().html, "ok");
+
+        let results = fetcher(FetcherConfig {
+            respect_robots: false,
+            ..Default::default()
+        })
+        .fetch_all(&urls)
+        .await;
+        assert_eq!(results[0].as_ref().unwrap().html, "secret");
+    }
+
+    #[tokio::test]
+    async fn test_robots_crawl_delay_and_server_error() {
+        let server = serve(vec![
+            ("/robots.txt", ok("text/plain", "User-agent: *\nCrawl-delay: 0.3\n")),
+            ("/a", ok("text/html", "a")),
+            ("/b", ok("text/html", "b")),
+        ])
+        .await;
+        let addr = server.uri();
+        let polite = fetcher(FetcherConfig::default());
+        let start = std::time::Instant::now();
+        let results = polite.fetch_all(&[format!("{}/a", addr), format!("{}/b", addr)]).await;
+        assert!(results.iter().all(Result::is_ok));
+        assert!(start.elapsed() >= Duration::from_millis(300));
+
+        let broken_server = serve(vec![
+            ("/robots.txt", ResponseTemplate::new(503)),
+            ("/a", ok("text/html", "a")),
+        ])
+        .await;
+        let broken = broken_server.uri();
+        let result = polite.fetch(&format!("{}/a", broken)).await;
+        assert!(matches!(result, Err(SearchError::ApiError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_paywall_wayback_fallback() {
+        let teaser = r#"<html><head><meta name="article:content_tier" content="locked"></head>
+            <body><p>Only the first paragraph.</p></body></html>"#;
+        let full = format!(
+            r#"<html><head><meta name="article:content_tier" content="locked"></head><body><p>{}</p></body></html>"#,
+            "Only the first paragraph. The rest of the story. ".repeat(20)
+        );
+        let origin_server = serve(vec![
+            ("/article", ok("text/html", teaser)),
+            ("/web/20240101000000id_/https://news.example/article", ok("text/html", &full)),
+        ])
+        .await;
+        let origin = origin_server.uri();
+        let snapshot = format!("{}/web/20240101000000/https://news.example/article", origin);
+        let archive_server = serve(vec![(
+            "/available",
+            ok(
+                "application/json",
+                &serde_json::json!({
+                    "archived_snapshots": { "closest": {
+                        "available": true, "status": "200", "timestamp": "20240101000000", "url": snapshot,
+                    }}
+                })
+                .to_string(),
+            ),
+        )])
+        .await;
+        let archive = archive_server.uri();
+        let url = format!("{}/article", origin);
+
+        let page = fetcher(FetcherConfig::default()).fetch(&url).await.unwrap();
+        assert!(page.is_paywalled);
+        assert_eq!(page.paywall_markers[0], PaywallMarker::ContentTierMeta);
+        assert!(!page.has_full_text());
+        let mut marks = BTreeMap::new();
+        page.mark_paywall(&mut marks);
+        assert_eq!(marks[metadata::PAYWALLED], "true");
+
+        let page = fetcher(FetcherConfig {
+            wayback_fallback: true,
+            wayback_api: format!("{}/available", archive),
+            ..Default::default()
+        })
+        .fetch(&url)
+        .await
+        .unwrap();
+        assert_eq!(page.url, url);
+        assert!(page.is_paywalled && page.has_full_text());
+        assert_eq!(
+            page.archived_url.as_deref(),
+            Some(format!("{}/web/20240101000000id_/https://news.example/article", origin).as_str())
+        );
+        assert!(page.cleaned_text().contains("The rest of the story."));
+        let mut marks = BTreeMap::new();
+        page.mark_paywall(&mut marks);
+        assert!(marks.is_empty());
+    }
+}
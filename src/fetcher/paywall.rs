@@ -0,0 +1,198 @@
+//! 付費牆偵測 - 由頁面標記判斷抓到的是否只是付費文章的前導段落
+//!
+//! 強訊號（任一即判定）：`article:content_tier` 為 locked / metered、結構化資料的
+//! `isAccessibleForFree: false`、常見付費牆元件的 class / id。
+//! 弱訊號（兩個以上才判定）：已知的付費網域、本文過短、「訂閱以繼續閱讀」之類的提示。
+
+use reqwest::Url;
+use scraper::{Html, Selector};
+
+/// 本文少於此字元數視為被截斷
+const SHORT_BODY_CHARS: usize = 1500;
+
+/// 常見的付費網域（含子網域）
+const PAYWALLED_DOMAINS: &[&str] = &[
+    "nytimes.com",
+    "wsj.com",
+    "ft.com",
+    "economist.com",
+    "bloomberg.com",
+    "washingtonpost.com",
+    "theatlantic.com",
+    "newyorker.com",
+    "barrons.com",
+    "thetimes.co.uk",
+    "telegraph.co.uk",
+    "hbr.org",
+    "theinformation.com",
+    "nikkei.com",
+];
+
+/// 付費牆元件的 class / id 片段（Piano、regwall 等）
+const PAYWALL_ELEMENTS: &[&str] = &[
+    "paywall",
+    "piano-offer",
+    "tp-modal",
+    "regwall",
+    "subscriber-only",
+    "premium-content",
+    "article-gate",
+];
+
+const SUBSCRIBE_PROMPTS: &[&str] = &[
+    "subscribe to continue reading",
+    "subscribe to read",
+    "to continue reading, subscribe",
+    "already a subscriber",
+    "this article is for subscribers",
+    "subscribers only",
+    "create a free account to continue",
+    "sign in to continue reading",
+    "reached your limit of free articles",
+    "reached your free article limit",
+    "訂閱以繼續閱讀",
+    "付費會員",
+];
+
+/// 判定依據
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaywallMarker {
+    /// `article:content_tier` 為 locked 或 metered
+    ContentTierMeta,
+    /// 結構化資料標示 `isAccessibleForFree: false`
+    NotAccessibleForFree,
+    /// 頁面含付費牆元件（Piano、regwall 等）
+    PaywallElement,
+    KnownDomain,
+    ShortBody,
+    SubscribePrompt,
+}
+
+impl PaywallMarker {
+    fn is_strong(self) -> bool {
+        matches!(self, Self::ContentTierMeta | Self::NotAccessibleForFree | Self::PaywallElement)
+    }
+}
+
+/// 依標記判定是否為付費牆
+pub fn is_paywalled(markers: &[PaywallMarker]) -> bool {
+    markers.iter().any(|m| m.is_strong()) || markers.len() >= 2
+}
+
+/// 找出頁面上的付費牆標記；`text` 為清理後的純文字
+pub fn detect(url: &str, html: &str, text: &str) -> Vec<PaywallMarker> {
+    let mut markers = Vec::new();
+    let document = Html::parse_document(html);
+
+    let mut content_tier = false;
+    let mut not_free = false;
+    if let Ok(selector) = Selector::parse("meta") {
+        for meta in document.select(&selector) {
+            let element = meta.value();
+            let key = element.attr("name").or(element.attr("property")).or(element.attr("itemprop"));
+            let content = element.attr("content").unwrap_or("").trim();
+            match key.map(str::to_ascii_lowercase).as_deref() {
+                Some("article:content_tier") => {
+                    content_tier |= content.eq_ignore_ascii_case("locked") || content.eq_ignore_ascii_case("metered")
+                }
+                Some("isaccessibleforfree") => not_free |= content.eq_ignore_ascii_case("false"),
+                _ => {}
+            }
+        }
+    }
+    if content_tier {
+        markers.push(PaywallMarker::ContentTierMeta);
+    }
+    if not_free || not_free_json_ld(html) {
+        markers.push(PaywallMarker::NotAccessibleForFree);
+    }
+    if let Ok(selector) = Selector::parse("[class], [id]") {
+        let has_element = document.select(&selector).any(|element| {
+            let element = element.value();
+            element
+                .classes()
+                .chain(element.id())
+                .any(|name| PAYWALL_ELEMENTS.iter().any(|p| name.to_ascii_lowercase().contains(p)))
+        });
+        if has_element {
+            markers.push(PaywallMarker::PaywallElement);
+        }
+    }
+
+    let host = Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.to_ascii_lowercase()));
+    if host.is_some_and(|host| {
+        PAYWALLED_DOMAINS
+            .iter()
+            .any(|d| host == *d || host.ends_with(&format!(".{}", d)))
+    }) {
+        markers.push(PaywallMarker::KnownDomain);
+    }
+    if text.chars().count() < SHORT_BODY_CHARS {
+        markers.push(PaywallMarker::ShortBody);
+    }
+    let lowered = text.to_lowercase();
+    if SUBSCRIBE_PROMPTS.iter().any(|p| lowered.contains(p)) {
+        markers.push(PaywallMarker::SubscribePrompt);
+    }
+    markers
+}
+
+/// JSON-LD 中的 `"isAccessibleForFree": false`（也接受字串 `"False"`）
+fn not_free_json_ld(html: &str) -> bool {
+    let lowered = html.to_ascii_lowercase();
+    lowered.match_indices("\"isaccessibleforfree\"").any(|(i, key)| {
+        let rest = lowered[i + key.len()..].trim_start();
+        rest.strip_prefix(':')
+            .map(|value| value.trim_start().trim_start_matches('"'))
+            .is_some_and(|value| value.starts_with("false"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn long_text() -> String {
+        "Full article paragraph. ".repeat(100)
+    }
+
+    #[test]
+    fn test_strong_markers() {
+        let html = r#"<html><head><meta property="article:content_tier" content="locked">
+            <script type="application/ld+json">{"@type":"NewsArticle","isAccessibleForFree": "False"}</script>
+            </head><body><div class="article-body paywall-overlay">Teaser</div></body></html>"#;
+        let markers = detect("https://example.com/a", html, &long_text());
+        assert_eq!(
+            markers,
+            vec![
+                PaywallMarker::ContentTierMeta,
+                PaywallMarker::NotAccessibleForFree,
+                PaywallMarker::PaywallElement,
+            ]
+        );
+        assert!(is_paywalled(&markers));
+    }
+
+    #[test]
+    fn test_weak_markers_need_two() {
+        let free = detect("https://blog.example.com/post", "<p>Short note.</p>", "Short note.");
+        assert_eq!(free, vec![PaywallMarker::ShortBody]);
+        assert!(!is_paywalled(&free));
+
+        // 付費網域的完整文章（例如贈閱連結）不算
+        assert!(!is_paywalled(&detect("https://www.nytimes.com/x", "<p></p>", &long_text())));
+
+        let teaser = detect(
+            "https://www.nytimes.com/2024/01/01/tech/a.html",
+            "<p>Teaser</p>",
+            "Teaser. Subscribe to continue reading.",
+        );
+        assert_eq!(
+            teaser,
+            vec![PaywallMarker::KnownDomain, PaywallMarker::ShortBody, PaywallMarker::SubscribePrompt]
+        );
+        assert!(is_paywalled(&teaser));
+    }
+}
@@ -0,0 +1,200 @@
+//! robots.txt - 解析規則、依主機快取，並依 Crawl-delay 排定同一主機的抓取間隔
+//!
+//! 支援 `*` 萬用字元與 `$` 結尾錨點（RFC 9309）。有針對本爬蟲的群組時只用該群組，否則用 `*`。
+//! 抓取 robots.txt 得到 4xx 視為全部允許，5xx 視為全部禁止（暫時性錯誤時不冒險抓取）。
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// robots.txt 中適用於本爬蟲的規則
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RobotsRules {
+    /// (是否允許, 路徑樣式)
+    rules: Vec<(bool, String)>,
+    crawl_delay: Option<Duration>,
+}
+
+impl RobotsRules {
+    /// 全部允許（robots.txt 不存在）
+    pub fn allow_all() -> Self {
+        Self::default()
+    }
+
+    /// 全部禁止（robots.txt 回應 5xx）
+    pub fn disallow_all() -> Self {
+        Self {
+            rules: vec![(false, "/".to_string())],
+            crawl_delay: None,
+        }
+    }
+
+    /// `user_agent` 取第一個 `/` 之前的產品名稱比對群組（不分大小寫）
+    pub fn parse(text: &str, user_agent: &str) -> Self {
+        let agent = user_agent.split('/').next().unwrap_or(user_agent).trim().to_ascii_lowercase();
+        let mut specific = Self::default();
+        let mut wildcard = Self::default();
+        let mut has_specific = false;
+        let mut agents: Vec<String> = Vec::new();
+        let mut in_rules = false;
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let Some((key, value)) = line.split_once(':') else { continue };
+            let (key, value) = (key.trim().to_ascii_lowercase(), value.trim());
+            if key == "user-agent" {
+                if in_rules {
+                    agents.clear();
+                    in_rules = false;
+                }
+                agents.push(value.to_ascii_lowercase());
+                continue;
+            }
+
+            let is_specific = agents.contains(&agent);
+            let is_wildcard = agents.iter().any(|a| a == "*");
+            let mut targets: Vec<&mut Self> = Vec::new();
+            if is_specific {
+                targets.push(&mut specific);
+            }
+            if is_wildcard {
+                targets.push(&mut wildcard);
+            }
+            match key.as_str() {
+                "allow" | "disallow" => {
+                    in_rules = true;
+                    has_specific |= is_specific;
+                    // `Disallow:` 空值代表全部允許
+                    if value.is_empty() {
+                        continue;
+                    }
+                    for target in targets {
+                        target.rules.push((key == "allow", value.to_string()));
+                    }
+                }
+                "crawl-delay" => {
+                    in_rules = true;
+                    has_specific |= is_specific;
+                    let Some(delay) = value.parse::<f64>().ok().filter(|d| d.is_finite() && *d >= 0.0) else {
+                        continue;
+                    };
+                    for target in targets {
+                        target.crawl_delay = Some(Duration::from_secs_f64(delay));
+                    }
+                }
+                _ => {}
+            }
+        }
+        if has_specific { specific } else { wildcard }
+    }
+
+    /// `path` 含查詢字串；最長的樣式勝出，長度相同時允許優先
+    pub fn allows(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .filter(|(_, pattern)| pattern_matches(pattern, path))
+            .max_by_key(|(allow, pattern)| (pattern.len(), *allow))
+            .is_none_or(|(allow, _)| *allow)
+    }
+
+    pub fn crawl_delay(&self) -> Option<Duration> {
+        self.crawl_delay
+    }
+}
+
+/// 比對 robots.txt 路徑樣式：`*` 比對任意字元，結尾的 `$` 要求路徑在此結束
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = path.strip_prefix(parts.next().unwrap_or("")) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return !anchored || rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    if anchored {
+        rest.ends_with(last)
+    } else {
+        rest.contains(last)
+    }
+}
+
+/// 依主機快取 robots.txt 規則，並記錄各主機下一次可抓取的時間
+#[derive(Debug, Default)]
+pub struct RobotsCache {
+    rules: Mutex<HashMap<String, Arc<RobotsRules>>>,
+    next_slot: Mutex<HashMap<String, Instant>>,
+}
+
+impl RobotsCache {
+    pub fn get(&self, host: &str) -> Option<Arc<RobotsRules>> {
+        self.rules.lock().unwrap().get(host).cloned()
+    }
+
+    pub fn insert(&self, host: &str, rules: RobotsRules) -> Arc<RobotsRules> {
+        let rules = Arc::new(rules);
+        self.rules.lock().unwrap().insert(host.to_string(), rules.clone());
+        rules
+    }
+
+    /// 預約主機的下一個抓取時段（與上一次相隔 `delay`），回傳需要等待的時間
+    pub fn reserve(&self, host: &str, delay: Duration) -> Duration {
+        let now = Instant::now();
+        let mut slots = self.next_slot.lock().unwrap();
+        let slot = slots.get(host).copied().filter(|slot| *slot > now).unwrap_or(now);
+        slots.insert(host.to_string(), slot + delay);
+        slot - now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_specific_group_wins() {
+        let text = "User-agent: *\nDisallow: /\n\nUser-agent: bose-search\nDisallow: /tmp\n";
+        let rules = RobotsRules::parse(text, "bose-search/0.1");
+        assert!(rules.allows("/docs"));
+        assert!(!rules.allows("/tmp/a"));
+        assert!(!RobotsRules::parse(text, "other-bot").allows("/docs"));
+        assert!(RobotsRules::parse("", "any").allows("/"));
+        assert!(!RobotsRules::disallow_all().allows("/anything"));
+    }
+
+    #[test]
+    fn test_wildcards_and_anchors() {
+        let text = "User-agent: *\nDisallow: /*.pdf$\nDisallow: /search*q=\nAllow: /search/about\nDisallow: /search\n";
+        let rules = RobotsRules::parse(text, "bose-search");
+        assert!(!rules.allows("/papers/a.pdf"));
+        assert!(rules.allows("/papers/a.pdf?download=1"));
+        assert!(!rules.allows("/search?q=rust"));
+        assert!(!rules.allows("/search/results"));
+        assert!(rules.allows("/search/about"));
+        assert!(rules.allows("/docs"));
+    }
+
+    #[test]
+    fn test_crawl_delay() {
+        let text = "User-agent: *\nCrawl-delay: 2.5\n\nUser-agent: BoseBot\nCrawl-delay: 1\nDisallow: /private\n";
+        assert_eq!(RobotsRules::parse(text, "other").crawl_delay(), Some(Duration::from_millis(2500)));
+        assert_eq!(RobotsRules::parse(text, "bosebot/1.0").crawl_delay(), Some(Duration::from_secs(1)));
+        assert_eq!(RobotsRules::parse("User-agent: *\nCrawl-delay: soon\n", "x").crawl_delay(), None);
+
+        let cache = RobotsCache::default();
+        let delay = Duration::from_secs(10);
+        assert_eq!(cache.reserve("a.com", delay), Duration::ZERO);
+        assert!(cache.reserve("a.com", delay) > Duration::from_secs(9));
+        assert_eq!(cache.reserve("b.com", delay), Duration::ZERO);
+    }
+}
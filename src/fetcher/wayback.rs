@@ -0,0 +1,73 @@
+//! Wayback Machine - 查詢網址最接近的封存快照，供付費牆與失效連結改用
+
+use crate::optimization::PooledClient;
+use crate::types::SearchError;
+
+/// Wayback Machine 可用性 API 的預設網址
+pub const DEFAULT_WAYBACK_API: &str = "https://archive.org/wayback/available";
+
+/// 封存快照
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    /// 含 Wayback 工具列的快照網址，適合呈現給使用者
+    pub url: String,
+    /// `YYYYMMDDhhmmss`
+    pub timestamp: String,
+}
+
+impl Snapshot {
+    /// 不含工具列的原始頁面（`/web/<時間>id_/`），適合抓取後擷取內容
+    pub fn raw_url(&self) -> String {
+        let marker = format!("/web/{}/", self.timestamp);
+        self.url.replacen(&marker, &format!("/web/{}id_/", self.timestamp), 1)
+    }
+}
+
+/// 解析可用性 API 的回應；沒有可用快照時為 None
+pub fn closest_snapshot(response: &serde_json::Value) -> Option<Snapshot> {
+    let closest = response.pointer("/archived_snapshots/closest")?;
+    if closest.get("available").and_then(|v| v.as_bool()) != Some(true) {
+        return None;
+    }
+    Some(Snapshot {
+        url: closest.get("url")?.as_str()?.to_string(),
+        timestamp: closest.get("timestamp")?.as_str()?.to_string(),
+    })
+}
+
+/// 向 `api` 查詢 `url` 最接近的快照
+pub async fn lookup(client: &PooledClient, api: &str, url: &str) -> Result<Option<Snapshot>, SearchError> {
+    let response = client
+        .get(&format!("{}?url={}", api, urlencoding::encode(url)))
+        .await
+        .map_err(|e| SearchError::NetworkError(e.to_string()))?;
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| SearchError::ParseError(e.to_string()))?;
+    Ok(closest_snapshot(&body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closest_snapshot() {
+        let response = serde_json::json!({
+            "archived_snapshots": { "closest": {
+                "available": true,
+                "status": "200",
+                "timestamp": "20240101000000",
+                "url": "http://web.archive.org/web/20240101000000/https://example.com/a",
+            }}
+        });
+        let snapshot = closest_snapshot(&response).unwrap();
+        assert_eq!(snapshot.url, "http://web.archive.org/web/20240101000000/https://example.com/a");
+        assert_eq!(
+            snapshot.raw_url(),
+            "http://web.archive.org/web/20240101000000id_/https://example.com/a"
+        );
+        assert_eq!(closest_snapshot(&serde_json::json!({ "archived_snapshots": {} })), None);
+    }
+}
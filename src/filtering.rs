@@ -0,0 +1,392 @@
+//! 網域過濾 - 全域的封鎖 / 允許清單，套用在每個引擎的結果上
+//!
+//! 清單可直接寫在設定，也可由檔案或網址載入（hosts 格式的廣告 / 惡意網域清單亦可）：
+//!
+//! ```toml
+//! [filtering]
+//! block = ["pinterest.*", "/^ad[sx]?\\./"]
+//! block_lists = ["/etc/bose/blocklist.txt", "https://example.com/hosts"]
+//! allow = ["docs.pinterest.com"]
+//! ```
+//!
+//! 項目語法：`example.com` 比對自身與所有子網域；含 `*` 時為萬用字元，比對整個主機名稱
+//! （`*.example.com` 只比對子網域）；`/…/` 包住的是正規表示式（不分大小寫）。
+//! `allow` 為例外，優先於 `block`；`allow_only` 開啟時只保留允許清單中的網域。
+//! 每次過濾的數量依查詢記在 `DomainFilter::stats()`。
+
+use crate::types::{SearchError, SearchResult};
+use regex::{Regex, RegexBuilder};
+use reqwest::Url;
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// `stats()` 保留的最近查詢數
+const RECENT_QUERIES: usize = 100;
+
+/// 單一網域項目
+#[derive(Debug, Clone)]
+pub enum DomainPattern {
+    /// 網域本身與所有子網域
+    Domain(String),
+    /// 含 `*` 的萬用字元，比對整個主機名稱
+    Wildcard(Regex),
+    /// 正規表示式，比對主機名稱
+    Regex(Regex),
+}
+
+impl DomainPattern {
+    pub fn parse(pattern: &str) -> Result<Self, SearchError> {
+        let pattern = pattern.trim();
+        let compile = |source: &str| {
+            RegexBuilder::new(source)
+                .case_insensitive(true)
+                .build()
+                .map_err(|e| SearchError::ConfigError(format!("無效的網域規則 `{}`: {}", pattern, e)))
+        };
+        if let Some(source) = pattern.strip_prefix('/').and_then(|p| p.strip_suffix('/')) {
+            return Ok(Self::Regex(compile(source)?));
+        }
+        let domain = pattern.to_lowercase();
+        if domain.contains('*') {
+            let source = domain.split('*').map(regex::escape).collect::<Vec<_>>().join(".*");
+            return Ok(Self::Wildcard(compile(&format!("^{}$", source))?));
+        }
+        let domain = domain.trim_start_matches("www.").trim_end_matches('.');
+        if domain.is_empty() {
+            return Err(SearchError::ConfigError(format!("無效的網域規則 `{}`", pattern)));
+        }
+        Ok(Self::Domain(domain.to_string()))
+    }
+
+    /// `host` 須為小寫主機名稱
+    pub fn matches(&self, host: &str) -> bool {
+        match self {
+            Self::Domain(domain) => {
+                host == domain
+                    || (host.len() > domain.len()
+                        && host.ends_with(domain.as_str())
+                        && host.as_bytes()[host.len() - domain.len() - 1] == b'.')
+            }
+            Self::Wildcard(regex) | Self::Regex(regex) => regex.is_match(host),
+        }
+    }
+}
+
+/// 一組網域項目
+#[derive(Debug, Clone, Default)]
+pub struct DomainList {
+    patterns: Vec<DomainPattern>,
+}
+
+impl DomainList {
+    pub fn new<'a>(patterns: impl IntoIterator<Item = &'a str>) -> Result<Self, SearchError> {
+        let mut list = Self::default();
+        for pattern in patterns {
+            list.patterns.push(DomainPattern::parse(pattern)?);
+        }
+        Ok(list)
+    }
+
+    /// 解析清單文字：每行一個項目，`#` 與 `!` 開頭為註解；
+    /// hosts 格式（`0.0.0.0 ads.example.com`）取主機名稱，`localhost` 等本機項目略過
+    pub fn parse(content: &str) -> Result<Self, SearchError> {
+        let mut list = Self::default();
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() || line.starts_with('!') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let first = fields.next().unwrap_or("");
+            let entries: Vec<&str> = if first.parse::<std::net::IpAddr>().is_ok() {
+                fields.filter(|host| !LOCAL_HOSTS.contains(host)).collect()
+            } else {
+                vec![line]
+            };
+            for entry in entries {
+                list.patterns.push(DomainPattern::parse(entry)?);
+            }
+        }
+        Ok(list)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, SearchError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| SearchError::ConfigError(format!("無法讀取網域清單 {}: {}", path.display(), e)))?;
+        Self::parse(&content)
+    }
+
+    pub async fn from_url(url: &str) -> Result<Self, SearchError> {
+        let response = reqwest::get(url)
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| SearchError::NetworkError(format!("無法下載網域清單 {}: {}", url, e)))?;
+        let content = response
+            .text()
+            .await
+            .map_err(|e| SearchError::NetworkError(format!("無法下載網域清單 {}: {}", url, e)))?;
+        Self::parse(&content)
+    }
+
+    /// 由網址（`http(s)://`）或檔案路徑載入
+    pub async fn load(source: &str) -> Result<Self, SearchError> {
+        if source.starts_with("http://") || source.starts_with("https://") {
+            Self::from_url(source).await
+        } else {
+            Self::from_file(Path::new(source))
+        }
+    }
+
+    pub fn extend(&mut self, other: DomainList) {
+        self.patterns.extend(other.patterns);
+    }
+
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    pub fn matches(&self, host: &str) -> bool {
+        self.patterns.iter().any(|p| p.matches(host))
+    }
+}
+
+/// hosts 檔中指向本機的項目
+const LOCAL_HOSTS: &[&str] = &[
+    "localhost",
+    "localhost.localdomain",
+    "local",
+    "broadcasthost",
+    "ip6-localhost",
+    "ip6-loopback",
+    "0.0.0.0",
+];
+
+/// 網域過濾設定
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FilterConfig {
+    pub block: Vec<String>,
+    /// 封鎖清單的檔案路徑或網址
+    pub block_lists: Vec<String>,
+    pub allow: Vec<String>,
+    /// 允許清單的檔案路徑或網址
+    pub allow_lists: Vec<String>,
+    /// 只保留允許清單中的網域
+    pub allow_only: bool,
+}
+
+impl FilterConfig {
+    /// 載入所有清單；任一清單無法讀取或規則無效時回傳錯誤
+    pub async fn build(&self) -> Result<DomainFilter, SearchError> {
+        let mut block = DomainList::new(self.block.iter().map(String::as_str))?;
+        for source in &self.block_lists {
+            block.extend(DomainList::load(source).await?);
+        }
+        let mut allow = DomainList::new(self.allow.iter().map(String::as_str))?;
+        for source in &self.allow_lists {
+            allow.extend(DomainList::load(source).await?);
+        }
+        Ok(DomainFilter::new(block, allow).with_allow_only(self.allow_only))
+    }
+}
+
+/// 單一查詢的過濾數量
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryFilterCount {
+    pub query: String,
+    pub checked: usize,
+    pub removed: usize,
+}
+
+/// 累計的過濾統計
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FilterStats {
+    pub checked: u64,
+    pub removed: u64,
+    /// 最近的查詢，舊的在前
+    pub recent: Vec<QueryFilterCount>,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    checked: u64,
+    removed: u64,
+    recent: VecDeque<QueryFilterCount>,
+}
+
+/// 封鎖 / 允許清單過濾器；可在多個客戶端間共用
+#[derive(Debug, Default)]
+pub struct DomainFilter {
+    block: DomainList,
+    allow: DomainList,
+    allow_only: bool,
+    counters: Mutex<Counters>,
+}
+
+impl DomainFilter {
+    pub fn new(block: DomainList, allow: DomainList) -> Self {
+        Self {
+            block,
+            allow,
+            ..Self::default()
+        }
+    }
+
+    /// 只保留允許清單中的網域
+    pub fn with_allow_only(mut self, allow_only: bool) -> Self {
+        self.allow_only = allow_only;
+        self
+    }
+
+    /// 沒有任何規則
+    pub fn is_empty(&self) -> bool {
+        self.block.is_empty() && self.allow.is_empty() && !self.allow_only
+    }
+
+    /// 網址是否保留；無法解析主機的網址只在 `allow_only` 時移除
+    pub fn allows(&self, url: &str) -> bool {
+        let Some(host) = Url::parse(url).ok().and_then(|u| u.host_str().map(str::to_lowercase)) else {
+            return !self.allow_only;
+        };
+        if self.allow.matches(&host) {
+            return true;
+        }
+        !self.allow_only && !self.block.matches(&host)
+    }
+
+    /// 移除被過濾的結果並記錄數量，回傳移除的筆數
+    pub fn apply(&self, query: &str, results: &mut Vec<SearchResult>) -> usize {
+        let checked = results.len();
+        results.retain(|r| self.allows(&r.url));
+        let removed = checked - results.len();
+
+        let mut counters = self.counters.lock().unwrap();
+        counters.checked += checked as u64;
+        counters.removed += removed as u64;
+        if counters.recent.len() == RECENT_QUERIES {
+            counters.recent.pop_front();
+        }
+        counters.recent.push_back(QueryFilterCount {
+            query: query.to_string(),
+            checked,
+            removed,
+        });
+        if removed > 0 {
+            log::info!("🚫 已過濾 {} 個結果（{}）", removed, query);
+        }
+        removed
+    }
+
+    pub fn stats(&self) -> FilterStats {
+        let counters = self.counters.lock().unwrap();
+        FilterStats {
+            checked: counters.checked,
+            removed: counters.removed,
+            recent: counters.recent.iter().cloned().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(url: &str) -> SearchResult {
+        SearchResult {
+            title: url.to_string(),
+            url: url.to_string(),
+            snippet: None,
+            content: None,
+            published_date: None,
+            metadata: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_patterns() {
+        let list = DomainList::new(["www.Example.com", "*.tracker.net", "pinterest.*", "/^ad[sx]?\\./"]).unwrap();
+        for host in ["example.com", "blog.example.com", "cdn.tracker.net", "pinterest.co.uk", "ads.foo.org"] {
+            assert!(list.matches(host), "{host}");
+        }
+        for host in ["notexample.com", "tracker.net", "mypinterest.com", "reads.foo.org"] {
+            assert!(!list.matches(host), "{host}");
+        }
+        assert!(DomainPattern::parse("/(/").is_err());
+    }
+
+    #[test]
+    fn test_parse_hosts_list() {
+        let list = DomainList::parse(
+            "# StevenBlack hosts\n127.0.0.1 localhost\n0.0.0.0 ads.example.com tracker.example.org # inline\n\n! adblock comment\nspam.test\n",
+        )
+        .unwrap();
+        assert_eq!(list.len(), 3);
+        assert!(list.matches("ads.example.com"));
+        assert!(list.matches("spam.test"));
+        assert!(!list.matches("localhost"));
+    }
+
+    #[test]
+    fn test_apply_and_stats() {
+        let filter = DomainFilter::new(
+            DomainList::new(["pinterest.com", "spam.test"]).unwrap(),
+            DomainList::new(["docs.pinterest.com"]).unwrap(),
+        );
+        let mut results = vec![
+            result("https://www.pinterest.com/pin/1"),
+            result("https://docs.pinterest.com/guide"),
+            result("https://rust-lang.org"),
+            result("http://spam.test/x"),
+        ];
+        assert_eq!(filter.apply("rust", &mut results), 2);
+        let urls: Vec<&str> = results.iter().map(|r| r.url.as_str()).collect();
+        assert_eq!(urls, vec!["https://docs.pinterest.com/guide", "https://rust-lang.org"]);
+
+        filter.apply("tokio", &mut vec![result("https://tokio.rs")]);
+        let stats = filter.stats();
+        assert_eq!((stats.checked, stats.removed), (5, 2));
+        assert_eq!(
+            stats.recent[0],
+            QueryFilterCount { query: "rust".into(), checked: 4, removed: 2 }
+        );
+        assert_eq!(stats.recent[1].removed, 0);
+    }
+
+    #[test]
+    fn test_allow_only() {
+        let filter = DomainFilter::new(DomainList::default(), DomainList::new(["*.gov", "nist.gov"]).unwrap())
+            .with_allow_only(true);
+        assert!(filter.allows("https://nvd.nist.gov/vuln"));
+        assert!(!filter.allows("https://example.com"));
+        assert!(!filter.allows("not a url"));
+        assert!(DomainFilter::default().allows("not a url"));
+    }
+
+    #[tokio::test]
+    async fn test_config_build() {
+        let path = std::env::temp_dir().join(format!("bose-blocklist-{}.txt", std::process::id()));
+        std::fs::write(&path, "0.0.0.0 malware.test\n").unwrap();
+        let config: FilterConfig = serde_json::from_value(serde_json::json!({
+            "block": ["spam.test"],
+            "block_lists": [path.to_string_lossy()],
+        }))
+        .unwrap();
+        let filter = config.build().await.unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(!filter.allows("https://malware.test/payload"));
+        assert!(!filter.allows("https://spam.test"));
+        assert!(filter.allows("https://rust-lang.org"));
+
+        let missing = FilterConfig {
+            block_lists: vec!["/nonexistent/blocklist.txt".into()],
+            ..FilterConfig::default()
+        };
+        assert!(matches!(missing.build().await, Err(SearchError::ConfigError(_))));
+    }
+}
@@ -6,14 +6,62 @@ pub mod client;
 pub mod routing;
 pub mod optimization;
 pub mod processing;
+pub mod fetcher;
+pub mod embeddings;
+pub mod vectorstore;
+pub mod llm;
+pub mod synthesis;
+pub mod research;
+pub mod report;
+pub mod storage;
+pub mod analytics;
+pub mod cost;
+pub mod telemetry;
+pub mod middleware;
+pub mod provider;
+pub mod filtering;
+pub mod spam;
+pub use bose_common::url;
 
-pub use types::{SearchEngine, SearchError, SearchResult};
+pub use types::{SearchEngine, SearchError, SearchQuery, SearchResult, TimeRange};
 pub use client::MultiSearchClient;
-pub use routing::{SemanticRouter, TaskComplexity, SearchStrategy};
+pub use routing::{SemanticRouter, TaskComplexity, SearchStrategy, QueryCategory};
+pub use routing::{Classification, ClassifierChain, QueryClassifier};
+pub use routing::{explain_relevance, RelevanceExplanation};
+pub use routing::{KeywordCondenser, PreparedQuery, QueryCondenser};
+pub use routing::{correct_query, SpellCorrection};
+pub use routing::{corroborate, engine_agreement, CorroboratedResult};
 pub use duckduckgo::DuckDuckGoClient;
-pub use exa::ExaClient;
-pub use tavily::TavilyClient;
-pub use optimization::{SearchCache, CachedSearchResult};
+pub use exa::{ExaClient, ExaSearchType};
+pub use tavily::{TavilyClient, TavilySearch, TavilyTopic};
+pub use optimization::{SearchCache, CachedSearchResult, CategoryTtls};
 pub use optimization::{PooledClient, PoolConfig};
 pub use optimization::{RateLimiter, RateLimiterConfig};
+pub use optimization::{IdleConfig, IdleReaper, IdleTracker};
+pub use optimization::{runtime_stats, RuntimeStats};
+pub use optimization::{with_backoff, RetryPolicy, Retryable};
+pub use optimization::ProxyConfig;
+pub use optimization::{KeyPool, KeyUsage, RotationStrategy};
+pub use middleware::{Middleware, MiddlewareStack};
+pub use provider::{ProviderFuture, SearchProvider, SimilarProvider};
+pub use filtering::{DomainFilter, DomainList, FilterConfig, FilterStats};
+pub use spam::{SpamAction, SpamConfig, SpamDetector, SpamVerdict};
 pub use processing::{HtmlCleaner, ContextPruner};
+pub use fetcher::{FetchedPage, Fetcher, FetcherConfig};
+pub use fetcher::dead_links::{DeadLinkAction, LinkCheckConfig, LinkChecker, LinkReport, LinkStatus};
+pub use fetcher::metadata::{EnrichConfig, Enricher, SiteMetadata};
+pub use fetcher::robots::RobotsRules;
+pub use embeddings::{cosine_similarity, Embedder, HashingEmbedder, OpenAiEmbedder};
+pub use vectorstore::{HnswConfig, HnswStore, HybridResult, QdrantStore, ResultOrigin, ScoredChunk, SemanticIndex, VectorStore};
+pub use llm::{Completion, CompletionRequest, LlmCondenser, Summarizer};
+pub use synthesis::{Citation, CitationStatus, CitationVerifier, SynthesizedAnswer, Synthesizer, VerificationReport};
+pub use research::{
+    sub_questions, Decomposer, DeepResearch, LlmDecomposer, ResearchConfig, ResearchReport, ResearchSection,
+    ResearchSource, RuleDecomposer,
+};
+pub use research::agent::{AgentConfig, AgentRun, AgentStop, GapFinder, KeywordGaps, LlmGaps, ResearchAgent};
+pub use research::decompose::{search_decomposed, DecomposedSearch, SubQuery};
+pub use storage::{Bookmark, EngineCall, QueryRecord, ResultStore, SavedSearch};
+pub use analytics::{QueryAnalytics, QueryStats};
+pub use cost::{Budget, BudgetPeriod, CostTracker, EngineSpend};
+pub use telemetry::{anonymize, Feedback, TelemetrySample, TelemetryStore};
@@ -0,0 +1,107 @@
+//! Anthropic Messages API
+
+use super::{Completion, CompletionFuture, CompletionRequest};
+use crate::types::SearchError;
+use reqwest::Client;
+use serde_json::{json, Value};
+
+const DEFAULT_BASE_URL: &str = "https://api.anthropic.com";
+const API_VERSION: &str = "2023-06-01";
+
+/// Anthropic 補全提供者
+pub struct AnthropicCompletion {
+    client: Client,
+    base_url: String,
+    api_key: String,
+}
+
+impl AnthropicCompletion {
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            client: Client::new(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            api_key: api_key.to_string(),
+        }
+    }
+
+    /// 改用代理或相容閘道
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+}
+
+impl Completion for AnthropicCompletion {
+    fn complete<'a>(&'a self, request: &'a CompletionRequest) -> CompletionFuture<'a> {
+        Box::pin(async move {
+            let mut body = json!({
+                "model": request.model,
+                "max_tokens": request.max_tokens,
+                "temperature": request.temperature,
+                "messages": [{ "role": "user", "content": request.prompt }],
+            });
+            if let Some(system) = &request.system {
+                body["system"] = json!(system);
+            }
+
+            let response = self
+                .client
+                .post(format!("{}/v1/messages", self.base_url))
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", API_VERSION)
+                .json(&body)
+                .send()
+                .await
+                .map_err(|e| SearchError::NetworkError(e.to_string()))?;
+            let status = response.status();
+            if !status.is_success() {
+                let body = response.text().await.unwrap_or_default();
+                return Err(SearchError::ApiError(format!("Anthropic API {}: {}", status, body)));
+            }
+            let body: Value = response
+                .json()
+                .await
+                .map_err(|e| SearchError::ParseError(e.to_string()))?;
+            let blocks = body["content"]
+                .as_array()
+                .ok_or_else(|| SearchError::ParseError("Anthropic 回應缺少 content".into()))?;
+            Ok(blocks
+                .iter()
+                .filter(|block| block["type"] == "text")
+                .filter_map(|block| block["text"].as_str())
+                .collect())
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "anthropic"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_messages_api() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .and(header("x-api-key", "key"))
+            .and(header("anthropic-version", "2023-06-01"))
+            .and(body_partial_json(json!({ "system": "Be brief" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "content": [{ "type": "text", "text": "Hel" }, { "type": "text", "text": "lo" }],
+                "stop_reason": "end_turn",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let completion = AnthropicCompletion::new("key").with_base_url(&server.uri());
+        let request = CompletionRequest::new("claude-haiku-4-5", "Hi").with_system("Be brief");
+        assert_eq!(completion.complete(&request).await.unwrap(), "Hello");
+    }
+}
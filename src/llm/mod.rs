@@ -0,0 +1,289 @@
+//! LLM 介接 - 可替換的 `Completion` 提供者與頁面摘要
+//!
+//! - `OpenAiCompletion`：OpenAI 相容的 `/chat/completions`（需 `openai` feature）
+//! - `AnthropicCompletion`：Anthropic Messages API（需 `anthropic` feature）
+//!
+//! 模型名稱由 `SemanticRouter::select_model` 依查詢複雜度決定。
+//! `LlmCondenser` 以同一個提供者濃縮過長的查詢，取代預設的關鍵字擷取。
+
+#[cfg(feature = "anthropic")]
+mod anthropic;
+#[cfg(feature = "openai")]
+mod openai;
+
+#[cfg(feature = "anthropic")]
+pub use anthropic::AnthropicCompletion;
+#[cfg(feature = "openai")]
+pub use openai::OpenAiCompletion;
+
+use crate::processing::{HeuristicTokenizer, Tokenizer};
+use crate::routing::{QueryCondenser, SemanticRouter, TaskComplexity};
+use crate::types::SearchError;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// `Completion::complete` 回傳的 future
+pub type CompletionFuture<'a> = Pin<Box<dyn Future<Output = Result<String, SearchError>> + Send + 'a>>;
+
+/// 一次補全請求
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompletionRequest {
+    pub model: String,
+    pub system: Option<String>,
+    pub prompt: String,
+    /// 輸出 token 上限
+    pub max_tokens: usize,
+    pub temperature: f32,
+}
+
+impl CompletionRequest {
+    pub fn new(model: &str, prompt: &str) -> Self {
+        Self {
+            model: model.to_string(),
+            system: None,
+            prompt: prompt.to_string(),
+            max_tokens: 1024,
+            temperature: 0.2,
+        }
+    }
+
+    pub fn with_system(mut self, system: &str) -> Self {
+        self.system = Some(system.to_string());
+        self
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = temperature;
+        self
+    }
+}
+
+/// LLM 補全提供者
+pub trait Completion: Send + Sync {
+    /// 回傳模型輸出的文字
+    fn complete<'a>(&'a self, request: &'a CompletionRequest) -> CompletionFuture<'a>;
+
+    /// 用於日誌的名稱
+    fn name(&self) -> &'static str;
+}
+
+/// 摘要輸入預設上限（token），超過時先截斷再送出
+pub const DEFAULT_SUMMARY_INPUT_TOKENS: usize = 8000;
+
+const SUMMARY_SYSTEM: &str = "You condense web pages for a research assistant. \
+Keep facts, figures, names and dates; drop navigation, ads and boilerplate. \
+Answer in the language of the page. Output only the summary.";
+
+/// 將抽取後的頁面濃縮到指定 token 數
+pub struct Summarizer {
+    completion: Arc<dyn Completion>,
+    model: String,
+    tokenizer: Arc<dyn Tokenizer>,
+    max_input_tokens: usize,
+}
+
+impl Summarizer {
+    pub fn new(completion: Arc<dyn Completion>, model: &str) -> Self {
+        Self {
+            completion,
+            model: model.to_string(),
+            tokenizer: Arc::new(HeuristicTokenizer),
+            max_input_tokens: DEFAULT_SUMMARY_INPUT_TOKENS,
+        }
+    }
+
+    /// 依查詢複雜度使用路由器選擇的模型
+    pub fn for_complexity(completion: Arc<dyn Completion>, router: &SemanticRouter, complexity: TaskComplexity) -> Self {
+        Self::new(completion, router.select_model(complexity))
+    }
+
+    /// 改用目標模型的 tokenizer 計算輸入與輸出長度
+    pub fn with_tokenizer(mut self, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    pub fn with_max_input_tokens(mut self, max_input_tokens: usize) -> Self {
+        self.max_input_tokens = max_input_tokens;
+        self
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// 摘要 `content` 到 `max_tokens` 以內；提供 `query` 時只保留與查詢相關的部分
+    ///
+    /// 內容本身已在上限內時直接回傳，不呼叫 LLM。
+    pub async fn summarize(&self, content: &str, query: Option<&str>, max_tokens: usize) -> Result<String, SearchError> {
+        let content = content.trim();
+        if self.tokenizer.count(content) <= max_tokens {
+            return Ok(content.to_string());
+        }
+
+        let input = self.tokenizer.truncate(content, self.max_input_tokens);
+        let mut prompt = format!("Summarize the following page in at most {} tokens.\n", max_tokens);
+        if let Some(query) = query {
+            prompt.push_str(&format!("Focus on what is relevant to: {}\n", query));
+        }
+        prompt.push_str("\n<page>\n");
+        prompt.push_str(input);
+        prompt.push_str("\n</page>");
+
+        let request = CompletionRequest::new(&self.model, &prompt)
+            .with_system(SUMMARY_SYSTEM)
+            .with_max_tokens(max_tokens);
+        let summary = self.completion.complete(&request).await?;
+        // 模型不一定遵守長度要求
+        Ok(self.tokenizer.truncate(summary.trim(), max_tokens).to_string())
+    }
+}
+
+const CONDENSE_SYSTEM: &str = "You rewrite long questions into short web search queries. \
+Keep names, versions and key terms; drop filler. Answer in the language of the question. \
+Output only the query, on one line.";
+
+/// 以 LLM 將過長的查詢改寫為精簡的搜尋查詢
+///
+/// 呼叫失敗、回傳空白或仍超過長度時回傳 None，由路由器改為截斷。
+pub struct LlmCondenser {
+    completion: Arc<dyn Completion>,
+    model: String,
+}
+
+impl LlmCondenser {
+    pub fn new(completion: Arc<dyn Completion>, model: &str) -> Self {
+        Self {
+            completion,
+            model: model.to_string(),
+        }
+    }
+
+    /// 濃縮屬於簡單任務，使用路由器為 `TaskComplexity::Simple` 選擇的模型
+    pub fn for_router(completion: Arc<dyn Completion>, router: &SemanticRouter) -> Self {
+        Self::new(completion, router.select_model(TaskComplexity::Simple))
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+}
+
+impl QueryCondenser for LlmCondenser {
+    fn condense<'a>(
+        &'a self,
+        query: &'a str,
+        max_chars: usize,
+    ) -> Pin<Box<dyn Future<Output = Option<String>> + Send + 'a>> {
+        Box::pin(async move {
+            let prompt = format!(
+                "Rewrite this as a web search query of at most {} characters:\n\n{}",
+                max_chars, query
+            );
+            let request = CompletionRequest::new(&self.model, &prompt)
+                .with_system(CONDENSE_SYSTEM)
+                .with_max_tokens(HeuristicTokenizer.count(query).min(max_chars).max(16))
+                .with_temperature(0.0);
+            let reply = match self.completion.complete(&request).await {
+                Ok(reply) => reply,
+                Err(e) => {
+                    log::warn!("⚠️ {} 濃縮查詢失敗: {}", self.completion.name(), e);
+                    return None;
+                }
+            };
+            let condensed = reply.lines().next().unwrap_or_default().trim().trim_matches('"').trim();
+            Some(condensed.to_string()).filter(|q| !q.is_empty() && q.chars().count() <= max_chars)
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "llm"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// 記錄請求並回傳固定文字；`reply` 為 Err 時模擬呼叫失敗
+    struct Echo {
+        reply: Result<String, String>,
+        requests: Mutex<Vec<CompletionRequest>>,
+    }
+
+    impl Completion for Echo {
+        fn complete<'a>(&'a self, request: &'a CompletionRequest) -> CompletionFuture<'a> {
+            self.requests.lock().unwrap().push(request.clone());
+            Box::pin(async move { self.reply.clone().map_err(SearchError::ApiError) })
+        }
+
+        fn name(&self) -> &'static str {
+            "echo"
+        }
+    }
+
+    fn echo(reply: &str) -> Arc<Echo> {
+        Arc::new(Echo {
+            reply: Ok(reply.to_string()),
+            requests: Mutex::new(Vec::new()),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_summarize_uses_router_model() {
+        let completion = echo("  Tokio schedules tasks on a work-stealing pool.  ");
+        let router = SemanticRouter::with_defaults();
+        let summarizer = Summarizer::for_complexity(completion.clone(), &router, TaskComplexity::Simple)
+            .with_max_input_tokens(50);
+        let page = "Tokio is a runtime. ".repeat(100);
+
+        let summary = summarizer.summarize(&page, Some("tokio scheduler"), 20).await.unwrap();
+        assert_eq!(summary, "Tokio schedules tasks on a work-stealing pool.");
+
+        let requests = completion.requests.lock().unwrap();
+        assert_eq!(requests[0].model, "claude-haiku-4-5");
+        assert_eq!(requests[0].max_tokens, 20);
+        assert!(requests[0].prompt.contains("tokio scheduler"));
+        // 輸入已截斷到 50 token
+        assert!(requests[0].prompt.len() < page.len() / 4);
+    }
+
+    #[tokio::test]
+    async fn test_summarize_short_content_and_long_reply() {
+        let completion = echo(&"word ".repeat(100));
+        let summarizer = Summarizer::new(completion.clone(), "m");
+        assert_eq!(summarizer.summarize(" short page ", None, 100).await.unwrap(), "short page");
+        assert!(completion.requests.lock().unwrap().is_empty());
+
+        let summary = summarizer.summarize(&"long page ".repeat(50), None, 10).await.unwrap();
+        assert!(HeuristicTokenizer.count(&summary) <= 10);
+    }
+
+    #[tokio::test]
+    async fn test_llm_condenser() {
+        let completion = echo("\"tokio work stealing scheduler\"\nExplanation: ...");
+        let condenser = LlmCondenser::for_router(completion.clone(), &SemanticRouter::with_defaults());
+        let query = "how does the tokio runtime schedule tasks across worker threads when one is idle";
+        assert_eq!(condenser.condense(query, 40).await.as_deref(), Some("tokio work stealing scheduler"));
+        assert_eq!(condenser.name(), "llm");
+
+        let request = completion.requests.lock().unwrap()[0].clone();
+        assert_eq!(request.model, "claude-haiku-4-5");
+        assert!(request.prompt.contains(query));
+
+        // 超過長度或呼叫失敗時交回路由器截斷
+        assert_eq!(condenser.condense(query, 10).await, None);
+        let failing = Arc::new(Echo {
+            reply: Err("503".into()),
+            requests: Mutex::new(Vec::new()),
+        });
+        assert_eq!(LlmCondenser::new(failing, "m").condense(query, 40).await, None);
+    }
+}
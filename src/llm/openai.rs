@@ -0,0 +1,100 @@
+//! OpenAI 相容的 `/chat/completions`（OpenAI、OpenRouter、Ollama、vLLM ...）
+
+use super::{Completion, CompletionFuture, CompletionRequest};
+use crate::types::SearchError;
+use reqwest::Client;
+use serde_json::{json, Value};
+
+/// OpenAI 相容的補全提供者
+pub struct OpenAiCompletion {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl OpenAiCompletion {
+    /// `base_url` 例如 `https://api.openai.com/v1` 或 `http://localhost:11434/v1`
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: None,
+        }
+    }
+
+    pub fn with_api_key(mut self, api_key: &str) -> Self {
+        self.api_key = Some(api_key.to_string());
+        self
+    }
+}
+
+impl Completion for OpenAiCompletion {
+    fn complete<'a>(&'a self, request: &'a CompletionRequest) -> CompletionFuture<'a> {
+        Box::pin(async move {
+            let mut messages = Vec::new();
+            if let Some(system) = &request.system {
+                messages.push(json!({ "role": "system", "content": system }));
+            }
+            messages.push(json!({ "role": "user", "content": request.prompt }));
+
+            let mut http = self.client.post(format!("{}/chat/completions", self.base_url)).json(&json!({
+                "model": request.model,
+                "messages": messages,
+                "max_tokens": request.max_tokens,
+                "temperature": request.temperature,
+            }));
+            if let Some(key) = &self.api_key {
+                http = http.bearer_auth(key);
+            }
+            let response = http
+                .send()
+                .await
+                .map_err(|e| SearchError::NetworkError(e.to_string()))?;
+            let status = response.status();
+            if !status.is_success() {
+                let body = response.text().await.unwrap_or_default();
+                return Err(SearchError::ApiError(format!("Completion API {}: {}", status, body)));
+            }
+            let body: Value = response
+                .json()
+                .await
+                .map_err(|e| SearchError::ParseError(e.to_string()))?;
+            body["choices"][0]["message"]["content"]
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| SearchError::ParseError("Completion 回應缺少 choices[0].message.content".into()))
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_chat_completion() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(header("authorization", "Bearer sk-test"))
+            .and(body_partial_json(json!({
+                "messages": [{ "role": "system", "content": "Be brief" }, { "role": "user", "content": "Hi" }],
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{ "message": { "role": "assistant", "content": "Hello" } }],
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let completion = OpenAiCompletion::new(&format!("{}/v1", server.uri())).with_api_key("sk-test");
+        let request = CompletionRequest::new("gpt-4o-mini", "Hi").with_system("Be brief");
+        assert_eq!(completion.complete(&request).await.unwrap(), "Hello");
+    }
+}
@@ -1,22 +1,36 @@
-mod types;
-mod duckduckgo;
-mod exa;
-mod client;
+use bose_search::{
+    explain_relevance, fetcher::metadata, report, routing::ConfidenceCalculator, routing::RouterConfig, routing::TieredRetrieval, CategoryTtls,
+    CostTracker, DeadLinkAction, DeepResearch, DomainFilter, EnrichConfig, Enricher, Feedback, Fetcher, FetcherConfig, FilterConfig,
+    KeyPool, LinkCheckConfig, LinkChecker, MultiSearchClient, PoolConfig, PooledClient, QueryAnalytics, QueryCondenser,
+    RotationStrategy, ResultStore, SearchCache, SearchEngine, SearchResult, SemanticRouter, SpamAction, SpamConfig,
+    SpamDetector, Synthesizer, TelemetrySample, TelemetryStore,
+};
+#[cfg(feature = "openai")]
+use bose_search::{llm::OpenAiCompletion, LlmCondenser};
 
-pub use types::{SearchEngine, SearchError, SearchResult};
-pub use client::MultiSearchClient;
-
-use clap::{Parser, ValueEnum};
+use bose_common::event_log::{EventLog, SearchEvent};
+use bose_common::BoseConfig;
+use clap::{Parser, Subcommand, ValueEnum};
 use dotenv::dotenv;
 use std::env;
+use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(name = "bose-search")]
 #[command(about = "Bose 安全研究 - 多引擎搜尋工具", long_about = None)]
+#[command(subcommand_negates_reqs = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// 搜尋查詢
-    #[arg(short, long)]
-    query: String,
+    #[arg(short, long, required_unless_present_any = ["feedback", "export_telemetry", "export_dataset", "saved"])]
+    query: Option<String>,
+
+    /// 執行以 `save` 保存的搜尋（需搭配 --history）
+    #[arg(long, value_name = "NAME", requires = "history", conflicts_with = "query")]
+    saved: Option<String>,
 
     /// 搜尋引擎選擇
     #[arg(short, long, value_enum, default_value = "duckduckgo")]
@@ -25,6 +39,164 @@ struct Cli {
     /// 結果數量
     #[arg(short, long, default_value = "10")]
     num: usize,
+
+    /// 解釋第 N 個結果為何被視為相關
+    #[arg(long, value_name = "N")]
+    why: Option<usize>,
+
+    /// 由結果合成附編號引用的答案（抽取式，不需 LLM）
+    #[arg(long)]
+    answer: bool,
+
+    /// 依逐筆置信度重新排序結果
+    #[arg(long)]
+    rerank: bool,
+
+    /// 將結果匯出為文獻引用（`.json` 為 CSL-JSON，其他副檔名為 BibTeX）
+    #[arg(long, value_name = "FILE")]
+    cite: Option<std::path::PathBuf>,
+
+    /// 深度研究：拆成子問題逐題檢索並抓取頁面，輸出附來源的報告（有 EXA_API_KEY / TAVILY_API_KEY 時逐層升級）
+    #[arg(long)]
+    research: bool,
+
+    /// 付費引擎每日（UTC）花費上限，單位美元；用盡後不再升級到 Exa / Tavily（覆寫 BUDGET_DAILY_USD）
+    #[arg(long, value_name = "USD")]
+    daily_budget: Option<f64>,
+
+    /// 付費引擎每月（UTC）花費上限，單位美元（覆寫 BUDGET_MONTHLY_USD）
+    #[arg(long, value_name = "USD")]
+    monthly_budget: Option<f64>,
+
+    /// 花費帳本（SQLite），讓預算跨執行累計（覆寫 COST_LEDGER_PATH）
+    #[arg(long, value_name = "DB")]
+    cost_ledger: Option<std::path::PathBuf>,
+
+    /// 將深度研究報告以 Markdown 寫入檔案（隱含 --research）
+    #[arg(long, value_name = "FILE")]
+    report: Option<std::path::PathBuf>,
+
+    /// 顯示路由決策（複雜度、策略、過長查詢的濃縮結果）
+    #[arg(long)]
+    explain: bool,
+
+    /// 將查詢與結果記錄到 SQLite 資料庫（保存的搜尋與書籤也存在這裡）
+    #[arg(long, value_name = "DB", global = true)]
+    history: Option<std::path::PathBuf>,
+
+    /// 將查詢歷史以評估資料集格式（JSON Lines）匯出到 stdout（需搭配 --history）
+    #[arg(long, requires = "history")]
+    export_dataset: bool,
+
+    /// 以磁碟快取保存結果，TTL 內重複查詢不再連線（新聞 10 分鐘、文件 3 天、其他 1 小時）
+    #[arg(long, value_name = "DIR")]
+    cache_dir: Option<std::path::PathBuf>,
+
+    /// 零結果與暫時性錯誤的快取秒數（需搭配 --cache-dir，0 表示不快取；錯誤只在本次執行內快取，不寫入磁碟）
+    #[arg(long, value_name = "SECS", default_value_t = 60)]
+    negative_ttl: u64,
+
+    /// 經由代理送出請求，例如 Tor 的 `socks5h://127.0.0.1:9050`（覆寫 SEARCH_PROXY；ENGINE_PROXIES 可依引擎設定）
+    #[arg(long, value_name = "URL")]
+    proxy: Option<String>,
+
+    /// 過濾掉此網域的結果，可重複（`example.com` 含子網域、`*.example.com`、`/regex/`）
+    #[arg(long, value_name = "PATTERN")]
+    block: Vec<String>,
+
+    /// 由檔案或網址載入封鎖清單（每行一個網域，支援 hosts 格式），可重複
+    #[arg(long, value_name = "FILE|URL")]
+    blocklist: Vec<String>,
+
+    /// 即使符合封鎖清單也保留此網域的結果，可重複
+    #[arg(long, value_name = "PATTERN")]
+    allow: Vec<String>,
+
+    /// 只保留 --allow 中的網域
+    #[arg(long, requires = "allow")]
+    allow_only: bool,
+
+    /// 偵測疑似內容農場 / SEO 垃圾的結果：標記、移到最後或移除
+    #[arg(long, value_enum, value_name = "ACTION")]
+    spam: Option<SpamChoice>,
+
+    /// 以 HEAD 檢查前 N 個結果的連結，標記 404 / 410 / 逾時的失效連結
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "10")]
+    check_links: Option<usize>,
+
+    /// 移除失效連結（需搭配 --check-links）
+    #[arg(long, requires = "check_links")]
+    drop_dead: bool,
+
+    /// 失效連結改用 Wayback Machine 的快照（需搭配 --check-links）
+    #[arg(long, requires = "check_links")]
+    wayback: bool,
+
+    /// 抓取頁面時（--research、--enrich）不理會 robots.txt 與 Crawl-delay
+    #[arg(long)]
+    ignore_robots: bool,
+
+    /// 抓取頁面時送出的 User-Agent（也用來比對 robots.txt 的群組）
+    #[arg(long, value_name = "UA")]
+    user_agent: Option<String>,
+
+    /// 抓取前 N 個結果的頁面，補充網站名稱、favicon 與 OpenGraph 描述
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "5")]
+    enrich: Option<usize>,
+
+    /// 自願開啟：將匿名化的查詢、路由決策與置信度記錄到本機資料集（不會上傳）
+    #[arg(long, value_name = "DB")]
+    telemetry: Option<std::path::PathBuf>,
+
+    /// 對先前記錄的樣本給予回饋，例如 `--feedback 12:good`（需搭配 --telemetry）
+    #[arg(long, value_name = "ID:good|bad", requires = "telemetry")]
+    feedback: Option<String>,
+
+    /// 將遙測資料集以 JSON Lines 匯出到 stdout（需搭配 --telemetry）
+    #[arg(long, requires = "telemetry")]
+    export_telemetry: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 以名稱保存搜尋（使用目前的 --engine 與 --num），之後以 --saved NAME 執行
+    Save { name: String, query: String },
+    /// 列出保存的搜尋
+    Saved,
+    /// 查詢歷史統計：熱門查詢、引擎延遲百分位、升級率與快取命中率
+    Stats {
+        /// 統計最近幾天
+        #[arg(long, default_value_t = 30)]
+        days: u64,
+        /// 熱門查詢的數量
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+    },
+    /// 管理結果書籤
+    Bookmarks {
+        #[command(subcommand)]
+        action: BookmarkAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum BookmarkAction {
+    /// 列出書籤，新的在前
+    List,
+    /// 加入書籤；網址出現在搜尋歷史中時自動帶入標題與摘要
+    Add {
+        url: String,
+        #[arg(long)]
+        title: Option<String>,
+        /// 筆記
+        #[arg(long)]
+        note: Option<String>,
+        /// 找到此結果的查詢
+        #[arg(long)]
+        query: Option<String>,
+    },
+    /// 刪除書籤
+    Remove { id: i64 },
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
@@ -35,6 +207,26 @@ enum EngineChoice {
     Exa,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum SpamChoice {
+    /// 只標記
+    Flag,
+    /// 移到所有正常結果之後
+    Downrank,
+    /// 直接移除
+    Remove,
+}
+
+impl From<SpamChoice> for SpamAction {
+    fn from(choice: SpamChoice) -> Self {
+        match choice {
+            SpamChoice::Flag => SpamAction::Flag,
+            SpamChoice::Downrank => SpamAction::Downrank,
+            SpamChoice::Remove => SpamAction::Remove,
+        }
+    }
+}
+
 impl From<EngineChoice> for SearchEngine {
     fn from(choice: EngineChoice) -> Self {
         match choice {
@@ -49,35 +241,375 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // 載入 .env 檔案
     dotenv().ok();
 
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
+
+    if let Some(command) = &cli.command {
+        let path = cli.history.as_ref().ok_or("需要以 --history DB 指定資料庫")?;
+        return run_command(command, &ResultStore::open(path)?, &cli);
+    }
+
+    if let Some(path) = &cli.telemetry
+        && (cli.feedback.is_some() || cli.export_telemetry)
+    {
+        let store = TelemetryStore::open(path)?;
+        if let Some(spec) = &cli.feedback {
+            let (id, feedback) = spec
+                .split_once(':')
+                .ok_or_else(|| format!("無效的回饋 `{}`（格式為 ID:good|bad）", spec))?;
+            let id: i64 = id.trim().parse().map_err(|_| format!("無效的樣本 ID `{}`", id))?;
+            let feedback: Feedback = feedback.parse()?;
+            if store.set_feedback(id, feedback)? {
+                eprintln!("👍 已記錄樣本 #{} 的回饋: {}", id, feedback.as_str());
+            } else {
+                eprintln!("⚠️  找不到樣本 #{}", id);
+            }
+        }
+        if cli.export_telemetry {
+            let count = store.export_jsonl(&mut std::io::stdout().lock())?;
+            eprintln!("📦 已匯出 {} 筆樣本", count);
+        }
+        return Ok(());
+    }
+
+    if let Some(path) = cli.history.as_ref().filter(|_| cli.export_dataset) {
+        let records: Vec<report::dataset::DatasetRecord> =
+            ResultStore::open(path)?.all()?.iter().map(Into::into).collect();
+        let count = report::dataset::write_jsonl(&mut std::io::stdout().lock(), &records)?;
+        eprintln!("📦 已匯出 {} 筆查詢", count);
+        return Ok(());
+    }
+
+    if let (Some(name), Some(path)) = (cli.saved.clone(), &cli.history) {
+        let saved = ResultStore::open(path)?
+            .saved_search(&name)?
+            .ok_or_else(|| format!("找不到保存的搜尋 `{}`", name))?;
+        if let Some(engine) = saved.params["engine"].as_str().and_then(|e| EngineChoice::from_str(e, true).ok()) {
+            cli.engine = engine;
+        }
+        if let Some(num) = saved.params["num"].as_u64() {
+            cli.num = num as usize;
+        }
+        cli.query = Some(saved.query);
+    }
+
+    let Some(query) = cli.query.as_deref() else {
+        return Ok(());
+    };
+
+    let filter = FilterConfig {
+        block: cli.block.clone(),
+        block_lists: cli.blocklist.clone(),
+        allow: cli.allow.clone(),
+        allow_lists: Vec::new(),
+        allow_only: cli.allow_only,
+    }
+    .build()
+    .await?;
+    let filter = (!filter.is_empty()).then(|| Arc::new(filter));
+    let spam = cli.spam.map(|choice| {
+        Arc::new(SpamDetector::new(SpamConfig {
+            action: choice.into(),
+            ..SpamConfig::default()
+        }))
+    });
+
+    // 金鑰與其他設定：`BOSE_CONFIG` 設定檔 → 環境變數（含 `*_FILE` 與金鑰圈）
+    let mut config = BoseConfig::from_env_or_file()?;
+    if cli.daily_budget.is_some() {
+        config.budget.daily_usd = cli.daily_budget;
+    }
+    if cli.monthly_budget.is_some() {
+        config.budget.monthly_usd = cli.monthly_budget;
+    }
+    if cli.cost_ledger.is_some() {
+        config.budget.ledger_path = cli.cost_ledger.clone();
+    }
+
+    if cli.research || cli.report.is_some() {
+        return research(query, &config, cli.proxy.clone(), cli.report.as_deref(), filter, spam, fetcher_config(&cli))
+            .await;
+    }
+
+    let engine: SearchEngine = cli.engine.into();
+    if !config.engine_enabled(engine.name()) {
+        return Err(format!("{} 已在設定中停用（engines.{}.enabled = false）", engine.name(), engine.name()).into());
+    }
 
     // 建立搜尋客戶端
-    let mut client = MultiSearchClient::new();
+    let mut client = MultiSearchClient::new().with_router(SemanticRouter::new(RouterConfig::from(&config.router)));
+    for engine in [SearchEngine::DuckDuckGo, SearchEngine::Exa] {
+        if let Some(rps) = config.engine_rps(engine.name()) {
+            client = client.with_rate_limit(engine, rps);
+        }
+    }
+    if let Some(filter) = filter {
+        client = client.with_filter(filter);
+    }
+    if let Some(spam) = spam {
+        client = client.with_spam_detector(spam);
+    }
+
+    let mut proxies = config.proxy.clone();
+    if let Some(url) = &cli.proxy {
+        proxies.default = Some(url.clone());
+    }
+    if !proxies.is_empty() {
+        client = client.with_proxies(proxies)?;
+    }
+
+    if let Some(condenser) = llm_condenser(client.router())? {
+        client = client.with_condenser(condenser);
+    }
+
+    // 如果有 Exa API 金鑰，則設定（逗號分隔多把金鑰時依 API_KEY_ROTATION 輪替）
+    if let Some(exa_key) = config.exa_api_key.as_ref().filter(|_| config.engine_enabled("exa")) {
+        client = client.with_exa_keys(Arc::new(KeyPool::parse(exa_key.expose(), rotation_strategy()?)));
+    }
 
-    // 如果有 Exa API 金鑰，則設定
-    if let Ok(exa_key) = env::var("EXA_API_KEY") {
-        client = client.with_exa(&exa_key);
+    if let Some(dir) = &cli.cache_dir {
+        client = client
+            .with_cache(Arc::new(
+                SearchCache::with_disk(1000, 3600, dir)?.with_category_ttls(CategoryTtls::default()),
+            ))
+            .with_negative_ttl(Duration::from_secs(cli.negative_ttl));
+    }
+
+    // 有 REDIS_URL 時與 bose-mcp / bose-http 及其他 CLI 共用結果快取
+    #[cfg(feature = "redis")]
+    if let Some(url) = env::var("REDIS_URL").ok().filter(|url| !url.is_empty()) {
+        // 結果依查詢類別的 TTL 寫入，這裡的 TTL 只用於 SearXNG 回應快取
+        let backend = bose_common::redis_cache::RedisBackend::connect(&url, "bose", 3600).await?;
+        client = client.with_shared_cache(Arc::new(backend));
     }
 
     // 執行搜尋
-    println!("🔎 搜尋: \"{}\"", cli.query);
+    println!("🔎 搜尋: \"{}\"", query);
     println!("📊 引擎: {:?}", cli.engine);
     println!("📈 結果數: {}\n", cli.num);
 
-    match client.search(&cli.query, cli.engine.into(), cli.num).await {
-        Ok(results) => {
+    if cli.explain {
+        // 濃縮結果會保留在客戶端，搜尋時不會再呼叫濃縮器
+        let prepared = client.prepare_query(query, engine).await;
+        let router = client.router();
+        let complexity = router.classify(query);
+        println!("🧭 路由決策:");
+        println!("   • 複雜度: {:?}", complexity);
+        println!("   • 策略: {:?}", router.select_search_strategy(complexity));
+        if let Some(rule) = router.match_rule(query) {
+            println!("   • 路由規則: {}", rule.name);
+        }
+        let category = router.categorize(query);
+        println!(
+            "   • 類別: {:?}（快取 {} 秒）",
+            category,
+            client
+                .cache()
+                .map_or(CategoryTtls::default(), |cache| cache.category_ttls())
+                .ttl(category)
+                .as_secs()
+        );
+        match prepared.condenser {
+            Some(method) => println!(
+                "   • 查詢過長 ({} 字元)，以 {} 濃縮為: {}",
+                prepared.original.chars().count(),
+                method,
+                prepared.sent
+            ),
+            None => println!("   • 查詢未濃縮"),
+        }
+        println!();
+    }
+
+    let cache_hits = client.cache().map(|cache| cache.stats().hits);
+    let filtered_before = client.filter().map_or(0, |filter| filter.stats().removed);
+    let started = std::time::Instant::now();
+    let outcome = client.search_with_spellcheck(query, engine, cli.num).await;
+    let latency = started.elapsed();
+    let cache_hit = cache_hits.zip(client.cache()).map(|(before, cache)| cache.stats().hits > before);
+    let filtered = client.filter().map_or(0, |filter| filter.stats().removed) - filtered_before;
+
+    if let Some(events) = EventLog::from_config(&config)?.map(Arc::new) {
+        let event = SearchEvent::new(engine.name(), query, latency);
+        let event = match &outcome {
+            Ok((results, _)) => {
+                let cached = cache_hit.unwrap_or(false);
+                event
+                    .with_results(engine.name(), results.len(), cached)
+                    .with_confidence(ConfidenceCalculator::new().calculate(query, results) as f64)
+                    .with_cost(if cached { 0.0 } else { bose_search::cost::estimate(engine) as f64 })
+            }
+            Err(e) => event.with_error(e),
+        };
+        if let Err(e) = events.append(event).await {
+            eprintln!("⚠️  無法寫入搜尋事件: {}", e);
+        }
+    }
+
+    match outcome {
+        Ok((mut results, correction)) => {
+            if let Some(correction) = &correction {
+                let kinds: Vec<String> = correction.kinds.iter().map(ToString::to_string).collect();
+                println!(
+                    "🔤 原查詢沒有結果，改用修正後的查詢: \"{}\" ({})\n",
+                    correction.corrected,
+                    kinds.join(", ")
+                );
+            }
+
+            if filtered > 0 {
+                println!("🚫 已依網域清單過濾 {} 個結果\n", filtered);
+            }
+
+            let mut dead_links = Vec::new();
+            if let Some(top_n) = cli.check_links {
+                let pool = PooledClient::new(PoolConfig {
+                    proxy: cli.proxy.clone(),
+                    ..PoolConfig::default()
+                })?;
+                let checker = LinkChecker::new(
+                    Arc::new(pool),
+                    LinkCheckConfig {
+                        top_n,
+                        action: if cli.drop_dead { DeadLinkAction::Drop } else { DeadLinkAction::Mark },
+                        wayback_fallback: cli.wayback,
+                        ..LinkCheckConfig::default()
+                    },
+                );
+                let reports = checker.verify(&mut results).await;
+                let dead: Vec<_> = reports.iter().filter(|r| r.status.is_dead()).collect();
+                let archived = dead.iter().filter(|r| r.snapshot.is_some()).count();
+                print!("🔗 已檢查前 {} 個連結：{} 個失效", reports.len(), dead.len());
+                if archived > 0 {
+                    print!("（{} 個改用封存快照）", archived);
+                }
+                println!("\n");
+                dead_links = dead
+                    .into_iter()
+                    .filter(|r| r.snapshot.is_none())
+                    .map(|r| (r.url.clone(), r.status.clone()))
+                    .collect();
+            }
+
+            if let Some(top_n) = cli.enrich {
+                let pool = PooledClient::new(PoolConfig {
+                    proxy: cli.proxy.clone(),
+                    ..PoolConfig::default()
+                })?;
+                let enricher = Enricher::new(
+                    Fetcher::new(Arc::new(pool), fetcher_config(&cli)),
+                    EnrichConfig {
+                        top_n,
+                        ..EnrichConfig::default()
+                    },
+                );
+                let enriched = enricher.enrich(&mut results).await;
+                println!("🏷️  已補充 {} 個結果的網站資訊\n", enriched);
+            }
+
+            if let Some(path) = &cli.history {
+                let engine = format!("{:?}", cli.engine).to_lowercase();
+                let mut params = serde_json::json!({
+                    "engine": engine,
+                    "num": cli.num,
+                });
+                let prepared = client.prepare_query(query, cli.engine.into()).await;
+                if prepared.is_condensed() {
+                    params["sent_query"] = prepared.sent.into();
+                }
+                if let Some(correction) = &correction {
+                    params["corrected_query"] = correction.corrected.clone().into();
+                }
+                let recorded = ResultStore::open(path).and_then(|store| {
+                    let id = store.record(query, &params, None, None, &results)?;
+                    store.record_call(id, &engine, latency, cache_hit)
+                });
+                if let Err(e) = recorded {
+                    eprintln!("⚠️  無法記錄查詢歷史: {}", e);
+                }
+            }
+
+            if let Some(path) = &cli.telemetry {
+                let router = client.router();
+                let confidence = ConfidenceCalculator::new().calculate(query, &results);
+                let sample = TelemetrySample::from_router(router, query, results.len())
+                    .with_outcome(None, Some(confidence));
+                match TelemetryStore::open(path).and_then(|store| store.record(&sample)) {
+                    Ok(id) => eprintln!("📊 已記錄遙測樣本 #{}（以 --feedback {}:good|bad 回饋）", id, id),
+                    Err(e) => eprintln!("⚠️  無法記錄遙測: {}", e),
+                }
+            }
+
             if results.is_empty() {
                 println!("❌ 沒有找到結果");
             } else {
+                let calc = ConfidenceCalculator::new();
+                let scores = if cli.rerank {
+                    let (reranked, scores) = calc.rerank(query, results).into_iter().unzip();
+                    results = reranked;
+                    scores
+                } else {
+                    calc.score_each(query, &results)
+                };
+
                 println!("✅ 找到 {} 個結果:\n", results.len());
-                for (i, result) in results.iter().enumerate() {
+                for (i, (result, score)) in results.iter().zip(&scores).enumerate() {
                     println!("{}. {}", i + 1, result.title);
                     println!("   🔗 {}", result.url);
-                    if let Some(snippet) = &result.snippet {
+                    println!("   📈 置信度: {:.2}", score);
+                    if let Some((_, status)) = dead_links.iter().find(|(url, _)| *url == result.url) {
+                        println!("   💀 失效連結（{}）", status);
+                    }
+                    let verdict = client.spam_detector().map(|spam| spam.assess(result));
+                    if let Some(verdict) = verdict.filter(|v| v.is_spam) {
+                        let signals: Vec<String> = verdict.signals.iter().map(ToString::to_string).collect();
+                        println!("   🧹 疑似內容農場 ({:.2}): {}", verdict.score, signals.join("、"));
+                    }
+                    if let Some(site) = result.metadata.get(metadata::SITE_NAME) {
+                        println!("   🏷️  {}", site);
+                    }
+                    let description = result.metadata.get(metadata::OG_DESCRIPTION);
+                    if let Some(snippet) = result.snippet.as_ref().or(description) {
                         println!("   📝 {}", snippet);
                     }
                     println!();
                 }
+
+                if let Some(path) = &cli.cite {
+                    let csl = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+                    let exported = if csl {
+                        serde_json::to_string_pretty(&report::citations::to_csl_json(&results))?
+                    } else {
+                        report::citations::to_bibtex(&results)
+                    };
+                    match std::fs::write(path, exported) {
+                        Ok(()) => println!("📚 引用已匯出到 {}\n", path.display()),
+                        Err(e) => eprintln!("⚠️  無法寫入 {}: {}", path.display(), e),
+                    }
+                }
+
+                if cli.answer {
+                    match Synthesizer::extractive().synthesize(query, &results).await {
+                        Ok(answer) if !answer.citations.is_empty() => {
+                            println!("🧾 答案:");
+                            print!("{}", answer);
+                            println!();
+                        }
+                        Ok(_) => println!("🧾 結果中沒有足以回答查詢的句子\n"),
+                        Err(e) => eprintln!("⚠️  無法合成答案: {}", e),
+                    }
+                }
+
+                if let Some(n) = cli.why {
+                    match n.checked_sub(1).and_then(|i| results.get(i)) {
+                        Some(result) => {
+                            let explanation = explain_relevance(query, result)
+                                .with_engines(vec![format!("{:?}", cli.engine).to_lowercase()]);
+                            println!("💡 第 {} 個結果的相關性:", n);
+                            print!("{}", explanation);
+                        }
+                        None => eprintln!("⚠️  --why {} 超出結果範圍 (1..={})", n, results.len()),
+                    }
+                }
             }
         }
         Err(e) => {
@@ -88,3 +620,155 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+/// `save`、`saved`、`stats` 與 `bookmarks` 子命令
+fn run_command(command: &Command, store: &ResultStore, cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        Command::Save { name, query } => {
+            let params = serde_json::json!({
+                "engine": format!("{:?}", cli.engine).to_lowercase(),
+                "num": cli.num,
+            });
+            store.save_search(name, query, &params)?;
+            println!("💾 已保存搜尋 `{}`（以 --saved {} 執行）", name, name);
+        }
+        Command::Saved => {
+            let saved = store.saved_searches()?;
+            if saved.is_empty() {
+                println!("（沒有保存的搜尋）");
+            }
+            for search in saved {
+                println!("{}: {}  {}", search.name, search.query, search.params);
+            }
+        }
+        Command::Stats { days, top } => {
+            let now = std::time::SystemTime::now();
+            let from = now - Duration::from_secs(days * 86_400);
+            let stats = QueryAnalytics::new().with_top(*top).collect(store, from, now + Duration::from_secs(1))?;
+            println!("📊 最近 {} 天", days);
+            print!("{}", stats);
+        }
+        Command::Bookmarks { action: BookmarkAction::List } => {
+            let bookmarks = store.bookmarks()?;
+            if bookmarks.is_empty() {
+                println!("（沒有書籤）");
+            }
+            for bookmark in bookmarks {
+                println!("#{} {}", bookmark.id, bookmark.title);
+                println!("   🔗 {}", bookmark.url);
+                if let Some(query) = &bookmark.query {
+                    println!("   🔎 {}", query);
+                }
+                if let Some(note) = &bookmark.note {
+                    println!("   🗒️  {}", note);
+                }
+                println!();
+            }
+        }
+        Command::Bookmarks { action: BookmarkAction::Add { url, title, note, query } } => {
+            let mut result = store.find_result(url)?.unwrap_or_else(|| SearchResult {
+                title: url.clone(),
+                url: url.clone(),
+                snippet: None,
+                content: None,
+                published_date: None,
+                metadata: Default::default(),
+            });
+            if let Some(title) = title {
+                result.title = title.clone();
+            }
+            let id = store.add_bookmark(&result, query.as_deref(), note.as_deref())?;
+            println!("🔖 已加入書籤 #{}: {}", id, result.title);
+        }
+        Command::Bookmarks { action: BookmarkAction::Remove { id } } => {
+            if store.remove_bookmark(*id)? {
+                println!("🗑️  已刪除書籤 #{}", id);
+            } else {
+                eprintln!("⚠️  找不到書籤 #{}", id);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 第一方抓取的配置（`--ignore-robots`、`--user-agent`）
+fn fetcher_config(cli: &Cli) -> FetcherConfig {
+    let mut config = FetcherConfig {
+        respect_robots: !cli.ignore_robots,
+        ..FetcherConfig::default()
+    };
+    if let Some(agent) = &cli.user_agent {
+        config.user_agent = agent.clone();
+    }
+    config
+}
+
+/// 設定 `CONDENSE_LLM_URL` 時以 OpenAI 相容端點濃縮過長的查詢，否則沿用關鍵字擷取
+///
+/// 模型為 `CONDENSE_LLM_MODEL`，未設定時使用路由器的簡單任務模型；金鑰為 `CONDENSE_LLM_API_KEY`。
+#[cfg(feature = "openai")]
+fn llm_condenser(router: &SemanticRouter) -> Result<Option<Arc<dyn QueryCondenser>>, Box<dyn std::error::Error>> {
+    let Some(url) = env::var("CONDENSE_LLM_URL").ok().filter(|url| !url.is_empty()) else {
+        return Ok(None);
+    };
+    let mut completion = OpenAiCompletion::new(&url);
+    if let Some(key) = bose_common::load_secret("CONDENSE_LLM_API_KEY")? {
+        completion = completion.with_api_key(key.expose());
+    }
+    let condenser = match env::var("CONDENSE_LLM_MODEL") {
+        Ok(model) => LlmCondenser::new(Arc::new(completion), &model),
+        Err(_) => LlmCondenser::for_router(Arc::new(completion), router),
+    };
+    Ok(Some(Arc::new(condenser)))
+}
+
+#[cfg(not(feature = "openai"))]
+fn llm_condenser(_router: &SemanticRouter) -> Result<Option<Arc<dyn QueryCondenser>>, Box<dyn std::error::Error>> {
+    Ok(None)
+}
+
+/// 多把金鑰的輪替策略（`API_KEY_ROTATION`）
+fn rotation_strategy() -> Result<RotationStrategy, Box<dyn std::error::Error>> {
+    Ok(match env::var("API_KEY_ROTATION") {
+        Ok(value) => value.parse::<RotationStrategy>()?,
+        Err(_) => RotationStrategy::default(),
+    })
+}
+
+/// `--research`：DuckDuckGo 起步，依設定中的金鑰加入 Exa 與 Tavily；有 `--report` 時寫成 Markdown
+async fn research(
+    query: &str,
+    config: &BoseConfig,
+    proxy: Option<String>,
+    report_path: Option<&std::path::Path>,
+    filter: Option<Arc<DomainFilter>>,
+    spam: Option<Arc<SpamDetector>>,
+    fetcher_config: FetcherConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut retrieval = TieredRetrieval::from_config(config)?;
+    if !config.budget.is_empty() {
+        retrieval = retrieval.with_cost_tracker(Arc::new(CostTracker::from_settings(&config.budget)?));
+    }
+    if let Some(filter) = filter {
+        retrieval = retrieval.with_filter(filter);
+    }
+    if let Some(spam) = spam {
+        retrieval = retrieval.with_spam_detector(spam);
+    }
+    let pool = PooledClient::new(PoolConfig {
+        proxy,
+        ..PoolConfig::default()
+    })?;
+    let fetcher = Fetcher::new(Arc::new(pool), fetcher_config);
+
+    println!("🔬 深度研究: \"{}\"\n", query);
+    let report = DeepResearch::new(retrieval).with_fetcher(fetcher).run(query).await?;
+    match report_path {
+        Some(path) => {
+            std::fs::write(path, report::markdown::render(&report))?;
+            println!("📝 報告已寫入 {}", path.display());
+        }
+        None => print!("{}", report),
+    }
+    Ok(())
+}
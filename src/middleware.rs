@@ -0,0 +1,136 @@
+//! 請求 / 回應中介層 - 不修改客戶端即可加入自訂日誌、標頭或指標
+//!
+//! 在 `MultiSearchClient::with_middleware` 註冊後，DuckDuckGo、Exa、Tavily 的每個 HTTP 請求
+//! （包含重試）都會依註冊順序經過 `on_request`，完成後呼叫 `on_response` 或 `on_error`。
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::types::{SearchEngine, SearchError};
+
+/// 中介層；所有方法預設不做任何事，只需實作用得到的掛鉤
+pub trait Middleware: Send + Sync {
+    /// 送出前呼叫，可修改請求（例如加入標頭）
+    fn on_request(&self, _engine: SearchEngine, _request: &mut reqwest::Request) {}
+
+    /// 收到回應時呼叫（包含非 2xx 回應）
+    fn on_response(&self, _engine: SearchEngine, _response: &reqwest::Response, _elapsed: Duration) {}
+
+    /// 連線失敗、逾時等沒有回應的錯誤
+    fn on_error(&self, _engine: SearchEngine, _error: &SearchError, _elapsed: Duration) {}
+}
+
+/// 依註冊順序執行的中介層清單；複製成本低，可在客戶端間共用
+#[derive(Clone, Default)]
+pub struct MiddlewareStack {
+    layers: Vec<Arc<dyn Middleware>>,
+}
+
+impl MiddlewareStack {
+    pub fn push(&mut self, middleware: Arc<dyn Middleware>) {
+        self.layers.push(middleware);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// 經過所有中介層送出請求
+    pub async fn send(
+        &self,
+        engine: SearchEngine,
+        client: &reqwest::Client,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, SearchError> {
+        let mut request = request
+            .build()
+            .map_err(|e| SearchError::NetworkError(e.to_string()))?;
+        for layer in &self.layers {
+            layer.on_request(engine, &mut request);
+        }
+
+        let start = Instant::now();
+        match client.execute(request).await {
+            Ok(response) => {
+                for layer in &self.layers {
+                    layer.on_response(engine, &response, start.elapsed());
+                }
+                Ok(response)
+            }
+            Err(e) => {
+                let error = SearchError::NetworkError(e.to_string());
+                for layer in &self.layers {
+                    layer.on_error(engine, &error, start.elapsed());
+                }
+                Err(error)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use wiremock::matchers::{header, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Mutex<Vec<String>>,
+    }
+
+    impl Middleware for Recorder {
+        fn on_request(&self, engine: SearchEngine, request: &mut reqwest::Request) {
+            request
+                .headers_mut()
+                .insert("x-trace-id", reqwest::header::HeaderValue::from_static("abc"));
+            self.events.lock().unwrap().push(format!("request {:?}", engine));
+        }
+
+        fn on_response(&self, _engine: SearchEngine, response: &reqwest::Response, _elapsed: Duration) {
+            self.events.lock().unwrap().push(format!("response {}", response.status().as_u16()));
+        }
+
+        fn on_error(&self, _engine: SearchEngine, _error: &SearchError, _elapsed: Duration) {
+            self.events.lock().unwrap().push("error".into());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hooks_run_in_order() {
+        let recorder = Arc::new(Recorder::default());
+        let mut stack = MiddlewareStack::default();
+        stack.push(recorder.clone());
+        let client = reqwest::Client::new();
+
+        // 只有帶著中介層加入的標頭時才回 204
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("x-trace-id", "abc"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let response = stack
+            .send(SearchEngine::Exa, &client, client.get(server.uri()))
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 204);
+
+        // 連線被拒：沒有回應，呼叫 on_error（MockServer 會被放回池中重用，這裡改用釋放掉的埠）
+        let closed = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        };
+        let result = stack
+            .send(SearchEngine::DuckDuckGo, &client, client.get(closed))
+            .await;
+        assert!(matches!(result, Err(SearchError::NetworkError(_))));
+
+        assert_eq!(
+            *recorder.events.lock().unwrap(),
+            vec!["request Exa", "response 204", "request DuckDuckGo", "error"]
+        );
+    }
+}
@@ -1,8 +1,25 @@
 use reqwest::Client;
 use tokio::sync::Semaphore;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
+use super::stats::IN_FLIGHT_REQUESTS;
+use crate::types::SearchError;
+use bose_common::ProxyConfig;
+
+/// 以代理網址建立 `reqwest::Proxy`；協定檢查沿用 `bose_common::ProxyConfig`
+pub(crate) fn to_proxy(url: &str) -> Result<reqwest::Proxy, SearchError> {
+    ProxyConfig::proxy(url).map_err(|e| SearchError::ConfigError(e.to_string()))
+}
+
+/// 以代理建立新的 reqwest 客戶端
+pub(crate) fn proxied_client(url: &str) -> Result<Client, SearchError> {
+    Client::builder()
+        .proxy(to_proxy(url)?)
+        .build()
+        .map_err(|e| SearchError::NetworkError(e.to_string()))
+}
+
 /// 連線池配置
 #[derive(Debug, Clone)]
 pub struct PoolConfig {
@@ -10,6 +27,10 @@ pub struct PoolConfig {
     pub pool_idle_per_host: usize,
     pub pool_idle_timeout: Duration,
     pub request_timeout: Duration,
+    /// 代理網址（HTTP / SOCKS5）；None 時沿用系統代理設定
+    pub proxy: Option<String>,
+    /// 最多跟隨幾次重導
+    pub max_redirects: usize,
 }
 
 impl Default for PoolConfig {
@@ -19,42 +40,89 @@ impl Default for PoolConfig {
             pool_idle_per_host: 20,
             pool_idle_timeout: Duration::from_secs(90),
             request_timeout: Duration::from_secs(30),
+            proxy: None,
+            max_redirects: 10,
         }
     }
 }
 
 /// 帶連線池的 HTTP 客戶端
 pub struct PooledClient {
-    client: Client,
+    client: RwLock<Client>,
     pub(crate) semaphore: Arc<Semaphore>,
     config: PoolConfig,
 }
 
 impl PooledClient {
-    pub fn new(config: PoolConfig) -> Result<Self, reqwest::Error> {
-        let client = Client::builder()
-            .pool_idle_timeout(config.pool_idle_timeout)
-            .pool_max_idle_per_host(config.pool_idle_per_host)
-            .timeout(config.request_timeout)
-            .build()?;
+    pub fn new(config: PoolConfig) -> Result<Self, SearchError> {
+        let client = Self::build_client(&config)?;
 
         Ok(Self {
-            client,
+            client: RwLock::new(client),
             semaphore: Arc::new(Semaphore::new(config.max_concurrent)),
             config,
         })
     }
 
-    pub fn with_defaults() -> Result<Self, reqwest::Error> {
+    pub fn with_defaults() -> Result<Self, SearchError> {
         Self::new(PoolConfig::default())
     }
 
+    fn build_client(config: &PoolConfig) -> Result<Client, SearchError> {
+        let mut builder = Client::builder()
+            .pool_idle_timeout(config.pool_idle_timeout)
+            .pool_max_idle_per_host(config.pool_idle_per_host)
+            .timeout(config.request_timeout)
+            .redirect(reqwest::redirect::Policy::limited(config.max_redirects));
+        if let Some(url) = &config.proxy {
+            builder = builder.proxy(to_proxy(url)?);
+        }
+        builder.build().map_err(|e| SearchError::NetworkError(e.to_string()))
+    }
+
+    /// 丟棄目前的連線池並重建客戶端，關閉所有閒置連線
+    ///
+    /// 進行中的請求持有舊客戶端的複本，會正常完成。
+    pub fn close_idle_connections(&self) -> Result<(), SearchError> {
+        let fresh = Self::build_client(&self.config)?;
+        *self.client.write().unwrap() = fresh;
+        Ok(())
+    }
+
+    fn client(&self) -> Client {
+        self.client.read().unwrap().clone()
+    }
+
     pub async fn get(
         &self,
         url: &str,
     ) -> Result<reqwest::Response, Box<dyn std::error::Error + Send + Sync>> {
         let _permit = self.semaphore.acquire().await?;
-        let response = self.client.get(url).send().await?;
+        let _in_flight = IN_FLIGHT_REQUESTS.track();
+        let response = self.client().get(url).send().await?;
+        Ok(response)
+    }
+
+    /// 附帶額外標頭的 GET（例如抓取網頁時的 User-Agent / Accept）
+    pub async fn get_with_headers(
+        &self,
+        url: &str,
+        headers: reqwest::header::HeaderMap,
+    ) -> Result<reqwest::Response, Box<dyn std::error::Error + Send + Sync>> {
+        let _permit = self.semaphore.acquire().await?;
+        let _in_flight = IN_FLIGHT_REQUESTS.track();
+        let response = self.client().get(url).headers(headers).send().await?;
+        Ok(response)
+    }
+
+    /// HEAD 請求（檢查連結是否仍存在，不下載本文）
+    pub async fn head(
+        &self,
+        url: &str,
+    ) -> Result<reqwest::Response, Box<dyn std::error::Error + Send + Sync>> {
+        let _permit = self.semaphore.acquire().await?;
+        let _in_flight = IN_FLIGHT_REQUESTS.track();
+        let response = self.client().head(url).send().await?;
         Ok(response)
     }
 
@@ -64,7 +132,8 @@ impl PooledClient {
         body: &T,
     ) -> Result<reqwest::Response, Box<dyn std::error::Error + Send + Sync>> {
         let _permit = self.semaphore.acquire().await?;
-        let response = self.client.post(url).json(body).send().await?;
+        let _in_flight = IN_FLIGHT_REQUESTS.track();
+        let response = self.client().post(url).json(body).send().await?;
         Ok(response)
     }
 
@@ -93,10 +162,27 @@ mod tests {
             pool_idle_per_host: 10,
             pool_idle_timeout: Duration::from_secs(60),
             request_timeout: Duration::from_secs(15),
+            proxy: None,
+            max_redirects: 3,
         };
         assert_eq!(config.max_concurrent, 5);
     }
 
+    #[tokio::test]
+    async fn test_pooled_client_with_proxy() {
+        let config = PoolConfig {
+            proxy: Some("socks5h://127.0.0.1:9050".into()),
+            ..Default::default()
+        };
+        assert!(PooledClient::new(config).is_ok());
+
+        let config = PoolConfig {
+            proxy: Some("gopher://proxy".into()),
+            ..Default::default()
+        };
+        assert!(matches!(PooledClient::new(config), Err(SearchError::ConfigError(_))));
+    }
+
     #[tokio::test]
     async fn test_pooled_client_creation() {
         let client = PooledClient::with_defaults();
@@ -109,6 +195,13 @@ mod tests {
         assert_eq!(client.active_permits(), 0);
     }
 
+    #[tokio::test]
+    async fn test_close_idle_connections() {
+        let client = PooledClient::with_defaults().unwrap();
+        assert!(client.close_idle_connections().is_ok());
+        assert_eq!(client.active_permits(), 0);
+    }
+
     #[tokio::test]
     async fn test_concurrent_limit() {
         let config = PoolConfig {
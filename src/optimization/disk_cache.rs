@@ -0,0 +1,250 @@
+//! 快取持久化 - 將序列化後的快取項目寫入目錄，啟動時以 mmap 載入
+//!
+//! 每個項目一個檔案：固定標頭（魔術字、格式版本、到期時間、鍵值）之後接 rkyv 資料，
+//! 資料起點對齊 16 位元組，mmap 後可直接零拷貝存取。格式版本不符或資料驗證失敗的檔案會被刪除。
+
+use bose_common::hash::fnv1a_hex;
+use memmap2::Mmap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::zero_copy::CachedSearchResult;
+
+/// 磁碟格式版本；`CachedSearchResult` 欄位或標頭配置變動時必須遞增
+pub const CACHE_FORMAT_VERSION: u32 = 3;
+
+const MAGIC: &[u8; 8] = b"BOSECACH";
+/// 魔術字 8 + 版本 4 + 鍵長 4 + 到期秒數 8
+const FIXED_HEADER: usize = 24;
+const PAYLOAD_ALIGN: usize = 16;
+const EXTENSION: &str = "bin";
+
+/// 快取項目的位元組來源：記憶體或映射的檔案
+pub(crate) enum EntryBytes {
+    Heap(Vec<u8>),
+    Mapped { map: Mmap, offset: usize },
+}
+
+impl Deref for EntryBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            EntryBytes::Heap(bytes) => bytes,
+            EntryBytes::Mapped { map, offset } => &map[*offset..],
+        }
+    }
+}
+
+/// 從磁碟載入的項目
+pub(crate) struct LoadedEntry {
+    pub key: String,
+    pub bytes: EntryBytes,
+    pub expires_at: SystemTime,
+}
+
+/// 快取目錄
+pub(crate) struct DiskStore {
+    dir: PathBuf,
+}
+
+impl DiskStore {
+    pub fn open(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 鍵值以 FNV-1a 雜湊命名（跨版本穩定），完整鍵值存於標頭
+    fn path_for(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}", fnv1a_hex(key), EXTENSION))
+    }
+
+    /// 寫入項目（先寫暫存檔再改名，避免讀到寫一半的檔案）
+    pub fn write(&self, key: &str, payload: &[u8], expires_at: SystemTime) -> io::Result<()> {
+        let key_bytes = key.as_bytes();
+        let header_len = payload_offset(key_bytes.len());
+        let expires = expires_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let mut buf = Vec::with_capacity(header_len + payload.len());
+        buf.extend_from_slice(MAGIC);
+        buf.extend_from_slice(&CACHE_FORMAT_VERSION.to_le_bytes());
+        buf.extend_from_slice(&(key_bytes.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&expires.to_le_bytes());
+        buf.extend_from_slice(key_bytes);
+        buf.resize(header_len, 0);
+        buf.extend_from_slice(payload);
+
+        let path = self.path_for(key);
+        let tmp = path.with_extension("tmp");
+        let mut file = File::create(&tmp)?;
+        file.write_all(&buf)?;
+        file.sync_data()?;
+        fs::rename(&tmp, &path)
+    }
+
+    pub fn remove(&self, key: &str) {
+        let _ = fs::remove_file(self.path_for(key));
+    }
+
+    /// 刪除目錄中所有快取檔案
+    pub fn clear(&self) {
+        for path in self.entry_paths() {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    /// 載入所有有效且未過期的項目；無效檔案會被刪除
+    pub fn load(&self) -> Vec<LoadedEntry> {
+        let now = SystemTime::now();
+        let mut loaded = Vec::new();
+        for path in self.entry_paths() {
+            match Self::load_file(&path) {
+                Some(entry) if entry.expires_at > now => loaded.push(entry),
+                Some(_) => {
+                    let _ = fs::remove_file(&path);
+                }
+                None => {
+                    log::warn!("🗑️ 捨棄無效的快取檔案: {}", path.display());
+                    let _ = fs::remove_file(&path);
+                }
+            }
+        }
+        loaded
+    }
+
+    fn entry_paths(&self) -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == EXTENSION))
+            .collect()
+    }
+
+    fn load_file(path: &Path) -> Option<LoadedEntry> {
+        let file = File::open(path).ok()?;
+        // SAFETY: 檔案只由本模組以「寫暫存檔後改名」的方式產生，映射期間不會被原地修改
+        let map = unsafe { Mmap::map(&file) }.ok()?;
+
+        if map.len() < FIXED_HEADER || &map[..8] != MAGIC {
+            return None;
+        }
+        let version = u32::from_le_bytes(map[8..12].try_into().ok()?);
+        if version != CACHE_FORMAT_VERSION {
+            return None;
+        }
+        let key_len = u32::from_le_bytes(map[12..16].try_into().ok()?) as usize;
+        let expires = u64::from_le_bytes(map[16..24].try_into().ok()?);
+        let offset = payload_offset(key_len);
+        if map.len() < offset {
+            return None;
+        }
+        let key = std::str::from_utf8(&map[FIXED_HEADER..FIXED_HEADER + key_len])
+            .ok()?
+            .to_string();
+
+        // 磁碟資料不可信，存取前先驗證
+        rkyv::access::<rkyv::Archived<Vec<CachedSearchResult>>, rkyv::rancor::Error>(
+            &map[offset..],
+        )
+        .ok()?;
+
+        Some(LoadedEntry {
+            key,
+            bytes: EntryBytes::Mapped { map, offset },
+            expires_at: UNIX_EPOCH + Duration::from_secs(expires),
+        })
+    }
+}
+
+/// 標頭長度（含鍵值與對齊填充）
+fn payload_offset(key_len: usize) -> usize {
+    (FIXED_HEADER + key_len).div_ceil(PAYLOAD_ALIGN) * PAYLOAD_ALIGN
+}
+
+#[cfg(test)]
+pub(crate) fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("bose-cache-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload() -> Vec<u8> {
+        let results = vec![CachedSearchResult {
+            title: "Rust".to_string(),
+            url: "https://rust-lang.org".to_string(),
+            snippet: None,
+            content: None,
+            published_date: None,
+            metadata: Default::default(),
+            timestamp: 0,
+        }];
+        rkyv::to_bytes::<rkyv::rancor::Error>(&results).unwrap().to_vec()
+    }
+
+    fn in_one_hour() -> SystemTime {
+        SystemTime::now() + Duration::from_secs(3600)
+    }
+
+    #[test]
+    fn test_write_and_load() {
+        let store = DiskStore::open(test_dir("write-load")).unwrap();
+        store.write("rust query", &payload(), in_one_hour()).unwrap();
+
+        let loaded = store.load();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].key, "rust query");
+        assert_eq!(&*loaded[0].bytes, &payload()[..]);
+        assert_eq!(loaded[0].bytes.as_ptr() as usize % PAYLOAD_ALIGN, 0);
+        let _ = fs::remove_dir_all(store.dir());
+    }
+
+    #[test]
+    fn test_version_mismatch_discarded() {
+        let store = DiskStore::open(test_dir("version")).unwrap();
+        store.write("k", &payload(), in_one_hour()).unwrap();
+
+        let path = store.path_for("k");
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[8..12].copy_from_slice(&(CACHE_FORMAT_VERSION + 1).to_le_bytes());
+        fs::write(&path, bytes).unwrap();
+
+        assert!(store.load().is_empty());
+        assert!(!path.exists());
+        let _ = fs::remove_dir_all(store.dir());
+    }
+
+    #[test]
+    fn test_corrupt_and_expired_discarded() {
+        let store = DiskStore::open(test_dir("corrupt")).unwrap();
+        store.write("old", &payload(), UNIX_EPOCH + Duration::from_secs(1)).unwrap();
+        store.write("bad", &[0xFF; 7], in_one_hour()).unwrap();
+
+        assert!(store.load().is_empty());
+        assert!(store.entry_paths().is_empty());
+        let _ = fs::remove_dir_all(store.dir());
+    }
+
+    #[test]
+    fn test_payload_offset_alignment() {
+        assert_eq!(payload_offset(0), 32);
+        assert_eq!(payload_offset(8), 32);
+        assert_eq!(payload_offset(9), 48);
+    }
+}
@@ -0,0 +1,175 @@
+//! 閒置資源回收 - 嵌入式使用時在閒置後釋放連線、快取與背景任務
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+use super::stats::BACKGROUND_TASKS;
+
+/// 閒置回收配置
+#[derive(Debug, Clone)]
+pub struct IdleConfig {
+    /// 無活動多久後執行回收
+    pub idle_timeout: Duration,
+    /// 檢查間隔
+    pub check_interval: Duration,
+}
+
+impl Default for IdleConfig {
+    fn default() -> Self {
+        Self {
+            idle_timeout: Duration::from_secs(300),
+            check_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+/// 活動追蹤器（可複製，共用同一個時間戳）
+#[derive(Debug, Clone)]
+pub struct IdleTracker {
+    last_activity: Arc<Mutex<Instant>>,
+}
+
+impl IdleTracker {
+    pub fn new() -> Self {
+        Self {
+            last_activity: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// 標記一次活動
+    pub fn touch(&self) {
+        *self.last_activity.lock().unwrap() = Instant::now();
+    }
+
+    /// 距離上次活動的時間
+    pub fn idle_for(&self) -> Duration {
+        self.last_activity.lock().unwrap().elapsed()
+    }
+}
+
+impl Default for IdleTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 閒置回收器
+///
+/// 背景任務定期檢查 `IdleTracker`，閒置超過 `idle_timeout` 時呼叫一次 teardown；
+/// 之後有新活動才會再次觸發。Drop 時自動停止背景任務。
+pub struct IdleReaper {
+    handle: JoinHandle<()>,
+    teardowns: Arc<AtomicUsize>,
+}
+
+impl IdleReaper {
+    /// 啟動回收背景任務（需在 tokio runtime 內呼叫）
+    pub fn spawn<F>(tracker: IdleTracker, config: IdleConfig, teardown: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        let teardowns = Arc::new(AtomicUsize::new(0));
+        let counter = teardowns.clone();
+
+        let handle = tokio::spawn(async move {
+            let _task = BACKGROUND_TASKS.track();
+            let mut torn_down = false;
+            loop {
+                tokio::time::sleep(config.check_interval).await;
+
+                if tracker.idle_for() >= config.idle_timeout {
+                    if !torn_down {
+                        log::info!("💤 閒置 {:?}，釋放資源", config.idle_timeout);
+                        teardown();
+                        counter.fetch_add(1, Ordering::Relaxed);
+                        torn_down = true;
+                    }
+                } else {
+                    torn_down = false;
+                }
+            }
+        });
+
+        Self { handle, teardowns }
+    }
+
+    /// 已執行的回收次數
+    pub fn teardown_count(&self) -> usize {
+        self.teardowns.load(Ordering::Relaxed)
+    }
+
+    /// 停止背景任務
+    pub fn shutdown(self) {
+        self.handle.abort();
+    }
+}
+
+impl Drop for IdleReaper {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fast_config() -> IdleConfig {
+        IdleConfig {
+            idle_timeout: Duration::from_millis(30),
+            check_interval: Duration::from_millis(5),
+        }
+    }
+
+    #[test]
+    fn test_idle_config_default() {
+        let config = IdleConfig::default();
+        assert_eq!(config.idle_timeout, Duration::from_secs(300));
+        assert_eq!(config.check_interval, Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_tracker_touch_resets() {
+        let tracker = IdleTracker::new();
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(tracker.idle_for() >= Duration::from_millis(20));
+        tracker.touch();
+        assert!(tracker.idle_for() < Duration::from_millis(20));
+    }
+
+    #[tokio::test]
+    async fn test_reaper_runs_once_per_idle_period() {
+        let tracker = IdleTracker::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let calls_clone = calls.clone();
+
+        let reaper = IdleReaper::spawn(tracker.clone(), fast_config(), move || {
+            calls_clone.fetch_add(1, Ordering::Relaxed);
+        });
+
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+        // 新活動後再次閒置會重新觸發
+        tracker.touch();
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+        assert_eq!(reaper.teardown_count(), 2);
+
+        reaper.shutdown();
+    }
+
+    #[tokio::test]
+    async fn test_reaper_skips_while_active() {
+        let tracker = IdleTracker::new();
+        let reaper = IdleReaper::spawn(tracker.clone(), fast_config(), || {});
+
+        for _ in 0..10 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            tracker.touch();
+        }
+        assert_eq!(reaper.teardown_count(), 0);
+    }
+}
@@ -0,0 +1,260 @@
+//! API 金鑰池 - 同一付費引擎設定多把金鑰，輪流使用或在 429 / 402 時切換
+//!
+//! 共用免費額度的團隊不必再各自部署一份；每把金鑰的使用量與狀態可由 `usage()` 查詢。
+
+use crate::optimization::rate_limiter::parse_retry_after;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 429 沒有 `Retry-After` 時的冷卻時間
+const DEFAULT_RATE_LIMIT_COOLDOWN: Duration = Duration::from_secs(60);
+/// 402（額度用盡）後的冷卻時間
+const EXHAUSTED_COOLDOWN: Duration = Duration::from_secs(24 * 3600);
+
+/// 金鑰輪替策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RotationStrategy {
+    /// 每個請求換下一把金鑰，平均分攤用量
+    #[default]
+    RoundRobin,
+    /// 持續使用同一把，遇到 429 / 402 才換下一把
+    OnLimit,
+}
+
+impl std::str::FromStr for RotationStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "round-robin" | "round_robin" | "rr" => Ok(RotationStrategy::RoundRobin),
+            "on-limit" | "on_limit" | "failover" => Ok(RotationStrategy::OnLimit),
+            other => Err(format!("未知的金鑰輪替策略 `{}`（round-robin / on-limit）", other)),
+        }
+    }
+}
+
+struct KeySlot {
+    key: String,
+    requests: AtomicU64,
+    rate_limited: AtomicU64,
+    exhausted: AtomicU64,
+    cooldown_until: Mutex<Option<Instant>>,
+}
+
+impl KeySlot {
+    fn available(&self, now: Instant) -> bool {
+        self.cooldown_until.lock().unwrap().is_none_or(|until| now >= until)
+    }
+}
+
+/// 租用中的金鑰；請求完成後以 `KeyPool::report` 回報狀態
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyLease {
+    pub index: usize,
+    pub key: String,
+}
+
+/// 單把金鑰的使用統計（金鑰只顯示頭尾）
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyUsage {
+    pub key_hint: String,
+    pub requests: u64,
+    pub rate_limited: u64,
+    pub exhausted: u64,
+    /// 冷卻中時為剩餘時間
+    pub cooling_down: Option<Duration>,
+}
+
+/// 金鑰池
+pub struct KeyPool {
+    slots: Vec<KeySlot>,
+    strategy: RotationStrategy,
+    cursor: AtomicUsize,
+}
+
+impl KeyPool {
+    pub fn new(keys: Vec<String>, strategy: RotationStrategy) -> Self {
+        let slots = keys
+            .into_iter()
+            .map(|key| key.trim().to_string())
+            .filter(|key| !key.is_empty())
+            .map(|key| KeySlot {
+                key,
+                requests: AtomicU64::new(0),
+                rate_limited: AtomicU64::new(0),
+                exhausted: AtomicU64::new(0),
+                cooldown_until: Mutex::new(None),
+            })
+            .collect();
+        Self {
+            slots,
+            strategy,
+            cursor: AtomicUsize::new(0),
+        }
+    }
+
+    /// 解析逗號分隔的金鑰清單，例如 `EXA_API_KEY=key1,key2`
+    pub fn parse(keys: &str, strategy: RotationStrategy) -> Self {
+        Self::new(keys.split(',').map(str::to_string).collect(), strategy)
+    }
+
+    /// 單一金鑰
+    pub fn single(key: &str) -> Self {
+        Self::new(vec![key.to_string()], RotationStrategy::OnLimit)
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// 取得下一把可用金鑰；全部冷卻中時回傳最快恢復的一把
+    pub fn next(&self) -> Option<KeyLease> {
+        if self.slots.is_empty() {
+            return None;
+        }
+        let now = Instant::now();
+        let start = match self.strategy {
+            RotationStrategy::RoundRobin => self.cursor.fetch_add(1, Ordering::Relaxed),
+            RotationStrategy::OnLimit => self.cursor.load(Ordering::Relaxed),
+        };
+        let n = self.slots.len();
+        let index = (0..n)
+            .map(|offset| (start + offset) % n)
+            .find(|&i| self.slots[i].available(now))
+            .unwrap_or_else(|| self.soonest_available());
+
+        if self.strategy == RotationStrategy::OnLimit {
+            self.cursor.store(index, Ordering::Relaxed);
+        }
+        let slot = &self.slots[index];
+        slot.requests.fetch_add(1, Ordering::Relaxed);
+        Some(KeyLease {
+            index,
+            key: slot.key.clone(),
+        })
+    }
+
+    fn soonest_available(&self) -> usize {
+        (0..self.slots.len())
+            .min_by_key(|&i| *self.slots[i].cooldown_until.lock().unwrap())
+            .unwrap_or(0)
+    }
+
+    /// 回報請求結果；429 與 402 讓該金鑰進入冷卻，回傳是否應換一把金鑰重送
+    pub fn report(&self, lease: &KeyLease, status: StatusCode, retry_after: Option<Duration>) -> bool {
+        let Some(slot) = self.slots.get(lease.index) else {
+            return false;
+        };
+        let cooldown = match status {
+            StatusCode::TOO_MANY_REQUESTS => {
+                slot.rate_limited.fetch_add(1, Ordering::Relaxed);
+                retry_after.unwrap_or(DEFAULT_RATE_LIMIT_COOLDOWN)
+            }
+            StatusCode::PAYMENT_REQUIRED => {
+                slot.exhausted.fetch_add(1, Ordering::Relaxed);
+                EXHAUSTED_COOLDOWN
+            }
+            _ => return false,
+        };
+        *slot.cooldown_until.lock().unwrap() = Some(Instant::now() + cooldown);
+        log::warn!(
+            "🔑 金鑰 {} 回應 {}，冷卻 {} 秒",
+            key_hint(&slot.key),
+            status.as_u16(),
+            cooldown.as_secs()
+        );
+        let now = Instant::now();
+        self.slots.iter().any(|s| s.available(now))
+    }
+
+    /// 同 `report`，`Retry-After` 由回應標頭解析
+    pub fn observe(&self, lease: &KeyLease, status: StatusCode, headers: &HeaderMap) -> bool {
+        let retry_after = headers
+            .get(RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_retry_after);
+        self.report(lease, status, retry_after)
+    }
+
+    /// 每把金鑰的使用統計
+    pub fn usage(&self) -> Vec<KeyUsage> {
+        let now = Instant::now();
+        self.slots
+            .iter()
+            .map(|slot| KeyUsage {
+                key_hint: key_hint(&slot.key),
+                requests: slot.requests.load(Ordering::Relaxed),
+                rate_limited: slot.rate_limited.load(Ordering::Relaxed),
+                exhausted: slot.exhausted.load(Ordering::Relaxed),
+                cooling_down: slot
+                    .cooldown_until
+                    .lock()
+                    .unwrap()
+                    .filter(|until| *until > now)
+                    .map(|until| until - now),
+            })
+            .collect()
+    }
+}
+
+/// `sk-abcdef123456` → `sk-a…3456`
+fn key_hint(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    if chars.len() <= 8 {
+        return "…".to_string();
+    }
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}…{}", head, tail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_robin() {
+        let pool = KeyPool::parse("key-aaaa-1111, key-bbbb-2222,,", RotationStrategy::RoundRobin);
+        assert_eq!(pool.len(), 2);
+        let keys: Vec<usize> = (0..4).map(|_| pool.next().unwrap().index).collect();
+        assert_eq!(keys, vec![0, 1, 0, 1]);
+        assert_eq!(pool.usage()[0].requests, 2);
+        assert_eq!(pool.usage()[0].key_hint, "key-…1111");
+    }
+
+    #[test]
+    fn test_rotate_on_limit() {
+        let pool = KeyPool::parse("key-aaaa-1111,key-bbbb-2222", RotationStrategy::OnLimit);
+        let first = pool.next().unwrap();
+        assert_eq!(pool.next().unwrap().index, 0);
+
+        // 429 後換到第二把，且之後持續使用第二把
+        assert!(pool.report(&first, StatusCode::TOO_MANY_REQUESTS, Some(Duration::from_secs(30))));
+        let second = pool.next().unwrap();
+        assert_eq!(second.index, 1);
+        assert_eq!(pool.next().unwrap().index, 1);
+
+        // 兩把都冷卻中：不建議重送，仍回傳最快恢復的一把
+        assert!(!pool.report(&second, StatusCode::PAYMENT_REQUIRED, None));
+        assert_eq!(pool.next().unwrap().index, 0);
+
+        let usage = pool.usage();
+        assert_eq!((usage[0].rate_limited, usage[1].exhausted), (1, 1));
+        assert!(usage[1].cooling_down.unwrap() > Duration::from_secs(3600));
+        assert!(!pool.report(&second, StatusCode::OK, None));
+    }
+
+    #[test]
+    fn test_strategy_parse() {
+        assert_eq!("on-limit".parse::<RotationStrategy>(), Ok(RotationStrategy::OnLimit));
+        assert_eq!("RR".parse::<RotationStrategy>(), Ok(RotationStrategy::RoundRobin));
+        assert!("random".parse::<RotationStrategy>().is_err());
+    }
+}
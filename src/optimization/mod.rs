@@ -1,7 +1,18 @@
 pub mod zero_copy;
+pub mod disk_cache;
 pub mod connection_pool;
 pub mod rate_limiter;
+pub mod idle;
+pub mod stats;
+pub mod retry;
+pub mod key_pool;
 
-pub use zero_copy::{SearchCache, CachedSearchResult, CacheStats};
+pub use zero_copy::{SearchCache, CachedSearchResult, CacheStats, CacheSweeper, CategoryTtls};
 pub use connection_pool::{PooledClient, PoolConfig};
-pub use rate_limiter::{RateLimiter, RateLimiterConfig};
+pub use rate_limiter::{parse_retry_after, RateLimiter, RateLimiterConfig};
+pub use idle::{IdleConfig, IdleReaper, IdleTracker};
+pub use stats::{runtime_stats, RuntimeStats};
+pub use retry::{with_backoff, RetryPolicy, Retryable};
+pub use bose_common::ProxyConfig;
+pub use key_pool::{KeyLease, KeyPool, KeyUsage, RotationStrategy};
+pub use disk_cache::CACHE_FORMAT_VERSION;
@@ -1,5 +1,8 @@
+use chrono::DateTime;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
 use std::sync::Mutex;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 use tokio::time::sleep;
 
 /// 速率限制器配置
@@ -18,12 +21,29 @@ impl Default for RateLimiterConfig {
     }
 }
 
+/// 收到 429 時補充速率乘上的係數
+const BACKOFF_FACTOR: f64 = 0.5;
+/// 每次成功請求恢復的速率（設定值的比例）
+const RECOVERY_STEP: f64 = 0.05;
+/// 速率下限（設定值的比例），避免降到幾乎停止
+const MIN_RATE_RATIO: f64 = 1.0 / 32.0;
+/// 429 沒有附上 `Retry-After` 時的暫停時間
+const DEFAULT_PAUSE: Duration = Duration::from_secs(1);
+/// `Retry-After` 的上限，避免錯誤的標頭讓客戶端停擺太久
+const MAX_PAUSE: Duration = Duration::from_secs(300);
+
 /// Token Bucket 速率限制器
+///
+/// 補充速率會依上游回應自動調整（AIMD）：引擎回報 429 時速率減半並暫停到 `Retry-After`
+/// 指定的時間，之後每次成功請求逐步恢復，直到設定的速率為止。
 pub struct RateLimiter {
     tokens: Mutex<f64>,
     max_tokens: f64,
-    refill_rate: f64,
+    /// 設定的速率（恢復上限）
+    base_rate: f64,
+    refill_rate: Mutex<f64>,
     last_refill: Mutex<Instant>,
+    paused_until: Mutex<Option<Instant>>,
 }
 
 impl RateLimiter {
@@ -31,13 +51,27 @@ impl RateLimiter {
         Self {
             tokens: Mutex::new(config.burst_size as f64),
             max_tokens: config.burst_size as f64,
-            refill_rate: config.requests_per_second,
+            base_rate: config.requests_per_second,
+            refill_rate: Mutex::new(config.requests_per_second),
             last_refill: Mutex::new(Instant::now()),
+            paused_until: Mutex::new(None),
         }
     }
 
+    /// 每秒 `rps` 個請求，突發量為一秒的額度
+    pub fn per_second(rps: f64) -> Self {
+        Self::new(RateLimiterConfig {
+            requests_per_second: rps,
+            burst_size: (rps.ceil() as usize).max(1),
+        })
+    }
+
     pub async fn acquire(&self) {
         loop {
+            if let Some(pause) = self.paused_for() {
+                sleep(pause).await;
+                continue;
+            }
             self.refill();
             {
                 let mut tokens = self.tokens.lock().unwrap();
@@ -47,12 +81,15 @@ impl RateLimiter {
                 }
             }
 
-            let wait_time = Duration::from_secs_f64(1.0 / self.refill_rate);
+            let wait_time = Duration::from_secs_f64(1.0 / self.current_rate());
             sleep(wait_time).await;
         }
     }
 
     pub fn try_acquire(&self) -> bool {
+        if self.paused_for().is_some() {
+            return false;
+        }
         self.refill();
         let mut tokens = self.tokens.lock().unwrap();
         if *tokens >= 1.0 {
@@ -70,7 +107,7 @@ impl RateLimiter {
 
         if elapsed > 0.0 {
             let mut tokens = self.tokens.lock().unwrap();
-            let new_tokens = elapsed * self.refill_rate;
+            let new_tokens = elapsed * self.current_rate();
             *tokens = (*tokens + new_tokens).min(self.max_tokens);
             *last_refill = now;
         }
@@ -80,6 +117,79 @@ impl RateLimiter {
         self.refill();
         *self.tokens.lock().unwrap()
     }
+
+    /// 目前的補充速率（每秒請求數）
+    pub fn current_rate(&self) -> f64 {
+        *self.refill_rate.lock().unwrap()
+    }
+
+    /// 距離暫停結束的時間；未暫停時為 None
+    pub fn paused_for(&self) -> Option<Duration> {
+        let mut paused_until = self.paused_until.lock().unwrap();
+        match *paused_until {
+            Some(until) if until > Instant::now() => Some(until - Instant::now()),
+            Some(_) => {
+                *paused_until = None;
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// 引擎回報 429：速率減半、清空 token，並暫停到 `retry_after` 之後
+    pub fn report_rate_limited(&self, retry_after: Option<Duration>) {
+        let pause = retry_after.unwrap_or(DEFAULT_PAUSE).min(MAX_PAUSE);
+        let rate = {
+            let mut rate = self.refill_rate.lock().unwrap();
+            *rate = (*rate * BACKOFF_FACTOR).max(self.base_rate * MIN_RATE_RATIO);
+            *rate
+        };
+        *self.tokens.lock().unwrap() = 0.0;
+        *self.last_refill.lock().unwrap() = Instant::now() + pause;
+
+        let until = Instant::now() + pause;
+        let mut paused_until = self.paused_until.lock().unwrap();
+        if paused_until.is_none_or(|current| current < until) {
+            *paused_until = Some(until);
+        }
+        log::warn!("🚦 被限流，速率降為 {:.2} req/s，暫停 {:.1} 秒", rate, pause.as_secs_f64());
+    }
+
+    /// 請求成功：逐步恢復速率，最多回到設定值
+    pub fn report_success(&self) {
+        let mut rate = self.refill_rate.lock().unwrap();
+        *rate = (*rate + self.base_rate * RECOVERY_STEP).min(self.base_rate);
+    }
+
+    /// 依引擎回應自動調整：429（或附 `Retry-After` 的 503）降速，2xx 恢復
+    pub fn observe(&self, status: StatusCode, headers: &HeaderMap) {
+        let retry_after = headers
+            .get(RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_retry_after);
+        if status == StatusCode::TOO_MANY_REQUESTS
+            || (status == StatusCode::SERVICE_UNAVAILABLE && retry_after.is_some())
+        {
+            self.report_rate_limited(retry_after);
+        } else if status.is_success() {
+            self.report_success();
+        }
+    }
+}
+
+/// 解析 `Retry-After`：秒數或 HTTP 日期（`Wed, 21 Oct 2015 07:28:00 GMT`）
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = parse_http_date(value)?;
+    Some(at.duration_since(SystemTime::now()).unwrap_or_default())
+}
+
+/// RFC 7231 的 IMF-fixdate（`GMT` 結尾的 RFC 2822 日期）
+fn parse_http_date(value: &str) -> Option<SystemTime> {
+    DateTime::parse_from_rfc2822(value).ok().map(SystemTime::from)
 }
 
 #[cfg(test)]
@@ -160,6 +270,62 @@ mod tests {
         assert!(tokens_after > tokens_before);
     }
 
+    #[test]
+    fn test_rate_limited_backs_off_and_recovers() {
+        let limiter = RateLimiter::new(RateLimiterConfig {
+            requests_per_second: 8.0,
+            burst_size: 5,
+        });
+        limiter.report_rate_limited(Some(Duration::from_secs(30)));
+        assert_eq!(limiter.current_rate(), 4.0);
+        assert!(!limiter.try_acquire());
+        assert!(limiter.paused_for().unwrap() > Duration::from_secs(29));
+
+        // 速率有下限
+        for _ in 0..10 {
+            limiter.report_rate_limited(None);
+        }
+        assert_eq!(limiter.current_rate(), 8.0 / 32.0);
+
+        // 成功請求逐步恢復，不超過設定值
+        for _ in 0..100 {
+            limiter.report_success();
+        }
+        assert_eq!(limiter.current_rate(), 8.0);
+    }
+
+    #[tokio::test]
+    async fn test_acquire_waits_for_retry_after() {
+        let limiter = RateLimiter::new(RateLimiterConfig::default());
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, "0".parse().unwrap());
+        limiter.observe(StatusCode::TOO_MANY_REQUESTS, &headers);
+        assert_eq!(limiter.current_rate(), 5.0);
+
+        limiter.report_rate_limited(Some(Duration::from_millis(100)));
+        let start = Instant::now();
+        limiter.acquire().await;
+        assert!(start.elapsed() >= Duration::from_millis(90));
+
+        limiter.observe(StatusCode::OK, &HeaderMap::new());
+        assert!(limiter.current_rate() > 2.5);
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(
+            parse_http_date("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(std::time::UNIX_EPOCH + Duration::from_secs(1_445_412_480))
+        );
+        // 過去的日期視為立即可重試
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        assert!(parse_retry_after("soon").is_none());
+    }
+
     #[tokio::test]
     async fn test_max_tokens_limit() {
         let config = RateLimiterConfig {
@@ -0,0 +1,83 @@
+//! 重試 - 對逾時、連線失敗與 5xx 以指數退避加抖動重試
+//!
+//! 退避邏輯沿用 `bose_common::retry`；這裡只定義 `SearchError` 哪些情況值得重試。
+
+use crate::types::SearchError;
+
+pub use bose_common::retry::{with_backoff, RetryPolicy, Retryable};
+
+impl Retryable for SearchError {
+    /// 網路錯誤（逾時、連線失敗）與 5xx 可重試；4xx、解析、儲存與配置錯誤重試也不會成功
+    fn is_retryable(&self) -> bool {
+        match self {
+            SearchError::NetworkError(_) => true,
+            SearchError::ApiError(msg) => api_status(msg).is_some_and(|s| (500..600).contains(&s)),
+            SearchError::ParseError(_) | SearchError::StorageError(_) | SearchError::ConfigError(_) => false,
+        }
+    }
+}
+
+/// 從 `"Exa API 錯誤 503 Service Unavailable: ..."` 取出 HTTP 狀態碼
+fn api_status(msg: &str) -> Option<u16> {
+    msg.split_whitespace()
+        .map(|token| token.trim_end_matches(':'))
+        .find(|token| token.len() == 3 && token.bytes().all(|b| b.is_ascii_digit()))
+        .and_then(|token| token.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn test_classification() {
+        assert!(SearchError::NetworkError("timed out".into()).is_retryable());
+        assert!(SearchError::ApiError("Exa API 錯誤 503 Service Unavailable: busy".into()).is_retryable());
+        assert!(!SearchError::ApiError("Tavily API 錯誤 401 Unauthorized: bad key".into()).is_retryable());
+        assert!(!SearchError::ParseError("eof".into()).is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_with_backoff() {
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(4),
+            jitter: 0.5,
+        };
+
+        let calls = AtomicU32::new(0);
+        let result = with_backoff(&policy, || async {
+            match calls.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(SearchError::NetworkError("connection reset".into())),
+                _ => Ok(7),
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 7);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // 不可重試的錯誤立即回傳；可重試的錯誤最多嘗試 max_attempts 次
+        calls.store(0, Ordering::SeqCst);
+        let result: Result<(), _> = with_backoff(&policy, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(SearchError::ParseError("bad json".into()))
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        calls.store(0, Ordering::SeqCst);
+        let result: Result<(), _> = with_backoff(&policy, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(SearchError::ApiError("Exa API 錯誤 502 Bad Gateway: ".into()))
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        assert_eq!(RetryPolicy::default().delay(10), Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,174 @@
+//! 執行期統計 - 提供嵌入方監控搜尋子系統的資源佔用
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// 可增減的計量器
+#[derive(Debug)]
+pub(crate) struct Gauge(AtomicUsize);
+
+impl Gauge {
+    pub(crate) const fn new() -> Self {
+        Self(AtomicUsize::new(0))
+    }
+
+    pub(crate) fn add(&self, n: usize) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub(crate) fn sub(&self, n: usize) {
+        // 防止計數下溢
+        let _ = self
+            .0
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |v| Some(v.saturating_sub(n)));
+    }
+
+    pub(crate) fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// 計數 +1，guard drop 時 -1
+    pub(crate) fn track(&'static self) -> GaugeGuard {
+        self.add(1);
+        GaugeGuard(self)
+    }
+}
+
+/// `Gauge::track` 回傳的 RAII guard
+#[derive(Debug)]
+pub(crate) struct GaugeGuard(&'static Gauge);
+
+impl Drop for GaugeGuard {
+    fn drop(&mut self) {
+        self.0.sub(1);
+    }
+}
+
+pub(crate) static CACHE_BYTES: Gauge = Gauge::new();
+pub(crate) static CACHE_ENTRIES: Gauge = Gauge::new();
+pub(crate) static IN_FLIGHT_REQUESTS: Gauge = Gauge::new();
+pub(crate) static BACKGROUND_TASKS: Gauge = Gauge::new();
+
+/// 執行期統計快照
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeStats {
+    /// 所有 `SearchCache` 序列化資料的總位元組
+    pub cache_bytes: usize,
+    /// 所有 `SearchCache` 的項目總數
+    pub cache_entries: usize,
+    /// 進行中的 HTTP 請求數
+    pub in_flight_requests: usize,
+    /// 存活的背景任務數
+    pub background_tasks: usize,
+    /// 配置器統計（需啟用 `alloc-stats` feature 並註冊 `CountingAllocator`）
+    #[cfg(feature = "alloc-stats")]
+    pub allocator: AllocatorStats,
+}
+
+/// 取得目前的執行期統計
+pub fn runtime_stats() -> RuntimeStats {
+    RuntimeStats {
+        cache_bytes: CACHE_BYTES.get(),
+        cache_entries: CACHE_ENTRIES.get(),
+        in_flight_requests: IN_FLIGHT_REQUESTS.get(),
+        background_tasks: BACKGROUND_TASKS.get(),
+        #[cfg(feature = "alloc-stats")]
+        allocator: CountingAllocator::stats(),
+    }
+}
+
+#[cfg(feature = "alloc-stats")]
+pub use alloc_stats::{AllocatorStats, CountingAllocator};
+
+#[cfg(feature = "alloc-stats")]
+mod alloc_stats {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+    static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+    /// 配置器統計
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct AllocatorStats {
+        /// 目前仍在使用的位元組
+        pub allocated_bytes: usize,
+        /// 累計配置次數
+        pub total_allocations: usize,
+    }
+
+    /// 包裝系統配置器的計數配置器
+    ///
+    /// 嵌入方以 `#[global_allocator] static A: CountingAllocator = CountingAllocator;` 啟用。
+    pub struct CountingAllocator;
+
+    impl CountingAllocator {
+        pub fn stats() -> AllocatorStats {
+            AllocatorStats {
+                allocated_bytes: ALLOCATED.load(Ordering::Relaxed),
+                total_allocations: ALLOCATIONS.load(Ordering::Relaxed),
+            }
+        }
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = unsafe { System.alloc(layout) };
+            if !ptr.is_null() {
+                ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+                ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            unsafe { System.dealloc(ptr, layout) };
+            ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static TEST_GAUGE: Gauge = Gauge::new();
+
+    #[test]
+    fn test_gauge_add_sub() {
+        let gauge = Gauge::new();
+        gauge.add(10);
+        gauge.sub(4);
+        assert_eq!(gauge.get(), 6);
+        gauge.sub(100);
+        assert_eq!(gauge.get(), 0);
+    }
+
+    #[test]
+    fn test_gauge_guard() {
+        {
+            let _a = TEST_GAUGE.track();
+            let _b = TEST_GAUGE.track();
+            assert_eq!(TEST_GAUGE.get(), 2);
+        }
+        assert_eq!(TEST_GAUGE.get(), 0);
+    }
+
+    #[test]
+    fn test_runtime_stats_includes_cache() {
+        let cache = crate::optimization::SearchCache::new(10, 3600);
+        let results = vec![crate::optimization::CachedSearchResult {
+            title: "Stats".to_string(),
+            url: "https://example.com".to_string(),
+            snippet: None,
+            content: None,
+            published_date: None,
+            metadata: Default::default(),
+            timestamp: 0,
+        }];
+        cache.store("stats", &results).unwrap();
+
+        let stats = runtime_stats();
+        assert!(stats.cache_bytes >= cache.stats().total_bytes);
+        assert!(stats.cache_entries >= 1);
+    }
+}
@@ -1,9 +1,17 @@
 //! 零拷貝序列化 - 使用 rkyv 實現高效能資料處理
 
 use rkyv::{Archive, Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::RwLock;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::task::JoinHandle;
+
+use super::disk_cache::{DiskStore, EntryBytes};
+use super::stats::{BACKGROUND_TASKS, CACHE_BYTES, CACHE_ENTRIES};
+use crate::routing::QueryCategory;
+use crate::types::SearchError;
 
 /// 可序列化的搜尋結果
 #[derive(Archive, Deserialize, Serialize, Debug, Clone, PartialEq)]
@@ -18,6 +26,9 @@ pub struct CachedSearchResult {
     pub url: String,
     pub snippet: Option<String>,
     pub content: Option<String>,
+    pub published_date: Option<String>,
+    /// 引擎與擴充資料（favicon、OpenGraph、作者等），見 `SearchResult::metadata`
+    pub metadata: BTreeMap<String, String>,
     pub timestamp: u64,
 }
 
@@ -29,6 +40,8 @@ impl CachedSearchResult {
             url: result.url.clone(),
             snippet: result.snippet.clone(),
             content: result.content.clone(),
+            published_date: result.published_date.clone(),
+            metadata: result.metadata.clone(),
             timestamp: SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .unwrap()
@@ -43,78 +56,376 @@ impl CachedSearchResult {
             url: self.url.clone(),
             snippet: self.snippet.clone(),
             content: self.content.clone(),
+            published_date: self.published_date.clone(),
+            metadata: self.metadata.clone(),
         }
     }
 }
 
-/// 搜尋結果快取
+/// 快取項目：序列化後的位元組、最近一次存取的序號與到期時間
+struct Entry {
+    bytes: EntryBytes,
+    tick: u64,
+    /// 到期索引的鍵（到期時間, 寫入序號）
+    expiry: (Instant, u64),
+}
+
+/// 依存取順序排列的快取狀態
+///
+/// `recency` 以單調遞增的存取序號索引鍵值，最小序號即最久未使用的項目；
+/// `expiry` 依到期時間排序，清除過期項目時只需從頭掃描。
+#[derive(Default)]
+struct LruState {
+    map: HashMap<String, Entry>,
+    recency: BTreeMap<u64, String>,
+    expiry: BTreeMap<(Instant, u64), String>,
+    next_tick: u64,
+    expired: u64,
+    evicted: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl LruState {
+    fn bump(&mut self) -> u64 {
+        self.next_tick += 1;
+        self.next_tick
+    }
+
+    /// 將項目標記為最近使用
+    fn promote(&mut self, key: &str) {
+        let tick = self.bump();
+        if let Some(entry) = self.map.get_mut(key) {
+            let old = std::mem::replace(&mut entry.tick, tick);
+            if let Some(k) = self.recency.remove(&old) {
+                self.recency.insert(tick, k);
+            }
+        }
+    }
+
+    /// 插入或覆寫項目，回傳被覆寫的舊值
+    fn insert(&mut self, key: String, bytes: EntryBytes, expires_at: Instant) -> Option<EntryBytes> {
+        let tick = self.bump();
+        let expiry = (expires_at, tick);
+        self.recency.insert(tick, key.clone());
+        self.expiry.insert(expiry, key.clone());
+        let replaced = self.map.insert(key, Entry { bytes, tick, expiry })?;
+        self.recency.remove(&replaced.tick);
+        self.expiry.remove(&replaced.expiry);
+        Some(replaced.bytes)
+    }
+
+    fn remove(&mut self, key: &str) -> Option<EntryBytes> {
+        let entry = self.map.remove(key)?;
+        self.recency.remove(&entry.tick);
+        self.expiry.remove(&entry.expiry);
+        Some(entry.bytes)
+    }
+
+    /// 移除最久未使用的項目
+    fn pop_lru(&mut self) -> Option<(String, EntryBytes)> {
+        let key = self.recency.first_key_value()?.1.clone();
+        let bytes = self.remove(&key)?;
+        self.evicted += 1;
+        Some((key, bytes))
+    }
+
+    /// 移除所有在 `now` 之前到期的項目，回傳被移除的鍵值與資料
+    fn purge_expired(&mut self, now: Instant) -> Vec<(String, EntryBytes)> {
+        let mut purged = Vec::new();
+        while let Some((&(expires_at, _), key)) = self.expiry.first_key_value() {
+            if expires_at > now {
+                break;
+            }
+            let key = key.clone();
+            if let Some(bytes) = self.remove(&key) {
+                purged.push((key, bytes));
+            }
+        }
+        self.expired += purged.len() as u64;
+        purged
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+        self.recency.clear();
+        self.expiry.clear();
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.map.shrink_to_fit();
+    }
+}
+
+/// 各查詢類別的快取 TTL
+///
+/// 新聞幾分鐘就過時，文件可以保留數天；類別由路由器的 `categorize` 決定。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CategoryTtls {
+    pub news: Duration,
+    pub docs: Duration,
+    pub general: Duration,
+}
+
+impl CategoryTtls {
+    /// 所有類別使用相同的 TTL
+    pub fn uniform(ttl: Duration) -> Self {
+        Self {
+            news: ttl,
+            docs: ttl,
+            general: ttl,
+        }
+    }
+
+    /// 取得類別對應的 TTL
+    pub fn ttl(&self, category: QueryCategory) -> Duration {
+        match category {
+            QueryCategory::News => self.news,
+            QueryCategory::Docs => self.docs,
+            QueryCategory::General => self.general,
+        }
+    }
+}
+
+impl Default for CategoryTtls {
+    /// 預設：新聞 10 分鐘、文件 3 天、其他 1 小時
+    fn default() -> Self {
+        Self {
+            news: Duration::from_secs(10 * 60),
+            docs: Duration::from_secs(3 * 24 * 3600),
+            general: Duration::from_secs(3600),
+        }
+    }
+}
+
+/// 負面快取的失敗紀錄
+struct Failure {
+    /// true 為網路錯誤，false 為 API 錯誤
+    network: bool,
+    message: String,
+    expires_at: Instant,
+}
+
+/// 搜尋結果快取（LRU 淘汰、每筆項目各自的 TTL，可選擇持久化到磁碟）
 pub struct SearchCache {
-    cache: RwLock<HashMap<String, Vec<u8>>>,
+    state: Mutex<LruState>,
+    /// 失敗紀錄與正常結果分開存放，不計入命中率，也不寫入磁碟
+    failures: Mutex<HashMap<String, Failure>>,
     max_size: usize,
-    ttl_seconds: u64,
+    ttls: CategoryTtls,
+    disk: Option<DiskStore>,
 }
 
 impl SearchCache {
-    /// 建立新的快取
+    /// 建立新的快取，所有類別使用相同的 TTL（可再以 `with_category_ttls` 分別設定）
     pub fn new(max_size: usize, ttl_seconds: u64) -> Self {
         Self {
-            cache: RwLock::new(HashMap::new()),
+            state: Mutex::new(LruState::default()),
+            failures: Mutex::new(HashMap::new()),
             max_size,
-            ttl_seconds,
+            ttls: CategoryTtls::uniform(Duration::from_secs(ttl_seconds)),
+            disk: None,
         }
     }
 
+    /// 設定各類別的 TTL
+    pub fn with_category_ttls(mut self, ttls: CategoryTtls) -> Self {
+        self.ttls = ttls;
+        self
+    }
+
+    /// 目前的各類別 TTL
+    pub fn category_ttls(&self) -> CategoryTtls {
+        self.ttls
+    }
+
+    /// 建立持久化到 `dir` 的快取，並以 mmap 載入目錄中仍有效的項目
+    ///
+    /// CLI 每次執行或 MCP 伺服器重啟後仍保有先前的快取；格式版本不符的檔案會被刪除。
+    pub fn with_disk(max_size: usize, ttl_seconds: u64, dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let disk = DiskStore::open(dir)?;
+        let mut loaded = disk.load();
+        // 越晚到期視為越新，先插入較舊的項目以便超出容量時優先淘汰
+        loaded.sort_by_key(|e| e.expires_at);
+
+        let mut cache = Self::new(max_size, ttl_seconds);
+        cache.disk = Some(disk);
+        {
+            let mut state = cache.state.lock().unwrap();
+            let now = Instant::now();
+            let wall_now = SystemTime::now();
+            for entry in loaded {
+                while state.map.len() >= max_size.max(1) {
+                    match state.pop_lru() {
+                        Some((key, bytes)) => cache.discard(&key, &bytes),
+                        None => break,
+                    }
+                }
+                let remaining = entry.expires_at.duration_since(wall_now).unwrap_or_default();
+                CACHE_BYTES.add(entry.bytes.len());
+                CACHE_ENTRIES.add(1);
+                if let Some(replaced) = state.insert(entry.key, entry.bytes, now + remaining) {
+                    Self::untrack(&replaced);
+                }
+            }
+            log::info!("💾 從磁碟載入 {} 筆快取", state.map.len());
+        }
+        Ok(cache)
+    }
+
+    /// 持久化目錄（僅磁碟模式）
+    pub fn disk_dir(&self) -> Option<&Path> {
+        self.disk.as_ref().map(DiskStore::dir)
+    }
+
     /// 使用預設配置建立快取
     /// 預設：最多 1000 個項目，TTL 1 小時
     pub fn with_defaults() -> Self {
         Self::new(1000, 3600)
     }
 
-    /// 儲存搜尋結果（零拷貝序列化）
+    /// 儲存搜尋結果（零拷貝序列化），使用一般類別的 TTL
     pub fn store(&self, key: &str, results: &[CachedSearchResult]) -> Result<(), String> {
+        self.store_for(key, results, QueryCategory::General)
+    }
+
+    /// 依查詢類別的 TTL 儲存搜尋結果
+    pub fn store_for(
+        &self,
+        key: &str,
+        results: &[CachedSearchResult],
+        category: QueryCategory,
+    ) -> Result<(), String> {
+        self.store_with_ttl(key, results, self.ttls.ttl(category))
+    }
+
+    /// 以指定 TTL 儲存搜尋結果
+    ///
+    /// 寫入前先清除已過期的項目，仍然滿載時才淘汰最久未使用的項目。
+    pub fn store_with_ttl(
+        &self,
+        key: &str,
+        results: &[CachedSearchResult],
+        ttl: Duration,
+    ) -> Result<(), String> {
         // 轉換為 Vec 以便序列化
         let results_vec = results.to_vec();
         let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&results_vec)
             .map_err(|e| format!("序列化錯誤: {:?}", e))?;
 
-        let mut cache = self.cache.write().unwrap();
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
 
-        // 檢查快取大小，實作簡單的 LRU
-        if cache.len() >= self.max_size {
-            // 移除第一個項目（簡化版 LRU）
-            if let Some(oldest_key) = cache.keys().next().cloned() {
-                cache.remove(&oldest_key);
+        for (purged_key, purged) in state.purge_expired(now) {
+            self.discard(&purged_key, &purged);
+        }
+
+        // 覆寫既有鍵值不需要淘汰；否則淘汰最久未使用的項目直到有空間
+        if !state.map.contains_key(key) {
+            while state.map.len() >= self.max_size.max(1) {
+                match state.pop_lru() {
+                    Some((evicted_key, evicted)) => self.discard(&evicted_key, &evicted),
+                    None => break,
+                }
             }
         }
 
-        cache.insert(key.to_string(), bytes.to_vec());
+        CACHE_BYTES.add(bytes.len());
+        CACHE_ENTRIES.add(1);
+        let entry = EntryBytes::Heap(bytes.to_vec());
+        if let Some(replaced) = state.insert(key.to_string(), entry, now + ttl) {
+            Self::untrack(&replaced);
+        }
+        drop(state);
+
+        if let Some(disk) = &self.disk
+            && let Err(e) = disk.write(key, &bytes, SystemTime::now() + ttl)
+        {
+            log::warn!("⚠️ 快取寫入磁碟失敗: {}", e);
+        }
         Ok(())
     }
 
-    /// 讀取搜尋結果（零拷貝反序列化）
+    /// 讀取搜尋結果（零拷貝反序列化），命中時提升為最近使用；過期項目會在此移除
     pub fn get(&self, key: &str) -> Option<Vec<CachedSearchResult>> {
-        let cache = self.cache.read().unwrap();
+        let mut state = self.state.lock().unwrap();
+        let Some(entry) = state.map.get(key) else {
+            state.misses += 1;
+            return None;
+        };
 
-        cache.get(key).and_then(|bytes| {
-            // 零拷貝存取
-            let archived = unsafe {
-                rkyv::access_unchecked::<rkyv::Archived<Vec<CachedSearchResult>>>(bytes)
-            };
+        // 檢查 TTL
+        if entry.expiry.0 <= Instant::now() {
+            if let Some(bytes) = state.remove(key) {
+                state.expired += 1;
+                self.discard(key, &bytes);
+            }
+            state.misses += 1;
+            return None;
+        }
 
-            // 檢查 TTL
-            let now = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs();
+        // 零拷貝存取
+        let archived = unsafe {
+            rkyv::access_unchecked::<rkyv::Archived<Vec<CachedSearchResult>>>(&entry.bytes)
+        };
 
-            if let Some(first) = archived.first() {
-                if now - first.timestamp > self.ttl_seconds {
-                    return None;  // 已過期
-                }
+        // 反序列化（如果需要修改）；無法解碼的項目視為未命中並移除，避免每次查詢都重新解碼失敗
+        let Ok(results) = rkyv::deserialize::<Vec<CachedSearchResult>, rkyv::rancor::Error>(archived) else {
+            if let Some(bytes) = state.remove(key) {
+                self.discard(key, &bytes);
+            }
+            state.misses += 1;
+            return None;
+        };
+        state.promote(key);
+        state.hits += 1;
+        Some(results)
+    }
+
+    /// 記錄查詢失敗（負面快取），TTL 內相同查詢直接回傳同一個錯誤，不再重送請求
+    ///
+    /// 失敗與正常結果分開存放，不會覆蓋同一查詢的正常結果，且只保存在記憶體中；
+    /// 只記錄網路與 API 錯誤，其餘錯誤（解析、儲存）多半是本地問題，重試才有意義。
+    pub fn store_failure(&self, key: &str, error: &SearchError, ttl: Duration) -> Result<(), String> {
+        let (network, message) = match error {
+            SearchError::NetworkError(msg) => (true, msg),
+            SearchError::ApiError(msg) => (false, msg),
+            SearchError::ParseError(_) | SearchError::StorageError(_) | SearchError::ConfigError(_) => {
+                return Ok(())
             }
+        };
+        let now = Instant::now();
+        let mut failures = self.failures.lock().unwrap();
+        failures.retain(|_, failure| failure.expires_at > now);
+        // 滿載時捨棄最早到期的紀錄
+        if !failures.contains_key(key) && failures.len() >= self.max_size.max(1) {
+            let soonest = failures.iter().min_by_key(|(_, f)| f.expires_at).map(|(k, _)| k.clone());
+            if let Some(soonest) = soonest {
+                failures.remove(&soonest);
+            }
+        }
+        failures.insert(
+            key.to_string(),
+            Failure {
+                network,
+                message: message.clone(),
+                expires_at: now + ttl,
+            },
+        );
+        Ok(())
+    }
 
-            // 反序列化（如果需要修改）
-            rkyv::deserialize::<Vec<CachedSearchResult>, rkyv::rancor::Error>(archived).ok()
+    /// 讀取仍在 TTL 內的失敗紀錄；不計入命中率
+    pub fn get_failure(&self, key: &str) -> Option<SearchError> {
+        let mut failures = self.failures.lock().unwrap();
+        let failure = failures.get(key)?;
+        if failure.expires_at <= Instant::now() {
+            failures.remove(key);
+            return None;
+        }
+        Some(match failure.network {
+            true => SearchError::NetworkError(failure.message.clone()),
+            false => SearchError::ApiError(failure.message.clone()),
         })
     }
 
@@ -123,44 +434,168 @@ impl SearchCache {
         self.get(key).is_some()
     }
 
-    /// 清除快取
+    /// 清除所有已過期的項目，回傳清除數量
+    pub fn purge_expired(&self) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let purged = state.purge_expired(Instant::now());
+        for (key, bytes) in &purged {
+            self.discard(key, bytes);
+        }
+        purged.len()
+    }
+
+    /// 啟動背景清除任務，每隔 `interval` 清除一次過期項目（需在 tokio runtime 內呼叫）
+    ///
+    /// 任務只持有弱參照，快取被釋放後自動結束。
+    pub fn spawn_sweeper(cache: &Arc<Self>, interval: Duration) -> CacheSweeper {
+        let weak: Weak<Self> = Arc::downgrade(cache);
+        let handle = tokio::spawn(async move {
+            let _task = BACKGROUND_TASKS.track();
+            loop {
+                tokio::time::sleep(interval).await;
+                let Some(cache) = weak.upgrade() else {
+                    break;
+                };
+                let purged = cache.purge_expired();
+                if purged > 0 {
+                    log::debug!("🧹 清除 {} 筆過期快取", purged);
+                }
+            }
+        });
+        CacheSweeper { handle }
+    }
+
+    /// 從執行期統計扣除一個項目
+    fn untrack(bytes: &[u8]) {
+        CACHE_BYTES.sub(bytes.len());
+        CACHE_ENTRIES.sub(1);
+    }
+
+    /// 項目被移除（過期、淘汰、刪除）時一併刪除磁碟檔案
+    fn discard(&self, key: &str, bytes: &[u8]) {
+        Self::untrack(bytes);
+        if let Some(disk) = &self.disk {
+            disk.remove(key);
+        }
+    }
+
+    /// 只清空記憶體中的項目，磁碟檔案保留
+    fn drain_memory(&self) {
+        let mut state = self.state.lock().unwrap();
+        for entry in state.map.values() {
+            Self::untrack(&entry.bytes);
+        }
+        state.clear();
+        self.failures.lock().unwrap().clear();
+    }
+
+    /// 清除快取（磁碟模式下也刪除檔案）
     pub fn clear(&self) {
-        let mut cache = self.cache.write().unwrap();
-        cache.clear();
+        self.drain_memory();
+        if let Some(disk) = &self.disk {
+            disk.clear();
+        }
     }
 
-    /// 取得快取大小
+    /// 釋放記憶體中的快取與底層配置（閒置回收時使用）；磁碟檔案保留供下次載入
+    pub fn release_memory(&self) {
+        self.drain_memory();
+        self.state.lock().unwrap().shrink_to_fit();
+    }
+
+    /// 取得快取大小（包含尚未清除的過期項目）
     pub fn size(&self) -> usize {
-        self.cache.read().unwrap().len()
+        self.state.lock().unwrap().map.len()
     }
 
     /// 移除指定項目
     pub fn remove(&self, key: &str) -> bool {
-        let mut cache = self.cache.write().unwrap();
-        cache.remove(key).is_some()
+        let mut state = self.state.lock().unwrap();
+        match state.remove(key) {
+            Some(bytes) => {
+                self.discard(key, &bytes);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 依最久未使用到最近使用的順序列出鍵值
+    pub fn keys_by_recency(&self) -> Vec<String> {
+        let state = self.state.lock().unwrap();
+        state.recency.values().cloned().collect()
     }
 
     /// 取得快取統計資訊
     pub fn stats(&self) -> CacheStats {
-        let cache = self.cache.read().unwrap();
-        let total_bytes: usize = cache.values().map(|v| v.len()).sum();
+        let state = self.state.lock().unwrap();
+        let total_bytes: usize = state.map.values().map(|e| e.bytes.len()).sum();
 
         CacheStats {
-            entries: cache.len(),
+            entries: state.map.len(),
             total_bytes,
             max_size: self.max_size,
-            ttl_seconds: self.ttl_seconds,
+            ttls: self.ttls,
+            expired: state.expired,
+            evicted: state.evicted,
+            hits: state.hits,
+            misses: state.misses,
         }
     }
 }
 
+
+/// 過期快取的背景清除任務，Drop 時自動停止
+pub struct CacheSweeper {
+    handle: JoinHandle<()>,
+}
+
+impl CacheSweeper {
+    /// 停止背景任務
+    pub fn shutdown(self) {
+        self.handle.abort();
+    }
+}
+
+impl Drop for CacheSweeper {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+impl Drop for SearchCache {
+    fn drop(&mut self) {
+        self.drain_memory();
+    }
+}
+
 /// 快取統計資訊
 #[derive(Debug, Clone)]
 pub struct CacheStats {
     pub entries: usize,
     pub total_bytes: usize,
     pub max_size: usize,
-    pub ttl_seconds: u64,
+    pub ttls: CategoryTtls,
+    /// 因 TTL 到期被移除的累計數量
+    pub expired: u64,
+    /// 因容量不足被 LRU 淘汰的累計數量
+    pub evicted: u64,
+    /// `get` 命中的累計次數
+    pub hits: u64,
+    /// `get` 未命中（含已過期）的累計次數
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// 命中率（0.0 - 1.0）；尚未查詢過時為 0
+    pub fn hit_ratio(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
 }
 
 #[cfg(test)]
@@ -174,6 +609,8 @@ mod tests {
                 url: "https://example.com/1".to_string(),
                 snippet: Some("Test snippet 1".to_string()),
                 content: None,
+                published_date: None,
+                metadata: BTreeMap::new(),
                 timestamp: SystemTime::now()
                     .duration_since(UNIX_EPOCH)
                     .unwrap()
@@ -184,6 +621,8 @@ mod tests {
                 url: "https://example.com/2".to_string(),
                 snippet: Some("Test snippet 2".to_string()),
                 content: Some("Full content here".to_string()),
+                published_date: None,
+                metadata: BTreeMap::new(),
                 timestamp: SystemTime::now()
                     .duration_since(UNIX_EPOCH)
                     .unwrap()
@@ -234,6 +673,16 @@ mod tests {
         assert_eq!(cache.size(), 0);
     }
 
+    #[test]
+    fn test_cache_release_memory() {
+        let cache = SearchCache::new(100, 3600);
+        cache.store("test", &create_test_results()).unwrap();
+
+        cache.release_memory();
+        assert_eq!(cache.size(), 0);
+        assert_eq!(cache.stats().total_bytes, 0);
+    }
+
     #[test]
     fn test_cache_remove() {
         let cache = SearchCache::new(100, 3600);
@@ -258,6 +707,55 @@ mod tests {
         // 新增第三個項目應該觸發淘汰
         cache.store("key3", &results).unwrap();
         assert_eq!(cache.size(), 2);
+        assert!(!cache.contains("key1"));
+    }
+
+    #[test]
+    fn test_cache_lru_promote_on_get() {
+        let cache = SearchCache::new(2, 3600);
+        let results = create_test_results();
+
+        cache.store("key1", &results).unwrap();
+        cache.store("key2", &results).unwrap();
+
+        // 讀取 key1 後，key2 成為最久未使用
+        assert!(cache.get("key1").is_some());
+        assert_eq!(cache.keys_by_recency(), vec!["key2", "key1"]);
+
+        cache.store("key3", &results).unwrap();
+        assert!(cache.contains("key1"));
+        assert!(!cache.contains("key2"));
+        assert!(cache.contains("key3"));
+    }
+
+    #[test]
+    fn test_cache_overwrite_does_not_evict() {
+        let cache = SearchCache::new(2, 3600);
+        let results = create_test_results();
+
+        cache.store("key1", &results).unwrap();
+        cache.store("key2", &results).unwrap();
+        cache.store("key1", &results).unwrap();
+
+        assert_eq!(cache.size(), 2);
+        assert_eq!(cache.keys_by_recency(), vec!["key2", "key1"]);
+    }
+
+    #[test]
+    fn test_cache_eviction_order() {
+        let cache = SearchCache::new(3, 3600);
+        let results = create_test_results();
+
+        for key in ["a", "b", "c"] {
+            cache.store(key, &results).unwrap();
+        }
+        cache.get("a");
+        cache.get("b");
+
+        cache.store("d", &results).unwrap();
+        assert_eq!(cache.keys_by_recency(), vec!["a", "b", "d"]);
+        cache.store("e", &results).unwrap();
+        assert_eq!(cache.keys_by_recency(), vec!["b", "d", "e"]);
     }
 
     #[test]
@@ -271,7 +769,189 @@ mod tests {
         assert_eq!(stats.entries, 1);
         assert!(stats.total_bytes > 0);
         assert_eq!(stats.max_size, 100);
-        assert_eq!(stats.ttl_seconds, 3600);
+        assert_eq!(stats.ttls, CategoryTtls::uniform(Duration::from_secs(3600)));
+        assert_eq!((stats.expired, stats.evicted), (0, 0));
+    }
+
+    #[test]
+    fn test_cache_per_entry_ttl() {
+        let cache = SearchCache::new(100, 3600);
+        let results = create_test_results();
+
+        cache
+            .store_with_ttl("short", &results, Duration::from_millis(20))
+            .unwrap();
+        cache.store("long", &results).unwrap();
+        assert!(cache.contains("short"));
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(!cache.contains("short"));
+        assert!(cache.contains("long"));
+
+        // 讀取時即移除過期項目
+        assert_eq!(cache.size(), 1);
+        assert_eq!(cache.stats().expired, 1);
+    }
+
+    #[test]
+    fn test_cache_purges_expired_on_insert() {
+        let cache = SearchCache::new(2, 3600);
+        let results = create_test_results();
+
+        cache.store("keep", &results).unwrap();
+        cache
+            .store_with_ttl("stale", &results, Duration::from_millis(10))
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+
+        // 過期項目先被清除，因此不需要淘汰 LRU 的 "keep"
+        cache.store("new", &results).unwrap();
+        assert!(cache.contains("keep"));
+        assert!(cache.contains("new"));
+
+        let stats = cache.stats();
+        assert_eq!(stats.expired, 1);
+        assert_eq!(stats.evicted, 0);
+    }
+
+    #[test]
+    fn test_cache_evicted_counter() {
+        let cache = SearchCache::new(1, 3600);
+        let results = create_test_results();
+
+        cache.store("a", &results).unwrap();
+        cache.store("b", &results).unwrap();
+        cache.store("c", &results).unwrap();
+        assert_eq!(cache.stats().evicted, 2);
+    }
+
+    #[test]
+    fn test_cache_hit_miss_counters() {
+        let cache = SearchCache::new(10, 3600);
+        assert_eq!(cache.stats().hit_ratio(), 0.0);
+
+        cache.store("a", &create_test_results()).unwrap();
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("b").is_none());
+        cache
+            .store_with_ttl("c", &create_test_results(), Duration::ZERO)
+            .unwrap();
+        assert!(cache.get("c").is_none());
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (2, 2));
+        assert!((stats.hit_ratio() - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_cache_category_ttls() {
+        let cache = SearchCache::new(10, 3600).with_category_ttls(CategoryTtls {
+            news: Duration::from_millis(10),
+            ..CategoryTtls::default()
+        });
+        let results = create_test_results();
+        cache.store_for("news", &results, QueryCategory::News).unwrap();
+        cache.store_for("docs", &results, QueryCategory::Docs).unwrap();
+        cache.store("general", &results).unwrap();
+
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(!cache.contains("news"));
+        assert!(cache.contains("docs"));
+        assert!(cache.contains("general"));
+        assert_eq!(CategoryTtls::default().ttl(QueryCategory::Docs), Duration::from_secs(259_200));
+    }
+
+    #[test]
+    fn test_cache_failures() {
+        let cache = SearchCache::new(10, 3600);
+        let ttl = Duration::from_secs(60);
+        cache.store("q", &create_test_results()).unwrap();
+        cache
+            .store_failure("q", &SearchError::ApiError("HTTP 429".into()), ttl)
+            .unwrap();
+
+        // 失敗紀錄不覆蓋正常結果
+        assert_eq!(cache.get("q").unwrap().len(), 2);
+        assert!(matches!(cache.get_failure("q"), Some(SearchError::ApiError(m)) if m == "HTTP 429"));
+        assert!(cache.get_failure("other").is_none());
+        // 失敗紀錄不佔用結果快取，也不影響命中率
+        assert_eq!(cache.size(), 1);
+        assert_eq!((cache.stats().hits, cache.stats().misses), (1, 0));
+
+        // 解析錯誤不快取；過期後不再命中
+        cache
+            .store_failure("p", &SearchError::ParseError("bad".into()), ttl)
+            .unwrap();
+        assert!(cache.get_failure("p").is_none());
+        cache
+            .store_failure("n", &SearchError::NetworkError("timeout".into()), Duration::ZERO)
+            .unwrap();
+        assert!(cache.get_failure("n").is_none());
+    }
+
+    #[test]
+    fn test_disk_cache_survives_restart() {
+        let dir = crate::optimization::disk_cache::test_dir("restart");
+        {
+            let cache = SearchCache::with_disk(100, 3600, &dir).unwrap();
+            cache.store("rust", &create_test_results()).unwrap();
+        }
+
+        let cache = SearchCache::with_disk(100, 3600, &dir).unwrap();
+        let retrieved = cache.get("rust").unwrap();
+        assert_eq!(retrieved[1].content.as_deref(), Some("Full content here"));
+        assert_eq!(cache.disk_dir(), Some(dir.as_path()));
+
+        // 記憶體回收不影響磁碟，重新載入仍可命中
+        cache.release_memory();
+        assert_eq!(cache.size(), 0);
+        drop(cache);
+        let cache = SearchCache::with_disk(100, 3600, &dir).unwrap();
+        assert!(cache.contains("rust"));
+
+        // remove / clear 會刪除檔案
+        cache.remove("rust");
+        drop(cache);
+        let cache = SearchCache::with_disk(100, 3600, &dir).unwrap();
+        assert_eq!(cache.size(), 0);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_disk_cache_respects_capacity_on_load() {
+        let dir = crate::optimization::disk_cache::test_dir("capacity");
+        {
+            let cache = SearchCache::with_disk(10, 3600, &dir).unwrap();
+            let results = create_test_results();
+            cache.store_with_ttl("a", &results, Duration::from_secs(60)).unwrap();
+            cache.store_with_ttl("b", &results, Duration::from_secs(120)).unwrap();
+            cache.store_with_ttl("c", &results, Duration::from_secs(180)).unwrap();
+        }
+
+        let cache = SearchCache::with_disk(2, 3600, &dir).unwrap();
+        assert_eq!(cache.size(), 2);
+        assert!(!cache.contains("a"));
+        assert!(cache.contains("b"));
+        assert!(cache.contains("c"));
+        cache.clear();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_cache_sweeper() {
+        let cache = Arc::new(SearchCache::new(100, 3600));
+        let results = create_test_results();
+        cache
+            .store_with_ttl("stale", &results, Duration::from_millis(10))
+            .unwrap();
+
+        let sweeper = SearchCache::spawn_sweeper(&cache, Duration::from_millis(20));
+        tokio::time::sleep(Duration::from_millis(60)).await;
+
+        assert_eq!(cache.size(), 0);
+        assert_eq!(cache.stats().expired, 1);
+        sweeper.shutdown();
     }
 
     #[test]
@@ -281,6 +961,8 @@ mod tests {
             url: "https://example.com".to_string(),
             snippet: Some("Snippet".to_string()),
             content: None,
+            published_date: None,
+            metadata: Default::default(),
         };
 
         let cached = CachedSearchResult::from_search_result(&search_result);
@@ -291,4 +973,32 @@ mod tests {
         assert_eq!(converted.title, search_result.title);
         assert_eq!(converted.url, search_result.url);
     }
+
+    #[test]
+    fn test_metadata_survives_disk_cache() {
+        let dir = crate::optimization::disk_cache::test_dir("metadata");
+        let result = crate::types::SearchResult {
+            title: "Tokio".to_string(),
+            url: "https://tokio.rs".to_string(),
+            snippet: None,
+            content: None,
+            published_date: None,
+            metadata: BTreeMap::from([
+                ("favicon".to_string(), "https://tokio.rs/favicon.ico".to_string()),
+                ("author".to_string(), "Tokio Contributors".to_string()),
+            ]),
+        };
+        {
+            let cache = SearchCache::with_disk(10, 3600, &dir).unwrap();
+            cache
+                .store("tokio", &[CachedSearchResult::from_search_result(&result)])
+                .unwrap();
+        }
+
+        let cache = SearchCache::with_disk(10, 3600, &dir).unwrap();
+        let restored = cache.get("tokio").unwrap()[0].to_search_result();
+        assert_eq!(restored.metadata, result.metadata);
+        cache.clear();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
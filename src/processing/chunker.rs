@@ -0,0 +1,213 @@
+//! 分塊 - 將清理後的內容切成有重疊、受 token 上限約束的片段，供向量庫與本地索引使用
+//!
+//! 優先切在段落與句子邊界；單句超過上限時才在字元邊界硬切。
+//! 每個片段帶來源網址與在原文中的位元組位移，id 由網址與位移雜湊而來，重跑結果相同。
+
+use super::context_pruner::split_sentences;
+use super::tokenizer::{HeuristicTokenizer, Tokenizer};
+use crate::types::SearchResult;
+use bose_common::hash::fnv1a_hex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// 分塊配置
+#[derive(Debug, Clone)]
+pub struct ChunkerConfig {
+    /// 每個片段的 token 上限
+    pub max_tokens: usize,
+    /// 相鄰片段重疊的 token 數（以整句為單位，不超過此值）
+    pub overlap_tokens: usize,
+}
+
+impl Default for ChunkerConfig {
+    fn default() -> Self {
+        Self {
+            max_tokens: 512,
+            overlap_tokens: 64,
+        }
+    }
+}
+
+/// 一個片段
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Chunk {
+    /// 網址與位移的 FNV-1a 雜湊（16 位十六進位），同一來源重新分塊時不變
+    pub id: String,
+    pub source_url: String,
+    /// 在來源中的序號
+    pub index: usize,
+    /// 在原文中的位元組範圍 `[start, end)`
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+    pub tokens: usize,
+}
+
+/// 分塊器
+pub struct Chunker {
+    config: ChunkerConfig,
+    tokenizer: Arc<dyn Tokenizer>,
+}
+
+impl Chunker {
+    pub fn new(config: ChunkerConfig) -> Self {
+        Self {
+            config,
+            tokenizer: Arc::new(HeuristicTokenizer),
+        }
+    }
+
+    /// 改用下游嵌入模型的 tokenizer
+    pub fn with_tokenizer(mut self, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    /// 將 `content` 切成片段
+    pub fn chunk(&self, source_url: &str, content: &str) -> Vec<Chunk> {
+        let max = self.config.max_tokens.max(1);
+        let units = self.units(content, max);
+        let mut chunks = Vec::new();
+        let mut first = 0;
+
+        while first < units.len() {
+            // 盡量多放句子，至少一句
+            let mut last = first;
+            let mut tokens = units[first].2;
+            while last + 1 < units.len() {
+                let next = tokens + units[last + 1].2;
+                if next > max {
+                    break;
+                }
+                tokens = next;
+                last += 1;
+            }
+
+            let (start, end) = (units[first].0, units[last].1);
+            chunks.push(Chunk {
+                id: chunk_id(source_url, start, end),
+                source_url: source_url.to_string(),
+                index: chunks.len(),
+                start,
+                end,
+                text: content[start..end].to_string(),
+                tokens: self.tokenizer.count(&content[start..end]),
+            });
+            if last + 1 >= units.len() {
+                break;
+            }
+
+            // 往回取整句當作重疊，但一定要前進
+            let mut next_first = last + 1;
+            let mut overlap = 0;
+            while next_first > first + 1 && overlap + units[next_first - 1].2 <= self.config.overlap_tokens {
+                next_first -= 1;
+                overlap += units[next_first].2;
+            }
+            first = next_first;
+        }
+        chunks
+    }
+
+    /// 為搜尋結果的內容（沒有則用摘要）分塊
+    pub fn chunk_result(&self, result: &SearchResult) -> Vec<Chunk> {
+        let content = result.content.as_deref().or(result.snippet.as_deref()).unwrap_or("");
+        self.chunk(&result.url, content)
+    }
+
+    /// 句子單位 `(start, end, tokens)`；過長的句子硬切到上限以內
+    fn units(&self, content: &str, max: usize) -> Vec<(usize, usize, usize)> {
+        let base = content.as_ptr() as usize;
+        let mut units = Vec::new();
+        for sentence in content.split("\n\n").flat_map(split_sentences) {
+            let mut start = sentence.as_ptr() as usize - base;
+            let mut rest = sentence;
+            while !rest.is_empty() {
+                let mut piece = self.tokenizer.truncate(rest, max);
+                if piece.is_empty() {
+                    // 單一字元就超過上限：仍須前進
+                    let first = rest.chars().next().map_or(rest.len(), char::len_utf8);
+                    piece = &rest[..first];
+                }
+                units.push((start, start + piece.len(), self.tokenizer.count(piece)));
+                start += piece.len();
+                rest = &rest[piece.len()..];
+            }
+        }
+        units
+    }
+}
+
+/// FNV-1a（跨版本穩定），與磁碟快取的檔名雜湊相同
+fn chunk_id(source_url: &str, start: usize, end: usize) -> String {
+    fnv1a_hex(&format!("{}#{}-{}", source_url, start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTENT: &str = "Rust guarantees memory safety. It has no garbage collector.\n\n\
+                           Ownership tracks who frees memory. Borrowing allows references. \
+                           Lifetimes keep references valid.";
+
+    #[test]
+    fn test_chunks_respect_budget_and_overlap() {
+        let chunker = Chunker::new(ChunkerConfig {
+            max_tokens: 20,
+            overlap_tokens: 10,
+        });
+        let chunks = chunker.chunk("https://example.com/rust", CONTENT);
+
+        assert!(chunks.len() >= 3);
+        for (i, chunk) in chunks.iter().enumerate() {
+            assert_eq!(chunk.index, i);
+            assert!(chunk.tokens <= 20);
+            assert_eq!(&CONTENT[chunk.start..chunk.end], chunk.text);
+        }
+        // 相鄰片段重疊：下一片段從上一片段內開始
+        assert!(chunks[1].start < chunks[0].end);
+        assert!(chunks.last().unwrap().text.ends_with("valid."));
+    }
+
+    #[test]
+    fn test_stable_ids() {
+        let chunker = Chunker::new(ChunkerConfig::default());
+        let a = chunker.chunk("https://example.com", CONTENT);
+        let b = chunker.chunk("https://example.com", CONTENT);
+        let c = chunker.chunk("https://example.org", CONTENT);
+        assert_eq!(a.len(), 1);
+        assert_eq!(a[0].id, b[0].id);
+        assert_ne!(a[0].id, c[0].id);
+        assert_eq!(a[0].id.len(), 16);
+    }
+
+    #[test]
+    fn test_long_sentence_hard_split() {
+        let content = "繁體中文".repeat(30);
+        let chunker = Chunker::new(ChunkerConfig {
+            max_tokens: 50,
+            overlap_tokens: 0,
+        });
+        let chunks = chunker.chunk("u", &content);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks.iter().map(|c| c.tokens).collect::<Vec<_>>(), vec![50, 50, 20]);
+        assert_eq!(chunks.iter().map(|c| c.text.as_str()).collect::<String>(), content);
+    }
+
+    #[test]
+    fn test_chunk_result_and_empty() {
+        let chunker = Chunker::new(ChunkerConfig::default());
+        let result = SearchResult {
+            title: "t".into(),
+            url: "https://example.com".into(),
+            snippet: Some("Only a snippet.".into()),
+            content: None,
+            published_date: None,
+            metadata: Default::default(),
+        };
+        let chunks = chunker.chunk_result(&result);
+        assert_eq!(chunks[0].text, "Only a snippet.");
+        assert!(chunker.chunk("u", "").is_empty());
+    }
+}
@@ -1,4 +1,9 @@
+use super::tokenizer::{HeuristicTokenizer, Tokenizer};
+use crate::fetcher::metadata::PAYWALLED;
+use crate::types::SearchResult;
+use bose_common::text::truncate_chars;
 use std::collections::HashSet;
+use std::sync::Arc;
 
 /// 文本塊類型
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -17,6 +22,8 @@ struct TextBlock {
     #[allow(dead_code)]
     block_type: BlockType,
     priority: u32,
+    /// 在原文中的順序
+    position: usize,
 }
 
 impl TextBlock {
@@ -31,85 +38,185 @@ impl TextBlock {
             content,
             block_type,
             priority,
+            position: 0,
         }
     }
 
-    fn estimate_tokens(&self) -> usize {
-        self.content.len() / 4
+    fn estimate_tokens(&self, tokenizer: &dyn Tokenizer) -> usize {
+        tokenizer.count(&self.content)
+    }
+}
+
+/// 裁剪配置
+#[derive(Debug, Clone)]
+pub struct PrunerConfig {
+    /// Token 預算
+    pub max_tokens: usize,
+    /// 各區塊類型的優先度，高者先納入預算
+    pub heading_priority: u32,
+    pub code_priority: u32,
+    pub paragraph_priority: u32,
+    pub other_priority: u32,
+    /// 輸出維持原文順序；優先度只決定保留哪些區塊
+    pub preserve_order: bool,
+    /// 短於此字元數的段落直接捨棄（「分享」、「閱讀更多」之類的殘留）
+    pub min_block_chars: usize,
+    /// 長於此字元數的段落先截斷，避免單一長段落吃掉整個預算；程式碼超過則整塊捨棄
+    pub max_block_chars: Option<usize>,
+}
+
+impl Default for PrunerConfig {
+    fn default() -> Self {
+        Self {
+            max_tokens: 4000,
+            heading_priority: 100,
+            code_priority: 80,
+            paragraph_priority: 50,
+            other_priority: 10,
+            preserve_order: false,
+            min_block_chars: 0,
+            max_block_chars: None,
+        }
+    }
+}
+
+impl PrunerConfig {
+    fn priority(&self, block_type: &BlockType) -> u32 {
+        match block_type {
+            BlockType::Heading => self.heading_priority,
+            BlockType::Code => self.code_priority,
+            BlockType::Paragraph => self.paragraph_priority,
+            BlockType::Other => self.other_priority,
+        }
     }
 }
 
 /// 上下文裁剪器
 pub struct ContextPruner {
-    max_tokens: usize,
+    config: PrunerConfig,
+    tokenizer: Arc<dyn Tokenizer>,
+    /// 句子層級裁剪用的查詢詞；None 時只做區塊層級
+    sentence_terms: Option<Vec<String>>,
 }
 
 impl ContextPruner {
     pub fn new(max_tokens: usize) -> Self {
-        Self { max_tokens }
+        Self::with_config(PrunerConfig {
+            max_tokens,
+            ..PrunerConfig::default()
+        })
+    }
+
+    pub fn with_config(config: PrunerConfig) -> Self {
+        Self {
+            config,
+            tokenizer: Arc::new(HeuristicTokenizer),
+            sentence_terms: None,
+        }
+    }
+
+    /// 啟用句子層級裁剪：保留段落的首句與含查詢詞的句子，其餘刪除，
+    /// 省下的預算留給更多不同來源；截斷時也停在句尾而非字元中間
+    pub fn with_sentence_pruning(mut self, query: &str) -> Self {
+        self.sentence_terms = Some(query_terms(query));
+        self
+    }
+
+    /// 改用指定的 tokenizer 計算預算（例如下游模型的 `TiktokenTokenizer`）
+    pub fn with_tokenizer(mut self, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.tokenizer = tokenizer;
+        self
     }
 
     /// 裁剪內容到指定的 Token 預算
     pub fn prune(&self, content: &str) -> String {
         let blocks = self.split_into_blocks(content);
         let blocks = self.remove_duplicates(blocks);
-        let mut blocks = blocks;
+        let mut blocks = self.apply_config(blocks);
         self.rank_blocks(&mut blocks);
+        if let Some(terms) = &self.sentence_terms {
+            for block in blocks.iter_mut().filter(|b| b.block_type == BlockType::Paragraph) {
+                block.content = trim_sentences(&block.content, terms);
+            }
+        }
         self.truncate_to_budget(&blocks)
     }
 
+    /// 裁剪搜尋結果抓回的頁面文字；結果標記為付費牆時內容只是前導段落，
+    /// 整段捨棄（回傳 None），呼叫端保留摘要
+    pub fn prune_result(&self, result: &SearchResult, content: &str) -> Option<String> {
+        if result.metadata.get(PAYWALLED).is_some_and(|v| v == "true") {
+            return None;
+        }
+        Some(self.prune(content))
+    }
+
     fn split_into_blocks(&self, text: &str) -> Vec<TextBlock> {
         let mut blocks = Vec::new();
-        let lines: Vec<&str> = text.lines().collect();
         let mut current_paragraph = String::new();
+        // 進行中的程式碼區塊：保留原始縮排，整塊保留或捨棄
+        let mut code = String::new();
+        let mut fence: Option<(char, usize)> = None;
+        let mut blank_lines = 0;
 
-        for line in lines {
+        for line in text.lines() {
             let trimmed = line.trim();
 
-            if trimmed.is_empty() {
-                if !current_paragraph.is_empty() {
-                    blocks.push(TextBlock::new(
-                        current_paragraph.clone(),
-                        BlockType::Paragraph,
-                    ));
-                    current_paragraph.clear();
+            if let Some((marker, len)) = fence {
+                code.push('\n');
+                code.push_str(line.trim_end());
+                if trimmed.len() >= len && trimmed.chars().all(|c| c == marker) {
+                    fence = None;
+                    flush(&mut blocks, &mut code, BlockType::Code);
                 }
                 continue;
             }
 
-            if trimmed.starts_with('#')
-                || (trimmed.len() < 100
-                    && trimmed.chars().filter(|c| c.is_uppercase()).count()
-                        > trimmed.len() / 2)
-            {
-                if !current_paragraph.is_empty() {
-                    blocks.push(TextBlock::new(
-                        current_paragraph.clone(),
-                        BlockType::Paragraph,
-                    ));
-                    current_paragraph.clear();
+            if let Some(open) = fence_marker(trimmed) {
+                flush(&mut blocks, &mut current_paragraph, BlockType::Paragraph);
+                flush(&mut blocks, &mut code, BlockType::Code);
+                code.push_str(line.trim_end());
+                fence = Some(open);
+                continue;
+            }
+
+            if trimmed.is_empty() {
+                flush(&mut blocks, &mut current_paragraph, BlockType::Paragraph);
+                if !code.is_empty() {
+                    blank_lines += 1;
                 }
-                blocks.push(TextBlock::new(trimmed.to_string(), BlockType::Heading));
                 continue;
             }
 
-            let code_indicators = [
-                "{", "}", "()", "=>", "fn ", "def ", "class ", "import ", "const ", "let ",
-                "var ",
-            ];
-            let is_code = code_indicators
-                .iter()
-                .any(|&indicator| trimmed.contains(indicator));
+            // 段落外的縮排行視為程式碼；段落內的縮排只是換行續接
+            let indented = (line.starts_with("    ") || line.starts_with('\t'))
+                && current_paragraph.is_empty();
+            let is_heading = !indented
+                && (trimmed.starts_with('#')
+                    || (trimmed.len() < 100
+                        && trimmed.chars().filter(|c| c.is_uppercase()).count()
+                            > trimmed.len() / 2));
+            let is_code = indented
+                || (!is_heading
+                    && CODE_INDICATORS
+                        .iter()
+                        .any(|&indicator| trimmed.contains(indicator)));
 
             if is_code {
-                if !current_paragraph.is_empty() {
-                    blocks.push(TextBlock::new(
-                        current_paragraph.clone(),
-                        BlockType::Paragraph,
-                    ));
-                    current_paragraph.clear();
+                flush(&mut blocks, &mut current_paragraph, BlockType::Paragraph);
+                if !code.is_empty() {
+                    code.push_str(&"\n".repeat(blank_lines + 1));
                 }
-                blocks.push(TextBlock::new(trimmed.to_string(), BlockType::Code));
+                code.push_str(line.trim_end());
+                blank_lines = 0;
+                continue;
+            }
+            flush(&mut blocks, &mut code, BlockType::Code);
+            blank_lines = 0;
+
+            if is_heading {
+                flush(&mut blocks, &mut current_paragraph, BlockType::Paragraph);
+                blocks.push(TextBlock::new(trimmed.to_string(), BlockType::Heading));
                 continue;
             }
 
@@ -119,13 +226,39 @@ impl ContextPruner {
             current_paragraph.push_str(trimmed);
         }
 
-        if !current_paragraph.is_empty() {
-            blocks.push(TextBlock::new(current_paragraph, BlockType::Paragraph));
+        flush(&mut blocks, &mut current_paragraph, BlockType::Paragraph);
+        // 未關閉的圍欄：補上結尾，避免後續內容被當成程式碼
+        if let Some((marker, len)) = fence {
+            code.push('\n');
+            code.extend(std::iter::repeat_n(marker, len));
         }
+        flush(&mut blocks, &mut code, BlockType::Code);
 
         blocks
     }
 
+    /// 套用優先度與長度門檻，並記下原文順序
+    fn apply_config(&self, blocks: Vec<TextBlock>) -> Vec<TextBlock> {
+        let config = &self.config;
+        let mut result = Vec::with_capacity(blocks.len());
+        for (position, mut block) in blocks.into_iter().enumerate() {
+            let chars = block.content.chars().count();
+            if block.block_type == BlockType::Paragraph && chars < config.min_block_chars {
+                continue;
+            }
+            if let Some(max) = config.max_block_chars.filter(|&max| chars > max) {
+                if block.block_type == BlockType::Code {
+                    continue;
+                }
+                block.content = format!("{}...", truncate_chars(&block.content, max).trim_end());
+            }
+            block.priority = config.priority(&block.block_type);
+            block.position = position;
+            result.push(block);
+        }
+        result
+    }
+
     fn rank_blocks(&self, blocks: &mut [TextBlock]) {
         blocks.sort_by(|a, b| b.priority.cmp(&a.priority));
     }
@@ -135,24 +268,41 @@ impl ContextPruner {
         let mut total_tokens = 0;
 
         for block in blocks {
-            let block_tokens = block.estimate_tokens();
+            let block_tokens = block.estimate_tokens(self.tokenizer.as_ref());
 
-            if total_tokens + block_tokens <= self.max_tokens {
-                result.push(block.content.clone());
+            if total_tokens + block_tokens <= self.config.max_tokens {
+                result.push((block.position, block.content.clone()));
                 total_tokens += block_tokens;
+            } else if block.block_type == BlockType::Code {
+                // 程式碼不截斷，放不下就整塊略過
+                continue;
             } else {
-                let remaining_tokens = self.max_tokens.saturating_sub(total_tokens);
-                let remaining_chars = remaining_tokens * 4;
-
-                if remaining_chars > 100 {
-                    let truncated: String =
-                        block.content.chars().take(remaining_chars).collect();
-                    result.push(format!("{}...", truncated));
+                let remaining_tokens = self.config.max_tokens.saturating_sub(total_tokens);
+
+                if remaining_tokens > 25 {
+                    let truncated = self.tokenizer.truncate(&block.content, remaining_tokens);
+                    if self.sentence_terms.is_none() {
+                        result.push((block.position, format!("{}...", truncated)));
+                    } else {
+                        // 只留完整的句子
+                        let complete = join_sentences(
+                            split_sentences(truncated)
+                                .into_iter()
+                                .filter(|s| s.ends_with(SENTENCE_ENDS)),
+                        );
+                        if !complete.is_empty() {
+                            result.push((block.position, complete));
+                        }
+                    }
                 }
                 break;
             }
         }
 
+        if self.config.preserve_order {
+            result.sort_by_key(|(position, _)| *position);
+        }
+        let result: Vec<String> = result.into_iter().map(|(_, content)| content).collect();
         result.join("\n\n")
     }
 
@@ -161,7 +311,7 @@ impl ContextPruner {
         let mut result = Vec::new();
 
         for block in blocks {
-            let key: String = block.content.chars().take(100).collect();
+            let key = truncate_chars(&block.content, 100).to_string();
 
             if seen.insert(key) {
                 result.push(block);
@@ -172,6 +322,94 @@ impl ContextPruner {
     }
 }
 
+const CODE_INDICATORS: &[&str] = &[
+    "{", "}", "()", "=>", "fn ", "def ", "class ", "import ", "const ", "let ", "var ",
+];
+
+/// 將累積的文字推為一個區塊並清空
+fn flush(blocks: &mut Vec<TextBlock>, buf: &mut String, block_type: BlockType) {
+    if !buf.is_empty() {
+        blocks.push(TextBlock::new(std::mem::take(buf), block_type));
+    }
+}
+
+/// 圍欄開頭（至少三個 ` 或 ~，後面可接語言）
+fn fence_marker(trimmed: &str) -> Option<(char, usize)> {
+    let marker = trimmed.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let len = trimmed.chars().take_while(|&c| c == marker).count();
+    (len >= 3).then_some((marker, len))
+}
+
+const SENTENCE_ENDS: &[char] = &['.', '!', '?', '。', '！', '？'];
+
+/// 依句尾標點切句（保留標點）；英文句號後須接空白，避免切開 `3.14` 或網址
+pub(crate) fn split_sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, ch)) = chars.next() {
+        if !ch.is_ascii_punctuation() && !SENTENCE_ENDS.contains(&ch) {
+            continue;
+        }
+        let end = i + ch.len_utf8();
+        let boundary = match ch {
+            '。' | '！' | '？' => true,
+            '.' | '!' | '?' => chars.peek().is_none_or(|(_, next)| next.is_whitespace()),
+            _ => false,
+        };
+        if boundary {
+            let sentence = text[start..end].trim();
+            if !sentence.is_empty() {
+                sentences.push(sentence);
+            }
+            start = end;
+        }
+    }
+    let rest = text[start..].trim();
+    if !rest.is_empty() {
+        sentences.push(rest);
+    }
+    sentences
+}
+
+/// 查詢詞：英數字詞轉小寫（至少兩個字元），中日韓文字取相鄰兩字
+pub(crate) fn query_terms(query: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for word in query.split(|c: char| !c.is_alphanumeric()) {
+        let chars: Vec<char> = word.chars().collect();
+        if chars.iter().any(|c| !c.is_ascii()) && chars.len() >= 2 {
+            terms.extend(chars.windows(2).map(|w| w.iter().collect::<String>()));
+        } else if chars.len() >= 2 {
+            terms.push(word.to_lowercase());
+        }
+    }
+    terms
+}
+
+/// 保留首句與含查詢詞的句子
+fn trim_sentences(paragraph: &str, terms: &[String]) -> String {
+    let sentences = split_sentences(paragraph);
+    if sentences.len() <= 1 || terms.is_empty() {
+        return paragraph.to_string();
+    }
+    join_sentences(sentences.into_iter().enumerate().filter_map(|(i, sentence)| {
+        let lower = sentence.to_lowercase();
+        (i == 0 || terms.iter().any(|t| lower.contains(t.as_str()))).then_some(sentence)
+    }))
+}
+
+/// 接回句子；中日韓句子之間不加空白
+pub(crate) fn join_sentences<'a>(sentences: impl Iterator<Item = &'a str>) -> String {
+    let mut text = String::new();
+    for sentence in sentences {
+        if !text.is_empty() && text.ends_with(|c: char| c.is_ascii()) {
+            text.push(' ');
+        }
+        text.push_str(sentence);
+    }
+    text
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -189,6 +427,41 @@ mod tests {
         assert_eq!(blocks[3].block_type, BlockType::Paragraph);
     }
 
+    #[test]
+    fn test_fenced_code_is_one_block() {
+        let pruner = ContextPruner::new(1000);
+        let text = "Intro text.\n```python\n# not a heading\ndef f():\n\n    return 1\n```\nAfter the code.";
+        let blocks = pruner.split_into_blocks(text);
+
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[1].block_type, BlockType::Code);
+        assert_eq!(blocks[1].content, "```python\n# not a heading\ndef f():\n\n    return 1\n```");
+        assert_eq!(blocks[2].block_type, BlockType::Paragraph);
+    }
+
+    #[test]
+    fn test_indented_and_unclosed_code() {
+        let pruner = ContextPruner::new(1000);
+        let text = "Example:\n\n    let x = 1;\n\n    # comment\n    x + 1\n\nDone.\n~~~\nleft open";
+        let blocks = pruner.split_into_blocks(text);
+
+        assert_eq!(blocks.len(), 4);
+        assert_eq!(blocks[1].content, "    let x = 1;\n\n    # comment\n    x + 1");
+        assert_eq!(blocks[2].content, "Done.");
+        assert_eq!(blocks[3].content, "~~~\nleft open\n~~~");
+    }
+
+    #[test]
+    fn test_code_dropped_atomically() {
+        let pruner = ContextPruner::new(60);
+        let code = format!("```\n{}\n```", "let value = compute();\n".repeat(20));
+        let content = format!("{code}\n\n{}", "word ".repeat(40));
+        let result = pruner.prune(&content);
+
+        assert!(!result.contains("```"));
+        assert!(result.starts_with("word word"));
+    }
+
     #[test]
     fn test_rank_blocks() {
         let pruner = ContextPruner::new(1000);
@@ -205,6 +478,59 @@ mod tests {
         assert_eq!(blocks[2].block_type, BlockType::Paragraph);
     }
 
+    #[test]
+    fn test_config_priorities_and_order() {
+        let content = "Intro paragraph about the topic.\n\n# Heading\n\nfn main() {}\n\nClosing paragraph.";
+        let config = PrunerConfig {
+            max_tokens: 1000,
+            paragraph_priority: 200,
+            ..PrunerConfig::default()
+        };
+        let reordered = ContextPruner::with_config(config.clone()).prune(content);
+        assert!(reordered.starts_with("Intro paragraph about the topic.\n\nClosing paragraph.\n\n# Heading"));
+
+        let ordered = ContextPruner::with_config(PrunerConfig {
+            preserve_order: true,
+            ..config
+        })
+        .prune(content);
+        assert_eq!(
+            ordered,
+            "Intro paragraph about the topic.\n\n# Heading\n\nfn main() {}\n\nClosing paragraph."
+        );
+    }
+
+    #[test]
+    fn test_preserve_order_keeps_high_priority_blocks() {
+        let content = format!("{}\n\n# Title\n\nShort tail.", "filler ".repeat(40));
+        let pruner = ContextPruner::with_config(PrunerConfig {
+            max_tokens: 40,
+            preserve_order: true,
+            ..PrunerConfig::default()
+        });
+        let result = pruner.prune(&content);
+        // 標題優先納入，但輸出仍排在段落之後
+        assert!(result.starts_with("filler filler"));
+        assert!(result.ends_with("...\n\n# Title"));
+        assert!(!result.contains("Short tail"));
+    }
+
+    #[test]
+    fn test_block_length_thresholds() {
+        let content = format!("Share\n\n{}\n\nKeep this paragraph.", "long ".repeat(50));
+        let pruner = ContextPruner::with_config(PrunerConfig {
+            max_tokens: 1000,
+            min_block_chars: 10,
+            max_block_chars: Some(20),
+            preserve_order: true,
+            ..PrunerConfig::default()
+        });
+        assert_eq!(
+            pruner.prune(&content),
+            "long long long long...\n\nKeep this paragraph."
+        );
+    }
+
     #[test]
     fn test_remove_duplicates() {
         let pruner = ContextPruner::new(1000);
@@ -265,6 +591,73 @@ This is a very important paragraph with lots of information.
     #[test]
     fn test_estimate_tokens() {
         let block = TextBlock::new("A".repeat(400), BlockType::Paragraph);
-        assert_eq!(block.estimate_tokens(), 100);
+        assert_eq!(block.estimate_tokens(&HeuristicTokenizer), 100);
+    }
+
+    #[test]
+    fn test_split_sentences() {
+        assert_eq!(
+            split_sentences("Pi is 3.14. See https://a.com/x.y for more! Done"),
+            vec!["Pi is 3.14.", "See https://a.com/x.y for more!", "Done"]
+        );
+        assert_eq!(split_sentences("第一句。第二句！"), vec!["第一句。", "第二句！"]);
+    }
+
+    #[test]
+    fn test_sentence_pruning() {
+        let content = "Rust is a systems language. The weather was nice that day. \
+                       Ownership rules prevent data races. Lunch was pasta.\n\n\
+                       所有權是 Rust 的核心。今天天氣很好。借用檢查器負責檢查所有權。";
+        let pruner = ContextPruner::new(1000).with_sentence_pruning("rust ownership 所有權");
+        let result = pruner.prune(content);
+        assert_eq!(
+            result,
+            "Rust is a systems language. Ownership rules prevent data races.\n\n\
+             所有權是 Rust 的核心。借用檢查器負責檢查所有權。"
+        );
+    }
+
+    #[test]
+    fn test_sentence_mode_truncates_at_sentence_end() {
+        let content = "Point one about rust. Point two about rust. Point six about rust. \
+                       Point four about rust. Point five about rust.";
+        let pruner = ContextPruner::new(30).with_sentence_pruning("rust");
+        assert_eq!(
+            pruner.prune(content),
+            "Point one about rust. Point two about rust. Point six about rust. Point four about rust."
+        );
+    }
+
+    #[test]
+    fn test_custom_tokenizer_budget() {
+        struct PerChar;
+        impl Tokenizer for PerChar {
+            fn count(&self, text: &str) -> usize {
+                text.chars().count()
+            }
+        }
+
+        let content = format!("# 標題\n\n{}", "內容".repeat(50));
+        let pruner = ContextPruner::new(40).with_tokenizer(Arc::new(PerChar));
+        let result = pruner.prune(&content);
+        assert_eq!(result, format!("# 標題\n\n{}...", "內容".repeat(18)));
+    }
+
+    #[test]
+    fn test_prune_result_drops_paywalled_teaser() {
+        let mut result = SearchResult {
+            title: "Article".to_string(),
+            url: "https://news.example/article".to_string(),
+            snippet: Some("Summary".to_string()),
+            content: None,
+            published_date: None,
+            metadata: Default::default(),
+        };
+        let pruner = ContextPruner::new(100);
+        let teaser = "Only the first paragraph of the story is visible.";
+        assert_eq!(pruner.prune_result(&result, teaser).as_deref(), Some(teaser));
+
+        result.metadata.insert(PAYWALLED.to_string(), "true".to_string());
+        assert_eq!(pruner.prune_result(&result, teaser), None);
     }
 }
\ No newline at end of file
@@ -5,10 +5,32 @@ impl HtmlCleaner {
     /// 清理 HTML 內容
     pub fn clean(html: &str) -> String {
         let text = Self::remove_tags(html);
+        let text = Self::decode_entities(&text);
         let text = Self::remove_noise(&text);
         Self::normalize_whitespace(&text)
     }
 
+    /// 擷取主要內容：啟用 `readability` feature 時依 DOM 結構找出文章主體，
+    /// 找不到主體時退回 `clean`
+    pub fn extract(html: &str) -> String {
+        #[cfg(feature = "readability")]
+        if let Some(text) = super::readability::extract_main_content(html) {
+            return text;
+        }
+        Self::clean(html)
+    }
+
+    /// 同 `clean`，另外回傳頁面中的超連結（相對網址以 `base_url` 解析），供後續追蹤引用
+    pub fn clean_with_links(html: &str, base_url: &str) -> (String, Vec<super::Link>) {
+        (Self::clean(html), super::extract_links(html, base_url))
+    }
+
+    /// 轉為 Markdown，保留標題、清單、連結、表格與程式碼區塊，
+    /// 讓 `ContextPruner` 能依區塊類型排序
+    pub fn to_markdown(html: &str) -> String {
+        super::markdown::html_to_markdown(html)
+    }
+
     /// 移除 HTML 標籤（狀態機實作）
     fn remove_tags(text: &str) -> String {
         let mut result = String::with_capacity(text.len());
@@ -40,6 +62,13 @@ impl HtmlCleaner {
         result
     }
 
+    /// 解碼 HTML 實體（`&amp;`、`&nbsp;`、`&#8217;`、`&#x2019;`）
+    ///
+    /// 在移除標籤之後執行，`&lt;script&gt;` 因此保留為文字而不會被當成標籤。
+    pub fn decode_entities(text: &str) -> String {
+        bose_common::html::decode_entities(text)
+    }
+
     /// 正規化空白字元
     fn normalize_whitespace(text: &str) -> String {
         let mut result = String::with_capacity(text.len());
@@ -154,6 +183,19 @@ mod tests {
         assert!(!result.contains("console.log"));
     }
 
+    #[test]
+    fn test_clean_decodes_after_tags() {
+        let html = "<p>Use &lt;script&gt; tags&nbsp;&nbsp;carefully &amp; safely</p>";
+        assert_eq!(HtmlCleaner::clean(html), "Use <script> tags carefully & safely");
+    }
+
+    #[test]
+    fn test_extract_falls_back() {
+        // 沒有足夠的主體內容時與 clean 相同
+        let html = "<p>Short &amp; sweet</p>";
+        assert_eq!(HtmlCleaner::extract(html), HtmlCleaner::clean(html));
+    }
+
     #[test]
     fn test_empty_input() {
         let result = HtmlCleaner::clean("");
@@ -0,0 +1,117 @@
+//! 外連擷取 - 清理頁面時一併取出超連結（錨點文字 + 絕對網址）
+//!
+//! 供深度研究與爬蟲從排名靠前的結果繼續追蹤引用來源。
+
+use super::markdown::{attr, tokenize, Token};
+use super::HtmlCleaner;
+use reqwest::Url;
+use std::collections::HashSet;
+
+/// 頁面中的一個超連結
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// 錨點文字（空白已正規化）；圖片連結取 `alt`
+    pub text: String,
+    /// 已解析為絕對網址，去掉 `#fragment`
+    pub url: String,
+}
+
+/// 擷取頁面中的 http(s) 連結，相對網址以 `base_url`（或頁面的 `<base href>`）解析；
+/// 同一網址只保留第一次出現
+pub fn extract_links(html: &str, base_url: &str) -> Vec<Link> {
+    let mut base = base_url.to_string();
+    let mut links = Vec::new();
+    let mut seen = HashSet::new();
+    // 未關閉的 <a>：(href, 文字)
+    let mut open: Option<(Option<String>, String)> = None;
+
+    for token in tokenize(html) {
+        match token {
+            Token::Open { name, attrs } if name == "base" => {
+                if let Some(href) = attr(attrs, "href").and_then(|h| resolve_url(base_url, &h)) {
+                    base = href;
+                }
+            }
+            Token::Open { name, attrs } if name == "a" => {
+                open = Some((attr(attrs, "href"), String::new()));
+            }
+            Token::Open { name, attrs } if name == "img" => {
+                if let (Some((_, text)), Some(alt)) = (&mut open, attr(attrs, "alt")) {
+                    text.push(' ');
+                    text.push_str(&alt);
+                }
+            }
+            Token::Text(raw) => {
+                if let Some((_, text)) = &mut open {
+                    text.push_str(&HtmlCleaner::decode_entities(raw));
+                }
+            }
+            Token::Close(name) if name == "a" => {
+                let Some((Some(href), text)) = open.take() else { continue };
+                let Some(url) = resolve_url(&base, &href) else { continue };
+                if seen.insert(url.clone()) {
+                    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                    links.push(Link { text, url });
+                }
+            }
+            _ => {}
+        }
+    }
+    links
+}
+
+/// 以 `base` 解析 `href`（`Url::join`，與 `fetcher::metadata` 相同語意）；
+/// 非 http(s) 連結（`mailto:`、`javascript:`、純 `#錨點`）回傳 None
+pub fn resolve_url(base: &str, href: &str) -> Option<String> {
+    let href = href.trim();
+    if href.is_empty() || href.starts_with('#') {
+        return None;
+    }
+    let base = Url::parse(base).ok();
+    let mut url = Url::options().base_url(base.as_ref()).parse(href).ok()?;
+    if !matches!(url.scheme(), "http" | "https") || url.host().is_none() {
+        return None;
+    }
+    url.set_fragment(None);
+    Some(url.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_url() {
+        let base = "https://Example.com/docs/guide/intro.html?x=1";
+        assert_eq!(resolve_url(base, "setup.html").as_deref(), Some("https://example.com/docs/guide/setup.html"));
+        assert_eq!(resolve_url(base, "../api/").as_deref(), Some("https://example.com/docs/api/"));
+        assert_eq!(resolve_url(base, "/about#team").as_deref(), Some("https://example.com/about"));
+        assert_eq!(resolve_url(base, "//cdn.example.org/a").as_deref(), Some("https://cdn.example.org/a"));
+        assert_eq!(resolve_url(base, "?page=2").as_deref(), Some("https://example.com/docs/guide/intro.html?page=2"));
+        assert_eq!(resolve_url(base, "HTTP://Other.org").as_deref(), Some("http://other.org/"));
+        assert_eq!(resolve_url("https://a.com", "b/./c/../d").as_deref(), Some("https://a.com/b/d"));
+        assert_eq!(resolve_url(base, "#section"), None);
+        assert_eq!(resolve_url(base, "mailto:me@example.com"), None);
+        assert_eq!(resolve_url(base, "javascript:void(0)"), None);
+    }
+
+    #[test]
+    fn test_extract_links() {
+        let html = r#"<html><head><base href="/blog/"></head><body>
+            <p>See <a href="post-1">the &amp; first   post</a> and
+            <a href="https://rust-lang.org/">Rust</a>.</p>
+            <a href="post-1#comments">again</a>
+            <a href="/feed"><img src="rss.png" alt="RSS feed"></a>
+            <a href="mailto:x@y.z">mail</a><a>no href</a>
+        </body></html>"#;
+        let links = extract_links(html, "https://example.com/index.html");
+        assert_eq!(
+            links,
+            vec![
+                Link { text: "the & first post".into(), url: "https://example.com/blog/post-1".into() },
+                Link { text: "Rust".into(), url: "https://rust-lang.org/".into() },
+                Link { text: "RSS feed".into(), url: "https://example.com/feed".into() },
+            ]
+        );
+    }
+}
@@ -0,0 +1,490 @@
+//! HTML → Markdown 轉換 - 保留標題、清單、連結、表格與程式碼區塊
+//!
+//! 與 `HtmlCleaner::clean` 相同不依賴 DOM 函式庫，以標籤串流逐一轉換；
+//! 結構保留下來後，`ContextPruner` 才分得出標題、程式碼與段落。
+
+use super::tables::Table;
+use super::HtmlCleaner;
+
+/// 內容整段略過的標籤
+const SKIP_TAGS: &[&str] = &["head", "noscript", "template", "svg", "iframe", "select", "button"];
+
+/// 內容為原始文字、需直接跳到結束標籤的標籤
+const RAW_TAGS: &[&str] = &["script", "style"];
+
+/// 只需換段的區塊標籤
+const BLOCK_TAGS: &[&str] = &[
+    "p", "div", "section", "article", "main", "header", "footer", "aside", "nav", "figure",
+    "figcaption", "dl", "dt", "dd", "form", "address", "details", "summary",
+];
+
+pub(crate) enum Token<'a> {
+    Text(&'a str),
+    Open { name: String, attrs: &'a str },
+    Close(String),
+}
+
+/// 將 HTML 轉為 Markdown
+pub(crate) fn html_to_markdown(html: &str) -> String {
+    let mut writer = Writer::default();
+    for token in tokenize(html) {
+        match token {
+            Token::Text(text) => writer.text(text),
+            Token::Open { name, attrs } => writer.open(&name, attrs),
+            Token::Close(name) => writer.close(&name),
+        }
+    }
+    finish(&writer.out)
+}
+
+/// 切成文字與標籤；註解、DOCTYPE 與 script / style 內容直接略過
+pub(crate) fn tokenize(html: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let is_tag = rest.starts_with('<')
+            && rest[1..]
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '/' || c == '!' || c == '?');
+        if !is_tag {
+            let first = rest.chars().next().map_or(1, char::len_utf8);
+            let end = rest[first..].find('<').map_or(rest.len(), |i| i + first);
+            tokens.push(Token::Text(&rest[..end]));
+            rest = &rest[end..];
+            continue;
+        }
+
+        let Some(end) = tag_end(rest) else {
+            tokens.push(Token::Text(rest));
+            break;
+        };
+        let inner = &rest[1..end];
+        rest = &rest[end + 1..];
+        if inner.starts_with(['!', '?']) {
+            continue;
+        }
+
+        let (closing, inner) = match inner.strip_prefix('/') {
+            Some(inner) => (true, inner),
+            None => (false, inner),
+        };
+        let name_len = inner
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(inner.len());
+        let name = inner[..name_len].to_ascii_lowercase();
+        if closing {
+            tokens.push(Token::Close(name));
+            continue;
+        }
+        if RAW_TAGS.contains(&name.as_str()) {
+            let close = format!("</{name}");
+            rest = rest
+                .to_ascii_lowercase()
+                .find(&close)
+                .and_then(|i| rest[i..].find('>').map(|j| &rest[i + j + 1..]))
+                .unwrap_or("");
+            continue;
+        }
+        let attrs = inner[name_len..].trim_end_matches('/');
+        tokens.push(Token::Open { name, attrs });
+    }
+    tokens
+}
+
+/// 標籤結尾 `>` 的位置；略過引號內的 `>`
+fn tag_end(s: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in s.char_indices().skip(1) {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// 取出屬性值（`href="…"`、`href='…'`、`href=…`）
+pub(crate) fn attr(attrs: &str, name: &str) -> Option<String> {
+    let lower = attrs.to_ascii_lowercase();
+    let mut from = 0;
+    while let Some(pos) = lower[from..].find(name) {
+        let start = from + pos;
+        from = start + name.len();
+        let boundary = start == 0 || lower.as_bytes()[start - 1].is_ascii_whitespace();
+        let value = attrs[from..].trim_start();
+        if !boundary || !value.starts_with('=') {
+            continue;
+        }
+        let value = value[1..].trim_start();
+        let raw = match value.chars().next() {
+            Some(q @ ('"' | '\'')) => value[1..].split(q).next().unwrap_or(""),
+            _ => value.split(|c: char| c.is_whitespace()).next().unwrap_or(""),
+        };
+        return Some(HtmlCleaner::decode_entities(raw));
+    }
+    None
+}
+
+/// `class="language-rust"` / `lang-rust` 的語言名稱
+fn code_language(attrs: &str) -> Option<String> {
+    attr(attrs, "class")?
+        .split_whitespace()
+        .find_map(|class| {
+            class
+                .strip_prefix("language-")
+                .or_else(|| class.strip_prefix("lang-"))
+        })
+        .map(str::to_string)
+}
+
+#[derive(Default)]
+struct TableBuf {
+    rows: Vec<Vec<String>>,
+    cell: Option<String>,
+}
+
+#[derive(Default)]
+struct Writer {
+    out: String,
+    /// 巢狀清單：None 為項目符號，Some(n) 為下一個編號
+    lists: Vec<Option<usize>>,
+    /// 未關閉的連結：(文字起點, href)
+    links: Vec<(usize, Option<String>)>,
+    /// 程式碼區塊：(內容起點, 語言)
+    pre: Option<(usize, String)>,
+    quotes: Vec<usize>,
+    table: Option<TableBuf>,
+    skip_depth: usize,
+}
+
+impl Writer {
+    /// 目前寫入的位置：表格儲存格或輸出
+    fn buf(&mut self) -> &mut String {
+        match self.table.as_mut().and_then(|t| t.cell.as_mut()) {
+            Some(cell) => cell,
+            None => &mut self.out,
+        }
+    }
+
+    fn in_cell(&self) -> bool {
+        self.table.as_ref().is_some_and(|t| t.cell.is_some())
+    }
+
+    /// 確保接下來從新段落開始
+    fn block(&mut self) {
+        if self.in_cell() {
+            self.inline_space();
+            return;
+        }
+        let trailing = self.out.len() - self.out.trim_end_matches('\n').len();
+        if !self.out.is_empty() && trailing < 2 {
+            self.out.push_str(if trailing == 1 { "\n" } else { "\n\n" });
+        }
+    }
+
+    fn inline_space(&mut self) {
+        let buf = self.buf();
+        if !buf.is_empty() && !buf.ends_with([' ', '\n']) {
+            buf.push(' ');
+        }
+    }
+
+    fn push(&mut self, s: &str) {
+        self.buf().push_str(s);
+    }
+
+    fn text(&mut self, raw: &str) {
+        if self.skip_depth > 0 {
+            return;
+        }
+        let decoded = HtmlCleaner::decode_entities(raw);
+        if self.pre.is_some() {
+            self.out.push_str(&decoded);
+            return;
+        }
+        let mut collapsed = String::with_capacity(decoded.len());
+        let mut space = decoded.starts_with(char::is_whitespace);
+        for word in decoded.split_whitespace() {
+            if space {
+                collapsed.push(' ');
+            }
+            collapsed.push_str(word);
+            space = true;
+        }
+        if decoded.ends_with(char::is_whitespace) && !collapsed.is_empty() {
+            collapsed.push(' ');
+        }
+        let buf = self.buf();
+        // 行首不留空白
+        let collapsed = if buf.is_empty() || buf.ends_with([' ', '\n']) {
+            collapsed.trim_start()
+        } else {
+            collapsed.as_str()
+        };
+        buf.push_str(collapsed);
+    }
+
+    fn open(&mut self, name: &str, attrs: &str) {
+        if SKIP_TAGS.contains(&name) {
+            self.skip_depth += 1;
+            return;
+        }
+        if self.skip_depth > 0 {
+            return;
+        }
+        if let Some((_, lang)) = &mut self.pre {
+            // 程式碼區塊內只看 <code class="language-…"> 的語言
+            if name == "code" && lang.is_empty() {
+                *lang = code_language(attrs).unwrap_or_default();
+            }
+            return;
+        }
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.block();
+                let level = usize::from(name.as_bytes()[1] - b'0');
+                if !self.in_cell() {
+                    self.push(&format!("{} ", "#".repeat(level)));
+                }
+            }
+            "p" if !self.lists.is_empty() => self.inline_space(),
+            "ul" | "ol" => {
+                if self.lists.is_empty() {
+                    self.block();
+                }
+                let start = attr(attrs, "start").and_then(|s| s.parse().ok()).unwrap_or(1);
+                self.lists.push((name == "ol").then_some(start));
+            }
+            "li" => {
+                let depth = self.lists.len().max(1);
+                let marker = match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        *n += 1;
+                        format!("{}. ", *n - 1)
+                    }
+                    _ => "- ".to_string(),
+                };
+                if !self.out.is_empty() && !self.out.ends_with('\n') {
+                    self.out.push('\n');
+                }
+                self.out.push_str(&"  ".repeat(depth - 1));
+                self.out.push_str(&marker);
+            }
+            "pre" => {
+                self.block();
+                self.pre = Some((self.out.len(), code_language(attrs).unwrap_or_default()));
+            }
+            "code" => self.push("`"),
+            "strong" | "b" => self.push("**"),
+            "em" | "i" => self.push("*"),
+            "a" => {
+                let start = self.buf().len();
+                self.links.push((start, attr(attrs, "href")));
+            }
+            "img" => {
+                let alt = attr(attrs, "alt").unwrap_or_default();
+                if let (false, Some(src)) = (alt.trim().is_empty(), attr(attrs, "src")) {
+                    self.push(&format!("![{}]({})", alt.trim(), src));
+                }
+            }
+            "br" if self.in_cell() => self.inline_space(),
+            "br" => self.out.push('\n'),
+            "hr" => {
+                self.block();
+                self.out.push_str("---");
+                self.block();
+            }
+            "blockquote" => {
+                self.block();
+                self.quotes.push(self.out.len());
+            }
+            "table" => {
+                self.block();
+                self.table = Some(TableBuf::default());
+            }
+            "tr" => {
+                if let Some(table) = &mut self.table {
+                    table.rows.push(Vec::new());
+                }
+            }
+            "td" | "th" => {
+                if let Some(table) = &mut self.table {
+                    if table.rows.is_empty() {
+                        table.rows.push(Vec::new());
+                    }
+                    table.cell = Some(String::new());
+                }
+            }
+            _ if BLOCK_TAGS.contains(&name) => {
+                if self.lists.is_empty() {
+                    self.block();
+                } else {
+                    self.inline_space();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn close(&mut self, name: &str) {
+        if SKIP_TAGS.contains(&name) {
+            self.skip_depth = self.skip_depth.saturating_sub(1);
+            return;
+        }
+        if self.skip_depth > 0 {
+            return;
+        }
+        if self.pre.is_some() && name != "pre" {
+            return;
+        }
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "p" | "div" | "section" | "article"
+                if self.lists.is_empty() =>
+            {
+                self.block();
+            }
+            "ul" | "ol" => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.block();
+                }
+            }
+            "pre" => {
+                if let Some((start, lang)) = self.pre.take() {
+                    let code = self.out.split_off(start);
+                    let code = code.trim_matches('\n');
+                    self.out.push_str(&format!("```{lang}\n{code}\n```"));
+                    self.block();
+                }
+            }
+            "code" => self.push("`"),
+            "strong" | "b" => self.push("**"),
+            "em" | "i" => self.push("*"),
+            "a" => {
+                let Some((start, href)) = self.links.pop() else { return };
+                let buf = self.buf();
+                if start > buf.len() {
+                    return;
+                }
+                let text = buf.split_off(start);
+                let text = text.trim();
+                match href.filter(|h| !h.is_empty() && !h.starts_with('#') && !h.starts_with("javascript:")) {
+                    Some(href) if !text.is_empty() => buf.push_str(&format!("[{text}]({href})")),
+                    _ => buf.push_str(text),
+                }
+            }
+            "blockquote" => {
+                if let Some(start) = self.quotes.pop() {
+                    let quoted = self.out.split_off(start);
+                    let quoted: Vec<String> = quoted
+                        .trim()
+                        .lines()
+                        .map(|line| if line.is_empty() { ">".to_string() } else { format!("> {line}") })
+                        .collect();
+                    self.out.push_str(&quoted.join("\n"));
+                    self.block();
+                }
+            }
+            "td" | "th" => {
+                if let Some(table) = &mut self.table
+                    && let (Some(cell), Some(row)) = (table.cell.take(), table.rows.last_mut())
+                {
+                    row.push(cell.trim().to_string());
+                }
+            }
+            "table" => {
+                if let Some(table) = self.table.take() {
+                    self.out.push_str(&Table::from_rows(table.rows, true).to_markdown());
+                    self.block();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 去掉行尾空白、合併多餘空行
+fn finish(out: &str) -> String {
+    let mut result = String::with_capacity(out.len());
+    let mut blank = 0;
+    let mut in_fence = false;
+    for line in out.lines() {
+        if line.starts_with("```") {
+            in_fence = !in_fence;
+        }
+        let line = if in_fence { line } else { line.trim_end() };
+        if line.trim().is_empty() && !in_fence {
+            blank += 1;
+            continue;
+        }
+        if !result.is_empty() {
+            result.push_str(if blank > 0 { "\n\n" } else { "\n" });
+        }
+        blank = 0;
+        result.push_str(line);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headings_and_paragraphs() {
+        let html = "<html><head><title>T</title></head><body><h1>Title</h1><p>First   para.</p>\
+                    <h2>Sub</h2><p>Second <b>bold</b> and <em>it</em>.</p><script>x()</script></body></html>";
+        assert_eq!(
+            html_to_markdown(html),
+            "# Title\n\nFirst para.\n\n## Sub\n\nSecond **bold** and *it*."
+        );
+    }
+
+    #[test]
+    fn test_lists_and_links() {
+        let html = r##"<ul><li>One <a href="https://a.com/?x=1&amp;y=2">link</a></li>
+            <li>Two<ol><li>inner</li><li>next</li></ol></li></ul><p>after <a href="#top">top</a></p>"##;
+        assert_eq!(
+            html_to_markdown(html),
+            "- One [link](https://a.com/?x=1&y=2)\n- Two\n  1. inner\n  2. next\n\nafter top"
+        );
+    }
+
+    #[test]
+    fn test_code_blocks() {
+        let html = "<p>Use <code>Vec::new()</code>:</p><pre><code class=\"language-rust\">fn main() {\n    \
+                    let v = vec![1 &lt; 2];\n}\n</code></pre><p>done</p>";
+        assert_eq!(
+            html_to_markdown(html),
+            "Use `Vec::new()`:\n\n```rust\nfn main() {\n    let v = vec![1 < 2];\n}\n```\n\ndone"
+        );
+    }
+
+    #[test]
+    fn test_table_and_quote() {
+        let html = "<table><tr><th>Plan</th><th>Price</th></tr><tr><td>Pro <b>x</b></td><td>$10 | mo</td></tr>\
+                    <tr><td>Free</td></tr></table><blockquote><p>Quoted</p><p>Two</p></blockquote>";
+        assert_eq!(
+            html_to_markdown(html),
+            "| Plan      | Price     |\n\
+             | --------- | --------- |\n\
+             | Pro **x** | $10 \\| mo |\n\
+             | Free      |           |\n\n\
+             > Quoted\n>\n> Two"
+        );
+    }
+
+    #[test]
+    fn test_attr() {
+        assert_eq!(attr(r#" class="a b" data-href='x' href=/p"#, "href"), Some("/p".to_string()));
+        assert_eq!(attr(r#" class="language-py""#, "lang"), None);
+    }
+}
@@ -1,5 +1,24 @@
 pub mod html_cleaner;
 pub mod context_pruner;
+pub mod tokenizer;
+pub mod chunker;
+pub mod snippet;
+pub mod markdown;
+pub mod tables;
+pub mod links;
+#[cfg(feature = "readability")]
+pub mod readability;
+#[cfg(feature = "pdf")]
+pub mod pdf;
 
 pub use html_cleaner::HtmlCleaner;
-pub use context_pruner::ContextPruner;
+pub use context_pruner::{ContextPruner, PrunerConfig};
+pub use tokenizer::{HeuristicTokenizer, Tokenizer};
+pub use chunker::{Chunk, Chunker, ChunkerConfig};
+#[cfg(feature = "tiktoken")]
+pub use tokenizer::TiktokenTokenizer;
+#[cfg(feature = "pdf")]
+pub use pdf::extract_pdf_text;
+pub use snippet::{fill_missing_snippets, query_snippet};
+pub use links::{extract_links, resolve_url, Link};
+pub use tables::{attach_tables, extract_tables, Table, TableFormat};
@@ -0,0 +1,113 @@
+//! PDF 文字擷取 - 論文、資安公告等 PDF 結果轉為可裁剪的純文字
+//!
+//! 抓取器遇到 `application/pdf` 時呼叫；pdf-extract 遇到損毀檔案可能 panic，這裡一律轉為錯誤。
+
+use crate::types::SearchError;
+
+/// 擷取並清理 PDF 文字；段落之間以空行分隔，可直接交給 `ContextPruner`
+pub fn extract_pdf_text(bytes: &[u8]) -> Result<String, SearchError> {
+    // 檔頭前允許少量垃圾位元組（部分伺服器會多送 BOM 或空白）
+    let header = &bytes[..bytes.len().min(1024)];
+    if !header.windows(5).any(|w| w == b"%PDF-") {
+        return Err(SearchError::ParseError("不是 PDF 檔案".into()));
+    }
+    let raw = std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem(bytes))
+        .map_err(|_| SearchError::ParseError("PDF 解析失敗".into()))?
+        .map_err(|e| SearchError::ParseError(format!("PDF 解析失敗: {}", e)))?;
+    Ok(clean_pdf_text(&raw))
+}
+
+/// 接回被版面切斷的行：連字號斷字合併、頁碼行移除、段落保留空行
+fn clean_pdf_text(raw: &str) -> String {
+    let mut paragraphs = Vec::new();
+    let mut current = String::new();
+
+    for line in raw.replace('\x0c', "\n\n").lines() {
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if line.is_empty() {
+            if !current.is_empty() {
+                paragraphs.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        // 單獨的頁碼
+        if line.chars().all(|c| c.is_ascii_digit()) && line.len() <= 4 {
+            continue;
+        }
+
+        let next_lower = line.chars().next().is_some_and(char::is_lowercase);
+        if current.ends_with('-') && next_lower {
+            current.pop();
+        } else if !current.is_empty() && !ends_with_cjk(&current) {
+            current.push(' ');
+        }
+        current.push_str(&line);
+    }
+    if !current.is_empty() {
+        paragraphs.push(current);
+    }
+    paragraphs.join("\n\n")
+}
+
+fn ends_with_cjk(text: &str) -> bool {
+    text.chars()
+        .next_back()
+        .is_some_and(|c| matches!(c as u32, 0x2E80..=0x9FFF | 0xAC00..=0xD7AF | 0xFF00..=0xFFEF))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 以正確的 xref 位移組出單頁 PDF
+    fn minimal_pdf(text: &str) -> Vec<u8> {
+        let stream = format!("BT /F1 12 Tf 72 712 Td ({}) Tj ET", text);
+        let objects = [
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Contents 4 0 R \
+             /Resources << /Font << /F1 5 0 R >> >> >>"
+                .to_string(),
+            format!("<< /Length {} >>\nstream\n{}\nendstream", stream.len(), stream),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+        ];
+        let mut pdf = String::from("%PDF-1.4\n");
+        let mut offsets = Vec::new();
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.push_str(&format!("{} 0 obj\n{}\nendobj\n", i + 1, object));
+        }
+        let xref = pdf.len();
+        pdf.push_str(&format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1));
+        for offset in offsets {
+            pdf.push_str(&format!("{:010} 00000 n \n", offset));
+        }
+        pdf.push_str(&format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        ));
+        pdf.into_bytes()
+    }
+
+    #[test]
+    fn test_extract_pdf_text() {
+        let text = extract_pdf_text(&minimal_pdf("Memory safety advisory")).unwrap();
+        assert_eq!(text, "Memory safety advisory");
+    }
+
+    #[test]
+    fn test_rejects_non_pdf() {
+        assert!(matches!(extract_pdf_text(b"<html></html>"), Err(SearchError::ParseError(_))));
+        assert!(extract_pdf_text(b"%PDF-1.4 garbage").is_err());
+    }
+
+    #[test]
+    fn test_clean_pdf_text() {
+        let raw = "Ownership and bor-\nrowing rules\nprevent data races.\n\n12\n\x0c所有權\n系統";
+        assert_eq!(
+            clean_pdf_text(raw),
+            "Ownership and borrowing rules prevent data races.\n\n所有權系統"
+        );
+    }
+}
@@ -0,0 +1,197 @@
+//! DOM 主體擷取（readability）- 依文件結構找出文章主體
+//!
+//! 導覽列、頁尾、側欄、表單依標籤與 ARIA role 整段移除，而不是比對關鍵字；
+//! 主體優先取 `<article>` / `<main>`，沒有時以段落文字量為容器評分。
+
+use scraper::{ElementRef, Html, Node, Selector};
+use std::collections::HashMap;
+
+/// 整段略過的標籤
+const SKIP_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "nav", "header", "footer", "aside", "form",
+    "button", "select", "iframe", "svg", "canvas", "dialog", "menu",
+];
+
+/// 整段略過的 ARIA role
+const SKIP_ROLES: &[&str] = &[
+    "navigation", "banner", "contentinfo", "complementary", "search", "dialog", "menu",
+];
+
+/// 換行分隔的區塊標籤
+const BLOCK_TAGS: &[&str] = &[
+    "p", "div", "section", "article", "main", "h1", "h2", "h3", "h4", "h5", "h6", "li", "ul",
+    "ol", "pre", "blockquote", "table", "tr", "dl", "dt", "dd", "figure", "figcaption",
+];
+
+/// 主體至少要有的字元數，否則視為沒找到
+const MIN_CONTENT_CHARS: usize = 140;
+
+/// 計分時忽略過短的段落（多半是按鈕文字或說明）
+const MIN_PARAGRAPH_CHARS: usize = 25;
+
+/// 擷取主要內容；找不到像樣的主體時回傳 None，由呼叫端退回 `HtmlCleaner::clean`
+pub fn extract_main_content(html: &str) -> Option<String> {
+    let document = Html::parse_document(html);
+    let candidate = landmark(&document).or_else(|| best_scored(&document))?;
+
+    let mut raw = String::new();
+    collect_text(candidate, &mut raw);
+    let text = raw
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    (text.chars().count() >= MIN_CONTENT_CHARS).then_some(text)
+}
+
+/// `<article>`、`<main>`、`role="main"` 中文字最多者
+fn landmark(document: &Html) -> Option<ElementRef<'_>> {
+    let selector = Selector::parse(r#"article, main, [role="main"]"#).unwrap();
+    document
+        .select(&selector)
+        .filter(|el| !skipped_with_ancestors(*el))
+        .map(|el| (text_len(el), el))
+        .filter(|(len, _)| *len >= MIN_CONTENT_CHARS)
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, el)| el)
+}
+
+/// 段落文字量計分：段落的分數加給父節點，一半加給祖父節點，再依連結密度折減
+fn best_scored(document: &Html) -> Option<ElementRef<'_>> {
+    let selector = Selector::parse("p, pre, blockquote, td").unwrap();
+    let mut scores = HashMap::new();
+
+    for paragraph in document.select(&selector) {
+        if skipped_with_ancestors(paragraph) {
+            continue;
+        }
+        let text: String = paragraph.text().collect();
+        let len = text.chars().count();
+        if len < MIN_PARAGRAPH_CHARS {
+            continue;
+        }
+        let score = 1.0 + text.matches([',', '，', '、']).count() as f64 + (len as f64 / 100.0).min(3.0);
+
+        let mut ancestors = paragraph.ancestors().filter_map(ElementRef::wrap);
+        if let Some(parent) = ancestors.next() {
+            *scores.entry(parent.id()).or_insert(0.0) += score;
+        }
+        if let Some(grandparent) = ancestors.next() {
+            *scores.entry(grandparent.id()).or_insert(0.0) += score / 2.0;
+        }
+    }
+
+    scores
+        .into_iter()
+        .filter_map(|(id, score)| {
+            let el = ElementRef::wrap(document.tree.get(id)?)?;
+            Some((score * (1.0 - link_density(el)), el))
+        })
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, el)| el)
+}
+
+fn skipped(el: ElementRef) -> bool {
+    let element = el.value();
+    SKIP_TAGS.contains(&element.name())
+        || element.attr("role").is_some_and(|role| SKIP_ROLES.contains(&role))
+        || element.attr("aria-hidden") == Some("true")
+        || element.attr("hidden").is_some()
+}
+
+fn skipped_with_ancestors(el: ElementRef) -> bool {
+    skipped(el) || el.ancestors().filter_map(ElementRef::wrap).any(skipped)
+}
+
+/// 子樹的可見文字，區塊之間以換行分隔
+fn collect_text(el: ElementRef, out: &mut String) {
+    for child in el.children() {
+        match child.value() {
+            Node::Text(text) => out.push_str(text),
+            Node::Element(element) => {
+                let Some(child) = ElementRef::wrap(child) else { continue };
+                if skipped(child) {
+                    continue;
+                }
+                if element.name() == "br" {
+                    out.push('\n');
+                    continue;
+                }
+                let block = BLOCK_TAGS.contains(&element.name());
+                if block {
+                    out.push('\n');
+                }
+                collect_text(child, out);
+                if block {
+                    out.push('\n');
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn text_len(el: ElementRef) -> usize {
+    let mut text = String::new();
+    collect_text(el, &mut text);
+    text.split_whitespace().map(|w| w.chars().count()).sum()
+}
+
+/// 連結文字佔全部文字的比例；導覽區塊接近 1
+fn link_density(el: ElementRef) -> f64 {
+    let total = text_len(el);
+    if total == 0 {
+        return 1.0;
+    }
+    let selector = Selector::parse("a").unwrap();
+    let links: usize = el.select(&selector).map(text_len).sum();
+    links as f64 / total as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &str = "Rust 的所有權系統在編譯期保證記憶體安全，不需要垃圾回收器。\
+        借用檢查器追蹤每個參考的生命週期，確保沒有懸垂指標，也沒有資料競爭。";
+
+    #[test]
+    fn test_prefers_article() {
+        let html = format!(
+            r#"<html><body>
+                <nav><a href="/">首頁</a><a href="/docs">文件</a></nav>
+                <article><h1>所有權</h1><p>{BODY}</p><aside>相關文章推薦</aside><p>{BODY}</p><p>{BODY}</p></article>
+                <footer>Copyright 2024</footer>
+            </body></html>"#
+        );
+        let text = extract_main_content(&html).unwrap();
+        assert!(text.starts_with("所有權\n"));
+        assert!(text.contains("借用檢查器"));
+        assert!(!text.contains("首頁"));
+        assert!(!text.contains("相關文章"));
+        assert!(!text.contains("Copyright"));
+    }
+
+    #[test]
+    fn test_scores_paragraph_container() {
+        let links: String = (0..20).map(|i| format!("<p><a href='/{i}'>連結 {i} 是一篇很長的推薦文章標題</a></p>")).collect();
+        let html = format!(
+            r#"<html><body>
+                <div class="sidebar">{links}</div>
+                <div id="content"><p>{BODY}</p><p>{BODY}</p><p>{BODY}</p></div>
+                <div role="contentinfo"><p>{BODY}</p></div>
+            </body></html>"#
+        );
+        let text = extract_main_content(&html).unwrap();
+        assert_eq!(text.lines().count(), 3);
+        assert!(!text.contains("連結"));
+    }
+
+    #[test]
+    fn test_no_content() {
+        assert_eq!(extract_main_content("<nav><a href='/'>首頁</a></nav>"), None);
+        assert_eq!(extract_main_content(""), None);
+    }
+}
@@ -0,0 +1,226 @@
+//! 查詢導向摘要 - 從全文中挑出與查詢最相關的片段
+//!
+//! Exa 的 `text`、Tavily 的 `raw_content` 只提供全文，snippet 常為空。
+//! 這裡以查詢詞出現的位置為錨點，選出涵蓋最多不同查詢詞的視窗作為摘要。
+
+use crate::types::SearchResult;
+use std::collections::HashSet;
+
+/// 預設摘要長度（字元）
+pub const DEFAULT_SNIPPET_CHARS: usize = 200;
+
+/// 切齊詞邊界時最多往前 / 往後找的字元數
+const BOUNDARY_SLACK: usize = 15;
+
+/// 從全文產生約 `max_chars` 字元、以查詢詞為中心的摘要
+///
+/// 沒有任何查詢詞命中時取開頭；全文為空時回傳 None。
+pub fn query_snippet(content: &str, query: &str, max_chars: usize) -> Option<String> {
+    let normalized = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    if normalized.is_empty() {
+        return None;
+    }
+
+    let chars: Vec<char> = normalized.chars().collect();
+    if chars.len() <= max_chars {
+        return Some(normalized);
+    }
+    // 逐字元轉小寫以維持索引一對一
+    let lower: Vec<char> = chars.iter().map(|c| lowercase(*c)).collect();
+
+    let terms = query_terms(query);
+    let mut hits: Vec<(usize, usize, usize)> = Vec::new(); // (位置, 詞長, 詞編號)
+    for (idx, term) in terms.iter().enumerate() {
+        if term.len() > lower.len() {
+            continue;
+        }
+        for pos in 0..=lower.len() - term.len() {
+            if lower[pos..pos + term.len()] == term[..] {
+                hits.push((pos, term.len(), idx));
+            }
+        }
+    }
+
+    let max_start = chars.len() - max_chars;
+    let mut best_start = 0;
+    let mut best_score = 0;
+    for &(pos, _, _) in &hits {
+        let start = pos.saturating_sub(max_chars / 4).min(max_start);
+        let end = start + max_chars;
+        let mut distinct = HashSet::new();
+        let mut count = 0;
+        for &(p, len, idx) in &hits {
+            if p >= start && p + len <= end {
+                distinct.insert(idx);
+                count += 1;
+            }
+        }
+        let score = distinct.len() * 100 + count;
+        if score > best_score {
+            best_score = score;
+            best_start = start;
+        }
+    }
+
+    let (start, end) = snap_to_words(&chars, best_start, best_start + max_chars);
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push('…');
+    }
+    snippet.extend(chars[start..end].iter());
+    let mut snippet = snippet.trim_end().to_string();
+    if end < chars.len() {
+        snippet.push('…');
+    }
+    Some(snippet)
+}
+
+/// 為缺少 snippet 但有全文的結果補上查詢導向摘要
+pub fn fill_missing_snippets(results: &mut [SearchResult], query: &str) {
+    for result in results.iter_mut() {
+        let missing = result.snippet.as_deref().is_none_or(|s| s.trim().is_empty());
+        if !missing {
+            continue;
+        }
+        if let Some(content) = result.content.as_deref() {
+            result.snippet = query_snippet(content, query, DEFAULT_SNIPPET_CHARS);
+        }
+    }
+}
+
+fn lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// 拆出查詢詞（小寫、去除標點、去重）；單一 ASCII 字元略過
+fn query_terms(query: &str) -> Vec<Vec<char>> {
+    let mut terms: Vec<Vec<char>> = Vec::new();
+    for word in query.split_whitespace() {
+        let term: Vec<char> = word
+            .trim_matches(|c: char| !c.is_alphanumeric())
+            .chars()
+            .map(lowercase)
+            .collect();
+        let too_short = term.len() < 2 && term.iter().all(|c| c.is_ascii());
+        if term.is_empty() || too_short || terms.contains(&term) {
+            continue;
+        }
+        terms.push(term);
+    }
+    terms
+}
+
+/// 將視窗邊界調整到空白處，避免把單字切成兩半（CJK 等無空白文字維持原邊界）
+fn snap_to_words(chars: &[char], start: usize, end: usize) -> (usize, usize) {
+    let mut s = start;
+    if start > 0
+        && !chars[start - 1].is_whitespace()
+        && let Some(offset) = chars[start..end.min(start + BOUNDARY_SLACK)]
+            .iter()
+            .position(|c| c.is_whitespace())
+    {
+        s = start + offset + 1;
+    }
+
+    let mut e = end;
+    if end < chars.len() && !chars[end].is_whitespace() {
+        let floor = end.saturating_sub(BOUNDARY_SLACK).max(s);
+        if let Some(offset) = chars[floor..end].iter().rposition(|c| c.is_whitespace()) {
+            e = floor + offset;
+        }
+    }
+    (s, e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filler(n: usize) -> String {
+        "lorem ipsum dolor sit amet ".repeat(n)
+    }
+
+    #[test]
+    fn test_short_content_returned_whole() {
+        assert_eq!(
+            query_snippet("  Rust   is fast. ", "rust", 200).as_deref(),
+            Some("Rust is fast.")
+        );
+        assert!(query_snippet("   ", "rust", 200).is_none());
+    }
+
+    #[test]
+    fn test_snippet_centers_on_terms() {
+        let content = format!(
+            "{}Tokio is an async runtime for Rust applications. {}",
+            filler(20),
+            filler(20)
+        );
+        let snippet = query_snippet(&content, "tokio async runtime", 120).unwrap();
+        assert!(snippet.contains("Tokio is an async runtime"));
+        assert!(snippet.starts_with('…'));
+        assert!(snippet.ends_with('…'));
+        assert!(snippet.chars().count() <= 122);
+    }
+
+    #[test]
+    fn test_snippet_prefers_window_with_more_distinct_terms() {
+        let content = format!(
+            "rust rust rust {}rust ownership borrow checker {}",
+            filler(10),
+            filler(10)
+        );
+        let snippet = query_snippet(&content, "rust borrow checker", 80).unwrap();
+        assert!(snippet.contains("borrow checker"));
+    }
+
+    #[test]
+    fn test_snippet_without_match_uses_start() {
+        let content = filler(30);
+        let snippet = query_snippet(&content, "tokio", 50).unwrap();
+        assert!(snippet.starts_with("lorem"));
+        assert!(snippet.ends_with('…'));
+    }
+
+    #[test]
+    fn test_snippet_cjk() {
+        let pad = "填充文字".repeat(40);
+        let content = format!("{pad}非同步執行環境是現代伺服器的核心{pad}");
+        let snippet = query_snippet(&content, "非同步", 40).unwrap();
+        assert!(snippet.contains("非同步執行環境"));
+    }
+
+    #[test]
+    fn test_fill_missing_snippets() {
+        let mut results = vec![
+            SearchResult {
+                title: "A".into(),
+                url: "https://a.com".into(),
+                snippet: None,
+                content: Some("Rust ownership explained".into()),
+                published_date: None,
+                metadata: Default::default(),
+            },
+            SearchResult {
+                title: "B".into(),
+                url: "https://b.com".into(),
+                snippet: Some("kept".into()),
+                content: Some("other".into()),
+                published_date: None,
+                metadata: Default::default(),
+            },
+            SearchResult {
+                title: "C".into(),
+                url: "https://c.com".into(),
+                snippet: None,
+                content: None,
+                published_date: None,
+                metadata: Default::default(),
+            },
+        ];
+        fill_missing_snippets(&mut results, "ownership");
+        assert_eq!(results[0].snippet.as_deref(), Some("Rust ownership explained"));
+        assert_eq!(results[1].snippet.as_deref(), Some("kept"));
+        assert!(results[2].snippet.is_none());
+    }
+}
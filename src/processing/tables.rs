@@ -0,0 +1,350 @@
+//! 表格擷取 - 將 `<table>` 轉為對齊的 Markdown 表格或 CSV
+//!
+//! 價格、比較、效能數據多半放在表格裡，`HtmlCleaner::clean` 去掉標籤後只剩一串數字；
+//! 這裡保留列與欄，另外附在結果內容之後。
+
+use super::markdown::{attr, tokenize, Token};
+use super::HtmlCleaner;
+use crate::types::SearchResult;
+use bose_common::text::collapse_whitespace;
+
+/// 輸出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TableFormat {
+    #[default]
+    Markdown,
+    Csv,
+}
+
+/// 一個表格
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Table {
+    pub caption: Option<String>,
+    /// 表頭（`<thead>` 或第一列全為 `<th>`）；沒有時為空
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// 由列建立；`header_row` 為真時第一列當表頭
+    pub fn from_rows(mut rows: Vec<Vec<String>>, header_row: bool) -> Self {
+        rows.retain(|row| !row.is_empty());
+        let headers = if header_row && !rows.is_empty() {
+            rows.remove(0)
+        } else {
+            Vec::new()
+        };
+        Self {
+            caption: None,
+            headers,
+            rows,
+        }
+    }
+
+    pub fn columns(&self) -> usize {
+        self.rows
+            .iter()
+            .map(Vec::len)
+            .chain(std::iter::once(self.headers.len()))
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.columns() == 0
+    }
+
+    pub fn render(&self, format: TableFormat) -> String {
+        match format {
+            TableFormat::Markdown => self.to_markdown(),
+            TableFormat::Csv => self.to_csv(),
+        }
+    }
+
+    /// 欄寬對齊的 Markdown 表格；數字欄靠右。沒有表頭時以第一列當表頭
+    pub fn to_markdown(&self) -> String {
+        let cols = self.columns();
+        if cols == 0 {
+            return String::new();
+        }
+        let (header, body) = match (self.headers.is_empty(), self.rows.split_first()) {
+            (false, _) => (&self.headers, &self.rows[..]),
+            (true, Some((first, rest))) => (first, rest),
+            (true, None) => return String::new(),
+        };
+        let cell = |row: &Vec<String>, i: usize| row.get(i).map_or(String::new(), |c| c.replace('|', "\\|"));
+
+        let widths: Vec<usize> = (0..cols)
+            .map(|i| {
+                std::iter::once(header)
+                    .chain(body)
+                    .map(|row| display_width(&cell(row, i)))
+                    .max()
+                    .unwrap_or(0)
+                    .max(3)
+            })
+            .collect();
+        let numeric: Vec<bool> = (0..cols)
+            .map(|i| {
+                let mut values = body.iter().map(|row| cell(row, i)).filter(|c| !c.is_empty()).peekable();
+                values.peek().is_some() && values.all(|c| is_numeric(&c))
+            })
+            .collect();
+
+        let line = |row: &Vec<String>| {
+            let cells: Vec<String> = (0..cols)
+                .map(|i| {
+                    let text = cell(row, i);
+                    let pad = " ".repeat(widths[i] - display_width(&text));
+                    if numeric[i] {
+                        format!("{pad}{text}")
+                    } else {
+                        format!("{text}{pad}")
+                    }
+                })
+                .collect();
+            format!("| {} |", cells.join(" | "))
+        };
+        let separator: Vec<String> = (0..cols)
+            .map(|i| {
+                if numeric[i] {
+                    format!("{}:", "-".repeat(widths[i] - 1))
+                } else {
+                    "-".repeat(widths[i])
+                }
+            })
+            .collect();
+
+        let mut lines = Vec::with_capacity(body.len() + 3);
+        if let Some(caption) = &self.caption {
+            lines.push(format!("**{caption}**"));
+            lines.push(String::new());
+        }
+        lines.push(line(header));
+        lines.push(format!("| {} |", separator.join(" | ")));
+        lines.extend(body.iter().map(line));
+        lines.join("\n")
+    }
+
+    /// RFC 4180 CSV；含逗號、引號或換行的欄位加上引號
+    pub fn to_csv(&self) -> String {
+        let cols = self.columns();
+        let line = |row: &Vec<String>| {
+            (0..cols)
+                .map(|i| {
+                    let c = row.get(i).map_or("", String::as_str);
+                    if c.contains([',', '"', '\n', '\r']) {
+                        format!("\"{}\"", c.replace('"', "\"\""))
+                    } else {
+                        c.to_string()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        let mut lines = Vec::with_capacity(self.rows.len() + 1);
+        if !self.headers.is_empty() {
+            lines.push(line(&self.headers));
+        }
+        lines.extend(self.rows.iter().map(line));
+        lines.join("\n")
+    }
+}
+
+/// 擷取所有表格（巢狀表格各自獨立）；略過沒有資料的表格
+pub fn extract_tables(html: &str) -> Vec<Table> {
+    #[derive(Default)]
+    struct Builder {
+        caption: Option<String>,
+        rows: Vec<(Vec<String>, bool)>,
+        cell: Option<(String, usize)>,
+        in_thead: bool,
+        in_caption: bool,
+    }
+
+    let mut stack: Vec<Builder> = Vec::new();
+    let mut tables = Vec::new();
+
+    for token in tokenize(html) {
+        let Some(table) = stack.last_mut() else {
+            if matches!(&token, Token::Open { name, .. } if name == "table") {
+                stack.push(Builder::default());
+            }
+            continue;
+        };
+        match token {
+            Token::Text(text) => {
+                let text = HtmlCleaner::decode_entities(text);
+                if table.in_caption {
+                    table.caption.get_or_insert_with(String::new).push_str(&text);
+                } else if let Some((cell, _)) = &mut table.cell {
+                    cell.push_str(&text);
+                }
+            }
+            Token::Open { name, attrs } => match name.as_str() {
+                "table" => stack.push(Builder::default()),
+                "caption" => table.in_caption = true,
+                "thead" => table.in_thead = true,
+                "tr" => table.rows.push((Vec::new(), true)),
+                "td" | "th" => {
+                    if table.rows.is_empty() {
+                        table.rows.push((Vec::new(), true));
+                    }
+                    let span = attr(attrs, "colspan").and_then(|s| s.parse().ok()).unwrap_or(1);
+                    // 列是否為表頭：全部儲存格都是 <th>
+                    if name == "td"
+                        && let Some(row) = table.rows.last_mut()
+                    {
+                        row.1 &= table.in_thead;
+                    }
+                    table.cell = Some((String::new(), span));
+                }
+                "br" | "p" | "div" | "li" => {
+                    if let Some((cell, _)) = &mut table.cell {
+                        cell.push(' ');
+                    }
+                }
+                _ => {}
+            },
+            Token::Close(name) => match name.as_str() {
+                "caption" => table.in_caption = false,
+                "thead" => table.in_thead = false,
+                "td" | "th" => {
+                    if let (Some((cell, span)), Some((row, _))) = (table.cell.take(), table.rows.last_mut()) {
+                        row.push(collapse_whitespace(&cell));
+                        row.extend(std::iter::repeat_n(String::new(), span.clamp(1, 64) - 1));
+                    }
+                }
+                "table" => {
+                    let builder = stack.pop().unwrap_or_default();
+                    let header_row = builder.rows.first().is_some_and(|(row, header)| *header && !row.is_empty());
+                    let mut table = Table::from_rows(builder.rows.into_iter().map(|(row, _)| row).collect(), header_row);
+                    table.caption = builder.caption.map(|c| collapse_whitespace(&c)).filter(|c| !c.is_empty());
+                    if table.rows.iter().flatten().any(|cell| !cell.is_empty()) {
+                        tables.push(table);
+                    }
+                }
+                _ => {}
+            },
+        }
+    }
+    tables
+}
+
+/// 將頁面中的表格附加到結果內容之後
+pub fn attach_tables(result: &mut SearchResult, html: &str, format: TableFormat) {
+    let blocks: Vec<String> = extract_tables(html)
+        .iter()
+        .map(|table| match format {
+            TableFormat::Markdown => table.to_markdown(),
+            TableFormat::Csv => format!("```csv\n{}\n```", table.to_csv()),
+        })
+        .collect();
+    if blocks.is_empty() {
+        return;
+    }
+    let content = result.content.get_or_insert_with(String::new);
+    for block in blocks {
+        if !content.is_empty() {
+            content.push_str("\n\n");
+        }
+        content.push_str(&block);
+    }
+}
+
+/// 數字、金額、百分比（`$1,299`、`-3.5%`、`12 ms`）
+fn is_numeric(cell: &str) -> bool {
+    let core = cell
+        .trim()
+        .trim_start_matches(['$', '€', '£', '¥', '+', '-', '−', '~', '≈'])
+        .trim_end_matches(['%', 'x', '×'])
+        .trim_end_matches(|c: char| c.is_alphabetic() || c == ' ' || c == '/');
+    !core.is_empty()
+        && core.chars().next().is_some_and(|c| c.is_ascii_digit())
+        && core.chars().all(|c| c.is_ascii_digit() || matches!(c, ',' | '.' | '_'))
+}
+
+/// 終端機顯示寬度：全形字元佔兩格
+fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| match c as u32 {
+            0x1100..=0x115F | 0x2E80..=0xA4CF | 0xAC00..=0xD7A3 | 0xF900..=0xFAFF | 0xFE30..=0xFE4F
+            | 0xFF00..=0xFF60 | 0xFFE0..=0xFFE6 | 0x20000..=0x3FFFD => 2,
+            _ => 1,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRICING: &str = r#"
+        <p>Intro</p>
+        <table>
+          <caption>Plans</caption>
+          <thead><tr><th>Plan</th><th>Price</th><th>Notes</th></tr></thead>
+          <tbody>
+            <tr><td>Free</td><td>$0</td><td>1,000 calls, "basic"</td></tr>
+            <tr><td>專業版</td><td>$1,299</td><td><a href="/pro">details</a></td></tr>
+            <tr><td colspan="2">Enterprise</td><td>contact</td></tr>
+          </tbody>
+        </table>"#;
+
+    #[test]
+    fn test_extract() {
+        let tables = extract_tables(PRICING);
+        assert_eq!(tables.len(), 1);
+        let table = &tables[0];
+        assert_eq!(table.caption.as_deref(), Some("Plans"));
+        assert_eq!(table.headers, vec!["Plan", "Price", "Notes"]);
+        assert_eq!(table.rows[1], vec!["專業版", "$1,299", "details"]);
+        assert_eq!(table.rows[2], vec!["Enterprise", "", "contact"]);
+    }
+
+    #[test]
+    fn test_markdown_aligned() {
+        let table = &extract_tables(PRICING)[0];
+        assert_eq!(
+            table.to_markdown(),
+            "**Plans**\n\n\
+             | Plan       |  Price | Notes                |\n\
+             | ---------- | -----: | -------------------- |\n\
+             | Free       |     $0 | 1,000 calls, \"basic\" |\n\
+             | 專業版     | $1,299 | details              |\n\
+             | Enterprise |        | contact              |"
+        );
+    }
+
+    #[test]
+    fn test_csv_and_attach() {
+        let table = &extract_tables(PRICING)[0];
+        assert_eq!(
+            table.to_csv().lines().nth(1),
+            Some("Free,$0,\"1,000 calls, \"\"basic\"\"\"")
+        );
+
+        let mut result = SearchResult {
+            title: "Pricing".into(),
+            url: "https://example.com".into(),
+            snippet: None,
+            content: Some("Intro".into()),
+            published_date: None,
+            metadata: Default::default(),
+        };
+        attach_tables(&mut result, PRICING, TableFormat::Csv);
+        let content = result.content.unwrap();
+        assert!(content.starts_with("Intro\n\n```csv\nPlan,Price,Notes\n"));
+    }
+
+    #[test]
+    fn test_nested_and_headerless() {
+        let html = "<table><tr><td>outer</td><td><table><tr><td>a</td><td>1</td></tr></table></td></tr></table>\
+                    <table><tr><td></td></tr></table>";
+        let tables = extract_tables(html);
+        assert_eq!(tables.len(), 2);
+        assert_eq!(tables[0].rows, vec![vec!["a", "1"]]);
+        assert!(tables[1].headers.is_empty());
+        assert_eq!(tables[1].to_markdown(), "| outer |     |\n| ----- | --- |");
+    }
+}
@@ -0,0 +1,119 @@
+//! Token 計數 - 讓 `ContextPruner` 的預算對應實際消費內容的模型
+//!
+//! 預設的 `HeuristicTokenizer` 不需要詞表：英數字約 4 字元一個 token，
+//! CJK 每字一個、標點與符號各一個，比 `len / 4` 更貼近中文與程式碼的實際數量。
+//! 啟用 `tiktoken` feature 後可改用 `TiktokenTokenizer` 精確計算。
+
+use bose_common::text::{estimate_tokens, truncate_tokens};
+
+/// Token 計數器
+pub trait Tokenizer: Send + Sync {
+    /// 文字的 token 數
+    fn count(&self, text: &str) -> usize;
+
+    /// 不超過 `max_tokens` 的最長前綴，切在字元邊界上
+    fn truncate<'a>(&self, text: &'a str, max_tokens: usize) -> &'a str {
+        if self.count(text) <= max_tokens {
+            return text;
+        }
+        // 二分搜尋前綴的字元數；整段已確定超出預算
+        let bounds: Vec<usize> = text.char_indices().map(|(i, _)| i).collect();
+        let (mut lo, mut hi) = (0, bounds.len() - 1);
+        while lo < hi {
+            let mid = (lo + hi).div_ceil(2);
+            if self.count(&text[..bounds[mid]]) <= max_tokens {
+                lo = mid;
+            } else {
+                hi = mid - 1;
+            }
+        }
+        &text[..bounds[lo]]
+    }
+}
+
+/// 免詞表的估算
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeuristicTokenizer;
+
+impl Tokenizer for HeuristicTokenizer {
+    fn count(&self, text: &str) -> usize {
+        estimate_tokens(text)
+    }
+
+    /// 單次掃描即可，不必二分搜尋
+    fn truncate<'a>(&self, text: &'a str, max_tokens: usize) -> &'a str {
+        truncate_tokens(text, max_tokens)
+    }
+}
+
+/// tiktoken 相容的 BPE 計數（OpenAI 系列模型）
+#[cfg(feature = "tiktoken")]
+pub struct TiktokenTokenizer {
+    bpe: tiktoken_rs::CoreBPE,
+}
+
+#[cfg(feature = "tiktoken")]
+impl TiktokenTokenizer {
+    /// GPT-4 / GPT-3.5 使用的 `cl100k_base`
+    pub fn cl100k() -> Result<Self, crate::types::SearchError> {
+        Self::load(tiktoken_rs::cl100k_base())
+    }
+
+    /// GPT-4o 使用的 `o200k_base`
+    pub fn o200k() -> Result<Self, crate::types::SearchError> {
+        Self::load(tiktoken_rs::o200k_base())
+    }
+
+    /// 依模型名稱挑選詞表（例如 `gpt-4o-mini`）
+    pub fn for_model(model: &str) -> Result<Self, crate::types::SearchError> {
+        Self::load(tiktoken_rs::get_bpe_from_model(model))
+    }
+
+    fn load<E: std::fmt::Display>(
+        bpe: Result<tiktoken_rs::CoreBPE, E>,
+    ) -> Result<Self, crate::types::SearchError> {
+        bpe.map(|bpe| Self { bpe })
+            .map_err(|e| crate::types::SearchError::ConfigError(format!("載入 tokenizer 失敗: {}", e)))
+    }
+}
+
+#[cfg(feature = "tiktoken")]
+impl Tokenizer for TiktokenTokenizer {
+    fn count(&self, text: &str) -> usize {
+        // 網頁內容中的 `<|endoftext|>` 之類字串當一般文字處理
+        self.bpe.encode_ordinary(text).len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heuristic_count() {
+        let t = HeuristicTokenizer;
+        assert_eq!(t.count(&"A".repeat(400)), 100);
+        assert_eq!(t.count("hello world"), 4);
+        // len / 4 只算得到 3
+        assert_eq!(t.count("繁體中文"), 4);
+        assert_eq!(t.count("fn main() {}"), 6);
+        assert_eq!(t.count(""), 0);
+    }
+
+    #[test]
+    fn test_truncate_on_char_boundary() {
+        let t = HeuristicTokenizer;
+        assert_eq!(t.truncate("繁體中文內容", 3), "繁體中");
+        assert_eq!(t.truncate("short", 10), "short");
+        assert_eq!(t.truncate("abcdefgh ijkl", 2), "abcdefgh ");
+        assert_eq!(t.truncate("中文", 0), "");
+    }
+
+    #[cfg(feature = "tiktoken")]
+    #[test]
+    fn test_tiktoken_count() {
+        let t = TiktokenTokenizer::cl100k().unwrap();
+        assert_eq!(t.count("hello world"), 2);
+        assert!(TiktokenTokenizer::for_model("no-such-model").is_err());
+    }
+}
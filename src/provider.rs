@@ -0,0 +1,137 @@
+//! 搜尋提供者 - 階梯式檢索中可替換的一層（引擎、SearXNG、本地索引 ...）
+//!
+//! 內建 DuckDuckGo、Exa 與 Tavily 的實作；其他來源實作 `SearchProvider` 後即可用
+//! `TieredRetrieval::with_tier` 加入檢索鏈。
+//! `SimilarProvider` 由種子網址找相似頁面，供研究代理延伸證據（內建 Exa `findSimilar`）。
+
+use crate::cost::{EXA_COST, TAVILY_COST};
+use crate::duckduckgo::DuckDuckGoClient;
+use crate::exa::ExaClient;
+use crate::tavily::TavilyClient;
+use crate::types::{SearchEngine, SearchError, SearchResult};
+use std::future::Future;
+use std::pin::Pin;
+
+/// `SearchProvider::search` 回傳的 future
+pub type ProviderFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<SearchResult>, SearchError>> + Send + 'a>>;
+
+/// 檢索鏈中的搜尋來源
+pub trait SearchProvider: Send + Sync {
+    /// 用於日誌、佐證來源與設定檔 `tiers` 的名稱
+    fn name(&self) -> &str;
+
+    /// 搜尋；`previous` 為上一層的結果（第一層為空），可用於深度提取等延伸查詢
+    fn search<'a>(&'a self, query: &'a str, previous: &'a [SearchResult], num_results: usize) -> ProviderFuture<'a>;
+
+    /// 單次呼叫的成本估計（美元）
+    fn cost(&self) -> f32 {
+        0.0
+    }
+
+    /// 計入花費帳本的引擎；自架或免費來源為 None
+    fn engine(&self) -> Option<SearchEngine> {
+        None
+    }
+
+    /// 結果是否獨立於上一層，可作為交叉佐證與引擎一致度的來源
+    fn independent(&self) -> bool {
+        true
+    }
+}
+
+/// 由種子網址找內容相似的頁面
+pub trait SimilarProvider: Send + Sync {
+    /// 用於日誌的名稱
+    fn name(&self) -> &str;
+
+    /// 相似頁面，不含種子網址本身
+    fn find_similar<'a>(&'a self, url: &'a str, num_results: usize) -> ProviderFuture<'a>;
+
+    /// 單次呼叫的成本估計（美元）
+    fn cost(&self) -> f32 {
+        0.0
+    }
+}
+
+impl SearchProvider for DuckDuckGoClient {
+    fn name(&self) -> &str {
+        SearchEngine::DuckDuckGo.name()
+    }
+
+    fn search<'a>(&'a self, query: &'a str, _previous: &'a [SearchResult], num_results: usize) -> ProviderFuture<'a> {
+        Box::pin(DuckDuckGoClient::search(self, query, num_results))
+    }
+
+    fn engine(&self) -> Option<SearchEngine> {
+        Some(SearchEngine::DuckDuckGo)
+    }
+}
+
+impl SearchProvider for ExaClient {
+    fn name(&self) -> &str {
+        SearchEngine::Exa.name()
+    }
+
+    fn search<'a>(&'a self, query: &'a str, _previous: &'a [SearchResult], num_results: usize) -> ProviderFuture<'a> {
+        Box::pin(ExaClient::search(self, query, num_results))
+    }
+
+    fn cost(&self) -> f32 {
+        EXA_COST
+    }
+
+    fn engine(&self) -> Option<SearchEngine> {
+        Some(SearchEngine::Exa)
+    }
+}
+
+impl SimilarProvider for ExaClient {
+    fn name(&self) -> &str {
+        SearchEngine::Exa.name()
+    }
+
+    fn find_similar<'a>(&'a self, url: &'a str, num_results: usize) -> ProviderFuture<'a> {
+        Box::pin(ExaClient::find_similar(self, url, num_results))
+    }
+
+    fn cost(&self) -> f32 {
+        EXA_COST
+    }
+}
+
+/// 深度提取的網址數
+const TAVILY_EXTRACT_URLS: usize = 3;
+
+/// Tavily 作為後段層級時，只對上一層最相關的網址做深度內容提取；作為第一層時一般搜尋
+impl SearchProvider for TavilyClient {
+    fn name(&self) -> &str {
+        SearchEngine::Tavily.name()
+    }
+
+    fn search<'a>(&'a self, query: &'a str, previous: &'a [SearchResult], num_results: usize) -> ProviderFuture<'a> {
+        Box::pin(async move {
+            if previous.is_empty() {
+                return TavilyClient::search(self, query, num_results).await;
+            }
+            let top_urls: Vec<&str> = previous
+                .iter()
+                .take(TAVILY_EXTRACT_URLS)
+                .map(|r| r.url.as_str())
+                .collect();
+            self.extract_content(&top_urls).await
+        })
+    }
+
+    fn cost(&self) -> f32 {
+        TAVILY_COST
+    }
+
+    fn engine(&self) -> Option<SearchEngine> {
+        Some(SearchEngine::Tavily)
+    }
+
+    /// 提取的是上一層的網址，不能拿來佐證上一層
+    fn independent(&self) -> bool {
+        false
+    }
+}
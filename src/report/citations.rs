@@ -0,0 +1,370 @@
+//! 文獻引用匯出 - 將結果集轉成 BibTeX 或 CSL-JSON，可直接匯入 Zotero、JabRef 等文獻管理工具
+//!
+//! 引擎只回傳標題、網址、摘要與日期，識別碼由網址辨識：arXiv（`arxiv.org/abs/…`）、
+//! DOI（`doi.org/…` 或網址中的 `10.xxxx/…`）、PubMed（`pubmed.ncbi.nlm.nih.gov/…`）。
+//! arXiv 為預印本、帶 DOI 或 PMID 與 Semantic Scholar 的頁面為論文，其餘為網頁。
+
+use crate::routing::confidence::parse_date;
+use crate::routing::corroboration::url_key;
+use crate::types::SearchResult;
+use bose_common::text::collapse_whitespace;
+use reqwest::Url;
+use serde_json::{json, Map, Value};
+use std::collections::HashSet;
+
+/// BibTeX 的月份巨集
+const MONTHS: &[&str] = &["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+
+/// 產生引用鍵時略過的標題字
+const KEY_STOPWORDS: &[&str] = &["a", "an", "and", "for", "from", "how", "in", "of", "on", "the", "to", "what", "with"];
+
+/// 文獻類型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CitationKind {
+    Preprint,
+    Article,
+    Webpage,
+}
+
+/// 單筆文獻
+#[derive(Debug, Clone, PartialEq)]
+pub struct Citation {
+    /// 引用鍵，例如 `attention2017`；同一批內唯一
+    pub key: String,
+    pub kind: CitationKind,
+    pub title: String,
+    pub url: String,
+    /// 發布日期 (年, 月, 日)
+    pub issued: Option<(i64, i64, i64)>,
+    pub doi: Option<String>,
+    /// arXiv 編號（不含版本）
+    pub arxiv: Option<String>,
+    pub pmid: Option<String>,
+    /// 網站主機名稱（不含 `www.`）
+    pub site: Option<String>,
+    /// 搜尋摘要
+    pub summary: Option<String>,
+}
+
+impl Citation {
+    /// 由單一結果辨識識別碼與類型（引用鍵未去重）
+    pub fn from_result(result: &SearchResult) -> Self {
+        let parsed = Url::parse(&result.url).ok();
+        let host = parsed
+            .as_ref()
+            .and_then(|u| u.host_str())
+            .map(|h| h.to_lowercase().trim_start_matches("www.").to_string());
+        let path = parsed.as_ref().map_or("", |u| u.path());
+
+        let arxiv = host.as_deref().filter(|h| is_site(h, "arxiv.org")).and_then(|_| arxiv_id(path));
+        let doi = match host.as_deref() {
+            Some(h) if is_site(h, "doi.org") => {
+                Some(path.trim_start_matches('/').to_string()).filter(|d| d.starts_with("10."))
+            }
+            _ => find_doi(&result.url),
+        };
+        let pmid = host.as_deref().and_then(|h| pubmed_id(h, path));
+
+        let scholarly = host.as_deref().is_some_and(|h| is_site(h, "semanticscholar.org"));
+        let kind = if arxiv.is_some() {
+            CitationKind::Preprint
+        } else if doi.is_some() || pmid.is_some() || scholarly {
+            CitationKind::Article
+        } else {
+            CitationKind::Webpage
+        };
+        let title = collapse_whitespace(&result.title);
+        let issued = result.published_date.as_deref().and_then(parse_date);
+        Self {
+            key: base_key(&title, host.as_deref(), issued.map(|(year, _, _)| year)),
+            kind,
+            title: if title.is_empty() { result.url.clone() } else { title },
+            url: result.url.clone(),
+            issued,
+            doi,
+            arxiv,
+            pmid,
+            site: host,
+            summary: result.snippet.as_deref().map(collapse_whitespace).filter(|s| !s.is_empty()),
+        }
+    }
+
+    /// arXiv 論文的 DOI（arXiv 為每篇論文註冊的 `10.48550/arXiv.<編號>`）
+    fn effective_doi(&self) -> Option<String> {
+        self.doi
+            .clone()
+            .or_else(|| self.arxiv.as_ref().map(|id| format!("10.48550/arXiv.{}", id)))
+    }
+
+    /// BibTeX 條目
+    pub fn to_bibtex(&self) -> String {
+        let entry = match self.kind {
+            CitationKind::Article => "article",
+            CitationKind::Preprint | CitationKind::Webpage => "misc",
+        };
+        let mut fields: Vec<(&str, String)> = vec![("title", format!("{{{}}}", escape_bibtex(&self.title)))];
+        if let Some((year, month, _)) = self.issued {
+            fields.push(("year", format!("{{{}}}", year)));
+            fields.push(("month", MONTHS[(month - 1) as usize].to_string()));
+        }
+        if let Some(id) = &self.arxiv {
+            fields.push(("eprint", format!("{{{}}}", id)));
+            fields.push(("archivePrefix", "{arXiv}".to_string()));
+        }
+        if let Some(doi) = self.effective_doi() {
+            fields.push(("doi", format!("{{{}}}", doi)));
+        }
+        if let Some(pmid) = &self.pmid {
+            fields.push(("pmid", format!("{{{}}}", pmid)));
+        }
+        if let (CitationKind::Webpage, Some(site)) = (self.kind, &self.site) {
+            fields.push(("howpublished", format!("{{{}}}", escape_bibtex(site))));
+        }
+        fields.push(("url", format!("{{{}}}", self.url)));
+        if let Some(summary) = &self.summary {
+            fields.push(("abstract", format!("{{{}}}", escape_bibtex(summary))));
+        }
+
+        let mut bib = format!("@{}{{{},\n", entry, self.key);
+        for (name, value) in fields {
+            bib.push_str(&format!("  {} = {},\n", name, value));
+        }
+        bib.push_str("}\n");
+        bib
+    }
+
+    /// CSL-JSON 項目
+    pub fn to_csl(&self) -> Value {
+        let kind = match self.kind {
+            CitationKind::Preprint => "article",
+            CitationKind::Article => "article-journal",
+            CitationKind::Webpage => "webpage",
+        };
+        let mut item = Map::new();
+        item.insert("id".into(), json!(self.key));
+        item.insert("type".into(), json!(kind));
+        item.insert("title".into(), json!(self.title));
+        item.insert("URL".into(), json!(self.url));
+        if let Some((year, month, day)) = self.issued {
+            item.insert("issued".into(), json!({ "date-parts": [[year, month, day]] }));
+        }
+        if let Some(doi) = self.effective_doi() {
+            item.insert("DOI".into(), json!(doi));
+        }
+        if let Some(id) = &self.arxiv {
+            item.insert("publisher".into(), json!("arXiv"));
+            item.insert("number".into(), json!(format!("arXiv:{}", id)));
+        }
+        if let Some(pmid) = &self.pmid {
+            item.insert("PMID".into(), json!(pmid));
+        }
+        if let (CitationKind::Webpage, Some(site)) = (self.kind, &self.site) {
+            item.insert("container-title".into(), json!(site));
+        }
+        if let Some(summary) = &self.summary {
+            item.insert("abstract".into(), json!(summary));
+        }
+        Value::Object(item)
+    }
+}
+
+/// 將結果集轉成文獻；同一網址只保留第一筆，重複的引用鍵依序加上 `b`、`c` …
+pub fn citations(results: &[SearchResult]) -> Vec<Citation> {
+    let mut urls = HashSet::new();
+    let mut keys = HashSet::new();
+    let mut citations = Vec::new();
+    for result in results.iter().filter(|r| urls.insert(url_key(&r.url))) {
+        let mut citation = Citation::from_result(result);
+        let base = citation.key.clone();
+        let mut n = 1;
+        while !keys.insert(citation.key.clone()) {
+            citation.key = match char::from_u32('a' as u32 + n).filter(char::is_ascii_lowercase) {
+                Some(letter) => format!("{}{}", base, letter),
+                None => format!("{}-{}", base, n + 1),
+            };
+            n += 1;
+        }
+        citations.push(citation);
+    }
+    citations
+}
+
+/// 結果集的 BibTeX 文件
+pub fn to_bibtex(results: &[SearchResult]) -> String {
+    citations(results)
+        .iter()
+        .map(Citation::to_bibtex)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 結果集的 CSL-JSON 陣列
+pub fn to_csl_json(results: &[SearchResult]) -> Value {
+    Value::Array(citations(results).iter().map(Citation::to_csl).collect())
+}
+
+fn is_site(host: &str, domain: &str) -> bool {
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+/// `/abs/2401.01234v2`、`/pdf/2401.01234.pdf`、`/abs/hep-th/9901001`
+fn arxiv_id(path: &str) -> Option<String> {
+    let id = path
+        .strip_prefix("/abs/")
+        .or_else(|| path.strip_prefix("/pdf/"))?
+        .trim_end_matches('/')
+        .trim_end_matches(".pdf");
+    let id = match id.rfind('v') {
+        Some(i) if i > 0 && id[i + 1..].chars().all(|c| c.is_ascii_digit()) && i + 1 < id.len() => &id[..i],
+        _ => id,
+    };
+    (!id.is_empty() && id.chars().any(|c| c.is_ascii_digit())).then(|| id.to_string())
+}
+
+/// `pubmed.ncbi.nlm.nih.gov/38123456/` 或 `ncbi.nlm.nih.gov/pubmed/38123456`
+fn pubmed_id(host: &str, path: &str) -> Option<String> {
+    let id = if host == "pubmed.ncbi.nlm.nih.gov" {
+        path.trim_matches('/')
+    } else if is_site(host, "ncbi.nlm.nih.gov") {
+        path.strip_prefix("/pubmed/")?.trim_matches('/')
+    } else {
+        return None;
+    };
+    (!id.is_empty() && id.chars().all(|c| c.is_ascii_digit())).then(|| id.to_string())
+}
+
+/// 網址中的 DOI：`10.` 加上 4 ~ 9 位數字、`/` 與後綴
+fn find_doi(text: &str) -> Option<String> {
+    let mut rest = text;
+    while let Some(start) = rest.find("10.") {
+        let candidate = &rest[start..];
+        let digits = candidate[3..].chars().take_while(|c| c.is_ascii_digit()).count();
+        let preceded_by_digit = rest[..start].ends_with(|c: char| c.is_ascii_digit() || c == '.');
+        if (4..=9).contains(&digits) && !preceded_by_digit && candidate[3 + digits..].starts_with('/') {
+            let end = candidate
+                .find(|c: char| c.is_whitespace() || matches!(c, '?' | '#' | '"' | '<' | '>'))
+                .unwrap_or(candidate.len());
+            let doi = candidate[..end].trim_end_matches(['.', ',', ';', ')', '/']);
+            if doi.len() > 4 + digits {
+                return Some(doi.to_string());
+            }
+        }
+        rest = &rest[start + 3..];
+    }
+    None
+}
+
+/// 標題第一個有意義的英文字（或網站名稱）加上年份，例如 `attention2017`
+fn base_key(title: &str, host: Option<&str>, year: Option<i64>) -> String {
+    let word = title
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .map(str::to_lowercase)
+        .find(|w| w.len() >= 3 && !KEY_STOPWORDS.contains(&w.as_str()))
+        .or_else(|| {
+            host.and_then(|h| h.split('.').next())
+                .map(|label| label.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>())
+                .filter(|label| !label.is_empty())
+        })
+        .unwrap_or_else(|| "ref".to_string());
+    match year {
+        Some(year) => format!("{}{}", word, year),
+        None => word,
+    }
+}
+
+/// 跳脫 LaTeX 特殊字元；大括號會破壞欄位界線，直接去掉
+fn escape_bibtex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '{' | '}' | '\\' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(title: &str, url: &str, date: Option<&str>) -> SearchResult {
+        SearchResult {
+            title: title.to_string(),
+            url: url.to_string(),
+            snippet: Some("The dominant sequence transduction models are based on RNNs & CNNs.".to_string()),
+            content: None,
+            published_date: date.map(str::to_string),
+            metadata: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_identifiers_from_urls() {
+        let arxiv = Citation::from_result(&result(
+            "Attention Is All You Need",
+            "https://arxiv.org/abs/1706.03762v7",
+            Some("2017-06-12"),
+        ));
+        assert_eq!(arxiv.kind, CitationKind::Preprint);
+        assert_eq!(arxiv.arxiv.as_deref(), Some("1706.03762"));
+        assert_eq!(arxiv.key, "attention2017");
+        let old_style = Citation::from_result(&result("x", "https://arxiv.org/pdf/hep-th/9901001v1.pdf", None));
+        assert_eq!(old_style.arxiv.as_deref(), Some("hep-th/9901001"));
+
+        let doi = Citation::from_result(&result("Deep learning", "https://doi.org/10.1038/nature14539", None));
+        assert_eq!((doi.kind, doi.doi.as_deref()), (CitationKind::Article, Some("10.1038/nature14539")));
+        let acm = Citation::from_result(&result("BERT", "https://dl.acm.org/doi/10.1145/3292500.3330701?x=1", None));
+        assert_eq!(acm.doi.as_deref(), Some("10.1145/3292500.3330701"));
+
+        let pubmed =
+            Citation::from_result(&result("CRISPR review", "https://pubmed.ncbi.nlm.nih.gov/38123456/", None));
+        assert_eq!((pubmed.kind, pubmed.pmid.as_deref()), (CitationKind::Article, Some("38123456")));
+
+        let web = Citation::from_result(&result("Tokio tutorial", "https://www.tokio.rs/tokio/tutorial", None));
+        assert_eq!((web.kind, web.site.as_deref(), web.doi), (CitationKind::Webpage, Some("tokio.rs"), None));
+        // 版本號中的 10.1234 不是 DOI
+        assert_eq!(find_doi("https://example.com/v2.10.1234/notes"), None);
+    }
+
+    #[test]
+    fn test_bibtex() {
+        let results = vec![
+            result("Attention Is All You Need", "https://arxiv.org/abs/1706.03762", Some("2017-06-12T17:57:34Z")),
+            result("Attention {mechanisms} in 50% of models", "https://example.com/attention_post", Some("2017-01-02")),
+            result("Attention Is All You Need", "https://arxiv.org/abs/1706.03762/", None),
+        ];
+        let bib = to_bibtex(&results);
+        assert_eq!(bib.matches('@').count(), 2);
+        assert!(bib.starts_with(
+            "@misc{attention2017,\n  title = {Attention Is All You Need},\n  year = {2017},\n  month = jun,\n  \
+             eprint = {1706.03762},\n  archivePrefix = {arXiv},\n  doi = {10.48550/arXiv.1706.03762},\n"
+        ));
+        assert!(bib.contains("@misc{attention2017b,\n  title = {Attention mechanisms in 50\\% of models},"));
+        assert!(bib.contains("  howpublished = {example.com},\n  url = {https://example.com/attention_post},\n"));
+        assert!(bib.contains("abstract = {The dominant sequence transduction models are based on RNNs \\& CNNs.}"));
+    }
+
+    #[test]
+    fn test_csl_json() {
+        let csl = to_csl_json(&[
+            result(
+                "Attention Is All You Need",
+                "https://arxiv.org/abs/1706.03762",
+                Some("Mon, 12 Jun 2017 00:00:00 GMT"),
+            ),
+            result("Deep learning", "https://doi.org/10.1038/nature14539", None),
+        ]);
+        assert_eq!(csl[0]["type"], "article");
+        assert_eq!(csl[0]["issued"]["date-parts"], json!([[2017, 6, 12]]));
+        assert_eq!(csl[0]["number"], "arXiv:1706.03762");
+        assert_eq!(csl[1]["type"], "article-journal");
+        assert_eq!(csl[1]["id"], "deep");
+        assert_eq!(csl[1]["DOI"], "10.1038/nature14539");
+        assert!(csl[1].get("issued").is_none());
+    }
+}
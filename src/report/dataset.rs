@@ -0,0 +1,157 @@
+//! 評估資料集匯出 - 把實際查詢與結果寫成 JSON Lines，供建立檢索評估或訓練資料
+//!
+//! 每行一筆 `DatasetRecord`：查詢、依序的結果（含提取的全文）與待標註的 `labels`。
+//! 欄位變動時遞增 `DATASET_SCHEMA_VERSION`，下游工具依 `schema_version` 判斷格式。
+
+use crate::storage::{tier_name, unix_secs, QueryRecord};
+use crate::types::{SearchError, SearchResult};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+/// 資料集格式版本
+pub const DATASET_SCHEMA_VERSION: u32 = 1;
+
+/// 單一結果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatasetResult {
+    /// 排名（從 1 起算）
+    pub rank: usize,
+    pub title: String,
+    pub url: String,
+    pub snippet: Option<String>,
+    /// 提取的全文
+    pub content: Option<String>,
+}
+
+/// 待標註欄位；匯出時皆為空值
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DatasetLabels {
+    /// 與 `results` 一一對應的相關性等級（0 = 不相關 ~ 3 = 完全回答）
+    pub relevance: Vec<Option<u8>>,
+    /// 參考答案
+    pub answer: Option<String>,
+}
+
+/// 資料集的一筆紀錄
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatasetRecord {
+    pub schema_version: u32,
+    pub query: String,
+    /// 查詢參數（引擎、結果數等）
+    pub params: serde_json::Value,
+    /// 使用的檢索層級（`L1` ~ `L3`）
+    pub tier: Option<String>,
+    pub confidence: Option<f32>,
+    /// 查詢時間（Unix 秒）
+    pub recorded_at: Option<i64>,
+    pub results: Vec<DatasetResult>,
+    pub labels: DatasetLabels,
+}
+
+impl DatasetRecord {
+    /// 由查詢與結果建立（沒有參數、層級與時間）
+    pub fn new(query: &str, results: &[SearchResult]) -> Self {
+        Self {
+            schema_version: DATASET_SCHEMA_VERSION,
+            query: query.to_string(),
+            params: serde_json::Value::Object(Default::default()),
+            tier: None,
+            confidence: None,
+            recorded_at: None,
+            results: results
+                .iter()
+                .enumerate()
+                .map(|(i, r)| DatasetResult {
+                    rank: i + 1,
+                    title: r.title.clone(),
+                    url: r.url.clone(),
+                    snippet: r.snippet.clone(),
+                    content: r.content.clone(),
+                })
+                .collect(),
+            labels: DatasetLabels {
+                relevance: vec![None; results.len()],
+                answer: None,
+            },
+        }
+    }
+}
+
+impl From<&QueryRecord> for DatasetRecord {
+    fn from(record: &QueryRecord) -> Self {
+        Self {
+            params: record.params.clone(),
+            tier: record.tier.map(|tier| tier_name(tier).to_string()),
+            confidence: record.confidence,
+            recorded_at: Some(unix_secs(record.recorded_at)),
+            ..Self::new(&record.query, &record.results)
+        }
+    }
+}
+
+/// 寫成 JSON Lines，回傳寫入筆數
+pub fn write_jsonl<'a>(
+    out: &mut impl Write,
+    records: impl IntoIterator<Item = &'a DatasetRecord>,
+) -> Result<usize, SearchError> {
+    let mut count = 0;
+    for record in records {
+        let line = serde_json::to_string(record).map_err(|e| SearchError::ParseError(e.to_string()))?;
+        writeln!(out, "{}", line).map_err(|e| SearchError::StorageError(e.to_string()))?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routing::RetrievalTier;
+    use crate::storage::ResultStore;
+
+    fn result(title: &str, content: Option<&str>) -> SearchResult {
+        SearchResult {
+            title: title.to_string(),
+            url: format!("https://example.com/{}", title),
+            snippet: Some(format!("{} snippet", title)),
+            content: content.map(str::to_string),
+            published_date: None,
+            metadata: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_record_schema() {
+        let record = DatasetRecord::new("rust async", &[result("a", Some("full text")), result("b", None)]);
+        let value = serde_json::to_value(&record).unwrap();
+        assert_eq!(value["schema_version"], DATASET_SCHEMA_VERSION);
+        assert_eq!(value["results"][0]["rank"], 1);
+        assert_eq!(value["results"][0]["content"], "full text");
+        assert!(value["results"][1]["content"].is_null());
+        assert_eq!(value["labels"], serde_json::json!({ "relevance": [null, null], "answer": null }));
+    }
+
+    #[test]
+    fn test_export_history() {
+        let store = ResultStore::in_memory().unwrap();
+        let params = serde_json::json!({ "engine": "exa" });
+        store.record("tokio", &params, Some(RetrievalTier::L2), Some(0.5), &[result("t", None)]).unwrap();
+        store.record("serde", &params, None, None, &[]).unwrap();
+
+        let records: Vec<DatasetRecord> = store.all().unwrap().iter().map(DatasetRecord::from).collect();
+        let mut out = Vec::new();
+        assert_eq!(write_jsonl(&mut out, &records).unwrap(), 2);
+
+        let lines: Vec<DatasetRecord> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines, records);
+        assert_eq!(lines[0].query, "tokio");
+        assert_eq!(lines[0].tier.as_deref(), Some("L2"));
+        assert_eq!(lines[0].params, params);
+        assert!(lines[0].recorded_at.is_some());
+        assert!(lines[1].results.is_empty());
+    }
+}
@@ -0,0 +1,179 @@
+//! Markdown 研究報告 - 將 `ResearchReport` 輸出為結構化文件（CLI `--report out.md`）
+//!
+//! 文件依序為：問題、重點發現（每個子問題的第一句）、逐題發現、來源摘要、引用清單與統計。
+//! 發現中的 `[n]` 對應引用清單的編號。
+
+use crate::processing::context_pruner::split_sentences;
+use crate::research::ResearchReport;
+use bose_common::text::collapse_whitespace;
+use std::fmt::Write;
+
+/// 輸出 Markdown 文件
+pub fn render(report: &ResearchReport) -> String {
+    let mut md = String::new();
+    // 寫入 String 不會失敗
+    let _ = write_report(&mut md, report);
+    md
+}
+
+fn write_report(md: &mut String, report: &ResearchReport) -> std::fmt::Result {
+    writeln!(md, "# {}", collapse_whitespace(&report.query))?;
+    writeln!(md)?;
+    writeln!(
+        md,
+        "> {} 個子問題 · {} 個來源 · 花費 ${:.4}",
+        report.sections.len(),
+        report.sources.len(),
+        report.cost_estimate
+    )?;
+
+    writeln!(md)?;
+    writeln!(md, "## 重點發現")?;
+    writeln!(md)?;
+    for section in &report.sections {
+        match split_sentences(&section.findings).first() {
+            Some(first) => writeln!(md, "- **{}** {}", collapse_whitespace(&section.question), collapse_whitespace(first))?,
+            None => writeln!(md, "- **{}** 結果中沒有足以回答的內容", collapse_whitespace(&section.question))?,
+        }
+    }
+
+    writeln!(md)?;
+    writeln!(md, "## 發現")?;
+    for (i, section) in report.sections.iter().enumerate() {
+        writeln!(md)?;
+        writeln!(md, "### {}. {}", i + 1, collapse_whitespace(&section.question))?;
+        writeln!(md)?;
+        let sources: Vec<String> = section.sources.iter().map(|n| format!("[{}]", n)).collect();
+        writeln!(
+            md,
+            "*置信度 {:.2} · 層級 {:?}{}*",
+            section.confidence,
+            section.tier_used,
+            if sources.is_empty() { String::new() } else { format!(" · 來源 {}", sources.join(" ")) }
+        )?;
+        writeln!(md)?;
+        if section.findings.is_empty() {
+            writeln!(md, "結果中沒有足以回答的內容。")?;
+        } else {
+            writeln!(md, "{}", section.findings.trim())?;
+        }
+    }
+
+    if !report.sources.is_empty() {
+        writeln!(md)?;
+        writeln!(md, "## 來源摘要")?;
+        for source in &report.sources {
+            writeln!(md)?;
+            writeln!(md, "### [{}] {}", source.number, collapse_whitespace(&source.title))?;
+            writeln!(md)?;
+            writeln!(md, "<{}>", source.url)?;
+            let cited_by: Vec<String> = report
+                .sections
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| s.sources.contains(&source.number))
+                .map(|(i, _)| (i + 1).to_string())
+                .collect();
+            if !cited_by.is_empty() {
+                writeln!(md)?;
+                writeln!(md, "引用於第 {} 題", cited_by.join("、"))?;
+            }
+            if let Some(excerpt) = &source.excerpt {
+                writeln!(md)?;
+                writeln!(md, "> {}", collapse_whitespace(excerpt))?;
+            }
+        }
+
+        writeln!(md)?;
+        writeln!(md, "## 引用")?;
+        writeln!(md)?;
+        for source in &report.sources {
+            writeln!(md, "{}. [{}]({})", source.number, link_text(&source.title), link_target(&source.url))?;
+        }
+    }
+
+    writeln!(md)?;
+    writeln!(md, "## 統計")?;
+    writeln!(md)?;
+    writeln!(md, "- 花費: ${:.4}", report.cost_estimate)?;
+    let engines = if report.engines.is_empty() { "無".to_string() } else { report.engines.join(", ") };
+    writeln!(md, "- 引擎: {}", engines)
+}
+
+/// 連結文字中的方括號需跳脫
+fn link_text(text: &str) -> String {
+    collapse_whitespace(text).replace('[', "\\[").replace(']', "\\]")
+}
+
+/// 含空白或括號的網址以角括號包住
+fn link_target(url: &str) -> String {
+    if url.contains([' ', '(', ')']) {
+        format!("<{}>", url)
+    } else {
+        url.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::research::{ResearchSection, ResearchSource};
+    use crate::routing::RetrievalTier;
+
+    fn report() -> ResearchReport {
+        ResearchReport {
+            query: "How does tokio\nschedule tasks?".to_string(),
+            sections: vec![
+                ResearchSection {
+                    question: "How does the tokio scheduler work?".to_string(),
+                    findings: "The scheduler uses work stealing [1]. Each worker owns a queue [2].".to_string(),
+                    confidence: 0.82,
+                    tier_used: RetrievalTier::L2,
+                    sources: vec![1, 2],
+                },
+                ResearchSection {
+                    question: "Who maintains tokio?".to_string(),
+                    findings: String::new(),
+                    confidence: 0.1,
+                    tier_used: RetrievalTier::L1,
+                    sources: Vec::new(),
+                },
+            ],
+            sources: vec![
+                ResearchSource {
+                    number: 1,
+                    url: "https://docs.rs/tokio".to_string(),
+                    title: "tokio [docs]".to_string(),
+                    excerpt: Some("Tokio is an\nasync runtime.".to_string()),
+                },
+                ResearchSource {
+                    number: 2,
+                    url: "https://en.wikipedia.org/wiki/Tokio_(software)".to_string(),
+                    title: "Tokio".to_string(),
+                    excerpt: None,
+                },
+            ],
+            cost_estimate: 0.005,
+            engines: vec!["duckduckgo".to_string(), "exa".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_render_sections() {
+        let md = render(&report());
+        assert!(md.starts_with("# How does tokio schedule tasks?\n"));
+        assert!(md.contains("- **How does the tokio scheduler work?** The scheduler uses work stealing [1].\n"));
+        assert!(md.contains("- **Who maintains tokio?** 結果中沒有足以回答的內容\n"));
+        assert!(md.contains("### 1. How does the tokio scheduler work?\n\n*置信度 0.82 · 層級 L2 · 來源 [1] [2]*\n"));
+        assert!(md.contains("*置信度 0.10 · 層級 L1*\n\n結果中沒有足以回答的內容。"));
+        assert!(md.contains("### [1] tokio [docs]\n\n<https://docs.rs/tokio>\n\n引用於第 1 題\n\n> Tokio is an async runtime.\n"));
+        assert!(md.ends_with("- 花費: $0.0050\n- 引擎: duckduckgo, exa\n"));
+    }
+
+    #[test]
+    fn test_citation_links_are_escaped() {
+        let md = render(&report());
+        assert!(md.contains("1. [tokio \\[docs\\]](https://docs.rs/tokio)\n"));
+        assert!(md.contains("2. [Tokio](<https://en.wikipedia.org/wiki/Tokio_(software)>)\n"));
+    }
+}
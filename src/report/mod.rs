@@ -0,0 +1,8 @@
+//! 匯出 - 把研究報告與結果集轉成可分享或可匯入其他工具的格式
+//!
+//! `markdown` 將深度研究報告輸出為 Markdown 文件；`citations` 將結果集輸出為 BibTeX / CSL-JSON；
+//! `dataset` 將查詢紀錄輸出為評估用的 JSON Lines。
+
+pub mod citations;
+pub mod dataset;
+pub mod markdown;
@@ -0,0 +1,559 @@
+//! 迭代式研究代理 - 搜尋 → 閱讀 → 找出缺口 → 改寫查詢的有界迴圈
+//!
+//! 每一輪以階梯式檢索搜尋一個查詢、抓取新結果的全文，再由 `GapFinder` 判斷問題還有哪些面向
+//! 缺少證據；缺口成為之後的查詢。回合數與總花費都有上限。
+//! `KeywordGaps` 以關鍵字覆蓋率判斷缺口（不需外部服務），`LlmGaps` 交給模型提出追問。
+//! 設定 `SimilarProvider`（例如 Exa `findSimilar`）時，每輪再由第一個新結果延伸出相似頁面。
+
+use super::{query_lines, read_pages};
+use crate::fetcher::metadata::SITE_NAME;
+use crate::fetcher::Fetcher;
+use crate::llm::{Completion, CompletionRequest};
+use crate::processing::{HeuristicTokenizer, Tokenizer};
+use crate::provider::SimilarProvider;
+use crate::routing::condense::condense_keywords;
+use crate::routing::corroboration::url_key;
+use crate::routing::{SearchOptions, TieredRetrieval};
+use crate::types::{SearchError, SearchResult};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// `GapFinder::find_gaps` 回傳的 future
+pub type GapFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<String>, SearchError>> + Send + 'a>>;
+
+/// 缺口判斷
+pub trait GapFinder: Send + Sync {
+    /// 依目前的證據提出後續查詢；證據已足夠時回傳空清單
+    fn find_gaps<'a>(&'a self, question: &'a str, evidence: &'a [SearchResult]) -> GapFuture<'a>;
+
+    /// 用於日誌的名稱
+    fn name(&self) -> &'static str;
+}
+
+/// 關鍵字覆蓋率：問題的關鍵字出現在少於 `min_sources` 個證據中即為缺口
+#[derive(Debug, Clone, Copy)]
+pub struct KeywordGaps {
+    pub min_sources: usize,
+}
+
+impl Default for KeywordGaps {
+    fn default() -> Self {
+        Self { min_sources: 2 }
+    }
+}
+
+impl KeywordGaps {
+    /// 同步判斷；缺少的關鍵字合併成一個查詢，並加上覆蓋最多的關鍵字讓追問留在同一主題
+    pub fn gaps(&self, question: &str, evidence: &[SearchResult]) -> Vec<String> {
+        let keywords: Vec<String> = condense_keywords(question, usize::MAX)
+            .split_whitespace()
+            .map(|k| k.trim_matches('"').to_lowercase())
+            .filter(|k| !k.is_empty())
+            .collect();
+        let texts: Vec<String> = evidence
+            .iter()
+            .map(|r| {
+                let snippet = r.snippet.as_deref().unwrap_or("");
+                let content = r.content.as_deref().unwrap_or("");
+                format!("{} {} {}", r.title, snippet, content).to_lowercase()
+            })
+            .collect();
+
+        let support: Vec<(String, usize)> = keywords
+            .into_iter()
+            .map(|k| {
+                let count = texts.iter().filter(|t| t.contains(k.as_str())).count();
+                (k, count)
+            })
+            .collect();
+        let missing: Vec<&str> = support
+            .iter()
+            .filter(|(_, count)| *count < self.min_sources)
+            .map(|(k, _)| k.as_str())
+            .collect();
+        if missing.is_empty() {
+            return Vec::new();
+        }
+        let anchor = support
+            .iter()
+            .filter(|(_, count)| *count >= self.min_sources)
+            .max_by_key(|(_, count)| *count)
+            .map(|(k, _)| k.as_str());
+        vec![anchor.into_iter().chain(missing).collect::<Vec<_>>().join(" ")]
+    }
+}
+
+impl GapFinder for KeywordGaps {
+    fn find_gaps<'a>(&'a self, question: &'a str, evidence: &'a [SearchResult]) -> GapFuture<'a> {
+        Box::pin(async move { Ok(self.gaps(question, evidence)) })
+    }
+
+    fn name(&self) -> &'static str {
+        "keywords"
+    }
+}
+
+const GAPS_SYSTEM: &str = "You review evidence gathered for a research question. \
+List web search queries that would find what the evidence does not yet answer, one query per line, \
+without numbering or commentary. Reply with NONE if the evidence already answers the question.";
+
+/// 由 LLM 閱讀證據後提出追問
+pub struct LlmGaps {
+    completion: Arc<dyn Completion>,
+    model: String,
+    tokenizer: Arc<dyn Tokenizer>,
+    /// 每輪最多幾個追問
+    pub max_gaps: usize,
+    /// 每個證據送給 LLM 的 token 上限
+    pub tokens_per_source: usize,
+    /// 最多送出幾個證據（取最新的）
+    pub max_sources: usize,
+}
+
+impl LlmGaps {
+    pub fn new(completion: Arc<dyn Completion>, model: &str) -> Self {
+        Self {
+            completion,
+            model: model.to_string(),
+            tokenizer: Arc::new(HeuristicTokenizer),
+            max_gaps: 3,
+            tokens_per_source: 200,
+            max_sources: 12,
+        }
+    }
+
+    pub fn with_tokenizer(mut self, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    async fn ask(&self, question: &str, evidence: &[SearchResult]) -> Result<Vec<String>, SearchError> {
+        let mut prompt = format!("Question: {}\n\nEvidence:\n\n", question);
+        let recent = &evidence[evidence.len().saturating_sub(self.max_sources)..];
+        for (i, result) in recent.iter().enumerate() {
+            let text = result.content.as_deref().or(result.snippet.as_deref()).unwrap_or("");
+            let text = self.tokenizer.truncate(text, self.tokens_per_source);
+            let site = result.metadata.get(SITE_NAME).map(|name| format!(" — {}", name)).unwrap_or_default();
+            prompt.push_str(&format!("[{}] {}{} ({})\n{}\n\n", i + 1, result.title, site, result.url, text.trim()));
+        }
+        prompt.push_str(&format!("List at most {} follow-up search queries.", self.max_gaps));
+
+        let request = CompletionRequest::new(&self.model, &prompt)
+            .with_system(GAPS_SYSTEM)
+            .with_max_tokens(200);
+        let reply = self.completion.complete(&request).await?;
+        Ok(query_lines(&reply, self.max_gaps))
+    }
+}
+
+impl GapFinder for LlmGaps {
+    fn find_gaps<'a>(&'a self, question: &'a str, evidence: &'a [SearchResult]) -> GapFuture<'a> {
+        Box::pin(self.ask(question, evidence))
+    }
+
+    fn name(&self) -> &'static str {
+        "llm"
+    }
+}
+
+/// 研究代理配置
+#[derive(Debug, Clone)]
+pub struct AgentConfig {
+    /// 搜尋回合上限
+    pub max_iterations: usize,
+    /// 總花費上限（美元）；每輪只能使用剩餘的額度，用完後不再開始新的回合
+    pub max_cost: Option<f32>,
+    /// 每輪抓取全文的新結果數（需設定 `Fetcher`）
+    pub pages_per_iteration: usize,
+    /// 抓取的頁面裁剪到的 token 數
+    pub page_tokens: usize,
+    /// 每輪由種子結果延伸的相似頁面數（需設定 `SimilarProvider`，0 表示不延伸）
+    pub similar_per_seed: usize,
+}
+
+impl Default for AgentConfig {
+    fn default() -> Self {
+        Self {
+            max_iterations: 3,
+            max_cost: None,
+            pages_per_iteration: 3,
+            page_tokens: 1500,
+            similar_per_seed: 3,
+        }
+    }
+}
+
+/// 迴圈停止的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgentStop {
+    /// 沒有缺口（或缺口都已查過）
+    NoGaps,
+    /// 達到回合上限
+    MaxIterations,
+    /// 花費已達上限
+    MaxCost,
+    /// 最後一輪沒有找到新的結果
+    NoNewEvidence,
+    /// 後續回合的搜尋失敗，回傳目前為止的證據
+    SearchFailed,
+}
+
+/// 一輪搜尋的紀錄
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentIteration {
+    pub query: String,
+    /// 先前回合沒有出現過的結果數
+    pub new_results: usize,
+    pub confidence: f32,
+    pub cost: f32,
+    /// 延伸相似頁面的種子網址
+    pub seed: Option<String>,
+    /// 這一輪之後提出的新缺口
+    pub gaps: Vec<String>,
+}
+
+/// 研究代理的執行結果
+#[derive(Debug, Clone)]
+pub struct AgentRun {
+    pub question: String,
+    pub iterations: Vec<AgentIteration>,
+    /// 所有回合去重後的結果，依發現順序
+    pub evidence: Vec<SearchResult>,
+    pub cost_estimate: f32,
+    pub stop: AgentStop,
+}
+
+/// 迭代式研究代理
+pub struct ResearchAgent {
+    retrieval: TieredRetrieval,
+    fetcher: Option<Fetcher>,
+    similar: Option<Arc<dyn SimilarProvider>>,
+    gaps: Arc<dyn GapFinder>,
+    config: AgentConfig,
+}
+
+impl ResearchAgent {
+    /// 以關鍵字覆蓋率判斷缺口
+    pub fn new(retrieval: TieredRetrieval) -> Self {
+        Self {
+            retrieval,
+            fetcher: None,
+            similar: None,
+            gaps: Arc::new(KeywordGaps::default()),
+            config: AgentConfig::default(),
+        }
+    }
+
+    /// 抓取新結果的全文再判斷缺口
+    pub fn with_fetcher(mut self, fetcher: Fetcher) -> Self {
+        self.fetcher = Some(fetcher);
+        self
+    }
+
+    /// 每輪由第一個新結果找相似頁面（例如 `ExaClient`），花費計入 `max_cost`
+    pub fn with_similar(mut self, similar: Arc<dyn SimilarProvider>) -> Self {
+        self.similar = Some(similar);
+        self
+    }
+
+    /// 改用其他缺口判斷（例如 `LlmGaps`）
+    pub fn with_gap_finder(mut self, gaps: Arc<dyn GapFinder>) -> Self {
+        self.gaps = gaps;
+        self
+    }
+
+    pub fn with_config(mut self, config: AgentConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn config(&self) -> &AgentConfig {
+        &self.config
+    }
+
+    /// 執行研究迴圈
+    ///
+    /// 第一輪搜尋失敗時回傳錯誤；之後的失敗以 `AgentStop::SearchFailed` 結束並保留已取得的證據。
+    /// 缺口判斷失敗時視為沒有缺口。
+    pub async fn run(&self, question: &str) -> Result<AgentRun, SearchError> {
+        let question = question.trim();
+        let mut queue: VecDeque<String> = VecDeque::from([question.to_string()]);
+        let mut asked: Vec<String> = Vec::new();
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut pages: HashMap<String, String> = HashMap::new();
+        let mut evidence: Vec<SearchResult> = Vec::new();
+        let mut iterations: Vec<AgentIteration> = Vec::new();
+        let mut spent = 0.0;
+
+        let stop = loop {
+            let Some(query) = queue.pop_front() else {
+                break AgentStop::NoGaps;
+            };
+            if iterations.len() >= self.config.max_iterations {
+                break AgentStop::MaxIterations;
+            }
+            if !iterations.is_empty() && self.config.max_cost.is_some_and(|max| spent >= max) {
+                break AgentStop::MaxCost;
+            }
+
+            log::info!("🔁 第 {} 輪: {}", iterations.len() + 1, query);
+            asked.push(query.to_lowercase());
+            let options = SearchOptions {
+                max_cost: self.config.max_cost.map(|max| (max - spent).max(0.0)),
+                ..Default::default()
+            };
+            let tiered = match self.retrieval.search_with_options(&query, &options).await {
+                Ok(tiered) => tiered,
+                Err(e) if iterations.is_empty() => return Err(e),
+                Err(e) => {
+                    log::warn!("⚠️  「{}」搜尋失敗: {}", query, e);
+                    break AgentStop::SearchFailed;
+                }
+            };
+            spent += tiered.cost_estimate;
+            let mut cost = tiered.cost_estimate;
+
+            let mut fresh: Vec<SearchResult> = tiered
+                .results
+                .into_iter()
+                .filter(|r| seen.insert(url_key(&r.url)))
+                .collect();
+            let mut seed = None;
+            if let Some(similar) = &self.similar {
+                let affordable = self.config.max_cost.is_none_or(|max| spent + similar.cost() <= max);
+                let first = fresh.first().map(|r| r.url.clone());
+                if let Some(url) = first.filter(|_| affordable && self.config.similar_per_seed > 0) {
+                    match similar.find_similar(&url, self.config.similar_per_seed).await {
+                        Ok(pages) => fresh.extend(pages.into_iter().filter(|r| seen.insert(url_key(&r.url)))),
+                        Err(e) => log::warn!("⚠️  {} 找不到 {} 的相似頁面: {}", similar.name(), url, e),
+                    }
+                    spent += similar.cost();
+                    cost += similar.cost();
+                    seed = Some(url);
+                }
+            }
+            if let Some(fetcher) = &self.fetcher {
+                let top = self.config.pages_per_iteration.min(fresh.len());
+                read_pages(fetcher, &query, &mut fresh[..top], &mut pages, self.config.page_tokens).await;
+            }
+            let new_results = fresh.len();
+            evidence.extend(fresh);
+
+            let gaps = self
+                .gaps
+                .find_gaps(question, &evidence)
+                .await
+                .inspect_err(|e| log::warn!("⚠️  缺口判斷 ({}) 失敗: {}", self.gaps.name(), e))
+                .unwrap_or_default();
+            let gaps: Vec<String> = gaps
+                .into_iter()
+                .map(|gap| gap.trim().to_string())
+                .filter(|gap| {
+                    let key = gap.to_lowercase();
+                    !gap.is_empty() && !asked.contains(&key) && !queue.iter().any(|q| q.to_lowercase() == key)
+                })
+                .collect();
+            queue.extend(gaps.iter().cloned());
+            iterations.push(AgentIteration {
+                query,
+                new_results,
+                confidence: tiered.confidence,
+                cost,
+                seed,
+                gaps,
+            });
+
+            if new_results == 0 {
+                break AgentStop::NoNewEvidence;
+            }
+        };
+
+        log::info!("🏁 研究結束（{:?}），共 {} 輪、{} 個結果", stop, iterations.len(), evidence.len());
+        Ok(AgentRun {
+            question: question.to_string(),
+            iterations,
+            evidence,
+            cost_estimate: spent,
+            stop,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::CompletionFuture;
+    use crate::provider::{ProviderFuture, SearchProvider};
+    use crate::routing::TieredConfig;
+
+    fn result(url: &str, snippet: &str) -> SearchResult {
+        SearchResult {
+            title: url.to_string(),
+            url: url.to_string(),
+            snippet: Some(snippet.to_string()),
+            content: None,
+            published_date: None,
+            metadata: Default::default(),
+        }
+    }
+
+    /// 一般查詢只找到 tokio 的概述；追問 scheduler 時才有排程器的資料
+    struct Library;
+
+    impl SearchProvider for Library {
+        fn name(&self) -> &str {
+            "library"
+        }
+
+        fn search<'a>(&'a self, query: &'a str, _previous: &'a [SearchResult], _num: usize) -> ProviderFuture<'a> {
+            let results = if query.starts_with("tokio scheduler") {
+                vec![
+                    result("https://a.com/sched", "The tokio scheduler uses work stealing."),
+                    result("https://b.com/sched", "Each tokio scheduler worker owns a queue."),
+                    result("https://docs.rs/tokio", "Tokio is an async runtime."),
+                ]
+            } else {
+                vec![
+                    result("https://docs.rs/tokio", "Tokio is an async runtime."),
+                    result("https://tokio.rs", "Tokio powers many Rust services."),
+                ]
+            };
+            Box::pin(async move { Ok(results) })
+        }
+    }
+
+    fn agent() -> ResearchAgent {
+        let retrieval = TieredRetrieval::from_providers(TieredConfig::default(), vec![Box::new(Library)]).unwrap();
+        ResearchAgent::new(retrieval)
+    }
+
+    #[test]
+    fn test_keyword_gaps() {
+        let evidence = vec![
+            result("https://docs.rs/tokio", "Tokio is an async runtime."),
+            result("https://tokio.rs", "Tokio powers many Rust services."),
+        ];
+        let gaps = KeywordGaps::default();
+        assert_eq!(gaps.gaps("How does the tokio scheduler work?", &evidence), vec!["tokio scheduler work"]);
+        assert!(gaps.gaps("What is tokio?", &evidence).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_agent_follows_gaps_until_covered() {
+        let run = agent().run("How does the tokio scheduler work?").await.unwrap();
+        assert_eq!(run.iterations.len(), 2);
+        assert_eq!(run.iterations[0].gaps, vec!["tokio scheduler work"]);
+        assert_eq!(run.iterations[1].query, "tokio scheduler work");
+        // docs.rs/tokio 在第二輪重複出現，不再計入
+        assert_eq!(run.iterations[1].new_results, 2);
+        assert_eq!(run.evidence.len(), 4);
+        // 第二輪後 "work" 仍只有一個來源，但同樣的追問已查過
+        assert_eq!(run.stop, AgentStop::NoGaps);
+    }
+
+    #[tokio::test]
+    async fn test_agent_respects_iteration_limit() {
+        let run = agent()
+            .with_config(AgentConfig {
+                max_iterations: 1,
+                ..Default::default()
+            })
+            .run("How does the tokio scheduler work?")
+            .await
+            .unwrap();
+        assert_eq!(run.iterations.len(), 1);
+        assert_eq!(run.stop, AgentStop::MaxIterations);
+
+        let free_only = agent()
+            .with_config(AgentConfig {
+                max_cost: Some(0.0),
+                ..Default::default()
+            })
+            .run("How does the tokio scheduler work?")
+            .await
+            .unwrap();
+        assert_eq!(free_only.iterations.len(), 1);
+        assert_eq!(free_only.stop, AgentStop::MaxCost);
+    }
+
+    /// 任何種子都回傳同一組相似頁面，其中一個與搜尋結果重複
+    struct Neighbours;
+
+    impl SimilarProvider for Neighbours {
+        fn name(&self) -> &str {
+            "neighbours"
+        }
+
+        fn find_similar<'a>(&'a self, _url: &'a str, num: usize) -> ProviderFuture<'a> {
+            let pages = vec![
+                result("https://tokio.rs", "Tokio powers many Rust services."),
+                result("https://smol.rs", "Smol is a small async runtime."),
+                result("https://async.rs", "async-std mirrors the standard library."),
+            ];
+            Box::pin(async move { Ok(pages.into_iter().take(num).collect()) })
+        }
+
+        fn cost(&self) -> f32 {
+            0.005
+        }
+    }
+
+    #[tokio::test]
+    async fn test_agent_expands_from_seed() {
+        let run = agent()
+            .with_similar(Arc::new(Neighbours))
+            .with_config(AgentConfig {
+                max_iterations: 1,
+                ..Default::default()
+            })
+            .run("What is tokio?")
+            .await
+            .unwrap();
+        let iteration = &run.iterations[0];
+        assert_eq!(iteration.seed.as_deref(), Some("https://docs.rs/tokio"));
+        // tokio.rs 已在搜尋結果中
+        assert_eq!(iteration.new_results, 4);
+        assert!((run.cost_estimate - 0.005).abs() < 1e-6);
+
+        // 預算不足時不延伸
+        let capped = agent()
+            .with_similar(Arc::new(Neighbours))
+            .with_config(AgentConfig {
+                max_iterations: 1,
+                max_cost: Some(0.001),
+                ..Default::default()
+            })
+            .run("What is tokio?")
+            .await
+            .unwrap();
+        assert_eq!(capped.iterations[0].seed, None);
+        assert_eq!(capped.evidence.len(), 2);
+    }
+
+    struct Followups(&'static str);
+
+    impl Completion for Followups {
+        fn complete<'a>(&'a self, request: &'a CompletionRequest) -> CompletionFuture<'a> {
+            assert!(request.prompt.contains("[1] https://docs.rs/tokio"));
+            Box::pin(async move { Ok(self.0.to_string()) })
+        }
+
+        fn name(&self) -> &'static str {
+            "followups"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_llm_gaps() {
+        let evidence = vec![result("https://docs.rs/tokio", "Tokio is an async runtime.")];
+        let gaps = LlmGaps::new(Arc::new(Followups("1. tokio scheduler design\n- \"tokio io driver\"\n\n")), "m");
+        assert_eq!(
+            gaps.find_gaps("How does tokio work?", &evidence).await.unwrap(),
+            vec!["tokio scheduler design", "tokio io driver"]
+        );
+        let done = LlmGaps::new(Arc::new(Followups("NONE")), "m");
+        assert!(done.find_gaps("How does tokio work?", &evidence).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,473 @@
+//! 問題拆解 - 把複雜查詢拆成 2 ~ 5 個聚焦的子查詢，並行檢索後融合
+//!
+//! `RuleDecomposer` 先依問號、分號與換行分句，再依子句連接詞（`, and`、`以及`、`並提出` ...）
+//! 切開；後段子句缺少主題時補上第一段的關鍵字，例如
+//! 「assess the security of bluetooth le pairing and propose mitigations」拆成
+//! 「assess the security of bluetooth le pairing」與「propose mitigations security bluetooth le pairing」。
+//! `LlmDecomposer` 交給模型拆解，失敗時退回規則。
+
+use super::query_lines;
+use crate::llm::{Completion, CompletionRequest};
+use crate::routing::condense::condense_keywords;
+use crate::routing::tiered_retrieval::fuse_tiers;
+use crate::routing::{TieredResult, TieredRetrieval};
+use crate::types::{SearchError, SearchResult};
+use crate::vectorstore::hybrid::RRF_K;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Poll;
+
+/// 子查詢數上限
+pub const MAX_SUB_QUERIES: usize = 5;
+
+/// 子問題最少字元數，過短的片段（例如只剩「以及」）併回原查詢
+const MIN_SUB_QUESTION_CHARS: usize = 8;
+
+/// 一律切開的子句連接詞（比對時英文不分大小寫，較長者優先）
+const CLAUSE_SEPARATORS: &[&str] = &[
+    ", and then ", " and then ", ", as well as ", " as well as ", ", and also ", " and also ", ", and ",
+    ", then ", "以及", "並且", "然後", "同時",
+];
+
+/// 後面接著動作動詞時才切開的連接詞；`tcp and udp` 這類並列名詞不切
+const VERB_CONJUNCTIONS: &[&str] = &[" and ", ", ", "並", "，"];
+
+/// 引出獨立子任務的動詞
+const ACTION_VERBS: &[&str] = &[
+    "analyse", "analyze", "assess", "compare", "describe", "design", "estimate", "evaluate", "explain",
+    "find", "identify", "investigate", "list", "outline", "propose", "recommend", "review", "suggest",
+    "summarise", "summarize", "分析", "評估", "比較", "說明", "解釋", "提出", "列出", "找出", "建議",
+    "設計", "總結", "整理",
+];
+
+/// `Decomposer::decompose` 回傳的 future
+pub type DecomposeFuture<'a> = Pin<Box<dyn Future<Output = Vec<String>> + Send + 'a>>;
+
+/// 問題拆解器
+pub trait Decomposer: Send + Sync {
+    /// 拆成至多 `max` 個子查詢；無法拆解時只有原查詢
+    fn decompose<'a>(&'a self, query: &'a str, max: usize) -> DecomposeFuture<'a>;
+
+    /// 用於日誌的名稱
+    fn name(&self) -> &'static str;
+}
+
+/// 依標點與子句連接詞拆解（不需要外部服務）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RuleDecomposer;
+
+impl RuleDecomposer {
+    /// 同步拆解
+    pub fn split(&self, query: &str, max: usize) -> Vec<String> {
+        let mut sentences = split_sentences(query);
+        if sentences.is_empty() {
+            sentences.push(query.trim().to_string());
+        }
+        let mut queries: Vec<String> = Vec::new();
+        for sentence in &sentences {
+            for clause in split_clauses(sentence) {
+                push_question(&mut queries, &clause);
+            }
+        }
+
+        if queries.len() < 2 || max < 2 {
+            return vec![query.trim().to_string()];
+        }
+        queries.truncate(max.min(MAX_SUB_QUERIES));
+        queries
+    }
+}
+
+impl Decomposer for RuleDecomposer {
+    fn decompose<'a>(&'a self, query: &'a str, max: usize) -> DecomposeFuture<'a> {
+        Box::pin(async move { self.split(query, max) })
+    }
+
+    fn name(&self) -> &'static str {
+        "rules"
+    }
+}
+
+const DECOMPOSE_SYSTEM: &str = "You split research questions into focused web search queries. \
+Each query must stand on its own, so repeat the subject instead of using pronouns. \
+Reply with one query per line, without numbering or commentary. \
+If the question is already focused, reply with the question unchanged.";
+
+/// 由 LLM 拆解；呼叫失敗時退回 `RuleDecomposer`
+pub struct LlmDecomposer {
+    completion: Arc<dyn Completion>,
+    model: String,
+}
+
+impl LlmDecomposer {
+    pub fn new(completion: Arc<dyn Completion>, model: &str) -> Self {
+        Self {
+            completion,
+            model: model.to_string(),
+        }
+    }
+
+    async fn ask(&self, query: &str, max: usize) -> Vec<String> {
+        let max = max.min(MAX_SUB_QUERIES);
+        if max < 2 {
+            return vec![query.trim().to_string()];
+        }
+        let prompt = format!("Split into 2 to {} search queries:\n\n{}", max, query.trim());
+        let request = CompletionRequest::new(&self.model, &prompt)
+            .with_system(DECOMPOSE_SYSTEM)
+            .with_max_tokens(300);
+        match self.completion.complete(&request).await {
+            Ok(reply) => {
+                let queries = query_lines(&reply, max);
+                if queries.len() < 2 {
+                    return vec![query.trim().to_string()];
+                }
+                queries
+            }
+            Err(e) => {
+                log::warn!("⚠️  LLM 拆解 ({}) 失敗，改用規則: {}", self.completion.name(), e);
+                RuleDecomposer.split(query, max)
+            }
+        }
+    }
+}
+
+impl Decomposer for LlmDecomposer {
+    fn decompose<'a>(&'a self, query: &'a str, max: usize) -> DecomposeFuture<'a> {
+        Box::pin(self.ask(query, max))
+    }
+
+    fn name(&self) -> &'static str {
+        "llm"
+    }
+}
+
+/// 依問號、分號與換行把查詢拆成子問題；拆不出兩題以上時只有原查詢
+pub fn sub_questions(query: &str, max: usize) -> Vec<String> {
+    let mut questions = split_sentences(query);
+    if questions.len() < 2 {
+        return vec![query.trim().to_string()];
+    }
+    questions.truncate(max.max(1));
+    questions
+}
+
+fn split_sentences(query: &str) -> Vec<String> {
+    let mut questions: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for c in query.chars() {
+        current.push(c);
+        if matches!(c, '?' | '？' | ';' | '；' | '\n') {
+            push_question(&mut questions, &current);
+            current.clear();
+        }
+    }
+    push_question(&mut questions, &current);
+    questions
+}
+
+fn push_question(questions: &mut Vec<String>, fragment: &str) {
+    let question = fragment.trim().trim_end_matches([';', '；']).trim();
+    if question.chars().count() < MIN_SUB_QUESTION_CHARS {
+        return;
+    }
+    if !questions.iter().any(|q| q.eq_ignore_ascii_case(question)) {
+        questions.push(question.to_string());
+    }
+}
+
+/// 依子句連接詞切開單一句子；後段缺少主題時補上第一段的關鍵字，任一段過短時不切
+fn split_clauses(sentence: &str) -> Vec<String> {
+    let mut parts: Vec<&str> = Vec::new();
+    // 只轉換 ASCII 大小寫，位元組位置與原字串一致
+    let lower = sentence.to_ascii_lowercase();
+    let (mut start, mut i) = (0, 0);
+    while i < lower.len() {
+        if !lower.is_char_boundary(i) {
+            i += 1;
+            continue;
+        }
+        let rest = &lower[i..];
+        let separator = CLAUSE_SEPARATORS.iter().find(|s| rest.starts_with(**s)).or_else(|| {
+            VERB_CONJUNCTIONS.iter().find(|c| {
+                rest.strip_prefix(**c)
+                    .is_some_and(|after| ACTION_VERBS.iter().any(|verb| starts_with_word(after, verb)))
+            })
+        });
+        match separator {
+            Some(separator) => {
+                parts.push(&sentence[start..i]);
+                i += separator.len();
+                start = i;
+            }
+            None => i += 1,
+        }
+    }
+    parts.push(&sentence[start..]);
+
+    let parts: Vec<&str> = parts
+        .into_iter()
+        .map(|p| p.trim().trim_matches([',', '，', '、']).trim())
+        .collect();
+    if parts.len() < 2 || parts.iter().any(|p| p.is_empty()) {
+        return vec![sentence.to_string()];
+    }
+
+    let topic = topic_keywords(parts[0]);
+    let clauses: Vec<String> = parts
+        .iter()
+        .enumerate()
+        .map(|(i, part)| {
+            let own = topic_keywords(part);
+            let shares_topic = own
+                .iter()
+                .any(|k| topic.iter().any(|t| t.eq_ignore_ascii_case(k)));
+            if i == 0 || shares_topic || topic.is_empty() {
+                part.to_string()
+            } else {
+                format!("{} {}", part, topic.join(" "))
+            }
+        })
+        .collect();
+    if clauses.iter().any(|c| c.chars().count() < MIN_SUB_QUESTION_CHARS) {
+        return vec![sentence.to_string()];
+    }
+    clauses
+}
+
+/// 去掉停用詞與動作動詞後的關鍵字，依原文順序
+fn topic_keywords(text: &str) -> Vec<String> {
+    condense_keywords(text, usize::MAX)
+        .split_whitespace()
+        .map(|k| k.trim_matches('"'))
+        .filter(|k| !ACTION_VERBS.contains(&k.to_lowercase().as_str()))
+        .map(str::to_string)
+        .collect()
+}
+
+/// `text` 以 `word` 開頭，且英文單字沒有延續（`list` 不比對 `listen`）
+fn starts_with_word(text: &str, word: &str) -> bool {
+    text.strip_prefix(word)
+        .is_some_and(|after| !after.starts_with(|c: char| c.is_ascii_alphabetic()))
+}
+
+/// 單一子查詢的檢索結果
+#[derive(Debug)]
+pub struct SubQuery {
+    pub query: String,
+    /// 搜尋失敗時為 None
+    pub tiered: Option<TieredResult>,
+}
+
+/// 拆解後並行檢索的結果
+#[derive(Debug)]
+pub struct DecomposedSearch {
+    pub query: String,
+    pub sub_queries: Vec<SubQuery>,
+    /// 各子查詢結果以 RRF 融合、依網址去重
+    pub results: Vec<SearchResult>,
+    /// 所有子查詢的檢索成本合計（美元）
+    pub cost_estimate: f32,
+}
+
+/// 拆解查詢、並行執行各子查詢的階梯式檢索，再融合結果
+///
+/// 個別子查詢失敗時略過；全部失敗時回傳最後一個錯誤。
+pub async fn search_decomposed(
+    retrieval: &TieredRetrieval,
+    decomposer: &dyn Decomposer,
+    query: &str,
+    max: usize,
+) -> Result<DecomposedSearch, SearchError> {
+    let queries = decomposer.decompose(query, max).await;
+    log::info!("🧩 {} 拆成 {} 個子查詢", decomposer.name(), queries.len());
+    let searches = join_all(queries.iter().map(|q| retrieval.search(q)).collect()).await;
+
+    let mut sub_queries = Vec::new();
+    let mut last_error = None;
+    for (query, search) in queries.into_iter().zip(searches) {
+        let tiered = search
+            .inspect_err(|e| log::warn!("⚠️  子查詢「{}」搜尋失敗: {}", query, e))
+            .map_err(|e| last_error = Some(e))
+            .ok();
+        sub_queries.push(SubQuery { query, tiered });
+    }
+    if let Some(e) = last_error.filter(|_| sub_queries.iter().all(|s| s.tiered.is_none())) {
+        return Err(e);
+    }
+
+    let lists: Vec<&[SearchResult]> = sub_queries
+        .iter()
+        .filter_map(|s| s.tiered.as_ref())
+        .map(|t| t.results.as_slice())
+        .collect();
+    Ok(DecomposedSearch {
+        query: query.trim().to_string(),
+        results: fuse_tiers(&lists, RRF_K),
+        cost_estimate: sub_queries.iter().filter_map(|s| s.tiered.as_ref()).map(|t| t.cost_estimate).sum(),
+        sub_queries,
+    })
+}
+
+/// 在同一個任務中並行等待多個 future，輸出順序與輸入相同
+pub(crate) async fn join_all<F: Future>(futures: Vec<F>) -> Vec<F::Output> {
+    let mut futures: Vec<Pin<Box<F>>> = futures.into_iter().map(Box::pin).collect();
+    let mut outputs: Vec<Option<F::Output>> = futures.iter().map(|_| None).collect();
+    std::future::poll_fn(|cx| {
+        let mut pending = false;
+        for (future, output) in futures.iter_mut().zip(outputs.iter_mut()) {
+            if output.is_some() {
+                continue;
+            }
+            match future.as_mut().poll(cx) {
+                Poll::Ready(value) => *output = Some(value),
+                Poll::Pending => pending = true,
+            }
+        }
+        if pending {
+            Poll::Pending
+        } else {
+            Poll::Ready(())
+        }
+    })
+    .await;
+    outputs.into_iter().flatten().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::CompletionFuture;
+    use crate::provider::{ProviderFuture, SearchProvider};
+    use crate::routing::TieredConfig;
+
+    #[test]
+    fn test_sub_questions() {
+        assert_eq!(
+            sub_questions("What is tokio? How does its scheduler work?", 4),
+            vec!["What is tokio?", "How does its scheduler work?"]
+        );
+        assert_eq!(sub_questions("Rust 的所有權是什麼？借用檢查器如何運作？", 4).len(), 2);
+        // 單一問題、重複與過短的片段
+        assert_eq!(sub_questions("tokio runtime", 4), vec!["tokio runtime"]);
+        assert_eq!(sub_questions("What is tokio? what is tokio? ok?", 4), vec!["What is tokio? what is tokio? ok?"]);
+        assert_eq!(sub_questions("What is tokio; what is async-std; what is smol", 2).len(), 2);
+    }
+
+    #[test]
+    fn test_rule_decomposer_splits_clauses() {
+        let rules = RuleDecomposer;
+        assert_eq!(
+            rules.split("Assess the security of Bluetooth LE pairing and propose mitigations", 5),
+            vec!["Assess the security of Bluetooth LE pairing", "propose mitigations security Bluetooth LE pairing"]
+        );
+        assert_eq!(
+            rules.split("分析 Bose 藍牙協議的安全性並提出改進方案", 5),
+            vec!["分析 Bose 藍牙協議的安全性", "提出改進方案 Bose 藍牙協議的安全性"]
+        );
+        // 子句之間已有共同主題時不補
+        assert_eq!(
+            rules.split("How does the tokio scheduler work, and then compare the tokio scheduler with smol", 5),
+            vec!["How does the tokio scheduler work", "compare the tokio scheduler with smol"]
+        );
+        // 並列名詞不切
+        assert_eq!(rules.split("difference between tcp and udp", 5), vec!["difference between tcp and udp"]);
+        assert_eq!(rules.split("pros and cons of postgres", 5), vec!["pros and cons of postgres"]);
+    }
+
+    #[test]
+    fn test_rule_decomposer_limits() {
+        let query = "What is tokio? How does its scheduler work? Explain the io driver; \
+                     list tokio alternatives and compare their performance";
+        let rules = RuleDecomposer;
+        assert_eq!(rules.split(query, 10).len(), MAX_SUB_QUERIES);
+        assert_eq!(rules.split(query, 3).len(), 3);
+        assert_eq!(rules.split(query, 1), vec![query]);
+    }
+
+    struct Planner(Option<&'static str>);
+
+    impl Completion for Planner {
+        fn complete<'a>(&'a self, request: &'a CompletionRequest) -> CompletionFuture<'a> {
+            assert!(request.prompt.contains("2 to 3"));
+            Box::pin(async move {
+                self.0
+                    .map(str::to_string)
+                    .ok_or_else(|| SearchError::NetworkError("offline".to_string()))
+            })
+        }
+
+        fn name(&self) -> &'static str {
+            "planner"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_llm_decomposer() {
+        let query = "What is tokio? How does its scheduler work?";
+        let llm = LlmDecomposer::new(
+            Arc::new(Planner(Some("1. tokio runtime overview\n2. tokio scheduler work stealing\n"))),
+            "m",
+        );
+        assert_eq!(
+            llm.decompose(query, 3).await,
+            vec!["tokio runtime overview", "tokio scheduler work stealing"]
+        );
+        // 模型認為不需拆解
+        let single = LlmDecomposer::new(Arc::new(Planner(Some(query))), "m");
+        assert_eq!(single.decompose(query, 3).await, vec![query]);
+        // 呼叫失敗退回規則
+        let offline = LlmDecomposer::new(Arc::new(Planner(None)), "m");
+        assert_eq!(offline.decompose(query, 3).await, RuleDecomposer.split(query, 3));
+    }
+
+    /// 依查詢關鍵字回傳固定結果
+    struct Library;
+
+    impl SearchProvider for Library {
+        fn name(&self) -> &str {
+            "library"
+        }
+
+        fn search<'a>(&'a self, query: &'a str, _previous: &'a [SearchResult], _num: usize) -> ProviderFuture<'a> {
+            let page = |url: &str| SearchResult {
+                title: url.to_string(),
+                url: url.to_string(),
+                snippet: Some(format!("About {}", query)),
+                content: None,
+                published_date: None,
+                metadata: Default::default(),
+            };
+            let results = if query.contains("scheduler") {
+                vec![page("https://b.com/sched"), page("https://docs.rs/tokio")]
+            } else if query.contains("fail") {
+                return Box::pin(async { Err(SearchError::NetworkError("offline".to_string())) });
+            } else {
+                vec![page("https://www.docs.rs/tokio/")]
+            };
+            Box::pin(async move { Ok(results) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_decomposed_fuses_sub_queries() {
+        let retrieval = TieredRetrieval::from_providers(TieredConfig::default(), vec![Box::new(Library)]).unwrap();
+        let search = search_decomposed(
+            &retrieval,
+            &RuleDecomposer,
+            "What is the tokio runtime? Why did the build fail? How does the tokio scheduler work?",
+            5,
+        )
+        .await
+        .unwrap();
+        assert_eq!(search.sub_queries.len(), 3);
+        assert!(search.sub_queries[1].tiered.is_none());
+        // docs.rs/tokio 出現在兩個子查詢，融合後排第一
+        let urls: Vec<&str> = search.results.iter().map(|r| r.url.as_str()).collect();
+        assert_eq!(urls, vec!["https://www.docs.rs/tokio/", "https://b.com/sched"]);
+
+        let failed = search_decomposed(&retrieval, &RuleDecomposer, "Why did the build fail?", 5).await;
+        assert!(matches!(failed, Err(SearchError::NetworkError(_))));
+    }
+}
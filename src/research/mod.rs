@@ -0,0 +1,387 @@
+//! 深度研究 - `SearchStrategy::DeepResearch` 的完整流程
+//!
+//! 將查詢拆成子問題 → 各自執行階梯式檢索 → 抓取排名最前的頁面全文 → 跨子問題去重 →
+//! 逐題合成附引用的段落，彙整成共用同一份來源清單的 `ResearchReport`。
+//! 子問題由 `decompose` 拆解並行檢索；`agent` 則是單一問題的迭代版本：搜尋、閱讀、
+//! 找出缺口後改寫查詢再搜尋。
+
+pub mod agent;
+pub mod decompose;
+
+pub use decompose::{sub_questions, Decomposer, LlmDecomposer, RuleDecomposer};
+
+use decompose::join_all;
+use crate::fetcher::Fetcher;
+use crate::processing::{ContextPruner, HeuristicTokenizer, Tokenizer};
+use crate::routing::corroboration::url_key;
+use crate::routing::{RetrievalTier, TieredRetrieval};
+use crate::synthesis::{map_citations, Synthesizer};
+use crate::types::{SearchError, SearchResult};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// 深度研究配置
+#[derive(Debug, Clone)]
+pub struct ResearchConfig {
+    /// 子問題上限（最多 `decompose::MAX_SUB_QUERIES`）
+    pub max_sub_questions: usize,
+    /// 每個子問題交給合成器的結果數
+    pub sources_per_question: usize,
+    /// 每個子問題抓取全文的頁面數（需設定 `Fetcher`）
+    pub pages_per_question: usize,
+    /// 抓取的頁面裁剪到的 token 數
+    pub page_tokens: usize,
+    /// 來源清單中每個來源的摘錄長度（token）
+    pub excerpt_tokens: usize,
+}
+
+impl Default for ResearchConfig {
+    fn default() -> Self {
+        Self {
+            max_sub_questions: 4,
+            sources_per_question: 6,
+            pages_per_question: 3,
+            page_tokens: 1500,
+            excerpt_tokens: 80,
+        }
+    }
+}
+
+/// 報告引用的來源
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResearchSource {
+    /// 報告中的編號（從 1 起算）
+    pub number: usize,
+    pub url: String,
+    pub title: String,
+    /// 搜尋摘要或頁面開頭的摘錄
+    pub excerpt: Option<String>,
+}
+
+/// 單一子問題的研究結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResearchSection {
+    pub question: String,
+    /// 以 `[n]` 引用報告來源清單的段落；結果不足以回答時為空
+    pub findings: String,
+    pub confidence: f32,
+    pub tier_used: RetrievalTier,
+    /// 此題引用的來源編號
+    pub sources: Vec<usize>,
+}
+
+/// 深度研究報告
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResearchReport {
+    pub query: String,
+    pub sections: Vec<ResearchSection>,
+    /// 跨子問題去重後的來源，依首次引用編號
+    pub sources: Vec<ResearchSource>,
+    /// 所有子問題的檢索成本合計（美元）
+    pub cost_estimate: f32,
+    /// 實際用到的搜尋提供者
+    pub engines: Vec<String>,
+}
+
+impl fmt::Display for ResearchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.query)?;
+        for (i, section) in self.sections.iter().enumerate() {
+            writeln!(f)?;
+            writeln!(f, "{}. {}（置信度 {:.2}）", i + 1, section.question, section.confidence)?;
+            if section.findings.is_empty() {
+                writeln!(f, "（結果中沒有足以回答的內容）")?;
+            } else {
+                writeln!(f, "{}", section.findings)?;
+            }
+        }
+        if !self.sources.is_empty() {
+            writeln!(f)?;
+        }
+        for source in &self.sources {
+            writeln!(f, "[{}] {} — {}", source.number, source.title, source.url)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "花費: ${:.4} | 引擎: {}", self.cost_estimate, self.engines.join(", "))
+    }
+}
+
+/// 深度研究流程
+pub struct DeepResearch {
+    retrieval: TieredRetrieval,
+    fetcher: Option<Fetcher>,
+    synthesizer: Synthesizer,
+    decomposer: Arc<dyn Decomposer>,
+    config: ResearchConfig,
+}
+
+impl DeepResearch {
+    /// 只用搜尋結果的摘要與 Tavily 提取的全文，以抽取式合成
+    pub fn new(retrieval: TieredRetrieval) -> Self {
+        Self {
+            retrieval,
+            fetcher: None,
+            synthesizer: Synthesizer::extractive(),
+            decomposer: Arc::new(RuleDecomposer),
+            config: ResearchConfig::default(),
+        }
+    }
+
+    /// 以第一方抓取補上排名最前頁面的全文
+    pub fn with_fetcher(mut self, fetcher: Fetcher) -> Self {
+        self.fetcher = Some(fetcher);
+        self
+    }
+
+    /// 改用指定的合成器（例如 LLM 撰寫）
+    pub fn with_synthesizer(mut self, synthesizer: Synthesizer) -> Self {
+        self.synthesizer = synthesizer;
+        self
+    }
+
+    /// 改用其他問題拆解器（例如 `LlmDecomposer`）
+    pub fn with_decomposer(mut self, decomposer: Arc<dyn Decomposer>) -> Self {
+        self.decomposer = decomposer;
+        self
+    }
+
+    pub fn with_config(mut self, config: ResearchConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn config(&self) -> &ResearchConfig {
+        &self.config
+    }
+
+    /// 執行深度研究
+    ///
+    /// 單一子問題搜尋失敗時略過該題；所有子問題都失敗時回傳最後一個錯誤。
+    pub async fn run(&self, query: &str) -> Result<ResearchReport, SearchError> {
+        let mut report = ResearchReport {
+            query: query.trim().to_string(),
+            sections: Vec::new(),
+            sources: Vec::new(),
+            cost_estimate: 0.0,
+            engines: Vec::new(),
+        };
+        // 已抓取頁面的全文與已編號的來源，依正規化網址跨子問題共用
+        let mut pages: HashMap<String, String> = HashMap::new();
+        let mut numbers: HashMap<String, usize> = HashMap::new();
+        let mut last_error = None;
+
+        // 各子問題並行檢索，之後依序抓取與合成，來源編號才會穩定
+        let questions = self.decomposer.decompose(query, self.config.max_sub_questions).await;
+        let searches = join_all(questions.iter().map(|q| self.retrieval.search(q)).collect()).await;
+
+        for (question, search) in questions.into_iter().zip(searches) {
+            log::info!("🔬 子問題: {}", question);
+            let tiered = match search {
+                Ok(tiered) => tiered,
+                Err(e) => {
+                    log::warn!("⚠️  子問題「{}」搜尋失敗: {}", question, e);
+                    last_error = Some(e);
+                    continue;
+                }
+            };
+            report.cost_estimate += tiered.cost_estimate;
+            for engine in tiered.provider.split('+') {
+                if !report.engines.iter().any(|e| e == engine) {
+                    report.engines.push(engine.to_string());
+                }
+            }
+
+            let mut results = tiered.results;
+            results.truncate(self.config.sources_per_question);
+            if let Some(fetcher) = &self.fetcher {
+                let top = self.config.pages_per_question.min(results.len());
+                read_pages(fetcher, &question, &mut results[..top], &mut pages, self.config.page_tokens).await;
+            }
+            let answer = self.synthesizer.synthesize(&question, &results).await?;
+
+            // 答案的編號對應到報告的來源清單
+            let mut cited = Vec::new();
+            let global: Vec<usize> = answer
+                .citations
+                .iter()
+                .map(|citation| {
+                    let key = url_key(&citation.url);
+                    let number = match numbers.get(&key) {
+                        Some(&number) => number,
+                        None => {
+                            let number = report.sources.len() + 1;
+                            numbers.insert(key, number);
+                            report.sources.push(ResearchSource {
+                                number,
+                                url: citation.url.clone(),
+                                title: citation.title.clone(),
+                                excerpt: results
+                                    .iter()
+                                    .find(|r| r.url == citation.url)
+                                    .and_then(|r| self.excerpt(r)),
+                            });
+                            number
+                        }
+                    };
+                    if !cited.contains(&number) {
+                        cited.push(number);
+                    }
+                    number
+                })
+                .collect();
+
+            report.sections.push(ResearchSection {
+                findings: map_citations(&answer.text, |n| global.get(n - 1).copied().unwrap_or(n)),
+                question,
+                confidence: tiered.confidence,
+                tier_used: tiered.tier_used,
+                sources: cited,
+            });
+        }
+
+        match last_error {
+            Some(e) if report.sections.is_empty() => Err(e),
+            _ => Ok(report),
+        }
+    }
+
+    fn excerpt(&self, result: &SearchResult) -> Option<String> {
+        let text = result.snippet.as_deref().or(result.content.as_deref())?.trim();
+        (!text.is_empty()).then(|| HeuristicTokenizer.truncate(text, self.config.excerpt_tokens).to_string())
+    }
+}
+
+/// 抓取沒有全文的結果，依 `question` 裁剪後填入 `content`；抓取失敗或遇到付費牆時保留摘要
+///
+/// `pages` 以正規化網址保存清理後的全文，跨查詢共用，同一頁面不重複抓取。
+pub(crate) async fn read_pages(
+    fetcher: &Fetcher,
+    question: &str,
+    results: &mut [SearchResult],
+    pages: &mut HashMap<String, String>,
+    page_tokens: usize,
+) {
+    let urls: Vec<String> = results
+        .iter()
+        .filter(|r| r.content.is_none() && !pages.contains_key(&url_key(&r.url)))
+        .map(|r| r.url.clone())
+        .collect();
+    for (url, page) in urls.iter().zip(fetcher.fetch_all(&urls).await) {
+        match page {
+            // 付費牆的前導段落不值得佔用裁剪預算，標記在結果上並保留摘要
+            Ok(page) if !page.has_full_text() => {
+                log::debug!("{} 有付費牆，保留摘要", url);
+                for result in results.iter_mut().filter(|r| r.url == *url) {
+                    page.mark_paywall(&mut result.metadata);
+                }
+            }
+            Ok(page) => {
+                pages.insert(url_key(url), page.cleaned_text());
+            }
+            Err(e) => log::debug!("無法抓取 {}: {}", url, e),
+        }
+    }
+
+    let pruner = ContextPruner::new(page_tokens).with_sentence_pruning(question);
+    for result in results.iter_mut().filter(|r| r.content.is_none()) {
+        if let Some(text) = pages.get(&url_key(&result.url)) {
+            result.content = pruner.prune_result(result, text);
+        }
+    }
+}
+
+/// 每行一個查詢；去掉清單符號與編號，`NONE` 表示沒有
+pub(crate) fn query_lines(reply: &str, max: usize) -> Vec<String> {
+    reply
+        .lines()
+        .map(|line| {
+            line.trim()
+                .trim_start_matches(|c: char| c.is_ascii_digit() || matches!(c, '-' | '*' | '•' | '.' | ')'))
+                .trim()
+                .trim_matches('"')
+        })
+        .filter(|line| !line.is_empty() && !line.eq_ignore_ascii_case("none"))
+        .take(max)
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::{ProviderFuture, SearchProvider};
+    use crate::routing::TieredConfig;
+
+    /// 依查詢關鍵字回傳固定結果
+    struct Library;
+
+    impl SearchProvider for Library {
+        fn name(&self) -> &str {
+            "library"
+        }
+
+        fn search<'a>(&'a self, query: &'a str, _previous: &'a [SearchResult], _num: usize) -> ProviderFuture<'a> {
+            let page = |url: &str, content: &str| SearchResult {
+                title: format!("Title {}", url),
+                url: url.to_string(),
+                snippet: Some(content.to_string()),
+                content: None,
+                published_date: None,
+                metadata: Default::default(),
+            };
+            let results = if query.contains("scheduler") {
+                vec![
+                    page("https://docs.rs/tokio", "The tokio scheduler uses work stealing."),
+                    page("https://b.com/sched", "Each scheduler worker owns a local queue."),
+                ]
+            } else if query.contains("fail") {
+                return Box::pin(async { Err(SearchError::NetworkError("offline".to_string())) });
+            } else {
+                vec![page("https://www.docs.rs/tokio/", "Tokio is an async runtime for Rust.")]
+            };
+            Box::pin(async move { Ok(results) })
+        }
+    }
+
+    fn research() -> DeepResearch {
+        let retrieval = TieredRetrieval::from_providers(TieredConfig::default(), vec![Box::new(Library)]).unwrap();
+        DeepResearch::new(retrieval)
+    }
+
+    #[tokio::test]
+    async fn test_report_dedups_sources_across_questions() {
+        let report = research()
+            .run("What is the tokio runtime? How does the tokio scheduler work?")
+            .await
+            .unwrap();
+        assert_eq!(report.sections.len(), 2);
+        assert_eq!(report.engines, vec!["library"]);
+
+        // docs.rs/tokio 在兩題都被引用，只編號一次
+        assert_eq!(report.sources.len(), 2);
+        assert_eq!(report.sources[0].url, "https://www.docs.rs/tokio/");
+        assert_eq!(report.sections[0].sources, vec![1]);
+        assert_eq!(report.sections[1].sources, vec![1, 2]);
+        assert!(report.sections[1].findings.contains("work stealing [1]"));
+        assert!(report.sections[1].findings.contains("local queue [2]"));
+        assert_eq!(report.sources[1].excerpt.as_deref(), Some("Each scheduler worker owns a local queue."));
+
+        let text = report.to_string();
+        assert!(text.contains("2. How does the tokio scheduler work?"));
+        assert!(text.contains("[2] Title https://b.com/sched — https://b.com/sched"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_sub_questions_are_skipped() {
+        let report = research()
+            .run("Why did the tokio build fail? What is the tokio runtime?")
+            .await
+            .unwrap();
+        assert_eq!(report.sections.len(), 1);
+        assert_eq!(report.sections[0].question, "What is the tokio runtime?");
+
+        let all_failed = research().run("Why did the build fail?").await;
+        assert!(matches!(all_failed, Err(SearchError::NetworkError(_))));
+    }
+}
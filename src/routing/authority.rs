@@ -0,0 +1,288 @@
+//! 權威網域清單 - 依網域加權的來源可信度，供置信度計算使用
+//!
+//! 清單可由設定反序列化，依類別（例如 `security`）另外加權，也可匯入 Tranco / Majestic 排名檔：
+//!
+//! ```toml
+//! [authority]
+//! tranco_file = "top-1m.csv"
+//! tranco_top = 10000
+//!
+//! [authority.general]
+//! "docs.rs" = 1.0
+//! "*.wikipedia.org" = 0.8
+//!
+//! [authority.categories.security]
+//! "nvd.nist.gov" = 1.0
+//! "*.gov" = 0.7
+//! ```
+//!
+//! 網域 `example.com` 比對自身與所有子網域；`*.example.com` 只比對子網域。
+//! 多個項目命中時取最具體（最長）的項目。
+
+use crate::types::SearchError;
+use reqwest::Url;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// 預設權威網域（權重皆為 1.0）
+const DEFAULT_AUTHORITY_DOMAINS: &[&str] = &[
+    "github.com",
+    "stackoverflow.com",
+    "docs.rs",
+    "rust-lang.org",
+    "arxiv.org",
+    "wikipedia.org",
+    "cve.mitre.org",
+    "nvd.nist.gov",
+];
+
+/// 排名檔匯入的網域最高權重：熱門不等於權威
+const DEFAULT_TRANCO_MAX_WEIGHT: f32 = 0.5;
+
+/// 權威網域設定
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthorityConfig {
+    /// 所有查詢共用的網域權重（0.0 ~ 1.0）
+    pub general: BTreeMap<String, f32>,
+    /// 依類別追加的網域權重，與 `general` 合併且優先
+    pub categories: BTreeMap<String, BTreeMap<String, f32>>,
+    /// Tranco / Majestic 排名檔（CSV，`排名,網域` 或 Majestic 的 `GlobalRank,...,Domain,...`）
+    pub tranco_file: Option<PathBuf>,
+    /// 只匯入排名前 N 的網域
+    pub tranco_top: usize,
+    /// 排名第一的網域權重，依排名線性遞減
+    pub tranco_max_weight: f32,
+}
+
+impl Default for AuthorityConfig {
+    fn default() -> Self {
+        Self {
+            general: DEFAULT_AUTHORITY_DOMAINS.iter().map(|d| (d.to_string(), 1.0)).collect(),
+            categories: BTreeMap::new(),
+            tranco_file: None,
+            tranco_top: 10_000,
+            tranco_max_weight: DEFAULT_TRANCO_MAX_WEIGHT,
+        }
+    }
+}
+
+impl AuthorityConfig {
+    /// 建立一般查詢用的清單（含排名檔）
+    pub fn build(&self) -> Result<AuthorityList, SearchError> {
+        self.build_for(None)
+    }
+
+    /// 建立指定類別的清單：排名檔 → `general` → 類別清單，後者覆寫前者
+    pub fn build_for(&self, category: Option<&str>) -> Result<AuthorityList, SearchError> {
+        let mut list = match &self.tranco_file {
+            Some(path) => AuthorityList::from_ranking_file(path, self.tranco_top, self.tranco_max_weight)?,
+            None => AuthorityList::default_empty(),
+        };
+        list.extend(&self.general);
+        if let Some(extra) = category.and_then(|c| self.categories.get(c)) {
+            list.extend(extra);
+        }
+        Ok(list)
+    }
+}
+
+/// 單一權威網域項目
+#[derive(Debug, Clone, PartialEq)]
+struct AuthorityEntry {
+    /// 正規化後的網域（小寫、不含 `*.` 與 `www.`）
+    domain: String,
+    /// `*.` 開頭：只比對子網域
+    subdomains_only: bool,
+    weight: f32,
+}
+
+/// 加權的權威網域清單
+#[derive(Debug, Clone)]
+pub struct AuthorityList {
+    entries: Vec<AuthorityEntry>,
+}
+
+impl Default for AuthorityList {
+    fn default() -> Self {
+        let mut list = Self::default_empty();
+        for domain in DEFAULT_AUTHORITY_DOMAINS {
+            list.insert(domain, 1.0);
+        }
+        list
+    }
+}
+
+impl AuthorityList {
+    fn default_empty() -> Self {
+        Self { entries: Vec::new() }
+    }
+
+    /// 由 `(網域, 權重)` 建立
+    pub fn new<'a>(entries: impl IntoIterator<Item = (&'a str, f32)>) -> Self {
+        let mut list = Self::default_empty();
+        for (domain, weight) in entries {
+            list.insert(domain, weight);
+        }
+        list
+    }
+
+    /// 加入或覆寫網域權重（權重限制在 0.0 ~ 1.0）
+    pub fn insert(&mut self, pattern: &str, weight: f32) {
+        let pattern = pattern.trim().to_lowercase();
+        let (domain, subdomains_only) = match pattern.strip_prefix("*.") {
+            Some(rest) => (rest.to_string(), true),
+            None => (pattern.trim_start_matches("www.").to_string(), false),
+        };
+        if domain.is_empty() {
+            return;
+        }
+        let entry = AuthorityEntry {
+            domain,
+            subdomains_only,
+            weight: weight.clamp(0.0, 1.0),
+        };
+        match self
+            .entries
+            .iter_mut()
+            .find(|e| e.domain == entry.domain && e.subdomains_only == entry.subdomains_only)
+        {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    fn extend(&mut self, entries: &BTreeMap<String, f32>) {
+        for (pattern, weight) in entries {
+            self.insert(pattern, *weight);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 讀取 Tranco（`1,google.com`）或 Majestic（含標題列，`Domain` 欄）排名檔
+    pub fn from_ranking_file(path: &Path, top: usize, max_weight: f32) -> Result<Self, SearchError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| SearchError::ConfigError(format!("無法讀取排名檔 {}: {}", path.display(), e)))?;
+        Ok(Self::from_ranking_csv(&content, top, max_weight))
+    }
+
+    /// 解析排名 CSV；排名第 1 的權重為 `max_weight`，第 `top` 名接近 0
+    pub fn from_ranking_csv(content: &str, top: usize, max_weight: f32) -> Self {
+        let mut lines = content.lines().filter(|l| !l.trim().is_empty()).peekable();
+        // Majestic 有標題列，網域在 `Domain` 欄；Tranco 沒有標題列，網域在第二欄
+        let domain_column = match lines.peek() {
+            Some(header) if header.to_lowercase().contains("domain") => {
+                let column = header
+                    .split(',')
+                    .position(|c| c.trim().eq_ignore_ascii_case("domain"))
+                    .unwrap_or(1);
+                lines.next();
+                column
+            }
+            _ => 1,
+        };
+
+        let mut list = Self::default_empty();
+        for (index, line) in lines.take(top).enumerate() {
+            let Some(domain) = line.split(',').nth(domain_column) else {
+                continue;
+            };
+            let weight = max_weight * (1.0 - index as f32 / top.max(1) as f32);
+            list.insert(domain, weight);
+        }
+        list
+    }
+
+    /// 網址命中的最具體項目：(網域, 權重)
+    pub fn lookup(&self, url: &str) -> Option<(&str, f32)> {
+        let host = host_of(url)?;
+        self.entries
+            .iter()
+            .filter(|e| {
+                let is_subdomain = host.len() > e.domain.len()
+                    && host.ends_with(e.domain.as_str())
+                    && host.as_bytes()[host.len() - e.domain.len() - 1] == b'.';
+                is_subdomain || (!e.subdomains_only && host == e.domain)
+            })
+            .max_by_key(|e| e.domain.len())
+            .map(|e| (e.domain.as_str(), e.weight))
+    }
+
+    /// 網址的權威權重；不在清單中為 0
+    pub fn weight(&self, url: &str) -> f32 {
+        self.lookup(url).map_or(0.0, |(_, weight)| weight)
+    }
+}
+
+fn host_of(url: &str) -> Option<String> {
+    let parsed = Url::parse(url).ok()?;
+    let host = parsed.host_str()?.to_lowercase();
+    Some(host.trim_start_matches("www.").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcard_and_specificity() {
+        let list = AuthorityList::new([("*.gov", 0.6), ("nvd.nist.gov", 1.0), ("wikipedia.org", 0.8)]);
+        assert_eq!(list.lookup("https://nvd.nist.gov/vuln/detail/CVE-2024-3094"), Some(("nvd.nist.gov", 1.0)));
+        assert_eq!(list.weight("https://www.cisa.gov/known-exploited"), 0.6);
+        assert_eq!(list.weight("https://en.wikipedia.org/wiki/Rust"), 0.8);
+        assert_eq!(list.weight("https://wikipedia.org"), 0.8);
+        // 萬用字元不比對頂層本身，也不比對相似字尾
+        assert_eq!(list.weight("https://notwikipedia.org"), 0.0);
+        assert_eq!(list.weight("not a url"), 0.0);
+    }
+
+    #[test]
+    fn test_config_categories() {
+        let config: AuthorityConfig = serde_json::from_value(serde_json::json!({
+            "general": { "docs.rs": 1.0, "github.com": 0.7 },
+            "categories": { "security": { "github.com": 0.4, "*.cert.org": 0.9 } }
+        }))
+        .unwrap();
+        let general = config.build().unwrap();
+        assert_eq!(general.weight("https://github.com/tokio-rs/tokio"), 0.7);
+        assert_eq!(general.weight("https://kb.cert.org/vuls"), 0.0);
+
+        let security = config.build_for(Some("security")).unwrap();
+        assert_eq!(security.weight("https://github.com/advisories"), 0.4);
+        assert_eq!(security.weight("https://kb.cert.org/vuls"), 0.9);
+        assert_eq!(security.weight("https://docs.rs/serde"), 1.0);
+    }
+
+    #[test]
+    fn test_ranking_csv() {
+        let tranco = AuthorityList::from_ranking_csv("1,google.com\n2,github.com\n3,example.com\n", 2, 0.5);
+        assert_eq!(tranco.len(), 2);
+        assert_eq!(tranco.weight("https://google.com"), 0.5);
+        assert_eq!(tranco.weight("https://github.com"), 0.25);
+        assert_eq!(tranco.weight("https://example.com"), 0.0);
+
+        let majestic = AuthorityList::from_ranking_csv(
+            "GlobalRank,TldRank,Domain,TLD\n1,1,facebook.com,com\n2,2,docs.rs,rs\n",
+            10,
+            0.5,
+        );
+        assert_eq!(majestic.lookup("https://docs.rs/tokio").map(|(d, _)| d), Some("docs.rs"));
+    }
+
+    #[test]
+    fn test_missing_ranking_file() {
+        let config = AuthorityConfig {
+            tranco_file: Some(PathBuf::from("/nonexistent/top-1m.csv")),
+            ..Default::default()
+        };
+        assert!(matches!(config.build(), Err(SearchError::ConfigError(_))));
+    }
+}
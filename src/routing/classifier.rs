@@ -0,0 +1,280 @@
+//! 查詢分類器 - 可替換的複雜度分類（規則、嵌入模型、LLM ...）
+//!
+//! `SemanticRouter` 依序詢問 `ClassifierChain` 中的分類器，第一個信心度達到門檻的結果勝出；
+//! 都沒有達到門檻時退回 `RuleClassifier` 的關鍵字規則。
+
+use super::semantic_router::TaskComplexity;
+use crate::embeddings::{cosine_similarity, embed_one, Embedder};
+use crate::types::SearchError;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+/// `QueryClassifier::classify` 回傳的 future
+pub type ClassifyFuture<'a> = Pin<Box<dyn Future<Output = Option<Classification>> + Send + 'a>>;
+
+/// 分類結果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Classification {
+    pub complexity: TaskComplexity,
+    /// 0.0 ~ 1.0
+    pub confidence: f32,
+    /// 做出判斷的分類器名稱
+    pub classifier: &'static str,
+}
+
+/// 查詢複雜度分類器
+pub trait QueryClassifier: Send + Sync {
+    /// 分類查詢；無法判斷（例如模型不可用）時回傳 None
+    fn classify<'a>(&'a self, query: &'a str) -> ClassifyFuture<'a>;
+
+    /// 用於 `--explain` 與日誌的名稱
+    fn name(&self) -> &'static str;
+}
+
+/// 關鍵字規則的信心度固定，只作為最後的退路
+const RULE_CONFIDENCE: f32 = 0.5;
+
+/// 關鍵字與長度規則（不需要外部服務）
+#[derive(Debug, Clone)]
+pub struct RuleClassifier {
+    /// 簡單查詢的最大長度（字元數）
+    pub simple_max_length: usize,
+    /// 複雜查詢的關鍵字
+    pub complex_keywords: Vec<String>,
+}
+
+impl RuleClassifier {
+    pub fn new(simple_max_length: usize, complex_keywords: Vec<String>) -> Self {
+        Self {
+            simple_max_length,
+            complex_keywords,
+        }
+    }
+
+    /// 同步分類
+    pub fn classify_rules(&self, query: &str) -> TaskComplexity {
+        let query_lower = query.to_lowercase();
+        let query_len = query.chars().count();
+
+        // 檢查複雜查詢關鍵字
+        let has_complex_keyword = self.complex_keywords
+            .iter()
+            .any(|kw| query_lower.contains(&kw.to_lowercase()));
+
+        // 檢查多個子句（使用標點符號判斷）
+        let clause_count = query.matches([',', '，', '、']).count() + 1;
+
+        // 檢查問號數量（多個問題 = 複雜）
+        let question_count = query.matches(['?', '？']).count();
+
+        // 分類邏輯
+        if question_count > 1 || clause_count > 2 ||
+           (has_complex_keyword && query_len > 100) {
+            TaskComplexity::Complex
+        } else if has_complex_keyword || query_len > self.simple_max_length {
+            TaskComplexity::Medium
+        } else {
+            TaskComplexity::Simple
+        }
+    }
+
+    pub(crate) fn classification(&self, query: &str) -> Classification {
+        Classification {
+            complexity: self.classify_rules(query),
+            confidence: RULE_CONFIDENCE,
+            classifier: self.name(),
+        }
+    }
+}
+
+impl QueryClassifier for RuleClassifier {
+    fn classify<'a>(&'a self, query: &'a str) -> ClassifyFuture<'a> {
+        Box::pin(async move { Some(self.classification(query)) })
+    }
+
+    fn name(&self) -> &'static str {
+        "rules"
+    }
+}
+
+/// 每個類別取最相近的幾個範例平均
+const TOP_EXEMPLARS_PER_CLASS: usize = 2;
+
+/// 與已標記範例查詢比較嵌入相似度；信心度為最相近類別的平均相似度
+pub struct EmbeddingClassifier {
+    embedder: Arc<dyn Embedder>,
+    exemplars: Vec<(TaskComplexity, String)>,
+    /// 範例查詢的嵌入在第一次分類時計算，失敗時下次重試
+    vectors: OnceCell<Vec<(TaskComplexity, Vec<f32>)>>,
+}
+
+impl EmbeddingClassifier {
+    pub fn new(embedder: Arc<dyn Embedder>, exemplars: Vec<(TaskComplexity, String)>) -> Self {
+        Self {
+            embedder,
+            exemplars,
+            vectors: OnceCell::new(),
+        }
+    }
+
+    async fn exemplar_vectors(&self) -> Result<&[(TaskComplexity, Vec<f32>)], SearchError> {
+        let vectors = self
+            .vectors
+            .get_or_try_init(|| async {
+                let texts: Vec<String> = self.exemplars.iter().map(|(_, q)| q.clone()).collect();
+                let vectors = self.embedder.embed(&texts).await?;
+                Ok::<_, SearchError>(self.exemplars.iter().map(|(c, _)| *c).zip(vectors).collect())
+            })
+            .await?;
+        Ok(vectors)
+    }
+
+    async fn classify_embedding(&self, query: &str) -> Option<Classification> {
+        if self.exemplars.is_empty() {
+            return None;
+        }
+        let exemplars = self
+            .exemplar_vectors()
+            .await
+            .inspect_err(|e| tracing::warn!(error = %e, "Exemplar embedding failed"))
+            .ok()?;
+        let vector = embed_one(self.embedder.as_ref(), query)
+            .await
+            .inspect_err(|e| tracing::warn!(error = %e, "Query embedding failed"))
+            .ok()?;
+
+        [TaskComplexity::Simple, TaskComplexity::Medium, TaskComplexity::Complex]
+            .into_iter()
+            .filter_map(|class| {
+                let mut scores: Vec<f32> = exemplars
+                    .iter()
+                    .filter(|(c, _)| *c == class)
+                    .map(|(_, v)| cosine_similarity(&vector, v))
+                    .collect();
+                if scores.is_empty() {
+                    return None;
+                }
+                scores.sort_by(|a, b| b.total_cmp(a));
+                scores.truncate(TOP_EXEMPLARS_PER_CLASS);
+                Some((class, scores.iter().sum::<f32>() / scores.len() as f32))
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(complexity, similarity)| Classification {
+                complexity,
+                confidence: similarity.clamp(0.0, 1.0),
+                classifier: self.name(),
+            })
+    }
+}
+
+impl QueryClassifier for EmbeddingClassifier {
+    fn classify<'a>(&'a self, query: &'a str) -> ClassifyFuture<'a> {
+        Box::pin(self.classify_embedding(query))
+    }
+
+    fn name(&self) -> &'static str {
+        "embedding"
+    }
+}
+
+/// 依序嘗試的分類器，各自帶信心度門檻
+#[derive(Clone, Default)]
+pub struct ClassifierChain {
+    stages: Vec<(Arc<dyn QueryClassifier>, f32)>,
+}
+
+impl ClassifierChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 加入分類器；信心度低於 `min_confidence` 的結果交給下一個分類器
+    pub fn then(mut self, classifier: Arc<dyn QueryClassifier>, min_confidence: f32) -> Self {
+        self.push(classifier, min_confidence);
+        self
+    }
+
+    pub fn push(&mut self, classifier: Arc<dyn QueryClassifier>, min_confidence: f32) {
+        self.stages.push((classifier, min_confidence));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// 第一個達到門檻的結果；都沒有時為 None
+    pub async fn classify_first(&self, query: &str) -> Option<Classification> {
+        for (classifier, min_confidence) in &self.stages {
+            match classifier.classify(query).await {
+                Some(result) if result.confidence >= *min_confidence => return Some(result),
+                Some(result) => tracing::debug!(
+                    classifier = classifier.name(),
+                    confidence = result.confidence,
+                    "Classification below threshold"
+                ),
+                None => tracing::debug!(classifier = classifier.name(), "Classifier abstained"),
+            }
+        }
+        None
+    }
+}
+
+impl QueryClassifier for ClassifierChain {
+    fn classify<'a>(&'a self, query: &'a str) -> ClassifyFuture<'a> {
+        Box::pin(self.classify_first(query))
+    }
+
+    fn name(&self) -> &'static str {
+        "chain"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 固定回傳同一個結果的分類器
+    struct Fixed(Option<(TaskComplexity, f32)>, &'static str);
+
+    impl QueryClassifier for Fixed {
+        fn classify<'a>(&'a self, _query: &'a str) -> ClassifyFuture<'a> {
+            let result = self.0.map(|(complexity, confidence)| Classification {
+                complexity,
+                confidence,
+                classifier: self.1,
+            });
+            Box::pin(async move { result })
+        }
+
+        fn name(&self) -> &'static str {
+            self.1
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chain_respects_thresholds() {
+        let chain = ClassifierChain::new()
+            .then(Arc::new(Fixed(None, "offline")), 0.0)
+            .then(Arc::new(Fixed(Some((TaskComplexity::Complex, 0.4)), "unsure")), 0.7)
+            .then(Arc::new(Fixed(Some((TaskComplexity::Medium, 0.8)), "llm")), 0.7);
+        let result = chain.classify("anything").await.unwrap();
+        assert_eq!(result.complexity, TaskComplexity::Medium);
+        assert_eq!(result.classifier, "llm");
+
+        let strict = ClassifierChain::new()
+            .then(Arc::new(Fixed(Some((TaskComplexity::Complex, 0.4)), "unsure")), 0.7);
+        assert!(strict.classify("anything").await.is_none());
+        assert!(ClassifierChain::new().classify("anything").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rule_classifier() {
+        let rules = RuleClassifier::new(50, vec!["compare".into()]);
+        assert_eq!(rules.classify_rules("compare rust and go"), TaskComplexity::Medium);
+        let result = rules.classify("what? why? how?").await.unwrap();
+        assert_eq!(result.complexity, TaskComplexity::Complex);
+        assert_eq!(result.classifier, "rules");
+    }
+}
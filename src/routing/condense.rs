@@ -0,0 +1,244 @@
+//! 長查詢濃縮 - 將多段落的「查詢」縮短為引擎可接受的關鍵字查詢
+//!
+//! 預設以關鍵字擷取實作（保留引號片語、專有名詞與數字，依出現頻率與位置排序）；
+//! 設定 LLM 時可改用實作 `QueryCondenser` 的濃縮器。
+
+use bose_common::text::is_cjk;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+
+/// 濃縮後的查詢長度預設上限（字元數）
+pub const DEFAULT_CONDENSED_CHARS: usize = 120;
+
+/// 查詢濃縮器
+pub trait QueryCondenser: Send + Sync {
+    /// 將 `query` 縮短到 `max_chars` 字元以內；無法濃縮時回傳 None
+    fn condense<'a>(
+        &'a self,
+        query: &'a str,
+        max_chars: usize,
+    ) -> Pin<Box<dyn Future<Output = Option<String>> + Send + 'a>>;
+
+    /// 用於 `--explain` 的名稱
+    fn name(&self) -> &'static str;
+}
+
+/// 關鍵字擷取濃縮器（不需要外部服務）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeywordCondenser;
+
+impl QueryCondenser for KeywordCondenser {
+    fn condense<'a>(
+        &'a self,
+        query: &'a str,
+        max_chars: usize,
+    ) -> Pin<Box<dyn Future<Output = Option<String>> + Send + 'a>> {
+        Box::pin(async move { Some(condense_keywords(query, max_chars)).filter(|q| !q.is_empty()) })
+    }
+
+    fn name(&self) -> &'static str {
+        "keywords"
+    }
+}
+
+/// 實際送出的查詢與濃縮紀錄
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreparedQuery {
+    pub original: String,
+    /// 送往引擎的查詢（未濃縮時與原查詢相同）
+    pub sent: String,
+    /// 使用的濃縮器；None 表示未濃縮
+    pub condenser: Option<&'static str>,
+}
+
+impl PreparedQuery {
+    pub fn unchanged(query: &str) -> Self {
+        Self {
+            original: query.to_string(),
+            sent: query.to_string(),
+            condenser: None,
+        }
+    }
+
+    pub fn is_condensed(&self) -> bool {
+        self.condenser.is_some()
+    }
+}
+
+const STOPWORDS: &[&str] = &[
+    "a", "about", "after", "all", "also", "am", "an", "and", "any", "are", "as", "at", "be",
+    "because", "been", "but", "by", "can", "could", "did", "do", "does", "for", "from", "get",
+    "had", "has", "have", "he", "her", "here", "how", "i", "if", "in", "into", "is", "it", "its",
+    "just", "like", "me", "more", "most", "my", "need", "no", "not", "of", "on", "one", "or",
+    "our", "out", "please", "should", "so", "some", "such", "than", "that", "the", "their",
+    "them", "then", "there", "these", "they", "this", "those", "to", "too", "up", "us", "very",
+    "want", "was", "we", "were", "what", "when", "where", "which", "while", "who", "why", "will",
+    "with", "would", "you", "your", "我", "我們", "你", "你們", "他", "她", "它", "的", "了",
+    "是", "在", "和", "與", "及", "或", "也", "都", "就", "很", "請", "想", "要", "需要", "一個",
+    "這個", "那個", "什麼", "怎麼", "如何", "可以", "能夠", "是否", "以及", "還有",
+];
+
+/// 以關鍵字擷取濃縮查詢
+///
+/// 引號內的片語整段保留且優先；其餘詞依「出現次數 + 專有名詞/數字 + 越前面越重要」評分，
+/// 在長度限制內挑選高分詞，最後依原文順序輸出。
+pub fn condense_keywords(query: &str, max_chars: usize) -> String {
+    let (phrases, rest) = split_quoted(query);
+    let tokens = tokenize(&rest);
+    let total = tokens.len().max(1) as f32;
+
+    // 詞 → (分數, 第一次出現位置, 原始寫法)
+    let mut scored: HashMap<String, (f32, usize, String)> = HashMap::new();
+    for (pos, token) in tokens.iter().enumerate() {
+        let key = token.to_lowercase();
+        if STOPWORDS.contains(&key.as_str()) || (token.is_ascii() && token.chars().count() < 2) {
+            continue;
+        }
+        let entry = scored
+            .entry(key)
+            .or_insert_with(|| (0.0, pos, token.clone()));
+        entry.0 += 1.0;
+        if pos == entry.1 {
+            let salient = token.chars().any(|c| c.is_ascii_digit())
+                || token.chars().next().is_some_and(|c| c.is_uppercase())
+                || token.contains(['.', '-', '_', ':']);
+            entry.0 += if salient { 1.0 } else { 0.0 } + 0.5 * (1.0 - pos as f32 / total);
+        }
+    }
+
+    let mut picked: Vec<(usize, String)> = Vec::new();
+    let mut used = 0usize;
+    let mut take = |text: String, order: usize, used: &mut usize| {
+        let len = text.chars().count() + usize::from(*used > 0);
+        if *used + len <= max_chars {
+            *used += len;
+            picked.push((order, text));
+        }
+    };
+
+    for (i, phrase) in phrases.into_iter().enumerate() {
+        take(format!("\"{}\"", phrase), i, &mut used);
+    }
+    let mut ranked: Vec<_> = scored.into_values().collect();
+    ranked.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+    for (_, pos, token) in ranked {
+        take(token, 1_000 + pos, &mut used);
+    }
+
+    picked.sort_by_key(|(order, _)| *order);
+    picked
+        .into_iter()
+        .map(|(_, text)| text)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 拆出引號片語（"..." 或「...」），回傳 (片語, 其餘文字)
+fn split_quoted(query: &str) -> (Vec<String>, String) {
+    let mut phrases = Vec::new();
+    let mut rest = String::with_capacity(query.len());
+    let mut current: Option<(char, String)> = None;
+
+    for ch in query.chars() {
+        match &mut current {
+            Some((close, phrase)) if ch == *close => {
+                let phrase = phrase.split_whitespace().collect::<Vec<_>>().join(" ");
+                if !phrase.is_empty() {
+                    phrases.push(phrase);
+                }
+                current = None;
+                rest.push(' ');
+            }
+            Some((_, phrase)) => phrase.push(ch),
+            None if ch == '"' => current = Some(('"', String::new())),
+            None if ch == '「' => current = Some(('」', String::new())),
+            None => rest.push(ch),
+        }
+    }
+    // 未閉合的引號視為一般文字
+    if let Some((_, phrase)) = current {
+        rest.push_str(&phrase);
+    }
+    (phrases, rest)
+}
+
+/// 以空白與標點切詞；連續的中日韓文字視為一個詞
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut current_cjk = false;
+
+    let flush = |current: &mut String, tokens: &mut Vec<String>| {
+        let token = current.trim_matches(['.', '-', '_', ':']).to_string();
+        if !token.is_empty() {
+            tokens.push(token);
+        }
+        current.clear();
+    };
+
+    for ch in text.chars() {
+        let cjk = is_cjk(ch);
+        let word = ch.is_alphanumeric() || matches!(ch, '.' | '-' | '_' | ':' | '+' | '#');
+        if !word {
+            flush(&mut current, &mut tokens);
+            continue;
+        }
+        if !current.is_empty() && cjk != current_cjk {
+            flush(&mut current, &mut tokens);
+        }
+        current_cjk = cjk;
+        current.push(ch);
+    }
+    flush(&mut current, &mut tokens);
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LONG_QUERY: &str = "Hi, I am working on a backend service written in Rust and I keep \
+        seeing deadlocks when using tokio::sync::Mutex inside async functions. The service \
+        uses Axum 0.8 and the deadlocks appear under load. Could you please find what the \
+        recommended way is to avoid deadlocks with tokio Mutex in Axum handlers?";
+
+    #[test]
+    fn test_condense_keeps_salient_terms() {
+        let condensed = condense_keywords(LONG_QUERY, 80);
+        assert!(condensed.chars().count() <= 80);
+        for term in ["tokio::sync::Mutex", "deadlocks", "Axum", "Rust"] {
+            assert!(condensed.contains(term), "{condensed}");
+        }
+        assert!(!condensed.to_lowercase().contains("please"));
+        // 原文順序
+        assert!(condensed.find("Rust").unwrap() < condensed.find("Axum").unwrap());
+    }
+
+    #[test]
+    fn test_condense_keeps_quoted_phrases() {
+        let condensed = condense_keywords(
+            "I get the error \"cannot borrow as mutable\" when I compile my Rust program",
+            60,
+        );
+        assert!(condensed.starts_with("\"cannot borrow as mutable\""));
+        assert!(condensed.contains("Rust"));
+    }
+
+    #[test]
+    fn test_condense_cjk() {
+        let condensed = condense_keywords("我想要了解 Rust 的 所有權 機制 以及 借用檢查器 如何 運作", 30);
+        assert!(condensed.contains("所有權"));
+        assert!(condensed.contains("借用檢查器"));
+        assert!(!condensed.contains("以及"));
+    }
+
+    #[tokio::test]
+    async fn test_keyword_condenser() {
+        let condenser = KeywordCondenser;
+        assert_eq!(condenser.name(), "keywords");
+        let condensed = condenser.condense(LONG_QUERY, 50).await.unwrap();
+        assert!(condensed.chars().count() <= 50);
+        assert!(condenser.condense("the a of", 50).await.is_none());
+    }
+}
@@ -1,6 +1,12 @@
 //! 置信度計算 - 評估搜尋結果的品質
 
-use crate::types::SearchResult;
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use crate::embeddings::{cosine_similarity, Embedder};
+use crate::routing::authority::AuthorityList;
+use crate::routing::corroboration::{corroboration_score, CorroboratedResult};
+use crate::routing::semantic_router::QueryCategory;
+use crate::types::{SearchResult, TimeRange};
+use std::sync::Arc;
 
 /// 置信度計算器配置
 #[derive(Debug, Clone)]
@@ -15,6 +21,14 @@ pub struct ConfidenceConfig {
     pub content_quality_weight: f32,
     /// 語義密度權重
     pub semantic_density_weight: f32,
+    /// 交叉佐證最多可補上的比例（補在與滿分之間的差距上）
+    pub corroboration_boost: f32,
+    /// 時效性查詢中，過時結果最多扣掉的比例
+    pub freshness_weight: f32,
+    /// 新聞類查詢（未指定 `time_range` 時）可接受的結果年齡（天）
+    pub news_max_age_days: f32,
+    /// 引擎一致度權重；只在有多個引擎的結果可比較時加入加權總分
+    pub engine_agreement_weight: f32,
 }
 
 impl Default for ConfidenceConfig {
@@ -25,36 +39,125 @@ impl Default for ConfidenceConfig {
             url_authority_weight: 0.20,
             content_quality_weight: 0.20,
             semantic_density_weight: 0.15,
+            corroboration_boost: 0.3,
+            freshness_weight: 0.3,
+            news_max_age_days: 7.0,
+            engine_agreement_weight: 0.2,
         }
     }
 }
 
+/// 結果本身以外的查詢層級訊號
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ConfidenceSignals {
+    /// 時效性查詢可接受的結果年齡（天），見 `ConfidenceCalculator::freshness_window`
+    pub max_age_days: Option<f32>,
+    /// 多引擎的一致度，見 `engine_agreement`
+    pub engine_agreement: Option<f32>,
+    /// 查詢與結果的平均嵌入相似度（見 `ConfidenceCalculator::semantic_similarity`），
+    /// 有值時取代以子字串估算的語義密度
+    pub semantic_similarity: Option<f32>,
+}
+
 /// 置信度計算器
 pub struct ConfidenceCalculator {
     config: ConfidenceConfig,
-    authority_domains: Vec<String>,
+    authority: AuthorityList,
+    embedder: Option<Arc<dyn Embedder>>,
 }
 
 impl ConfidenceCalculator {
-    /// 建立新的置信度計算器
+    /// 建立新的置信度計算器（預設權威網域清單）
     pub fn new() -> Self {
         Self {
             config: ConfidenceConfig::default(),
-            authority_domains: vec![
-                "github.com".to_string(),
-                "stackoverflow.com".to_string(),
-                "docs.rs".to_string(),
-                "rust-lang.org".to_string(),
-                "arxiv.org".to_string(),
-                "wikipedia.org".to_string(),
-                "cve.mitre.org".to_string(),
-                "nvd.nist.gov".to_string(),
-            ],
+            authority: AuthorityList::default(),
+            embedder: None,
+        }
+    }
+
+    /// 改用自訂權重
+    pub fn with_config(mut self, config: ConfidenceConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// 改用設定載入的權威網域清單（見 `AuthorityConfig::build_for`）
+    pub fn with_authority(mut self, authority: AuthorityList) -> Self {
+        self.authority = authority;
+        self
+    }
+
+    /// 以嵌入相似度取代語義密度的字面估算（見 `semantic_similarity`）
+    pub fn with_embedder(mut self, embedder: Arc<dyn Embedder>) -> Self {
+        self.embedder = Some(embedder);
+        self
+    }
+
+    /// 逐筆計算查詢與結果（標題 + 摘要）的嵌入餘弦相似度（0.0 ~ 1.0）
+    ///
+    /// 未設定 `Embedder` 或嵌入失敗時回傳 None，呼叫端退回字面的語義密度。
+    pub async fn embedding_similarities(&self, query: &str, results: &[SearchResult]) -> Option<Vec<f32>> {
+        let embedder = self.embedder.as_ref()?;
+        if results.is_empty() {
+            return Some(Vec::new());
+        }
+        let texts: Vec<String> = std::iter::once(query.to_string())
+            .chain(results.iter().map(|r| {
+                let body = r.snippet.as_deref().or(r.content.as_deref()).unwrap_or("");
+                format!("{} {}", r.title, body)
+            }))
+            .collect();
+        let vectors = embedder
+            .embed(&texts)
+            .await
+            .inspect_err(|e| tracing::warn!(error = %e, "Result embedding failed, using keyword density"))
+            .ok()?;
+        let (query_vector, result_vectors) = vectors.split_first()?;
+        if result_vectors.len() != results.len() {
+            return None;
+        }
+        Some(
+            result_vectors
+                .iter()
+                .map(|v| cosine_similarity(query_vector, v).clamp(0.0, 1.0))
+                .collect(),
+        )
+    }
+
+    /// 查詢與結果的平均嵌入相似度，用於 `ConfidenceSignals::semantic_similarity`
+    pub async fn semantic_similarity(&self, query: &str, results: &[SearchResult]) -> Option<f32> {
+        let similarities = self.embedding_similarities(query, results).await?;
+        if similarities.is_empty() {
+            return None;
+        }
+        Some(similarities.iter().sum::<f32>() / similarities.len() as f32)
+    }
+
+    /// 回傳 URL 命中的權威網域（若有）
+    pub fn authority_domain(&self, url: &str) -> Option<&str> {
+        self.authority.lookup(url).map(|(domain, _)| domain)
+    }
+
+    /// 時效性查詢可接受的結果年齡（天）：`time_range` 優先，其次新聞類查詢；其他查詢為 None
+    pub fn freshness_window(&self, time_range: Option<TimeRange>, category: Option<QueryCategory>) -> Option<f32> {
+        match (time_range, category) {
+            (Some(range), _) => Some(range.days()),
+            (None, Some(QueryCategory::News)) => Some(self.config.news_max_age_days),
+            _ => None,
         }
     }
 
     /// 計算搜尋結果的置信度
     pub fn calculate(&self, query: &str, results: &[SearchResult]) -> f32 {
+        self.calculate_with(query, results, &ConfidenceSignals::default())
+    }
+
+    /// 計算置信度並納入查詢層級訊號
+    ///
+    /// 有引擎一致度時以 `engine_agreement_weight` 加入加權總分（再正規化）；
+    /// 有 `max_age_days`（時效性查詢）時，超過此年齡的結果會扣分。
+    pub fn calculate_with(&self, query: &str, results: &[SearchResult], signals: &ConfidenceSignals) -> f32 {
         if results.is_empty() {
             return 0.0;
         }
@@ -63,7 +166,9 @@ impl ConfidenceCalculator {
         let title_relevance_score = self.score_title_relevance(query, results);
         let url_authority_score = self.score_url_authority(results);
         let content_quality_score = self.score_content_quality(results);
-        let semantic_density_score = self.score_semantic_density(query, results);
+        let semantic_density_score = signals
+            .semantic_similarity
+            .unwrap_or_else(|| self.score_semantic_density(query, results));
 
         // 加權總分
         let total = result_count_score * self.config.result_count_weight
@@ -72,9 +177,91 @@ impl ConfidenceCalculator {
             + content_quality_score * self.config.content_quality_weight
             + semantic_density_score * self.config.semantic_density_weight;
 
+        // 單一引擎時沒有一致度可言，不讓缺少的訊號拉低分數
+        let total = match signals.engine_agreement {
+            Some(agreement) => {
+                let weight = self.config.engine_agreement_weight;
+                (total + agreement * weight) / (1.0 + weight)
+            }
+            None => total,
+        };
+
+        // 時效不足只扣分、不加分，非時效性查詢不受影響
+        let total = match signals.max_age_days {
+            Some(window) => {
+                let freshness = self.score_freshness(results, window, today());
+                total * (1.0 - self.config.freshness_weight * (1.0 - freshness))
+            }
+            None => total,
+        };
+
         total.clamp(0.0, 1.0)
     }
 
+    /// 逐筆計算置信度（0.0 ~ 1.0），與 `results` 順序相同
+    ///
+    /// 使用與 `calculate` 相同的權重，但不含結果數量這類整體指標。
+    pub fn score_each(&self, query: &str, results: &[SearchResult]) -> Vec<f32> {
+        self.score_each_with(query, results, None)
+    }
+
+    /// 同 `score_each`，設定 `Embedder` 時以逐筆嵌入相似度取代語義密度
+    pub async fn score_each_semantic(&self, query: &str, results: &[SearchResult]) -> Vec<f32> {
+        let similarities = self.embedding_similarities(query, results).await;
+        self.score_each_with(query, results, similarities.as_deref())
+    }
+
+    fn score_each_with(&self, query: &str, results: &[SearchResult], similarities: Option<&[f32]>) -> Vec<f32> {
+        let weight_sum = self.config.title_relevance_weight
+            + self.config.url_authority_weight
+            + self.config.content_quality_weight
+            + self.config.semantic_density_weight;
+        if weight_sum <= 0.0 {
+            return vec![0.0; results.len()];
+        }
+
+        results
+            .iter()
+            .enumerate()
+            .map(|(i, result)| {
+                let single = std::slice::from_ref(result);
+                let semantic = match similarities {
+                    Some(similarities) => similarities[i],
+                    None => self.score_semantic_density(query, single),
+                };
+                let total = self.score_title_relevance(query, single) * self.config.title_relevance_weight
+                    + self.score_url_authority(single) * self.config.url_authority_weight
+                    + self.score_content_quality(single) * self.config.content_quality_weight
+                    + semantic * self.config.semantic_density_weight;
+                (total / weight_sum).clamp(0.0, 1.0)
+            })
+            .collect()
+    }
+
+    /// 依逐筆置信度由高到低重新排序（同分保持原順序），回傳 (結果, 置信度)
+    pub fn rerank(&self, query: &str, results: Vec<SearchResult>) -> Vec<(SearchResult, f32)> {
+        let scores = self.score_each(query, &results);
+        let mut ranked: Vec<(SearchResult, f32)> = results.into_iter().zip(scores).collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranked
+    }
+
+    /// 計算多來源合併結果的置信度，並依交叉佐證提高
+    ///
+    /// `source_count` 為參與合併的引擎或層級數量。
+    pub fn calculate_corroborated(
+        &self,
+        query: &str,
+        results: &[CorroboratedResult],
+        source_count: usize,
+        signals: &ConfidenceSignals,
+    ) -> f32 {
+        let plain: Vec<SearchResult> = results.iter().map(|r| r.result.clone()).collect();
+        let base = self.calculate_with(query, &plain, signals);
+        let boost = corroboration_score(results, source_count) * self.config.corroboration_boost;
+        (base + (1.0 - base) * boost).clamp(0.0, 1.0)
+    }
+
     /// 評分：結果數量
     fn score_result_count(&self, count: usize) -> f32 {
         match count {
@@ -111,18 +298,34 @@ impl ConfidenceCalculator {
         (total_score / results.len() as f32).min(1.0)
     }
 
-    /// 評分：URL 權威性
+    /// 評分：URL 權威性（各結果網域權重的平均）
     fn score_url_authority(&self, results: &[SearchResult]) -> f32 {
-        let authority_count = results
+        let total_weight: f32 = results.iter().map(|r| self.authority.weight(&r.url)).sum();
+
+        (total_weight / results.len() as f32).min(1.0)
+    }
+
+    /// 評分：時效（有發布日期的結果平均；都沒有日期時不扣分）
+    ///
+    /// 不超過 `window` 天的結果得滿分，更舊的結果依 `window / 年齡` 遞減。
+    fn score_freshness(&self, results: &[SearchResult], window: f32, today: i64) -> f32 {
+        let scores: Vec<f32> = results
             .iter()
-            .filter(|r| {
-                self.authority_domains
-                    .iter()
-                    .any(|d| r.url.contains(d))
+            .filter_map(|r| r.published_date.as_deref().and_then(parse_day))
+            .map(|day| {
+                let age = (today - day).max(0) as f32;
+                if age <= window {
+                    1.0
+                } else {
+                    window.max(0.0) / age
+                }
             })
-            .count();
+            .collect();
 
-        (authority_count as f32 / results.len() as f32).min(1.0)
+        if scores.is_empty() {
+            return 1.0;
+        }
+        scores.iter().sum::<f32>() / scores.len() as f32
     }
 
     /// 評分：內容品質
@@ -207,6 +410,57 @@ impl Default for ConfidenceCalculator {
     }
 }
 
+const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+
+/// 今天（UTC）距 1970-01-01 的天數
+pub(crate) fn today() -> i64 {
+    epoch_days(Utc::now().date_naive())
+}
+
+/// 距 1970-01-01 的天數
+pub(crate) fn epoch_days(date: NaiveDate) -> i64 {
+    (date - DateTime::UNIX_EPOCH.date_naive()).num_days()
+}
+
+/// `epoch_days` 的反函數
+pub(crate) fn epoch_date(days: i64) -> NaiveDate {
+    DateTime::UNIX_EPOCH.date_naive() + TimeDelta::days(days)
+}
+
+/// 解析發布日期為距 1970-01-01 的天數
+fn parse_day(date: &str) -> Option<i64> {
+    let (year, month, day) = parse_date(date)?;
+    Some(epoch_days(NaiveDate::from_ymd_opt(year as i32, month as u32, day as u32)?))
+}
+
+/// 解析發布日期為 (年, 月, 日)
+///
+/// 支援 ISO 8601（Exa：`2024-03-14T08:00:00.000Z`）與 RFC 2822（Tavily：`Thu, 14 Mar 2024 08:00:00 GMT`）。
+pub(crate) fn parse_date(date: &str) -> Option<(i64, i64, i64)> {
+    let date = date.trim();
+    let iso = date.get(..10).and_then(|prefix| {
+        let mut parts = prefix.split('-').map(|p| p.parse::<i64>().ok());
+        Some((parts.next()??, parts.next()??, parts.next()??))
+    });
+    let (year, month, day) = match iso {
+        Some(ymd) => ymd,
+        None => {
+            let tokens: Vec<&str> = date.split([' ', ',']).filter(|t| !t.is_empty()).collect();
+            // 以字元邊界取前三個位元組：非 ASCII 的月份（`août`）直接不符合
+            let month_of = |t: &str| t.get(..3).and_then(|p| MONTHS.iter().position(|m| p.eq_ignore_ascii_case(m)));
+            let index = tokens.iter().position(|t| month_of(t).is_some())?;
+            let month = month_of(tokens[index])? as i64 + 1;
+            let day = tokens.get(index.checked_sub(1)?)?.parse().ok()?;
+            let year = tokens.get(index + 1)?.parse().ok()?;
+            (year, month, day)
+        }
+    };
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    Some((year, month, day))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -218,12 +472,16 @@ mod tests {
                 url: "https://github.com/rust-lang/rust".to_string(),
                 snippet: Some("Learn about Rust security features".to_string()),
                 content: None,
+                published_date: None,
+                metadata: Default::default(),
             },
             SearchResult {
                 title: "Rust Programming Language".to_string(),
                 url: "https://rust-lang.org".to_string(),
                 snippet: Some("A language empowering everyone".to_string()),
                 content: Some("Rust is a systems programming language...".to_string()),
+                published_date: None,
+                metadata: Default::default(),
             },
         ]
     }
@@ -245,6 +503,172 @@ mod tests {
         assert_eq!(confidence, 0.0);
     }
 
+    #[test]
+    fn test_corroboration_boosts_confidence() {
+        let calc = ConfidenceCalculator::new();
+        let results = create_test_results();
+        let base = calc.calculate("Rust security", &results);
+
+        let alone = crate::routing::corroborate(&[("duckduckgo", &results)]);
+        assert_eq!(calc.calculate_corroborated("Rust security", &alone, 1, &ConfidenceSignals::default()), base);
+
+        let merged = crate::routing::corroborate(&[("duckduckgo", &results), ("exa", &results)]);
+        assert!(calc.calculate_corroborated("Rust security", &merged, 2, &ConfidenceSignals::default()) > base);
+    }
+
+    #[test]
+    fn test_weighted_authority() {
+        let results = create_test_results();
+        let calc = ConfidenceCalculator::new();
+        assert_eq!(calc.score_url_authority(&results), 1.0);
+
+        let calc = ConfidenceCalculator::new().with_authority(AuthorityList::new([("github.com", 0.5)]));
+        assert_eq!(calc.score_url_authority(&results), 0.25);
+        assert_eq!(calc.authority_domain("https://rust-lang.org"), None);
+    }
+
+    #[test]
+    fn test_engine_agreement_signal() {
+        let calc = ConfidenceCalculator::new();
+        let results = create_test_results();
+        let base = calc.calculate("Rust security", &results);
+        let with = |agreement| ConfidenceSignals {
+            engine_agreement: Some(agreement),
+            ..Default::default()
+        };
+        assert!(calc.calculate_with("Rust security", &results, &with(1.0)) > base);
+        assert!(calc.calculate_with("Rust security", &results, &with(0.0)) < base);
+
+        let ignored = ConfidenceCalculator::new().with_config(ConfidenceConfig {
+            engine_agreement_weight: 0.0,
+            ..Default::default()
+        });
+        assert_eq!(ignored.calculate_with("Rust security", &results, &with(1.0)), base);
+    }
+
+    /// 固定回傳錯誤的嵌入後端
+    struct FailingEmbedder;
+
+    impl Embedder for FailingEmbedder {
+        fn dimensions(&self) -> usize {
+            8
+        }
+
+        fn embed<'a>(&'a self, _texts: &'a [String]) -> crate::embeddings::EmbedFuture<'a> {
+            Box::pin(async { Err(crate::types::SearchError::NetworkError("offline".into())) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_embedding_similarity_component() {
+        let results = create_test_results();
+        let plain = ConfidenceCalculator::new();
+        assert!(plain.semantic_similarity("Rust security", &results).await.is_none());
+        assert_eq!(
+            plain.score_each_semantic("Rust security", &results).await,
+            plain.score_each("Rust security", &results)
+        );
+
+        let calc = ConfidenceCalculator::new()
+            .with_embedder(Arc::new(crate::embeddings::HashingEmbedder::default()));
+        let similarities = calc.embedding_similarities("Rust security", &results).await.unwrap();
+        assert_eq!(similarities.len(), 2);
+        assert!(similarities[0] > similarities[1]);
+
+        let similarity = calc.semantic_similarity("Rust security", &results).await.unwrap();
+        let signals = ConfidenceSignals {
+            semantic_similarity: Some(similarity),
+            ..Default::default()
+        };
+        let semantic = calc.calculate_with("Rust security", &results, &signals);
+        assert!(semantic > 0.0 && semantic <= 1.0);
+        assert_ne!(semantic, calc.calculate("Rust security", &results));
+
+        // 嵌入失敗時退回字面估算
+        let failing = ConfidenceCalculator::new().with_embedder(Arc::new(FailingEmbedder));
+        assert!(failing.semantic_similarity("Rust security", &results).await.is_none());
+        assert_eq!(
+            failing.score_each_semantic("Rust security", &results).await,
+            plain.score_each("Rust security", &results)
+        );
+    }
+
+    #[test]
+    fn test_score_each_and_rerank() {
+        let calc = ConfidenceCalculator::new();
+        let mut results = create_test_results();
+        results.push(SearchResult {
+            title: "Unrelated page".to_string(),
+            url: "https://example.com".to_string(),
+            snippet: None,
+            content: None,
+            published_date: None,
+            metadata: Default::default(),
+        });
+
+        let scores = calc.score_each("Rust security", &results);
+        assert_eq!(scores.len(), 3);
+        assert!(scores.iter().all(|s| (0.0..=1.0).contains(s)));
+        assert!(scores[0] > scores[2]);
+        assert!(calc.score_each("Rust security", &[]).is_empty());
+
+        let ranked = calc.rerank("Rust security", results.into_iter().rev().collect());
+        assert_eq!(ranked[0].0.url, "https://github.com/rust-lang/rust");
+        assert_eq!(ranked[2].0.url, "https://example.com");
+        assert!(ranked.windows(2).all(|w| w[0].1 >= w[1].1));
+    }
+
+    #[test]
+    fn test_parse_published_date() {
+        assert_eq!(parse_day("1970-01-01"), Some(0));
+        assert_eq!(parse_day("2024-03-14T08:00:00.000Z"), Some(19_796));
+        assert_eq!(parse_day("Thu, 14 Mar 2024 08:00:00 GMT"), Some(19_796));
+        assert_eq!(parse_day("2000-02-29"), Some(11_016));
+        assert_eq!(parse_day("last week"), None);
+        assert_eq!(parse_day("2024-13-01"), None);
+        assert_eq!(parse_day("2023-02-29"), None);
+        // 多位元組的月份名稱不可在字元中間切開
+        assert_eq!(parse_day("jeu., 15 août 2024 08:00:00 GMT"), None);
+        assert_eq!(parse_day("ven., 15 mars 2024"), Some(19_797));
+        assert_eq!(parse_date("Ökotest 3 déc 2024"), None);
+    }
+
+    #[test]
+    fn test_freshness_penalizes_stale_results() {
+        let calc = ConfidenceCalculator::new();
+        let today = parse_day("2024-03-14").unwrap();
+        let mut results = create_test_results();
+        // 沒有日期：不扣分
+        assert_eq!(calc.score_freshness(&results, 7.0, today), 1.0);
+
+        results[0].published_date = Some("2024-03-10".to_string());
+        results[1].published_date = Some("Sat, 13 Jan 2024 00:00:00 GMT".to_string());
+        let score = calc.score_freshness(&results, 7.0, today);
+        assert!((score - (1.0 + 7.0 / 61.0) / 2.0).abs() < 1e-6);
+        assert_eq!(calc.score_freshness(&results, 365.0, today), 1.0);
+
+        // 只有時效性查詢會扣分
+        let stale = vec![SearchResult {
+            published_date: Some("2015-06-01".to_string()),
+            ..results[1].clone()
+        }];
+        let base = calc.calculate("Rust programming", &stale);
+        let recent = ConfidenceSignals {
+            max_age_days: Some(7.0),
+            ..Default::default()
+        };
+        assert!(calc.calculate_with("Rust programming", &stale, &recent) < base);
+    }
+
+    #[test]
+    fn test_freshness_window() {
+        let calc = ConfidenceCalculator::new();
+        assert_eq!(calc.freshness_window(Some(TimeRange::Month), Some(QueryCategory::News)), Some(30.0));
+        assert_eq!(calc.freshness_window(None, Some(QueryCategory::News)), Some(7.0));
+        assert_eq!(calc.freshness_window(None, Some(QueryCategory::Docs)), None);
+        assert_eq!(calc.freshness_window(None, None), None);
+    }
+
     #[test]
     fn test_result_count_scoring() {
         let calc = ConfidenceCalculator::new();
@@ -0,0 +1,228 @@
+//! 交叉佐證 - 同一網址、網域或說法被多個引擎 / 層級各自找到時提高可信度
+//!
+//! 每筆結果記錄有哪些其他來源（引擎或層級名稱）獨立找到相同的網址、同網域的頁面，
+//! 或摘要內容高度重疊的說法。安全相關的結論通常需要多個來源互相印證。
+
+use crate::processing::context_pruner::query_terms;
+use crate::types::SearchResult;
+use crate::url::canonicalize;
+use reqwest::Url;
+use std::collections::{HashMap, HashSet};
+
+/// 兩段摘要被視為同一說法所需的詞彙 Jaccard 相似度
+const FACT_SIMILARITY: f32 = 0.5;
+/// 比對說法時摘要至少要有的詞數，太短的摘要容易誤判
+const FACT_MIN_TERMS: usize = 4;
+
+/// 附佐證來源的結果
+#[derive(Debug, Clone)]
+pub struct CorroboratedResult {
+    pub result: SearchResult,
+    /// 最先找到這筆結果的來源
+    pub source: String,
+    /// 其他獨立找到相同網址、網域或說法的來源
+    pub corroborated_by: Vec<String>,
+}
+
+/// 合併多個來源的結果並標記佐證
+///
+/// `sources` 為 `(來源名稱, 結果)`，依序合併並以網址去重，保留首次出現的版本與順序。
+pub fn corroborate(sources: &[(&str, &[SearchResult])]) -> Vec<CorroboratedResult> {
+    let mut merged: Vec<CorroboratedResult> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for (source, results) in sources {
+        for result in results.iter() {
+            match index.get(&url_key(&result.url)) {
+                Some(&i) => add_source(&mut merged[i], source),
+                None => {
+                    index.insert(url_key(&result.url), merged.len());
+                    merged.push(CorroboratedResult {
+                        result: result.clone(),
+                        source: source.to_string(),
+                        corroborated_by: Vec::new(),
+                    });
+                }
+            }
+        }
+    }
+
+    // 以網址層級的來源為準比對網域與說法，避免佐證一路傳遞下去
+    let found_by: Vec<Vec<String>> = merged
+        .iter()
+        .map(|entry| std::iter::once(&entry.source).chain(&entry.corroborated_by).cloned().collect())
+        .collect();
+    let domains: Vec<Option<String>> = merged.iter().map(|entry| domain(&entry.result.url)).collect();
+    let facts: Vec<HashSet<String>> = merged
+        .iter()
+        .map(|entry| query_terms(entry.result.snippet.as_deref().unwrap_or("")).into_iter().collect())
+        .collect();
+
+    for i in 0..merged.len() {
+        for j in 0..merged.len() {
+            if i == j {
+                continue;
+            }
+            let same_domain = domains[i].is_some() && domains[i] == domains[j];
+            if same_domain || same_fact(&facts[i], &facts[j]) {
+                for source in &found_by[j] {
+                    add_source(&mut merged[i], source);
+                }
+            }
+        }
+    }
+    merged
+}
+
+/// 佐證分數（0.0 - 1.0）：每筆結果被其他來源佐證的比例平均
+///
+/// `source_count` 為參與比對的來源數，少於兩個時沒有佐證可言。
+pub fn corroboration_score(results: &[CorroboratedResult], source_count: usize) -> f32 {
+    if results.is_empty() || source_count < 2 {
+        return 0.0;
+    }
+    let others = (source_count - 1) as f32;
+    let total: f32 = results
+        .iter()
+        .map(|r| (r.corroborated_by.len() as f32 / others).min(1.0))
+        .sum();
+    total / results.len() as f32
+}
+
+/// 引擎一致度（0.0 - 1.0）：與至少一個其他引擎找到相同網址或網域的引擎比例
+///
+/// 只計入有回傳結果的引擎；少於兩個引擎時無從比較，回傳 None。
+/// 與 `corroboration_score` 不同，這裡看的是整個查詢有多少引擎意見一致，而非個別結果。
+pub fn engine_agreement(sources: &[(&str, &[SearchResult])]) -> Option<f32> {
+    // 每個引擎的網址與網域集合（同名來源合併）
+    let mut engines: Vec<(&str, HashSet<String>)> = Vec::new();
+    for (source, results) in sources {
+        let keys = results
+            .iter()
+            .flat_map(|r| std::iter::once(url_key(&r.url)).chain(domain(&r.url).map(|d| format!("@{}", d))));
+        match engines.iter_mut().find(|(name, _)| name == source) {
+            Some((_, existing)) => existing.extend(keys),
+            None => engines.push((source, keys.collect())),
+        }
+    }
+    engines.retain(|(_, keys)| !keys.is_empty());
+    if engines.len() < 2 {
+        return None;
+    }
+
+    let agreeing = engines
+        .iter()
+        .enumerate()
+        .filter(|(i, (_, keys))| {
+            engines
+                .iter()
+                .enumerate()
+                .any(|(j, (_, other))| j != *i && !keys.is_disjoint(other))
+        })
+        .count();
+    Some(agreeing as f32 / engines.len() as f32)
+}
+
+fn add_source(entry: &mut CorroboratedResult, source: &str) {
+    if entry.source != source && !entry.corroborated_by.iter().any(|s| s == source) {
+        entry.corroborated_by.push(source.to_string());
+    }
+}
+
+/// 比對用網址：正規化（去掉追蹤參數、片段）後再忽略 scheme、`www.` 與結尾斜線
+pub(crate) fn url_key(url: &str) -> String {
+    match Url::parse(&canonicalize(url)) {
+        Ok(parsed) => {
+            let host = parsed.host_str().unwrap_or("").trim_start_matches("www.");
+            let path = parsed.path().trim_end_matches('/');
+            match parsed.query() {
+                Some(query) => format!("{}{}?{}", host, path, query),
+                None => format!("{}{}", host, path),
+            }
+        }
+        Err(_) => url.trim_end_matches('/').to_lowercase(),
+    }
+}
+
+fn domain(url: &str) -> Option<String> {
+    let parsed = Url::parse(url).ok()?;
+    let host = parsed.host_str()?.trim_start_matches("www.");
+    (!host.is_empty()).then(|| host.to_string())
+}
+
+fn same_fact(a: &HashSet<String>, b: &HashSet<String>) -> bool {
+    if a.len() < FACT_MIN_TERMS || b.len() < FACT_MIN_TERMS {
+        return false;
+    }
+    let shared = a.intersection(b).count();
+    let union = a.len() + b.len() - shared;
+    shared as f32 / union as f32 >= FACT_SIMILARITY
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(url: &str, snippet: &str) -> SearchResult {
+        SearchResult {
+            title: url.to_string(),
+            url: url.to_string(),
+            snippet: Some(snippet.to_string()),
+            content: None,
+            published_date: None,
+            metadata: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_corroborate_by_url_domain_and_fact() {
+        let ddg = vec![
+            result("https://www.example.com/advisory/", "Example advisory"),
+            result("https://nvd.nist.gov/vuln/detail/CVE-2024-1", "Heap overflow in libfoo parser allows remote code execution"),
+            result("https://blog.one/post", "Unrelated"),
+        ];
+        let exa = vec![
+            result("http://example.com/advisory", "Same page"),
+            result("https://nvd.nist.gov/vuln/search", "NVD search"),
+            result("https://news.two/libfoo", "Remote code execution via heap overflow in the libfoo parser"),
+        ];
+        let merged = corroborate(&[("duckduckgo", &ddg), ("exa", &exa)]);
+        assert_eq!(merged.len(), 5);
+        assert_eq!(merged[0].source, "duckduckgo");
+        assert_eq!(merged[0].corroborated_by, vec!["exa"]);
+        assert_eq!(merged[1].corroborated_by, vec!["exa"]);
+        assert!(merged[2].corroborated_by.is_empty());
+        assert_eq!(merged[4].source, "exa");
+        assert_eq!(merged[4].corroborated_by, vec!["duckduckgo"]);
+    }
+
+    #[test]
+    fn test_same_source_does_not_corroborate_itself() {
+        let ddg = vec![result("https://a.com/1", "x"), result("https://a.com/2", "y")];
+        let merged = corroborate(&[("duckduckgo", &ddg)]);
+        assert!(merged.iter().all(|r| r.corroborated_by.is_empty()));
+        assert_eq!(corroboration_score(&merged, 1), 0.0);
+    }
+
+    #[test]
+    fn test_corroboration_score() {
+        let a = vec![result("https://a.com/1", "x"), result("https://b.com/1", "y")];
+        let b = vec![result("https://a.com/1", "x")];
+        let merged = corroborate(&[("duckduckgo", &a), ("exa", &b)]);
+        assert_eq!(corroboration_score(&merged, 2), 0.5);
+    }
+
+    #[test]
+    fn test_engine_agreement() {
+        let ddg = vec![result("https://www.example.com/a", "x"), result("https://one.org/", "y")];
+        let exa = vec![result("https://example.com/b", "z")];
+        let tavily = vec![result("https://elsewhere.net/", "w")];
+        // 網域相同即算一致
+        assert_eq!(engine_agreement(&[("duckduckgo", &ddg), ("exa", &exa)]), Some(1.0));
+        let three = engine_agreement(&[("duckduckgo", &ddg), ("exa", &exa), ("tavily", &tavily)]).unwrap();
+        assert!((three - 2.0 / 3.0).abs() < 1e-6);
+        assert_eq!(engine_agreement(&[("duckduckgo", &ddg), ("tavily", &tavily)]), Some(0.0));
+        // 沒有結果的引擎不算；只剩一個引擎時無從比較
+        assert_eq!(engine_agreement(&[("duckduckgo", &ddg), ("exa", &[])]), None);
+        assert_eq!(engine_agreement(&[("duckduckgo", &ddg), ("duckduckgo", &exa)]), None);
+    }
+}
@@ -0,0 +1,183 @@
+//! 排名解釋 - 以簡短文字說明結果為何被視為相關
+
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::confidence::ConfidenceCalculator;
+use crate::types::SearchResult;
+
+/// 單一結果的相關性說明
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelevanceExplanation {
+    /// 出現在標題中的查詢詞
+    pub title_terms: Vec<String>,
+    /// 只出現在摘要 / 內文 / URL 的查詢詞
+    pub body_terms: Vec<String>,
+    /// 完全沒有出現的查詢詞
+    pub missing_terms: Vec<String>,
+    /// 命中的權威網域
+    pub authority_domain: Option<String>,
+    /// 結果中提到的最近年份
+    pub latest_year: Option<u32>,
+    /// 同樣回傳此結果的引擎（由呼叫端提供）
+    pub engines: Vec<String>,
+}
+
+impl RelevanceExplanation {
+    /// 附上回傳此結果的引擎，用於顯示引擎一致性
+    pub fn with_engines(mut self, engines: Vec<String>) -> Self {
+        self.engines = engines;
+        self
+    }
+
+    /// 查詢詞命中比例（0.0 ~ 1.0）
+    pub fn term_coverage(&self) -> f32 {
+        let matched = self.title_terms.len() + self.body_terms.len();
+        let total = matched + self.missing_terms.len();
+        if total == 0 {
+            return 0.0;
+        }
+        matched as f32 / total as f32
+    }
+}
+
+impl fmt::Display for RelevanceExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.title_terms.is_empty() {
+            writeln!(f, "• 標題符合: {}", self.title_terms.join(", "))?;
+        }
+        if !self.body_terms.is_empty() {
+            writeln!(f, "• 內文符合: {}", self.body_terms.join(", "))?;
+        }
+        if !self.missing_terms.is_empty() {
+            writeln!(f, "• 未出現: {}", self.missing_terms.join(", "))?;
+        }
+        match &self.authority_domain {
+            Some(domain) => writeln!(f, "• 權威來源: {}", domain)?,
+            None => writeln!(f, "• 非權威清單網域")?,
+        }
+        match self.latest_year {
+            Some(year) => writeln!(f, "• 時效: 提及 {} 年", year)?,
+            None => writeln!(f, "• 時效: 無日期資訊")?,
+        }
+        match self.engines.len() {
+            0 => {}
+            1 => writeln!(f, "• 引擎: 僅 {}", self.engines[0])?,
+            n => writeln!(f, "• 引擎一致: {} 個引擎 ({})", n, self.engines.join(", "))?,
+        }
+        Ok(())
+    }
+}
+
+/// 以預設權威網域清單解釋結果的相關性
+pub fn explain_relevance(query: &str, result: &SearchResult) -> RelevanceExplanation {
+    ConfidenceCalculator::new().explain_relevance(query, result)
+}
+
+impl ConfidenceCalculator {
+    /// 解釋單一結果的相關性（符合的查詢詞、權威性、時效）
+    pub fn explain_relevance(&self, query: &str, result: &SearchResult) -> RelevanceExplanation {
+        let title = result.title.to_lowercase();
+        let body = format!(
+            "{} {} {}",
+            result.snippet.as_deref().unwrap_or(""),
+            result.content.as_deref().unwrap_or(""),
+            result.url
+        )
+        .to_lowercase();
+
+        let mut explanation = RelevanceExplanation {
+            title_terms: Vec::new(),
+            body_terms: Vec::new(),
+            missing_terms: Vec::new(),
+            authority_domain: self.authority_domain(&result.url).map(str::to_string),
+            latest_year: latest_year(&format!("{} {}", title, body)),
+            engines: Vec::new(),
+        };
+
+        let query_lower = query.to_lowercase();
+        let mut seen = Vec::new();
+        for word in query_lower.split_whitespace().filter(|w| w.len() > 2) {
+            if seen.contains(&word) {
+                continue;
+            }
+            seen.push(word);
+            if title.contains(word) {
+                explanation.title_terms.push(word.to_string());
+            } else if body.contains(word) {
+                explanation.body_terms.push(word.to_string());
+            } else {
+                explanation.missing_terms.push(word.to_string());
+            }
+        }
+
+        explanation
+    }
+}
+
+/// 找出文字中最近且不晚於今年的四位數年份
+fn latest_year(text: &str) -> Option<u32> {
+    let current = current_year();
+    text.split(|c: char| !c.is_ascii_digit())
+        .filter(|s| s.len() == 4)
+        .filter_map(|s| s.parse::<u32>().ok())
+        .filter(|y| (1990..=current).contains(y))
+        .max()
+}
+
+fn current_year() -> u32 {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    // 以平均回歸年估算，年份邊界前後幾小時的誤差不影響用途
+    1970 + (secs / 31_556_952) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result() -> SearchResult {
+        SearchResult {
+            title: "Tokio Async Runtime Guide".to_string(),
+            url: "https://docs.rs/tokio/latest/tokio/".to_string(),
+            snippet: Some("Updated 2019, revised 2024 for Rust 1.75".to_string()),
+            content: None,
+            published_date: None,
+            metadata: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_explain_matched_terms() {
+        let explanation = explain_relevance("tokio rust tutorial", &result());
+        assert_eq!(explanation.title_terms, vec!["tokio"]);
+        assert_eq!(explanation.body_terms, vec!["rust"]);
+        assert_eq!(explanation.missing_terms, vec!["tutorial"]);
+        assert!((explanation.term_coverage() - 2.0 / 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_explain_authority_and_recency() {
+        let explanation = explain_relevance("tokio", &result());
+        assert_eq!(explanation.authority_domain.as_deref(), Some("docs.rs"));
+        assert_eq!(explanation.latest_year, Some(2024));
+    }
+
+    #[test]
+    fn test_latest_year_ignores_future_and_noise() {
+        assert_eq!(latest_year("port 8080 in 3021, since 2015"), Some(2015));
+        assert_eq!(latest_year("no dates"), None);
+    }
+
+    #[test]
+    fn test_explain_display() {
+        let text = explain_relevance("tokio", &result())
+            .with_engines(vec!["duckduckgo".to_string(), "exa".to_string()])
+            .to_string();
+        assert!(text.contains("標題符合: tokio"));
+        assert!(text.contains("權威來源: docs.rs"));
+        assert!(text.contains("引擎一致: 2 個引擎"));
+    }
+}
@@ -1,7 +1,25 @@
 pub mod semantic_router;
+pub mod authority;
+pub mod classifier;
+pub mod condense;
 pub mod confidence;
+pub mod corroboration;
+pub mod rules;
+pub mod explain;
+pub mod spellcheck;
 pub mod tiered_retrieval;
 
-pub use semantic_router::{SemanticRouter, TaskComplexity, SearchStrategy, RouterConfig};
-pub use confidence::{ConfidenceCalculator, ConfidenceConfig};
-pub use tiered_retrieval::{TieredRetrieval, TieredConfig, RetrievalTier, TieredResult};
+pub use semantic_router::{SemanticRouter, TaskComplexity, SearchStrategy, RouterConfig, QueryCategory};
+pub use semantic_router::StrategyMapping;
+pub use classifier::{Classification, ClassifierChain, ClassifyFuture, EmbeddingClassifier, QueryClassifier, RuleClassifier};
+pub use condense::{condense_keywords, KeywordCondenser, PreparedQuery, QueryCondenser};
+pub use authority::{AuthorityConfig, AuthorityList};
+pub use confidence::{ConfidenceCalculator, ConfidenceConfig, ConfidenceSignals};
+pub use corroboration::{corroborate, corroboration_score, engine_agreement, CorroboratedResult};
+pub use rules::{match_rule, RoutingRule};
+pub use explain::{explain_relevance, RelevanceExplanation};
+pub use spellcheck::{correct_query, CorrectionKind, SpellCorrection};
+pub use tiered_retrieval::{
+    TieredRetrieval, TieredConfig, RetrievalTier, TieredResult, Tier, TierSpec, TierAttempt, SkipReason,
+};
+pub use tiered_retrieval::{SearchOptions, SearchQuality};
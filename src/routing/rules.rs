@@ -0,0 +1,172 @@
+//! 領域路由規則 - 以正規表示式或關鍵字把特定查詢導向偏好的引擎、類別與檢索層級
+//!
+//! 讓安全團隊把分診流程寫成設定，例如 CVE 編號直接從 L2 起跳：
+//!
+//! ```toml
+//! [[router.rules]]
+//! name = "cve"
+//! patterns = ['CVE-\d{4}-\d{4,}']
+//! keywords = ["exploit", "poc"]
+//! engines = ["exa"]
+//! category = "news"
+//! min_tier = "l2"
+//! ```
+//!
+//! 規則依序比對，第一條命中的規則生效。
+
+use super::semantic_router::QueryCategory;
+use super::tiered_retrieval::RetrievalTier;
+use crate::types::SearchEngine;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Deserializer};
+
+/// 單條路由規則；`patterns` 或 `keywords` 任一命中即套用
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RoutingRule {
+    pub name: String,
+    /// 正規表示式（不分大小寫），載入時即編譯，語法錯誤會讓設定檔載入失敗
+    #[serde(default, deserialize_with = "deserialize_patterns")]
+    pub patterns: Vec<Regex>,
+    /// 關鍵字（不分大小寫的子字串比對）
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// 偏好的引擎，依序嘗試
+    #[serde(default)]
+    pub engines: Vec<SearchEngine>,
+    /// 覆寫查詢類別（影響快取 TTL）
+    #[serde(default)]
+    pub category: Option<QueryCategory>,
+    /// 階梯式檢索至少升級到此層（仍受預算限制）
+    #[serde(default)]
+    pub min_tier: Option<RetrievalTier>,
+}
+
+impl RoutingRule {
+    /// 以關鍵字建立規則（程式內設定用）
+    pub fn keywords(name: &str, keywords: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            patterns: Vec::new(),
+            keywords: keywords.iter().map(|k| k.to_string()).collect(),
+            engines: Vec::new(),
+            category: None,
+            min_tier: None,
+        }
+    }
+
+    /// 加入正規表示式
+    pub fn with_pattern(mut self, pattern: &str) -> Result<Self, regex::Error> {
+        self.patterns.push(compile(pattern)?);
+        Ok(self)
+    }
+
+    pub fn with_engines(mut self, engines: Vec<SearchEngine>) -> Self {
+        self.engines = engines;
+        self
+    }
+
+    pub fn with_category(mut self, category: QueryCategory) -> Self {
+        self.category = Some(category);
+        self
+    }
+
+    pub fn with_min_tier(mut self, tier: RetrievalTier) -> Self {
+        self.min_tier = Some(tier);
+        self
+    }
+
+    /// 查詢是否命中此規則
+    pub fn matches(&self, query: &str) -> bool {
+        if self.patterns.iter().any(|p| p.is_match(query)) {
+            return true;
+        }
+        let lower = query.to_lowercase();
+        self.keywords
+            .iter()
+            .any(|kw| !kw.is_empty() && lower.contains(&kw.to_lowercase()))
+    }
+}
+
+/// 依序比對，回傳第一條命中的規則
+pub fn match_rule<'a>(rules: &'a [RoutingRule], query: &str) -> Option<&'a RoutingRule> {
+    rules.iter().find(|rule| rule.matches(query))
+}
+
+fn compile(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern).case_insensitive(true).build()
+}
+
+fn deserialize_patterns<'de, D>(deserializer: D) -> Result<Vec<Regex>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|pattern| {
+            compile(pattern)
+                .map_err(|e| serde::de::Error::custom(format!("無效的正規表示式 `{}`: {}", pattern, e)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn playbook() -> Vec<RoutingRule> {
+        serde_json::from_value(serde_json::json!([
+            {
+                "name": "cve",
+                "patterns": ["CVE-\\d{4}-\\d{4,}"],
+                "keywords": ["exploit"],
+                "engines": ["exa", "tavily"],
+                "min_tier": "l2"
+            },
+            {
+                "name": "crates",
+                "keywords": ["crates.io"],
+                "category": "docs"
+            }
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        let rules = playbook();
+        let cve = match_rule(&rules, "cve-2024-3094 xz backdoor").unwrap();
+        assert_eq!(cve.name, "cve");
+        assert_eq!(cve.engines, vec![SearchEngine::Exa, SearchEngine::Tavily]);
+        assert_eq!(cve.min_tier, Some(RetrievalTier::L2));
+
+        assert_eq!(match_rule(&rules, "Exploit for crates.io typosquat").unwrap().name, "cve");
+        let crates = match_rule(&rules, "serde on Crates.io").unwrap();
+        assert_eq!(crates.category, Some(QueryCategory::Docs));
+        assert!(match_rule(&rules, "CVE program history").is_none());
+    }
+
+    #[test]
+    fn test_invalid_rules_rejected() {
+        let bad_regex = serde_json::from_value::<Vec<RoutingRule>>(serde_json::json!([
+            { "name": "broken", "patterns": ["CVE-(\\d{4}"] }
+        ]));
+        assert!(bad_regex.unwrap_err().to_string().contains("CVE-(\\d{4}"));
+
+        let bad_engine = serde_json::from_value::<Vec<RoutingRule>>(serde_json::json!([
+            { "name": "x", "keywords": ["a"], "engines": ["bing"] }
+        ]));
+        assert!(bad_engine.is_err());
+    }
+
+    #[test]
+    fn test_builder() {
+        let rule = RoutingRule::keywords("advisories", &["advisory"])
+            .with_pattern(r"GHSA-\w{4}-\w{4}-\w{4}")
+            .unwrap()
+            .with_min_tier(RetrievalTier::L3);
+        assert!(rule.matches("ghsa-abcd-efgh-ijkl"));
+        assert!(rule.matches("Vendor ADVISORY list"));
+        assert!(!rule.matches("weather"));
+    }
+}
@@ -1,7 +1,18 @@
 //! 語義路由器 - 根據查詢複雜度選擇最適合的處理策略
 
+use super::classifier::{Classification, ClassifierChain, EmbeddingClassifier, QueryClassifier, RuleClassifier};
+use super::condense::{PreparedQuery, QueryCondenser, DEFAULT_CONDENSED_CHARS};
+use super::rules::{match_rule, RoutingRule};
+use crate::embeddings::Embedder;
+use bose_common::text;
+use bose_common::{ComplexityTable, RouterSettings, ROUTER_STRATEGIES};
+use serde::Deserialize;
+use std::str::FromStr;
+use std::sync::Arc;
+
 /// 任務複雜度分類
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum TaskComplexity {
     /// 簡單查詢：事實查詢、定義查詢
     /// 例如："Rust 是什麼？"、"Bose 產品價格"
@@ -16,15 +27,116 @@ pub enum TaskComplexity {
     Complex,
 }
 
+/// 查詢類別，決定結果多久會過時（快取 TTL）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryCategory {
+    /// 新聞、價格、即時資訊：幾分鐘內就會過時
+    News,
+    /// 文件、API 參考、教學：可以保留數天
+    Docs,
+    /// 其他查詢
+    General,
+}
+
+/// 新聞類查詢的關鍵字
+const NEWS_KEYWORDS: &[&str] = &[
+    "news", "latest", "today", "yesterday", "breaking", "this week", "announced", "stock price",
+    "score", "election", "新聞", "最新", "今天", "今日", "昨天", "本週", "即時", "股價",
+];
+
+/// 文件類查詢的關鍵字
+const DOCS_KEYWORDS: &[&str] = &[
+    "docs", "documentation", "api", "reference", "manual", "man page", "syntax", "spec",
+    "rfc", "how to", "tutorial", "example", "文件", "文檔", "教學", "用法", "語法", "範例",
+];
+
+/// 預設的已標記範例查詢，供嵌入分類比對
+const DEFAULT_EXEMPLARS: &[(TaskComplexity, &str)] = &[
+    (TaskComplexity::Simple, "what is rust"),
+    (TaskComplexity::Simple, "who founded bose"),
+    (TaskComplexity::Simple, "python list sort syntax"),
+    (TaskComplexity::Simple, "capital of australia"),
+    (TaskComplexity::Simple, "bose qc45 price"),
+    (TaskComplexity::Simple, "define idempotent"),
+    (TaskComplexity::Simple, "Rust 是什麼"),
+    (TaskComplexity::Medium, "rust vs go performance"),
+    (TaskComplexity::Medium, "difference between tcp and udp"),
+    (TaskComplexity::Medium, "pros and cons of postgres versus mysql"),
+    (TaskComplexity::Medium, "how does the linux kernel scheduler work"),
+    (TaskComplexity::Medium, "best practices for error handling in async rust"),
+    (TaskComplexity::Medium, "比較 Rust 和 Go 的效能"),
+    (TaskComplexity::Complex, "assess the security of bluetooth le pairing and propose mitigations"),
+    (TaskComplexity::Complex, "investigate why our kubernetes pods are oom killed and recommend a fix"),
+    (TaskComplexity::Complex, "literature review of retrieval augmented generation evaluation methods"),
+    (TaskComplexity::Complex, "design a migration plan from a monolith to microservices with tradeoffs"),
+    (TaskComplexity::Complex, "what are the long term economic impacts of ai regulation across regions"),
+    (TaskComplexity::Complex, "分析 Bose 藍牙協議的安全性並提出改進方案"),
+];
+
+/// 複雜度 → 搜尋策略（由 `RouterSettings::strategies` 的名稱解析而來）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrategyMapping {
+    pub simple: SearchStrategy,
+    pub medium: SearchStrategy,
+    pub complex: SearchStrategy,
+}
+
+impl Default for StrategyMapping {
+    fn default() -> Self {
+        Self {
+            simple: SearchStrategy::SingleEngine,
+            medium: SearchStrategy::TieredRetrieval,
+            complex: SearchStrategy::DeepResearch,
+        }
+    }
+}
+
+impl From<&ComplexityTable> for StrategyMapping {
+    /// 名稱已由 `BoseConfig::validate` 檢查；無法辨識時沿用預設策略
+    fn from(table: &ComplexityTable) -> Self {
+        let defaults = Self::default();
+        let parse = |name: &str, default| name.parse().unwrap_or(default);
+        Self {
+            simple: parse(&table.simple, defaults.simple),
+            medium: parse(&table.medium, defaults.medium),
+            complex: parse(&table.complex, defaults.complex),
+        }
+    }
+}
+
 /// 語義路由器配置
+///
+/// 設定檔的 `[router]` 區段載入為 `BoseConfig::router`，再以 `From<&RouterSettings>` 轉成此結構：
+///
+/// ```toml
+/// [router.models]
+/// simple = "gpt-4o-mini"
+/// complex = "gpt-4o"
+///
+/// [router.strategies]
+/// medium = "hybrid"
+/// ```
 #[derive(Debug, Clone)]
 pub struct RouterConfig {
     /// 簡單查詢的最大長度（字元數）
     pub simple_max_length: usize,
     /// 複雜查詢的關鍵字
     pub complex_keywords: Vec<String>,
-    /// 是否啟用語義分析
+    /// 是否啟用語義分析（設定嵌入模型時以範例查詢的相似度分類）
     pub enable_semantic_analysis: bool,
+    /// 已標記的範例查詢
+    pub exemplars: Vec<(TaskComplexity, String)>,
+    /// 嵌入分類的信心度（最相近類別的相似度）低於此值時交給下一個分類器
+    pub min_exemplar_similarity: f32,
+    /// 超過此長度（字元數）的查詢在送出前先濃縮
+    pub max_query_chars: usize,
+    /// 各複雜度使用的 LLM 模型
+    pub models: ComplexityTable,
+    /// 各複雜度使用的搜尋策略
+    pub strategies: StrategyMapping,
+    /// 領域路由規則（`[[router.rules]]`），依序比對
+    pub rules: Vec<RoutingRule>,
 }
 
 impl Default for RouterConfig {
@@ -41,19 +153,51 @@ impl Default for RouterConfig {
                 "compare".to_string(),
             ],
             enable_semantic_analysis: true,
+            exemplars: DEFAULT_EXEMPLARS
+                .iter()
+                .map(|(complexity, query)| (*complexity, query.to_string()))
+                .collect(),
+            min_exemplar_similarity: 0.5,
+            max_query_chars: 300,
+            models: RouterSettings::default().models,
+            strategies: StrategyMapping::default(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+impl From<&RouterSettings> for RouterConfig {
+    fn from(settings: &RouterSettings) -> Self {
+        Self {
+            simple_max_length: settings.simple_max_length,
+            complex_keywords: settings.complex_keywords.clone(),
+            max_query_chars: settings.max_query_chars,
+            models: settings.models.clone(),
+            strategies: StrategyMapping::from(&settings.strategies),
+            ..Self::default()
         }
     }
 }
 
 /// 語義路由器
+///
+/// 複雜度由 `ClassifierChain` 判斷（可插入自訂分類器），都沒有把握時退回關鍵字規則；
+/// 模型與搜尋策略的對應由 `select_model` / `select_search_strategy` 決定。
 pub struct SemanticRouter {
     config: RouterConfig,
+    rules: RuleClassifier,
+    chain: ClassifierChain,
 }
 
 impl SemanticRouter {
     /// 建立新的語義路由器
     pub fn new(config: RouterConfig) -> Self {
-        Self { config }
+        let rules = RuleClassifier::new(config.simple_max_length, config.complex_keywords.clone());
+        Self {
+            config,
+            rules,
+            chain: ClassifierChain::new(),
+        }
     }
 
     /// 使用預設配置建立路由器
@@ -61,52 +205,129 @@ impl SemanticRouter {
         Self::new(RouterConfig::default())
     }
 
-    /// 分類查詢複雜度
+    /// 加入以範例查詢嵌入相似度分類的 `EmbeddingClassifier`（需啟用 `enable_semantic_analysis`）
+    pub fn with_embedder(self, embedder: Arc<dyn Embedder>) -> Self {
+        if !self.config.enable_semantic_analysis {
+            return self;
+        }
+        let classifier = EmbeddingClassifier::new(embedder, self.config.exemplars.clone());
+        let min_confidence = self.config.min_exemplar_similarity;
+        self.with_classifier(Arc::new(classifier), min_confidence)
+    }
+
+    /// 在分類鏈末端加入分類器；信心度低於 `min_confidence` 時交給下一個
+    pub fn with_classifier(mut self, classifier: Arc<dyn QueryClassifier>, min_confidence: f32) -> Self {
+        self.chain.push(classifier, min_confidence);
+        self
+    }
+
+    /// 依分類鏈判斷複雜度，並回報做出判斷的分類器與信心度
+    pub async fn classification(&self, query: &str) -> Classification {
+        match self.chain.classify_first(query).await {
+            Some(result) => result,
+            None => self.rules.classification(query),
+        }
+    }
+
+    /// 分類查詢複雜度：依序詢問分類鏈，都沒有達到門檻時退回 `classify` 的關鍵字規則
+    pub async fn classify_semantic(&self, query: &str) -> TaskComplexity {
+        self.classification(query).await.complexity
+    }
+
+    /// 分類查詢複雜度（關鍵字規則）
     pub fn classify(&self, query: &str) -> TaskComplexity {
+        self.rules.classify_rules(query)
+    }
+
+    /// 第一條命中的領域路由規則
+    pub fn match_rule(&self, query: &str) -> Option<&RoutingRule> {
+        match_rule(&self.config.rules, query)
+    }
+
+    /// 判斷查詢類別：路由規則指定的類別優先，其次新聞、文件，皆非時為一般
+    pub fn categorize(&self, query: &str) -> QueryCategory {
+        if let Some(category) = self.match_rule(query).and_then(|rule| rule.category) {
+            return category;
+        }
         let query_lower = query.to_lowercase();
-        let query_len = query.chars().count();
+        let has_word = |keywords: &[&str]| {
+            keywords.iter().any(|kw| {
+                // 英文關鍵字需落在詞界上，避免 "api" 命中 "rapid"
+                if kw.is_ascii() {
+                    query_lower
+                        .match_indices(kw)
+                        .any(|(i, _)| is_word_boundary(&query_lower, i, i + kw.len()))
+                } else {
+                    query_lower.contains(kw)
+                }
+            })
+        };
 
-        // 檢查複雜查詢關鍵字
-        let has_complex_keyword = self.config.complex_keywords
-            .iter()
-            .any(|kw| query_lower.contains(&kw.to_lowercase()));
+        if has_word(NEWS_KEYWORDS) {
+            QueryCategory::News
+        } else if has_word(DOCS_KEYWORDS) || query.contains("::") || query.contains("()") {
+            QueryCategory::Docs
+        } else {
+            QueryCategory::General
+        }
+    }
 
-        // 檢查多個子句（使用標點符號判斷）
-        let clause_count = query.matches([',', '，', '、']).count() + 1;
+    /// 查詢是否過長（超過 `max_query_chars` 或引擎的 `engine_limit`）
+    pub fn is_overlong(&self, query: &str, engine_limit: usize) -> bool {
+        query.chars().count() > self.config.max_query_chars.min(engine_limit)
+    }
 
-        // 檢查問號數量（多個問題 = 複雜）
-        let question_count = query.matches(['?', '？']).count();
+    /// 過長的查詢先經 `condenser` 濃縮；濃縮失敗時直接截斷到上限
+    pub async fn prepare_query(
+        &self,
+        query: &str,
+        engine_limit: usize,
+        condenser: &dyn QueryCondenser,
+    ) -> PreparedQuery {
+        if !self.is_overlong(query, engine_limit) {
+            return PreparedQuery::unchanged(query);
+        }
 
-        // 分類邏輯
-        if question_count > 1 || clause_count > 2 ||
-           (has_complex_keyword && query_len > 100) {
-            TaskComplexity::Complex
-        } else if has_complex_keyword || query_len > self.config.simple_max_length {
-            TaskComplexity::Medium
-        } else {
-            TaskComplexity::Simple
+        let limit = self.config.max_query_chars.min(engine_limit);
+        let target = DEFAULT_CONDENSED_CHARS.min(limit);
+        let (sent, condenser) = match condenser.condense(query, target).await {
+            Some(condensed) if condensed.chars().count() <= limit => (condensed, condenser.name()),
+            _ => (text::truncate_chars(query, limit).to_string(), "truncate"),
+        };
+        PreparedQuery {
+            original: query.to_string(),
+            sent,
+            condenser: Some(condenser),
         }
     }
 
-    /// 根據複雜度選擇 LLM 模型
-    pub fn select_model(&self, complexity: TaskComplexity) -> &'static str {
+    /// 根據複雜度選擇 LLM 模型（`RouterConfig::models`）
+    pub fn select_model(&self, complexity: TaskComplexity) -> &str {
+        let models = &self.config.models;
         match complexity {
-            TaskComplexity::Simple => "claude-haiku-4-5",
-            TaskComplexity::Medium => "claude-sonnet-4-5",
-            TaskComplexity::Complex => "claude-opus-4-5",
+            TaskComplexity::Simple => &models.simple,
+            TaskComplexity::Medium => &models.medium,
+            TaskComplexity::Complex => &models.complex,
         }
     }
 
-    /// 根據複雜度選擇搜尋策略
+    /// 根據複雜度選擇搜尋策略（`RouterConfig::strategies`）
     pub fn select_search_strategy(&self, complexity: TaskComplexity) -> SearchStrategy {
+        let strategies = &self.config.strategies;
         match complexity {
-            TaskComplexity::Simple => SearchStrategy::SingleEngine,
-            TaskComplexity::Medium => SearchStrategy::TieredRetrieval,
-            TaskComplexity::Complex => SearchStrategy::DeepResearch,
+            TaskComplexity::Simple => strategies.simple,
+            TaskComplexity::Medium => strategies.medium,
+            TaskComplexity::Complex => strategies.complex,
         }
     }
 }
 
+fn is_word_boundary(text: &str, start: usize, end: usize) -> bool {
+    let before = text[..start].chars().next_back();
+    let after = text[end..].chars().next();
+    !before.is_some_and(|c| c.is_alphanumeric()) && !after.is_some_and(|c| c.is_alphanumeric())
+}
+
 /// 搜尋策略
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SearchStrategy {
@@ -116,11 +337,29 @@ pub enum SearchStrategy {
     TieredRetrieval,
     /// 深度研究（所有引擎 + 內容提取）
     DeepResearch,
+    /// 混合檢索（本地語義索引 + 網路，RRF 融合）
+    Hybrid,
+}
+
+impl FromStr for SearchStrategy {
+    type Err = String;
+
+    /// 名稱與 `ROUTER_STRATEGIES` 相同
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "single_engine" => Ok(Self::SingleEngine),
+            "tiered_retrieval" => Ok(Self::TieredRetrieval),
+            "deep_research" => Ok(Self::DeepResearch),
+            "hybrid" => Ok(Self::Hybrid),
+            other => Err(format!("未知的搜尋策略 `{}`（可用: {}）", other, ROUTER_STRATEGIES.join("、"))),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::routing::RetrievalTier;
 
     #[test]
     fn test_simple_query() {
@@ -149,6 +388,148 @@ mod tests {
         );
     }
 
+    /// 以關鍵字決定三個軸的測試用嵌入：簡單 / 比較 / 深入分析
+    struct AxisEmbedder;
+
+    impl Embedder for AxisEmbedder {
+        fn dimensions(&self) -> usize {
+            3
+        }
+
+        fn embed<'a>(&'a self, texts: &'a [String]) -> crate::embeddings::EmbedFuture<'a> {
+            let axis = |text: &str, words: &[&str]| {
+                if words.iter().any(|w| text.contains(w)) { 1.0 } else { 0.0 }
+            };
+            Box::pin(async move {
+                Ok(texts
+                    .iter()
+                    .map(|t| {
+                        let t = t.to_lowercase();
+                        vec![
+                            axis(&t, &["what is", "price", "who", "capital", "define", "syntax", "是什麼"]),
+                            axis(&t, &["vs", "versus", "difference", "pros and cons", "how does", "best practices", "比較"]),
+                            axis(&t, &["assess", "investigate", "review", "design", "impacts", "分析"]),
+                        ]
+                    })
+                    .collect())
+            })
+        }
+    }
+
+    struct FailingEmbedder;
+
+    impl Embedder for FailingEmbedder {
+        fn dimensions(&self) -> usize {
+            3
+        }
+
+        fn embed<'a>(&'a self, _texts: &'a [String]) -> crate::embeddings::EmbedFuture<'a> {
+            Box::pin(async { Err(crate::types::SearchError::NetworkError("offline".into())) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_classify_semantic() {
+        let router = SemanticRouter::with_defaults().with_embedder(Arc::new(AxisEmbedder));
+        // 關鍵字規則把這些短英文查詢都當成簡單查詢
+        assert_eq!(router.classify("go vs zig compile speed"), TaskComplexity::Simple);
+        assert_eq!(router.classify_semantic("go vs zig compile speed").await, TaskComplexity::Medium);
+        assert_eq!(
+            router.classify_semantic("assess npm supply chain risk").await,
+            TaskComplexity::Complex
+        );
+        // 沒有相近範例時退回關鍵字規則
+        assert_eq!(router.classify_semantic("hello world").await, TaskComplexity::Simple);
+
+        let disabled = SemanticRouter::new(RouterConfig {
+            enable_semantic_analysis: false,
+            ..RouterConfig::default()
+        })
+        .with_embedder(Arc::new(AxisEmbedder));
+        assert_eq!(disabled.classify_semantic("go vs zig compile speed").await, TaskComplexity::Simple);
+    }
+
+    #[tokio::test]
+    async fn test_custom_classifier_chain() {
+        use crate::routing::classifier::ClassifyFuture;
+
+        /// 只對含 "CVE" 的查詢有把握
+        struct CveClassifier;
+
+        impl QueryClassifier for CveClassifier {
+            fn classify<'a>(&'a self, query: &'a str) -> ClassifyFuture<'a> {
+                let confidence = if query.contains("CVE") { 0.9 } else { 0.1 };
+                Box::pin(async move {
+                    Some(Classification {
+                        complexity: TaskComplexity::Complex,
+                        confidence,
+                        classifier: "cve",
+                    })
+                })
+            }
+
+            fn name(&self) -> &'static str {
+                "cve"
+            }
+        }
+
+        let router = SemanticRouter::with_defaults()
+            .with_classifier(Arc::new(CveClassifier), 0.8)
+            .with_embedder(Arc::new(AxisEmbedder));
+        let cve = router.classification("CVE-2024-3094 impact").await;
+        assert_eq!((cve.complexity, cve.classifier), (TaskComplexity::Complex, "cve"));
+        assert_eq!(
+            router.select_search_strategy(cve.complexity),
+            SearchStrategy::DeepResearch
+        );
+        let fallthrough = router.classification("go vs zig compile speed").await;
+        assert_eq!((fallthrough.complexity, fallthrough.classifier), (TaskComplexity::Medium, "embedding"));
+        let rules = router.classification("hello world").await;
+        assert_eq!(rules.classifier, "rules");
+    }
+
+    #[tokio::test]
+    async fn test_classify_semantic_falls_back_without_embeddings() {
+        let query = "比較 Rust 和 Go 的效能差異";
+        let failing = SemanticRouter::with_defaults().with_embedder(Arc::new(FailingEmbedder));
+        assert_eq!(failing.classify_semantic(query).await, TaskComplexity::Medium);
+        let plain = SemanticRouter::with_defaults();
+        assert_eq!(plain.classify_semantic("go vs zig compile speed").await, TaskComplexity::Simple);
+    }
+
+    #[test]
+    fn test_categorize() {
+        let router = SemanticRouter::with_defaults();
+        assert_eq!(router.categorize("latest Rust release news"), QueryCategory::News);
+        assert_eq!(router.categorize("台積電 今天 股價"), QueryCategory::News);
+        assert_eq!(router.categorize("tokio spawn API reference"), QueryCategory::Docs);
+        assert_eq!(router.categorize("std::fs::read_to_string"), QueryCategory::Docs);
+        assert_eq!(router.categorize("rapid prototyping"), QueryCategory::General);
+    }
+
+    #[tokio::test]
+    async fn test_prepare_query() {
+        use crate::routing::condense::KeywordCondenser;
+
+        let router = SemanticRouter::with_defaults();
+        let short = router.prepare_query("rust async", 500, &KeywordCondenser).await;
+        assert!(!short.is_condensed());
+        assert_eq!(short.sent, "rust async");
+
+        let long = "Please explain how the Rust borrow checker handles closures. ".repeat(8);
+        assert!(router.is_overlong(&long, 500));
+        let prepared = router.prepare_query(&long, 500, &KeywordCondenser).await;
+        assert_eq!(prepared.condenser, Some("keywords"));
+        assert!(prepared.sent.chars().count() <= DEFAULT_CONDENSED_CHARS);
+        assert!(prepared.sent.contains("borrow"));
+
+        // 全是停用詞時退回截斷
+        let filler = "the ".repeat(100);
+        let truncated = router.prepare_query(&filler, 50, &KeywordCondenser).await;
+        assert_eq!(truncated.condenser, Some("truncate"));
+        assert_eq!(truncated.sent.chars().count(), 50);
+    }
+
     #[test]
     fn test_model_selection() {
         let router = SemanticRouter::with_defaults();
@@ -157,6 +538,40 @@ mod tests {
         assert_eq!(router.select_model(TaskComplexity::Complex), "claude-opus-4-5");
     }
 
+    #[test]
+    fn test_custom_model_and_strategy_mapping() {
+        let mut settings = RouterSettings {
+            complex_keywords: vec!["analyze".to_string()],
+            ..RouterSettings::default()
+        };
+        settings.models.simple = "gpt-4o-mini".to_string();
+        settings.models.complex = "gpt-4o".to_string();
+        settings.strategies.medium = "hybrid".to_string();
+        let router = SemanticRouter::new(RouterConfig::from(&settings));
+        assert_eq!(router.select_model(TaskComplexity::Simple), "gpt-4o-mini");
+        assert_eq!(router.select_model(TaskComplexity::Medium), "claude-sonnet-4-5");
+        assert_eq!(router.select_model(TaskComplexity::Complex), "gpt-4o");
+        assert_eq!(router.select_search_strategy(TaskComplexity::Medium), SearchStrategy::Hybrid);
+        assert_eq!(router.select_search_strategy(TaskComplexity::Complex), SearchStrategy::DeepResearch);
+
+        assert!("deep".parse::<SearchStrategy>().is_err());
+        for name in ROUTER_STRATEGIES {
+            assert!(name.parse::<SearchStrategy>().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_rules_override_category() {
+        let rules = serde_json::from_value(serde_json::json!([
+            { "name": "cve", "patterns": ["CVE-\\d{4}"], "category": "news", "min_tier": "l3" }
+        ]))
+        .unwrap();
+        let router = SemanticRouter::new(RouterConfig { rules, ..RouterConfig::default() });
+        assert_eq!(router.categorize("CVE-2021-44228 docs"), QueryCategory::News);
+        assert_eq!(router.categorize("log4j docs"), QueryCategory::Docs);
+        assert_eq!(router.match_rule("cve-2021-44228").unwrap().min_tier, Some(RetrievalTier::L3));
+    }
+
     #[test]
     fn test_search_strategy_selection() {
         let router = SemanticRouter::with_defaults();
@@ -0,0 +1,233 @@
+//! 查詢拼字修正 - 零結果時以啟發式規則修正查詢後重試一次
+//!
+//! 不依賴任何引擎的「您是不是要找」功能，所有引擎共用同一套規則：
+//! 1. CLI 參數：全形 / 破折號的 `—force` 還原為 `--force`，放在指令前面的參數移到指令之後
+//! 2. 黏在一起的 CamelCase 識別字拆成單字（`ReadToString` → `Read To String`）
+//! 3. 常見技術詞彙的拼字錯誤（編輯距離，包含相鄰字母對調）
+
+use std::fmt;
+
+/// 套用的修正規則
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CorrectionKind {
+    /// 參數的破折號寫法錯誤
+    FlagDashes,
+    /// 參數放在指令前面
+    FlagOrder,
+    /// CamelCase 識別字拆字
+    CamelCase,
+    /// 拼字錯誤
+    Spelling,
+}
+
+impl fmt::Display for CorrectionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CorrectionKind::FlagDashes => "flag-dashes",
+            CorrectionKind::FlagOrder => "flag-order",
+            CorrectionKind::CamelCase => "camel-case",
+            CorrectionKind::Spelling => "spelling",
+        };
+        f.write_str(name)
+    }
+}
+
+/// 修正結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpellCorrection {
+    pub original: String,
+    pub corrected: String,
+    /// 依套用順序列出的規則（不重複）
+    pub kinds: Vec<CorrectionKind>,
+}
+
+/// 拼字修正的詞彙表（小寫）；排在前面的詞在距離相同時優先
+const VOCABULARY: &[&str] = &[
+    "rust", "python", "javascript", "typescript", "golang", "java", "kotlin", "swift",
+    "async", "await", "tokio", "future", "thread", "mutex", "channel", "deadlock", "runtime",
+    "error", "exception", "panic", "result", "option", "string", "vector", "array", "slice",
+    "struct", "enum", "trait", "generic", "lifetime", "borrow", "ownership", "closure",
+    "iterator", "function", "method", "module", "package", "crate", "library", "compile",
+    "compiler", "install", "import", "export", "config", "configuration", "database", "query",
+    "index", "server", "client", "request", "response", "header", "cookie", "session",
+    "docker", "kubernetes", "container", "deploy", "deployment", "linux", "windows", "macos",
+    "ubuntu", "debian", "github", "version", "release", "update", "upgrade", "memory", "leak",
+    "performance", "benchmark", "optimize", "example", "tutorial", "documentation",
+    "command", "terminal", "script", "shell", "network", "socket", "timeout", "connection",
+    "authentication", "authorization", "token", "password", "encryption", "certificate",
+    "serialize", "deserialize", "parse", "parser", "format", "regex", "pattern", "search",
+    "engine", "cache", "redis", "postgres", "postgresql", "mysql", "sqlite", "mongodb",
+    "react", "angular", "webpack", "frontend", "backend", "framework", "testing", "debug",
+    "debugger", "warning", "undefined", "null", "pointer", "reference", "segmentation",
+    "fault", "overflow", "stack", "heap", "allocation", "garbage", "collection", "machine",
+    "learning", "neural", "model", "transformer", "embedding", "dataset", "training",
+    "language", "programming", "algorithm", "recursion", "sorting", "binary", "integer",
+    "float", "boolean", "variable", "constant", "macro", "attribute", "derive", "implement",
+    "implementation", "interface", "inheritance", "polymorphism", "dependency", "dependencies",
+    "environment", "permission", "denied", "failed", "missing", "between", "difference",
+    "without", "because", "should", "which", "where", "while", "through",
+];
+
+/// 嘗試修正查詢；沒有任何規則適用時回傳 None
+pub fn correct_query(query: &str) -> Option<SpellCorrection> {
+    let mut kinds = Vec::new();
+    let mut note = |kind: CorrectionKind| {
+        if !kinds.contains(&kind) {
+            kinds.push(kind);
+        }
+    };
+
+    let mut tokens: Vec<String> = Vec::new();
+    for token in query.split_whitespace() {
+        if let Some(fixed) = fix_dashes(token) {
+            note(CorrectionKind::FlagDashes);
+            tokens.push(fixed);
+        } else if let Some(words) = split_camel_case(token) {
+            note(CorrectionKind::CamelCase);
+            tokens.extend(words);
+        } else if let Some(fixed) = fix_spelling(token) {
+            note(CorrectionKind::Spelling);
+            tokens.push(fixed);
+        } else {
+            tokens.push(token.to_string());
+        }
+    }
+
+    // 開頭連續的參數移到第一個非參數詞之後：`--version cargo` → `cargo --version`
+    let leading = tokens.iter().take_while(|t| is_flag(t)).count();
+    if leading > 0 && leading < tokens.len() {
+        let flags: Vec<String> = tokens.drain(..leading).collect();
+        tokens.splice(1..1, flags);
+        note(CorrectionKind::FlagOrder);
+    }
+
+    let corrected = tokens.join(" ");
+    (!kinds.is_empty() && corrected != query.trim()).then(|| SpellCorrection {
+        original: query.to_string(),
+        corrected,
+        kinds,
+    })
+}
+
+fn is_flag(token: &str) -> bool {
+    token.len() > 1 && token.starts_with('-') && !token[1..].starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// `—force`、`–force`、`－f` → `--force`、`--force`、`-f`
+fn fix_dashes(token: &str) -> Option<String> {
+    let rest = token.strip_prefix(['—', '–', '－'])?;
+    if rest.is_empty() || !rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let dashes = if rest.chars().count() == 1 { "-" } else { "--" };
+    Some(format!("{dashes}{rest}"))
+}
+
+/// 拆開 CamelCase：至少兩個「小寫 → 大寫」轉折才視為黏在一起的識別字
+///
+/// 只處理純英數詞，`std::io::Read`、`foo.bar` 這類路徑保留原樣；
+/// 連續大寫（縮寫）視為同一個字：`HTTPServerError` → `HTTP Server Error`。
+fn split_camel_case(token: &str) -> Option<Vec<String>> {
+    if !token.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    let chars: Vec<char> = token.chars().collect();
+    let mut words = Vec::new();
+    let mut start = 0;
+    for i in 1..chars.len() {
+        let lower_to_upper = chars[i - 1].is_ascii_lowercase() && chars[i].is_ascii_uppercase();
+        let acronym_end = chars[i - 1].is_ascii_uppercase()
+            && chars[i].is_ascii_uppercase()
+            && chars.get(i + 1).is_some_and(|c| c.is_ascii_lowercase());
+        if lower_to_upper || acronym_end {
+            words.push(chars[start..i].iter().collect::<String>());
+            start = i;
+        }
+    }
+    words.push(chars[start..].iter().collect());
+    (words.len() >= 3).then_some(words)
+}
+
+/// 以詞彙表修正拼字：4 字元以上的全小寫英文詞，距離 1（8 字元以上允許 2）
+fn fix_spelling(token: &str) -> Option<String> {
+    let len = token.chars().count();
+    if len < 4 || !token.chars().all(|c| c.is_ascii_lowercase()) || VOCABULARY.contains(&token) {
+        return None;
+    }
+    let max_distance = if len >= 8 { 2 } else { 1 };
+    VOCABULARY
+        .iter()
+        .filter(|w| w.len().abs_diff(len) <= max_distance)
+        .map(|w| (edit_distance(token, w), *w))
+        .filter(|(d, _)| *d <= max_distance)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, w)| w.to_string())
+}
+
+/// Damerau-Levenshtein（相鄰對調算一次）距離
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.as_bytes();
+    let b = b.as_bytes();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spelling() {
+        let c = correct_query("rust asnyc mutx deadlock").unwrap();
+        assert_eq!(c.corrected, "rust async mutex deadlock");
+        assert_eq!(c.kinds, vec![CorrectionKind::Spelling]);
+        assert_eq!(edit_distance("asnyc", "async"), 1);
+
+        // 正確的查詢、短詞與大寫詞不修正
+        assert!(correct_query("rust async mutex").is_none());
+        assert!(correct_query("cgo FFI").is_none());
+    }
+
+    #[test]
+    fn test_flags() {
+        let c = correct_query("—version cargo").unwrap();
+        assert_eq!(c.corrected, "cargo --version");
+        assert_eq!(c.kinds, vec![CorrectionKind::FlagDashes, CorrectionKind::FlagOrder]);
+
+        assert_eq!(correct_query("–v git").unwrap().corrected, "git -v");
+        assert!(correct_query("git log --oneline").is_none());
+        assert!(correct_query("-1 temperature").is_none());
+    }
+
+    #[test]
+    fn test_camel_case() {
+        let c = correct_query("tokio SpawnBlockingHandle").unwrap();
+        assert_eq!(c.corrected, "tokio Spawn Blocking Handle");
+        assert_eq!(c.kinds, vec![CorrectionKind::CamelCase]);
+        assert_eq!(
+            split_camel_case("HTTPServerError").unwrap(),
+            vec!["HTTP", "Server", "Error"]
+        );
+
+        // 單一駝峰與路徑保留
+        assert!(split_camel_case("HashMap").is_none());
+        assert!(correct_query("std::io::BufReader").is_none());
+    }
+}
@@ -1,20 +1,50 @@
 //! 階梯式檢索 - 根據置信度自動升級搜尋引擎
+//!
+//! 檢索鏈是有序的 `Tier` 清單，預設為 DuckDuckGo → Exa → Tavily；
+//! 任何實作 `SearchProvider` 的來源（SearXNG、Brave、本地索引 ...）都可以加入，
+//! 順序也可由設定檔的 `tiers` 指定。
 
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use bose_common::{BoseConfig, TierEntry, TierThresholds};
+
+use crate::client::{DUCKDUCKGO_REQUESTS_PER_SECOND, EXA_REQUESTS_PER_SECOND, TAVILY_REQUESTS_PER_SECOND};
+use crate::cost::{CostTracker, EXA_COST};
 use crate::duckduckgo::DuckDuckGoClient;
+use crate::embeddings::Embedder;
 use crate::exa::ExaClient;
+use crate::filtering::DomainFilter;
+use crate::optimization::RateLimiter;
+use crate::provider::SearchProvider;
+use crate::spam::SpamDetector;
 use crate::tavily::TavilyClient;
-use crate::routing::confidence::ConfidenceCalculator;
-use crate::types::{SearchResult, SearchError};
+use crate::routing::confidence::{ConfidenceCalculator, ConfidenceSignals};
+use crate::routing::corroboration::{corroborate, engine_agreement, url_key};
+use crate::routing::semantic_router::QueryCategory;
+use crate::types::{SearchEngine, SearchResult, SearchError, TimeRange};
+use crate::vectorstore::hybrid::RRF_K;
 
-/// 階梯式檢索配置
+/// 階梯式檢索配置；設定檔的 `[tiers]` 經由 `From<&TierThresholds>` 轉成此結構
 #[derive(Debug, Clone)]
 pub struct TieredConfig {
     /// L1 → L2 的置信度閾值
     pub l1_threshold: f32,
-    /// L2 → L3 的置信度閾值
+    /// L2 → L3 的置信度閾值（也是第三層之後未指定閾值時的預設）
     pub l2_threshold: f32,
     /// 每層的最大結果數
     pub max_results_per_tier: usize,
+    /// 檢索鏈的順序；空白時依 `TieredRetrieval::from_providers` 傳入的順序
+    pub tiers: Vec<TierSpec>,
+    /// 單次搜尋的花費上限（美元）；單次覆寫與品質預設都不能超過
+    pub max_cost: Option<f32>,
+    /// 對沖模式：L1 開始後經過此毫秒數仍未完成，就以原查詢推測性啟動 L2；None 為停用
+    pub hedge_after_ms: Option<u64>,
+    /// 合併模式：升級後保留前面各層的結果，以 RRF 融合並對聯集計算置信度
+    pub merge_tiers: bool,
 }
 
 impl Default for TieredConfig {
@@ -23,46 +53,305 @@ impl Default for TieredConfig {
             l1_threshold: 0.80,  // DDG → Exa
             l2_threshold: 0.85,  // Exa → Tavily
             max_results_per_tier: 10,
+            tiers: Vec::new(),
+            max_cost: None,
+            hedge_after_ms: None,
+            merge_tiers: false,
         }
     }
 }
 
-/// 檢索層級
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+impl TieredConfig {
+    /// 第 `index` 層（從 0 起算）未指定時的停止閾值
+    fn default_threshold(&self, index: usize) -> f32 {
+        match index {
+            0 => self.l1_threshold,
+            _ => self.l2_threshold,
+        }
+    }
+}
+
+impl From<&TierThresholds> for TieredConfig {
+    fn from(tiers: &TierThresholds) -> Self {
+        Self {
+            l1_threshold: tiers.l1_threshold as f32,
+            l2_threshold: tiers.l2_threshold as f32,
+            max_results_per_tier: tiers.max_results_per_tier,
+            tiers: tiers.chain.iter().map(TierSpec::from).collect(),
+            max_cost: tiers.max_cost_usd.map(|usd| usd as f32),
+            hedge_after_ms: tiers.hedge_after_ms,
+            merge_tiers: tiers.merge_tiers,
+        }
+    }
+}
+
+/// 檢索鏈中的一層，對應設定檔的 `[[tiers.chain]]`：
+///
+/// ```toml
+/// [[tiers.chain]]
+/// provider = "duckduckgo"
+/// threshold = 0.75
+///
+/// [[tiers.chain]]
+/// provider = "exa"
+/// ```
+#[derive(Debug, Clone)]
+pub struct TierSpec {
+    /// `SearchProvider::name`
+    pub provider: String,
+    /// 此層置信度達到閾值即停止；未指定時依層級沿用 `l1_threshold` / `l2_threshold`
+    pub threshold: Option<f32>,
+    /// 覆寫 `SearchProvider::cost` 的成本估計
+    pub cost: Option<f32>,
+}
+
+impl From<&TierEntry> for TierSpec {
+    fn from(entry: &TierEntry) -> Self {
+        Self {
+            provider: entry.provider.clone(),
+            threshold: entry.threshold.map(|t| t as f32),
+            cost: entry.cost.map(|c| c as f32),
+        }
+    }
+}
+
+/// L2 (Exa) 單次成本估計，用於品質預設的預算
+const L2_COST: f32 = EXA_COST;
+/// 搜尋品質預設
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchQuality {
+    /// 快速：低閾值，最多只用到 L2
+    Fast,
+    /// 平衡：與 `TieredConfig::default()` 相同
+    #[default]
+    Balanced,
+    /// 徹底：高閾值，不限成本
+    Thorough,
+}
+
+impl SearchQuality {
+    /// 對應的閾值與預算組合：(l1_threshold, l2_threshold, max_cost)
+    pub fn preset(self) -> (f32, f32, Option<f32>) {
+        match self {
+            SearchQuality::Fast => (0.60, 0.70, Some(L2_COST)),
+            SearchQuality::Balanced => (0.80, 0.85, None),
+            SearchQuality::Thorough => (0.90, 0.95, None),
+        }
+    }
+}
+
+/// 單次搜尋的覆寫選項
+///
+/// 明確指定的閾值與成本優先於 `quality` 預設，未指定的部分沿用 `TieredConfig`。
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    pub quality: Option<SearchQuality>,
+    pub l1_threshold: Option<f32>,
+    pub l2_threshold: Option<f32>,
+    pub max_cost: Option<f32>,
+    /// 至少升級到此層（例如路由規則的 `min_tier`）；仍受預算限制
+    pub min_tier: Option<RetrievalTier>,
+    /// 查詢限定的時間範圍；有值時過時的結果會降低置信度
+    pub time_range: Option<TimeRange>,
+    /// 查詢類別（`SemanticRouter::categorize`）；新聞類查詢同樣要求時效
+    pub category: Option<QueryCategory>,
+    /// 整次搜尋的時限；超過時取消進行中的請求，回傳已完成層級的部分結果
+    pub deadline: Option<Duration>,
+}
+
+impl SearchOptions {
+    pub fn with_quality(quality: SearchQuality) -> Self {
+        Self {
+            quality: Some(quality),
+            ..Default::default()
+        }
+    }
+}
+
+/// 實際生效的閾值與預算
+#[derive(Debug, Clone, PartialEq)]
+struct EffectiveLimits {
+    /// 各層的停止閾值，與檢索鏈同序
+    thresholds: Vec<f32>,
+    max_cost: Option<f32>,
+}
+
+impl EffectiveLimits {
+    fn threshold(&self, index: usize) -> f32 {
+        self.thresholds.get(index).copied().unwrap_or(f32::INFINITY)
+    }
+
+    fn allows(&self, cost: f32) -> bool {
+        self.max_cost.is_none_or(|max| cost <= max)
+    }
+}
+
+/// 檢索層級（依成本遞增排序）
+///
+/// 檢索鏈的第一層為 L1、第二層為 L2，第三層之後都記為 L3。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum RetrievalTier {
     L1,  // DuckDuckGo (免費)
     L2,  // Exa (付費，精準)
     L3,  // Tavily (付費，深度)
 }
 
+impl RetrievalTier {
+    /// 檢索鏈第 `index` 層（從 0 起算）對應的層級
+    pub fn at(index: usize) -> Self {
+        match index {
+            0 => RetrievalTier::L1,
+            1 => RetrievalTier::L2,
+            _ => RetrievalTier::L3,
+        }
+    }
+
+    /// 在檢索鏈中的位置（從 0 起算）
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
 /// 階梯式檢索結果
 #[derive(Debug)]
 pub struct TieredResult {
     pub results: Vec<SearchResult>,
     pub tier_used: RetrievalTier,
+    /// 產生結果的 `SearchProvider::name`；合併模式下為各層名稱以 `+` 連接
+    pub provider: String,
     pub confidence: f32,
+    /// 本次搜尋所有已執行層級的累計成本
     pub cost_estimate: f32,
+    /// 置信度未達閾值，但花費上限或預算不允許再升級
+    pub budget_stopped: bool,
+    /// 超過 `SearchOptions::deadline`，結果只含時限內完成的層級
+    pub timed_out: bool,
+    /// 檢索鏈每一層的執行紀錄（與檢索鏈同序），說明為何升級或停止
+    pub tier_trace: Vec<TierAttempt>,
+}
+
+/// 某一層未執行（或結果被捨棄）的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// 前面的層級置信度已達閾值
+    ThresholdMet,
+    /// 超過本次搜尋的花費上限（`max_cost`）
+    MaxCost,
+    /// 花費帳本的預算不足
+    Budget,
+    /// 對沖時另一層先達標，此層被取消
+    Cancelled,
+    /// 超過本次搜尋的時限（`deadline`）
+    Deadline,
+}
+
+/// 單一層級的執行紀錄
+#[derive(Debug, Clone, PartialEq)]
+pub struct TierAttempt {
+    pub tier: RetrievalTier,
+    /// `SearchProvider::name`
+    pub provider: String,
+    /// 搜尋請求的耗時；未執行時為零
+    pub latency: Duration,
+    pub result_count: usize,
+    /// 此層結果的置信度；未執行或被取消時為 None
+    pub confidence: Option<f32>,
+    /// 此層計入的成本
+    pub cost: f32,
+    pub skipped_reason: Option<SkipReason>,
+}
+
+impl TierAttempt {
+    fn completed(index: usize, tier: &Tier, latency: Duration, result_count: usize, confidence: f32) -> Self {
+        Self {
+            tier: RetrievalTier::at(index),
+            provider: tier.provider.name().to_string(),
+            latency,
+            result_count,
+            confidence: Some(confidence),
+            cost: tier.cost,
+            skipped_reason: None,
+        }
+    }
+
+    fn skipped(index: usize, tier: &Tier, reason: SkipReason) -> Self {
+        Self {
+            tier: RetrievalTier::at(index),
+            provider: tier.provider.name().to_string(),
+            latency: Duration::ZERO,
+            result_count: 0,
+            confidence: None,
+            cost: 0.0,
+            skipped_reason: Some(reason),
+        }
+    }
+}
+
+/// 對沖前兩層的結果
+enum Hedged {
+    /// L2 先完成且置信度達標，L1 已取消
+    Speculative(TieredResult),
+    /// L1 的結果；`next` 為已完成（已計費）的 L2 結果與耗時
+    Primary {
+        results: Vec<SearchResult>,
+        confidence: f32,
+        latency: Duration,
+        next: Option<(Vec<SearchResult>, Duration)>,
+        /// 已送出（已計費）的 L2 請求因 L1 達標而被取消
+        cancelled: bool,
+        /// 等待 L2 時超過時限
+        timed_out: bool,
+    },
+    /// 超過時限時兩層都未完成
+    TimedOut,
+}
+
+/// 檢索鏈中的一層
+pub struct Tier {
+    pub provider: Box<dyn SearchProvider>,
+    /// 此層置信度達到閾值即停止，不再升級；最後一層不檢查
+    pub threshold: f32,
+    /// 單次成本估計（美元）
+    pub cost: f32,
+}
+
+impl Tier {
+    /// 以提供者自己的成本估計建立
+    pub fn new(provider: Box<dyn SearchProvider>, threshold: f32) -> Self {
+        let cost = provider.cost();
+        Self {
+            provider,
+            threshold,
+            cost,
+        }
+    }
 }
 
 /// 階梯式檢索引擎
 pub struct TieredRetrieval {
-    duckduckgo: DuckDuckGoClient,
-    exa: Option<ExaClient>,
-    tavily: Option<TavilyClient>,
+    tiers: Vec<Tier>,
     confidence_calc: ConfidenceCalculator,
     config: TieredConfig,
+    costs: Option<Arc<CostTracker>>,
+    filter: Option<Arc<DomainFilter>>,
+    spam: Option<Arc<SpamDetector>>,
 }
 
 impl TieredRetrieval {
-    /// 建立新的階梯式檢索引擎
+    /// 建立新的階梯式檢索引擎（只有 DuckDuckGo 一層，以 `with_exa` / `with_tavily` 延伸）
     pub fn new(config: TieredConfig) -> Self {
-        Self {
-            duckduckgo: DuckDuckGoClient::new(),
-            exa: None,
-            tavily: None,
+        let mut retrieval = Self {
+            tiers: Vec::new(),
             confidence_calc: ConfidenceCalculator::new(),
             config,
-        }
+            costs: None,
+            filter: None,
+            spam: None,
+        };
+        let limiter = RateLimiter::per_second(DUCKDUCKGO_REQUESTS_PER_SECOND);
+        retrieval.push_provider(Box::new(DuckDuckGoClient::new().with_rate_limiter(Arc::new(limiter))));
+        retrieval
     }
 
     /// 使用預設配置建立
@@ -70,108 +359,635 @@ impl TieredRetrieval {
         Self::new(TieredConfig::default())
     }
 
-    /// 設定 Exa 客戶端
+    /// 由 `BoseConfig` 建立：DuckDuckGo 加上有金鑰的 Exa / Tavily，順序與閾值依設定檔的 `[tiers]`
+    ///
+    /// `engines.<名稱>.enabled = false` 的引擎不加入，`rate_limit_rps` 覆寫該引擎的初始速率。
+    pub fn from_config(config: &BoseConfig) -> Result<Self, SearchError> {
+        let enabled = |engine: SearchEngine| config.engine_enabled(engine.name());
+        let limiter = |engine: SearchEngine, default| {
+            Arc::new(RateLimiter::per_second(config.engine_rps(engine.name()).unwrap_or(default)))
+        };
+        let mut providers: Vec<Box<dyn SearchProvider>> = Vec::new();
+        if enabled(SearchEngine::DuckDuckGo) {
+            let limiter = limiter(SearchEngine::DuckDuckGo, DUCKDUCKGO_REQUESTS_PER_SECOND);
+            providers.push(Box::new(DuckDuckGoClient::new().with_rate_limiter(limiter)));
+        }
+        // 逗號分隔的多把金鑰整串交給客戶端，由金鑰池輪替
+        if let Some(keys) = config.exa_api_key.as_ref().filter(|_| enabled(SearchEngine::Exa)) {
+            let limiter = limiter(SearchEngine::Exa, EXA_REQUESTS_PER_SECOND);
+            providers.push(Box::new(ExaClient::new(keys.expose()).with_rate_limiter(limiter)));
+        }
+        if let Some(keys) = config.tavily_api_key.as_ref().filter(|_| enabled(SearchEngine::Tavily)) {
+            let limiter = limiter(SearchEngine::Tavily, TAVILY_REQUESTS_PER_SECOND);
+            providers.push(Box::new(TavilyClient::new(keys.expose()).with_rate_limiter(limiter)));
+        }
+        Self::from_providers(TieredConfig::from(&config.tiers), providers)
+    }
+
+    /// 依 `config.tiers` 的順序組出檢索鏈；`tiers` 空白時依 `providers` 的順序
+    ///
+    /// 設定中的提供者不存在、重複或檢索鏈為空時回傳 `ConfigError`。
+    pub fn from_providers(config: TieredConfig, providers: Vec<Box<dyn SearchProvider>>) -> Result<Self, SearchError> {
+        let mut retrieval = Self {
+            tiers: Vec::new(),
+            confidence_calc: ConfidenceCalculator::new(),
+            config,
+            costs: None,
+            filter: None,
+            spam: None,
+        };
+
+        if retrieval.config.tiers.is_empty() {
+            for provider in providers {
+                retrieval.push_provider(provider);
+            }
+        } else {
+            let mut by_name: HashMap<String, Box<dyn SearchProvider>> =
+                providers.into_iter().map(|p| (p.name().to_string(), p)).collect();
+            for (index, spec) in retrieval.config.tiers.iter().enumerate() {
+                let provider = by_name.remove(&spec.provider).ok_or_else(|| {
+                    SearchError::ConfigError(format!(
+                        "tiers[{}]: 找不到或重複的搜尋提供者 `{}`",
+                        index, spec.provider
+                    ))
+                })?;
+                let mut tier = Tier::new(provider, spec.threshold.unwrap_or(retrieval.config.default_threshold(index)));
+                if let Some(cost) = spec.cost {
+                    tier.cost = cost;
+                }
+                retrieval.tiers.push(tier);
+            }
+        }
+
+        if retrieval.tiers.is_empty() {
+            return Err(SearchError::ConfigError("檢索鏈至少需要一層".to_string()));
+        }
+        Ok(retrieval)
+    }
+
+    fn push_provider(&mut self, provider: Box<dyn SearchProvider>) {
+        let threshold = self.config.default_threshold(self.tiers.len());
+        self.tiers.push(Tier::new(provider, threshold));
+    }
+
+    /// 在檢索鏈末端加入一層
+    pub fn with_tier(mut self, tier: Tier) -> Self {
+        self.tiers.push(tier);
+        self
+    }
+
+    /// 目前的檢索鏈
+    pub fn tiers(&self) -> &[Tier] {
+        &self.tiers
+    }
+
+    /// 在檢索鏈末端加入 Exa
     pub fn with_exa(mut self, api_key: &str) -> Self {
-        self.exa = Some(ExaClient::new(api_key));
+        let limiter = RateLimiter::per_second(EXA_REQUESTS_PER_SECOND);
+        self.push_provider(Box::new(ExaClient::new(api_key).with_rate_limiter(Arc::new(limiter))));
         self
     }
 
-    /// 設定 Tavily 客戶端
+    /// 在檢索鏈末端加入 Tavily（對上一層最相關的網址做深度提取）
     pub fn with_tavily(mut self, api_key: &str) -> Self {
-        self.tavily = Some(TavilyClient::new(api_key));
+        let limiter = RateLimiter::per_second(TAVILY_REQUESTS_PER_SECOND);
+        self.push_provider(Box::new(TavilyClient::new(api_key).with_rate_limiter(Arc::new(limiter))));
+        self
+    }
+
+    /// 以嵌入相似度評估置信度（取代語義密度的字面估算）
+    pub fn with_embedder(mut self, embedder: Arc<dyn Embedder>) -> Self {
+        self.confidence_calc = std::mem::take(&mut self.confidence_calc).with_embedder(embedder);
+        self
+    }
+
+    /// 記錄付費層級的花費；預算用盡時不再升級到付費層級
+    pub fn with_cost_tracker(mut self, costs: Arc<CostTracker>) -> Self {
+        self.costs = Some(costs);
+        self
+    }
+
+    /// 以網域封鎖 / 允許清單過濾每一層的結果（在評估置信度之前）
+    pub fn with_filter(mut self, filter: Arc<DomainFilter>) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// 對每一層的結果套用內容農場偵測（在評估置信度之前）
+    pub fn with_spam_detector(mut self, spam: Arc<SpamDetector>) -> Self {
+        self.spam = Some(spam);
         self
     }
 
+    /// 以某一層搜尋並套用網域過濾與內容農場偵測
+    async fn search_tier(
+        &self,
+        tier: &Tier,
+        query: &str,
+        previous: &[SearchResult],
+        num_results: usize,
+    ) -> Result<Vec<SearchResult>, SearchError> {
+        let mut results = tier.provider.search(query, previous, num_results).await?;
+        if let Some(filter) = &self.filter {
+            filter.apply(query, &mut results);
+        }
+        if let Some(spam) = &self.spam {
+            spam.apply(&mut results);
+        }
+        Ok(results)
+    }
+
+    /// 花費帳本（查詢目前花費與預算）
+    pub fn cost_tracker(&self) -> Option<&Arc<CostTracker>> {
+        self.costs.as_ref()
+    }
+
+    fn within_budget(&self, tier: &Tier) -> bool {
+        match (&self.costs, tier.provider.engine()) {
+            (Some(costs), Some(engine)) => costs.allows(engine, tier.cost),
+            _ => true,
+        }
+    }
+
+    /// 累計本次花費並記入帳本
+    fn charge(&self, tier: &Tier, spent: &mut f32) {
+        *spent += tier.cost;
+        let (Some(costs), Some(engine)) = (&self.costs, tier.provider.engine()) else {
+            return;
+        };
+        if tier.cost <= 0.0 {
+            return;
+        }
+        if let Err(e) = costs.record(engine, tier.cost) {
+            log::warn!("⚠️  無法記錄花費: {}", e);
+        }
+    }
+
+    /// 可以對沖時回傳延遲：第二層不依賴第一層的結果，且預算允許直接走到第二層
+    fn hedge_delay(&self, limits: &EffectiveLimits) -> Option<Duration> {
+        let delay = Duration::from_millis(self.config.hedge_after_ms?);
+        let [first, second, ..] = self.tiers.as_slice() else {
+            return None;
+        };
+        let affordable = limits.allows(first.cost + second.cost) && self.within_budget(second);
+        (second.provider.independent() && affordable).then_some(delay)
+    }
+
+    /// 同時執行前兩層：L2 在 `delay` 後以原查詢推測性啟動，先達標者勝出，另一個被取消
+    ///
+    /// 付費服務對收到的請求計費，因此已送出但因 L1 達標而被取消的 L2 仍計入花費。
+    /// 超過 `deadline` 時丟棄仍在進行的請求。
+    async fn hedged(
+        &self,
+        query: &str,
+        delay: Duration,
+        deadline: Option<Instant>,
+        limits: &EffectiveLimits,
+        signals: ConfidenceSignals,
+        spent: &mut f32,
+    ) -> Result<Hedged, SearchError> {
+        let (first, second) = (&self.tiers[0], &self.tiers[1]);
+        let num_results = self.config.max_results_per_tier;
+        let started = Instant::now();
+        let l2_sent = Cell::new(false);
+        let primary = async {
+            let results = self.search_tier(first, query, &[], num_results).await?;
+            let latency = started.elapsed();
+            let confidence = self.tier_confidence(query, first, &results, &[], signals).await;
+            Ok::<_, SearchError>((results, confidence, latency))
+        };
+        let speculative = async {
+            tokio::time::sleep(delay).await;
+            log::info!("🏁 L1 尚未完成，推測性啟動 L2: {}", second.provider.name());
+            l2_sent.set(true);
+            let launched = Instant::now();
+            let results = self.search_tier(second, query, &[], num_results).await?;
+            Ok::<_, SearchError>((results, launched.elapsed()))
+        };
+        let expired = async {
+            match deadline {
+                Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::pin!(primary, speculative);
+
+        tokio::select! {
+            _ = expired => {
+                log::info!("⏱️  已超過時限，前兩層都未完成");
+                Ok(Hedged::TimedOut)
+            }
+            outcome = &mut primary => {
+                let (results, confidence, latency) = outcome?;
+                self.charge(first, spent);
+                if confidence >= limits.threshold(0) {
+                    log::info!("📊 L1 置信度 {:.2} 已達標，取消 L2", confidence);
+                    let cancelled = l2_sent.get();
+                    if cancelled {
+                        self.charge(second, spent);
+                    }
+                    return Ok(Hedged::Primary { results, confidence, latency, next: None, cancelled, timed_out: false });
+                }
+                // 推測性的 L2 失敗時交回一般流程重試
+                let (next, timed_out) = match until(deadline, speculative).await {
+                    Some(Ok(next)) => {
+                        self.charge(second, spent);
+                        (Some(next), false)
+                    }
+                    Some(Err(e)) => {
+                        log::warn!("⚠️  推測性 L2 失敗: {}", e);
+                        (None, false)
+                    }
+                    None => (None, true),
+                };
+                Ok(Hedged::Primary { results, confidence, latency, next, cancelled: false, timed_out })
+            }
+            outcome = &mut speculative => {
+                let next = match outcome {
+                    Ok((l2_results, l2_latency)) => {
+                        self.charge(second, spent);
+                        let confidence = self.tier_confidence(query, second, &l2_results, &[], signals).await;
+                        if confidence >= limits.threshold(1) {
+                            log::info!("📊 推測性 L2 置信度 {:.2} 已達標，取消 L1", confidence);
+                            let l1_elapsed = started.elapsed();
+                            return Ok(Hedged::Speculative(
+                                self.speculative_result(l2_results, l2_latency, confidence, l1_elapsed, *spent, false),
+                            ));
+                        }
+                        Some((l2_results, l2_latency, confidence))
+                    }
+                    Err(e) => {
+                        log::warn!("⚠️  推測性 L2 失敗: {}", e);
+                        None
+                    }
+                };
+                match until(deadline, primary).await {
+                    Some(outcome) => {
+                        let (results, confidence, latency) = outcome?;
+                        self.charge(first, spent);
+                        let next = next.map(|(results, latency, _)| (results, latency));
+                        Ok(Hedged::Primary { results, confidence, latency, next, cancelled: false, timed_out: false })
+                    }
+                    // L1 逾時：未達標的 L2 結果即為部分結果
+                    None => Ok(match next {
+                        Some((results, latency, confidence)) => Hedged::Speculative(
+                            self.speculative_result(results, latency, confidence, started.elapsed(), *spent, true),
+                        ),
+                        None => Hedged::TimedOut,
+                    }),
+                }
+            }
+        }
+    }
+
+    /// 只有推測性 L2 完成時的結果；L1 因 L2 達標被取消，或因逾時被丟棄
+    fn speculative_result(
+        &self,
+        results: Vec<SearchResult>,
+        latency: Duration,
+        confidence: f32,
+        l1_elapsed: Duration,
+        spent: f32,
+        timed_out: bool,
+    ) -> TieredResult {
+        let (l1_reason, rest) = if timed_out {
+            (SkipReason::Deadline, SkipReason::Deadline)
+        } else {
+            (SkipReason::Cancelled, SkipReason::ThresholdMet)
+        };
+        let second = &self.tiers[1];
+        let mut tier_trace = vec![
+            TierAttempt {
+                latency: l1_elapsed,
+                ..TierAttempt::skipped(0, &self.tiers[0], l1_reason)
+            },
+            TierAttempt::completed(1, second, latency, results.len(), confidence),
+        ];
+        tier_trace.extend(self.skipped_from(2, rest));
+        TieredResult {
+            results,
+            tier_used: RetrievalTier::L2,
+            provider: second.provider.name().to_string(),
+            confidence,
+            cost_estimate: spent,
+            budget_stopped: false,
+            timed_out,
+            tier_trace,
+        }
+    }
+
+    /// 升級到 `next` 會超過花費上限或預算時的原因
+    fn budget_stop(&self, limits: &EffectiveLimits, spent: f32, next: &Tier) -> Option<SkipReason> {
+        if !limits.allows(spent + next.cost) {
+            Some(SkipReason::MaxCost)
+        } else if !self.within_budget(next) {
+            Some(SkipReason::Budget)
+        } else {
+            None
+        }
+    }
+
+    /// 從第 `index` 層起未執行的各層
+    fn skipped_from(&self, index: usize, reason: SkipReason) -> impl Iterator<Item = TierAttempt> + '_ {
+        self.tiers
+            .iter()
+            .enumerate()
+            .skip(index)
+            .map(move |(i, tier)| TierAttempt::skipped(i, tier, reason))
+    }
+
     /// 執行階梯式檢索
     pub async fn search(&self, query: &str) -> Result<TieredResult, SearchError> {
-        // L1: DuckDuckGo (免費)
-        log::info!("🔍 L1: 使用 DuckDuckGo 搜尋...");
-        let l1_results = self.duckduckgo
-            .search(query, self.config.max_results_per_tier)
-            .await?;
-
-        let l1_confidence = self.confidence_calc.calculate(query, &l1_results);
-        log::info!("📊 L1 置信度: {:.2}", l1_confidence);
-
-        if l1_confidence >= self.config.l1_threshold {
-            return Ok(TieredResult {
-                results: l1_results,
-                tier_used: RetrievalTier::L1,
-                confidence: l1_confidence,
-                cost_estimate: 0.0,  // 免費
-            });
-        }
-
-        // L2: Exa (付費，精準語義搜尋)
-        if let Some(ref exa) = self.exa {
-            log::info!("🔍 L2: 使用 Exa 搜尋...");
-
-            // 使用 L1 結果提取關鍵字優化查詢
-            let refined_query = self.refine_query(query, &l1_results);
-            let l2_results = exa
-                .search(&refined_query, self.config.max_results_per_tier)
-                .await?;
-
-            let l2_confidence = self.confidence_calc.calculate(query, &l2_results);
-            log::info!("📊 L2 置信度: {:.2}", l2_confidence);
-
-            if l2_confidence >= self.config.l2_threshold {
-                return Ok(TieredResult {
-                    results: l2_results,
-                    tier_used: RetrievalTier::L2,
-                    confidence: l2_confidence,
-                    cost_estimate: 0.005,  // ~$0.005/次
-                });
+        self.search_with_options(query, &SearchOptions::default()).await
+    }
+
+    /// 在時限內執行階梯式檢索；逾時回傳部分結果並標記 `timed_out`
+    pub async fn search_with_deadline(&self, query: &str, deadline: Duration) -> Result<TieredResult, SearchError> {
+        self.search_with_options(query, &SearchOptions {
+            deadline: Some(deadline),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// 合併各層閾值、品質預設與單次覆寫
+    ///
+    /// 品質預設與 `l1_threshold` / `l2_threshold` 覆寫作用於前兩層，其餘層沿用各自的閾值。
+    fn resolve_limits(&self, options: &SearchOptions) -> EffectiveLimits {
+        let mut thresholds: Vec<f32> = self.tiers.iter().map(|tier| tier.threshold).collect();
+        let mut max_cost = None;
+        if let Some(quality) = options.quality {
+            let (l1, l2, preset_cost) = quality.preset();
+            for (threshold, preset) in thresholds.iter_mut().zip([l1, l2]) {
+                *threshold = preset;
+            }
+            max_cost = preset_cost;
+        }
+        for (threshold, value) in thresholds.iter_mut().zip([options.l1_threshold, options.l2_threshold]) {
+            if let Some(value) = value {
+                *threshold = value;
             }
+        }
+
+        // 置信度永遠達不到無限大的閾值，因此一定會升級
+        if let Some(min_tier) = options.min_tier {
+            for threshold in thresholds.iter_mut().take(min_tier.index()) {
+                *threshold = f32::INFINITY;
+            }
+        }
+        // 設定的上限是硬上限，單次覆寫只能更嚴格
+        let max_cost = match (options.max_cost.or(max_cost), self.config.max_cost) {
+            (Some(requested), Some(cap)) => Some(requested.min(cap)),
+            (requested, cap) => requested.or(cap),
+        };
+        EffectiveLimits { thresholds, max_cost }
+    }
 
-            // L3: Tavily (付費，深度內容提取)
-            if let Some(ref tavily) = self.tavily {
-                log::info!("🔍 L3: 使用 Tavily 深度提取...");
-
-                // 只對最相關的 URL 進行深度提取
-                let top_urls: Vec<&str> = l2_results
-                    .iter()
-                    .take(3)
-                    .map(|r| r.url.as_str())
-                    .collect();
-
-                let l3_results = tavily
-                    .extract_content(&top_urls)
-                    .await?;
-
-                let l3_confidence = self.confidence_calc.calculate(query, &l3_results);
-                log::info!("📊 L3 置信度: {:.2}", l3_confidence);
-
-                return Ok(TieredResult {
-                    results: l3_results,
-                    tier_used: RetrievalTier::L3,
-                    confidence: l3_confidence,
-                    cost_estimate: 0.015,  // ~$0.015/次
+    /// 以單次覆寫選項執行階梯式檢索
+    pub async fn search_with_options(
+        &self,
+        query: &str,
+        options: &SearchOptions,
+    ) -> Result<TieredResult, SearchError> {
+        let limits = self.resolve_limits(options);
+        let signals = ConfidenceSignals {
+            max_age_days: self.confidence_calc.freshness_window(options.time_range, options.category),
+            engine_agreement: None,
+            semantic_similarity: None,
+        };
+        let deadline = options.deadline.map(|timeout| Instant::now() + timeout);
+
+        // 已執行各層的 (名稱, 結果)，供後續層級佐證；置信度與之同序
+        let mut history: Vec<(&str, Vec<SearchResult>)> = Vec::new();
+        let mut confidences: Vec<f32> = Vec::new();
+        let hedge = self.hedge_delay(&limits);
+        // 對沖時已完成並計費的下一層結果
+        let mut prefetched: Option<(Vec<SearchResult>, Duration)> = None;
+        let mut hedge_cancelled = false;
+        let mut hedge_timed_out = false;
+        let mut tier_trace: Vec<TierAttempt> = Vec::new();
+        let mut spent = 0.0;
+        for (index, tier) in self.tiers.iter().enumerate() {
+            let level = RetrievalTier::at(index);
+            let name = tier.provider.name();
+            let started = Instant::now();
+
+            let outcome = match (index, hedge) {
+                (0, Some(delay)) => match self.hedged(query, delay, deadline, &limits, signals, &mut spent).await? {
+                    Hedged::Speculative(result) => return Ok(result),
+                    Hedged::Primary { results, confidence, latency, next, cancelled, timed_out } => {
+                        prefetched = next;
+                        hedge_cancelled = cancelled;
+                        hedge_timed_out = timed_out;
+                        Some((results, Some(confidence), latency))
+                    }
+                    Hedged::TimedOut => None,
+                },
+                _ => match prefetched.take() {
+                    Some((results, latency)) => Some((results, None, latency)),
+                    None => {
+                        // 後段層級以前一層結果的關鍵字優化查詢
+                        let previous = history.last().map(|(_, results)| results.as_slice()).unwrap_or(&[]);
+                        let tier_query = if index == 0 {
+                            query.to_string()
+                        } else {
+                            self.refine_query(query, previous)
+                        };
+                        log::info!("🔍 {:?}: 使用 {} 搜尋...", level, name);
+                        let search = self.search_tier(tier, &tier_query, previous, self.config.max_results_per_tier);
+                        match until(deadline, search).await {
+                            Some(results) => {
+                                let results = results?;
+                                self.charge(tier, &mut spent);
+                                Some((results, None, started.elapsed()))
+                            }
+                            None => None,
+                        }
+                    }
+                },
+            };
+
+            // 逾時：丟棄這一層，回傳時限內完成的層級
+            let Some((results, confidence, latency)) = outcome else {
+                log::info!("⏱️  已超過時限，停止於 {:?}（已花費 ${:.3}）", level, spent);
+                tier_trace.push(TierAttempt {
+                    latency: started.elapsed(),
+                    ..TierAttempt::skipped(index, tier, SkipReason::Deadline)
                 });
+                tier_trace.extend(self.skipped_from(index + 1, SkipReason::Deadline));
+                return Ok(self.conclude(history, confidences, tier_trace, spent, Some(SkipReason::Deadline)));
+            };
+
+            let result_count = results.len();
+            history.push((name, results));
+            let confidence = match confidence {
+                Some(confidence) => confidence,
+                None if self.config.merge_tiers => self.union_confidence(query, &history, signals).await,
+                None => {
+                    let (earlier, current) = history.split_at(index);
+                    self.tier_confidence(query, tier, &current[0].1, earlier, signals).await
+                }
+            };
+            confidences.push(confidence);
+            tier_trace.push(TierAttempt::completed(index, tier, latency, result_count, confidence));
+            log::info!("📊 {:?} 置信度: {:.2}", level, confidence);
+
+            // 下一層已預先取得時不必再檢查預算
+            let next = self.tiers.get(index + 1);
+            let stop = if confidence >= limits.threshold(index) {
+                Some(SkipReason::ThresholdMet)
+            } else if hedge_timed_out {
+                Some(SkipReason::Deadline)
+            } else if prefetched.is_some() {
+                None
+            } else {
+                next.and_then(|next| self.budget_stop(&limits, spent, next))
+            };
+            if next.is_some() && stop.is_none() {
+                continue;
             }
+            if let Some(reason) = stop {
+                tier_trace.extend(self.skipped_from(index + 1, reason));
+            }
+            // 對沖時已送出的 L2 是被取消，而不是沒有執行；請求已送出，照樣計費
+            if let Some(attempt) = tier_trace.get_mut(1).filter(|_| hedge_cancelled) {
+                attempt.skipped_reason = Some(SkipReason::Cancelled);
+                attempt.cost = self.tiers[1].cost;
+            }
+            return Ok(self.conclude(history, confidences, tier_trace, spent, stop));
+        }
 
-            // 沒有 Tavily，返回 L2 結果
-            return Ok(TieredResult {
-                results: l2_results,
-                tier_used: RetrievalTier::L2,
-                confidence: l2_confidence,
-                cost_estimate: 0.005,
-            });
-        }
-
-        // 沒有 Exa，返回 L1 結果
-        Ok(TieredResult {
-            results: l1_results,
-            tier_used: RetrievalTier::L1,
-            confidence: l1_confidence,
-            cost_estimate: 0.0,
-        })
+        Err(SearchError::ConfigError("檢索鏈至少需要一層".to_string()))
     }
 
-    /// 使用 L1 結果優化查詢
-    fn refine_query(&self, original: &str, l1_results: &[SearchResult]) -> String {
-        // 從 L1 結果提取關鍵字
-        let keywords: Vec<&str> = l1_results
+    /// 組出最終結果：合併模式融合已執行的各層，否則取最後一層
+    ///
+    /// `stop` 為其餘層級未執行的原因；因預算或時限提前停止時，回傳目前為止置信度最高的一層
+    /// （同分取較後層），一層都沒完成時回傳空結果。
+    fn conclude(
+        &self,
+        mut history: Vec<(&str, Vec<SearchResult>)>,
+        confidences: Vec<f32>,
+        tier_trace: Vec<TierAttempt>,
+        spent: f32,
+        stop: Option<SkipReason>,
+    ) -> TieredResult {
+        let budget_stopped = matches!(stop, Some(SkipReason::MaxCost | SkipReason::Budget));
+        let timed_out = stop == Some(SkipReason::Deadline);
+        let Some(last) = history.len().checked_sub(1) else {
+            return TieredResult {
+                results: Vec::new(),
+                tier_used: RetrievalTier::L1,
+                provider: self.tiers[0].provider.name().to_string(),
+                confidence: 0.0,
+                cost_estimate: spent,
+                budget_stopped,
+                timed_out,
+                tier_trace,
+            };
+        };
+
+        if self.config.merge_tiers {
+            let names: Vec<&str> = history.iter().map(|(name, _)| *name).collect();
+            let lists: Vec<&[SearchResult]> = history.iter().map(|(_, results)| results.as_slice()).collect();
+            return TieredResult {
+                results: fuse_tiers(&lists, RRF_K),
+                tier_used: RetrievalTier::at(last),
+                provider: names.join("+"),
+                confidence: confidences[last],
+                cost_estimate: spent,
+                budget_stopped,
+                timed_out,
+                tier_trace,
+            };
+        }
+
+        let best = if budget_stopped || timed_out {
+            if budget_stopped {
+                log::info!("💰 已達花費上限，停止於 {:?}（已花費 ${:.3}）", RetrievalTier::at(last), spent);
+            }
+            confidences
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(b.1))
+                .map_or(last, |(i, _)| i)
+        } else {
+            last
+        };
+        let (provider, results) = history.swap_remove(best);
+        TieredResult {
+            results,
+            tier_used: RetrievalTier::at(best),
+            provider: provider.to_string(),
+            confidence: confidences[best],
+            cost_estimate: spent,
+            budget_stopped,
+            timed_out,
+            tier_trace,
+        }
+    }
+
+    /// 合併模式的置信度：對各層結果的聯集計算，引擎一致度只計入獨立的層級
+    async fn union_confidence(
+        &self,
+        query: &str,
+        history: &[(&str, Vec<SearchResult>)],
+        signals: ConfidenceSignals,
+    ) -> f32 {
+        let lists: Vec<&[SearchResult]> = history.iter().map(|(_, results)| results.as_slice()).collect();
+        let union = fuse_tiers(&lists, RRF_K);
+        let independent: Vec<(&str, &[SearchResult])> = history
+            .iter()
+            .zip(&self.tiers)
+            .filter(|(_, tier)| tier.provider.independent())
+            .map(|((name, results), _)| (*name, results.as_slice()))
+            .collect();
+        let signals = ConfidenceSignals {
+            engine_agreement: engine_agreement(&independent),
+            semantic_similarity: self.confidence_calc.semantic_similarity(query, &union).await,
+            ..signals
+        };
+        self.confidence_calc.calculate_with(query, &union, &signals)
+    }
+
+    /// 單層置信度：獨立來源與前面各層比對佐證與引擎一致度
+    async fn tier_confidence(
+        &self,
+        query: &str,
+        tier: &Tier,
+        results: &[SearchResult],
+        history: &[(&str, Vec<SearchResult>)],
+        signals: ConfidenceSignals,
+    ) -> f32 {
+        let signals = ConfidenceSignals {
+            semantic_similarity: self.confidence_calc.semantic_similarity(query, results).await,
+            ..signals
+        };
+        if history.is_empty() || !tier.provider.independent() {
+            return self.confidence_calc.calculate_with(query, results, &signals);
+        }
+
+        // 前面各層也找到的網址、網域或說法視為佐證
+        let name = tier.provider.name();
+        let sources: Vec<(&str, &[SearchResult])> = std::iter::once((name, results))
+            .chain(history.iter().map(|(source, found)| (*source, found.as_slice())))
+            .collect();
+        let corroborated: Vec<_> = corroborate(&sources)
+            .into_iter()
+            .filter(|r| r.source == name)
+            .collect();
+        let signals = ConfidenceSignals {
+            engine_agreement: engine_agreement(&sources),
+            ..signals
+        };
+        self.confidence_calc
+            .calculate_corroborated(query, &corroborated, sources.len(), &signals)
+    }
+
+    /// 使用前一層的結果優化查詢
+    fn refine_query(&self, original: &str, previous: &[SearchResult]) -> String {
+        // 從前一層結果提取關鍵字
+        let keywords: Vec<&str> = previous
             .iter()
             .filter_map(|r| r.snippet.as_deref())
             .flat_map(|s| s.split_whitespace())
@@ -187,9 +1003,136 @@ impl TieredRetrieval {
     }
 }
 
+/// 在時限內等待 `future`；逾時即丟棄它（連帶中止進行中的 HTTP 請求）並回傳 None
+async fn until<F: Future>(deadline: Option<Instant>, future: F) -> Option<F::Output> {
+    match deadline {
+        Some(deadline) => tokio::time::timeout_at(deadline.into(), future).await.ok(),
+        None => Some(future.await),
+    }
+}
+
+/// 以 RRF 融合各層結果：依網址去重，分數為 `Σ 1 / (k + rank)`
+///
+/// 重複的結果保留最先出現的版本，缺少的摘要、全文與日期由其他層補上
+/// （例如 Tavily 提取的全文補到 Exa 的結果上）。
+pub(crate) fn fuse_tiers(lists: &[&[SearchResult]], k: f64) -> Vec<SearchResult> {
+    let mut fused: Vec<(SearchResult, f64)> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for list in lists {
+        for (rank, result) in list.iter().enumerate() {
+            let contribution = 1.0 / (k + rank as f64 + 1.0);
+            match index.get(&url_key(&result.url)) {
+                Some(&i) => {
+                    let (existing, score) = &mut fused[i];
+                    *score += contribution;
+                    if existing.snippet.is_none() {
+                        existing.snippet = result.snippet.clone();
+                    }
+                    if existing.content.is_none() {
+                        existing.content = result.content.clone();
+                    }
+                    if existing.published_date.is_none() {
+                        existing.published_date = result.published_date.clone();
+                    }
+                }
+                None => {
+                    index.insert(url_key(&result.url), fused.len());
+                    fused.push((result.clone(), contribution));
+                }
+            }
+        }
+    }
+
+    // sort_by 為穩定排序，同分保留首次出現的順序
+    fused.sort_by(|a, b| b.1.total_cmp(&a.1));
+    fused.into_iter().map(|(result, _)| result).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cost::TAVILY_COST;
+    use crate::provider::ProviderFuture;
+
+    /// 升級到 Tavily 的累計成本
+    const L3_COST: f32 = EXA_COST + TAVILY_COST;
+
+    /// 預設的 DuckDuckGo → Exa → Tavily 檢索鏈（建構時不會連線）
+    fn full_chain() -> TieredRetrieval {
+        TieredRetrieval::with_defaults().with_exa("test").with_tavily("test")
+    }
+
+    /// 固定回傳同一組結果的提供者
+    struct Canned {
+        name: &'static str,
+        results: Vec<SearchResult>,
+        cost: f32,
+        delay: Duration,
+        engine: Option<SearchEngine>,
+    }
+
+    impl Canned {
+        fn boxed(name: &'static str, urls: &[&str], cost: f32) -> Box<dyn SearchProvider> {
+            Self::slow(name, urls, cost, 0)
+        }
+
+        /// 延遲 `delay_ms` 毫秒才回傳
+        fn slow(name: &'static str, urls: &[&str], cost: f32, delay_ms: u64) -> Box<dyn SearchProvider> {
+            Box::new(Self::new(name, urls, cost, delay_ms))
+        }
+
+        /// 以 `engine` 計費的延遲提供者，讓花費記入 `CostTracker`
+        fn billed(name: &'static str, urls: &[&str], engine: SearchEngine, delay_ms: u64) -> Box<dyn SearchProvider> {
+            Box::new(Self {
+                engine: Some(engine),
+                ..Self::new(name, urls, crate::cost::estimate(engine), delay_ms)
+            })
+        }
+
+        fn new(name: &'static str, urls: &[&str], cost: f32, delay_ms: u64) -> Self {
+            let results = urls
+                .iter()
+                .map(|url| SearchResult {
+                    title: "Rust async runtime".to_string(),
+                    url: url.to_string(),
+                    snippet: Some("Rust async runtime overview".to_string()),
+                    content: None,
+                    published_date: None,
+                    metadata: Default::default(),
+                })
+                .collect();
+            Self {
+                name,
+                results,
+                cost,
+                delay: Duration::from_millis(delay_ms),
+                engine: None,
+            }
+        }
+    }
+
+    impl SearchProvider for Canned {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn search<'a>(&'a self, _query: &'a str, _previous: &'a [SearchResult], _num: usize) -> ProviderFuture<'a> {
+            let results = self.results.clone();
+            let delay = self.delay;
+            Box::pin(async move {
+                tokio::time::sleep(delay).await;
+                Ok(results)
+            })
+        }
+
+        fn cost(&self) -> f32 {
+            self.cost
+        }
+
+        fn engine(&self) -> Option<SearchEngine> {
+            self.engine
+        }
+    }
 
     #[test]
     fn test_tiered_config_default() {
@@ -205,6 +1148,486 @@ mod tests {
         assert_ne!(RetrievalTier::L1, RetrievalTier::L2);
     }
 
+    #[test]
+    fn test_quality_presets() {
+        assert_eq!(SearchQuality::default(), SearchQuality::Balanced);
+        assert_eq!(SearchQuality::Fast.preset(), (0.60, 0.70, Some(L2_COST)));
+        assert_eq!(SearchQuality::Thorough.preset(), (0.90, 0.95, None));
+    }
+
+    #[test]
+    fn test_resolve_limits_defaults_to_config() {
+        let retrieval = TieredRetrieval::new(TieredConfig {
+            l1_threshold: 0.5,
+            l2_threshold: 0.6,
+            ..Default::default()
+        })
+        .with_exa("test")
+        .with_tavily("test");
+        let limits = retrieval.resolve_limits(&SearchOptions::default());
+        assert_eq!(limits.thresholds, vec![0.5, 0.6, 0.6]);
+        assert!(limits.max_cost.is_none());
+    }
+
+    #[test]
+    fn test_resolve_limits_quality_and_overrides() {
+        let retrieval = full_chain();
+        let options = SearchOptions {
+            quality: Some(SearchQuality::Fast),
+            l2_threshold: Some(0.99),
+            ..Default::default()
+        };
+        let limits = retrieval.resolve_limits(&options);
+        assert_eq!(limits.threshold(0), 0.60);
+        assert_eq!(limits.threshold(1), 0.99);
+        assert_eq!(limits.max_cost, Some(L2_COST));
+        assert!(limits.allows(L2_COST));
+        assert!(!limits.allows(L3_COST));
+    }
+
+    #[test]
+    fn test_max_cost_zero_blocks_paid_tiers() {
+        let retrieval = TieredRetrieval::with_defaults();
+        let options = SearchOptions {
+            max_cost: Some(0.0),
+            ..SearchOptions::with_quality(SearchQuality::Thorough)
+        };
+        let limits = retrieval.resolve_limits(&options);
+        assert!(limits.allows(0.0));
+        assert!(!limits.allows(L2_COST));
+    }
+
+    #[test]
+    fn test_min_tier_forces_escalation() {
+        let retrieval = full_chain();
+        let l2 = retrieval.resolve_limits(&SearchOptions {
+            min_tier: Some(RetrievalTier::L2),
+            ..Default::default()
+        });
+        assert!(l2.threshold(0) > 1.0);
+        assert_eq!(l2.threshold(1), 0.85);
+
+        let l3 = retrieval.resolve_limits(&SearchOptions {
+            min_tier: Some(RetrievalTier::L3),
+            max_cost: Some(0.0),
+            ..Default::default()
+        });
+        assert!(l3.threshold(1) > 1.0);
+        // 預算仍然優先
+        assert!(!l3.allows(L2_COST));
+    }
+
+    #[test]
+    fn test_default_chain_order() {
+        let retrieval = full_chain();
+        let names: Vec<&str> = retrieval.tiers().iter().map(|t| t.provider.name()).collect();
+        assert_eq!(names, vec!["duckduckgo", "exa", "tavily"]);
+        assert_eq!(RetrievalTier::at(5), RetrievalTier::L3);
+        assert_eq!(RetrievalTier::L2.index(), 1);
+    }
+
+    #[test]
+    fn test_from_config_skips_disabled_engines() {
+        let mut config = BoseConfig {
+            exa_api_key: Some(bose_common::Secret::new("key")),
+            ..BoseConfig::default()
+        };
+        config.engines.entry("duckduckgo".into()).or_default().enabled = false;
+        let retrieval = TieredRetrieval::from_config(&config).unwrap();
+        let names: Vec<&str> = retrieval.tiers().iter().map(|t| t.provider.name()).collect();
+        assert_eq!(names, vec!["exa"]);
+
+        config.engines.entry("exa".into()).or_default().enabled = false;
+        assert!(TieredRetrieval::from_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_from_providers_follows_config_order() {
+        let config = TieredConfig::from(&TierThresholds {
+            chain: vec![
+                TierEntry { provider: "local".into(), threshold: Some(0.7), cost: None },
+                TierEntry { provider: "remote".into(), threshold: None, cost: Some(0.001) },
+            ],
+            ..TierThresholds::default()
+        });
+        let retrieval = TieredRetrieval::from_providers(
+            config.clone(),
+            vec![Canned::boxed("remote", &[], 0.0), Canned::boxed("local", &[], 0.0)],
+        )
+        .unwrap();
+        let tiers = retrieval.tiers();
+        assert_eq!(tiers[0].provider.name(), "local");
+        assert_eq!(tiers[0].threshold, 0.7);
+        assert_eq!(tiers[1].threshold, 0.85);
+        assert_eq!(tiers[1].cost, 0.001);
+
+        let missing = TieredRetrieval::from_providers(config, vec![Canned::boxed("local", &[], 0.0)]);
+        assert!(matches!(missing, Err(SearchError::ConfigError(_))));
+        assert!(TieredRetrieval::from_providers(TieredConfig::default(), Vec::new()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_chain_escalates_until_threshold() {
+        let retrieval = TieredRetrieval::from_providers(
+            TieredConfig::default(),
+            vec![
+                Canned::boxed("first", &["https://a.com/1"], 0.0),
+                Canned::boxed("second", &["https://a.com/1", "https://b.com/2"], 0.002),
+                Canned::boxed("third", &["https://c.com/3"], 0.004),
+            ],
+        )
+        .unwrap();
+
+        // 閾值永遠達不到：一路升級到最後一層，成本累加
+        let result = retrieval
+            .search_with_options("rust async runtime", &SearchOptions {
+                l1_threshold: Some(2.0),
+                l2_threshold: Some(2.0),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(result.provider, "third");
+        assert_eq!(result.tier_used, RetrievalTier::L3);
+        assert!((result.cost_estimate - 0.006).abs() < 1e-6);
+
+        // 預算只夠到第二層
+        let capped = retrieval
+            .search_with_options("rust async runtime", &SearchOptions {
+                l1_threshold: Some(2.0),
+                l2_threshold: Some(2.0),
+                max_cost: Some(0.003),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(capped.provider, "second");
+        assert_eq!(capped.results.len(), 2);
+        assert!(capped.budget_stopped);
+        assert!(!result.budget_stopped);
+
+        // 第一層即達標
+        let first = retrieval
+            .search_with_options("rust async runtime", &SearchOptions {
+                l1_threshold: Some(0.0),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(first.tier_used, RetrievalTier::L1);
+        assert_eq!(first.cost_estimate, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_tier_trace_explains_escalation() {
+        let retrieval = TieredRetrieval::from_providers(
+            TieredConfig {
+                l1_threshold: 2.0,
+                l2_threshold: 2.0,
+                ..Default::default()
+            },
+            vec![
+                Canned::boxed("first", &["https://a.com/1"], 0.0),
+                Canned::boxed("second", &["https://a.com/1", "https://b.com/2"], 0.002),
+                Canned::boxed("third", &["https://c.com/3"], 0.004),
+            ],
+        )
+        .unwrap();
+
+        let capped = retrieval
+            .search_with_options("rust async runtime", &SearchOptions {
+                max_cost: Some(0.003),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let trace = &capped.tier_trace;
+        assert_eq!(trace.len(), 3);
+        assert_eq!(trace[0].provider, "first");
+        assert_eq!(trace[1].tier, RetrievalTier::L2);
+        assert_eq!(trace[1].result_count, 2);
+        assert_eq!(trace[1].cost, 0.002);
+        assert!(trace[..2].iter().all(|a| a.confidence.is_some() && a.skipped_reason.is_none()));
+        assert_eq!(trace[2].skipped_reason, Some(SkipReason::MaxCost));
+        assert_eq!(trace[2].confidence, None);
+        assert_eq!(trace[2].latency, Duration::ZERO);
+
+        let costs = Arc::new(CostTracker::in_memory().unwrap().with_budget(crate::cost::Budget::daily(0.0)));
+        let budgeted = TieredRetrieval::from_providers(
+            TieredConfig {
+                l1_threshold: 2.0,
+                ..Default::default()
+            },
+            vec![
+                Canned::boxed("free", &["https://a.com/1"], 0.0),
+                Box::new(ExaClient::new("test")),
+            ],
+        )
+        .unwrap()
+        .with_cost_tracker(costs);
+        let result = budgeted.search("rust async runtime").await.unwrap();
+        assert_eq!(result.tier_trace[1].skipped_reason, Some(SkipReason::Budget));
+
+        let early = retrieval
+            .search_with_options("rust async runtime", &SearchOptions {
+                l1_threshold: Some(0.0),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let reasons: Vec<_> = early.tier_trace.iter().map(|a| a.skipped_reason).collect();
+        assert_eq!(reasons, vec![None, Some(SkipReason::ThresholdMet), Some(SkipReason::ThresholdMet)]);
+    }
+
+    #[test]
+    fn test_config_max_cost_is_hard_cap() {
+        let retrieval = TieredRetrieval::new(TieredConfig {
+            max_cost: Some(0.001),
+            ..Default::default()
+        })
+        .with_exa("test");
+        let thorough = retrieval.resolve_limits(&SearchOptions::with_quality(SearchQuality::Thorough));
+        assert_eq!(thorough.max_cost, Some(0.001));
+        let generous = retrieval.resolve_limits(&SearchOptions {
+            max_cost: Some(1.0),
+            ..Default::default()
+        });
+        assert_eq!(generous.max_cost, Some(0.001));
+        let stricter = retrieval.resolve_limits(&SearchOptions {
+            max_cost: Some(0.0),
+            ..Default::default()
+        });
+        assert_eq!(stricter.max_cost, Some(0.0));
+    }
+
+    #[tokio::test]
+    async fn test_budget_stop_returns_best_tier_so_far() {
+        let retrieval = TieredRetrieval::from_providers(
+            TieredConfig {
+                l1_threshold: 2.0,
+                l2_threshold: 2.0,
+                max_cost: Some(0.002),
+                ..Default::default()
+            },
+            vec![
+                Canned::boxed("relevant", &["https://docs.rs/tokio", "https://github.com/tokio-rs/tokio"], 0.0),
+                Canned::boxed("weak", &["https://unknown.example"], 0.002),
+                Canned::boxed("paid", &["https://c.com/3"], 0.01),
+            ],
+        )
+        .unwrap();
+        let result = retrieval.search("rust async runtime").await.unwrap();
+        assert!(result.budget_stopped);
+        assert_eq!(result.provider, "relevant");
+        assert_eq!(result.tier_used, RetrievalTier::L1);
+        assert!((result.cost_estimate - 0.002).abs() < 1e-6);
+    }
+
+    fn hedged_chain(first_delay_ms: u64, l1_threshold: f32, l2_threshold: f32) -> TieredRetrieval {
+        TieredRetrieval::from_providers(
+            TieredConfig {
+                l1_threshold,
+                l2_threshold,
+                hedge_after_ms: Some(10),
+                ..Default::default()
+            },
+            vec![
+                Canned::slow("slow", &["https://docs.rs/tokio"], 0.0, first_delay_ms),
+                Canned::boxed("fast", &["https://github.com/tokio-rs/tokio"], 0.002),
+                Canned::boxed("deep", &["https://c.com/3"], 0.004),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_hedged_speculative_tier_wins() {
+        let started = std::time::Instant::now();
+        let result = hedged_chain(2_000, 2.0, 0.0).search("rust async runtime").await.unwrap();
+        assert_eq!(result.provider, "fast");
+        assert_eq!(result.tier_used, RetrievalTier::L2);
+        assert!((result.cost_estimate - 0.002).abs() < 1e-6);
+        // 慢的 L1 已被取消，不必等它完成
+        assert!(started.elapsed() < Duration::from_millis(1_000));
+        let reasons: Vec<_> = result.tier_trace.iter().map(|a| a.skipped_reason).collect();
+        assert_eq!(reasons, vec![Some(SkipReason::Cancelled), None, Some(SkipReason::ThresholdMet)]);
+        assert_eq!(result.tier_trace[0].cost, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_hedged_primary_cancels_speculative() {
+        // L1 在對沖延遲前就完成並達標：L2 從未啟動，不計費
+        let retrieval = TieredRetrieval::from_providers(
+            TieredConfig {
+                l1_threshold: 0.0,
+                hedge_after_ms: Some(1_000),
+                ..Default::default()
+            },
+            vec![
+                Canned::boxed("quick", &["https://docs.rs/tokio"], 0.0),
+                Canned::slow("paid", &["https://c.com/3"], 0.002, 0),
+            ],
+        )
+        .unwrap();
+        let result = retrieval.search("rust async runtime").await.unwrap();
+        assert_eq!(result.provider, "quick");
+        assert_eq!(result.cost_estimate, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_hedged_cancelled_speculative_is_charged() {
+        // L2 已送出後 L1 才達標：L2 被取消，但付費服務已收到請求，照樣計費
+        let costs = Arc::new(CostTracker::in_memory().unwrap());
+        let retrieval = TieredRetrieval::from_providers(
+            TieredConfig {
+                l1_threshold: 0.0,
+                hedge_after_ms: Some(10),
+                ..Default::default()
+            },
+            vec![
+                Canned::slow("slow", &["https://docs.rs/tokio"], 0.0, 100),
+                Canned::billed("paid", &["https://c.com/3"], SearchEngine::Exa, 2_000),
+            ],
+        )
+        .unwrap()
+        .with_cost_tracker(costs.clone());
+        let started = std::time::Instant::now();
+        let result = retrieval.search("rust async runtime").await.unwrap();
+        assert!(started.elapsed() < Duration::from_millis(1_000));
+        assert_eq!(result.provider, "slow");
+        assert!((result.cost_estimate - EXA_COST).abs() < 1e-6);
+        assert_eq!(result.tier_trace[1].skipped_reason, Some(SkipReason::Cancelled));
+        assert_eq!(result.tier_trace[1].cost, EXA_COST);
+        let spent = costs.spend(crate::cost::BudgetPeriod::Day, Some(SearchEngine::Exa)).unwrap();
+        assert!((spent - EXA_COST).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_hedged_falls_through_to_later_tiers() {
+        // 兩層都未達標：L2 的推測結果直接沿用，繼續升級到第三層
+        let result = hedged_chain(50, 2.0, 2.0).search("rust async runtime").await.unwrap();
+        assert_eq!(result.provider, "deep");
+        assert!(result.tier_trace.iter().all(|a| a.skipped_reason.is_none()));
+        assert!(result.tier_trace[0].latency >= Duration::from_millis(50));
+        assert!((result.cost_estimate - 0.006).abs() < 1e-6);
+
+        // 預算不足以直接走到第二層時不對沖
+        let retrieval = hedged_chain(50, 2.0, 2.0);
+        let limits = retrieval.resolve_limits(&SearchOptions {
+            max_cost: Some(0.001),
+            ..Default::default()
+        });
+        assert!(retrieval.hedge_delay(&limits).is_none());
+        assert!(retrieval.hedge_delay(&retrieval.resolve_limits(&SearchOptions::default())).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_deadline_returns_partial_results() {
+        let retrieval = TieredRetrieval::from_providers(
+            TieredConfig {
+                l1_threshold: 2.0,
+                ..Default::default()
+            },
+            vec![
+                Canned::boxed("quick", &["https://docs.rs/tokio"], 0.0),
+                Canned::slow("stalled", &["https://c.com/3"], 0.002, 2_000),
+            ],
+        )
+        .unwrap();
+        let started = std::time::Instant::now();
+        let result = retrieval
+            .search_with_deadline("rust async runtime", Duration::from_millis(100))
+            .await
+            .unwrap();
+        assert!(started.elapsed() < Duration::from_millis(1_000));
+        assert!(result.timed_out);
+        assert_eq!(result.provider, "quick");
+        assert_eq!(result.results.len(), 1);
+        // 被中止的請求不計費
+        assert_eq!(result.cost_estimate, 0.0);
+        assert_eq!(result.tier_trace[1].skipped_reason, Some(SkipReason::Deadline));
+        assert!(result.tier_trace[1].latency > Duration::ZERO);
+
+        let untimed = retrieval.search("rust async runtime").await;
+        assert!(!untimed.unwrap().timed_out);
+    }
+
+    #[tokio::test]
+    async fn test_deadline_before_any_tier_completes() {
+        let retrieval = TieredRetrieval::from_providers(
+            TieredConfig::default(),
+            vec![Canned::slow("stalled", &["https://docs.rs/tokio"], 0.0, 2_000)],
+        )
+        .unwrap();
+        let result = retrieval
+            .search_with_deadline("rust async runtime", Duration::from_millis(20))
+            .await
+            .unwrap();
+        assert!(result.timed_out);
+        assert!(result.results.is_empty());
+        assert_eq!(result.confidence, 0.0);
+
+        // 對沖中逾時：L1 被丟棄，未達標的 L2 仍作為部分結果回傳
+        let result = hedged_chain(2_000, 2.0, 2.0)
+            .search_with_deadline("rust async runtime", Duration::from_millis(200))
+            .await
+            .unwrap();
+        assert!(result.timed_out);
+        assert_eq!(result.provider, "fast");
+        let reasons: Vec<_> = result.tier_trace.iter().map(|a| a.skipped_reason).collect();
+        assert_eq!(reasons, vec![Some(SkipReason::Deadline), None, Some(SkipReason::Deadline)]);
+    }
+
+    #[tokio::test]
+    async fn test_merge_mode_fuses_tiers() {
+        let config = TieredConfig {
+            l1_threshold: 2.0,
+            l2_threshold: 2.0,
+            merge_tiers: true,
+            ..Default::default()
+        };
+        let retrieval = TieredRetrieval::from_providers(
+            config,
+            vec![
+                Canned::boxed("free", &["https://a.com/1", "https://www.b.com/2/"], 0.0),
+                Canned::boxed("paid", &["https://b.com/2", "https://c.com/3"], 0.002),
+            ],
+        )
+        .unwrap();
+        let result = retrieval.search("rust async runtime").await.unwrap();
+        assert_eq!(result.provider, "free+paid");
+        assert_eq!(result.tier_used, RetrievalTier::L2);
+        // 兩層都有的結果排第一，免費層的結果仍保留
+        let urls: Vec<&str> = result.results.iter().map(|r| r.url.as_str()).collect();
+        assert_eq!(urls, vec!["https://www.b.com/2/", "https://a.com/1", "https://c.com/3"]);
+        assert!(result.confidence > 0.0);
+    }
+
+    #[test]
+    fn test_fuse_tiers_fills_missing_content() {
+        let snippet_only = vec![SearchResult {
+            title: "Exa".to_string(),
+            url: "https://a.com/1".to_string(),
+            snippet: Some("snippet".to_string()),
+            content: None,
+            published_date: Some("2024-03-14".to_string()),
+            metadata: Default::default(),
+        }];
+        let extracted = vec![SearchResult {
+            title: "Tavily".to_string(),
+            url: "https://a.com/1".to_string(),
+            snippet: None,
+            content: Some("full text".to_string()),
+            published_date: None,
+            metadata: Default::default(),
+        }];
+        let fused = fuse_tiers(&[&snippet_only, &extracted], RRF_K);
+        assert_eq!(fused.len(), 1);
+        assert_eq!(fused[0].title, "Exa");
+        assert_eq!(fused[0].content.as_deref(), Some("full text"));
+        assert_eq!(fused[0].published_date.as_deref(), Some("2024-03-14"));
+    }
+
     #[test]
     fn test_refine_query_empty_results() {
         let retrieval = TieredRetrieval::with_defaults();
@@ -221,6 +1644,8 @@ mod tests {
                 url: "https://example.com".to_string(),
                 snippet: Some("Rust programming language security".to_string()),
                 content: None,
+                published_date: None,
+                metadata: Default::default(),
             },
         ];
         let refined = retrieval.refine_query("Rust", &results);
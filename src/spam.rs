@@ -0,0 +1,391 @@
+//! 內容農場偵測 - 以啟發式規則標記疑似 SEO 垃圾與內容農場的結果，並降低排名或移除
+//!
+//! 每條規則命中時累加其權重，總分達到 `threshold` 即視為垃圾；權重設為 0 可停用該規則：
+//!
+//! ```toml
+//! [spam]
+//! action = "remove"
+//! threshold = 0.5
+//! spam_tlds = ["xyz", "top", "click"]
+//!
+//! [spam.weights]
+//! thin_snippet = 0.0
+//! young_domain = 0.6
+//! ```
+//!
+//! 網域年齡需要外部資料（WHOIS / RDAP 快取），以 `with_domain_ages` 提供；沒有資料時略過該規則。
+
+use crate::types::SearchResult;
+use reqwest::Url;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// 常見於內容農場的頂級網域
+const DEFAULT_SPAM_TLDS: &[&str] = &[
+    "xyz", "top", "click", "buzz", "loan", "work", "gq", "tk", "ml", "cf", "ga", "icu", "cfd", "sbs", "rest", "cyou",
+];
+
+/// AI 生成或模板化文章常見的套話
+const DEFAULT_BOILERPLATE: &[&str] = &[
+    "in this article, we will",
+    "in this article we will",
+    "as an ai language model",
+    "the ultimate guide",
+    "everything you need to know",
+    "in today's fast-paced world",
+    "in today's digital age",
+];
+
+/// 命中垃圾判定後的處理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpamAction {
+    /// 只標記，不改變結果
+    Flag,
+    /// 移到所有正常結果之後（保持相對順序）
+    #[default]
+    Downrank,
+    /// 直接移除
+    Remove,
+}
+
+/// 各規則的權重（0.0 停用）
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpamWeights {
+    pub thin_snippet: f32,
+    pub keyword_stuffing: f32,
+    pub spam_tld: f32,
+    pub young_domain: f32,
+    pub hyphenated_domain: f32,
+    pub boilerplate: f32,
+}
+
+impl Default for SpamWeights {
+    fn default() -> Self {
+        Self {
+            thin_snippet: 0.25,
+            keyword_stuffing: 0.35,
+            spam_tld: 0.35,
+            young_domain: 0.4,
+            hyphenated_domain: 0.25,
+            boilerplate: 0.3,
+        }
+    }
+}
+
+/// 內容農場偵測設定
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpamConfig {
+    pub action: SpamAction,
+    /// 總分達到此值即視為垃圾
+    pub threshold: f32,
+    pub weights: SpamWeights,
+    /// 摘要少於此字元數視為內容單薄
+    pub min_snippet_chars: usize,
+    /// 標題中同一個詞出現達此次數視為關鍵字堆砌
+    pub max_keyword_repeats: usize,
+    /// 網域的連字號達此數量視為關鍵字網域（`best-cheap-vpn-reviews.com`）
+    pub max_domain_hyphens: usize,
+    /// 註冊未滿此天數的網域視為新網域
+    pub min_domain_age_days: u32,
+    pub spam_tlds: Vec<String>,
+    /// 摘要或標題中出現即命中的套話（不分大小寫）
+    pub boilerplate: Vec<String>,
+}
+
+impl Default for SpamConfig {
+    fn default() -> Self {
+        Self {
+            action: SpamAction::default(),
+            threshold: 0.5,
+            weights: SpamWeights::default(),
+            min_snippet_chars: 60,
+            max_keyword_repeats: 3,
+            max_domain_hyphens: 3,
+            min_domain_age_days: 180,
+            spam_tlds: DEFAULT_SPAM_TLDS.iter().map(|t| t.to_string()).collect(),
+            boilerplate: DEFAULT_BOILERPLATE.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+}
+
+/// 命中的規則
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpamSignal {
+    ThinSnippet,
+    KeywordStuffing,
+    SpamTld,
+    YoungDomain,
+    HyphenatedDomain,
+    Boilerplate,
+}
+
+impl fmt::Display for SpamSignal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Self::ThinSnippet => "內容單薄",
+            Self::KeywordStuffing => "關鍵字堆砌",
+            Self::SpamTld => "垃圾頂級網域",
+            Self::YoungDomain => "新註冊網域",
+            Self::HyphenatedDomain => "關鍵字網域",
+            Self::Boilerplate => "模板化內容",
+        };
+        f.write_str(label)
+    }
+}
+
+/// 單一結果的判定
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpamVerdict {
+    /// 命中規則的權重總和（上限 1.0）
+    pub score: f32,
+    pub signals: Vec<SpamSignal>,
+    pub is_spam: bool,
+}
+
+/// 被判定為垃圾的結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlaggedResult {
+    pub url: String,
+    pub verdict: SpamVerdict,
+}
+
+/// 網域年齡（天）的來源，例如 WHOIS / RDAP 查詢結果的快取
+pub trait DomainAge: Send + Sync {
+    /// `host` 為小寫、不含 `www.` 的主機名稱；未知時回傳 None
+    fn age_days(&self, host: &str) -> Option<u32>;
+}
+
+impl DomainAge for HashMap<String, u32> {
+    fn age_days(&self, host: &str) -> Option<u32> {
+        // 子網域沿用註冊網域的年齡
+        let mut candidate = host;
+        loop {
+            if let Some(&age) = self.get(candidate) {
+                return Some(age);
+            }
+            candidate = candidate.split_once('.')?.1;
+        }
+    }
+}
+
+/// 內容農場偵測器
+#[derive(Clone)]
+pub struct SpamDetector {
+    config: SpamConfig,
+    ages: Option<Arc<dyn DomainAge>>,
+}
+
+impl Default for SpamDetector {
+    fn default() -> Self {
+        Self::new(SpamConfig::default())
+    }
+}
+
+impl SpamDetector {
+    pub fn new(config: SpamConfig) -> Self {
+        Self { config, ages: None }
+    }
+
+    /// 提供網域年齡資料，啟用新網域規則
+    pub fn with_domain_ages(mut self, ages: Arc<dyn DomainAge>) -> Self {
+        self.ages = Some(ages);
+        self
+    }
+
+    pub fn config(&self) -> &SpamConfig {
+        &self.config
+    }
+
+    /// 評估單一結果
+    pub fn assess(&self, result: &SearchResult) -> SpamVerdict {
+        let config = &self.config;
+        let weights = &config.weights;
+        let host = Url::parse(&result.url)
+            .ok()
+            .and_then(|u| u.host_str().map(|h| h.to_lowercase().trim_start_matches("www.").to_string()));
+        let snippet = result.snippet.as_deref().unwrap_or("").trim();
+
+        let mut checks = vec![
+            (SpamSignal::ThinSnippet, weights.thin_snippet, snippet.chars().count() < config.min_snippet_chars),
+            (SpamSignal::KeywordStuffing, weights.keyword_stuffing, self.is_stuffed(&result.title)),
+            (SpamSignal::Boilerplate, weights.boilerplate, self.has_boilerplate(&result.title, snippet)),
+        ];
+        if let Some(host) = &host {
+            let tld = host.rsplit('.').next().unwrap_or("");
+            // 只看註冊網域那一段的連字號，子網域常有合法的連字號
+            let name = host.rsplit('.').nth(1).unwrap_or("");
+            let young = self
+                .ages
+                .as_ref()
+                .and_then(|ages| ages.age_days(host))
+                .is_some_and(|days| days < config.min_domain_age_days);
+            checks.extend([
+                (SpamSignal::SpamTld, weights.spam_tld, config.spam_tlds.iter().any(|t| t.eq_ignore_ascii_case(tld))),
+                (
+                    SpamSignal::HyphenatedDomain,
+                    weights.hyphenated_domain,
+                    name.matches('-').count() >= config.max_domain_hyphens,
+                ),
+                (SpamSignal::YoungDomain, weights.young_domain, young),
+            ]);
+        }
+
+        let mut verdict = SpamVerdict::default();
+        for (signal, weight, hit) in checks {
+            if hit && weight > 0.0 {
+                verdict.score += weight;
+                verdict.signals.push(signal);
+            }
+        }
+        verdict.score = verdict.score.min(1.0);
+        verdict.is_spam = !verdict.signals.is_empty() && verdict.score >= config.threshold;
+        verdict
+    }
+
+    fn is_stuffed(&self, title: &str) -> bool {
+        let words: Vec<String> = title
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| w.chars().count() >= 3)
+            .map(str::to_lowercase)
+            .collect();
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for word in &words {
+            *counts.entry(word.as_str()).or_default() += 1;
+        }
+        counts.values().any(|&n| n >= self.config.max_keyword_repeats)
+    }
+
+    fn has_boilerplate(&self, title: &str, snippet: &str) -> bool {
+        let text = format!("{}\n{}", title, snippet).to_lowercase();
+        self.config.boilerplate.iter().any(|phrase| text.contains(&phrase.to_lowercase()))
+    }
+
+    /// 依設定的處理方式套用到結果，回傳被判定為垃圾的結果（依原本順序）
+    pub fn apply(&self, results: &mut Vec<SearchResult>) -> Vec<FlaggedResult> {
+        let verdicts: Vec<SpamVerdict> = results.iter().map(|r| self.assess(r)).collect();
+        let flagged: Vec<FlaggedResult> = results
+            .iter()
+            .zip(&verdicts)
+            .filter(|(_, v)| v.is_spam)
+            .map(|(r, v)| FlaggedResult {
+                url: r.url.clone(),
+                verdict: v.clone(),
+            })
+            .collect();
+        if flagged.is_empty() || self.config.action == SpamAction::Flag {
+            return flagged;
+        }
+
+        let (clean, spam): (Vec<_>, Vec<_>) =
+            std::mem::take(results).into_iter().zip(verdicts).partition(|(_, v)| !v.is_spam);
+        results.extend(clean.into_iter().map(|(r, _)| r));
+        if self.config.action == SpamAction::Downrank {
+            results.extend(spam.into_iter().map(|(r, _)| r));
+        }
+        log::info!("🧹 {} 個疑似內容農場的結果（{:?}）", flagged.len(), self.config.action);
+        flagged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(url: &str, title: &str, snippet: &str) -> SearchResult {
+        SearchResult {
+            title: title.to_string(),
+            url: url.to_string(),
+            snippet: Some(snippet.to_string()),
+            content: None,
+            published_date: None,
+            metadata: Default::default(),
+        }
+    }
+
+    const GOOD_SNIPPET: &str = "The Rust Programming Language book covers ownership, borrowing and lifetimes in depth.";
+
+    #[test]
+    fn test_assess_signals() {
+        let detector = SpamDetector::default();
+        let clean = detector.assess(&result("https://doc.rust-lang.org/book/", "The Rust Book", GOOD_SNIPPET));
+        assert!(!clean.is_spam);
+        assert!(clean.signals.is_empty());
+
+        let farm = detector.assess(&result(
+            "https://best-rust-tips-2024.xyz/rust",
+            "Rust tips: best Rust tips for Rust beginners",
+            "In this article, we will explore",
+        ));
+        assert!(farm.is_spam);
+        assert_eq!(
+            farm.signals,
+            vec![
+                SpamSignal::ThinSnippet,
+                SpamSignal::KeywordStuffing,
+                SpamSignal::Boilerplate,
+                SpamSignal::SpamTld,
+                SpamSignal::HyphenatedDomain,
+            ]
+        );
+        assert_eq!(farm.score, 1.0);
+
+        // 單一弱訊號不足以判定
+        let thin = detector.assess(&result("https://github.com/rust-lang/rust", "rust-lang/rust", ""));
+        assert_eq!(thin.signals, vec![SpamSignal::ThinSnippet]);
+        assert!(!thin.is_spam);
+    }
+
+    #[test]
+    fn test_domain_age_and_weights() {
+        let ages: HashMap<String, u32> = [("fresh-site.com".to_string(), 20), ("rust-lang.org".to_string(), 5000)].into();
+        let mut config = SpamConfig::default();
+        config.weights.thin_snippet = 0.0;
+        let detector = SpamDetector::new(config).with_domain_ages(Arc::new(ages));
+
+        let verdict = detector.assess(&result("https://blog.fresh-site.com/a", "Guide", ""));
+        assert_eq!(verdict.signals, vec![SpamSignal::YoungDomain]);
+        assert!(!detector.assess(&result("https://rust-lang.org", "Rust", "")).signals.contains(&SpamSignal::YoungDomain));
+    }
+
+    #[test]
+    fn test_apply_actions() {
+        let results = vec![
+            result("https://cheap-rust-course-online.top/", "Rust Rust Rust course", "Buy now"),
+            result("https://doc.rust-lang.org/book/", "The Rust Book", GOOD_SNIPPET),
+        ];
+        let urls = |results: &[SearchResult]| results.iter().map(|r| r.url.clone()).collect::<Vec<_>>();
+
+        for (action, expected) in [
+            (SpamAction::Flag, urls(&results)),
+            (SpamAction::Downrank, vec![results[1].url.clone(), results[0].url.clone()]),
+            (SpamAction::Remove, vec![results[1].url.clone()]),
+        ] {
+            let detector = SpamDetector::new(SpamConfig { action, ..SpamConfig::default() });
+            let mut applied = results.clone();
+            let flagged = detector.apply(&mut applied);
+            assert_eq!(flagged.len(), 1, "{action:?}");
+            assert_eq!(flagged[0].url, results[0].url);
+            assert_eq!(urls(&applied), expected, "{action:?}");
+        }
+    }
+
+    #[test]
+    fn test_config_deserialize() {
+        let config: SpamConfig = serde_json::from_value(serde_json::json!({
+            "action": "remove",
+            "spam_tlds": ["zip"],
+            "weights": { "spam_tld": 0.6 },
+        }))
+        .unwrap();
+        assert_eq!(config.action, SpamAction::Remove);
+        assert_eq!(config.weights.spam_tld, 0.6);
+        assert_eq!(config.weights.boilerplate, SpamWeights::default().boilerplate);
+        let verdict = SpamDetector::new(config).assess(&result("https://files.zip/x", "x", GOOD_SNIPPET));
+        assert!(verdict.is_spam);
+    }
+}
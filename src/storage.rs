@@ -0,0 +1,650 @@
+//! 結果儲存 - 以 SQLite 記錄每次查詢、參數、使用層級、置信度與完整結果
+//!
+//! 讓搜尋紀錄可以離線重新分析：`recent()` 取最近查詢、`by_query()` 依查詢文字、
+//! `between()` 依時間區間、`all()` 匯出全部。時間以 Unix 秒儲存。
+//!
+//! 同一個資料庫也保存具名的搜尋（`save_search()`）與結果書籤（`add_bookmark()`，可附筆記）。
+//! 每次查詢實際呼叫的引擎、延遲與是否命中快取以 `record_call()` 另外記錄，供 `analytics` 統計。
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::routing::{RetrievalTier, TieredResult};
+use crate::types::{SearchError, SearchResult};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS queries (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    query       TEXT NOT NULL,
+    params      TEXT NOT NULL,
+    tier        TEXT,
+    confidence  REAL,
+    recorded_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_queries_query ON queries(query);
+CREATE INDEX IF NOT EXISTS idx_queries_recorded_at ON queries(recorded_at);
+CREATE TABLE IF NOT EXISTS results (
+    query_id INTEGER NOT NULL REFERENCES queries(id) ON DELETE CASCADE,
+    rank     INTEGER NOT NULL,
+    title    TEXT NOT NULL,
+    url      TEXT NOT NULL,
+    snippet  TEXT,
+    content  TEXT,
+    PRIMARY KEY (query_id, rank)
+);
+CREATE TABLE IF NOT EXISTS engine_calls (
+    query_id   INTEGER NOT NULL REFERENCES queries(id) ON DELETE CASCADE,
+    engine     TEXT NOT NULL,
+    latency_ms INTEGER NOT NULL,
+    cache_hit  INTEGER
+);
+CREATE INDEX IF NOT EXISTS idx_engine_calls_query_id ON engine_calls(query_id);
+CREATE TABLE IF NOT EXISTS saved_searches (
+    name       TEXT PRIMARY KEY,
+    query      TEXT NOT NULL,
+    params     TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS bookmarks (
+    id         INTEGER PRIMARY KEY AUTOINCREMENT,
+    url        TEXT NOT NULL UNIQUE,
+    title      TEXT NOT NULL,
+    snippet    TEXT,
+    note       TEXT,
+    query      TEXT,
+    created_at INTEGER NOT NULL
+);
+";
+
+/// 一筆查詢紀錄
+#[derive(Debug, Clone)]
+pub struct QueryRecord {
+    pub id: i64,
+    pub query: String,
+    /// 查詢參數（引擎、結果數、閾值等），以 JSON 保存
+    pub params: serde_json::Value,
+    pub tier: Option<RetrievalTier>,
+    pub confidence: Option<f32>,
+    pub recorded_at: SystemTime,
+    pub results: Vec<SearchResult>,
+}
+
+/// 一次引擎呼叫的量測
+#[derive(Debug, Clone, PartialEq)]
+pub struct EngineCall {
+    pub query_id: i64,
+    pub engine: String,
+    pub latency: Duration,
+    /// 是否由快取回應；未啟用快取時為 None
+    pub cache_hit: Option<bool>,
+    /// 所屬查詢的記錄時間
+    pub recorded_at: SystemTime,
+}
+
+/// 具名的搜尋
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedSearch {
+    pub name: String,
+    pub query: String,
+    /// 搜尋參數（引擎、結果數等），以 JSON 保存
+    pub params: serde_json::Value,
+    pub created_at: SystemTime,
+}
+
+/// 結果書籤；同一網址只有一筆
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bookmark {
+    pub id: i64,
+    pub url: String,
+    pub title: String,
+    pub snippet: Option<String>,
+    pub note: Option<String>,
+    /// 找到此結果的查詢
+    pub query: Option<String>,
+    pub created_at: SystemTime,
+}
+
+/// SQLite 結果儲存
+pub struct ResultStore {
+    conn: Mutex<Connection>,
+}
+
+impl ResultStore {
+    /// 開啟（或建立）資料庫檔案
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SearchError> {
+        Self::init(Connection::open(path).map_err(storage_error)?)
+    }
+
+    /// 記憶體資料庫（測試與暫時工作階段）
+    pub fn in_memory() -> Result<Self, SearchError> {
+        Self::init(Connection::open_in_memory().map_err(storage_error)?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, SearchError> {
+        conn.execute_batch("PRAGMA foreign_keys = ON;")
+            .and_then(|_| conn.execute_batch(SCHEMA))
+            .map_err(storage_error)?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    /// 記錄一次查詢與其結果，回傳紀錄 ID
+    pub fn record(
+        &self,
+        query: &str,
+        params: &serde_json::Value,
+        tier: Option<RetrievalTier>,
+        confidence: Option<f32>,
+        results: &[SearchResult],
+    ) -> Result<i64, SearchError> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(storage_error)?;
+        tx.execute(
+            "INSERT INTO queries (query, params, tier, confidence, recorded_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                query,
+                params.to_string(),
+                tier.map(tier_name),
+                confidence,
+                unix_secs(SystemTime::now()),
+            ],
+        )
+        .map_err(storage_error)?;
+        let id = tx.last_insert_rowid();
+
+        {
+            let mut insert = tx
+                .prepare(
+                    "INSERT INTO results (query_id, rank, title, url, snippet, content)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                )
+                .map_err(storage_error)?;
+            for (rank, result) in results.iter().enumerate() {
+                insert
+                    .execute(params![
+                        id,
+                        rank as i64,
+                        result.title,
+                        result.url,
+                        result.snippet,
+                        result.content,
+                    ])
+                    .map_err(storage_error)?;
+            }
+        }
+        tx.commit().map_err(storage_error)?;
+        Ok(id)
+    }
+
+    /// 記錄階梯式檢索的結果（層級與置信度取自 `TieredResult`）
+    pub fn record_tiered(
+        &self,
+        query: &str,
+        params: &serde_json::Value,
+        result: &TieredResult,
+    ) -> Result<i64, SearchError> {
+        self.record(
+            query,
+            params,
+            Some(result.tier_used),
+            Some(result.confidence),
+            &result.results,
+        )
+    }
+
+    /// 依 ID 取回紀錄
+    pub fn get(&self, id: i64) -> Result<Option<QueryRecord>, SearchError> {
+        let conn = self.conn.lock().unwrap();
+        let record = conn
+            .query_row(
+                "SELECT id, query, params, tier, confidence, recorded_at FROM queries WHERE id = ?1",
+                params![id],
+                read_record,
+            )
+            .optional()
+            .map_err(storage_error)?;
+        record
+            .map(|record| with_results(&conn, record))
+            .transpose()
+    }
+
+    /// 最近的 `limit` 筆查詢，新的在前
+    pub fn recent(&self, limit: usize) -> Result<Vec<QueryRecord>, SearchError> {
+        self.select(
+            "SELECT id, query, params, tier, confidence, recorded_at FROM queries
+             ORDER BY recorded_at DESC, id DESC LIMIT ?1",
+            params![limit as i64],
+        )
+    }
+
+    /// 查詢文字完全相同的所有紀錄，新的在前
+    pub fn by_query(&self, query: &str) -> Result<Vec<QueryRecord>, SearchError> {
+        self.select(
+            "SELECT id, query, params, tier, confidence, recorded_at FROM queries
+             WHERE query = ?1 ORDER BY recorded_at DESC, id DESC",
+            params![query],
+        )
+    }
+
+    /// 所有紀錄，依時間先後排序
+    pub fn all(&self) -> Result<Vec<QueryRecord>, SearchError> {
+        self.select(
+            "SELECT id, query, params, tier, confidence, recorded_at FROM queries ORDER BY recorded_at, id",
+            [],
+        )
+    }
+
+    /// `[from, to)` 區間內的紀錄，依時間先後排序
+    pub fn between(&self, from: SystemTime, to: SystemTime) -> Result<Vec<QueryRecord>, SearchError> {
+        self.select(
+            "SELECT id, query, params, tier, confidence, recorded_at FROM queries
+             WHERE recorded_at >= ?1 AND recorded_at < ?2 ORDER BY recorded_at, id",
+            params![unix_secs(from), unix_secs(to)],
+        )
+    }
+
+    /// 記錄查詢 `query_id` 的一次引擎呼叫
+    pub fn record_call(
+        &self,
+        query_id: i64,
+        engine: &str,
+        latency: Duration,
+        cache_hit: Option<bool>,
+    ) -> Result<(), SearchError> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO engine_calls (query_id, engine, latency_ms, cache_hit) VALUES (?1, ?2, ?3, ?4)",
+            params![query_id, engine, latency.as_millis() as i64, cache_hit],
+        )
+        .map_err(storage_error)?;
+        Ok(())
+    }
+
+    /// `[from, to)` 區間內查詢的引擎呼叫，依時間先後排序
+    pub fn calls_between(&self, from: SystemTime, to: SystemTime) -> Result<Vec<EngineCall>, SearchError> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT c.query_id, c.engine, c.latency_ms, c.cache_hit, q.recorded_at
+                 FROM engine_calls c JOIN queries q ON q.id = c.query_id
+                 WHERE q.recorded_at >= ?1 AND q.recorded_at < ?2
+                 ORDER BY q.recorded_at, c.rowid",
+            )
+            .map_err(storage_error)?;
+        let rows = stmt
+            .query_map(params![unix_secs(from), unix_secs(to)], |row| {
+                Ok(EngineCall {
+                    query_id: row.get(0)?,
+                    engine: row.get(1)?,
+                    latency: Duration::from_millis(row.get::<_, i64>(2)?.max(0) as u64),
+                    cache_hit: row.get(3)?,
+                    recorded_at: from_unix_secs(row.get(4)?),
+                })
+            })
+            .map_err(storage_error)?;
+        rows.collect::<Result<_, _>>().map_err(storage_error)
+    }
+
+    /// 保存（或覆寫）具名的搜尋
+    pub fn save_search(&self, name: &str, query: &str, params: &serde_json::Value) -> Result<(), SearchError> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO saved_searches (name, query, params, created_at) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(name) DO UPDATE SET query = excluded.query, params = excluded.params",
+            params![name, query, params.to_string(), unix_secs(SystemTime::now())],
+        )
+        .map_err(storage_error)?;
+        Ok(())
+    }
+
+    /// 依名稱取回保存的搜尋
+    pub fn saved_search(&self, name: &str) -> Result<Option<SavedSearch>, SearchError> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT name, query, params, created_at FROM saved_searches WHERE name = ?1",
+            params![name],
+            read_saved_search,
+        )
+        .optional()
+        .map_err(storage_error)
+    }
+
+    /// 所有保存的搜尋，依名稱排序
+    pub fn saved_searches(&self) -> Result<Vec<SavedSearch>, SearchError> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT name, query, params, created_at FROM saved_searches ORDER BY name")
+            .map_err(storage_error)?;
+        let rows = stmt.query_map([], read_saved_search).map_err(storage_error)?;
+        rows.collect::<Result<_, _>>().map_err(storage_error)
+    }
+
+    /// 刪除保存的搜尋；名稱不存在時回傳 false
+    pub fn delete_saved_search(&self, name: &str) -> Result<bool, SearchError> {
+        let conn = self.conn.lock().unwrap();
+        let deleted = conn
+            .execute("DELETE FROM saved_searches WHERE name = ?1", params![name])
+            .map_err(storage_error)?;
+        Ok(deleted > 0)
+    }
+
+    /// 將結果加入書籤，回傳書籤 ID
+    ///
+    /// 網址已有書籤時更新標題與摘要；`note` 與 `query` 有值時才覆寫原本的筆記與查詢。
+    pub fn add_bookmark(
+        &self,
+        result: &SearchResult,
+        query: Option<&str>,
+        note: Option<&str>,
+    ) -> Result<i64, SearchError> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "INSERT INTO bookmarks (url, title, snippet, note, query, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(url) DO UPDATE SET
+                 title = excluded.title,
+                 snippet = COALESCE(excluded.snippet, snippet),
+                 note = COALESCE(excluded.note, note),
+                 query = COALESCE(excluded.query, query)
+             RETURNING id",
+            params![
+                result.url,
+                result.title,
+                result.snippet,
+                note,
+                query,
+                unix_secs(SystemTime::now()),
+            ],
+            |row| row.get(0),
+        )
+        .map_err(storage_error)
+    }
+
+    /// 所有書籤，新的在前
+    pub fn bookmarks(&self) -> Result<Vec<Bookmark>, SearchError> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, url, title, snippet, note, query, created_at FROM bookmarks
+                 ORDER BY created_at DESC, id DESC",
+            )
+            .map_err(storage_error)?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(Bookmark {
+                    id: row.get(0)?,
+                    url: row.get(1)?,
+                    title: row.get(2)?,
+                    snippet: row.get(3)?,
+                    note: row.get(4)?,
+                    query: row.get(5)?,
+                    created_at: from_unix_secs(row.get(6)?),
+                })
+            })
+            .map_err(storage_error)?;
+        rows.collect::<Result<_, _>>().map_err(storage_error)
+    }
+
+    /// 刪除書籤；ID 不存在時回傳 false
+    pub fn remove_bookmark(&self, id: i64) -> Result<bool, SearchError> {
+        let conn = self.conn.lock().unwrap();
+        let deleted = conn
+            .execute("DELETE FROM bookmarks WHERE id = ?1", params![id])
+            .map_err(storage_error)?;
+        Ok(deleted > 0)
+    }
+
+    /// 最近一次記錄到此網址的結果（加入書籤時補上標題與摘要）
+    pub fn find_result(&self, url: &str) -> Result<Option<SearchResult>, SearchError> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT r.title, r.url, r.snippet, r.content FROM results r
+             JOIN queries q ON q.id = r.query_id
+             WHERE r.url = ?1 ORDER BY q.recorded_at DESC, q.id DESC LIMIT 1",
+            params![url],
+            |row| {
+                Ok(SearchResult {
+                    title: row.get(0)?,
+                    url: row.get(1)?,
+                    snippet: row.get(2)?,
+                    content: row.get(3)?,
+                    published_date: None,
+                    metadata: Default::default(),
+                })
+            },
+        )
+        .optional()
+        .map_err(storage_error)
+    }
+
+    fn select(
+        &self,
+        sql: &str,
+        args: impl rusqlite::Params,
+    ) -> Result<Vec<QueryRecord>, SearchError> {
+        let conn = self.conn.lock().unwrap();
+        let records = {
+            let mut stmt = conn.prepare(sql).map_err(storage_error)?;
+            let rows = stmt.query_map(args, read_record).map_err(storage_error)?;
+            rows.collect::<Result<Vec<_>, _>>().map_err(storage_error)?
+        };
+        records
+            .into_iter()
+            .map(|record| with_results(&conn, record))
+            .collect()
+    }
+}
+
+fn read_record(row: &Row<'_>) -> rusqlite::Result<QueryRecord> {
+    let params: String = row.get(2)?;
+    let tier: Option<String> = row.get(3)?;
+    let recorded_at: i64 = row.get(5)?;
+    Ok(QueryRecord {
+        id: row.get(0)?,
+        query: row.get(1)?,
+        params: serde_json::from_str(&params).unwrap_or(serde_json::Value::Null),
+        tier: tier.as_deref().and_then(parse_tier),
+        confidence: row.get::<_, Option<f64>>(4)?.map(|c| c as f32),
+        recorded_at: from_unix_secs(recorded_at),
+        results: Vec::new(),
+    })
+}
+
+fn read_saved_search(row: &Row<'_>) -> rusqlite::Result<SavedSearch> {
+    let params: String = row.get(2)?;
+    Ok(SavedSearch {
+        name: row.get(0)?,
+        query: row.get(1)?,
+        params: serde_json::from_str(&params).unwrap_or(serde_json::Value::Null),
+        created_at: from_unix_secs(row.get(3)?),
+    })
+}
+
+fn with_results(conn: &Connection, mut record: QueryRecord) -> Result<QueryRecord, SearchError> {
+    let mut stmt = conn
+        .prepare_cached(
+            "SELECT title, url, snippet, content FROM results WHERE query_id = ?1 ORDER BY rank",
+        )
+        .map_err(storage_error)?;
+    let rows = stmt
+        .query_map(params![record.id], |row| {
+            Ok(SearchResult {
+                title: row.get(0)?,
+                url: row.get(1)?,
+                snippet: row.get(2)?,
+                content: row.get(3)?,
+                published_date: None,
+                metadata: Default::default(),
+            })
+        })
+        .map_err(storage_error)?;
+    record.results = rows.collect::<Result<_, _>>().map_err(storage_error)?;
+    Ok(record)
+}
+
+pub(crate) fn tier_name(tier: RetrievalTier) -> &'static str {
+    match tier {
+        RetrievalTier::L1 => "L1",
+        RetrievalTier::L2 => "L2",
+        RetrievalTier::L3 => "L3",
+    }
+}
+
+fn parse_tier(name: &str) -> Option<RetrievalTier> {
+    match name {
+        "L1" => Some(RetrievalTier::L1),
+        "L2" => Some(RetrievalTier::L2),
+        "L3" => Some(RetrievalTier::L3),
+        _ => None,
+    }
+}
+
+pub(crate) fn unix_secs(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as i64
+}
+
+fn from_unix_secs(secs: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs.max(0) as u64)
+}
+
+pub(crate) fn storage_error(e: rusqlite::Error) -> SearchError {
+    SearchError::StorageError(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(title: &str) -> SearchResult {
+        SearchResult {
+            title: title.to_string(),
+            url: format!("https://example.com/{}", title),
+            snippet: Some(format!("{} snippet", title)),
+            content: None,
+            published_date: None,
+            metadata: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_record_and_get() {
+        let store = ResultStore::in_memory().unwrap();
+        let params = serde_json::json!({ "engine": "duckduckgo", "num": 2 });
+        let id = store
+            .record("rust async", &params, Some(RetrievalTier::L2), Some(0.9), &[result("a"), result("b")])
+            .unwrap();
+
+        let record = store.get(id).unwrap().unwrap();
+        assert_eq!(record.query, "rust async");
+        assert_eq!(record.params, params);
+        assert_eq!(record.tier, Some(RetrievalTier::L2));
+        assert!((record.confidence.unwrap() - 0.9).abs() < 1e-6);
+        let titles: Vec<_> = record.results.iter().map(|r| r.title.as_str()).collect();
+        assert_eq!(titles, vec!["a", "b"]);
+        assert!(store.get(id + 1).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_recent_and_by_query() {
+        let store = ResultStore::in_memory().unwrap();
+        let params = serde_json::json!({});
+        store.record("rust", &params, None, None, &[]).unwrap();
+        store.record("tokio", &params, None, None, &[result("t")]).unwrap();
+        let last = store.record("rust", &params, None, None, &[]).unwrap();
+
+        let recent = store.recent(2).unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].id, last);
+        assert_eq!(recent[1].results.len(), 1);
+
+        let rust = store.by_query("rust").unwrap();
+        assert_eq!(rust.len(), 2);
+        assert!(rust.iter().all(|r| r.tier.is_none() && r.confidence.is_none()));
+    }
+
+    #[test]
+    fn test_between() {
+        let store = ResultStore::in_memory().unwrap();
+        store.record("rust", &serde_json::json!({}), None, None, &[]).unwrap();
+
+        let now = SystemTime::now();
+        let hour = Duration::from_secs(3600);
+        assert_eq!(store.between(now - hour, now + hour).unwrap().len(), 1);
+        assert!(store.between(now - 2 * hour, now - hour).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_engine_calls() {
+        let store = ResultStore::in_memory().unwrap();
+        let id = store.record("rust", &serde_json::json!({}), None, None, &[]).unwrap();
+        store.record_call(id, "duckduckgo", Duration::from_millis(420), Some(false)).unwrap();
+        store.record_call(id, "exa", Duration::from_millis(900), None).unwrap();
+
+        let now = SystemTime::now();
+        let hour = Duration::from_secs(3600);
+        let calls = store.calls_between(now - hour, now + hour).unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].query_id, id);
+        assert_eq!(calls[0].engine, "duckduckgo");
+        assert_eq!(calls[0].latency, Duration::from_millis(420));
+        assert_eq!(calls[0].cache_hit, Some(false));
+        assert_eq!(calls[1].cache_hit, None);
+        assert!(store.calls_between(now - 2 * hour, now - hour).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_saved_searches() {
+        let store = ResultStore::in_memory().unwrap();
+        store.save_search("cve", "xz backdoor", &serde_json::json!({ "engine": "exa" })).unwrap();
+        store.save_search("async", "tokio vs smol", &serde_json::json!({})).unwrap();
+        store.save_search("cve", "CVE-2024-3094", &serde_json::json!({ "num": 5 })).unwrap();
+
+        let cve = store.saved_search("cve").unwrap().unwrap();
+        assert_eq!(cve.query, "CVE-2024-3094");
+        assert_eq!(cve.params, serde_json::json!({ "num": 5 }));
+        let names: Vec<_> = store.saved_searches().unwrap().into_iter().map(|s| s.name).collect();
+        assert_eq!(names, vec!["async", "cve"]);
+
+        assert!(store.delete_saved_search("cve").unwrap());
+        assert!(!store.delete_saved_search("cve").unwrap());
+        assert!(store.saved_search("cve").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_bookmarks() {
+        let store = ResultStore::in_memory().unwrap();
+        store.record("rust", &serde_json::json!({}), None, None, &[result("a")]).unwrap();
+        let found = store.find_result("https://example.com/a").unwrap().unwrap();
+        assert_eq!(found.snippet.as_deref(), Some("a snippet"));
+        assert!(store.find_result("https://example.com/z").unwrap().is_none());
+
+        let id = store.add_bookmark(&found, Some("rust"), Some("good intro")).unwrap();
+        let other = store.add_bookmark(&result("b"), None, None).unwrap();
+        // 再次加入同一網址只更新，未提供的筆記保留原值
+        assert_eq!(store.add_bookmark(&found, None, None).unwrap(), id);
+
+        let bookmarks = store.bookmarks().unwrap();
+        assert_eq!(bookmarks.len(), 2);
+        let a = bookmarks.iter().find(|b| b.id == id).unwrap();
+        assert_eq!((a.note.as_deref(), a.query.as_deref()), (Some("good intro"), Some("rust")));
+
+        assert!(store.remove_bookmark(other).unwrap());
+        assert!(!store.remove_bookmark(other).unwrap());
+        assert_eq!(store.bookmarks().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_open_persists() {
+        let path = std::env::temp_dir().join(format!("bose-history-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        {
+            let store = ResultStore::open(&path).unwrap();
+            store.record("rust", &serde_json::json!({}), None, None, &[result("a")]).unwrap();
+        }
+        let store = ResultStore::open(&path).unwrap();
+        assert_eq!(store.by_query("rust").unwrap()[0].results.len(), 1);
+        let _ = std::fs::remove_file(&path);
+    }
+}
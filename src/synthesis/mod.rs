@@ -0,0 +1,360 @@
+//! 答案合成 - 由排序後的結果與抽取內容產生附編號引用的答案
+//!
+//! 設定 LLM 時由模型撰寫答案並以 `[n]` 標註來源；未設定時退回抽取式合成，
+//! 從各來源挑出最符合查詢的句子。兩種方式都只保留實際被引用的來源，並依首次出現重新編號。
+//! `CitationVerifier` 可再比對被引用頁面的文字，標記找不到依據的引用。
+
+mod verify;
+
+pub use verify::{CitationCheck, CitationStatus, CitationVerifier, VerificationReport};
+
+use crate::llm::{Completion, CompletionRequest};
+use crate::processing::context_pruner::{join_sentences, query_terms, split_sentences};
+use crate::processing::{HeuristicTokenizer, Tokenizer};
+use crate::types::{SearchError, SearchResult};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// 一個引用來源
+#[derive(Debug, Clone, PartialEq)]
+pub struct Citation {
+    /// 答案中的編號（從 1 起算）
+    pub number: usize,
+    pub url: String,
+    pub title: String,
+}
+
+/// 附引用的答案
+#[derive(Debug, Clone, PartialEq)]
+pub struct SynthesizedAnswer {
+    /// 句子後以 `[n]` 標註來源
+    pub text: String,
+    pub citations: Vec<Citation>,
+}
+
+impl fmt::Display for SynthesizedAnswer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.text)?;
+        if !self.citations.is_empty() {
+            writeln!(f)?;
+        }
+        for citation in &self.citations {
+            writeln!(f, "[{}] {} — {}", citation.number, citation.title, citation.url)?;
+        }
+        Ok(())
+    }
+}
+
+const SYNTHESIS_SYSTEM: &str = "You answer research questions using only the numbered sources provided. \
+After every sentence, cite the sources that support it as [n] or [n, m]. \
+Do not cite anything that is not in the sources. If the sources do not answer the question, say so. \
+Answer in the language of the question.";
+
+/// 答案合成器
+pub struct Synthesizer {
+    completion: Option<(Arc<dyn Completion>, String)>,
+    tokenizer: Arc<dyn Tokenizer>,
+    /// 最多使用前幾個結果
+    pub max_sources: usize,
+    /// 每個來源送給 LLM 的 token 上限
+    pub tokens_per_source: usize,
+    /// 答案的 token 上限
+    pub answer_tokens: usize,
+    /// 抽取式合成最多取幾句
+    pub max_sentences: usize,
+}
+
+impl Default for Synthesizer {
+    fn default() -> Self {
+        Self::extractive()
+    }
+}
+
+impl Synthesizer {
+    /// 抽取式合成（不需 LLM）
+    pub fn extractive() -> Self {
+        Self {
+            completion: None,
+            tokenizer: Arc::new(HeuristicTokenizer),
+            max_sources: 8,
+            tokens_per_source: 600,
+            answer_tokens: 800,
+            max_sentences: 6,
+        }
+    }
+
+    /// 由 LLM 撰寫答案
+    pub fn with_completion(mut self, completion: Arc<dyn Completion>, model: &str) -> Self {
+        self.completion = Some((completion, model.to_string()));
+        self
+    }
+
+    pub fn with_tokenizer(mut self, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    /// 合成答案；`results` 應已依相關性排序，內容取 `content`，沒有則用摘要
+    pub async fn synthesize(&self, query: &str, results: &[SearchResult]) -> Result<SynthesizedAnswer, SearchError> {
+        let sources: Vec<&SearchResult> = results
+            .iter()
+            .filter(|r| source_text(r).is_some_and(|t| !t.trim().is_empty()))
+            .take(self.max_sources)
+            .collect();
+        if sources.is_empty() {
+            return Ok(SynthesizedAnswer {
+                text: String::new(),
+                citations: Vec::new(),
+            });
+        }
+
+        let text = match &self.completion {
+            Some((completion, model)) => self.generate(completion.as_ref(), model, query, &sources).await?,
+            None => self.extract(query, &sources),
+        };
+        Ok(renumber(&text, &sources))
+    }
+
+    async fn generate(
+        &self,
+        completion: &dyn Completion,
+        model: &str,
+        query: &str,
+        sources: &[&SearchResult],
+    ) -> Result<String, SearchError> {
+        let mut prompt = String::from("Sources:\n\n");
+        for (i, source) in sources.iter().enumerate() {
+            let content = self.tokenizer.truncate(source_text(source).unwrap_or(""), self.tokens_per_source);
+            prompt.push_str(&format!("[{}] {} ({})\n{}\n\n", i + 1, source.title, source.url, content.trim()));
+        }
+        prompt.push_str(&format!("Question: {}", query));
+
+        let request = CompletionRequest::new(model, &prompt)
+            .with_system(SYNTHESIS_SYSTEM)
+            .with_max_tokens(self.answer_tokens);
+        completion.complete(&request).await
+    }
+
+    /// 依排名輪流從各來源取最相關的句子（每個來源最多兩句），略過重複句
+    fn extract(&self, query: &str, sources: &[&SearchResult]) -> String {
+        let terms = query_terms(query);
+        let ranked: Vec<Vec<&str>> = sources
+            .iter()
+            .map(|source| {
+                let mut sentences: Vec<(usize, usize, &str)> = split_sentences(source_text(source).unwrap_or(""))
+                    .into_iter()
+                    .enumerate()
+                    .map(|(position, sentence)| {
+                        let lower = sentence.to_lowercase();
+                        let hits = terms.iter().filter(|t| lower.contains(t.as_str())).count();
+                        (hits, position, sentence)
+                    })
+                    .filter(|(hits, position, _)| *hits > 0 || (terms.is_empty() && *position == 0))
+                    .collect();
+                sentences.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+                sentences.into_iter().take(2).map(|(_, _, s)| s).collect()
+            })
+            .collect();
+
+        let mut picked: Vec<String> = Vec::new();
+        let mut seen: Vec<String> = Vec::new();
+        let mut tokens = 0;
+        'rounds: for round in 0..2 {
+            for (i, sentences) in ranked.iter().enumerate() {
+                let Some(sentence) = sentences.get(round) else {
+                    continue;
+                };
+                let key = sentence.to_lowercase();
+                if seen.contains(&key) {
+                    continue;
+                }
+                let cited = cite(sentence, i + 1);
+                tokens += self.tokenizer.count(&cited);
+                if picked.len() >= self.max_sentences || (tokens > self.answer_tokens && !picked.is_empty()) {
+                    break 'rounds;
+                }
+                seen.push(key);
+                picked.push(cited);
+            }
+        }
+        join_sentences(picked.iter().map(String::as_str))
+    }
+}
+
+/// 在句尾標點前加上引用標記
+fn cite(sentence: &str, number: usize) -> String {
+    let sentence = sentence.trim();
+    match sentence.char_indices().next_back() {
+        Some((i, c)) if ".!?。！？".contains(c) => format!("{} [{}]{}", &sentence[..i], number, &sentence[i..]),
+        _ => format!("{} [{}]", sentence, number),
+    }
+}
+
+fn source_text(result: &SearchResult) -> Option<&str> {
+    result.content.as_deref().or(result.snippet.as_deref())
+}
+
+/// 解析 `[n]` / `[n, m]` 標記，只保留有效編號並依首次出現重新編號
+fn renumber(text: &str, sources: &[&SearchResult]) -> SynthesizedAnswer {
+    let mut mapping: HashMap<usize, usize> = HashMap::new();
+    let mut citations = Vec::new();
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(open) = rest.find('[') {
+        let (before, after) = rest.split_at(open);
+        let Some(close) = after.find(']') else {
+            break;
+        };
+        let inner = &after[1..close];
+        let numbers: Option<Vec<usize>> = inner.split(',').map(|n| n.trim().parse().ok()).collect();
+        let Some(numbers) = numbers.filter(|n| !n.is_empty()) else {
+            // 不是引用標記，原樣保留
+            output.push_str(before);
+            output.push('[');
+            rest = &after[1..];
+            continue;
+        };
+
+        let mut renumbered: Vec<usize> = Vec::new();
+        for n in numbers.into_iter().filter(|n| (1..=sources.len()).contains(n)) {
+            let next = mapping.len() + 1;
+            let number = *mapping.entry(n).or_insert_with(|| {
+                let source = sources[n - 1];
+                citations.push(Citation {
+                    number: next,
+                    url: source.url.clone(),
+                    title: source.title.clone(),
+                });
+                next
+            });
+            if !renumbered.contains(&number) {
+                renumbered.push(number);
+            }
+        }
+
+        if renumbered.is_empty() {
+            output.push_str(before.trim_end());
+        } else {
+            output.push_str(before);
+            let joined: Vec<String> = renumbered.iter().map(usize::to_string).collect();
+            output.push_str(&format!("[{}]", joined.join(", ")));
+        }
+        rest = &after[close + 1..];
+    }
+    output.push_str(rest);
+
+    SynthesizedAnswer {
+        text: output.trim().to_string(),
+        citations,
+    }
+}
+
+/// 將已編號答案中的 `[n]` / `[n, m]` 換成 `map(n)`，用於把多個答案併入同一份來源清單
+pub(crate) fn map_citations(text: &str, mut map: impl FnMut(usize) -> usize) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open) = rest.find('[') {
+        let (before, after) = rest.split_at(open);
+        output.push_str(before);
+        let marker = after.find(']').and_then(|close| {
+            let numbers: Option<Vec<usize>> = after[1..close].split(',').map(|n| n.trim().parse().ok()).collect();
+            numbers.filter(|n| !n.is_empty()).map(|numbers| (close, numbers))
+        });
+        match marker {
+            Some((close, numbers)) => {
+                let mapped: Vec<String> = numbers.into_iter().map(|n| map(n).to_string()).collect();
+                output.push_str(&format!("[{}]", mapped.join(", ")));
+                rest = &after[close + 1..];
+            }
+            None => {
+                output.push('[');
+                rest = &after[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::CompletionFuture;
+
+    fn result(url: &str, content: &str) -> SearchResult {
+        SearchResult {
+            title: format!("Title {}", url),
+            url: url.to_string(),
+            snippet: None,
+            content: Some(content.to_string()),
+            published_date: None,
+            metadata: Default::default(),
+        }
+    }
+
+    struct Fixed(&'static str);
+
+    impl Completion for Fixed {
+        fn complete<'a>(&'a self, request: &'a CompletionRequest) -> CompletionFuture<'a> {
+            assert!(request.prompt.contains("[2] Title https://b"));
+            Box::pin(async move { Ok(self.0.to_string()) })
+        }
+
+        fn name(&self) -> &'static str {
+            "fixed"
+        }
+    }
+
+    #[test]
+    fn test_renumber_in_order_of_appearance() {
+        let a = result("https://a", "");
+        let b = result("https://b", "");
+        let c = result("https://c", "");
+        let answer = renumber("Tokio is fast [3]. It steals work [1, 3] [9]. See [docs].", &[&a, &b, &c]);
+        assert_eq!(answer.text, "Tokio is fast [1]. It steals work [2, 1]. See [docs].");
+        assert_eq!(answer.citations.len(), 2);
+        assert_eq!(answer.citations[0].url, "https://c");
+        assert_eq!(answer.citations[1].number, 2);
+        assert_eq!(answer.citations[1].url, "https://a");
+    }
+
+    #[test]
+    fn test_map_citations() {
+        let mapped = map_citations("Tokio is fast [1]. It steals work [2, 1]. See [docs].", |n| n + 4);
+        assert_eq!(mapped, "Tokio is fast [5]. It steals work [6, 5]. See [docs].");
+    }
+
+    #[tokio::test]
+    async fn test_llm_synthesis() {
+        let results = vec![result("https://a", "Tokio is a runtime."), result("https://b", "It uses work stealing.")];
+        let answer = Synthesizer::extractive()
+            .with_completion(Arc::new(Fixed("Tokio uses work stealing [2].")), "m")
+            .synthesize("how does tokio schedule", &results)
+            .await
+            .unwrap();
+        assert_eq!(answer.text, "Tokio uses work stealing [1].");
+        assert_eq!(answer.citations[0].url, "https://b");
+        assert!(answer.to_string().ends_with("[1] Title https://b — https://b\n"));
+    }
+
+    #[tokio::test]
+    async fn test_extractive_synthesis() {
+        let results = vec![
+            result("https://a", "Welcome to our site. Tokio is an async runtime for Rust. Subscribe now."),
+            result("https://b", "Cookies policy. The runtime schedules tasks with work stealing."),
+            result("https://c", "Unrelated text only."),
+            result("https://d", "Tokio is an async runtime for Rust."),
+        ];
+        let answer = Synthesizer::extractive().synthesize("tokio runtime", &results).await.unwrap();
+        assert_eq!(
+            answer.text,
+            "Tokio is an async runtime for Rust [1]. The runtime schedules tasks with work stealing [2]."
+        );
+        assert_eq!(answer.citations.len(), 2);
+
+        let empty = Synthesizer::extractive().synthesize("tokio", &[]).await.unwrap();
+        assert!(empty.text.is_empty() && empty.citations.is_empty());
+    }
+}
@@ -0,0 +1,302 @@
+//! 引用驗證 - 檢查每個附引用的句子是否真的能在被引用頁面的文字中找到依據
+//!
+//! 句中以引號標出的原文須在頁面中逐字出現（忽略大小寫與空白）；其餘句子以詞彙重疊做模糊比對，
+//! 取頁面中最相近的句子作為依據。找不到依據或頁面無法取得的引用會被標記出來。
+
+use super::SynthesizedAnswer;
+use crate::fetcher::Fetcher;
+use crate::processing::context_pruner::{query_terms, split_sentences};
+use std::collections::HashMap;
+
+/// 單一引用的驗證結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CitationStatus {
+    /// 頁面中找到足夠相近的句子或引號原文
+    Verified,
+    /// 頁面中找不到依據
+    Unsupported,
+    /// 沒有頁面內容可供比對（未抓取或抓取失敗）
+    Unavailable,
+}
+
+/// 一個句子對一個引用的檢查
+#[derive(Debug, Clone, PartialEq)]
+pub struct CitationCheck {
+    /// 去除 `[n]` 標記後的句子
+    pub claim: String,
+    /// 答案中的引用編號
+    pub number: usize,
+    pub url: String,
+    pub status: CitationStatus,
+    /// 頁面中最相近的句子
+    pub quote: Option<String>,
+    /// 句子詞彙在依據中出現的比例（0.0 - 1.0）
+    pub score: f32,
+}
+
+/// 整份答案的驗證結果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VerificationReport {
+    pub checks: Vec<CitationCheck>,
+}
+
+impl VerificationReport {
+    /// 未通過驗證的引用
+    pub fn flagged(&self) -> impl Iterator<Item = &CitationCheck> {
+        self.checks.iter().filter(|c| c.status != CitationStatus::Verified)
+    }
+
+    pub fn all_verified(&self) -> bool {
+        self.flagged().next().is_none()
+    }
+}
+
+/// 引用驗證器
+#[derive(Debug, Clone)]
+pub struct CitationVerifier {
+    /// 通過驗證所需的最低詞彙重疊比例
+    pub min_overlap: f32,
+}
+
+impl Default for CitationVerifier {
+    fn default() -> Self {
+        Self { min_overlap: 0.6 }
+    }
+}
+
+impl CitationVerifier {
+    pub fn new(min_overlap: f32) -> Self {
+        Self { min_overlap }
+    }
+
+    /// 以已擷取的頁面文字驗證；`pages` 以網址對應純文字
+    pub fn verify(&self, answer: &SynthesizedAnswer, pages: &HashMap<String, String>) -> VerificationReport {
+        let mut checks = Vec::new();
+        for (claim, numbers) in claims(&answer.text) {
+            for number in numbers {
+                let Some(citation) = answer.citations.iter().find(|c| c.number == number) else {
+                    continue;
+                };
+                let check = match pages.get(&citation.url).filter(|t| !t.trim().is_empty()) {
+                    Some(text) => self.check(&claim, text),
+                    None => (CitationStatus::Unavailable, None, 0.0),
+                };
+                checks.push(CitationCheck {
+                    claim: claim.clone(),
+                    number,
+                    url: citation.url.clone(),
+                    status: check.0,
+                    quote: check.1,
+                    score: check.2,
+                });
+            }
+        }
+        VerificationReport { checks }
+    }
+
+    /// 抓取被引用的頁面後驗證；抓取失敗的引用標記為 `Unavailable`
+    pub async fn verify_fetched(&self, answer: &SynthesizedAnswer, fetcher: &Fetcher) -> VerificationReport {
+        let urls: Vec<String> = answer.citations.iter().map(|c| c.url.clone()).collect();
+        let mut pages = HashMap::new();
+        for (url, page) in urls.iter().zip(fetcher.fetch_all(&urls).await) {
+            match page {
+                Ok(page) => {
+                    pages.insert(url.clone(), page.cleaned_text());
+                }
+                Err(e) => tracing::debug!("citation source {} unavailable: {}", url, e),
+            }
+        }
+        self.verify(answer, &pages)
+    }
+
+    fn check(&self, claim: &str, text: &str) -> (CitationStatus, Option<String>, f32) {
+        let page = normalize(text);
+        let quotes = quoted(claim);
+        if !quotes.is_empty() {
+            let found = quotes.iter().all(|q| page.contains(&normalize(q)));
+            let status = if found { CitationStatus::Verified } else { CitationStatus::Unsupported };
+            let quote = found.then(|| quotes.join(" … "));
+            return (status, quote, if found { 1.0 } else { 0.0 });
+        }
+
+        let terms = query_terms(claim);
+        if terms.is_empty() {
+            return (CitationStatus::Unsupported, None, 0.0);
+        }
+        let sentences = split_sentences(text);
+        // 相鄰兩句合併比對，容許一個說法橫跨兩句
+        let windows = sentences
+            .iter()
+            .map(|s| s.to_string())
+            .chain(sentences.windows(2).map(|w| format!("{} {}", w[0], w[1])));
+        let mut best: Option<(f32, String)> = None;
+        for window in windows {
+            let lower = window.to_lowercase();
+            let hits = terms.iter().filter(|t| lower.contains(t.as_str())).count();
+            let score = hits as f32 / terms.len() as f32;
+            if best.as_ref().is_none_or(|(s, _)| score > *s) {
+                best = Some((score, window));
+            }
+        }
+        match best {
+            Some((score, quote)) if score >= self.min_overlap => (CitationStatus::Verified, Some(quote), score),
+            Some((score, _)) => (CitationStatus::Unsupported, None, score),
+            None => (CitationStatus::Unsupported, None, 0.0),
+        }
+    }
+}
+
+/// 將答案切成句子與其引用編號；句首的標記（句號後才標註）歸給前一句
+fn claims(text: &str) -> Vec<(String, Vec<usize>)> {
+    let mut claims: Vec<(String, Vec<usize>)> = Vec::new();
+    for sentence in split_sentences(text) {
+        let (leading, body) = leading_markers(sentence);
+        if let Some((_, numbers)) = claims.last_mut() {
+            for n in leading {
+                if !numbers.contains(&n) {
+                    numbers.push(n);
+                }
+            }
+        }
+        let (claim, numbers) = strip_markers(body);
+        if claim.chars().any(char::is_alphanumeric) {
+            claims.push((claim, numbers));
+        }
+    }
+    claims.retain(|(_, numbers)| !numbers.is_empty());
+    claims
+}
+
+/// 句首連續的 `[n]` 標記
+fn leading_markers(sentence: &str) -> (Vec<usize>, &str) {
+    let mut numbers = Vec::new();
+    let mut rest = sentence.trim_start();
+    while let Some(after) = rest.strip_prefix('[') {
+        let Some(close) = after.find(']') else {
+            break;
+        };
+        let parsed: Option<Vec<usize>> = after[..close].split(',').map(|n| n.trim().parse().ok()).collect();
+        match parsed {
+            Some(parsed) if !parsed.is_empty() => numbers.extend(parsed),
+            _ => break,
+        }
+        rest = after[close + 1..].trim_start();
+    }
+    (numbers, rest)
+}
+
+/// 去掉 `[n]` / `[n, m]` 標記，回傳剩下的句子與編號
+fn strip_markers(sentence: &str) -> (String, Vec<usize>) {
+    let mut numbers = Vec::new();
+    let mut claim = String::with_capacity(sentence.len());
+    let mut rest = sentence;
+    while let Some(open) = rest.find('[') {
+        let (before, after) = rest.split_at(open);
+        let Some(close) = after.find(']') else {
+            break;
+        };
+        let parsed: Option<Vec<usize>> = after[1..close].split(',').map(|n| n.trim().parse().ok()).collect();
+        match parsed {
+            Some(parsed) if !parsed.is_empty() => {
+                claim.push_str(before.trim_end());
+                for n in parsed {
+                    if !numbers.contains(&n) {
+                        numbers.push(n);
+                    }
+                }
+                rest = &after[close + 1..];
+            }
+            _ => {
+                claim.push_str(before);
+                claim.push('[');
+                rest = &after[1..];
+            }
+        }
+    }
+    claim.push_str(rest);
+    (claim.trim().to_string(), numbers)
+}
+
+/// 句中以引號標出的原文
+fn quoted(claim: &str) -> Vec<String> {
+    let mut quotes = Vec::new();
+    for (open, close) in [('"', '"'), ('“', '”'), ('「', '」')] {
+        let mut rest = claim;
+        while let Some(start) = rest.find(open) {
+            let after = &rest[start + open.len_utf8()..];
+            let Some(end) = after.find(close) else {
+                break;
+            };
+            let quote = after[..end].trim();
+            if quote.chars().count() >= 8 {
+                quotes.push(quote.to_string());
+            }
+            rest = &after[end + close.len_utf8()..];
+        }
+    }
+    quotes
+}
+
+/// 小寫並合併空白，讓逐字比對不受排版影響
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::synthesis::Citation;
+
+    fn answer(text: &str, urls: &[&str]) -> SynthesizedAnswer {
+        SynthesizedAnswer {
+            text: text.to_string(),
+            citations: urls
+                .iter()
+                .enumerate()
+                .map(|(i, url)| Citation {
+                    number: i + 1,
+                    url: url.to_string(),
+                    title: String::new(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_claims_attach_trailing_markers() {
+        let parsed = claims("Tokio is fast [1]. It steals work. [2, 1] See [docs].");
+        assert_eq!(parsed[0], ("Tokio is fast.".to_string(), vec![1]));
+        assert_eq!(parsed[1], ("It steals work.".to_string(), vec![2, 1]));
+        assert_eq!(parsed.len(), 2);
+    }
+
+    #[test]
+    fn test_verify_flags_unsupported_and_missing() {
+        let answer = answer(
+            "Tokio schedules tasks with work stealing [1]. Tokio was written in Go [1]. It has 40k stars [2].",
+            &["https://a", "https://b"],
+        );
+        let pages = HashMap::from([(
+            "https://a".to_string(),
+            "Tokio is a runtime. The scheduler uses work stealing to balance tasks.".to_string(),
+        )]);
+        let report = CitationVerifier::default().verify(&answer, &pages);
+        assert_eq!(report.checks.len(), 3);
+        assert_eq!(report.checks[0].status, CitationStatus::Verified);
+        assert!(report.checks[0].quote.as_deref().unwrap().contains("work stealing"));
+        assert_eq!(report.checks[1].status, CitationStatus::Unsupported);
+        assert_eq!(report.checks[2].status, CitationStatus::Unavailable);
+        assert_eq!(report.flagged().count(), 2);
+        assert!(!report.all_verified());
+    }
+
+    #[test]
+    fn test_verify_quoted_text_must_match() {
+        let pages = HashMap::from([("https://a".to_string(), "The  advisory says:\nupgrade to version 2.1 now.".to_string())]);
+        let verifier = CitationVerifier::default();
+        let ok = verifier.verify(&answer("The vendor says \"Upgrade to version 2.1 now\" [1].", &["https://a"]), &pages);
+        assert!(ok.all_verified());
+        let bad = verifier.verify(&answer("The vendor says \"upgrade to version 3.0 now\" [1].", &["https://a"]), &pages);
+        assert_eq!(bad.checks[0].status, CitationStatus::Unsupported);
+    }
+}
@@ -1,106 +1,451 @@
-use crate::types::{SearchError, SearchResult};
-use reqwest::Client;
-use serde_json::{json, Value};
+use crate::fetcher::metadata::SCORE;
+use crate::middleware::MiddlewareStack;
+use crate::optimization::connection_pool::proxied_client;
+use crate::optimization::{with_backoff, KeyPool, RateLimiter, RetryPolicy, RotationStrategy};
+use crate::processing::fill_missing_snippets;
+use crate::types::{lenient_vec, log_unknown_fields, SearchEngine, SearchError, SearchQuery, SearchResult, TimeRange};
+use crate::url::canonicalize;
+use reqwest::{Client, Response};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// 搜尋主題；`news` 只搜尋新聞來源，並可用 `days` 限定最近幾天
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TavilyTopic {
+    #[default]
+    General,
+    News,
+}
+
+/// `/search` 的請求（金鑰在送出時加入）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TavilySearchRequest {
+    pub query: String,
+    /// `basic` 或 `advanced`
+    pub search_depth: String,
+    pub max_results: usize,
+    pub include_answer: bool,
+    pub include_raw_content: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topic: Option<TavilyTopic>,
+    /// 只取最近幾天的結果（僅 `topic: news` 有效）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub days: Option<u32>,
+    /// 一般主題的時間範圍（新聞主題改用 `days`）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_range: Option<TimeRange>,
+}
+
+impl TavilySearchRequest {
+    pub fn new(query: &str, max_results: usize) -> Self {
+        Self {
+            query: query.to_string(),
+            search_depth: "advanced".to_string(),
+            max_results,
+            include_answer: true,
+            include_raw_content: true,
+            topic: None,
+            days: None,
+            time_range: None,
+        }
+    }
+
+    /// 改搜尋新聞；`days` 為 None 時使用 Tavily 的預設（3 天）
+    pub fn with_news(mut self, days: Option<u32>) -> Self {
+        self.topic = Some(TavilyTopic::News);
+        self.days = days;
+        self.time_range = None;
+        self
+    }
+
+    /// 由共用的查詢條件建立：類別 `news` 對應新聞主題，`time_range` 換算成 `days`
+    pub fn from_query(query: &SearchQuery) -> Self {
+        let request = Self::new(&query.query, query.num_results);
+        let is_news = query.category.as_deref().is_some_and(|c| c.trim().eq_ignore_ascii_case("news"));
+        if is_news {
+            request.with_news(query.time_range.map(|range| range.days().ceil() as u32))
+        } else {
+            Self {
+                time_range: query.time_range,
+                ..request
+            }
+        }
+    }
+}
+
+/// `/search` 的回應；未知的欄位保留在 `extra`
+#[derive(Debug, Clone, Deserialize)]
+pub struct TavilySearchResponse {
+    pub query: Option<String>,
+    /// `include_answer` 時的 LLM 摘要答案
+    pub answer: Option<String>,
+    /// 無法解析的單筆結果會被略過
+    #[serde(deserialize_with = "lenient_vec")]
+    pub results: Vec<TavilyResult>,
+    /// 秒數；Tavily 有時回傳字串，保留原值
+    pub response_time: Option<Value>,
+    pub images: Option<Value>,
+    pub follow_up_questions: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 單筆搜尋結果
+#[derive(Debug, Clone, Deserialize)]
+pub struct TavilyResult {
+    pub url: String,
+    pub title: Option<String>,
+    /// 與查詢相關的摘錄
+    pub content: Option<String>,
+    /// 全文（`include_raw_content`）
+    pub raw_content: Option<String>,
+    /// 相關度分數（0–1）
+    pub score: Option<f64>,
+    /// RFC 2822（新聞主題才有）
+    pub published_date: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl From<TavilyResult> for SearchResult {
+    fn from(result: TavilyResult) -> Self {
+        let mut metadata = BTreeMap::new();
+        if let Some(score) = result.score {
+            metadata.insert(SCORE.to_string(), format!("{:.4}", score));
+        }
+        SearchResult {
+            title: result.title.filter(|t| !t.trim().is_empty()).unwrap_or_else(|| "無標題".to_string()),
+            url: canonicalize(&result.url),
+            snippet: result.content,
+            content: result.raw_content,
+            published_date: result.published_date,
+            metadata,
+        }
+    }
+}
+
+/// 搜尋結果與 Tavily 的摘要答案
+#[derive(Debug, Clone, Default)]
+pub struct TavilySearch {
+    /// `include_answer` 時 Tavily 由結果生成的簡短答案
+    pub answer: Option<String>,
+    pub results: Vec<SearchResult>,
+}
+
+impl TavilySearch {
+    /// 只有全文時以 `query` 補上查詢導向摘要
+    pub fn from_response(response: TavilySearchResponse, query: &str) -> Self {
+        log_unknown_fields("Tavily", std::iter::once(&response.extra).chain(response.results.iter().map(|r| &r.extra)));
+        let mut results: Vec<SearchResult> = response.results.into_iter().map(SearchResult::from).collect();
+        fill_missing_snippets(&mut results, query);
+        Self {
+            answer: response.answer.filter(|a| !a.trim().is_empty()),
+            results,
+        }
+    }
+}
+
+/// `/extract` 的請求
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TavilyExtractRequest {
+    pub urls: Vec<String>,
+}
+
+/// `/extract` 的回應
+#[derive(Debug, Clone, Deserialize)]
+pub struct TavilyExtractResponse {
+    #[serde(deserialize_with = "lenient_vec")]
+    pub results: Vec<TavilyExtractResult>,
+    /// 無法提取的網址與原因
+    pub failed_results: Option<Value>,
+    pub response_time: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 單一網址的提取結果
+#[derive(Debug, Clone, Deserialize)]
+pub struct TavilyExtractResult {
+    pub url: String,
+    pub title: Option<String>,
+    pub raw_content: Option<String>,
+    pub images: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 提取結果保留原網址（與上一層的結果比對）
+impl From<TavilyExtractResult> for SearchResult {
+    fn from(result: TavilyExtractResult) -> Self {
+        SearchResult {
+            title: result.title.filter(|t| !t.trim().is_empty()).unwrap_or_else(|| "無標題".to_string()),
+            url: result.url,
+            snippet: None,
+            content: result.raw_content,
+            published_date: None,
+            metadata: BTreeMap::new(),
+        }
+    }
+}
 
 /// Tavily 搜尋客戶端（深度內容提取）
 pub struct TavilyClient {
     client: Client,
-    api_key: String,
+    keys: Arc<KeyPool>,
+    limiter: Option<Arc<RateLimiter>>,
+    retry: RetryPolicy,
+    middleware: MiddlewareStack,
+    /// `search` 改用新聞主題時的天數（`Some(None)` 為 Tavily 預設）
+    news: Option<Option<u32>>,
 }
 
 impl TavilyClient {
+    /// `api_key` 可為逗號分隔的多把金鑰，預設輪流使用
     pub fn new(api_key: &str) -> Self {
+        Self::with_key_pool(Arc::new(KeyPool::parse(api_key, RotationStrategy::RoundRobin)))
+    }
+
+    /// 使用金鑰池；429 / 402 時自動換下一把金鑰
+    pub fn with_key_pool(keys: Arc<KeyPool>) -> Self {
         Self {
             client: Client::new(),
-            api_key: api_key.to_string(),
+            keys,
+            limiter: None,
+            retry: RetryPolicy::default(),
+            middleware: MiddlewareStack::default(),
+            news: None,
         }
     }
 
+    /// 請求前先取得 token，並將 429 / `Retry-After` 回報給限流器自動降速
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.limiter = Some(limiter);
+        self
+    }
+
+    /// 經由代理（HTTP / SOCKS5）送出請求
+    pub fn with_proxy(mut self, url: &str) -> Result<Self, SearchError> {
+        self.client = proxied_client(url)?;
+        Ok(self)
+    }
+
+    /// 每個 HTTP 請求都經過這些中介層
+    pub fn with_middleware(mut self, middleware: MiddlewareStack) -> Self {
+        self.middleware = middleware;
+        self
+    }
+
+    /// 覆寫逾時、連線失敗與 5xx 的重試策略
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// `search`（包括作為檢索鏈的一層）改搜尋新聞，`days` 限定最近幾天
+    pub fn with_news(mut self, days: Option<u32>) -> Self {
+        self.news = Some(days);
+        self
+    }
+
+    /// 金鑰池（查詢每把金鑰的用量）
+    pub fn key_pool(&self) -> &Arc<KeyPool> {
+        &self.keys
+    }
+
     /// 執行搜尋
     pub async fn search(&self, query: &str, num_results: usize) -> Result<Vec<SearchResult>, SearchError> {
-        let url = "https://api.tavily.com/search";
-
-        let body = json!({
-            "api_key": self.api_key,
-            "query": query,
-            "search_depth": "advanced",
-            "max_results": num_results,
-            "include_answer": true,
-            "include_raw_content": true,
-        });
-
-        let response = self.client
-            .post(url)
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| SearchError::NetworkError(e.to_string()))?;
+        Ok(self.search_with_answer(query, num_results).await?.results)
+    }
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(SearchError::ApiError(format!("Tavily API 錯誤 {}: {}", status, error_text)));
+    /// 搜尋並保留 Tavily 的摘要答案
+    pub async fn search_with_answer(&self, query: &str, num_results: usize) -> Result<TavilySearch, SearchError> {
+        let mut request = TavilySearchRequest::new(query, num_results);
+        if let Some(days) = self.news {
+            request = request.with_news(days);
         }
+        self.search_request(&request).await
+    }
 
-        let json: Value = response
-            .json()
-            .await
-            .map_err(|e| SearchError::ParseError(e.to_string()))?;
+    /// 帶過濾條件的搜尋（新聞類別、時間範圍）
+    pub async fn search_query(&self, query: &SearchQuery) -> Result<TavilySearch, SearchError> {
+        self.search_request(&TavilySearchRequest::from_query(query)).await
+    }
 
-        let results = json["results"]
-            .as_array()
-            .ok_or_else(|| SearchError::ParseError("無法解析結果".to_string()))?
-            .iter()
-            .map(|r| SearchResult {
-                title: r["title"].as_str().unwrap_or("無標題").to_string(),
-                url: r["url"].as_str().unwrap_or("").to_string(),
-                snippet: r["content"].as_str().map(|s| s.to_string()),
-                content: r["raw_content"].as_str().map(|s| s.to_string()),
-            })
-            .collect();
+    /// 送出自訂的搜尋請求
+    pub async fn search_request(&self, request: &TavilySearchRequest) -> Result<TavilySearch, SearchError> {
+        let url = "https://api.tavily.com/search";
 
-        Ok(results)
+        let response: TavilySearchResponse = self.post_json(url, request, "Tavily API").await?;
+        Ok(TavilySearch::from_response(response, &request.query))
     }
 
     /// 提取指定 URL 的內容
     pub async fn extract_content(&self, urls: &[&str]) -> Result<Vec<SearchResult>, SearchError> {
         let url = "https://api.tavily.com/extract";
 
-        let body = json!({
-            "api_key": self.api_key,
-            "urls": urls,
-        });
+        let request = TavilyExtractRequest {
+            urls: urls.iter().map(|u| u.to_string()).collect(),
+        };
+        let response: TavilyExtractResponse = self.post_json(url, &request, "Tavily Extract API").await?;
+        log_unknown_fields(
+            "Tavily Extract",
+            std::iter::once(&response.extra).chain(response.results.iter().map(|r| &r.extra)),
+        );
 
-        let response = self.client
-            .post(url)
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| SearchError::NetworkError(e.to_string()))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(SearchError::ApiError(format!("Tavily Extract API 錯誤 {}: {}", status, error_text)));
-        }
+        Ok(response.results.into_iter().map(SearchResult::from).collect())
+    }
 
-        let json: Value = response
+    /// 序列化請求、送出並解析回應
+    async fn post_json<Req: Serialize, Resp: DeserializeOwned>(
+        &self,
+        url: &str,
+        request: &Req,
+        api: &str,
+    ) -> Result<Resp, SearchError> {
+        let body = serde_json::to_value(request).map_err(|e| SearchError::ParseError(e.to_string()))?;
+        self.post(url, body, api)
+            .await?
             .json()
             .await
-            .map_err(|e| SearchError::ParseError(e.to_string()))?;
-
-        let results = json["results"]
-            .as_array()
-            .ok_or_else(|| SearchError::ParseError("無法解析結果".to_string()))?
-            .iter()
-            .map(|r| SearchResult {
-                title: r["title"].as_str().unwrap_or("無標題").to_string(),
-                url: r["url"].as_str().unwrap_or("").to_string(),
-                snippet: None,
-                content: r["raw_content"].as_str().map(|s| s.to_string()),
-            })
-            .collect();
-
-        Ok(results)
+            .map_err(|e| SearchError::ParseError(format!("無法解析 {} 回應: {}", api, e)))
+    }
+
+    /// 送出請求：金鑰放進 body；429 / 402 且還有可用金鑰時立即換一把重送
+    async fn post(&self, url: &str, body: Value, api: &str) -> Result<Response, SearchError> {
+        with_backoff(&self.retry, || async {
+            let mut remaining = self.keys.len();
+            loop {
+                if let Some(limiter) = &self.limiter {
+                    limiter.acquire().await;
+                }
+                let lease = self
+                    .keys
+                    .next()
+                    .ok_or_else(|| SearchError::ConfigError("未設定 Tavily API 金鑰".to_string()))?;
+                remaining -= 1;
+
+                let mut body = body.clone();
+                body["api_key"] = Value::String(lease.key.clone());
+                let request = self.client.post(url).json(&body);
+                let response = self.middleware.send(SearchEngine::Tavily, &self.client, request).await?;
+                if let Some(limiter) = &self.limiter {
+                    limiter.observe(response.status(), response.headers());
+                }
+
+                if !response.status().is_success() {
+                    let status = response.status();
+                    if self.keys.observe(&lease, status, response.headers()) && remaining > 0 {
+                        continue;
+                    }
+                    let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+                    return Err(SearchError::ApiError(format!("{} 錯誤 {}: {}", api, status, error_text)));
+                }
+                return Ok(response);
+            }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_search_request_snapshot() {
+        let mut body = serde_json::to_value(TavilySearchRequest::new("tokio scheduler", 5)).unwrap();
+        body["api_key"] = Value::String("tvly-test".into());
+        insta::assert_json_snapshot!(body);
+    }
+
+    #[test]
+    fn test_search_response_snapshot() {
+        let response: TavilySearchResponse = serde_json::from_value(json!({
+            "query": "tokio scheduler",
+            "answer": "Tokio uses a work-stealing scheduler.",
+            "response_time": "1.67",
+            "images": [],
+            "request_id": "123e4567",
+            "results": [
+                {
+                    "title": "Making the Tokio scheduler 10x faster",
+                    "url": "https://tokio.rs/blog/2019-10-scheduler#intro",
+                    "content": "The new scheduler uses work stealing.",
+                    "raw_content": null,
+                    "score": 0.91234,
+                    "published_date": "Mon, 14 Oct 2019 00:00:00 GMT",
+                    "favicon": "https://tokio.rs/favicon.ico"
+                },
+                { "title": "Missing URL", "content": "dropped" },
+                { "title": "", "url": "https://docs.rs/tokio", "raw_content": "Tokio runtime docs" }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(response.answer.as_deref(), Some("Tokio uses a work-stealing scheduler."));
+        assert_eq!(response.results.len(), 2);
+        assert!(response.extra.contains_key("request_id"));
+        assert!(response.results[0].extra.contains_key("favicon"));
+
+        let results: Vec<SearchResult> = response.results.into_iter().map(SearchResult::from).collect();
+        insta::assert_json_snapshot!(results);
+    }
+
+    #[test]
+    fn test_news_request_and_query_mapping() {
+        let body = serde_json::to_value(TavilySearchRequest::new("rust 1.80 release", 5).with_news(Some(7))).unwrap();
+        assert_eq!(body["topic"], "news");
+        assert_eq!(body["days"], 7);
+        assert!(body.get("time_range").is_none());
+
+        let news = TavilySearchRequest::from_query(
+            &SearchQuery::new("rust release").with_category("News").with_time_range(TimeRange::Month),
+        );
+        assert_eq!((news.topic, news.days, news.time_range), (Some(TavilyTopic::News), Some(30), None));
+
+        let general = TavilySearchRequest::from_query(&SearchQuery::new("rust").with_time_range(TimeRange::Week));
+        let body = serde_json::to_value(&general).unwrap();
+        assert_eq!(body["time_range"], "week");
+        assert!(body.get("topic").is_none() && body.get("days").is_none());
+    }
+
+    #[test]
+    fn test_search_keeps_answer() {
+        let response: TavilySearchResponse = serde_json::from_value(json!({
+            "answer": "Rust 1.80 stabilized LazyCell and LazyLock.",
+            "results": [
+                { "title": "Announcing Rust 1.80.0", "url": "https://blog.rust-lang.org/2024/07/25/Rust-1.80.0.html",
+                  "raw_content": "The Rust team is happy to announce Rust 1.80.0. LazyLock is now stable." }
+            ]
+        }))
+        .unwrap();
+        let search = TavilySearch::from_response(response, "LazyLock");
+        assert_eq!(search.answer.as_deref(), Some("Rust 1.80 stabilized LazyCell and LazyLock."));
+        assert!(search.results[0].snippet.as_deref().is_some_and(|s| s.contains("LazyLock")));
+
+        let empty: TavilySearchResponse = serde_json::from_value(json!({ "answer": " ", "results": [] })).unwrap();
+        assert_eq!(TavilySearch::from_response(empty, "q").answer, None);
+    }
+
+    #[test]
+    fn test_extract_response_keeps_urls() {
+        let response: TavilyExtractResponse = serde_json::from_value(json!({
+            "results": [
+                { "url": "https://example.com/a?utm_source=x", "raw_content": "Full text" },
+                { "raw_content": "no url" }
+            ],
+            "failed_results": [{ "url": "https://example.com/b", "error": "timeout" }]
+        }))
+        .unwrap();
+        let results: Vec<SearchResult> = response.results.into_iter().map(SearchResult::from).collect();
+        assert_eq!(results.len(), 1);
+        // 提取結果要與上一層的網址對應，不做正規化
+        assert_eq!(results[0].url, "https://example.com/a?utm_source=x");
+        assert_eq!(results[0].title, "無標題");
+        assert!(response.failed_results.is_some());
     }
 }
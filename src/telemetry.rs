@@ -0,0 +1,330 @@
+//! 路由遙測 - 自願開啟的匿名資料集，用來訓練更好的路由與置信度模型
+//!
+//! 每筆樣本記錄（匿名化後的查詢、路由決策、使用層級、最終置信度、使用者回饋）。
+//! 只存在本機 SQLite，不會自動上傳；`export_jsonl()` 匯出為每行一筆 JSON 的資料集。
+//! 時間只保留到日期，查詢中的 email、網址、IP、路徑、長數字與金鑰會被替換為佔位符。
+
+use rusqlite::{params, Connection};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::routing::{QueryCategory, RetrievalTier, SearchStrategy, SemanticRouter, TaskComplexity};
+use crate::storage::{storage_error, tier_name, unix_secs};
+use crate::types::SearchError;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS samples (
+    id           INTEGER PRIMARY KEY AUTOINCREMENT,
+    query        TEXT NOT NULL,
+    complexity   TEXT NOT NULL,
+    strategy     TEXT NOT NULL,
+    category     TEXT NOT NULL,
+    tier         TEXT,
+    confidence   REAL,
+    result_count INTEGER NOT NULL,
+    feedback     TEXT,
+    recorded_day INTEGER NOT NULL
+);
+";
+
+/// 使用者對結果的回饋
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feedback {
+    Good,
+    Bad,
+}
+
+impl Feedback {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Feedback::Good => "good",
+            Feedback::Bad => "bad",
+        }
+    }
+}
+
+impl std::str::FromStr for Feedback {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "good" | "+" | "1" => Ok(Feedback::Good),
+            "bad" | "-" | "0" => Ok(Feedback::Bad),
+            other => Err(format!("無效的回饋 `{}`（good / bad）", other)),
+        }
+    }
+}
+
+/// 一筆遙測樣本
+#[derive(Debug, Clone, PartialEq)]
+pub struct TelemetrySample {
+    pub query: String,
+    pub complexity: TaskComplexity,
+    pub strategy: SearchStrategy,
+    pub category: QueryCategory,
+    pub tier: Option<RetrievalTier>,
+    pub confidence: Option<f32>,
+    pub result_count: usize,
+}
+
+impl TelemetrySample {
+    /// 以路由器的決策建立樣本；查詢會先匿名化
+    pub fn from_router(router: &SemanticRouter, query: &str, result_count: usize) -> Self {
+        let complexity = router.classify(query);
+        Self {
+            query: anonymize(query),
+            complexity,
+            strategy: router.select_search_strategy(complexity),
+            category: router.categorize(query),
+            tier: None,
+            confidence: None,
+            result_count,
+        }
+    }
+
+    /// 附上實際使用的層級與最終置信度
+    pub fn with_outcome(mut self, tier: Option<RetrievalTier>, confidence: Option<f32>) -> Self {
+        self.tier = tier;
+        self.confidence = confidence;
+        self
+    }
+}
+
+/// 本機遙測資料集
+pub struct TelemetryStore {
+    conn: Mutex<Connection>,
+}
+
+impl TelemetryStore {
+    /// 開啟（或建立）資料集檔案
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SearchError> {
+        Self::init(Connection::open(path).map_err(storage_error)?)
+    }
+
+    /// 記憶體資料集（測試用）
+    pub fn in_memory() -> Result<Self, SearchError> {
+        Self::init(Connection::open_in_memory().map_err(storage_error)?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, SearchError> {
+        conn.execute_batch(SCHEMA).map_err(storage_error)?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    /// 記錄一筆樣本，回傳 ID（供之後補上回饋）
+    ///
+    /// 查詢在寫入前會再匿名化一次，呼叫端直接建構的樣本也不會留下原始內容。
+    pub fn record(&self, sample: &TelemetrySample) -> Result<i64, SearchError> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO samples
+                 (query, complexity, strategy, category, tier, confidence, result_count, recorded_day)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                anonymize(&sample.query),
+                complexity_name(sample.complexity),
+                strategy_name(sample.strategy),
+                category_name(sample.category),
+                sample.tier.map(tier_name),
+                sample.confidence,
+                sample.result_count as i64,
+                unix_secs(SystemTime::now()) / 86_400,
+            ],
+        )
+        .map_err(storage_error)?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// 補上（或更新）使用者回饋；ID 不存在時回傳 false
+    pub fn set_feedback(&self, id: i64, feedback: Feedback) -> Result<bool, SearchError> {
+        let conn = self.conn.lock().unwrap();
+        let updated = conn
+            .execute(
+                "UPDATE samples SET feedback = ?1 WHERE id = ?2",
+                params![feedback.as_str(), id],
+            )
+            .map_err(storage_error)?;
+        Ok(updated > 0)
+    }
+
+    /// 樣本數
+    pub fn len(&self) -> Result<usize, SearchError> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row("SELECT COUNT(*) FROM samples", [], |row| row.get::<_, i64>(0))
+            .map(|n| n as usize)
+            .map_err(storage_error)
+    }
+
+    pub fn is_empty(&self) -> Result<bool, SearchError> {
+        Ok(self.len()? == 0)
+    }
+
+    /// 依 ID 順序匯出為 JSON Lines，回傳匯出筆數
+    pub fn export_jsonl(&self, out: &mut impl Write) -> Result<usize, SearchError> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, query, complexity, strategy, category, tier, confidence,
+                        result_count, feedback, recorded_day
+                 FROM samples ORDER BY id",
+            )
+            .map_err(storage_error)?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(serde_json::json!({
+                    "id": row.get::<_, i64>(0)?,
+                    "query": row.get::<_, String>(1)?,
+                    "complexity": row.get::<_, String>(2)?,
+                    "strategy": row.get::<_, String>(3)?,
+                    "category": row.get::<_, String>(4)?,
+                    "tier": row.get::<_, Option<String>>(5)?,
+                    "confidence": row.get::<_, Option<f64>>(6)?,
+                    "result_count": row.get::<_, i64>(7)?,
+                    "feedback": row.get::<_, Option<String>>(8)?,
+                    "day": row.get::<_, i64>(9)? * 86_400,
+                }))
+            })
+            .map_err(storage_error)?;
+
+        let mut count = 0;
+        for row in rows {
+            let line = row.map_err(storage_error)?;
+            writeln!(out, "{}", line).map_err(|e| SearchError::StorageError(e.to_string()))?;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+/// 匿名化查詢：以佔位符取代可能識別個人或洩漏秘密的詞
+pub fn anonymize(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|token| match sensitive_kind(token) {
+            Some(kind) => kind.to_string(),
+            None => token.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn sensitive_kind(token: &str) -> Option<&'static str> {
+    let trimmed = token.trim_matches(|c: char| matches!(c, '"' | '\'' | ',' | '(' | ')' | '<' | '>'));
+    let lower = trimmed.to_lowercase();
+
+    if lower.starts_with("http://") || lower.starts_with("https://") || lower.starts_with("www.") {
+        return Some("<url>");
+    }
+    if let Some((user, domain)) = trimmed.split_once('@')
+        && !user.is_empty()
+        && domain.contains('.')
+    {
+        return Some("<email>");
+    }
+    let octets: Vec<&str> = trimmed.split('.').collect();
+    if octets.len() == 4 && octets.iter().all(|o| !o.is_empty() && o.len() <= 3 && o.bytes().all(|b| b.is_ascii_digit())) {
+        return Some("<ip>");
+    }
+    if ["/home/", "/users/", "\\users\\", "~/"].iter().any(|p| lower.contains(p)) {
+        return Some("<path>");
+    }
+    if ["sk-", "ghp_", "gho_", "xoxb-", "xoxp-", "akia"].iter().any(|p| lower.starts_with(p))
+        || (trimmed.len() >= 24 && trimmed.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            && trimmed.chars().any(|c| c.is_ascii_digit())
+            && trimmed.chars().any(|c| c.is_ascii_alphabetic()))
+    {
+        return Some("<secret>");
+    }
+    if trimmed.chars().filter(|c| c.is_ascii_digit()).count() >= 6 {
+        return Some("<number>");
+    }
+    None
+}
+
+fn complexity_name(complexity: TaskComplexity) -> &'static str {
+    match complexity {
+        TaskComplexity::Simple => "simple",
+        TaskComplexity::Medium => "medium",
+        TaskComplexity::Complex => "complex",
+    }
+}
+
+fn strategy_name(strategy: SearchStrategy) -> &'static str {
+    match strategy {
+        SearchStrategy::SingleEngine => "single_engine",
+        SearchStrategy::TieredRetrieval => "tiered_retrieval",
+        SearchStrategy::DeepResearch => "deep_research",
+        SearchStrategy::Hybrid => "hybrid",
+    }
+}
+
+fn category_name(category: QueryCategory) -> &'static str {
+    match category {
+        QueryCategory::News => "news",
+        QueryCategory::Docs => "docs",
+        QueryCategory::General => "general",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anonymize() {
+        assert_eq!(
+            anonymize("email alice@example.com about https://intranet.corp/x from 10.0.0.12"),
+            "email <email> about <url> from <ip>"
+        );
+        assert_eq!(
+            anonymize("error in /home/alice/project key sk-abc123 ticket 48213377"),
+            "error in <path> key <secret> ticket <number>"
+        );
+        // 一般技術詞與年份保留
+        assert_eq!(anonymize("rust 2024 edition tokio::spawn"), "rust 2024 edition tokio::spawn");
+    }
+
+    #[test]
+    fn test_record_feedback_and_export() {
+        let store = TelemetryStore::in_memory().unwrap();
+        let router = SemanticRouter::with_defaults();
+
+        let sample = TelemetrySample::from_router(&router, "latest rust news for bob@corp.io", 7)
+            .with_outcome(Some(RetrievalTier::L2), Some(0.75));
+        assert_eq!(sample.query, "latest rust news for <email>");
+        assert_eq!(sample.category, QueryCategory::News);
+
+        let id = store.record(&sample).unwrap();
+        store
+            .record(&TelemetrySample::from_router(&router, "比較 Rust 和 Go 的效能差異", 3))
+            .unwrap();
+        assert!(store.set_feedback(id, Feedback::Good).unwrap());
+        assert!(!store.set_feedback(999, Feedback::Bad).unwrap());
+        assert_eq!(store.len().unwrap(), 2);
+
+        let mut out = Vec::new();
+        assert_eq!(store.export_jsonl(&mut out).unwrap(), 2);
+        let lines: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines[0]["query"], "latest rust news for <email>");
+        assert_eq!(lines[0]["category"], "news");
+        assert_eq!(lines[0]["strategy"], "single_engine");
+        assert_eq!(lines[0]["tier"], "L2");
+        assert_eq!(lines[0]["feedback"], "good");
+        assert_eq!(lines[0]["day"].as_i64().unwrap() % 86_400, 0);
+        assert_eq!(lines[1]["complexity"], "medium");
+        assert!(lines[1]["feedback"].is_null());
+    }
+
+    #[test]
+    fn test_feedback_parse() {
+        assert_eq!("Good".parse::<Feedback>(), Ok(Feedback::Good));
+        assert_eq!("-".parse::<Feedback>(), Ok(Feedback::Bad));
+        assert!("meh".parse::<Feedback>().is_err());
+    }
+}
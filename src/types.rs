@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// 搜尋結果的統一格式
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -7,22 +8,140 @@ pub struct SearchResult {
     pub url: String,
     pub snippet: Option<String>,
     pub content: Option<String>,
+    /// 發布日期（ISO 8601，例如 `2024-03-14` 或 `2024-03-14T08:00:00Z`）；引擎未提供時為 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_date: Option<String>,
+    /// 網站中繼資料（`site_name`、`favicon`、`og_title`、`og_description`、`og_image`）與引擎提供的
+    /// `author`、`score`，鍵見 `fetcher::metadata`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+}
+
+/// 搜尋請求與共用的過濾條件；各引擎只採用支援的欄位
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchQuery {
+    pub query: String,
+    pub num_results: usize,
+    /// 只搜尋這些網域
+    pub include_domains: Vec<String>,
+    /// 排除這些網域
+    pub exclude_domains: Vec<String>,
+    /// 內容類別（`news`、`research paper`、`github` ...）
+    pub category: Option<String>,
+    /// 相對的時間範圍；沒有 `start_date` 時換算成起始日期
+    pub time_range: Option<TimeRange>,
+    /// 發布日期下限（ISO 8601，`2024-03-14`）
+    pub start_date: Option<String>,
+    /// 發布日期上限（含當天）
+    pub end_date: Option<String>,
+}
+
+impl SearchQuery {
+    pub fn new(query: impl Into<String>) -> Self {
+        Self {
+            query: query.into(),
+            num_results: 10,
+            include_domains: Vec::new(),
+            exclude_domains: Vec::new(),
+            category: None,
+            time_range: None,
+            start_date: None,
+            end_date: None,
+        }
+    }
+
+    pub fn with_num_results(mut self, n: usize) -> Self {
+        self.num_results = n;
+        self
+    }
+
+    pub fn with_include_domains(mut self, domains: Vec<String>) -> Self {
+        self.include_domains = domains;
+        self
+    }
+
+    pub fn with_exclude_domains(mut self, domains: Vec<String>) -> Self {
+        self.exclude_domains = domains;
+        self
+    }
+
+    pub fn with_category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+
+    pub fn with_time_range(mut self, range: TimeRange) -> Self {
+        self.time_range = Some(range);
+        self
+    }
+
+    /// 發布日期區間；任一端可為 None
+    pub fn with_date_range(mut self, start: Option<&str>, end: Option<&str>) -> Self {
+        self.start_date = start.map(str::to_string);
+        self.end_date = end.map(str::to_string);
+        self
+    }
 }
 
 /// 搜尋引擎類型
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum SearchEngine {
     DuckDuckGo,  // 完全免費
     Tavily,      // 1000次/月免費
     Exa,         // $10 免費額度
 }
 
+impl SearchEngine {
+    /// 小寫名稱，用於紀錄與佐證來源
+    pub fn name(self) -> &'static str {
+        match self {
+            SearchEngine::DuckDuckGo => "duckduckgo",
+            SearchEngine::Tavily => "tavily",
+            SearchEngine::Exa => "exa",
+        }
+    }
+
+    /// 引擎可接受的查詢長度上限（字元數），超過時會被截斷或拒絕
+    pub fn max_query_chars(self) -> usize {
+        match self {
+            SearchEngine::DuckDuckGo => 500,
+            SearchEngine::Tavily => 400,
+            SearchEngine::Exa => 1000,
+        }
+    }
+}
+
+/// 查詢的時間範圍（與 SearXNG 的 `time_range` 相同）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeRange {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl TimeRange {
+    /// 範圍涵蓋的天數
+    pub fn days(self) -> f32 {
+        match self {
+            TimeRange::Day => 1.0,
+            TimeRange::Week => 7.0,
+            TimeRange::Month => 30.0,
+            TimeRange::Year => 365.0,
+        }
+    }
+}
+
 /// 搜尋錯誤類型
 #[derive(Debug)]
 pub enum SearchError {
     NetworkError(String),
     ApiError(String),
     ParseError(String),
+    StorageError(String),
+    ConfigError(String),
 }
 
 impl std::fmt::Display for SearchError {
@@ -31,8 +150,41 @@ impl std::fmt::Display for SearchError {
             SearchError::NetworkError(msg) => write!(f, "網路錯誤: {}", msg),
             SearchError::ApiError(msg) => write!(f, "API 錯誤: {}", msg),
             SearchError::ParseError(msg) => write!(f, "解析錯誤: {}", msg),
+            SearchError::StorageError(msg) => write!(f, "儲存錯誤: {}", msg),
+            SearchError::ConfigError(msg) => write!(f, "配置錯誤: {}", msg),
         }
     }
 }
 
 impl std::error::Error for SearchError {}
+
+/// 逐筆解析陣列：格式不符的項目記錄後略過，避免單筆結果的結構變動讓整個回應失敗
+pub(crate) fn lenient_vec<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::de::DeserializeOwned,
+{
+    let values = Vec::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(values
+        .into_iter()
+        .filter_map(|value| {
+            serde_json::from_value(value)
+                .inspect_err(|e| log::debug!("略過無法解析的項目: {}", e))
+                .ok()
+        })
+        .collect())
+}
+
+/// 記錄 API 回應中沒有對應欄位的鍵，作為結構變動的早期訊號
+pub(crate) fn log_unknown_fields<'a>(
+    api: &str,
+    extras: impl IntoIterator<Item = &'a serde_json::Map<String, serde_json::Value>>,
+) {
+    let mut keys: Vec<&str> = extras.into_iter().flat_map(|extra| extra.keys().map(String::as_str)).collect();
+    if keys.is_empty() {
+        return;
+    }
+    keys.sort_unstable();
+    keys.dedup();
+    log::debug!("{} 回應含未知欄位: {}", api, keys.join(", "));
+}
@@ -0,0 +1,365 @@
+//! 行程內 HNSW（Hierarchical Navigable Small World）索引
+//!
+//! 向量寫入時正規化，距離為 `1 - 內積`；刪除只做標記，節點仍保留在圖中供導航。
+
+use super::{ScoredChunk, StoreFuture, VectorRecord, VectorStore};
+use crate::embeddings::normalize;
+use crate::processing::Chunk;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::sync::RwLock;
+
+/// HNSW 參數
+#[derive(Debug, Clone)]
+pub struct HnswConfig {
+    /// 每層每個節點的鄰居上限（第 0 層為兩倍）
+    pub m: usize,
+    /// 建圖時的候選數
+    pub ef_construction: usize,
+    /// 查詢時的候選數（至少為 top_k）
+    pub ef_search: usize,
+    /// 層級亂數種子，固定種子讓建出的圖可重現
+    pub seed: u64,
+}
+
+impl Default for HnswConfig {
+    fn default() -> Self {
+        Self {
+            m: 16,
+            ef_construction: 100,
+            ef_search: 50,
+            seed: 0x5EED,
+        }
+    }
+}
+
+struct Node {
+    vector: Vec<f32>,
+    chunk: Chunk,
+    /// 每層的鄰居
+    neighbors: Vec<Vec<usize>>,
+    deleted: bool,
+}
+
+#[derive(Default)]
+struct Graph {
+    nodes: Vec<Node>,
+    /// 片段 id → 目前有效的節點
+    ids: HashMap<String, usize>,
+    entry: Option<usize>,
+    max_level: usize,
+    rng: u64,
+}
+
+/// 依距離排序的候選
+#[derive(Clone, Copy, PartialEq)]
+struct Candidate {
+    distance: f32,
+    node: usize,
+}
+
+impl Eq for Candidate {}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance.total_cmp(&other.distance).then(self.node.cmp(&other.node))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn distance(a: &[f32], b: &[f32]) -> f32 {
+    1.0 - a.iter().zip(b).map(|(x, y)| x * y).sum::<f32>()
+}
+
+impl Graph {
+    /// splitmix64，層級 = floor(-ln(u) / ln(m))
+    fn random_level(&mut self, m: usize) -> usize {
+        self.rng = self.rng.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut x = self.rng;
+        x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        x ^= x >> 31;
+        let unit = ((x >> 11) as f64 + 1.0) / (1u64 << 53) as f64;
+        (-unit.ln() / (m.max(2) as f64).ln()) as usize
+    }
+
+    fn distance_to(&self, query: &[f32], node: usize) -> f32 {
+        distance(query, &self.nodes[node].vector)
+    }
+
+    /// 單層貪婪搜尋，回傳最近的 `ef` 個候選（由近到遠）
+    fn search_layer(&self, query: &[f32], entries: &[usize], ef: usize, level: usize) -> Vec<Candidate> {
+        let mut visited: HashSet<usize> = entries.iter().copied().collect();
+        let mut frontier = BinaryHeap::new();
+        let mut best = BinaryHeap::new();
+        for &node in entries {
+            let candidate = Candidate {
+                distance: self.distance_to(query, node),
+                node,
+            };
+            frontier.push(Reverse(candidate));
+            best.push(candidate);
+        }
+
+        while let Some(Reverse(current)) = frontier.pop() {
+            if best.len() >= ef && best.peek().is_some_and(|worst: &Candidate| current.distance > worst.distance) {
+                break;
+            }
+            for &neighbor in &self.nodes[current.node].neighbors[level] {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+                let candidate = Candidate {
+                    distance: self.distance_to(query, neighbor),
+                    node: neighbor,
+                };
+                if best.len() < ef || best.peek().is_some_and(|worst| candidate.distance < worst.distance) {
+                    frontier.push(Reverse(candidate));
+                    best.push(candidate);
+                    if best.len() > ef {
+                        best.pop();
+                    }
+                }
+            }
+        }
+        best.into_sorted_vec()
+    }
+
+    /// 從最上層往下走到 `level + 1`，回傳第 `level` 層的入口
+    fn descend(&self, query: &[f32], level: usize) -> Option<usize> {
+        let mut entry = self.entry?;
+        for layer in (level + 1..=self.max_level).rev() {
+            entry = self.search_layer(query, &[entry], 1, layer)[0].node;
+        }
+        Some(entry)
+    }
+
+    fn insert(&mut self, config: &HnswConfig, mut vector: Vec<f32>, chunk: Chunk) {
+        normalize(&mut vector);
+        if let Some(old) = self.ids.remove(&chunk.id) {
+            self.nodes[old].deleted = true;
+        }
+
+        let level = self.random_level(config.m);
+        let node = self.nodes.len();
+        self.ids.insert(chunk.id.clone(), node);
+        self.nodes.push(Node {
+            vector,
+            chunk,
+            neighbors: vec![Vec::new(); level + 1],
+            deleted: false,
+        });
+
+        let Some(entry) = self.descend(&self.nodes[node].vector.clone(), level) else {
+            self.entry = Some(node);
+            self.max_level = level;
+            return;
+        };
+
+        let query = self.nodes[node].vector.clone();
+        let mut entries = vec![entry];
+        for layer in (0..=level.min(self.max_level)).rev() {
+            let candidates = self.search_layer(&query, &entries, config.ef_construction.max(1), layer);
+            let max_links = if layer == 0 { config.m * 2 } else { config.m };
+            let selected: Vec<usize> = candidates.iter().take(config.m).map(|c| c.node).collect();
+
+            self.nodes[node].neighbors[layer] = selected.clone();
+            for &neighbor in &selected {
+                self.nodes[neighbor].neighbors[layer].push(node);
+                if self.nodes[neighbor].neighbors[layer].len() > max_links {
+                    self.prune(neighbor, layer, max_links);
+                }
+            }
+            entries = candidates.iter().map(|c| c.node).collect();
+        }
+
+        if level > self.max_level {
+            self.max_level = level;
+            self.entry = Some(node);
+        }
+    }
+
+    /// 只保留最近的 `max_links` 個鄰居
+    fn prune(&mut self, node: usize, layer: usize, max_links: usize) {
+        let base = &self.nodes[node].vector;
+        let mut links: Vec<Candidate> = self.nodes[node].neighbors[layer]
+            .iter()
+            .map(|&n| Candidate {
+                distance: distance(base, &self.nodes[n].vector),
+                node: n,
+            })
+            .collect();
+        links.sort();
+        links.truncate(max_links);
+        self.nodes[node].neighbors[layer] = links.into_iter().map(|c| c.node).collect();
+    }
+
+    fn search(&self, config: &HnswConfig, query: &[f32], top_k: usize) -> Vec<ScoredChunk> {
+        let mut query = query.to_vec();
+        normalize(&mut query);
+        let Some(entry) = self.descend(&query, 0) else {
+            return Vec::new();
+        };
+        // 被刪除的節點仍會佔候選名額，多取一些
+        let deleted = self.nodes.len() - self.ids.len();
+        let ef = config.ef_search.max(top_k) + deleted.min(top_k * 4);
+        self.search_layer(&query, &[entry], ef, 0)
+            .into_iter()
+            .filter(|c| !self.nodes[c.node].deleted)
+            .take(top_k)
+            .map(|c| ScoredChunk {
+                chunk: self.nodes[c.node].chunk.clone(),
+                score: 1.0 - c.distance,
+            })
+            .collect()
+    }
+}
+
+/// 行程內 HNSW 向量庫
+pub struct HnswStore {
+    config: HnswConfig,
+    graph: RwLock<Graph>,
+}
+
+impl HnswStore {
+    pub fn new(config: HnswConfig) -> Self {
+        let graph = Graph {
+            rng: config.seed,
+            ..Graph::default()
+        };
+        Self {
+            config,
+            graph: RwLock::new(graph),
+        }
+    }
+
+    /// 有效（未刪除）的片段數
+    pub fn len(&self) -> usize {
+        self.graph.read().map(|g| g.ids.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl VectorStore for HnswStore {
+    fn upsert<'a>(&'a self, records: Vec<VectorRecord>) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            let mut graph = self.graph.write().unwrap_or_else(|e| e.into_inner());
+            for record in records {
+                graph.insert(&self.config, record.vector, record.chunk);
+            }
+            Ok(())
+        })
+    }
+
+    fn search<'a>(&'a self, vector: &'a [f32], top_k: usize) -> StoreFuture<'a, Vec<ScoredChunk>> {
+        Box::pin(async move {
+            let graph = self.graph.read().unwrap_or_else(|e| e.into_inner());
+            Ok(graph.search(&self.config, vector, top_k))
+        })
+    }
+
+    fn delete<'a>(&'a self, ids: &'a [String]) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            let mut graph = self.graph.write().unwrap_or_else(|e| e.into_inner());
+            for id in ids {
+                if let Some(node) = graph.ids.remove(id) {
+                    graph.nodes[node].deleted = true;
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: usize, vector: Vec<f32>) -> VectorRecord {
+        VectorRecord {
+            vector,
+            chunk: Chunk {
+                id: format!("{:016x}", id),
+                source_url: format!("https://example.com/{}", id),
+                index: 0,
+                start: 0,
+                end: 0,
+                text: String::new(),
+                tokens: 0,
+            },
+        }
+    }
+
+    /// 確定性的偽隨機向量
+    fn vectors(count: usize, dims: usize) -> Vec<Vec<f32>> {
+        let mut state: u64 = 42;
+        (0..count)
+            .map(|_| {
+                (0..dims)
+                    .map(|_| {
+                        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                        ((state >> 33) as f32 / (1u64 << 31) as f32) - 0.5
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_recall_against_brute_force() {
+        let data = vectors(500, 16);
+        let store = HnswStore::new(HnswConfig::default());
+        store
+            .upsert(data.iter().cloned().enumerate().map(|(i, v)| record(i, v)).collect())
+            .await
+            .unwrap();
+        assert_eq!(store.len(), 500);
+
+        let mut found = 0;
+        for query in data.iter().take(20) {
+            let mut exact: Vec<(f32, usize)> = data
+                .iter()
+                .enumerate()
+                .map(|(i, v)| (crate::embeddings::cosine_similarity(query, v), i))
+                .collect();
+            exact.sort_by(|a, b| b.0.total_cmp(&a.0));
+            let expected: HashSet<String> = exact.iter().take(10).map(|(_, i)| format!("{:016x}", i)).collect();
+
+            let hits = store.search(query, 10).await.unwrap();
+            assert!(hits.windows(2).all(|w| w[0].score >= w[1].score));
+            found += hits.iter().filter(|h| expected.contains(&h.chunk.id)).count();
+        }
+        // 200 個真正的近鄰中至少找回 90%
+        assert!(found >= 180, "recall {}/200", found);
+    }
+
+    #[tokio::test]
+    async fn test_upsert_replaces_and_delete_hides() {
+        let store = HnswStore::new(HnswConfig::default());
+        store
+            .upsert(vec![record(1, vec![1.0, 0.0]), record(2, vec![0.0, 1.0])])
+            .await
+            .unwrap();
+        store.upsert(vec![record(1, vec![0.0, 2.0])]).await.unwrap();
+        assert_eq!(store.len(), 2);
+
+        let hits = store.search(&[0.0, 1.0], 5).await.unwrap();
+        assert_eq!(hits.len(), 2);
+        assert!((hits[0].score - 1.0).abs() < 1e-6);
+        assert!((hits[1].score - 1.0).abs() < 1e-6);
+
+        store.delete(&[format!("{:016x}", 2)]).await.unwrap();
+        let hits = store.search(&[0.0, 1.0], 5).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].chunk.id, format!("{:016x}", 1));
+        assert!(HnswStore::new(HnswConfig::default()).search(&[1.0], 3).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,143 @@
+//! 本地 + 網路混合檢索的 RRF 融合
+//!
+//! 本地索引命中的片段依網址合併為一筆結果，再與網路結果以 Reciprocal Rank Fusion 排名；
+//! 每筆結果標記來自先前研究、這次搜尋或兩者皆有。
+
+use super::ScoredChunk;
+use crate::types::SearchResult;
+use std::collections::{HashMap, HashSet};
+
+/// RRF 常數 k（原論文建議值）
+pub const RRF_K: f64 = 60.0;
+
+/// 結果來源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResultOrigin {
+    /// 本地索引（先前研究抓取過的內容）
+    Local,
+    /// 這次的網路搜尋
+    Web,
+    /// 兩邊都有
+    Both,
+}
+
+/// 混合檢索的一筆結果
+#[derive(Debug, Clone)]
+pub struct HybridResult {
+    pub result: SearchResult,
+    pub origin: ResultOrigin,
+    /// RRF 融合分數
+    pub score: f64,
+}
+
+/// 本地片段轉為搜尋結果：同一網址只保留分數最高的片段，順序依分數
+pub fn local_results(hits: &[ScoredChunk]) -> Vec<SearchResult> {
+    let mut seen = HashSet::new();
+    let mut results: Vec<SearchResult> = Vec::new();
+    for hit in hits {
+        if !seen.insert(hit.chunk.source_url.as_str()) {
+            continue;
+        }
+        results.push(SearchResult {
+            title: hit.chunk.source_url.clone(),
+            url: hit.chunk.source_url.clone(),
+            snippet: Some(hit.chunk.text.clone()),
+            content: None,
+            published_date: None,
+            metadata: Default::default(),
+        });
+    }
+    results
+}
+
+/// 以 RRF 融合本地與網路結果
+///
+/// 每個結果得分為 `Σ 1 / (k + rank)`（rank 從 1 起算），依網址去重；
+/// 兩邊都有時保留網路版本（標題較完整）。同分時本地優先、再依首次出現順序。
+pub fn rrf_fuse(local: &[SearchResult], web: &[SearchResult], k: f64) -> Vec<HybridResult> {
+    let mut fused: Vec<HybridResult> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for (origin, list) in [(ResultOrigin::Local, local), (ResultOrigin::Web, web)] {
+        for (rank, result) in list.iter().enumerate() {
+            let contribution = 1.0 / (k + rank as f64 + 1.0);
+            match index.get(&result.url) {
+                Some(&i) => {
+                    let entry = &mut fused[i];
+                    entry.score += contribution;
+                    if entry.origin != origin {
+                        entry.origin = ResultOrigin::Both;
+                        entry.result = result.clone();
+                    }
+                }
+                None => {
+                    index.insert(result.url.clone(), fused.len());
+                    fused.push(HybridResult {
+                        result: result.clone(),
+                        origin,
+                        score: contribution,
+                    });
+                }
+            }
+        }
+    }
+
+    // sort_by 為穩定排序，同分保留原順序
+    fused.sort_by(|a, b| b.score.total_cmp(&a.score));
+    fused
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processing::Chunk;
+
+    fn result(url: &str) -> SearchResult {
+        SearchResult {
+            title: format!("web {}", url),
+            url: url.into(),
+            snippet: None,
+            content: None,
+            published_date: None,
+            metadata: Default::default(),
+        }
+    }
+
+    fn hit(url: &str, text: &str, score: f32) -> ScoredChunk {
+        ScoredChunk {
+            chunk: Chunk {
+                id: String::new(),
+                source_url: url.into(),
+                index: 0,
+                start: 0,
+                end: text.len(),
+                text: text.into(),
+                tokens: 0,
+            },
+            score,
+        }
+    }
+
+    #[test]
+    fn test_local_results_dedup_by_url() {
+        let hits = [hit("https://a", "first", 0.9), hit("https://b", "b", 0.8), hit("https://a", "second", 0.7)];
+        let results = local_results(&hits);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].snippet.as_deref(), Some("first"));
+    }
+
+    #[test]
+    fn test_rrf_fuse_marks_origin() {
+        let local = vec![result("https://shared"), result("https://local")];
+        let web = vec![result("https://web"), result("https://shared")];
+        let fused = rrf_fuse(&local, &web, RRF_K);
+
+        assert_eq!(fused[0].result.url, "https://shared");
+        assert_eq!(fused[0].origin, ResultOrigin::Both);
+        assert!((fused[0].score - (1.0 / 61.0 + 1.0 / 62.0)).abs() < 1e-12);
+        // 網路第 1 名高於本地第 2 名
+        assert_eq!(fused[1].origin, ResultOrigin::Web);
+        assert_eq!(fused[2].origin, ResultOrigin::Local);
+        assert!(rrf_fuse(&[], &[], RRF_K).is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+//! 向量庫 - 已抓取並分塊的內容建立索引，之後可直接語義查詢而不必重新上網
+//!
+//! - `HnswStore`：行程內 HNSW 圖索引，不需外部服務
+//! - `QdrantStore`：Qdrant REST API，索引可跨行程保留
+
+pub mod hybrid;
+mod hnsw;
+mod qdrant;
+
+pub use hnsw::{HnswConfig, HnswStore};
+pub use hybrid::{HybridResult, ResultOrigin};
+pub use qdrant::QdrantStore;
+
+use crate::embeddings::{embed_one, Embedder};
+use crate::processing::Chunk;
+use crate::types::SearchError;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// `VectorStore` 方法回傳的 future
+pub type StoreFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, SearchError>> + Send + 'a>>;
+
+/// 一筆待索引的向量，id 沿用 `Chunk::id`
+#[derive(Debug, Clone)]
+pub struct VectorRecord {
+    pub vector: Vec<f32>,
+    pub chunk: Chunk,
+}
+
+/// 查詢命中的片段，`score` 為餘弦相似度
+#[derive(Debug, Clone)]
+pub struct ScoredChunk {
+    pub chunk: Chunk,
+    pub score: f32,
+}
+
+/// 向量庫後端
+pub trait VectorStore: Send + Sync {
+    /// 新增或覆蓋（同 id）向量
+    fn upsert<'a>(&'a self, records: Vec<VectorRecord>) -> StoreFuture<'a, ()>;
+
+    /// 取最相似的 `top_k` 筆，依分數由高到低
+    fn search<'a>(&'a self, vector: &'a [f32], top_k: usize) -> StoreFuture<'a, Vec<ScoredChunk>>;
+
+    /// 依片段 id 刪除
+    fn delete<'a>(&'a self, ids: &'a [String]) -> StoreFuture<'a, ()>;
+}
+
+/// 嵌入模型 + 向量庫：索引片段並以文字查詢
+#[derive(Clone)]
+pub struct SemanticIndex {
+    embedder: Arc<dyn Embedder>,
+    store: Arc<dyn VectorStore>,
+}
+
+impl SemanticIndex {
+    pub fn new(embedder: Arc<dyn Embedder>, store: Arc<dyn VectorStore>) -> Self {
+        Self { embedder, store }
+    }
+
+    /// 嵌入並寫入片段
+    pub async fn index(&self, chunks: Vec<Chunk>) -> Result<usize, SearchError> {
+        if chunks.is_empty() {
+            return Ok(0);
+        }
+        let texts: Vec<String> = chunks.iter().map(|c| c.text.clone()).collect();
+        let vectors = self.embedder.embed(&texts).await?;
+        let count = chunks.len();
+        let records = vectors
+            .into_iter()
+            .zip(chunks)
+            .map(|(vector, chunk)| VectorRecord { vector, chunk })
+            .collect();
+        self.store.upsert(records).await?;
+        Ok(count)
+    }
+
+    /// 以文字查詢最相關的片段
+    pub async fn query(&self, text: &str, top_k: usize) -> Result<Vec<ScoredChunk>, SearchError> {
+        let vector = embed_one(self.embedder.as_ref(), text).await?;
+        self.store.search(&vector, top_k).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embeddings::HashingEmbedder;
+    use crate::processing::{Chunker, ChunkerConfig};
+
+    #[tokio::test]
+    async fn test_semantic_index_round_trip() {
+        let index = SemanticIndex::new(
+            Arc::new(HashingEmbedder::default()),
+            Arc::new(HnswStore::new(HnswConfig::default())),
+        );
+        let chunker = Chunker::new(ChunkerConfig::default());
+        let mut chunks = chunker.chunk("https://a.example", "Tokio is an async runtime for Rust.");
+        chunks.extend(chunker.chunk("https://b.example", "Sourdough bread needs a starter."));
+        assert_eq!(index.index(chunks).await.unwrap(), 2);
+
+        let hits = index.query("rust async runtime", 1).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].chunk.source_url, "https://a.example");
+    }
+}
@@ -0,0 +1,234 @@
+//! Qdrant REST API 後端
+//!
+//! 片段 id 為 16 位十六進位的 FNV-1a 雜湊，直接轉為 Qdrant 的 u64 點 id；整個 `Chunk` 存在 payload。
+
+use super::{ScoredChunk, StoreFuture, VectorRecord, VectorStore};
+use crate::processing::Chunk;
+use crate::types::SearchError;
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde_json::{json, Value};
+
+/// Qdrant 集合
+pub struct QdrantStore {
+    client: Client,
+    base_url: String,
+    collection: String,
+    api_key: Option<String>,
+}
+
+impl QdrantStore {
+    /// `base_url` 例如 `http://localhost:6333`
+    pub fn new(base_url: &str, collection: &str) -> Self {
+        Self {
+            client: Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            collection: collection.to_string(),
+            api_key: None,
+        }
+    }
+
+    pub fn with_api_key(mut self, api_key: &str) -> Self {
+        self.api_key = Some(api_key.to_string());
+        self
+    }
+
+    /// 集合不存在時以餘弦距離建立
+    pub async fn ensure_collection(&self, dimensions: usize) -> Result<(), SearchError> {
+        let url = self.collection_url("");
+        let response = self
+            .authorized(self.client.get(&url))
+            .send()
+            .await
+            .map_err(|e| SearchError::NetworkError(e.to_string()))?;
+        if response.status().is_success() {
+            return Ok(());
+        }
+        if response.status() != StatusCode::NOT_FOUND {
+            return Err(api_error(response).await);
+        }
+        self.send(
+            self.client
+                .put(&url)
+                .json(&json!({ "vectors": { "size": dimensions, "distance": "Cosine" } })),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    fn collection_url(&self, path: &str) -> String {
+        format!("{}/collections/{}{}", self.base_url, self.collection, path)
+    }
+
+    fn authorized(&self, request: RequestBuilder) -> RequestBuilder {
+        match &self.api_key {
+            Some(key) => request.header("api-key", key),
+            None => request,
+        }
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Value, SearchError> {
+        let response = self
+            .authorized(request)
+            .send()
+            .await
+            .map_err(|e| SearchError::NetworkError(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(api_error(response).await);
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| SearchError::ParseError(e.to_string()))
+    }
+}
+
+async fn api_error(response: reqwest::Response) -> SearchError {
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    SearchError::ApiError(format!("Qdrant {}: {}", status, body))
+}
+
+/// 片段 id（16 位十六進位）轉為 Qdrant 點 id
+fn point_id(id: &str) -> Result<u64, SearchError> {
+    u64::from_str_radix(id, 16).map_err(|_| SearchError::ParseError(format!("無效的片段 id: {}", id)))
+}
+
+impl VectorStore for QdrantStore {
+    fn upsert<'a>(&'a self, records: Vec<VectorRecord>) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            if records.is_empty() {
+                return Ok(());
+            }
+            let points = records
+                .into_iter()
+                .map(|record| {
+                    let payload =
+                        serde_json::to_value(&record.chunk).map_err(|e| SearchError::ParseError(e.to_string()))?;
+                    Ok(json!({
+                        "id": point_id(&record.chunk.id)?,
+                        "vector": record.vector,
+                        "payload": payload,
+                    }))
+                })
+                .collect::<Result<Vec<_>, SearchError>>()?;
+            self.send(
+                self.client
+                    .put(self.collection_url("/points?wait=true"))
+                    .json(&json!({ "points": points })),
+            )
+            .await
+            .map(|_| ())
+        })
+    }
+
+    fn search<'a>(&'a self, vector: &'a [f32], top_k: usize) -> StoreFuture<'a, Vec<ScoredChunk>> {
+        Box::pin(async move {
+            let body = self
+                .send(self.client.post(self.collection_url("/points/search")).json(&json!({
+                    "vector": vector,
+                    "limit": top_k,
+                    "with_payload": true,
+                })))
+                .await?;
+            let hits = body["result"]
+                .as_array()
+                .ok_or_else(|| SearchError::ParseError("Qdrant 回應缺少 result".into()))?;
+            Ok(hits
+                .iter()
+                .filter_map(|hit| {
+                    let chunk: Chunk = serde_json::from_value(hit["payload"].clone()).ok()?;
+                    Some(ScoredChunk {
+                        chunk,
+                        score: hit["score"].as_f64().unwrap_or(0.0) as f32,
+                    })
+                })
+                .collect())
+        })
+    }
+
+    fn delete<'a>(&'a self, ids: &'a [String]) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            let points = ids.iter().map(|id| point_id(id)).collect::<Result<Vec<_>, _>>()?;
+            self.send(
+                self.client
+                    .post(self.collection_url("/points/delete?wait=true"))
+                    .json(&json!({ "points": points })),
+            )
+            .await
+            .map(|_| ())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn chunk() -> Chunk {
+        Chunk {
+            id: "00000000000000ff".into(),
+            source_url: "https://example.com".into(),
+            index: 0,
+            start: 0,
+            end: 5,
+            text: "hello".into(),
+            tokens: 2,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_qdrant_round_trip() {
+        let server = MockServer::start().await;
+        let json = |status: u16, body: serde_json::Value| ResponseTemplate::new(status).set_body_json(body);
+        Mock::given(method("GET"))
+            .and(path("/collections/research"))
+            .respond_with(json(404, serde_json::json!({ "status": { "error": "Not found" } })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/collections/research"))
+            .respond_with(json(200, serde_json::json!({ "result": true })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/collections/research/points"))
+            .and(query_param("wait", "true"))
+            .respond_with(json(200, serde_json::json!({ "result": { "status": "completed" } })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/collections/research/points/search"))
+            .respond_with(json(
+                200,
+                serde_json::json!({ "result": [{ "id": 255, "score": 0.93, "payload": chunk() }] }),
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let store = QdrantStore::new(&server.uri(), "research");
+        store.ensure_collection(2).await.unwrap();
+        store
+            .upsert(vec![VectorRecord {
+                vector: vec![1.0, 0.0],
+                chunk: chunk(),
+            }])
+            .await
+            .unwrap();
+        let hits = store.search(&[1.0, 0.0], 3).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].chunk, chunk());
+        assert!((hits[0].score - 0.93).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_point_id() {
+        assert_eq!(point_id("00000000000000ff").unwrap(), 255);
+        assert!(point_id("not-hex").is_err());
+    }
+}